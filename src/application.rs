@@ -2,21 +2,30 @@ use egui_wgpu::wgpu;
 use std::sync::Arc;
 use winit::{
     application::ApplicationHandler,
+    dpi::LogicalSize,
     event::{DeviceEvent, DeviceId, ElementState, KeyEvent, WindowEvent},
     event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
     keyboard::{KeyCode, PhysicalKey},
     window::{Window, WindowId},
 };
 
+pub mod handle;
+
+use handle::AppHandle;
+
 use crate::error::{
     ApplicationError, RenderError, RenderStateUnconfiguredError, SurfaceMissizedError,
 };
-use crate::render::{RenderState, RenderStateOptions};
+use crate::render::{RenderState, RenderStateOptions, SurfaceProfile};
+
+// Small window keeps the per-frame cost low in safe mode, as every pixel is raymarched
+const SAFE_MODE_WINDOW_SIZE: LogicalSize<u32> = LogicalSize::new(640, 360);
 
 pub struct Application {
     active: bool,
     state: Option<RenderState>,
     state_options: RenderStateOptions,
+    handle: AppHandle,
     // Needs to be Option<_> due to requiring .take() later, since ApplicationError is not Clone nor Copy
     exit_error: Option<ApplicationError>,
 }
@@ -28,10 +37,17 @@ impl Application {
             active: true,
             state: None,
             state_options,
+            handle: AppHandle::default(),
             exit_error: None,
         }
     }
 
+    /// Handle for reading and changing the parameters from other threads, also before `run` is called
+    #[must_use]
+    pub fn handle(&self) -> AppHandle {
+        self.handle.clone()
+    }
+
     /// ## Errors
     /// - `ApplicationError::EventLoop(EventLoopError)` when event loop creation failed or event loop terminated with an error
     pub fn run(&mut self) -> Result<(), ApplicationError> {
@@ -95,12 +111,23 @@ impl ApplicationHandler for Application {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         // Run once when the window is still not created and start the window event loop
         if !self.is_configured() && self.active {
-            match event_loop.create_window(Window::default_attributes()) {
+            let mut window_attributes = Window::default_attributes();
+            if self.state_options.is_safe_mode {
+                window_attributes = window_attributes.with_inner_size(SAFE_MODE_WINDOW_SIZE);
+            }
+            if self.state_options.surface_profile == SurfaceProfile::Transparent {
+                window_attributes = window_attributes.with_transparent(true);
+            }
+
+            match event_loop.create_window(window_attributes) {
                 Ok(window) => {
                     let window = Arc::new(window);
 
-                    match pollster::block_on(RenderState::new(window.clone(), &self.state_options))
-                    {
+                    match pollster::block_on(RenderState::new(
+                        window.clone(),
+                        &self.state_options,
+                        self.handle.clone(),
+                    )) {
                         Ok(state) => {
                             self.state = Some(state);
                             window.request_redraw();
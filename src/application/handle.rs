@@ -0,0 +1,151 @@
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, mpsc};
+
+pub use crate::data::options::{
+    AovLayer, ColoringMode, DebugView, FloorPattern, ParameterAxis, Projection, RegionOfInterest,
+    RenderMode, StereoMode,
+};
+pub use crate::data::scene::{FractalGroup, PrimitiveShape};
+pub use crate::data::{CameraData, GuiData, Light, MAX_LIGHTS};
+pub use crate::error::CaptureError;
+pub use crate::render::capture::{FloatImage, FrameCapture};
+pub use crate::util::math::{Radians, Vector2, Vector3, Vector4};
+
+#[derive(Debug, Default)]
+#[allow(clippy::struct_field_names)]
+struct SharedState {
+    // State of the last rendered frame
+    gui_data: GuiData,
+    camera_data: CameraData,
+    // Written by other threads and taken over by the render loop on the next frame
+    pending_gui_data: Option<GuiData>,
+    pending_camera_data: Option<CameraData>,
+    // Answered by the render loop once the next frame is rendered
+    capture_requests: Vec<mpsc::Sender<Result<FrameCapture, CaptureError>>>,
+}
+
+/// Thread-safe access to the parameters of a running `Application`
+///
+/// Reads return the state of the last rendered frame, unless a write is still waiting to be picked
+/// up, in which case the written value is returned. Writes are applied at the start of the next frame.
+#[derive(Clone, Debug, Default)]
+pub struct AppHandle {
+    shared: Arc<Mutex<SharedState>>,
+}
+
+impl AppHandle {
+    // Panicking while holding the lock cannot leave the state half written, as it is only ever
+    // replaced or pushed to as a whole
+    fn lock(&self) -> MutexGuard<'_, SharedState> {
+        self.shared.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Settings as seen in the settings menu, including the fractal group and its parameters
+    #[must_use]
+    pub fn gui_data(&self) -> GuiData {
+        let shared = self.lock();
+        shared.pending_gui_data.unwrap_or(shared.gui_data)
+    }
+
+    pub fn set_gui_data(&self, gui_data: GuiData) {
+        self.lock().pending_gui_data = Some(gui_data);
+    }
+
+    /// Changes the settings in place, without racing against other threads doing the same
+    pub fn update_gui_data(&self, update: impl FnOnce(&mut GuiData)) {
+        let mut shared = self.lock();
+        let mut gui_data = shared.pending_gui_data.unwrap_or(shared.gui_data);
+        update(&mut gui_data);
+        shared.pending_gui_data = Some(gui_data);
+    }
+
+    #[must_use]
+    pub fn camera_data(&self) -> CameraData {
+        let shared = self.lock();
+        shared.pending_camera_data.unwrap_or(shared.camera_data)
+    }
+
+    pub fn set_camera_data(&self, camera_data: CameraData) {
+        self.lock().pending_camera_data = Some(camera_data);
+    }
+
+    /// Switches the scene to another fractal group, keeping all other settings
+    pub fn set_fractal_group(&self, fractal_group: FractalGroup) {
+        self.update_gui_data(|gui_data| gui_data.fractal_group = fractal_group);
+    }
+
+    /// Switches the scene to another primitive shape of the kaleidoscopic IFS group
+    pub fn set_primitive_shape(&self, primitive_shape: PrimitiveShape) {
+        self.update_gui_data(|gui_data| gui_data.primitive_shape = primitive_shape);
+    }
+
+    /// Requests the next rendered frame as raw linear floats, sent once the frame is read back
+    #[must_use]
+    pub fn request_capture(&self) -> mpsc::Receiver<Result<FrameCapture, CaptureError>> {
+        let (sender, receiver) = mpsc::channel();
+        self.lock().capture_requests.push(sender);
+
+        receiver
+    }
+
+    /// Renders the current state and blocks until the frame is read back, must not be called from
+    /// the thread running the `Application`, which would wait on itself
+    ///
+    /// ## Errors
+    /// - `CaptureError::Poll(PollError)` when waiting for the copy of the frame failed
+    /// - `CaptureError::BufferAsync(BufferAsyncError)` when the copy could not be mapped
+    /// - `CaptureError::Closed(RecvError)` when the application exited before rendering the frame
+    pub fn capture_frame(&self) -> Result<FrameCapture, CaptureError> {
+        self.request_capture().recv()?
+    }
+
+    // Needs to be called by the render loop once the frame is rendered
+    #[must_use]
+    pub(crate) fn take_capture_requests(
+        &self,
+    ) -> Vec<mpsc::Sender<Result<FrameCapture, CaptureError>>> {
+        std::mem::take(&mut self.lock().capture_requests)
+    }
+
+    // Needs to be called by the render loop before updating the GUI
+    #[must_use]
+    pub(crate) fn take_pending(&self) -> (Option<GuiData>, Option<CameraData>) {
+        let mut shared = self.lock();
+        (
+            shared.pending_gui_data.take(),
+            shared.pending_camera_data.take(),
+        )
+    }
+
+    // Needs to be called by the render loop once the frame is rendered
+    pub(crate) fn publish(&self, gui_data: &GuiData, camera_data: CameraData) {
+        let mut shared = self.lock();
+        shared.gui_data = *gui_data;
+        shared.camera_data = camera_data;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_writes() {
+        let handle = AppHandle::default();
+        handle.set_fractal_group(FractalGroup::JuliaSet);
+        handle.update_gui_data(|gui_data| gui_data.power = 3.);
+
+        // Pending writes are visible to readers before the render loop picks them up
+        assert_eq!(handle.gui_data().fractal_group, FractalGroup::JuliaSet);
+
+        let (gui_data, camera_data) = handle.take_pending();
+        let gui_data = gui_data.unwrap();
+        assert_eq!(gui_data.fractal_group, FractalGroup::JuliaSet);
+        assert!((gui_data.power - 3.).abs() < f32::EPSILON);
+        assert!(camera_data.is_none());
+
+        // Render loop has not published the new state yet
+        assert_eq!(handle.gui_data().fractal_group, FractalGroup::default());
+        handle.publish(&gui_data, CameraData::default());
+        assert_eq!(handle.gui_data().fractal_group, FractalGroup::JuliaSet);
+    }
+}
@@ -0,0 +1,178 @@
+use limited_queue::LimitedQueue;
+use std::{
+    backtrace::Backtrace,
+    fmt::Write as _,
+    fs,
+    panic::{self, PanicHookInfo},
+    sync::{Mutex, OnceLock},
+};
+
+use crate::data::{CameraData, GuiData};
+
+// File the crash report is written to, relative to the working directory
+pub(crate) const CRASH_REPORT_PATH: &str = "crash_report.txt";
+// Left next to the report until the crash is shown on the next start
+const CRASH_MARKER_PATH: &str = "crash_report.pending";
+const REPORT_HEADER: &str = "kifs-raymarching crash report";
+const RECENT_LOG_LINES: usize = 200;
+
+// Settings of the last rendered frame, which is the work lost when the application crashes
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct CrashState {
+    pub(crate) gui_data: GuiData,
+    pub(crate) camera_data: CameraData,
+}
+
+static CRASH_STATE: Mutex<Option<CrashState>> = Mutex::new(None);
+static LOGGER: OnceLock<RecordingLogger> = OnceLock::new();
+
+// Forwards to env_logger, while remembering the most recent lines for the crash report
+struct RecordingLogger {
+    inner: env_logger::Logger,
+    recent_lines: Mutex<LimitedQueue<String>>,
+}
+
+impl log::Log for RecordingLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.inner.enabled(record.metadata()) {
+            return;
+        }
+
+        self.inner.log(record);
+        if let Ok(mut recent_lines) = self.recent_lines.lock() {
+            recent_lines.push(format!(
+                "[{} {}] {}",
+                record.level(),
+                record.target(),
+                record.args()
+            ));
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+// Needs to be called every frame, so the report holds the settings the user last saw
+pub(crate) fn record_state(state: &CrashState) {
+    if let Ok(mut crash_state) = CRASH_STATE.lock() {
+        *crash_state = Some(*state);
+    }
+}
+
+#[must_use]
+fn format_report(panic: &str, state: Option<&CrashState>, log_lines: &[String]) -> String {
+    let mut report = String::new();
+
+    // Writing into a String never fails
+    let _ = writeln!(report, "{REPORT_HEADER}\n");
+    let _ = writeln!(report, "{panic}\n");
+    let _ = writeln!(report, "Backtrace:\n{}\n", Backtrace::force_capture());
+    match state {
+        Some(state) => {
+            let _ = writeln!(report, "Settings:\n{:#?}\n", state.gui_data);
+            let _ = writeln!(report, "Camera:\n{:#?}\n", state.camera_data);
+        }
+        None => {
+            let _ = writeln!(report, "Crashed before the first frame was rendered\n");
+        }
+    }
+    let _ = writeln!(report, "Recent log lines:");
+    for line in log_lines {
+        let _ = writeln!(report, "{line}");
+    }
+
+    report
+}
+
+// Panic message at the start of a report written by format_report
+#[must_use]
+fn report_panic(report: &str) -> Option<&str> {
+    report
+        .strip_prefix(REPORT_HEADER)?
+        .trim_start()
+        .split_once("\n\nBacktrace:")
+        .map(|(panic, _)| panic)
+}
+
+/// Panic message of a crash the user has not been shown yet, only returned on the first start
+/// after the crash, empty when the report could not be read
+#[must_use]
+pub(crate) fn take_unseen_crash() -> Option<String> {
+    fs::remove_file(CRASH_MARKER_PATH).ok()?;
+    let report = fs::read_to_string(CRASH_REPORT_PATH).unwrap_or_default();
+
+    Some(report_panic(&report).unwrap_or_default().to_string())
+}
+
+fn write_report(info: &PanicHookInfo) {
+    // Lock may still be held by the panicking thread, in which case waiting for it would deadlock
+    let state = CRASH_STATE.try_lock().ok().and_then(|state| *state);
+    let log_lines = LOGGER
+        .get()
+        .and_then(|logger| logger.recent_lines.try_lock().ok())
+        .map(|recent_lines| recent_lines.iter().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let report = format_report(&info.to_string(), state.as_ref(), &log_lines);
+    if let Err(error) = fs::write(CRASH_REPORT_PATH, report) {
+        eprintln!("\nkifs-raymarching crashed and failed to save a crash report: {error}");
+        return;
+    }
+    // Console is often not visible, so the crash is also shown in the window on the next start
+    if let Err(error) = fs::write(CRASH_MARKER_PATH, "") {
+        eprintln!("Failed to mark the crash report to be shown on the next start: {error}");
+    }
+
+    eprintln!(
+        "\nkifs-raymarching crashed unexpectedly.\n\
+        The settings you were working with and the recent log were saved to {CRASH_REPORT_PATH}, \
+        please attach it when reporting the bug."
+    );
+}
+
+/// Initializes logging and installs a panic hook writing a crash report, needs to be called once at startup
+pub fn install() {
+    let logger = LOGGER.get_or_init(|| RecordingLogger {
+        inner: env_logger::Builder::from_default_env().build(),
+        recent_lines: Mutex::new(LimitedQueue::with_capacity(RECENT_LOG_LINES)),
+    });
+    log::set_max_level(logger.inner.filter());
+    if let Err(error) = log::set_logger(logger) {
+        eprintln!("Failed to initialize logging: {error}");
+    }
+
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        write_report(info);
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_report() {
+        let state = CrashState {
+            gui_data: GuiData::default(),
+            camera_data: CameraData::default(),
+        };
+        let report = format_report(
+            "panicked at src/render.rs:1:1",
+            Some(&state),
+            &["[WARN kifs] last line".to_string()],
+        );
+
+        assert!(report.contains("panicked at src/render.rs:1:1"));
+        assert!(report.contains("max_iterations: 256"));
+        assert!(report.ends_with("[WARN kifs] last line\n"));
+        assert_eq!(report_panic(&report), Some("panicked at src/render.rs:1:1"));
+    }
+}
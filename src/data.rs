@@ -1,3 +1,9 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    f32::consts::{PI, TAU},
+    time::Duration,
+};
+use strum::EnumCount as _;
 use winit::dpi::PhysicalSize;
 
 use crate::{
@@ -8,11 +14,47 @@ use crate::{
     },
 };
 
+pub(crate) mod assets;
+pub(crate) mod bake;
+pub(crate) mod bindings;
+pub(crate) mod camera_path;
+pub(crate) mod diagnostics;
+pub(crate) mod expression;
+pub(crate) mod formula;
+pub(crate) mod library;
+pub(crate) mod mesh;
+pub(crate) mod node_graph;
+pub(crate) mod options;
 pub(crate) mod packed;
+pub(crate) mod preset;
+pub(crate) mod project;
+pub(crate) mod report;
 pub(crate) mod scene;
+pub(crate) mod scene_graph;
+pub(crate) mod tiles;
+pub(crate) mod workspace;
+
+use diagnostics::MarchStatistics;
+use mesh::MeshSettings;
+use options::{
+    AovLayer, CameraMode, ColoringMode, CsgOperation, CsgShape, CutawaySide, DebugView,
+    DistanceClamping, FloorPattern, MirrorPlanes, ObjectShading, ParameterAxis, Projection,
+    RegionOfInterest, RenderMode, StereoMode, TransferFunction, UpscaleFilter, WarpAxis,
+};
+use packed::{
+    IntoPacked, IntoUnpacked, Matrix3x3F32Packed, Vector2Packed, Vector3Packed, Vector4Packed,
+};
+use scene::{
+    BoundingVolume, ConstantAnimation, FractalGroup, HybridFormula, JuliaEstimator, JuliaFormula,
+    PowerSweep, PrimitiveShape, SceneObjectKind,
+};
 
-use packed::{IntoPacked, Vector3Packed, Vector4Packed};
-use scene::{FractalGroup, PrimitiveShape};
+// Slowest animation of the Julia constant in radians per second, which keeps its period finite
+const MIN_ANIMATION_SPEED: f32 = 0.01;
+// Shortest period of the power sweep in seconds, anything faster only flickers
+const MIN_POWER_SWEEP_PERIOD: f32 = 0.1;
+// Longest interleave pattern of the hybrid group, one bit of the pattern per iteration
+pub(crate) const MAX_HYBRID_PATTERN_LENGTH: u32 = 8;
 
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
@@ -26,7 +68,7 @@ pub(crate) struct ScreenUniformData {
 #[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
 pub(crate) struct CameraUniformData {
     origin: Vector3Packed<f32>,
-    _padding: u32,
+    view_scale: f32,
     matrix: Vector3Packed<Vector4Packed<f32>>,
 }
 
@@ -40,12 +82,284 @@ pub(crate) struct OptionsUniformData {
     fractal_color: Vector3Packed<f32>,
     _padding2: u32,
     background_color: Vector3Packed<f32>,
-    is_heatmap: u32,
+    coloring_mode: u32,
     fractal_group_id: u32,
     primitive_id: u32,
     power: f32,
-    _padding3: u32,
+    is_analytic_normal: u32,
     constant: Vector4Packed<f32>,
+    debug_view: u32,
+    slice_offset: f32,
+    relaxation: f32,
+    is_smooth_heatmap: u32,
+    heatmap_scale: f32,
+    heatmap_offset: f32,
+    is_translucent: u32,
+    absorption_density: f32,
+    absorption_color: Vector3Packed<f32>,
+    render_mode: u32,
+    volume_density: f32,
+    volume_step: f32,
+    _padding3: [u32; 2],
+    clip_normal: Vector3Packed<f32>,
+    is_clipping: u32,
+    clip_color: Vector3Packed<f32>,
+    clip_offset: f32,
+    floor_color: Vector3Packed<f32>,
+    is_floor: u32,
+    floor_height: f32,
+    floor_checker_scale: f32,
+    floor_pattern: u32,
+    is_half_precision: u32,
+    stereo_mode: u32,
+    eye_separation: f32,
+    projection: u32,
+    menger_iterations: u32,
+    menger_scale: f32,
+    sierpinski_scale: f32,
+    _padding4: [u32; 2],
+    sierpinski_offset: Vector3Packed<f32>,
+    _padding5: u32,
+    sierpinski_rotation: Matrix3x3F32Packed,
+    kleinian_box_size: Vector3Packed<f32>,
+    kleinian_inversion_radius: f32,
+    kleinian_iterations: u32,
+    julia_formula: u32,
+    phoenix_strength: f32,
+    _padding6: u32,
+    cubic_constant: Vector4Packed<f32>,
+    hyperplane_rotation: [Vector4Packed<f32>; 4],
+    hyperplane_offset: f32,
+    animation_time: f32,
+    constant_animation: u32,
+    animation_speed: f32,
+    animation_radius: f32,
+    hybrid_formula_a: u32,
+    hybrid_formula_b: u32,
+    hybrid_pattern: u32,
+    hybrid_pattern_length: u32,
+    hybrid_iterations: u32,
+    hybrid_box_scale: f32,
+    julia_abs_folds: u32,
+    fractal_shading: u32,
+    floor_shading: u32,
+    sphere_radius: f32,
+    cylinder_radius: f32,
+    box_half_extents: Vector3Packed<f32>,
+    cylinder_half_height: f32,
+    torus_outer_radius: f32,
+    torus_inner_radius: f32,
+    csg_operation: u32,
+    csg_shape: u32,
+    csg_center: Vector3Packed<f32>,
+    csg_size: f32,
+    csg_smoothness: f32,
+    twist_strength: f32,
+    twist_axis: u32,
+    bend_strength: f32,
+    mirror_planes: u32,
+    distance_fudge: f32,
+    bailout_radius: f32,
+    is_interior_visible: u32,
+    julia_estimator: u32,
+    _padding7: [u32; 3],
+    interior_color: Vector3Packed<f32>,
+    surf_scale: f32,
+    surf_fold: Vector2Packed<f32>,
+    surf_min_radius: f32,
+    surf_iterations: u32,
+    custom_iterations: u32,
+    _padding8: [u32; 3],
+    cutaway_signs: Vector3Packed<f32>,
+    is_floor_cut: u32,
+    bounding_extents: Vector3Packed<f32>,
+    bounding_shape: u32,
+    max_step: f32,
+    _padding9: [u32; 3],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct PostUniformData {
+    chromatic_aberration: f32,
+    film_grain: f32,
+    frame: u32,
+    exposure: f32,
+    is_auto_exposure: u32,
+    is_outline: u32,
+    outline_thickness: f32,
+    is_transparent: u32,
+    outline_color: Vector3Packed<f32>,
+    transfer_function: u32,
+    gamma: f32,
+    is_srgb_target: u32,
+    upscale_filter: u32,
+    sharpness: f32,
+    render_scale: f32,
+    _padding: [u32; 3],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct ExposureUniformData {
+    min_exposure: f32,
+    max_exposure: f32,
+    adaptation_speed: f32,
+    delta_time: f32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct SampleUniformData {
+    jitter: Vector2Packed<f32>,
+    index: u32,
+    roi_mode: u32,
+    roi_center: Vector2Packed<f32>,
+    roi_extent: Vector2Packed<f32>,
+    inside_rate: f32,
+    outside_rate: f32,
+    view_offset: Vector2Packed<f32>,
+    view_size: Vector2Packed<f32>,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct PreviewUniformData {
+    grid_size: u32,
+    horizontal_axis: u32,
+    vertical_axis: u32,
+    range: f32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct DistanceSliceUniformData {
+    axis: u32,
+    offset: f32,
+    extent: f32,
+    _padding: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct DistanceGridUniformData {
+    resolution: u32,
+    extent: f32,
+    first_layer: u32,
+    _padding: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct AovUniformData {
+    layer: u32,
+    max_distance: f32,
+    max_iterations: u32,
+    _padding: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct AxesUniformData {
+    length: f32,
+    tick_spacing: f32,
+    is_ticks: u32,
+    render_scale: f32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct TaaUniformData {
+    blend: f32,
+    is_history_valid: u32,
+    _padding: [u32; 2],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct RayPathHeaderData {
+    pixel: Vector2Packed<u32>,
+    is_recording: u32,
+    length: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct RayStepData {
+    position: Vector3Packed<f32>,
+    distance: f32,
+}
+
+// Declared in the shader constants
+pub const MAX_LIGHTS: usize = 4;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct LightStorageData {
+    direction: Vector3Packed<f32>,
+    intensity: f32,
+    color: Vector3Packed<f32>,
+    is_enabled: u32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct LightingStorageData {
+    ambient: f32,
+    _padding: [u32; 3],
+    lights: [LightStorageData; MAX_LIGHTS],
+}
+
+// Declared in the shader constants
+pub const MAX_SCENE_OBJECTS: usize = 8;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct MaterialStorageData {
+    albedo: Vector3Packed<f32>,
+    specular: f32,
+    emissive: Vector3Packed<f32>,
+    reflectivity: f32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct SceneObjectStorageData {
+    inverse_rotation: Matrix3x3F32Packed,
+    translation: Vector3Packed<f32>,
+    scale: f32,
+    kind: u32,
+    _padding: [u32; 3],
+    material: MaterialStorageData,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct SceneStorageData {
+    object_count: u32,
+    is_plain: u32,
+    _padding: [u32; 2],
+    objects: [SceneObjectStorageData; MAX_SCENE_OBJECTS],
+}
+
+// Counters indexed by the bailout reason of the march
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct MarchStatisticsData {
+    max_iterations: u32,
+    max_distance: u32,
+    hits: u32,
+    _padding: u32,
+}
+
+impl From<MarchStatisticsData> for MarchStatistics {
+    fn from(data: MarchStatisticsData) -> Self {
+        Self {
+            max_iterations: data.max_iterations,
+            max_distance: data.max_distance,
+            hits: data.hits,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -80,11 +394,19 @@ impl BufferDataDescriptor for ScreenData {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-pub(crate) struct CameraData {
-    pub(crate) origin_distance: f32,
-    pub(crate) min_distance: f32,
-    pub(crate) angles: Vector2<Radians>,
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct CameraData {
+    pub mode: CameraMode,
+    pub origin_distance: f32,
+    pub min_distance: f32,
+    // Orientation in both modes, the orbit camera sits on the same side of the pivot it faces away from
+    pub angles: Vector2<Radians>,
+    // Position of the free-fly camera, the orbit camera follows from its distance and angles
+    pub fly_position: Vector3<f32>,
+    // Point the orbit camera looks at, moved off the pivot by panning
+    pub pan_target: Vector3<f32>,
+    // Angle the height of the view spans in degrees
+    pub vertical_fov: f32,
 }
 
 impl CameraData {
@@ -100,14 +422,186 @@ impl CameraData {
     pub(crate) fn transform_vector(&self, vector: Vector3<f32>) -> Vector3<f32> {
         self.camera_matrix() * vector
     }
+
+    // Half the height of the view at unit distance in front of the camera, by which the rays in
+    // entry.wgsl are spread
+    #[must_use]
+    pub(crate) fn view_scale(&self) -> f32 {
+        (self.vertical_fov.to_radians() / 2.).tan()
+    }
+
+    // Constructed via spherical coordinates, the first column of the matrix points from the target
+    // towards the orbit camera and behind the free-fly camera
+    #[must_use]
+    pub(crate) fn position(&self) -> Vector3<f32> {
+        match self.mode {
+            CameraMode::Orbit => {
+                self.pan_target + self.camera_matrix().columns().0 * self.origin_distance
+            }
+            CameraMode::FreeFly => self.fly_position,
+        }
+    }
+
+    // Switches the mode without jumping, the free-fly camera starts where the orbit camera was,
+    // while the orbit camera turns to its target from the side the free-fly camera was on
+    #[must_use]
+    pub(crate) fn with_mode(self, mode: CameraMode) -> Self {
+        match (self.mode, mode) {
+            (CameraMode::Orbit, CameraMode::FreeFly) => Self {
+                mode,
+                fly_position: self.position(),
+                ..self
+            },
+            (CameraMode::FreeFly, CameraMode::Orbit) => {
+                let direction = self.pan_target - self.fly_position;
+                Self {
+                    mode,
+                    origin_distance: direction.length().max(self.min_distance),
+                    ..self.facing(direction)
+                }
+            }
+            _ => self,
+        }
+    }
+
+    // Turns the camera to look along the direction
+    #[must_use]
+    fn facing(self, direction: Vector3<f32>) -> Self {
+        if direction.length() <= f32::EPSILON {
+            return self;
+        }
+
+        let Vector3(x, y, z) = -direction.normalize();
+        Self {
+            angles: Vector2(
+                Radians::from_radians(y.atan2(x)),
+                Radians::from_radians(z.clamp(-1., 1.).asin()),
+            ),
+            ..self
+        }
+    }
+
+    // Turns the camera by the angles, keeping it from looking past straight up or down
+    #[must_use]
+    pub(crate) fn rotated(self, delta_angles: Vector2<Radians>) -> Self {
+        let Vector2(phi, theta) = self.angles + delta_angles;
+        Self {
+            angles: Vector2(phi.standardize(), theta.clamp(-PI / 2., PI / 2.)),
+            ..self
+        }
+    }
+
+    // Moves the free-fly camera by the offset along its right, up and forward directions
+    #[must_use]
+    pub(crate) fn fly(self, Vector3(right, up, forward): Vector3<f32>) -> Self {
+        let (backward_direction, right_direction, up_direction) = self.camera_matrix().columns();
+        Self {
+            fly_position: self.fly_position + right_direction * right + up_direction * up
+                - backward_direction * forward,
+            ..self
+        }
+    }
+
+    // Moves the target of the orbit camera by the offset along its right and up directions, which
+    // slides the whole view sideways without turning it
+    #[must_use]
+    pub(crate) fn pan(self, Vector2(right, up): Vector2<f32>) -> Self {
+        let (_, right_direction, up_direction) = self.camera_matrix().columns();
+        Self {
+            pan_target: self.pan_target + right_direction * right + up_direction * up,
+            ..self
+        }
+    }
+
+    // Distance left until the camera reaches its minimum distance, shrinking tenfold with every
+    // order of magnitude zoomed in
+    #[must_use]
+    pub(crate) fn zoom_scale(&self) -> f32 {
+        (self.origin_distance - self.min_distance).max(MIN_ZOOM_SCALE)
+    }
+
+    // Moves the orbit camera by the lines scrolled, positive towards the target, shrinking the
+    // distance left until its minimum distance by the same factor every line, so deep zooms keep
+    // their pace and zooming from far away does not overshoot
+    #[must_use]
+    pub(crate) fn zoomed(self, lines: f32, speed: f32) -> Self {
+        let distance_left = (self.origin_distance - self.min_distance).max(0.);
+        Self {
+            origin_distance: self.min_distance
+                + distance_left * ZOOM_FACTOR_PER_LINE.powf(lines * speed),
+            ..self
+        }
+    }
+
+    // Point the camera orbits around and looks at until it is panned
+    pub(crate) const PIVOT: Vector3<f32> = Vector3(0., 0., 0.);
+
+    // Inverse of the perspective rays in entry.wgsl, giving the position of the point on the screen
+    // with both coordinates on [-1, 1] and y pointing up, or None when it lies behind the camera
+    #[must_use]
+    pub(crate) fn project(&self, point: Vector3<f32>, aspect_ratio: f32) -> Option<Vector2<f32>> {
+        // First column points from the target towards the camera, the other two right and up
+        let (backward, right, up) = self.camera_matrix().columns();
+        let Vector3(backward_distance, x, y) =
+            Matrix3x3::from_rows(backward, right, up) * (point - self.position());
+
+        let depth = -backward_distance * self.view_scale();
+        (depth > f32::EPSILON).then(|| Vector2(x / (depth * aspect_ratio), y / depth))
+    }
+
+    // Orbits the camera to the side of the target the point lies on, which puts the point in the
+    // center of the view, moving back as far as needed to keep the point in front of the camera
+    // The free-fly camera only turns towards the point
+    #[must_use]
+    pub(crate) fn centered_on(self, point: Vector3<f32>) -> Self {
+        if self.mode == CameraMode::FreeFly {
+            return self.facing(point - self.fly_position);
+        }
+
+        let offset = point - self.pan_target;
+        let length = offset.length();
+        if length <= f32::EPSILON {
+            return self;
+        }
+
+        Self {
+            origin_distance: self.origin_distance.max(MARKER_DISTANCE_FACTOR * length),
+            ..self.facing(-offset)
+        }
+    }
+}
+
+// Screen height spans [-1, 1] at unit depth by default, narrower views zoom in without moving
+pub(crate) const DEFAULT_VERTICAL_FOV: f32 = 90.;
+pub(crate) const MIN_VERTICAL_FOV: f32 = 10.;
+pub(crate) const MAX_VERTICAL_FOV: f32 = 150.;
+
+// Least distance of the camera from the pivot, relative to that of a point it is centered on
+const MARKER_DISTANCE_FACTOR: f32 = 1.5;
+
+// Named point of interest, dropped where the ray inspector's ray ended
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Marker {
+    pub(crate) name: String,
+    pub(crate) position: Vector3<f32>,
 }
 
+// Zoom scale once the camera sits at its minimum distance, around the finest step an f32 distance
+// of a few units still resolves
+const MIN_ZOOM_SCALE: f32 = 1e-6;
+// Share of the distance left which remains after zooming in by a line at unit speed
+const ZOOM_FACTOR_PER_LINE: f32 = 0.8;
+
 impl Default for CameraData {
     fn default() -> Self {
         Self {
+            mode: CameraMode::default(),
             origin_distance: 5.,
             min_distance: 2.,
             angles: Vector2(Radians::from_radians(0.), Radians::from_radians(0.)),
+            fly_position: Vector3(5., 0., 0.),
+            pan_target: Self::PIVOT,
+            vertical_fov: DEFAULT_VERTICAL_FOV,
         }
     }
 }
@@ -116,33 +610,357 @@ impl BufferDataDescriptor for CameraData {
     type BufferData = CameraUniformData;
 
     fn into_buffer_data(self) -> Self::BufferData {
-        let camera_matrix = self.camera_matrix();
-        let origin = self.origin_distance * camera_matrix * Vector3(1., 0., 0.);
-
         Self::BufferData {
-            // Construct origin via spherical coordinates
-            origin: origin.into_packed(),
-            matrix: camera_matrix.into_packed(),
-            ..Default::default()
+            origin: self.position().into_packed(),
+            view_scale: self.view_scale(),
+            matrix: self.camera_matrix().into_packed(),
         }
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-pub(crate) struct GuiData {
-    pub(crate) max_iterations: u32,
-    pub(crate) max_distance: f32,
-    pub(crate) epsilon: f32,
-    pub(crate) fractal_color: [u8; 3],
-    pub(crate) background_color: [u8; 3],
-    pub(crate) is_heatmap: bool,
-    pub(crate) fractal_group: FractalGroup,
-    pub(crate) primitive_shape: PrimitiveShape,
-    pub(crate) power: f32,
-    pub(crate) constant: Vector4<f32>,
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Light {
+    pub is_enabled: bool,
+    // Direction towards the light in degrees, around the vertical axis and above the horizon
+    pub azimuth: f32,
+    pub elevation: f32,
+    pub color: [u8; 3],
+    pub intensity: f32,
+}
+
+impl Light {
+    // Names of the lights in the default rig, in the order of the lights array
+    pub(crate) const NAMES: [&str; MAX_LIGHTS] = ["Key", "Fill", "Rim", "Extra"];
+
+    #[must_use]
+    pub(crate) fn direction(&self) -> Vector3<f32> {
+        let (azimuth, elevation) = (self.azimuth.to_radians(), self.elevation.to_radians());
+        Vector3(
+            elevation.cos() * azimuth.cos(),
+            elevation.cos() * azimuth.sin(),
+            elevation.sin(),
+        )
+    }
+
+    // Key light from above the corner of the first octant, with fill and rim lights left for the user to enable
+    #[must_use]
+    fn default_rig() -> [Self; MAX_LIGHTS] {
+        [
+            Self {
+                is_enabled: true,
+                azimuth: 45.,
+                elevation: 35.26,
+                color: [255; 3],
+                intensity: 0.9,
+            },
+            Self {
+                is_enabled: false,
+                azimuth: -60.,
+                elevation: 10.,
+                color: [190, 210, 255],
+                intensity: 0.3,
+            },
+            Self {
+                is_enabled: false,
+                azimuth: -135.,
+                elevation: 20.,
+                color: [255, 230, 200],
+                intensity: 0.6,
+            },
+            Self {
+                is_enabled: false,
+                azimuth: 135.,
+                elevation: -30.,
+                color: [255; 3],
+                intensity: 0.3,
+            },
+        ]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Material {
+    // Tint multiplied with the fractal color, so palettes still color the whole scene
+    pub albedo: [u8; 3],
+    // Strength of the highlights of the lights
+    pub specular: f32,
+    // Share of the color mirrored from the rest of the scene
+    pub reflectivity: f32,
+    // Light given off regardless of the lighting, scaled by the emission strength
+    pub emissive: [u8; 3],
+    pub emission_strength: f32,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            albedo: [255; 3],
+            specular: 0.,
+            reflectivity: 0.,
+            emissive: [255; 3],
+            emission_strength: 0.,
+        }
+    }
+}
+
+impl From<Material> for MaterialStorageData {
+    fn from(material: Material) -> Self {
+        let emissive = LinearRgb::from(material.emissive);
+
+        Self {
+            albedo: LinearRgb::from(material.albedo).into_packed(),
+            specular: material.specular,
+            emissive: Vector3(
+                emissive[0] * material.emission_strength,
+                emissive[1] * material.emission_strength,
+                emissive[2] * material.emission_strength,
+            )
+            .into_packed(),
+            reflectivity: material.reflectivity.clamp(0., 1.),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SceneObject {
+    pub is_enabled: bool,
+    pub kind: SceneObjectKind,
+    pub translation: Vector3<f32>,
+    // Rotation in degrees about the x, y and z axes, applied in that order
+    pub rotation: Vector3<f32>,
+    // Uniform, so distances scale along with the object
+    pub scale: f32,
+    // Object the transform is relative to, see scene_graph
+    pub parent: Option<usize>,
+    pub material: Material,
+}
+
+impl SceneObject {
+    #[must_use]
+    pub(crate) fn rotation_matrix(&self) -> Matrix3x3<f32> {
+        let Vector3(x, y, z) = self.rotation;
+
+        Matrix3x3::rotation_matrix_z(Radians::from_degrees(z))
+            * Matrix3x3::rotation_matrix_y(Radians::from_degrees(y))
+            * Matrix3x3::rotation_matrix_x(Radians::from_degrees(x))
+    }
+
+    // Only the fractal at the origin, with the primitives left for the user to enable
+    #[must_use]
+    fn default_list() -> [Self; MAX_SCENE_OBJECTS] {
+        let mut objects = [Self {
+            is_enabled: false,
+            kind: SceneObjectKind::Sphere,
+            translation: Vector3(0., 0., 0.),
+            rotation: Vector3(0., 0., 0.),
+            scale: 1.,
+            parent: None,
+            material: Material::default(),
+        }; MAX_SCENE_OBJECTS];
+        objects[0].is_enabled = true;
+        objects[0].kind = SceneObjectKind::Fractal;
+        objects
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct GuiData {
+    pub max_iterations: u32,
+    pub max_distance: f32,
+    pub epsilon: f32,
+    pub fractal_color: [u8; 3],
+    pub background_color: [u8; 3],
+    pub coloring_mode: ColoringMode,
+    pub is_smooth_heatmap: bool,
+    pub heatmap_scale: f32,
+    pub heatmap_offset: f32,
+    pub fractal_group: FractalGroup,
+    pub primitive_shape: PrimitiveShape,
+    // Sizes of the simple preset shapes, the cylinder and box measured from their center
+    pub sphere_radius: f32,
+    pub cylinder_radius: f32,
+    pub cylinder_half_height: f32,
+    pub box_half_extents: Vector3<f32>,
+    // Distance of the tube from the center and radius of the tube itself
+    pub torus_outer_radius: f32,
+    pub torus_inner_radius: f32,
+    pub menger_iterations: u32,
+    // Width of the holes as a fraction of the cube they are cut out of
+    pub menger_hole_size: f32,
+    pub sierpinski_scale: f32,
+    // Vertex the folded copies are scaled towards, the corner of the tetrahedron by default
+    pub sierpinski_offset: Vector3<f32>,
+    // Rotation applied after every fold, in degrees around the x, y and z axes
+    pub sierpinski_rotation: Vector3<f32>,
+    // Half extents of the box space is reflected in
+    pub kleinian_box_size: Vector3<f32>,
+    pub kleinian_inversion_radius: f32,
+    pub kleinian_iterations: u32,
+    // Formulas alternated by the hybrid group, the set bits of the pattern pick the second one
+    pub hybrid_formula_a: HybridFormula,
+    pub hybrid_formula_b: HybridFormula,
+    pub hybrid_pattern: u32,
+    pub hybrid_pattern_length: u32,
+    pub hybrid_iterations: u32,
+    // Scale of the Mandelbox fold, negative scales give the more intricate boxes
+    pub hybrid_box_scale: f32,
+    // Limits of the box fold along the x and y axes of the Amazing Surf, z is never folded
+    pub surf_fold: Vector2<f32>,
+    pub surf_scale: f32,
+    // Radius below which the sphere fold scales points up by a fixed factor instead of inverting them
+    pub surf_min_radius: f32,
+    pub surf_iterations: u32,
+    // Iterations of the formula typed into the formula window
+    pub custom_iterations: u32,
+    pub julia_formula: JuliaFormula,
+    // Components of the quaternion replaced by their absolute value before every iteration, in the
+    // order real, i, j and k, as in the burning ship fractal
+    pub julia_abs_folds: [bool; 4],
+    // Norm past which the Julia iteration counts as escaped
+    pub bailout_radius: f32,
+    pub julia_estimator: JuliaEstimator,
+    pub power: f32,
+    // Range the power is swept through while the sweep plays, in place of the power variable
+    pub power_sweep: PowerSweep,
+    pub power_sweep_min: f32,
+    pub power_sweep_max: f32,
+    // Seconds per sweep, and seconds into the current one
+    pub power_sweep_period: f32,
+    pub power_sweep_time: f32,
+    pub is_power_sweep_playing: bool,
+    pub constant: Vector4<f32>,
+    // Path the constant is moved along while the animation plays, speed in radians per second
+    pub constant_animation: ConstantAnimation,
+    pub animation_speed: f32,
+    pub animation_radius: f32,
+    // Seconds into the animation, advanced while it plays and set by the time scrubber
+    pub animation_time: f32,
+    pub is_animation_playing: bool,
+    // Multiplier of the quaternion two iterations back in the phoenix formula
+    pub phoenix_strength: f32,
+    // Constant multiplying the quaternion in the cubic formula
+    pub cubic_constant: Vector4<f32>,
+    // Fourth coordinate of the rendered 3D slice of quaternion space
+    pub hyperplane_offset: f32,
+    // Rotation of the slice, in degrees turning the x, y and z axes towards the fourth one
+    pub hyperplane_rotation: Vector3<f32>,
+    pub is_analytic_normal: bool,
+    pub relaxation: f32,
+    pub distance_clamping: DistanceClamping,
+    // Share of the distance estimate marched at once with custom clamping
+    pub distance_fudge: f32,
+    // Steps of the march are cut down to the max step
+    pub is_step_clamped: bool,
+    pub max_step: f32,
+    pub debug_view: DebugView,
+    pub slice_offset: f32,
+    pub is_translucent: bool,
+    pub absorption_density: f32,
+    pub absorption_color: [u8; 3],
+    // Camera inside the set sees the boundary from within instead of nothing
+    pub is_interior_visible: bool,
+    pub interior_color: [u8; 3],
+    // Indexed by the fractal group id
+    pub render_modes: [RenderMode; FractalGroup::COUNT],
+    pub volume_density: f32,
+    pub volume_step: f32,
+    pub is_clipping: bool,
+    pub clip_normal: Vector3<f32>,
+    pub clip_offset: f32,
+    pub clip_color: [u8; 3],
+    // Side of the x, y and z axes the cut away octant lies on, nothing is cut when all are any
+    pub cutaway: [CutawaySide; 3],
+    pub is_floor: bool,
+    pub floor_pattern: FloorPattern,
+    pub floor_height: f32,
+    pub floor_checker_scale: f32,
+    pub floor_color: [u8; 3],
+    // Removes everything below the floor, so the scene rests on it instead of passing through
+    pub is_floor_cut: bool,
+    // Shadows and occlusion are evaluated per object, the fractal itself skips them by default
+    pub fractal_shading: ObjectShading,
+    pub floor_shading: ObjectShading,
+    // Shape the fractal is combined with, sized by its radius or half side length
+    pub csg_operation: CsgOperation,
+    pub csg_shape: CsgShape,
+    pub csg_center: Vector3<f32>,
+    pub csg_size: f32,
+    // Smooth minimum blending factor k, zero keeps the sharp crease between the shapes
+    pub csg_smoothness: f32,
+    // Domain warps in radians per unit, zero leaves the fractal undeformed
+    pub twist_strength: f32,
+    pub twist_axis: WarpAxis,
+    pub bend_strength: f32,
+    pub mirror_planes: MirrorPlanes,
+    // Entities of the scene, each placed by its own transform and combined by their union
+    pub scene_objects: [SceneObject; MAX_SCENE_OBJECTS],
+    pub ambient_light: f32,
+    pub lights: [Light; MAX_LIGHTS],
+    pub projection: Projection,
+    pub stereo_mode: StereoMode,
+    pub eye_separation: f32,
+    pub is_parameter_preview: bool,
+    pub preview_horizontal_axis: ParameterAxis,
+    pub preview_vertical_axis: ParameterAxis,
+    pub preview_range: f32,
+    pub preview_grid_size: u32,
+    pub is_aov_preview: bool,
+    // Distance field on a plane across one of the axes, shown in a window of its own
+    pub is_distance_slice: bool,
+    pub distance_slice_axis: WarpAxis,
+    pub distance_slice_offset: f32,
+    pub distance_slice_extent: f32,
+    pub is_legend: bool,
+    pub is_half_precision: bool,
+    pub is_axes: bool,
+    pub axes_length: f32,
+    pub is_axes_ticks: bool,
+    pub axes_tick_spacing: f32,
+    // Crosshair in the center of the view and a marker on the point the camera orbits around
+    pub is_pivot_marker: bool,
+    pub aov_layer: AovLayer,
+    pub chromatic_aberration: f32,
+    pub film_grain: f32,
+    pub exposure: f32,
+    pub is_auto_exposure: bool,
+    pub min_exposure: f32,
+    pub max_exposure: f32,
+    pub adaptation_speed: f32,
+    pub is_outline: bool,
+    pub outline_thickness: f32,
+    pub outline_color: [u8; 3],
+    pub transfer_function: TransferFunction,
+    pub gamma: f32,
+    pub upscale_filter: UpscaleFilter,
+    pub sharpness: f32,
+    pub is_frame_budget: bool,
+    // Target frame time in milliseconds
+    pub target_frame_time: f32,
+    // Frames the CPU may encode ahead of the GPU, more keep the GPU busy at the cost of input lag
+    pub max_frame_latency: u32,
+    pub is_low_latency: bool,
+    // Frames rendered while a GUI value is dragged use fewer iterations and this fraction of the resolution
+    pub is_drag_preview: bool,
+    pub drag_preview_scale: f32,
+    // Mouse input moves the camera over a few frames, easing out with this time constant in seconds
+    pub is_camera_smoothing: bool,
+    pub camera_smoothing: f32,
+    // Multiplier of the lines scrolled when zooming
+    pub zoom_speed: f32,
+    // Keeps the camera out of the surface by the distance estimate at its position
+    pub is_camera_collision: bool,
+    pub is_progressive: bool,
+    pub is_taa: bool,
+    pub taa_blend: f32,
+    pub max_samples: u32,
+    pub region_of_interest: RegionOfInterest,
+    pub roi_radius: f32,
+    pub roi_outside_rate: f32,
 }
 
 impl Default for GuiData {
+    #[allow(clippy::too_many_lines)]
     fn default() -> Self {
         Self {
             max_iterations: 256,
@@ -150,27 +968,448 @@ impl Default for GuiData {
             epsilon: 0.0001,
             fractal_color: [200; 3],
             background_color: [0; 3],
-            is_heatmap: false,
+            coloring_mode: ColoringMode::default(),
+            is_smooth_heatmap: true,
+            heatmap_scale: 1.,
+            heatmap_offset: 0.,
             fractal_group: FractalGroup::default(),
             primitive_shape: PrimitiveShape::default(),
+            sphere_radius: 1.,
+            cylinder_radius: 1.,
+            cylinder_half_height: 2.,
+            box_half_extents: Vector3(1., 1., 1.),
+            torus_outer_radius: 1.,
+            torus_inner_radius: 0.3,
+            menger_iterations: 5,
+            menger_hole_size: 1. / 3.,
+            sierpinski_scale: 2.,
+            sierpinski_offset: Vector3(1., 1., 1.),
+            sierpinski_rotation: Vector3(0., 0., 0.),
+            kleinian_box_size: Vector3(0.924, 0.908, 0.924),
+            kleinian_inversion_radius: 1.,
+            kleinian_iterations: 12,
+            hybrid_formula_a: HybridFormula::MandelboxFold,
+            hybrid_formula_b: HybridFormula::JuliaStep,
+            hybrid_pattern: 0b10,
+            hybrid_pattern_length: 2,
+            hybrid_iterations: 12,
+            hybrid_box_scale: -1.5,
+            surf_fold: Vector2(1., 1.),
+            surf_scale: 1.8,
+            surf_min_radius: 0.5,
+            surf_iterations: 12,
+            custom_iterations: 12,
+            julia_formula: JuliaFormula::default(),
+            julia_abs_folds: [false; 4],
+            bailout_radius: 32.,
+            julia_estimator: JuliaEstimator::default(),
             power: 2.,
+            power_sweep: PowerSweep::default(),
+            power_sweep_min: 2.,
+            power_sweep_max: 8.,
+            power_sweep_period: 10.,
+            power_sweep_time: 0.,
+            is_power_sweep_playing: false,
             constant: Vector4(-0.1, 0.6, 0.9, -0.3),
+            constant_animation: ConstantAnimation::default(),
+            animation_speed: 0.5,
+            animation_radius: 0.05,
+            animation_time: 0.,
+            is_animation_playing: false,
+            phoenix_strength: -0.5,
+            cubic_constant: Vector4(0.2, 0., 0., 0.),
+            hyperplane_offset: 0.1,
+            hyperplane_rotation: Vector3(0., 0., 0.),
+            is_analytic_normal: true,
+            relaxation: 1.,
+            distance_clamping: DistanceClamping::default(),
+            distance_fudge: 0.7,
+            is_step_clamped: false,
+            max_step: 0.05,
+            debug_view: DebugView::default(),
+            slice_offset: 0.,
+            is_translucent: false,
+            absorption_density: 2.,
+            absorption_color: [120, 180, 255],
+            is_interior_visible: false,
+            interior_color: [230, 120, 60],
+            render_modes: [RenderMode::default(); FractalGroup::COUNT],
+            volume_density: 20.,
+            volume_step: 0.01,
+            is_clipping: false,
+            clip_normal: Vector3(1., 0., 0.),
+            clip_offset: 0.,
+            clip_color: [230, 90, 60],
+            cutaway: [CutawaySide::Any; 3],
+            is_floor: false,
+            floor_pattern: FloorPattern::default(),
+            floor_height: -2.,
+            floor_checker_scale: 0.5,
+            is_floor_cut: false,
+            floor_color: [180; 3],
+            fractal_shading: ObjectShading::default(),
+            floor_shading: ObjectShading::FULL,
+            csg_operation: CsgOperation::default(),
+            csg_shape: CsgShape::default(),
+            csg_center: Vector3(0., 0., 0.),
+            csg_size: 1.,
+            csg_smoothness: 0.,
+            twist_strength: 0.,
+            twist_axis: WarpAxis::default(),
+            bend_strength: 0.,
+            mirror_planes: MirrorPlanes::default(),
+            scene_objects: SceneObject::default_list(),
+            ambient_light: 0.1,
+            lights: Light::default_rig(),
+            projection: Projection::default(),
+            stereo_mode: StereoMode::default(),
+            eye_separation: 0.1,
+            is_parameter_preview: false,
+            preview_horizontal_axis: ParameterAxis::ConstantReal,
+            preview_vertical_axis: ParameterAxis::ConstantI,
+            preview_range: 0.5,
+            preview_grid_size: 6,
+            is_aov_preview: false,
+            is_distance_slice: false,
+            distance_slice_axis: WarpAxis::Z,
+            distance_slice_offset: 0.,
+            distance_slice_extent: 2.,
+            is_legend: true,
+            is_half_precision: false,
+            is_axes: false,
+            axes_length: 2.,
+            is_axes_ticks: true,
+            axes_tick_spacing: 0.5,
+            is_pivot_marker: false,
+            aov_layer: AovLayer::default(),
+            chromatic_aberration: 0.,
+            film_grain: 0.,
+            exposure: 0.,
+            is_auto_exposure: false,
+            min_exposure: -4.,
+            max_exposure: 4.,
+            adaptation_speed: 2.,
+            is_outline: false,
+            outline_thickness: 1.,
+            outline_color: [0; 3],
+            transfer_function: TransferFunction::default(),
+            gamma: 2.2,
+            upscale_filter: UpscaleFilter::default(),
+            sharpness: 0.5,
+            is_frame_budget: false,
+            target_frame_time: 16.,
+            max_frame_latency: 2,
+            is_low_latency: false,
+            is_drag_preview: true,
+            drag_preview_scale: 0.5,
+            is_camera_smoothing: false,
+            camera_smoothing: 0.1,
+            zoom_speed: 1.,
+            is_camera_collision: false,
+            is_progressive: false,
+            is_taa: false,
+            taa_blend: 0.1,
+            max_samples: 64,
+            region_of_interest: RegionOfInterest::default(),
+            roi_radius: 100.,
+            roi_outside_rate: 0.1,
+        }
+    }
+}
+
+impl GuiData {
+    // Frame time the iteration count is adapted to while interacting, if the budget is enabled
+    #[must_use]
+    pub(crate) fn frame_budget(&self) -> Option<Duration> {
+        self.is_frame_budget
+            .then(|| Duration::from_secs_f32(self.target_frame_time / 1000.))
+    }
+
+    // Low latency mode only lets a single frame wait for the GPU, so the view follows the input closely
+    #[must_use]
+    pub(crate) fn frame_latency(&self) -> u32 {
+        if self.is_low_latency {
+            1
+        } else {
+            self.max_frame_latency
+        }
+    }
+
+    // Share of the mouse input not yet applied to the camera which is applied after the elapsed time,
+    // so the camera covers most of the way within a time constant and slows down towards its end
+    #[must_use]
+    pub(crate) fn camera_input_share(&self, elapsed: Duration) -> f64 {
+        if !self.is_camera_smoothing || self.camera_smoothing <= 0. {
+            return 1.;
+        }
+
+        1. - (-elapsed.as_secs_f64() / f64::from(self.camera_smoothing)).exp()
+    }
+
+    // Default scene with a cheap configuration, used by the safe mode to get a usable picture on any adapter
+    #[must_use]
+    pub(crate) fn safe_mode() -> Self {
+        Self {
+            max_iterations: 64,
+            max_distance: 100.,
+            ..Self::default()
+        }
+    }
+
+    // Share of the distance estimate the march steps at once
+    #[must_use]
+    pub(crate) fn distance_fudge_factor(&self) -> f32 {
+        match self.distance_clamping {
+            DistanceClamping::Off => 1.,
+            DistanceClamping::FractalDefault => self.fractal_group.distance_fudge(),
+            DistanceClamping::Custom => self.distance_fudge.clamp(0.05, 1.),
+        }
+    }
+
+    // Each fold scales the sponge by this factor, with the corner copies leaving holes of the chosen size
+    #[must_use]
+    pub(crate) fn menger_scale(&self) -> f32 {
+        2. / (1. - self.menger_hole_size)
+    }
+
+    #[must_use]
+    pub(crate) fn sierpinski_rotation_matrix(&self) -> Matrix3x3<f32> {
+        let Vector3(x, y, z) = self.sierpinski_rotation;
+
+        Matrix3x3::rotation_matrix_z(Radians::from_degrees(z))
+            * Matrix3x3::rotation_matrix_y(Radians::from_degrees(y))
+            * Matrix3x3::rotation_matrix_x(Radians::from_degrees(x))
+    }
+
+    // Columns of the 4D rotation taking points of the slice into quaternion space
+    #[must_use]
+    pub(crate) fn hyperplane_rotation_columns(&self) -> [Vector4<f32>; 4] {
+        let Vector3(x, y, z) = self.hyperplane_rotation;
+        let mut columns = [
+            Vector4(1., 0., 0., 0.),
+            Vector4(0., 1., 0., 0.),
+            Vector4(0., 0., 1., 0.),
+            Vector4(0., 0., 0., 1.),
+        ];
+
+        // Rotations in the xw, yw and zw planes, applied in that order
+        for (axis, angle) in [x, y, z].into_iter().enumerate() {
+            let (sin, cos) = angle.to_radians().sin_cos();
+            for column in &mut columns {
+                let (component, w) = (column[axis], column[3]);
+                column[axis] = cos * component - sin * w;
+                column[3] = sin * component + cos * w;
+            }
+        }
+
+        columns
+    }
+
+    // Formula the hybrid group applies at the iteration, repeating the interleave pattern
+    #[must_use]
+    pub(crate) fn hybrid_formula(&self, iteration: u32) -> HybridFormula {
+        let length = self
+            .hybrid_pattern_length
+            .clamp(1, MAX_HYBRID_PATTERN_LENGTH);
+        if self.hybrid_pattern & (1 << (iteration % length)) == 0 {
+            self.hybrid_formula_a
+        } else {
+            self.hybrid_formula_b
+        }
+    }
+
+    // Interleave pattern written out with A and B for the two formulas, such as AAB
+    #[must_use]
+    pub(crate) fn hybrid_pattern_text(&self) -> String {
+        (0..self
+            .hybrid_pattern_length
+            .clamp(1, MAX_HYBRID_PATTERN_LENGTH))
+            .map(|step| {
+                if self.hybrid_pattern & (1 << step) == 0 {
+                    'A'
+                } else {
+                    'B'
+                }
+            })
+            .collect()
+    }
+
+    // Seconds after which the animated constant is back where it started
+    #[must_use]
+    pub(crate) fn animation_period(&self) -> f32 {
+        TAU / self.animation_speed.abs().max(MIN_ANIMATION_SPEED)
+    }
+
+    #[must_use]
+    pub(crate) fn power_sweep_period(&self) -> f32 {
+        self.power_sweep_period.max(MIN_POWER_SWEEP_PERIOD)
+    }
+
+    // Moves the constant animation and the power sweep on by the given seconds while they play,
+    // each wrapping around after its period
+    pub(crate) fn advance_animation(&mut self, seconds: f32) {
+        if self.is_animation_playing && self.constant_animation != ConstantAnimation::Off {
+            self.animation_time =
+                (self.animation_time + seconds).rem_euclid(self.animation_period());
+        }
+        if self.is_power_sweep_playing && self.power_sweep != PowerSweep::Off {
+            self.power_sweep_time =
+                (self.power_sweep_time + seconds).rem_euclid(self.power_sweep_period());
+        }
+    }
+
+    // Power the fractal is rendered with, which follows the sweep unless it is off
+    #[must_use]
+    pub(crate) fn swept_power(&self) -> f32 {
+        let phase = self.power_sweep_time / self.power_sweep_period();
+        let fraction = match self.power_sweep {
+            PowerSweep::Off => return self.power,
+            PowerSweep::Loop => phase,
+            PowerSweep::PingPong => 1. - (2. * phase - 1.).abs(),
+        };
+
+        self.power_sweep_min
+            + (self.power_sweep_max - self.power_sweep_min) * fraction.clamp(0., 1.)
+    }
+
+    #[must_use]
+    pub(crate) fn parameter(&self, axis: ParameterAxis) -> f32 {
+        match axis {
+            ParameterAxis::Power => self.power,
+            _ => self.constant[axis.id() as usize],
+        }
+    }
+
+    // Moves a parameter along one of the preview axes, keeping it within the range of the settings menu
+    pub(crate) fn offset_parameter(&mut self, axis: ParameterAxis, offset: f32) {
+        if axis == ParameterAxis::Power {
+            self.power = (self.power + offset).clamp(1., 10.);
+        } else {
+            let component = &mut self.constant[axis.id() as usize];
+            *component = (*component + offset).clamp(-1., 1.);
         }
     }
 }
 
 #[derive(Clone, Copy, Debug, Default)]
+#[allow(clippy::struct_excessive_bools)]
 pub(crate) struct OptionsData {
     pub(crate) max_iterations: u32,
     pub(crate) max_distance: f32,
     pub(crate) epsilon: f32,
     pub(crate) fractal_color: LinearRgb,
     pub(crate) background_color: LinearRgb,
-    pub(crate) is_heatmap: bool,
+    pub(crate) coloring_mode: ColoringMode,
+    pub(crate) is_smooth_heatmap: bool,
+    pub(crate) heatmap_scale: f32,
+    pub(crate) heatmap_offset: f32,
     pub(crate) fractal_group: FractalGroup,
     pub(crate) primitive_shape: PrimitiveShape,
+    pub(crate) sphere_radius: f32,
+    pub(crate) cylinder_radius: f32,
+    pub(crate) cylinder_half_height: f32,
+    pub(crate) box_half_extents: Vector3<f32>,
+    pub(crate) torus_outer_radius: f32,
+    pub(crate) torus_inner_radius: f32,
+    pub(crate) menger_iterations: u32,
+    pub(crate) menger_scale: f32,
+    pub(crate) sierpinski_scale: f32,
+    pub(crate) sierpinski_offset: Vector3<f32>,
+    pub(crate) sierpinski_rotation: Matrix3x3<f32>,
+    pub(crate) kleinian_box_size: Vector3<f32>,
+    pub(crate) kleinian_inversion_radius: f32,
+    pub(crate) kleinian_iterations: u32,
+    pub(crate) hybrid_formula_a: HybridFormula,
+    pub(crate) hybrid_formula_b: HybridFormula,
+    pub(crate) hybrid_pattern: u32,
+    pub(crate) hybrid_pattern_length: u32,
+    pub(crate) hybrid_iterations: u32,
+    pub(crate) hybrid_box_scale: f32,
+    pub(crate) surf_fold: Vector2<f32>,
+    pub(crate) surf_scale: f32,
+    pub(crate) surf_min_radius: f32,
+    pub(crate) surf_iterations: u32,
+    pub(crate) custom_iterations: u32,
+    pub(crate) julia_formula: JuliaFormula,
+    // Bit flags of the folded components, the lowest bit for the real part
+    pub(crate) julia_abs_folds: u32,
+    pub(crate) bailout_radius: f32,
+    pub(crate) julia_estimator: JuliaEstimator,
     pub(crate) power: f32,
     pub(crate) constant: Vector4<f32>,
+    pub(crate) constant_animation: ConstantAnimation,
+    pub(crate) animation_speed: f32,
+    pub(crate) animation_radius: f32,
+    pub(crate) animation_time: f32,
+    pub(crate) phoenix_strength: f32,
+    pub(crate) cubic_constant: Vector4<f32>,
+    pub(crate) hyperplane_offset: f32,
+    pub(crate) hyperplane_rotation: [Vector4<f32>; 4],
+    pub(crate) is_analytic_normal: bool,
+    // Step length multiplier for over-relaxed sphere tracing, 1 is plain sphere tracing
+    pub(crate) relaxation: f32,
+    // Applied to every distance estimate of the march, below 1 for estimates which overshoot
+    pub(crate) distance_fudge: f32,
+    // Longest step the march takes, however far the estimate reaches
+    pub(crate) max_step: f32,
+    pub(crate) debug_view: DebugView,
+    // Distance of the debug slicing plane from the origin, along the view direction
+    pub(crate) slice_offset: f32,
+    // Rays continue through the set, absorbing light by Beer-Lambert's law
+    pub(crate) is_translucent: bool,
+    pub(crate) absorption_density: f32,
+    // Color which passes through the set unabsorbed
+    pub(crate) absorption_color: LinearRgb,
+    pub(crate) is_interior_visible: bool,
+    // Color of the boundary seen from inside the set
+    pub(crate) interior_color: LinearRgb,
+    pub(crate) render_mode: RenderMode,
+    // Density scale of the volume, emitting the fractal color and absorbing everything behind it
+    pub(crate) volume_density: f32,
+    // Length of the fixed steps taken through the volume
+    pub(crate) volume_step: f32,
+    // Everything in front of the clipping plane, on the side its normal points to, is cut away
+    pub(crate) is_clipping: bool,
+    pub(crate) clip_normal: Vector3<f32>,
+    pub(crate) clip_offset: f32,
+    // Color of the cut surface
+    pub(crate) clip_color: LinearRgb,
+    // Sign of the cut away side of every axis, zero for axes the cut is open along
+    pub(crate) cutaway_signs: Vector3<f32>,
+    // Infinite horizontal plane below the fractal, shaded with the shadows and occlusion the fractal casts
+    pub(crate) is_floor: bool,
+    pub(crate) floor_pattern: FloorPattern,
+    pub(crate) floor_height: f32,
+    // Side length of a single checkerboard square
+    pub(crate) floor_checker_scale: f32,
+    pub(crate) floor_color: LinearRgb,
+    // Scene is intersected with the space above the floor
+    pub(crate) is_floor_cut: bool,
+    // Volume the fractal of the current group and shape stays in, used to skip the empty space around it
+    pub(crate) bounding_volume: BoundingVolume,
+    pub(crate) fractal_shading: ObjectShading,
+    pub(crate) floor_shading: ObjectShading,
+    // Union, intersection or subtraction of a primitive shape and the fractal, applied wherever the
+    // scene is evaluated so shadows, occlusion and the clipping plane see the combined shape
+    pub(crate) csg_operation: CsgOperation,
+    pub(crate) csg_shape: CsgShape,
+    pub(crate) csg_center: Vector3<f32>,
+    pub(crate) csg_size: f32,
+    // Distance over which the surfaces are blended into each other
+    pub(crate) csg_smoothness: f32,
+    // Deformations of the sample point before the fractal's distance estimate, the twist rotates
+    // it about the axis and the bend in the xz plane, by angles growing along the axis and x
+    pub(crate) twist_strength: f32,
+    pub(crate) twist_axis: WarpAxis,
+    pub(crate) bend_strength: f32,
+    // Reflections applied before the warps, making any fractal symmetric about the planes
+    pub(crate) mirror_planes: MirrorPlanes,
+    // Rounds the march state and fractal iterations to 16 bit floats, to judge half precision hardware
+    pub(crate) is_half_precision: bool,
+    pub(crate) stereo_mode: StereoMode,
+    // Distance between the eyes, which converge on the point the camera orbits
+    pub(crate) eye_separation: f32,
+    pub(crate) projection: Projection,
 }
 
 impl BufferDataDescriptor for OptionsData {
@@ -184,17 +1423,100 @@ impl BufferDataDescriptor for OptionsData {
             epsilon: self.epsilon,
             fractal_color: self.fractal_color.into_packed(),
             background_color: self.background_color.into_packed(),
-            is_heatmap: u32::from(self.is_heatmap),
+            coloring_mode: self.coloring_mode.id(),
             fractal_group_id: self.fractal_group.id(),
             primitive_id: self.primitive_shape.id(),
+            sphere_radius: self.sphere_radius,
+            cylinder_radius: self.cylinder_radius,
+            cylinder_half_height: self.cylinder_half_height,
+            box_half_extents: self.box_half_extents.into_packed(),
+            torus_outer_radius: self.torus_outer_radius,
+            torus_inner_radius: self.torus_inner_radius,
+            menger_iterations: self.menger_iterations,
+            menger_scale: self.menger_scale,
+            sierpinski_scale: self.sierpinski_scale,
+            sierpinski_offset: self.sierpinski_offset.into_packed(),
+            sierpinski_rotation: self.sierpinski_rotation.into_packed(),
+            kleinian_box_size: self.kleinian_box_size.into_packed(),
+            kleinian_inversion_radius: self.kleinian_inversion_radius,
+            kleinian_iterations: self.kleinian_iterations,
+            julia_formula: self.julia_formula.id(),
+            julia_abs_folds: self.julia_abs_folds,
+            phoenix_strength: self.phoenix_strength,
+            cubic_constant: self.cubic_constant.into_packed(),
+            hyperplane_rotation: self.hyperplane_rotation.map(IntoPacked::into_packed),
+            hyperplane_offset: self.hyperplane_offset,
+            animation_time: self.animation_time,
+            constant_animation: self.constant_animation.id(),
+            animation_speed: self.animation_speed,
+            animation_radius: self.animation_radius,
+            hybrid_formula_a: self.hybrid_formula_a.id(),
+            hybrid_formula_b: self.hybrid_formula_b.id(),
+            hybrid_pattern: self.hybrid_pattern,
+            hybrid_pattern_length: self.hybrid_pattern_length,
+            hybrid_iterations: self.hybrid_iterations,
+            hybrid_box_scale: self.hybrid_box_scale,
+            surf_fold: self.surf_fold.into_packed(),
+            surf_scale: self.surf_scale,
+            surf_min_radius: self.surf_min_radius,
+            surf_iterations: self.surf_iterations,
+            custom_iterations: self.custom_iterations,
+            cutaway_signs: self.cutaway_signs.into_packed(),
+            is_floor_cut: u32::from(self.is_floor_cut),
+            bounding_extents: self.bounding_volume.extents.into_packed(),
+            bounding_shape: self.bounding_volume.shape.id(),
+            max_step: self.max_step,
             power: self.power,
+            is_analytic_normal: u32::from(self.is_analytic_normal),
             constant: self.constant.into_packed(),
+            debug_view: self.debug_view.id(),
+            slice_offset: self.slice_offset,
+            relaxation: self.relaxation,
+            distance_fudge: self.distance_fudge,
+            bailout_radius: self.bailout_radius,
+            julia_estimator: self.julia_estimator.id(),
+            is_smooth_heatmap: u32::from(self.is_smooth_heatmap),
+            heatmap_scale: self.heatmap_scale,
+            heatmap_offset: self.heatmap_offset,
+            is_translucent: u32::from(self.is_translucent),
+            absorption_density: self.absorption_density,
+            absorption_color: self.absorption_color.into_packed(),
+            is_interior_visible: u32::from(self.is_interior_visible),
+            interior_color: self.interior_color.into_packed(),
+            render_mode: self.render_mode.id(),
+            volume_density: self.volume_density,
+            volume_step: self.volume_step,
+            clip_normal: self.clip_normal.into_packed(),
+            is_clipping: u32::from(self.is_clipping),
+            clip_color: self.clip_color.into_packed(),
+            clip_offset: self.clip_offset,
+            floor_color: self.floor_color.into_packed(),
+            is_floor: u32::from(self.is_floor),
+            floor_height: self.floor_height,
+            floor_checker_scale: self.floor_checker_scale,
+            floor_pattern: self.floor_pattern.id(),
+            fractal_shading: self.fractal_shading.bits(),
+            floor_shading: self.floor_shading.bits(),
+            csg_operation: self.csg_operation.id(),
+            csg_shape: self.csg_shape.id(),
+            csg_center: self.csg_center.into_packed(),
+            csg_size: self.csg_size,
+            csg_smoothness: self.csg_smoothness,
+            twist_strength: self.twist_strength,
+            twist_axis: self.twist_axis.id(),
+            bend_strength: self.bend_strength,
+            mirror_planes: self.mirror_planes.bits(),
+            is_half_precision: u32::from(self.is_half_precision),
+            stereo_mode: self.stereo_mode.id(),
+            eye_separation: self.eye_separation,
+            projection: self.projection.id(),
             ..Default::default()
         }
     }
 }
 
 impl From<GuiData> for OptionsData {
+    #[allow(clippy::too_many_lines)]
     fn from(gui_data: GuiData) -> Self {
         Self {
             max_iterations: gui_data.max_iterations,
@@ -210,35 +1532,1249 @@ impl From<GuiData> for OptionsData {
                 gui_data.background_color[1],
                 gui_data.background_color[2],
             ),
-            is_heatmap: gui_data.is_heatmap,
+            coloring_mode: gui_data.coloring_mode,
+            is_smooth_heatmap: gui_data.is_smooth_heatmap,
+            heatmap_scale: gui_data.heatmap_scale,
+            heatmap_offset: gui_data.heatmap_offset,
             fractal_group: gui_data.fractal_group,
             primitive_shape: gui_data.primitive_shape,
-            power: gui_data.power,
+            sphere_radius: gui_data.sphere_radius,
+            cylinder_radius: gui_data.cylinder_radius,
+            cylinder_half_height: gui_data.cylinder_half_height,
+            box_half_extents: gui_data.box_half_extents,
+            torus_outer_radius: gui_data.torus_outer_radius,
+            torus_inner_radius: gui_data.torus_inner_radius,
+            menger_iterations: gui_data.menger_iterations,
+            menger_scale: gui_data.menger_scale(),
+            sierpinski_scale: gui_data.sierpinski_scale,
+            sierpinski_offset: gui_data.sierpinski_offset,
+            sierpinski_rotation: gui_data.sierpinski_rotation_matrix(),
+            kleinian_box_size: gui_data.kleinian_box_size,
+            kleinian_inversion_radius: gui_data.kleinian_inversion_radius,
+            kleinian_iterations: gui_data.kleinian_iterations,
+            hybrid_formula_a: gui_data.hybrid_formula_a,
+            hybrid_formula_b: gui_data.hybrid_formula_b,
+            hybrid_pattern: gui_data.hybrid_pattern,
+            hybrid_pattern_length: gui_data
+                .hybrid_pattern_length
+                .clamp(1, MAX_HYBRID_PATTERN_LENGTH),
+            hybrid_iterations: gui_data.hybrid_iterations,
+            hybrid_box_scale: gui_data.hybrid_box_scale,
+            surf_fold: gui_data.surf_fold,
+            surf_scale: gui_data.surf_scale,
+            surf_min_radius: gui_data.surf_min_radius,
+            surf_iterations: gui_data.surf_iterations,
+            custom_iterations: gui_data.custom_iterations,
+            julia_formula: gui_data.julia_formula,
+            julia_abs_folds: gui_data
+                .julia_abs_folds
+                .iter()
+                .enumerate()
+                .fold(0, |flags, (component, &is_folded)| {
+                    flags | (u32::from(is_folded) << component)
+                }),
+            power: gui_data.swept_power(),
             constant: gui_data.constant,
+            constant_animation: gui_data.constant_animation,
+            animation_speed: gui_data.animation_speed,
+            animation_radius: gui_data.animation_radius,
+            animation_time: gui_data.animation_time,
+            phoenix_strength: gui_data.phoenix_strength,
+            cubic_constant: gui_data.cubic_constant,
+            hyperplane_offset: gui_data.hyperplane_offset,
+            hyperplane_rotation: gui_data.hyperplane_rotation_columns(),
+            is_analytic_normal: gui_data.is_analytic_normal,
+            relaxation: gui_data.relaxation,
+            distance_fudge: gui_data.distance_fudge_factor(),
+            // No step is ever longer than the max distance
+            max_step: if gui_data.is_step_clamped {
+                gui_data.max_step
+            } else {
+                gui_data.max_distance
+            },
+            bailout_radius: gui_data.bailout_radius,
+            julia_estimator: gui_data.julia_estimator,
+            debug_view: gui_data.debug_view,
+            slice_offset: gui_data.slice_offset,
+            // Only the Julia groups have an interior worth looking through
+            is_translucent: gui_data.is_translucent && gui_data.fractal_group.is_julia(),
+            absorption_density: gui_data.absorption_density,
+            absorption_color: gui_data.absorption_color.into(),
+            is_interior_visible: gui_data.is_interior_visible && gui_data.fractal_group.is_julia(),
+            interior_color: gui_data.interior_color.into(),
+            render_mode: gui_data.render_modes[gui_data.fractal_group.id() as usize],
+            volume_density: gui_data.volume_density,
+            volume_step: gui_data.volume_step,
+            // Plane cannot be oriented without a normal, so clipping is disabled instead
+            is_clipping: gui_data.is_clipping && gui_data.clip_normal.length() > 0.,
+            clip_normal: if gui_data.clip_normal.length() > 0. {
+                gui_data.clip_normal.normalize()
+            } else {
+                gui_data.clip_normal
+            },
+            clip_offset: gui_data.clip_offset,
+            clip_color: gui_data.clip_color.into(),
+            cutaway_signs: Vector3(
+                gui_data.cutaway[0].sign(),
+                gui_data.cutaway[1].sign(),
+                gui_data.cutaway[2].sign(),
+            ),
+            is_floor: gui_data.is_floor,
+            is_floor_cut: gui_data.is_floor && gui_data.is_floor_cut,
+            bounding_volume: gui_data
+                .fractal_group
+                .bounding_volume(gui_data.primitive_shape, gui_data.sierpinski_offset),
+            floor_pattern: gui_data.floor_pattern,
+            floor_height: gui_data.floor_height,
+            floor_checker_scale: gui_data.floor_checker_scale,
+            floor_color: gui_data.floor_color.into(),
+            fractal_shading: gui_data.fractal_shading,
+            floor_shading: gui_data.floor_shading,
+            csg_operation: gui_data.csg_operation,
+            csg_shape: gui_data.csg_shape,
+            csg_center: gui_data.csg_center,
+            csg_size: gui_data.csg_size,
+            csg_smoothness: gui_data.csg_smoothness.max(0.),
+            twist_strength: gui_data.twist_strength,
+            twist_axis: gui_data.twist_axis,
+            bend_strength: gui_data.bend_strength,
+            mirror_planes: gui_data.mirror_planes,
+            is_half_precision: gui_data.is_half_precision,
+            stereo_mode: gui_data.stereo_mode,
+            eye_separation: gui_data.eye_separation,
+            projection: gui_data.projection,
         }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::util::math::{PI, Vector3};
+#[derive(Clone, Copy, Debug, Default)]
+#[allow(clippy::struct_excessive_bools)]
+pub(crate) struct PostData {
+    pub(crate) chromatic_aberration: f32,
+    pub(crate) film_grain: f32,
+    // Frame counter used to seed the film grain, so the noise changes every frame
+    pub(crate) frame: u32,
+    // Exposure in EV, acts as compensation on top of the adapted exposure in auto mode
+    pub(crate) exposure: f32,
+    pub(crate) is_auto_exposure: bool,
+    // Edges found in the depth and normal output layers, drawn on top of the exposed image
+    pub(crate) is_outline: bool,
+    // Distance in pixels at which neighbouring depths and normals are compared
+    pub(crate) outline_thickness: f32,
+    pub(crate) outline_color: LinearRgb,
+    // Background gets zero alpha, set by the surface profile rather than the settings
+    pub(crate) is_transparent: bool,
+    // Encoding of the linear image into display values, done by the post pass on every platform
+    pub(crate) transfer_function: TransferFunction,
+    pub(crate) gamma: f32,
+    // Whether the target view encodes to sRGB on its own, set by the surface format
+    pub(crate) is_srgb_target: bool,
+    // Filter the render texture is read with, before any other effect
+    pub(crate) upscale_filter: UpscaleFilter,
+    pub(crate) sharpness: f32,
+    // Fraction of the render texture covered by the image, lowered while previewing a drag
+    pub(crate) render_scale: f32,
+}
 
-    #[test]
-    fn test_camera_matrix() {
-        let camera_data = CameraData {
-            origin_distance: 0.,
-            min_distance: 0.,
-            angles: Vector2(Radians::from_radians(PI), Radians::from_radians(PI)),
-        };
+impl BufferDataDescriptor for PostData {
+    type BufferData = PostUniformData;
 
-        assert_eq!(
-            camera_data.camera_matrix(),
-            Matrix3x3::from_columns(
-                Vector3(1., 0., 0.),
-                Vector3(0., -1., 0.),
-                Vector3(0., 0., -1.)
-            )
-        );
+    fn into_buffer_data(self) -> Self::BufferData {
+        Self::BufferData {
+            chromatic_aberration: self.chromatic_aberration,
+            film_grain: self.film_grain,
+            frame: self.frame,
+            exposure: self.exposure,
+            is_auto_exposure: u32::from(self.is_auto_exposure),
+            is_outline: u32::from(self.is_outline),
+            outline_thickness: self.outline_thickness,
+            outline_color: self.outline_color.into_packed(),
+            is_transparent: u32::from(self.is_transparent),
+            transfer_function: self.transfer_function.id(),
+            gamma: self.gamma,
+            is_srgb_target: u32::from(self.is_srgb_target),
+            upscale_filter: self.upscale_filter.id(),
+            sharpness: self.sharpness,
+            render_scale: self.render_scale,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<GuiData> for PostData {
+    fn from(gui_data: GuiData) -> Self {
+        Self {
+            chromatic_aberration: gui_data.chromatic_aberration,
+            film_grain: gui_data.film_grain,
+            exposure: gui_data.exposure,
+            is_auto_exposure: gui_data.is_auto_exposure,
+            is_outline: gui_data.is_outline,
+            outline_thickness: gui_data.outline_thickness,
+            outline_color: gui_data.outline_color.into(),
+            transfer_function: gui_data.transfer_function,
+            gamma: gui_data.gamma,
+            upscale_filter: gui_data.upscale_filter,
+            sharpness: gui_data.sharpness,
+            render_scale: 1.,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct ExposureData {
+    pub(crate) is_auto_exposure: bool,
+    // Bounds of the adapted exposure in EV
+    pub(crate) min_exposure: f32,
+    pub(crate) max_exposure: f32,
+    pub(crate) adaptation_speed: f32,
+    // Seconds since the last adaptation step
+    pub(crate) delta_time: f32,
+}
+
+impl BufferDataDescriptor for ExposureData {
+    type BufferData = ExposureUniformData;
+
+    fn into_buffer_data(self) -> Self::BufferData {
+        Self::BufferData {
+            min_exposure: self.min_exposure,
+            max_exposure: self.max_exposure,
+            adaptation_speed: self.adaptation_speed,
+            delta_time: self.delta_time,
+        }
+    }
+}
+
+impl From<GuiData> for ExposureData {
+    fn from(gui_data: GuiData) -> Self {
+        Self {
+            is_auto_exposure: gui_data.is_auto_exposure,
+            min_exposure: gui_data.min_exposure,
+            max_exposure: gui_data.max_exposure,
+            adaptation_speed: gui_data.adaptation_speed,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct PreviewData {
+    // Preview is only rendered for the Julia groups while its window is open
+    pub(crate) is_enabled: bool,
+    pub(crate) horizontal_axis: ParameterAxis,
+    pub(crate) vertical_axis: ParameterAxis,
+    // Extent of the parameter space covered along each axis, centered on the current parameters
+    pub(crate) range: f32,
+    // Number of tiles along each side of the grid
+    pub(crate) grid_size: u32,
+}
+
+impl PreviewData {
+    // Offsets of the parameters along the horizontal and vertical axis for the given tile
+    // Note: has to match preview_main in preview.wgsl
+    #[must_use]
+    pub(crate) fn tile_offsets(self, tile: Vector2<u32>) -> Vector2<f32> {
+        #[allow(clippy::cast_precision_loss)]
+        let grid_size = self.grid_size as f32;
+        #[allow(clippy::cast_precision_loss)]
+        let grid_position = Vector2(
+            (tile.0 as f32 + 0.5) / grid_size - 0.5,
+            (tile.1 as f32 + 0.5) / grid_size - 0.5,
+        );
+
+        // Vertical axis grows upwards, while tiles are counted from the top
+        Vector2(self.range * grid_position.0, -self.range * grid_position.1)
+    }
+}
+
+impl BufferDataDescriptor for PreviewData {
+    type BufferData = PreviewUniformData;
+
+    fn into_buffer_data(self) -> Self::BufferData {
+        Self::BufferData {
+            grid_size: self.grid_size,
+            horizontal_axis: self.horizontal_axis.id(),
+            vertical_axis: self.vertical_axis.id(),
+            range: self.range,
+        }
+    }
+}
+
+impl From<GuiData> for PreviewData {
+    fn from(gui_data: GuiData) -> Self {
+        Self {
+            is_enabled: gui_data.is_parameter_preview && gui_data.fractal_group.is_julia(),
+            horizontal_axis: gui_data.preview_horizontal_axis,
+            vertical_axis: gui_data.preview_vertical_axis,
+            range: gui_data.preview_range,
+            grid_size: gui_data.preview_grid_size,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct DistanceSliceData {
+    // Slice is only computed while its window is open
+    pub(crate) is_enabled: bool,
+    // Normal of the plane, which lies at the offset along it
+    pub(crate) axis: WarpAxis,
+    pub(crate) offset: f32,
+    // Half the side length of the square shown, centered on the axis
+    pub(crate) extent: f32,
+}
+
+impl BufferDataDescriptor for DistanceSliceData {
+    type BufferData = DistanceSliceUniformData;
+
+    fn into_buffer_data(self) -> Self::BufferData {
+        Self::BufferData {
+            axis: self.axis.id(),
+            offset: self.offset,
+            extent: self.extent,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<GuiData> for DistanceSliceData {
+    fn from(gui_data: GuiData) -> Self {
+        Self {
+            is_enabled: gui_data.is_distance_slice,
+            axis: gui_data.distance_slice_axis,
+            offset: gui_data.distance_slice_offset,
+            extent: gui_data.distance_slice_extent,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct DistanceGridData {
+    pub(crate) settings: MeshSettings,
+    // First layer along z of the band sampled by the next dispatch
+    pub(crate) first_layer: u32,
+}
+
+impl BufferDataDescriptor for DistanceGridData {
+    type BufferData = DistanceGridUniformData;
+
+    fn into_buffer_data(self) -> Self::BufferData {
+        Self::BufferData {
+            resolution: self.settings.resolution,
+            extent: self.settings.extent,
+            first_layer: self.first_layer,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct AovData {
+    // Layers are always rendered, only showing them in the GUI is optional
+    pub(crate) is_enabled: bool,
+    pub(crate) layer: AovLayer,
+    // Used to map depth and iteration count into displayable ranges
+    pub(crate) max_distance: f32,
+    pub(crate) max_iterations: u32,
+}
+
+impl BufferDataDescriptor for AovData {
+    type BufferData = AovUniformData;
+
+    fn into_buffer_data(self) -> Self::BufferData {
+        Self::BufferData {
+            layer: self.layer.id(),
+            max_distance: self.max_distance,
+            max_iterations: self.max_iterations,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<GuiData> for AovData {
+    fn from(gui_data: GuiData) -> Self {
+        Self {
+            is_enabled: gui_data.is_aov_preview,
+            layer: gui_data.aov_layer,
+            max_distance: gui_data.max_distance,
+            max_iterations: gui_data.max_iterations,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct LightingData {
+    pub(crate) ambient: f32,
+    pub(crate) lights: [Light; MAX_LIGHTS],
+}
+
+impl BufferDataDescriptor for LightingData {
+    type BufferData = LightingStorageData;
+
+    fn into_buffer_data(self) -> Self::BufferData {
+        Self::BufferData {
+            ambient: self.ambient,
+            lights: self.lights.map(|light| LightStorageData {
+                direction: light.direction().into_packed(),
+                intensity: light.intensity,
+                color: LinearRgb::from(light.color).into_packed(),
+                is_enabled: u32::from(light.is_enabled),
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<GuiData> for LightingData {
+    fn from(gui_data: GuiData) -> Self {
+        Self {
+            ambient: gui_data.ambient_light,
+            lights: gui_data.lights,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SceneData {
+    pub(crate) objects: [SceneObject; MAX_SCENE_OBJECTS],
+}
+
+impl SceneData {
+    // Objects with a shape of their own which are shown, together with their transforms composed
+    // along their groups
+    fn visible_objects(&self) -> impl Iterator<Item = (&SceneObject, scene_graph::Transform)> {
+        let transforms = scene_graph::world_transforms(&self.objects);
+
+        self.objects
+            .iter()
+            .zip(transforms)
+            .enumerate()
+            .filter(|(index, (object, _))| {
+                object.kind != SceneObjectKind::Group
+                    && scene_graph::is_visible(&self.objects, *index)
+            })
+            .map(|(_, object_transform)| object_transform)
+    }
+
+    // Scene is the fractal alone, which lets the shaders use its analytic normals
+    #[must_use]
+    pub(crate) fn is_plain(&self) -> bool {
+        let mut objects = self.visible_objects();
+
+        matches!(
+            (objects.next(), objects.next()),
+            (Some((object, transform)), None)
+                if object.kind == SceneObjectKind::Fractal && transform.is_identity()
+        )
+    }
+}
+
+impl BufferDataDescriptor for SceneData {
+    type BufferData = SceneStorageData;
+
+    // Shown objects are packed to the front, so the shaders only loop over those
+    fn into_buffer_data(self) -> Self::BufferData {
+        let mut buffer_data = Self::BufferData {
+            is_plain: u32::from(self.is_plain()),
+            ..Default::default()
+        };
+        for (slot, (object, transform)) in
+            buffer_data.objects.iter_mut().zip(self.visible_objects())
+        {
+            let (row1, row2, row3) = transform.rotation.columns();
+            *slot = SceneObjectStorageData {
+                inverse_rotation: Matrix3x3::from_rows(row1, row2, row3).into_packed(),
+                translation: transform.translation.into_packed(),
+                // Zero would divide the position in the shaders
+                scale: transform.scale.max(f32::EPSILON),
+                kind: object.kind.id(),
+                material: object.material.into(),
+                ..Default::default()
+            };
+            buffer_data.object_count += 1;
+        }
+
+        buffer_data
+    }
+}
+
+impl From<GuiData> for SceneData {
+    fn from(gui_data: GuiData) -> Self {
+        Self {
+            objects: gui_data.scene_objects,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct AxesData {
+    // Overlay only matches the rays of the mono perspective camera
+    pub(crate) is_enabled: bool,
+    // Axes reach this far from the origin in both directions
+    pub(crate) length: f32,
+    pub(crate) tick_spacing: f32,
+    pub(crate) is_ticks: bool,
+    // Fraction of the depth layer covered by the image, see PostData
+    pub(crate) render_scale: f32,
+}
+
+impl BufferDataDescriptor for AxesData {
+    type BufferData = AxesUniformData;
+
+    fn into_buffer_data(self) -> Self::BufferData {
+        Self::BufferData {
+            length: self.length,
+            tick_spacing: self.tick_spacing,
+            is_ticks: u32::from(self.is_ticks),
+            render_scale: self.render_scale,
+        }
+    }
+}
+
+impl From<GuiData> for AxesData {
+    fn from(gui_data: GuiData) -> Self {
+        Self {
+            is_enabled: gui_data.is_axes
+                && gui_data.projection == Projection::Perspective
+                && gui_data.stereo_mode == StereoMode::Off,
+            length: gui_data.axes_length,
+            tick_spacing: gui_data.axes_tick_spacing,
+            is_ticks: gui_data.is_axes_ticks,
+            render_scale: 1.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct TaaData {
+    // Reprojection only matches the rays of the mono perspective camera
+    pub(crate) is_enabled: bool,
+    // Weight of the current frame against the reprojected history
+    pub(crate) blend: f32,
+    // Cleared whenever the history no longer shows the same scene, set by the TAA state
+    pub(crate) is_history_valid: bool,
+}
+
+impl BufferDataDescriptor for TaaData {
+    type BufferData = TaaUniformData;
+
+    fn into_buffer_data(self) -> Self::BufferData {
+        Self::BufferData {
+            blend: self.blend,
+            is_history_valid: u32::from(self.is_history_valid),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<GuiData> for TaaData {
+    fn from(gui_data: GuiData) -> Self {
+        Self {
+            is_enabled: gui_data.is_taa
+                && gui_data.projection == Projection::Perspective
+                && gui_data.stereo_mode == StereoMode::Off,
+            blend: gui_data.taa_blend,
+            ..Default::default()
+        }
+    }
+}
+
+// Region of the screen in pixels
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum RegionShape {
+    Circle {
+        center: Vector2<f32>,
+        radius: f32,
+    },
+    Rectangle {
+        min: Vector2<f32>,
+        max: Vector2<f32>,
+    },
+}
+
+// Position of a tile within the image it is part of, in pixels
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TileView {
+    pub(crate) offset: Vector2<f32>,
+    pub(crate) image_size: Vector2<f32>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SampleData {
+    pub(crate) index: u32,
+    pub(crate) jitter: Vector2<f32>,
+    pub(crate) region: Option<RegionShape>,
+    // Probabilities of a pixel being sampled this frame, depending on whether it lies in the region
+    pub(crate) inside_rate: f32,
+    pub(crate) outside_rate: f32,
+    // Tile of a larger image, rendered into the top left corner instead of the whole screen
+    pub(crate) tile: Option<TileView>,
+}
+
+impl Default for SampleData {
+    fn default() -> Self {
+        Self {
+            index: 0,
+            jitter: Vector2(0., 0.),
+            region: None,
+            inside_rate: 1.,
+            outside_rate: 1.,
+            tile: None,
+        }
+    }
+}
+
+impl BufferDataDescriptor for SampleData {
+    type BufferData = SampleUniformData;
+
+    fn into_buffer_data(self) -> Self::BufferData {
+        let (roi_mode, roi_center, roi_extent) = match self.region {
+            None => (0, Vector2(0., 0.), Vector2(0., 0.)),
+            Some(RegionShape::Circle { center, radius }) => (1, center, Vector2(radius, radius)),
+            Some(RegionShape::Rectangle { min, max }) => (2, (min + max) / 2., (max - min) / 2.),
+        };
+        // Zero sized view stands for the screen
+        let (view_offset, view_size) = self
+            .tile
+            .map_or((Vector2(0., 0.), Vector2(0., 0.)), |tile| {
+                (tile.offset, tile.image_size)
+            });
+
+        Self::BufferData {
+            jitter: self.jitter.into_packed(),
+            index: self.index,
+            roi_mode,
+            roi_center: roi_center.into_packed(),
+            roi_extent: roi_extent.into_packed(),
+            inside_rate: self.inside_rate,
+            outside_rate: self.outside_rate,
+            view_offset: view_offset.into_packed(),
+            view_size: view_size.into_packed(),
+        }
+    }
+}
+
+// Samples are counted in the alpha channel of the 16 bit float render texture, which stops counting
+// at 2048, and a region of interest takes up to twice this many samples
+pub(crate) const MAX_SAMPLES: u32 = 1024;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct ProgressiveData {
+    pub(crate) is_progressive: bool,
+    // First sample after a reset is jittered as well, which the TAA resolve averages over frames
+    pub(crate) is_taa: bool,
+    pub(crate) max_samples: u32,
+    pub(crate) region_of_interest: RegionOfInterest,
+    pub(crate) roi_radius: f32,
+    pub(crate) roi_outside_rate: f32,
+}
+
+impl ProgressiveData {
+    // Sampling rates inside and outside the region of interest for the given sample index or None when done
+    // While the region converges, the outside only gets a fraction of samples, which it then catches up on,
+    // so every pixel ends up with roughly max_samples samples
+    #[must_use]
+    pub(crate) fn sample_rates(&self, index: u32, has_region: bool) -> Option<(f32, f32)> {
+        let max_samples = self.max_samples.max(1);
+
+        if index == 0 {
+            // First sample always covers the whole screen, so there are no empty pixels
+            Some((1., 1.))
+        } else if !self.is_progressive {
+            None
+        } else if !has_region {
+            (index < max_samples).then_some((1., 1.))
+        } else if index < max_samples {
+            Some((1., self.roi_outside_rate))
+        } else if index < 2 * max_samples {
+            Some((0., 1. - self.roi_outside_rate))
+        } else {
+            None
+        }
+    }
+}
+
+impl From<GuiData> for ProgressiveData {
+    fn from(gui_data: GuiData) -> Self {
+        Self {
+            is_progressive: gui_data.is_progressive,
+            is_taa: TaaData::from(gui_data).is_enabled,
+            max_samples: gui_data.max_samples.min(MAX_SAMPLES),
+            region_of_interest: gui_data.region_of_interest,
+            roi_radius: gui_data.roi_radius,
+            roi_outside_rate: gui_data.roi_outside_rate,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct RayPathRequestData {
+    pub(crate) pixel: Vector2<u32>,
+    pub(crate) is_recording: bool,
+}
+
+impl BufferDataDescriptor for RayPathRequestData {
+    type BufferData = RayPathHeaderData;
+
+    fn into_buffer_data(self) -> Self::BufferData {
+        Self::BufferData {
+            pixel: self.pixel.into_packed(),
+            is_recording: u32::from(self.is_recording),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct RayStep {
+    pub(crate) position: Vector3<f32>,
+    // Distance estimate evaluated at position
+    pub(crate) distance: f32,
+}
+
+// March of a single ray, as recorded by the shader
+#[derive(Clone, Debug)]
+pub(crate) struct RayPath {
+    pub(crate) pixel: Vector2<u32>,
+    pub(crate) steps: Vec<RayStep>,
+}
+
+impl RayPath {
+    // Parses the contents of the ray path storage buffer, a header followed by the recorded steps
+    #[must_use]
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Self {
+        let header_size = size_of::<RayPathHeaderData>();
+        let header: RayPathHeaderData = bytemuck::pod_read_unaligned(&bytes[..header_size]);
+
+        let steps = bytes[header_size..]
+            .chunks_exact(size_of::<RayStepData>())
+            .take(header.length as usize)
+            .map(|chunk| {
+                let step: RayStepData = bytemuck::pod_read_unaligned(chunk);
+                RayStep {
+                    position: step.position.into_unpacked(),
+                    distance: step.distance,
+                }
+            })
+            .collect();
+
+        Self {
+            pixel: header.pixel.into_unpacked(),
+            steps,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::scene::BoundingShape;
+    use crate::util::math::{PI, Vector3};
+
+    #[test]
+    fn test_camera_matrix() {
+        let camera_data = CameraData {
+            angles: Vector2(Radians::from_radians(PI), Radians::from_radians(PI)),
+            ..CameraData::default()
+        };
+
+        assert_eq!(
+            camera_data.camera_matrix(),
+            Matrix3x3::from_columns(
+                Vector3(1., 0., 0.),
+                Vector3(0., -1., 0.),
+                Vector3(0., 0., -1.)
+            )
+        );
+    }
+
+    #[test]
+    fn test_options_uniform_layout() {
+        macro_rules! assert_offsets {
+            ($($field:ident: $offset:expr),* $(,)?) => {
+                $(assert_eq!(
+                    std::mem::offset_of!(OptionsUniformData, $field),
+                    $offset,
+                    stringify!($field)
+                );)*
+            };
+        }
+
+        // Offsets of OptionsUniform in bindings.wgsl, where vec3 fields are 16 byte aligned
+        assert_offsets! {
+            max_iterations: 0,
+            max_distance: 4,
+            epsilon: 8,
+            fractal_color: 16,
+            background_color: 32,
+            coloring_mode: 44,
+            fractal_group_id: 48,
+            primitive_id: 52,
+            power: 56,
+            is_analytic_normal: 60,
+            constant: 64,
+            debug_view: 80,
+            slice_offset: 84,
+            relaxation: 88,
+            is_smooth_heatmap: 92,
+            heatmap_scale: 96,
+            heatmap_offset: 100,
+            is_translucent: 104,
+            absorption_density: 108,
+            absorption_color: 112,
+            render_mode: 124,
+            volume_density: 128,
+            volume_step: 132,
+            clip_normal: 144,
+            is_clipping: 156,
+            clip_color: 160,
+            clip_offset: 172,
+            floor_color: 176,
+            is_floor: 188,
+            floor_height: 192,
+            floor_checker_scale: 196,
+            floor_pattern: 200,
+            is_half_precision: 204,
+            stereo_mode: 208,
+            eye_separation: 212,
+            projection: 216,
+            menger_iterations: 220,
+            menger_scale: 224,
+            sierpinski_scale: 228,
+            sierpinski_offset: 240,
+            sierpinski_rotation: 256,
+            kleinian_box_size: 304,
+            kleinian_inversion_radius: 316,
+            kleinian_iterations: 320,
+            julia_formula: 324,
+            phoenix_strength: 328,
+            cubic_constant: 336,
+            hyperplane_rotation: 352,
+            hyperplane_offset: 416,
+            animation_time: 420,
+            constant_animation: 424,
+            animation_speed: 428,
+            animation_radius: 432,
+            hybrid_formula_a: 436,
+            hybrid_formula_b: 440,
+            hybrid_pattern: 444,
+            hybrid_pattern_length: 448,
+            hybrid_iterations: 452,
+            hybrid_box_scale: 456,
+            julia_abs_folds: 460,
+            fractal_shading: 464,
+            floor_shading: 468,
+            sphere_radius: 472,
+            cylinder_radius: 476,
+            box_half_extents: 480,
+            cylinder_half_height: 492,
+            torus_outer_radius: 496,
+            torus_inner_radius: 500,
+            csg_operation: 504,
+            csg_shape: 508,
+            csg_center: 512,
+            csg_size: 524,
+            csg_smoothness: 528,
+            twist_strength: 532,
+            twist_axis: 536,
+            bend_strength: 540,
+            mirror_planes: 544,
+            distance_fudge: 548,
+            bailout_radius: 552,
+            is_interior_visible: 556,
+            julia_estimator: 560,
+            interior_color: 576,
+            surf_scale: 588,
+            surf_fold: 592,
+            surf_min_radius: 600,
+            surf_iterations: 604,
+            custom_iterations: 608,
+            cutaway_signs: 624,
+            is_floor_cut: 636,
+            bounding_extents: 640,
+            bounding_shape: 652,
+            max_step: 656,
+        }
+        // The buffer is bound without a minimum size, so it has to cover the whole WGSL struct
+        assert_eq!(std::mem::size_of::<OptionsUniformData>(), 672);
+    }
+
+    #[test]
+    fn test_camera_projection() {
+        let camera_data = CameraData::default();
+        let pivot = camera_data
+            .project(CameraData::PIVOT, 2.)
+            .expect("pivot is in front of the camera");
+        assert!(pivot.0.abs() < 1e-6 && pivot.1.abs() < 1e-6);
+
+        // Default camera looks along -x from (5, 0, 0), so +y is to its right
+        let point = camera_data
+            .project(Vector3(0., 1., 1.), 2.)
+            .expect("point is in front of the camera");
+        assert!((point.0 - 0.1).abs() < 1e-6 && (point.1 - 0.2).abs() < 1e-6);
+        assert!(camera_data.project(Vector3(10., 0., 0.), 2.).is_none());
+
+        for point in [
+            Vector3(0., 1., 1.),
+            Vector3(-3., 0.5, -2.),
+            Vector3(0., 0., 8.),
+        ] {
+            let centered = camera_data
+                .centered_on(point)
+                .project(point, 2.)
+                .expect("point is in front of the camera");
+            assert!(centered.0.abs() < 1e-5 && centered.1.abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_camera_modes() {
+        let orbit_camera = CameraData {
+            angles: Vector2(Radians::from_radians(1.), Radians::from_radians(0.5)),
+            ..CameraData::default()
+        };
+        // Switching keeps the view as it was
+        let fly_camera = orbit_camera.with_mode(CameraMode::FreeFly);
+        assert_eq!(fly_camera.position(), orbit_camera.position());
+        assert_eq!(fly_camera.camera_matrix(), orbit_camera.camera_matrix());
+
+        // Flying forward approaches the pivot the orbit camera was looking at
+        let fly_camera = fly_camera.fly(Vector3(0., 0., 1.));
+        assert!((fly_camera.position().length() - 4.).abs() < 1e-5);
+        let pivot = fly_camera.project(CameraData::PIVOT, 2.).unwrap();
+        assert!(pivot.0.abs() < 1e-5 && pivot.1.abs() < 1e-5);
+
+        // Flying right moves the pivot to the left of the view
+        let pivot = fly_camera
+            .fly(Vector3(1., 0., 0.))
+            .project(CameraData::PIVOT, 2.)
+            .unwrap();
+        assert!(pivot.0 < 0. && pivot.1.abs() < 1e-5);
+
+        // Free-fly cameras turn towards points instead of orbiting to them
+        let point = Vector3(0., 3., -1.);
+        let turned_camera = fly_camera.centered_on(point);
+        assert_eq!(turned_camera.position(), fly_camera.position());
+        let centered = turned_camera.project(point, 2.).unwrap();
+        assert!(centered.0.abs() < 1e-5 && centered.1.abs() < 1e-5);
+
+        // Orbit camera looks at the pivot from where the free-fly camera was
+        let orbit_camera = turned_camera.with_mode(CameraMode::Orbit);
+        assert!((orbit_camera.position() - fly_camera.position()).length() < 1e-5);
+        let pivot = orbit_camera.project(CameraData::PIVOT, 2.).unwrap();
+        assert!(pivot.0.abs() < 1e-5 && pivot.1.abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_camera_input_share() {
+        let mut gui_data = GuiData::default();
+        assert!((gui_data.camera_input_share(Duration::ZERO) - 1.).abs() < 1e-9);
+
+        gui_data.is_camera_smoothing = true;
+        gui_data.camera_smoothing = 0.25;
+        assert!(gui_data.camera_input_share(Duration::ZERO).abs() < 1e-9);
+        // Within one time constant all but a share of 1/e is applied
+        let share = gui_data.camera_input_share(Duration::from_millis(250));
+        assert!((share - (1. - (-1_f64).exp())).abs() < 1e-9);
+        // Shares of consecutive frames add up to the share of their total time
+        let first = gui_data.camera_input_share(Duration::from_millis(50));
+        let second = gui_data.camera_input_share(Duration::from_millis(200));
+        assert!((first + (1. - first) * second - share).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_camera_zoom() {
+        let camera_data = CameraData::default();
+        let distance_left =
+            |camera_data: CameraData| camera_data.origin_distance - camera_data.min_distance;
+
+        // Every line shrinks the distance left by the same factor, however deep the zoom is
+        let zoomed_camera = camera_data.zoomed(1., 1.);
+        assert!((distance_left(zoomed_camera) - 0.8 * distance_left(camera_data)).abs() < 1e-5);
+        let deep_camera = camera_data.zoomed(20., 1.);
+        assert!(deep_camera.origin_distance > deep_camera.min_distance);
+        let deeper_camera = deep_camera.zoomed(1., 1.);
+        assert!((distance_left(deeper_camera) / distance_left(deep_camera) - 0.8).abs() < 1e-3);
+
+        // Speed multiplies the lines, zooming out again returns to the start
+        let fast_camera = camera_data.zoomed(1., 2.);
+        assert!((distance_left(fast_camera) - 0.64 * distance_left(camera_data)).abs() < 1e-5);
+        let restored_camera = fast_camera.zoomed(-2., 1.);
+        assert!((restored_camera.origin_distance - camera_data.origin_distance).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_camera_panning() {
+        let camera_data = CameraData::default();
+        // Default camera looks along -x, so its right is +y and its up +z
+        let panned_camera = camera_data.pan(Vector2(1., 2.));
+        assert!((panned_camera.pan_target - Vector3(0., 1., 2.)).length() < 1e-5);
+        assert!((panned_camera.position() - Vector3(5., 1., 2.)).length() < 1e-5);
+        assert_eq!(panned_camera.camera_matrix(), camera_data.camera_matrix());
+
+        // Target stays in the center of the view, with the pivot left behind below and to the left
+        let target = panned_camera.project(panned_camera.pan_target, 2.).unwrap();
+        assert!(target.0.abs() < 1e-5 && target.1.abs() < 1e-5);
+        let pivot = panned_camera.project(CameraData::PIVOT, 2.).unwrap();
+        assert!(pivot.0 < 0. && pivot.1 < 0.);
+
+        // Orbiting keeps the distance to the target
+        let orbited_camera = CameraData {
+            angles: Vector2(Radians::from_radians(2.), Radians::from_radians(-0.3)),
+            ..panned_camera
+        };
+        let distance = (orbited_camera.position() - orbited_camera.pan_target).length();
+        assert!((distance - camera_data.origin_distance).abs() < 1e-5);
+
+        // Centering orbits around the target instead of the pivot
+        let point = Vector3(0., 1., 5.);
+        let centered_camera = panned_camera.centered_on(point);
+        let centered = centered_camera.project(point, 2.).unwrap();
+        assert!(centered.0.abs() < 1e-5 && centered.1.abs() < 1e-5);
+        assert_eq!(centered_camera.pan_target, panned_camera.pan_target);
+    }
+
+    #[test]
+    fn test_region_of_interest_sample_rates() {
+        let progressive_data = ProgressiveData {
+            is_progressive: true,
+            max_samples: 4,
+            roi_outside_rate: 0.25,
+            ..Default::default()
+        };
+
+        assert_eq!(progressive_data.sample_rates(0, true), Some((1., 1.)));
+        assert_eq!(progressive_data.sample_rates(3, true), Some((1., 0.25)));
+        assert_eq!(progressive_data.sample_rates(4, true), Some((0., 0.75)));
+        assert_eq!(progressive_data.sample_rates(8, true), None);
+        assert_eq!(progressive_data.sample_rates(4, false), None);
+
+        let non_progressive_data = ProgressiveData {
+            is_progressive: false,
+            ..progressive_data
+        };
+        assert_eq!(non_progressive_data.sample_rates(0, true), Some((1., 1.)));
+        assert_eq!(non_progressive_data.sample_rates(1, true), None);
+
+        // Loaded settings are capped as well, so the sample count fits the render texture alpha
+        let capped_data = ProgressiveData::from(GuiData {
+            is_progressive: true,
+            max_samples: 4096,
+            ..GuiData::default()
+        });
+        assert_eq!(capped_data.max_samples, MAX_SAMPLES);
+        assert_eq!(capped_data.sample_rates(2 * MAX_SAMPLES, true), None);
+    }
+
+    #[test]
+    fn test_ray_path_from_bytes() {
+        let header = RayPathHeaderData {
+            pixel: Vector2(3, 7).into_packed(),
+            is_recording: 1,
+            length: 2,
+        };
+        let steps = [
+            RayStepData {
+                position: Vector3(0., 0., 5.).into_packed(),
+                distance: 2.,
+            },
+            RayStepData {
+                position: Vector3(0., 0., 3.).into_packed(),
+                distance: 0.5,
+            },
+            // Left over from an earlier, longer recording
+            RayStepData {
+                position: Vector3(1., 1., 1.).into_packed(),
+                distance: 9.,
+            },
+        ];
+
+        let mut bytes = bytemuck::bytes_of(&header).to_vec();
+        bytes.extend_from_slice(bytemuck::cast_slice(&steps));
+        let ray_path = RayPath::from_bytes(&bytes);
+
+        assert_eq!((ray_path.pixel.0, ray_path.pixel.1), (3, 7));
+        assert_eq!(ray_path.steps.len(), 2);
+        assert_eq!(ray_path.steps[1].position, Vector3(0., 0., 3.));
+        assert!((ray_path.steps[1].distance - 0.5).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_hyperplane_rotation() {
+        let gui_data = GuiData {
+            hyperplane_rotation: Vector3(90., 0., 0.),
+            ..GuiData::default()
+        };
+        let [x, y, _, w] = gui_data.hyperplane_rotation_columns();
+
+        // Turning x fully towards the fourth axis swaps the two, leaving y untouched
+        assert!((x.3 - 1.).abs() < 1e-6 && x.0.abs() < 1e-6);
+        assert!((w.0 + 1.).abs() < 1e-6 && w.3.abs() < 1e-6);
+        assert_eq!(y, Vector4(0., 1., 0., 0.));
+    }
+
+    #[test]
+    fn test_scene_objects() {
+        let mut scene_data = SceneData::from(GuiData::default());
+        assert!(scene_data.is_plain());
+
+        scene_data.objects[2] = SceneObject {
+            is_enabled: true,
+            kind: SceneObjectKind::Box,
+            translation: Vector3(1., 2., 3.),
+            rotation: Vector3(0., 0., 90.),
+            scale: 2.,
+            parent: None,
+            material: Material {
+                reflectivity: 1.5,
+                emissive: [0; 3],
+                emission_strength: 4.,
+                ..Material::default()
+            },
+        };
+        assert!(!scene_data.is_plain());
+
+        // Enabled objects are packed to the front, with the rotation sent inverted
+        let buffer_data = scene_data.into_buffer_data();
+        assert_eq!(buffer_data.object_count, 2);
+        let object = buffer_data.objects[1];
+        assert_eq!(object.kind, SceneObjectKind::Box.id());
+        let inverse_rotation: Matrix3x3<f32> = object.inverse_rotation.into_unpacked();
+        assert_eq!(
+            inverse_rotation * scene_data.objects[2].rotation_matrix(),
+            Matrix3x3::IDENTITY
+        );
+        // Material travels with its object, black stays dark whatever the emission strength
+        assert!((object.material.reflectivity - 1.).abs() < f32::EPSILON);
+        let emissive: Vector3<f32> = object.material.emissive.into_unpacked();
+        assert_eq!(emissive, Vector3(0., 0., 0.));
+    }
+
+    #[test]
+    fn test_distance_fudge_factor() {
+        let gui_data = GuiData {
+            fractal_group: FractalGroup::Hybrid,
+            ..GuiData::default()
+        };
+        assert!((gui_data.distance_fudge_factor() - 0.6).abs() < f32::EPSILON);
+
+        let gui_data = GuiData {
+            distance_clamping: DistanceClamping::Custom,
+            distance_fudge: 0.,
+            ..gui_data
+        };
+        // Zero would never get the march anywhere
+        assert!((gui_data.distance_fudge_factor() - 0.05).abs() < f32::EPSILON);
+        let gui_data = GuiData {
+            distance_clamping: DistanceClamping::Off,
+            ..gui_data
+        };
+        assert!((gui_data.distance_fudge_factor() - 1.).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_bounding_volume() {
+        // Plain primitives are cheap enough to estimate everywhere
+        let options_data = OptionsData::from(GuiData::default());
+        assert_eq!(options_data.bounding_volume.shape, BoundingShape::Unbounded);
+
+        let options_data = OptionsData::from(GuiData {
+            primitive_shape: PrimitiveShape::SierpinskiTetrahedron,
+            sierpinski_offset: Vector3(3., 0., 4.),
+            ..GuiData::default()
+        });
+        assert_eq!(options_data.bounding_volume.shape, BoundingShape::Sphere);
+        assert!((options_data.bounding_volume.extents.0 - 5.).abs() < f32::EPSILON);
+
+        let options_data = OptionsData::from(GuiData {
+            fractal_group: FractalGroup::JuliaSet,
+            ..GuiData::default()
+        });
+        assert!((options_data.bounding_volume.extents.2 - 2.).abs() < f32::EPSILON);
+
+        let options_data = OptionsData::from(GuiData {
+            fractal_group: FractalGroup::AmazingSurf,
+            ..GuiData::default()
+        });
+        assert_eq!(options_data.bounding_volume.shape, BoundingShape::Unbounded);
+    }
+
+    #[test]
+    fn test_preview_tile_offsets() {
+        let preview_data = PreviewData {
+            range: 1.,
+            grid_size: 4,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            preview_data.tile_offsets(Vector2(0, 0)),
+            Vector2(-0.375, 0.375)
+        );
+        assert_eq!(
+            preview_data.tile_offsets(Vector2(3, 1)),
+            Vector2(0.375, 0.125)
+        );
+
+        let mut gui_data = GuiData::default();
+        gui_data.offset_parameter(ParameterAxis::ConstantI, 0.2);
+        gui_data.offset_parameter(ParameterAxis::Power, -5.);
+        assert!((gui_data.parameter(ParameterAxis::ConstantI) - 0.8).abs() < 1e-6);
+        assert!((gui_data.parameter(ParameterAxis::Power) - 1.).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_constant_animation() {
+        let mut gui_data = GuiData {
+            constant_animation: ConstantAnimation::Circle,
+            animation_speed: 1.,
+            ..GuiData::default()
+        };
+        // Paused animations keep their time
+        gui_data.advance_animation(1.);
+        assert!(gui_data.animation_time.abs() < f32::EPSILON);
+
+        gui_data.is_animation_playing = true;
+        gui_data.advance_animation(TAU + 1.);
+        assert!((gui_data.animation_time - 1.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_power_sweep() {
+        let mut gui_data = GuiData {
+            power: 3.,
+            power_sweep_min: 2.,
+            power_sweep_max: 6.,
+            power_sweep_period: 4.,
+            is_power_sweep_playing: true,
+            ..GuiData::default()
+        };
+        // Power variable is used as is while the sweep is off, which also keeps its time
+        gui_data.advance_animation(1.);
+        assert!((gui_data.swept_power() - 3.).abs() < f32::EPSILON);
+        assert!(gui_data.power_sweep_time.abs() < f32::EPSILON);
+
+        gui_data.power_sweep = PowerSweep::Loop;
+        gui_data.advance_animation(7.);
+        assert!((gui_data.swept_power() - 5.).abs() < 1e-5);
+
+        // Ping-pong is halfway back down at three quarters of the period, at the bottom after it
+        gui_data.power_sweep = PowerSweep::PingPong;
+        assert!((gui_data.swept_power() - 4.).abs() < 1e-5);
+        gui_data.advance_animation(1.);
+        assert!((gui_data.swept_power() - 2.).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_hybrid_pattern() {
+        let gui_data = GuiData {
+            hybrid_pattern: 0b100,
+            hybrid_pattern_length: 3,
+            ..GuiData::default()
+        };
+        assert_eq!(gui_data.hybrid_pattern_text(), "AAB");
+        // Pattern repeats after its length, bits past it are ignored
+        assert_eq!(gui_data.hybrid_formula(2), gui_data.hybrid_formula_b);
+        assert_eq!(gui_data.hybrid_formula(3), gui_data.hybrid_formula_a);
+        assert_eq!(gui_data.hybrid_formula(5), gui_data.hybrid_formula_b);
     }
 }
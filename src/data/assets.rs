@@ -0,0 +1,244 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::error::AssetError;
+
+// Assets up to this size are kept inside the project file itself
+pub(crate) const MAX_EMBEDDED_ASSET_SIZE: u64 = 256 * 1024;
+
+// Contents are written as hex, so project files stay plain text
+mod hex {
+    use serde::{Deserialize, Deserializer, Serializer, de::Error as _};
+    use std::fmt::Write as _;
+
+    pub(super) fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut text = String::with_capacity(2 * bytes.len());
+        for byte in bytes {
+            // Writing into a string can not fail
+            let _ = write!(text, "{byte:02x}");
+        }
+
+        serializer.serialize_str(&text)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        if text.len() % 2 != 0 {
+            return Err(D::Error::custom("hex data of odd length"));
+        }
+
+        (0..text.len())
+            .step_by(2)
+            .map(|index| {
+                text.get(index..index + 2)
+                    .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                    .ok_or_else(|| D::Error::custom("invalid hex data"))
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) enum AssetSource {
+    // Relative paths are relative to the directory of the project file
+    File(PathBuf),
+    Embedded(#[serde(with = "hex")] Vec<u8>),
+}
+
+// File used by the scene, such as an environment map, a palette or a shader, looked up by its name
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Asset {
+    pub(crate) name: String,
+    pub(crate) source: AssetSource,
+}
+
+// Path relative to the base directory when it lies inside of it, otherwise left as it is
+#[must_use]
+fn relative_path(path: &Path, base_directory: &Path) -> PathBuf {
+    path.strip_prefix(base_directory)
+        .map_or_else(|_| path.to_path_buf(), Path::to_path_buf)
+}
+
+// Assets of a project, files are resolved against the directory of the project file, so a project
+// moved to another machine together with its assets keeps working
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AssetManager {
+    // Working directory while the project was never saved
+    base_directory: PathBuf,
+    assets: Vec<Asset>,
+}
+
+impl AssetManager {
+    #[must_use]
+    pub(crate) fn new(base_directory: PathBuf, assets: Vec<Asset>) -> Self {
+        Self {
+            base_directory,
+            assets,
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn assets(&self) -> &[Asset] {
+        &self.assets
+    }
+
+    #[must_use]
+    pub(crate) fn resolve(&self, path: &Path) -> PathBuf {
+        self.base_directory.join(path)
+    }
+
+    // Named after the file, paths inside the project directory are kept relative to it
+    pub(crate) fn add_file(&mut self, path: &Path) {
+        let name = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |name| name.to_string_lossy().to_string(),
+        );
+        self.assets.push(Asset {
+            name,
+            source: AssetSource::File(relative_path(path, &self.base_directory)),
+        });
+    }
+
+    pub(crate) fn remove(&mut self, index: usize) {
+        if index < self.assets.len() {
+            self.assets.remove(index);
+        }
+    }
+
+    /// ## Errors
+    /// - `AssetError::Missing` when the project has no asset of the name
+    /// - `AssetError::Io(io::Error)` when the file of the asset could not be read
+    pub(crate) fn load(&self, name: &str) -> Result<Vec<u8>, AssetError> {
+        let asset = self
+            .assets
+            .iter()
+            .find(|asset| asset.name == name)
+            .ok_or_else(|| AssetError::Missing(name.to_string()))?;
+
+        match &asset.source {
+            AssetSource::File(path) => Ok(fs::read(self.resolve(path))?),
+            AssetSource::Embedded(data) => Ok(data.clone()),
+        }
+    }
+
+    /// Loads the asset of the name, or the file at the path, which is then added to the project
+    /// unless it already is, so the project saved afterwards finds it again
+    ///
+    /// ## Errors
+    /// - `AssetError::Io(io::Error)` when the file could not be read
+    pub(crate) fn load_or_add(&mut self, name_or_path: &str) -> Result<Vec<u8>, AssetError> {
+        if self.assets.iter().any(|asset| asset.name == name_or_path) {
+            return self.load(name_or_path);
+        }
+
+        let path = Path::new(name_or_path);
+        let data = fs::read(self.resolve(path))?;
+        let source = AssetSource::File(relative_path(path, &self.base_directory));
+        if !self.assets.iter().any(|asset| asset.source == source) {
+            self.add_file(path);
+        }
+
+        Ok(data)
+    }
+
+    /// Copies the contents of a file asset into the project
+    ///
+    /// ## Errors
+    /// - `AssetError::Io(io::Error)` when the file could not be read
+    /// - `AssetError::TooLarge` when the file is larger than `MAX_EMBEDDED_ASSET_SIZE`
+    pub(crate) fn embed(&mut self, index: usize) -> Result<(), AssetError> {
+        let Some(asset) = self.assets.get(index) else {
+            return Ok(());
+        };
+        let AssetSource::File(path) = &asset.source else {
+            return Ok(());
+        };
+
+        let path = self.resolve(path);
+        let size = fs::metadata(&path)?.len();
+        if size > MAX_EMBEDDED_ASSET_SIZE {
+            return Err(AssetError::TooLarge {
+                name: asset.name.clone(),
+                size,
+            });
+        }
+        let data = fs::read(path)?;
+        self.assets[index].source = AssetSource::Embedded(data);
+
+        Ok(())
+    }
+
+    // Moves the project to another directory, keeping every file asset pointing at the same file
+    pub(crate) fn rebase(&mut self, base_directory: &Path) {
+        for asset in &mut self.assets {
+            if let AssetSource::File(path) = &asset.source {
+                let path = self.base_directory.join(path);
+                asset.source = AssetSource::File(relative_path(&path, base_directory));
+            }
+        }
+        self.base_directory = base_directory.to_path_buf();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asset_manager() {
+        let directory = std::env::temp_dir().join("kifs_raymarching_test_assets");
+        fs::create_dir_all(directory.join("maps")).unwrap();
+        fs::write(directory.join("maps/palette.txt"), b"\x00\xffpalette").unwrap();
+
+        // Files inside the project directory are kept relative to it
+        let mut assets = AssetManager::new(directory.clone(), Vec::new());
+        assets.add_file(&directory.join("maps/palette.txt"));
+        assert_eq!(
+            assets.assets()[0].source,
+            AssetSource::File(PathBuf::from("maps/palette.txt"))
+        );
+        assert_eq!(assets.load("palette.txt").unwrap(), b"\x00\xffpalette");
+        assert!(matches!(
+            assets.load("sky.hdr"),
+            Err(AssetError::Missing(_))
+        ));
+
+        // Saving the project next to the assets directory keeps the asset found
+        assets.rebase(&directory.join("maps"));
+        assert_eq!(
+            assets.assets()[0].source,
+            AssetSource::File(PathBuf::from("palette.txt"))
+        );
+
+        // Files loaded by their path become assets once, after which their name finds them too
+        fs::write(directory.join("maps/sdf.wgsl"), b"sdf").unwrap();
+        assert_eq!(assets.load_or_add("sdf.wgsl").unwrap(), b"sdf");
+        assert_eq!(assets.load_or_add("sdf.wgsl").unwrap(), b"sdf");
+        assert_eq!(assets.assets().len(), 2);
+        assert_eq!(
+            assets.assets()[1].source,
+            AssetSource::File(PathBuf::from("sdf.wgsl"))
+        );
+        assert!(matches!(
+            assets.load_or_add("missing.wgsl"),
+            Err(AssetError::Io(_))
+        ));
+        assert_eq!(assets.assets().len(), 2);
+
+        assets.embed(0).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(assets.load("palette.txt").unwrap(), b"\x00\xffpalette");
+
+        let json = serde_json::to_string(&assets.assets()[0]).unwrap();
+        assert!(json.contains("\"00ff70616c65747465\""));
+        assert_eq!(
+            serde_json::from_str::<Asset>(&json).unwrap(),
+            assets.assets()[0]
+        );
+    }
+}
@@ -0,0 +1,177 @@
+use std::time::Duration;
+
+use crate::data::ExposureData;
+
+// Has to match the constants of the histogram in exposure.wgsl
+const MIN_LOG_LUMINANCE: f32 = -10.;
+const LOG_LUMINANCE_RANGE: f32 = 20.;
+const KEY_VALUE: f32 = 0.18;
+// Iteration count of a baked frame is never lowered below this fraction of the configured one
+const MIN_ITERATION_SCALE: f32 = 0.25;
+
+// Times of every frame of an animation of the given length, starting at zero
+#[must_use]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub(crate) fn frame_times(duration: f32, frame_rate: u16) -> Vec<f32> {
+    let frame_rate = f32::from(frame_rate.max(1));
+    let frame_count = (duration.max(0.) * frame_rate).floor() as u32 + 1;
+
+    #[allow(clippy::cast_precision_loss)]
+    (0..frame_count)
+        .map(|frame| frame as f32 / frame_rate)
+        .collect()
+}
+
+// Exposure multiplier the auto exposure settles on for an image given as RGB values, computed the
+// same way as the histogram pass so baked frames look like adapted ones
+#[must_use]
+pub(crate) fn settled_exposure(values: &[f32], exposure_data: ExposureData) -> f32 {
+    let (log_sum, lit_pixels) = values
+        .chunks_exact(3)
+        .map(|pixel| 0.2126 * pixel[0] + 0.7152 * pixel[1] + 0.0722 * pixel[2])
+        // Black pixels are left out, so the empty background does not skew the average
+        .filter(|luminance| *luminance >= MIN_LOG_LUMINANCE.exp2())
+        .fold((0., 0_u32), |(log_sum, lit_pixels), luminance| {
+            let log_luminance = luminance
+                .log2()
+                .clamp(MIN_LOG_LUMINANCE, MIN_LOG_LUMINANCE + LOG_LUMINANCE_RANGE);
+            (log_sum + log_luminance, lit_pixels + 1)
+        });
+    if lit_pixels == 0 {
+        return 1.;
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let average_luminance = (log_sum / lit_pixels as f32).exp2();
+    (KEY_VALUE / average_luminance).clamp(
+        exposure_data.min_exposure.exp2(),
+        exposure_data.max_exposure.exp2(),
+    )
+}
+
+// Frame of the animation as rendered by the bake, before the iteration counts are evened out
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FrameMeasurement {
+    pub(crate) time: f32,
+    pub(crate) exposure: f32,
+    pub(crate) render_time: Duration,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct BakedFrame {
+    pub(crate) time: f32,
+    // Exposure multiplier used instead of the adapting auto exposure
+    pub(crate) exposure: f32,
+    pub(crate) max_iterations: u32,
+}
+
+// Per frame settings of an animation computed ahead of playback, so frames take about as long as
+// each other and the exposure does not lag behind the camera
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BakedTimeline {
+    frames: Vec<BakedFrame>,
+    frame_rate: u16,
+}
+
+impl BakedTimeline {
+    // Frames slower than the median get their iteration count lowered by the ratio of the render
+    // times, which the render time roughly follows
+    #[must_use]
+    pub(crate) fn new(
+        measurements: &[FrameMeasurement],
+        max_iterations: u32,
+        frame_rate: u16,
+    ) -> Self {
+        let mut render_times = measurements
+            .iter()
+            .map(|measurement| measurement.render_time)
+            .collect::<Vec<_>>();
+        render_times.sort_unstable();
+        let median_time = render_times
+            .get(render_times.len() / 2)
+            .copied()
+            .unwrap_or_default();
+
+        let frames = measurements
+            .iter()
+            .map(|measurement| {
+                let scale = (median_time.as_secs_f32()
+                    / measurement.render_time.as_secs_f32().max(f32::EPSILON))
+                .clamp(MIN_ITERATION_SCALE, 1.);
+                #[allow(
+                    clippy::cast_precision_loss,
+                    clippy::cast_possible_truncation,
+                    clippy::cast_sign_loss
+                )]
+                let iterations = (max_iterations as f32 * scale).round() as u32;
+
+                BakedFrame {
+                    time: measurement.time,
+                    exposure: measurement.exposure,
+                    max_iterations: iterations.max(1),
+                }
+            })
+            .collect();
+
+        Self { frames, frame_rate }
+    }
+
+    #[must_use]
+    pub(crate) fn frames(&self) -> &[BakedFrame] {
+        &self.frames
+    }
+
+    // Frame closest to the time, the last one after the end of the timeline
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub(crate) fn frame_at(&self, time: f32) -> Option<BakedFrame> {
+        let index = (time.max(0.) * f32::from(self.frame_rate.max(1))).round() as usize;
+        self.frames
+            .get(index.min(self.frames.len().checked_sub(1)?))
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_baked_timeline() {
+        assert_eq!(frame_times(1., 4), [0., 0.25, 0.5, 0.75, 1.]);
+
+        let exposure_data = ExposureData {
+            min_exposure: -4.,
+            max_exposure: 4.,
+            ..Default::default()
+        };
+        // Black background is ignored, grey of the key value needs no exposure
+        let values = [0., 0., 0., 0.18, 0.18, 0.18];
+        assert!((settled_exposure(&values, exposure_data) - 1.).abs() < 1e-3);
+        assert!((settled_exposure(&[0.; 6], exposure_data) - 1.).abs() < 1e-6);
+
+        let measurement = |time, milliseconds| FrameMeasurement {
+            time,
+            exposure: 1.,
+            render_time: Duration::from_millis(milliseconds),
+        };
+        let timeline = BakedTimeline::new(
+            &[
+                measurement(0., 10),
+                measurement(0.5, 20),
+                measurement(1., 10),
+            ],
+            100,
+            2,
+        );
+        let iterations = timeline
+            .frames()
+            .iter()
+            .map(|frame| frame.max_iterations)
+            .collect::<Vec<_>>();
+        assert_eq!(iterations, [100, 50, 100]);
+        assert_eq!(timeline.frame_at(0.6), Some(timeline.frames()[1]));
+        assert_eq!(timeline.frame_at(5.), Some(timeline.frames()[2]));
+        assert!(BakedTimeline::default().frame_at(0.).is_none());
+    }
+}
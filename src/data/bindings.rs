@@ -0,0 +1,313 @@
+use serde_json::{Number, Value};
+
+use crate::{data::GuiData, data::expression::Expression, error::ExpressionError};
+
+// Numeric setting driven by an expression, re-evaluated every frame
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ParameterBinding {
+    // Path of the setting such as power, constant[0] or lights[1].azimuth
+    pub(crate) target: String,
+    pub(crate) source: String,
+}
+
+// Settings are addressed through their serialization, so every numeric setting, including ones
+// added later, can be bound and read without a table of them
+#[must_use]
+fn json_pointer(path: &str) -> String {
+    let mut pointer = String::from("/");
+    for character in path.chars() {
+        match character {
+            '.' | '[' => pointer.push('/'),
+            ']' => {}
+            _ => pointer.push(character),
+        }
+    }
+
+    pointer
+}
+
+#[must_use]
+fn numeric_value(settings: &Value, path: &str) -> Option<f64> {
+    settings.pointer(&json_pointer(path))?.as_f64()
+}
+
+fn collect_numeric_parameters(path: &str, value: &Value, parameters: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                collect_numeric_parameters(&path, value, parameters);
+            }
+        }
+        Value::Array(values) => {
+            for (index, value) in values.iter().enumerate() {
+                collect_numeric_parameters(&format!("{path}[{index}]"), value, parameters);
+            }
+        }
+        Value::Number(_) => parameters.push(path.to_string()),
+        _ => {}
+    }
+}
+
+/// Paths of every setting which can be bound or read by an expression
+///
+/// ## Errors
+/// - `serde_json::Error` when the settings could not be serialized
+pub(crate) fn numeric_parameters(gui_data: &GuiData) -> Result<Vec<String>, serde_json::Error> {
+    let mut parameters = Vec::new();
+    collect_numeric_parameters("", &serde_json::to_value(gui_data)?, &mut parameters);
+
+    Ok(parameters)
+}
+
+// Integer settings such as iteration counts take the nearest integer they can hold
+fn write_value(settings: &mut Value, path: &str, value: f64) -> Result<(), ExpressionError> {
+    let Some(Value::Number(current)) = settings.pointer_mut(&json_pointer(path)) else {
+        return Err(ExpressionError::UnknownParameter(path.to_string()));
+    };
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let number = if current.is_u64() {
+        Some(Number::from(value.round().max(0.) as u64))
+    } else if current.is_i64() {
+        Some(Number::from(value.round() as i64))
+    } else {
+        Number::from_f64(value)
+    };
+    *current = number.ok_or_else(|| ExpressionError::NotFinite(path.to_string()))?;
+
+    Ok(())
+}
+
+// Settings only hold values their type can, such as iteration counts fitting 32 bits, so a value
+// they refuse is undone instead of failing every other binding
+fn write_checked_value(
+    settings: &mut Value,
+    path: &str,
+    value: f64,
+) -> Result<(), ExpressionError> {
+    let pointer = json_pointer(path);
+    let previous = settings.pointer(&pointer).cloned();
+    write_value(settings, path, value)?;
+
+    if serde_json::from_value::<GuiData>(settings.clone()).is_err() {
+        if let (Some(current), Some(previous)) = (settings.pointer_mut(&pointer), previous) {
+            *current = previous;
+        }
+        return Err(ExpressionError::OutOfRange(path.to_string()));
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Visit {
+    Unvisited,
+    InProgress,
+    Done,
+}
+
+// Depth first search ordering bindings after the ones they read, bindings on a cycle are marked
+// with the cycle and left out of the order
+fn visit(
+    index: usize,
+    dependencies: &[Vec<usize>],
+    visits: &mut [Visit],
+    stack: &mut Vec<usize>,
+    order: &mut Vec<usize>,
+    cycles: &mut Vec<Vec<usize>>,
+) {
+    visits[index] = Visit::InProgress;
+    stack.push(index);
+
+    for &dependency in &dependencies[index] {
+        match visits[dependency] {
+            Visit::Unvisited => visit(dependency, dependencies, visits, stack, order, cycles),
+            Visit::InProgress => {
+                let start = stack
+                    .iter()
+                    .position(|&entry| entry == dependency)
+                    .unwrap_or(0);
+                cycles.push(stack[start..].to_vec());
+            }
+            Visit::Done => {}
+        }
+    }
+
+    stack.pop();
+    visits[index] = Visit::Done;
+    order.push(index);
+}
+
+/// Evaluates the bindings in the order they depend on each other and writes their values into the
+/// settings, returning the value or error of every binding, failed bindings leave their setting alone
+///
+/// ## Errors
+/// - `serde_json::Error` when the settings could not be serialized or did not accept the values
+pub(crate) fn apply_bindings(
+    gui_data: &mut GuiData,
+    bindings: &[ParameterBinding],
+    time: f64,
+) -> Result<Vec<Result<f64, ExpressionError>>, serde_json::Error> {
+    let mut settings = serde_json::to_value(*gui_data)?;
+    let mut results = Vec::with_capacity(bindings.len());
+    let mut expressions = Vec::with_capacity(bindings.len());
+
+    for (index, binding) in bindings.iter().enumerate() {
+        let target = binding.target.trim();
+        let expression = if numeric_value(&settings, target).is_none() {
+            Err(ExpressionError::UnknownParameter(target.to_string()))
+        } else if bindings[..index]
+            .iter()
+            .any(|other| other.target.trim() == target)
+        {
+            Err(ExpressionError::DuplicateTarget(target.to_string()))
+        } else {
+            Expression::parse(&binding.source)
+        };
+
+        match expression {
+            Ok(expression) => {
+                results.push(Ok(0.));
+                expressions.push(Some(expression));
+            }
+            Err(error) => {
+                results.push(Err(error));
+                expressions.push(None);
+            }
+        }
+    }
+
+    // Bindings reading the setting of another valid binding have to be evaluated after it
+    let dependencies = expressions
+        .iter()
+        .map(|expression| {
+            expression.as_ref().map_or_else(Vec::new, |expression| {
+                expression
+                    .parameters()
+                    .into_iter()
+                    .filter_map(|parameter| {
+                        bindings
+                            .iter()
+                            .zip(&expressions)
+                            .position(|(binding, expression)| {
+                                expression.is_some() && binding.target.trim() == parameter
+                            })
+                    })
+                    .collect()
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut visits = vec![Visit::Unvisited; bindings.len()];
+    let mut order = Vec::with_capacity(bindings.len());
+    let mut cycles = Vec::new();
+    for index in 0..bindings.len() {
+        if visits[index] == Visit::Unvisited {
+            visit(
+                index,
+                &dependencies,
+                &mut visits,
+                &mut Vec::new(),
+                &mut order,
+                &mut cycles,
+            );
+        }
+    }
+    for cycle in cycles {
+        let mut names = cycle
+            .iter()
+            .map(|&index| bindings[index].target.trim().to_string())
+            .collect::<Vec<_>>();
+        names.push(names[0].clone());
+        for index in cycle {
+            results[index] = Err(ExpressionError::Cycle(names.clone()));
+        }
+    }
+
+    for index in order {
+        let (Some(expression), Ok(_)) = (&expressions[index], &results[index]) else {
+            continue;
+        };
+        let target = bindings[index].target.trim();
+        let value = expression.evaluate(time, &|parameter| numeric_value(&settings, parameter));
+        results[index] = value.and_then(|value| {
+            write_checked_value(&mut settings, target, value)?;
+            // Value as the setting holds it, e.g. rounded for integers
+            Ok(numeric_value(&settings, target).unwrap_or(value))
+        });
+    }
+
+    *gui_data = serde_json::from_value(settings)?;
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parameter_bindings() {
+        let binding = |target: &str, source: &str| ParameterBinding {
+            target: target.to_string(),
+            source: source.to_string(),
+        };
+        let mut gui_data = GuiData::default();
+        let parameters = numeric_parameters(&gui_data).unwrap();
+        assert!(parameters.contains(&"power".to_string()));
+        assert!(parameters.contains(&"lights[1].azimuth".to_string()));
+
+        // Bindings are evaluated after the ones they read, whatever order they are listed in
+        let results = apply_bindings(
+            &mut gui_data,
+            &[
+                binding("constant[0]", "power / 10"),
+                binding("power", "2 + t"),
+                binding("max_iterations", "power * 10.4"),
+            ],
+            1.,
+        )
+        .unwrap();
+        assert!(results.iter().all(Result::is_ok));
+        assert!((gui_data.power - 3.).abs() < f32::EPSILON);
+        assert!((gui_data.constant.0 - 0.3).abs() < f32::EPSILON);
+        assert_eq!(gui_data.max_iterations, 31);
+
+        let results = apply_bindings(
+            &mut gui_data,
+            &[
+                binding("power", "epsilon + 1"),
+                binding("epsilon", "power"),
+                binding("ambient_light", "0.5"),
+                binding("ambient_light", "0.25"),
+                binding("nothing", "1"),
+                binding("max_iterations", "100000 * 100000"),
+            ],
+            0.,
+        )
+        .unwrap();
+        assert!(matches!(
+            &results[0],
+            Err(ExpressionError::Cycle(names)) if names == &["power", "epsilon", "power"]
+        ));
+        assert!(matches!(&results[1], Err(ExpressionError::Cycle(_))));
+        assert!(matches!(
+            &results[3],
+            Err(ExpressionError::DuplicateTarget(_))
+        ));
+        assert!(matches!(
+            &results[4],
+            Err(ExpressionError::UnknownParameter(_))
+        ));
+        assert!(matches!(&results[5], Err(ExpressionError::OutOfRange(_))));
+        // Settings of failed bindings are left alone
+        assert!((gui_data.power - 3.).abs() < f32::EPSILON);
+        assert_eq!(gui_data.max_iterations, 31);
+        assert!((gui_data.ambient_light - 0.5).abs() < f32::EPSILON);
+    }
+}
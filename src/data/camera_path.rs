@@ -0,0 +1,525 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    f32::consts::{PI, TAU},
+    fmt::{self, Write as _},
+    fs,
+    path::{Path, PathBuf},
+};
+use strum_macros::EnumIter;
+
+use crate::{
+    data::{CameraData, DEFAULT_VERTICAL_FOV},
+    error::CameraPathError,
+    util::math::{Radians, Vector2, Vector3},
+};
+
+// Keyframes saved for loading back, written into the working directory unless another path is given
+pub(crate) const DEFAULT_CAMERA_PATH_FILE: &str = "camera_path.kifs.json";
+// Keyframes of an orbit, close enough together for the interpolation between them to stay round
+const ORBIT_KEYFRAMES: u16 = 12;
+
+// Difference of two angles the short way around
+#[must_use]
+fn angle_difference(from: f32, to: f32) -> f32 {
+    let difference = (to - from).rem_euclid(TAU);
+    if difference > PI {
+        difference - TAU
+    } else {
+        difference
+    }
+}
+
+// Camera the fraction of the way between the two, turning the short way around
+#[must_use]
+pub(crate) fn interpolate_cameras(from: CameraData, to: CameraData, fraction: f32) -> CameraData {
+    let interpolate = |from: f32, to: f32| from + (to - from) * fraction;
+    let phi = from.angles.0.radians();
+
+    CameraData {
+        mode: to.mode,
+        origin_distance: interpolate(from.origin_distance, to.origin_distance),
+        min_distance: interpolate(from.min_distance, to.min_distance),
+        angles: Vector2(
+            Radians::from_radians(phi + angle_difference(phi, to.angles.0.radians()) * fraction)
+                .standardize(),
+            Radians::from_radians(interpolate(from.angles.1.radians(), to.angles.1.radians())),
+        ),
+        fly_position: from.fly_position + (to.fly_position - from.fly_position) * fraction,
+        pan_target: from.pan_target + (to.pan_target - from.pan_target) * fraction,
+        vertical_fov: interpolate(from.vertical_fov, to.vertical_fov),
+    }
+}
+
+// Pace of the camera between two keyframes, eased ends slow down towards their keyframe
+#[derive(Clone, Copy, Debug, Default, PartialEq, EnumIter, Serialize, Deserialize)]
+pub(crate) enum KeyframeEasing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl KeyframeEasing {
+    // Share of the way between two keyframes covered after the share of the time between them
+    #[must_use]
+    fn ease(self, fraction: f32) -> f32 {
+        match self {
+            Self::Linear => fraction,
+            Self::EaseIn => fraction * fraction,
+            Self::EaseOut => 1. - (1. - fraction) * (1. - fraction),
+            Self::EaseInOut => fraction * fraction * (3. - 2. * fraction),
+        }
+    }
+}
+
+impl fmt::Display for KeyframeEasing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Linear => write!(f, "Linear"),
+            Self::EaseIn => write!(f, "Ease in"),
+            Self::EaseOut => write!(f, "Ease out"),
+            Self::EaseInOut => write!(f, "Ease in and out"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) enum CameraPathFormat {
+    // Plain JSON with one world matrix per keyframe, readable without an Alembic library
+    #[default]
+    AlembicJson,
+    // Python script creating an animated camera when run from Blender's text editor
+    BlenderScript,
+}
+
+impl CameraPathFormat {
+    #[must_use]
+    pub(crate) fn path(self) -> PathBuf {
+        match self {
+            CameraPathFormat::AlembicJson => PathBuf::from("camera_path.json"),
+            CameraPathFormat::BlenderScript => PathBuf::from("camera_path.py"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub(crate) struct CameraKeyframe {
+    // Seconds from the start of the path
+    pub(crate) time: f32,
+    pub(crate) camera_data: CameraData,
+}
+
+impl CameraKeyframe {
+    // Camera to world transform as rows, with the camera looking down its negative z axis and y up,
+    // which is the convention of both Alembic and Blender
+    #[must_use]
+    fn world_matrix(&self) -> [[f32; 4]; 4] {
+        let (backward, right, up) = self.camera_data.camera_matrix().columns();
+        let origin = self.camera_data.position();
+
+        let row = |index: usize| {
+            let component = |Vector3(x, y, z): Vector3<f32>| [x, y, z][index];
+            [
+                component(right),
+                component(up),
+                component(backward),
+                component(origin),
+            ]
+        };
+
+        [row(0), row(1), row(2), [0., 0., 0., 1.]]
+    }
+}
+
+#[derive(Serialize)]
+struct AlembicSample {
+    time: f32,
+    frame: u32,
+    vertical_fov_degrees: f32,
+    translation: [f32; 3],
+    matrix: [[f32; 4]; 4],
+}
+
+#[derive(Serialize)]
+struct AlembicCamera {
+    format: &'static str,
+    version: u32,
+    up_axis: &'static str,
+    frame_rate: u16,
+    vertical_fov_degrees: f32,
+    samples: Vec<AlembicSample>,
+}
+
+// Camera poses recorded one after another on a timeline, played back in the app, baked or matched
+// with footage in other 3D tools
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct CameraPath {
+    // Ordered by their time
+    keyframes: Vec<CameraKeyframe>,
+    pub(crate) frame_rate: u16,
+    // Seconds between a keyframe and the one added after it
+    pub(crate) keyframe_interval: f32,
+    pub(crate) easing: KeyframeEasing,
+}
+
+impl Default for CameraPath {
+    fn default() -> Self {
+        Self {
+            keyframes: Vec::new(),
+            frame_rate: 24,
+            keyframe_interval: 2.,
+            easing: KeyframeEasing::default(),
+        }
+    }
+}
+
+impl CameraPath {
+    #[must_use]
+    pub(crate) fn keyframes(&self) -> &[CameraKeyframe] {
+        &self.keyframes
+    }
+
+    pub(crate) fn add_keyframe(&mut self, camera_data: CameraData) {
+        let time = self
+            .keyframes
+            .last()
+            .map_or(0., |keyframe| keyframe.time + self.keyframe_interval);
+        self.keyframes.push(CameraKeyframe { time, camera_data });
+    }
+
+    pub(crate) fn replace_keyframe(&mut self, index: usize, camera_data: CameraData) {
+        if let Some(keyframe) = self.keyframes.get_mut(index) {
+            keyframe.camera_data = camera_data;
+        }
+    }
+
+    pub(crate) fn remove_keyframe(&mut self, index: usize) {
+        if index < self.keyframes.len() {
+            self.keyframes.remove(index);
+        }
+    }
+
+    // Moves the keyframe in time, no further than its neighbours so the keyframes stay in order
+    pub(crate) fn set_keyframe_time(&mut self, index: usize, time: f32) {
+        if index >= self.keyframes.len() {
+            return;
+        }
+
+        let earliest = index
+            .checked_sub(1)
+            .map_or(0., |previous| self.keyframes[previous].time);
+        let latest = self
+            .keyframes
+            .get(index + 1)
+            .map_or(f32::INFINITY, |next| next.time);
+        self.keyframes[index].time = time.clamp(earliest, latest);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.keyframes.clear();
+    }
+
+    // Replaces the keyframes with a full turn around the point the camera looks at, keeping its
+    // distance and elevation, so the camera never comes closer than its minimum distance
+    pub(crate) fn set_orbit(&mut self, camera_data: CameraData) {
+        let Vector2(phi, theta) = camera_data.angles;
+        let camera_data = CameraData {
+            origin_distance: camera_data.origin_distance.max(camera_data.min_distance),
+            ..camera_data
+        };
+
+        // Closing keyframe repeats the first one a full turn later
+        self.keyframes = (0..=ORBIT_KEYFRAMES)
+            .map(|index| {
+                let fraction = f32::from(index) / f32::from(ORBIT_KEYFRAMES);
+                CameraKeyframe {
+                    time: f32::from(index) * self.keyframe_interval,
+                    camera_data: CameraData {
+                        angles: Vector2(phi + Radians::from_radians(fraction * TAU), theta),
+                        ..camera_data
+                    },
+                }
+            })
+            .collect();
+    }
+
+    #[must_use]
+    pub(crate) fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0., |keyframe| keyframe.time)
+    }
+
+    // Camera at the time, moving between the keyframes around it at the pace of the easing
+    #[must_use]
+    pub(crate) fn camera_at(&self, time: f32) -> Option<CameraData> {
+        let next_index = self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.time >= time)?;
+        let next = self.keyframes[next_index];
+        let Some(previous) = next_index.checked_sub(1).map(|index| self.keyframes[index]) else {
+            return Some(next.camera_data);
+        };
+
+        let fraction = self.easing.ease(
+            ((time - previous.time) / (next.time - previous.time).max(f32::EPSILON)).clamp(0., 1.),
+        );
+
+        Some(interpolate_cameras(
+            previous.camera_data,
+            next.camera_data,
+            fraction,
+        ))
+    }
+
+    // Frames are numbered from 1, as Blender scenes start there
+    #[must_use]
+    fn frame(&self, keyframe: &CameraKeyframe) -> u32 {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let frame = (keyframe.time * f32::from(self.frame_rate)).round() as u32;
+        frame + 1
+    }
+
+    /// ## Errors
+    /// - `serde_json::Error` when the path could not be serialized
+    pub(crate) fn to_alembic_json(&self) -> Result<String, serde_json::Error> {
+        let samples = self
+            .keyframes
+            .iter()
+            .map(|keyframe| {
+                let matrix = keyframe.world_matrix();
+                AlembicSample {
+                    time: keyframe.time,
+                    frame: self.frame(keyframe),
+                    vertical_fov_degrees: keyframe.camera_data.vertical_fov,
+                    translation: [matrix[0][3], matrix[1][3], matrix[2][3]],
+                    matrix,
+                }
+            })
+            .collect();
+
+        serde_json::to_string_pretty(&AlembicCamera {
+            format: "alembic-lite",
+            version: 1,
+            up_axis: "Z",
+            frame_rate: self.frame_rate,
+            // Field of view of the first keyframe, for readers which take a single one
+            vertical_fov_degrees: self
+                .keyframes
+                .first()
+                .map_or(DEFAULT_VERTICAL_FOV, |keyframe| {
+                    keyframe.camera_data.vertical_fov
+                }),
+            samples,
+        })
+    }
+
+    #[must_use]
+    pub(crate) fn to_blender_script(&self) -> String {
+        let mut keyframes = String::new();
+        for keyframe in &self.keyframes {
+            let rows = keyframe
+                .world_matrix()
+                .map(|[a, b, c, d]| format!("({a:?}, {b:?}, {c:?}, {d:?})"))
+                .join(", ");
+            // Writing into a string can not fail
+            let _ = writeln!(
+                keyframes,
+                "    ({}, {:?}, ({rows})),",
+                self.frame(keyframe),
+                keyframe.camera_data.vertical_fov
+            );
+        }
+
+        format!(
+            "\
+import math
+
+import bpy
+from mathutils import Matrix
+
+FRAME_RATE = {frame_rate}
+# Frame, vertical field of view in degrees and camera to world matrix of every keyframe, z is up
+# as in Blender
+KEYFRAMES = [
+{keyframes}]
+
+scene = bpy.context.scene
+scene.render.fps = FRAME_RATE
+
+camera_data = bpy.data.cameras.new(\"FractalCamera\")
+camera_data.sensor_fit = \"VERTICAL\"
+camera = bpy.data.objects.new(\"FractalCamera\", camera_data)
+scene.collection.objects.link(camera)
+scene.camera = camera
+
+for frame, fov, rows in KEYFRAMES:
+    camera.matrix_world = Matrix(rows)
+    camera.keyframe_insert(data_path=\"location\", frame=frame)
+    camera.keyframe_insert(data_path=\"rotation_euler\", frame=frame)
+    camera_data.angle_y = math.radians(fov)
+    camera_data.keyframe_insert(data_path=\"lens\", frame=frame)
+
+if KEYFRAMES:
+    scene.frame_start = KEYFRAMES[0][0]
+    scene.frame_end = KEYFRAMES[-1][0]
+",
+            frame_rate = self.frame_rate,
+        )
+    }
+
+    /// Saves the keyframes and playback settings, which load back into any scene
+    ///
+    /// ## Errors
+    /// - `CameraPathError::Json(serde_json::Error)` when the path could not be serialized
+    /// - `CameraPathError::Io(io::Error)` when the file could not be written
+    pub(crate) fn save(&self, path: &Path) -> Result<(), CameraPathError> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+
+        Ok(())
+    }
+
+    /// Keyframes edited by hand are put back in order of their time
+    ///
+    /// ## Errors
+    /// - `CameraPathError::Io(io::Error)` when the file could not be read
+    /// - `CameraPathError::Json(serde_json::Error)` when the file is not a valid camera path
+    pub(crate) fn load(path: &Path) -> Result<Self, CameraPathError> {
+        let mut camera_path: Self = serde_json::from_str(&fs::read_to_string(path)?)?;
+        camera_path
+            .keyframes
+            .sort_by(|keyframe, other| keyframe.time.total_cmp(&other.time));
+
+        Ok(camera_path)
+    }
+
+    /// Writes the path into the working directory, returning the file written
+    ///
+    /// ## Errors
+    /// - `CameraPathError::Json(serde_json::Error)` when the path could not be serialized
+    /// - `CameraPathError::Io(io::Error)` when the file could not be written
+    pub(crate) fn export(&self, format: CameraPathFormat) -> Result<PathBuf, CameraPathError> {
+        let contents = match format {
+            CameraPathFormat::AlembicJson => self.to_alembic_json()?,
+            CameraPathFormat::BlenderScript => self.to_blender_script(),
+        };
+        let path = format.path();
+        fs::write(&path, contents)?;
+
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_camera_path_export() {
+        let mut camera_path = CameraPath::default();
+        camera_path.add_keyframe(CameraData::default());
+        camera_path.add_keyframe(CameraData::default());
+
+        let json: serde_json::Value =
+            serde_json::from_str(&camera_path.to_alembic_json().unwrap()).unwrap();
+        let samples = json["samples"].as_array().unwrap();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[1]["time"], 2.);
+        assert_eq!(samples[1]["frame"], 49);
+        // Default camera sits on the x axis looking at the origin
+        assert_eq!(samples[0]["translation"], serde_json::json!([5., 0., 0.]));
+        assert_eq!(samples[0]["matrix"][0][2], 1.);
+
+        let script = camera_path.to_blender_script();
+        assert!(script.contains("    (49, 90.0, ((0.0, 0.0, 1.0, 5.0), "));
+        assert_eq!(samples[1]["vertical_fov_degrees"], 90.);
+    }
+
+    #[test]
+    fn test_camera_path_orbit() {
+        let mut camera_path = CameraPath::default();
+        camera_path.set_orbit(CameraData::default());
+        assert_eq!(camera_path.keyframes().len(), 13);
+        assert!((camera_path.duration() - 24.).abs() < 1e-4);
+
+        // Halfway through the orbit the camera is on the other side of the focus point
+        let camera_data = camera_path.camera_at(12.).unwrap();
+        assert!((camera_data.angles.0.radians() - PI).abs() < 1e-4);
+        assert!((camera_data.origin_distance - 5.).abs() < 1e-4);
+        assert!(camera_path.camera_at(25.).is_none());
+
+        assert!((angle_difference(6., 0.5) - (0.5 + TAU - 6.)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_camera_path_save_load() {
+        let mut camera_path = CameraPath {
+            frame_rate: 30,
+            easing: KeyframeEasing::EaseOut,
+            ..CameraPath::default()
+        };
+        camera_path.set_orbit(CameraData {
+            vertical_fov: 60.,
+            ..CameraData::default()
+        });
+        camera_path.set_keyframe_time(0, 1.);
+
+        let path = std::env::temp_dir().join("kifs_test_camera_path.kifs.json");
+        camera_path.save(&path).unwrap();
+        // Keyframes moved out of order by hand are sorted again
+        let contents =
+            fs::read_to_string(&path)
+                .unwrap()
+                .replacen("\"time\": 1.0", "\"time\": 100.0", 1);
+        fs::write(&path, contents).unwrap();
+        let loaded = CameraPath::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.frame_rate, 30);
+        assert_eq!(loaded.easing, KeyframeEasing::EaseOut);
+        assert_eq!(loaded.keyframes().len(), camera_path.keyframes().len());
+        assert!((loaded.duration() - 100.).abs() < 1e-4);
+        assert!((loaded.keyframes()[0].time - 2.).abs() < 1e-4);
+        assert!((loaded.keyframes()[0].camera_data.vertical_fov - 60.).abs() < 1e-4);
+
+        assert!(matches!(
+            CameraPath::load(Path::new("missing_camera_path.kifs.json")),
+            Err(CameraPathError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_camera_path_editing() {
+        let mut camera_path = CameraPath::default();
+        for (origin_distance, vertical_fov) in [(3., 90.), (5., 60.), (9., 30.)] {
+            camera_path.add_keyframe(CameraData {
+                origin_distance,
+                vertical_fov,
+                ..CameraData::default()
+            });
+        }
+
+        // Eased paths move slower near the keyframes and reach them all the same
+        let distance_at = |camera_path: &CameraPath, time: f32| {
+            camera_path.camera_at(time).unwrap().origin_distance
+        };
+        assert!((distance_at(&camera_path, 0.5) - 3.5).abs() < 1e-4);
+        camera_path.easing = KeyframeEasing::EaseInOut;
+        assert!(distance_at(&camera_path, 0.5) < 3.5);
+        assert!((distance_at(&camera_path, 1.) - 4.).abs() < 1e-4);
+        assert!((distance_at(&camera_path, 2.) - 5.).abs() < 1e-4);
+        assert!((camera_path.camera_at(3.).unwrap().vertical_fov - 45.).abs() < 1e-4);
+
+        // Keyframes can not be moved past their neighbours
+        camera_path.set_keyframe_time(1, 10.);
+        assert!((camera_path.keyframes()[1].time - 4.).abs() < 1e-4);
+        camera_path.set_keyframe_time(1, 1.);
+        assert!((camera_path.keyframes()[1].time - 1.).abs() < 1e-4);
+
+        camera_path.replace_keyframe(0, CameraData::default());
+        assert!((camera_path.keyframes()[0].camera_data.origin_distance - 5.).abs() < 1e-4);
+        camera_path.remove_keyframe(1);
+        assert_eq!(camera_path.keyframes().len(), 2);
+        assert!((camera_path.duration() - 4.).abs() < 1e-4);
+    }
+}
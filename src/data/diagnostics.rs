@@ -0,0 +1,187 @@
+use std::fmt;
+
+use crate::data::{CameraData, OptionsData, ScreenData};
+
+// Fraction of rays running out of iterations above which we consider the iteration count saturated
+const SATURATION_THRESHOLD: f32 = 0.5;
+
+// Number of rays by the reason their march ended, counted over the last raymarch pass
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct MarchStatistics {
+    pub(crate) max_iterations: u32,
+    pub(crate) max_distance: u32,
+    pub(crate) hits: u32,
+}
+
+impl MarchStatistics {
+    #[must_use]
+    pub(crate) fn saturated_fraction(&self) -> f32 {
+        let total = self.max_iterations + self.max_distance + self.hits;
+        if total == 0 {
+            return 0.;
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let fraction = self.max_iterations as f32 / total as f32;
+        fraction
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ArtifactWarning {
+    EpsilonAbovePixelFootprint {
+        epsilon: f32,
+        pixel_footprint: f32,
+    },
+    CameraInsideMinDistance {
+        origin_distance: f32,
+        min_distance: f32,
+    },
+    MaxDistanceTooShort {
+        origin_distance: f32,
+        max_distance: f32,
+    },
+    IterationsSaturated {
+        saturated_fraction: f32,
+    },
+}
+
+impl fmt::Display for ArtifactWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArtifactWarning::EpsilonAbovePixelFootprint {
+                epsilon,
+                pixel_footprint,
+            } => write!(
+                f,
+                "Epsilon ({epsilon:.1e}) is larger than a pixel at the fractal ({pixel_footprint:.1e}), \
+                 so fine detail gets merged. Lower epsilon below {pixel_footprint:.1e}."
+            ),
+            ArtifactWarning::CameraInsideMinDistance {
+                origin_distance,
+                min_distance,
+            } => write!(
+                f,
+                "Camera distance ({origin_distance:.2}) is below the minimum distance ({min_distance:.2}). \
+                 Zoom out to move the camera back."
+            ),
+            ArtifactWarning::MaxDistanceTooShort {
+                origin_distance,
+                max_distance,
+            } => write!(
+                f,
+                "Max distance ({max_distance:.1}) does not reach the fractal ({origin_distance:.1} away). \
+                 Increase max distance."
+            ),
+            ArtifactWarning::IterationsSaturated { saturated_fraction } => write!(
+                f,
+                "{:.0}% of rays run out of iterations, which shows up as missing or dark surfaces. \
+                 Increase max iterations or epsilon.",
+                100. * saturated_fraction
+            ),
+        }
+    }
+}
+
+// Heuristically detects settings which commonly lead to rendering artifacts
+#[must_use]
+pub(crate) fn detect_artifacts(
+    options_data: &OptionsData,
+    camera_data: CameraData,
+    screen_data: ScreenData,
+    statistics: Option<MarchStatistics>,
+) -> Vec<ArtifactWarning> {
+    let mut warnings = Vec::new();
+    let origin_distance = camera_data.origin_distance;
+
+    // Screen height spans twice the view scale on the image plane at distance 1 from the camera
+    #[allow(clippy::cast_precision_loss)]
+    let pixel_footprint =
+        2. * camera_data.view_scale() * origin_distance / screen_data.height.max(1) as f32;
+    if options_data.epsilon > pixel_footprint {
+        warnings.push(ArtifactWarning::EpsilonAbovePixelFootprint {
+            epsilon: options_data.epsilon,
+            pixel_footprint,
+        });
+    }
+
+    if camera_data.min_distance > origin_distance {
+        warnings.push(ArtifactWarning::CameraInsideMinDistance {
+            origin_distance,
+            min_distance: camera_data.min_distance,
+        });
+    }
+
+    if options_data.max_distance < origin_distance {
+        warnings.push(ArtifactWarning::MaxDistanceTooShort {
+            origin_distance,
+            max_distance: options_data.max_distance,
+        });
+    }
+
+    if let Some(statistics) = statistics {
+        let saturated_fraction = statistics.saturated_fraction();
+        if saturated_fraction > SATURATION_THRESHOLD {
+            warnings.push(ArtifactWarning::IterationsSaturated { saturated_fraction });
+        }
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::GuiData;
+
+    #[test]
+    fn test_detect_artifacts() {
+        let options_data: OptionsData = GuiData::default().into();
+        let camera_data = CameraData::default();
+        let screen_data = ScreenData {
+            width: 800,
+            height: 600,
+        };
+        let statistics = MarchStatistics {
+            max_iterations: 10,
+            max_distance: 50,
+            hits: 40,
+        };
+
+        assert!(
+            detect_artifacts(&options_data, camera_data, screen_data, Some(statistics)).is_empty()
+        );
+
+        let coarse_options_data = OptionsData {
+            epsilon: 0.1,
+            max_distance: 1.,
+            ..options_data
+        };
+        let saturated_statistics = MarchStatistics {
+            max_iterations: 60,
+            max_distance: 0,
+            ..statistics
+        };
+        assert_eq!(
+            detect_artifacts(
+                &coarse_options_data,
+                camera_data,
+                screen_data,
+                Some(saturated_statistics)
+            ),
+            vec![
+                ArtifactWarning::EpsilonAbovePixelFootprint {
+                    epsilon: 0.1,
+                    pixel_footprint: 2. * 5. / 600.,
+                },
+                ArtifactWarning::MaxDistanceTooShort {
+                    origin_distance: 5.,
+                    max_distance: 1.,
+                },
+                ArtifactWarning::IterationsSaturated {
+                    saturated_fraction: 0.6,
+                },
+            ]
+        );
+    }
+}
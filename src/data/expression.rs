@@ -0,0 +1,443 @@
+use std::{f64::consts::PI, iter::Peekable, str::Chars};
+
+use crate::error::ExpressionError;
+
+// Seconds since the bindings started being evaluated
+pub(crate) const TIME_VARIABLE: &str = "t";
+const PI_VARIABLE: &str = "pi";
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    // Parameter paths such as lights[0].azimuth are a single name
+    Name(String),
+    Operator(char),
+    LeftParenthesis,
+    RightParenthesis,
+    Comma,
+}
+
+impl Token {
+    #[must_use]
+    fn text(&self) -> String {
+        match self {
+            Self::Number(number) => number.to_string(),
+            Self::Name(name) => name.clone(),
+            Self::Operator(operator) => operator.to_string(),
+            Self::LeftParenthesis => "(".to_string(),
+            Self::RightParenthesis => ")".to_string(),
+            Self::Comma => ",".to_string(),
+        }
+    }
+}
+
+fn take_while(
+    characters: &mut Peekable<Chars<'_>>,
+    text: &mut String,
+    predicate: fn(char) -> bool,
+) {
+    while let Some(&character) = characters.peek()
+        && predicate(character)
+    {
+        text.push(character);
+        characters.next();
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, ExpressionError> {
+    let mut tokens = Vec::new();
+    let mut characters = source.chars().peekable();
+
+    while let Some(&character) = characters.peek() {
+        match character {
+            _ if character.is_whitespace() => {
+                characters.next();
+            }
+            '0'..='9' | '.' => {
+                let mut text = String::new();
+                take_while(&mut characters, &mut text, |character| {
+                    character.is_ascii_digit() || character == '.'
+                });
+                // Exponent such as the one of 1e-3
+                if let Some(&exponent @ ('e' | 'E')) = characters.peek() {
+                    text.push(exponent);
+                    characters.next();
+                    if let Some(&sign @ ('+' | '-')) = characters.peek() {
+                        text.push(sign);
+                        characters.next();
+                    }
+                    take_while(&mut characters, &mut text, |character| {
+                        character.is_ascii_digit()
+                    });
+                }
+                let number = text
+                    .parse()
+                    .map_err(|_| ExpressionError::UnexpectedToken(text))?;
+                tokens.push(Token::Number(number));
+            }
+            _ if character.is_alphabetic() || character == '_' => {
+                let mut name = String::new();
+                take_while(&mut characters, &mut name, |character| {
+                    character.is_alphanumeric() || matches!(character, '_' | '.' | '[' | ']')
+                });
+                tokens.push(Token::Name(name));
+            }
+            '+' | '-' | '*' | '/' | '%' | '^' => {
+                tokens.push(Token::Operator(character));
+                characters.next();
+            }
+            '(' => {
+                tokens.push(Token::LeftParenthesis);
+                characters.next();
+            }
+            ')' => {
+                tokens.push(Token::RightParenthesis);
+                characters.next();
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                characters.next();
+            }
+            _ => return Err(ExpressionError::UnexpectedCharacter(character)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Abs,
+    Sqrt,
+    Exp,
+    Ln,
+    Floor,
+    Ceil,
+    Fract,
+    Sign,
+    Min,
+    Max,
+    Pow,
+    Clamp,
+    Mix,
+}
+
+impl Function {
+    #[must_use]
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "sin" => Self::Sin,
+            "cos" => Self::Cos,
+            "tan" => Self::Tan,
+            "abs" => Self::Abs,
+            "sqrt" => Self::Sqrt,
+            "exp" => Self::Exp,
+            "ln" => Self::Ln,
+            "floor" => Self::Floor,
+            "ceil" => Self::Ceil,
+            "fract" => Self::Fract,
+            "sign" => Self::Sign,
+            "min" => Self::Min,
+            "max" => Self::Max,
+            "pow" => Self::Pow,
+            "clamp" => Self::Clamp,
+            "mix" => Self::Mix,
+            _ => return None,
+        })
+    }
+
+    #[must_use]
+    fn argument_count(self) -> usize {
+        match self {
+            Self::Min | Self::Max | Self::Pow => 2,
+            Self::Clamp | Self::Mix => 3,
+            _ => 1,
+        }
+    }
+
+    // Arguments were checked to match the argument count when parsing
+    #[must_use]
+    fn apply(self, arguments: &[f64]) -> f64 {
+        match self {
+            Self::Sin => arguments[0].sin(),
+            Self::Cos => arguments[0].cos(),
+            Self::Tan => arguments[0].tan(),
+            Self::Abs => arguments[0].abs(),
+            Self::Sqrt => arguments[0].sqrt(),
+            Self::Exp => arguments[0].exp(),
+            Self::Ln => arguments[0].ln(),
+            Self::Floor => arguments[0].floor(),
+            Self::Ceil => arguments[0].ceil(),
+            Self::Fract => arguments[0] - arguments[0].floor(),
+            Self::Sign => arguments[0].signum(),
+            Self::Min => arguments[0].min(arguments[1]),
+            Self::Max => arguments[0].max(arguments[1]),
+            Self::Pow => arguments[0].powf(arguments[1]),
+            Self::Clamp => arguments[0].max(arguments[1]).min(arguments[2]),
+            Self::Mix => arguments[0] + (arguments[1] - arguments[0]) * arguments[2],
+        }
+    }
+}
+
+/// Arithmetic expression over numeric parameters and the time, such as `2 + sin(t) * 0.5`
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Expression {
+    Number(f64),
+    Variable(String),
+    Negate(Box<Expression>),
+    Binary(char, Box<Expression>, Box<Expression>),
+    Call(Function, Vec<Expression>),
+}
+
+// Recursive descent over the usual precedence, powers bind tighter than negation and are right
+// associative, so -2^2 is -4 and 2^3^2 is 512
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<Token, ExpressionError> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or(ExpressionError::UnexpectedEnd)?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: &Token) -> Result<(), ExpressionError> {
+        let token = self.next()?;
+        if token == *expected {
+            Ok(())
+        } else {
+            Err(ExpressionError::UnexpectedToken(token.text()))
+        }
+    }
+
+    fn binary(
+        &mut self,
+        operators: &[char],
+        operand: fn(&mut Self) -> Result<Expression, ExpressionError>,
+    ) -> Result<Expression, ExpressionError> {
+        let mut expression = operand(self)?;
+        while let Some(&Token::Operator(operator)) = self.peek()
+            && operators.contains(&operator)
+        {
+            self.position += 1;
+            expression =
+                Expression::Binary(operator, Box::new(expression), Box::new(operand(self)?));
+        }
+
+        Ok(expression)
+    }
+
+    fn sum(&mut self) -> Result<Expression, ExpressionError> {
+        self.binary(&['+', '-'], Self::product)
+    }
+
+    fn product(&mut self) -> Result<Expression, ExpressionError> {
+        self.binary(&['*', '/', '%'], Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<Expression, ExpressionError> {
+        match self.peek() {
+            Some(Token::Operator('-')) => {
+                self.position += 1;
+                Ok(Expression::Negate(Box::new(self.unary()?)))
+            }
+            Some(Token::Operator('+')) => {
+                self.position += 1;
+                self.unary()
+            }
+            _ => self.power(),
+        }
+    }
+
+    fn power(&mut self) -> Result<Expression, ExpressionError> {
+        let base = self.primary()?;
+        if self.peek() == Some(&Token::Operator('^')) {
+            self.position += 1;
+            return Ok(Expression::Binary(
+                '^',
+                Box::new(base),
+                Box::new(self.unary()?),
+            ));
+        }
+
+        Ok(base)
+    }
+
+    fn call(&mut self, name: String) -> Result<Expression, ExpressionError> {
+        let function = Function::from_name(&name)
+            .ok_or_else(|| ExpressionError::UnknownFunction(name.clone()))?;
+        self.expect(&Token::LeftParenthesis)?;
+
+        let mut arguments = Vec::new();
+        if self.peek() != Some(&Token::RightParenthesis) {
+            arguments.push(self.sum()?);
+            while self.peek() == Some(&Token::Comma) {
+                self.position += 1;
+                arguments.push(self.sum()?);
+            }
+        }
+        self.expect(&Token::RightParenthesis)?;
+
+        if arguments.len() != function.argument_count() {
+            return Err(ExpressionError::ArgumentCount {
+                function: name,
+                expected: function.argument_count(),
+                found: arguments.len(),
+            });
+        }
+
+        Ok(Expression::Call(function, arguments))
+    }
+
+    fn primary(&mut self) -> Result<Expression, ExpressionError> {
+        match self.next()? {
+            Token::Number(number) => Ok(Expression::Number(number)),
+            Token::Name(name) if self.peek() == Some(&Token::LeftParenthesis) => self.call(name),
+            Token::Name(name) => Ok(Expression::Variable(name)),
+            Token::LeftParenthesis => {
+                let expression = self.sum()?;
+                self.expect(&Token::RightParenthesis)?;
+                Ok(expression)
+            }
+            token => Err(ExpressionError::UnexpectedToken(token.text())),
+        }
+    }
+}
+
+impl Expression {
+    /// ## Errors
+    /// - `ExpressionError` describing the first part of the source which could not be parsed
+    pub(crate) fn parse(source: &str) -> Result<Self, ExpressionError> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            position: 0,
+        };
+        let expression = parser.sum()?;
+        if let Some(token) = parser.peek() {
+            return Err(ExpressionError::UnexpectedToken(token.text()));
+        }
+
+        Ok(expression)
+    }
+
+    // Names of the parameters the expression reads, without the time and constants
+    #[must_use]
+    pub(crate) fn parameters(&self) -> Vec<&str> {
+        match self {
+            Self::Number(_) => Vec::new(),
+            Self::Variable(name) if name == TIME_VARIABLE || name == PI_VARIABLE => Vec::new(),
+            Self::Variable(name) => vec![name.as_str()],
+            Self::Negate(operand) => operand.parameters(),
+            Self::Binary(_, left, right) => {
+                let mut parameters = left.parameters();
+                parameters.extend(right.parameters());
+                parameters
+            }
+            Self::Call(_, arguments) => arguments.iter().flat_map(Self::parameters).collect(),
+        }
+    }
+
+    /// Parameters are looked up by name, the time is given separately
+    ///
+    /// ## Errors
+    /// - `ExpressionError::UnknownParameter` when a parameter could not be looked up
+    pub(crate) fn evaluate(
+        &self,
+        time: f64,
+        parameter: &impl Fn(&str) -> Option<f64>,
+    ) -> Result<f64, ExpressionError> {
+        Ok(match self {
+            Self::Number(number) => *number,
+            Self::Variable(name) if name == TIME_VARIABLE => time,
+            Self::Variable(name) if name == PI_VARIABLE => PI,
+            Self::Variable(name) => {
+                parameter(name).ok_or_else(|| ExpressionError::UnknownParameter(name.clone()))?
+            }
+            Self::Negate(operand) => -operand.evaluate(time, parameter)?,
+            Self::Binary(operator, left, right) => {
+                let left = left.evaluate(time, parameter)?;
+                let right = right.evaluate(time, parameter)?;
+                match operator {
+                    '+' => left + right,
+                    '-' => left - right,
+                    '*' => left * right,
+                    '/' => left / right,
+                    '%' => left.rem_euclid(right),
+                    _ => left.powf(right),
+                }
+            }
+            Self::Call(function, arguments) => {
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| argument.evaluate(time, parameter))
+                    .collect::<Result<Vec<_>, _>>()?;
+                function.apply(&arguments)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expression() {
+        let evaluate = |source: &str| {
+            Expression::parse(source)
+                .unwrap()
+                .evaluate(2., &|name| (name == "lights[0].azimuth").then_some(30.))
+                .unwrap()
+        };
+        let is_close = |left: f64, right: f64| (left - right).abs() < 1e-9;
+
+        assert!(is_close(
+            evaluate("2 + sin(t) * 0.5"),
+            2. + 2_f64.sin() * 0.5
+        ));
+        assert!(is_close(evaluate("-2^2 + 2^3^2"), 508.));
+        assert!(is_close(evaluate("(1 + 2) * 3 % 4"), 1.));
+        assert!(is_close(
+            evaluate("clamp(lights[0].azimuth / 1e1, 0, pi)"),
+            3.
+        ));
+        assert!(is_close(evaluate("mix(1, 3, .5) - -1"), 3.));
+
+        let expression = Expression::parse("power * t + constant[0]").unwrap();
+        assert_eq!(expression.parameters(), ["power", "constant[0]"]);
+        assert!(matches!(
+            expression.evaluate(0., &|_| None),
+            Err(ExpressionError::UnknownParameter(name)) if name == "power"
+        ));
+
+        assert!(matches!(
+            Expression::parse("2 +"),
+            Err(ExpressionError::UnexpectedEnd)
+        ));
+        assert!(matches!(
+            Expression::parse("max(1)"),
+            Err(ExpressionError::ArgumentCount { found: 1, .. })
+        ));
+        assert!(matches!(
+            Expression::parse("foo(1)"),
+            Err(ExpressionError::UnknownFunction(_))
+        ));
+        assert!(matches!(
+            Expression::parse("1 $ 2"),
+            Err(ExpressionError::UnexpectedCharacter('$'))
+        ));
+    }
+}
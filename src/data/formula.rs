@@ -0,0 +1,299 @@
+use std::{collections::HashSet, f64::consts::PI, fmt::Write as _};
+
+use crate::data::expression::{Expression, Function};
+use crate::error::{ExpressionError, FormulaError};
+
+// Mandelbox of scale 2, the box fold followed by the sphere fold
+pub(crate) const DEFAULT_ITERATION: &str = "# Box fold
+x = 2 * clamp(x, -1, 1) - x
+y = 2 * clamp(y, -1, 1) - y
+z = 2 * clamp(z, -1, 1) - z
+# Sphere fold, inverting points inside the unit sphere up to a minimum radius of 0.5
+k = 2 / clamp(r^2, 0.25, 1)
+x = k * x + cx
+y = k * y + cy
+z = k * z + cz
+dr = dr * abs(k) + 1";
+pub(crate) const DEFAULT_DISTANCE: &str = "r / abs(dr)";
+
+// Point being iterated and its running derivative, assigned by the iteration formula
+const POINT_VARIABLES: [&str; 4] = ["x", "y", "z", "dr"];
+// Position the iteration started from and the index of the iteration
+const STEP_VARIABLES: [&str; 4] = ["cx", "cy", "cz", "i"];
+const DISTANCE_VARIABLES: [&str; 3] = ["cx", "cy", "cz"];
+const RADIUS_VARIABLE: &str = "r";
+const PI_VARIABLE: &str = "pi";
+
+#[must_use]
+fn function_name(function: Function) -> &'static str {
+    match function {
+        Function::Sin => "sin",
+        Function::Cos => "cos",
+        Function::Tan => "tan",
+        Function::Abs => "abs",
+        Function::Sqrt => "sqrt",
+        Function::Exp => "exp",
+        Function::Ln => "log",
+        Function::Floor => "floor",
+        Function::Ceil => "ceil",
+        Function::Fract => "fract",
+        Function::Sign => "sign",
+        Function::Min => "min",
+        Function::Max => "max",
+        Function::Pow => "pow",
+        Function::Clamp => "clamp",
+        Function::Mix => "mix",
+    }
+}
+
+// User variables are prefixed, so they can not shadow WGSL keywords or the shader's own names
+#[must_use]
+fn local_name(name: &str) -> String {
+    format!("user_{name}")
+}
+
+// Every operation is parenthesized, so the precedence of the parsed expression carries over as is
+fn write_wgsl(
+    expression: &Expression,
+    variable: &impl Fn(&str) -> Option<String>,
+    wgsl: &mut String,
+) -> Result<(), FormulaError> {
+    match expression {
+        Expression::Number(number) if !number.is_finite() => {
+            return Err(FormulaError::Expression {
+                line: None,
+                error: ExpressionError::NotFinite(number.to_string()),
+            });
+        }
+        // Debug formatting always keeps a decimal point or exponent, so literals stay floats
+        Expression::Number(number) => write!(wgsl, "{number:?}").unwrap_or_default(),
+        Expression::Variable(name) if name == PI_VARIABLE => {
+            write!(wgsl, "{PI:?}").unwrap_or_default();
+        }
+        Expression::Variable(name) if name == RADIUS_VARIABLE => {
+            wgsl.push_str("length(vec3(x, y, z))");
+        }
+        Expression::Variable(name) => {
+            let Some(variable) = variable(name) else {
+                return Err(FormulaError::UnknownVariable {
+                    line: None,
+                    name: name.clone(),
+                });
+            };
+            wgsl.push_str(&variable);
+        }
+        Expression::Negate(operand) => {
+            wgsl.push_str("(-");
+            write_wgsl(operand, variable, wgsl)?;
+            wgsl.push(')');
+        }
+        // Remainder takes the sign of the divisor, like the expressions evaluated on the CPU
+        Expression::Binary('%', left, right) => {
+            let mut left_wgsl = String::new();
+            let mut right_wgsl = String::new();
+            write_wgsl(left, variable, &mut left_wgsl)?;
+            write_wgsl(right, variable, &mut right_wgsl)?;
+            write!(
+                wgsl,
+                "({left_wgsl} - {right_wgsl} * floor({left_wgsl} / {right_wgsl}))"
+            )
+            .unwrap_or_default();
+        }
+        Expression::Binary('^', left, right) => {
+            wgsl.push_str("pow(");
+            write_wgsl(left, variable, wgsl)?;
+            wgsl.push_str(", ");
+            write_wgsl(right, variable, wgsl)?;
+            wgsl.push(')');
+        }
+        Expression::Binary(operator, left, right) => {
+            wgsl.push('(');
+            write_wgsl(left, variable, wgsl)?;
+            write!(wgsl, " {operator} ").unwrap_or_default();
+            write_wgsl(right, variable, wgsl)?;
+            wgsl.push(')');
+        }
+        Expression::Call(function, arguments) => {
+            write!(wgsl, "{}(", function_name(*function)).unwrap_or_default();
+            for (index, argument) in arguments.iter().enumerate() {
+                if index > 0 {
+                    wgsl.push_str(", ");
+                }
+                write_wgsl(argument, variable, wgsl)?;
+            }
+            wgsl.push(')');
+        }
+    }
+
+    Ok(())
+}
+
+// Errors of a single expression are placed on the line it came from
+#[must_use]
+fn with_line(error: FormulaError, line: Option<usize>) -> FormulaError {
+    match error {
+        FormulaError::Expression { error, .. } => FormulaError::Expression { line, error },
+        FormulaError::UnknownVariable { name, .. } => FormulaError::UnknownVariable { line, name },
+        error => error,
+    }
+}
+
+#[must_use]
+fn is_identifier(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && name
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || character == '_')
+}
+
+// Body of the iteration step, one statement per assignment, declaring user variables where they
+// are first assigned
+fn iteration_statements(iteration: &str) -> Result<String, FormulaError> {
+    let mut statements = String::new();
+    let mut locals = HashSet::new();
+
+    for (index, line) in iteration.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((target, source)) = line.split_once('=') else {
+            return Err(FormulaError::Assignment(line_number));
+        };
+        let target = target.trim();
+        if !is_identifier(target) {
+            return Err(FormulaError::Assignment(line_number));
+        }
+        if STEP_VARIABLES.contains(&target) || target == RADIUS_VARIABLE || target == PI_VARIABLE {
+            return Err(FormulaError::ReadOnly {
+                line: line_number,
+                name: target.to_string(),
+            });
+        }
+
+        let expression = Expression::parse(source).map_err(|error| FormulaError::Expression {
+            line: Some(line_number),
+            error,
+        })?;
+        let mut value = String::new();
+        write_wgsl(
+            &expression,
+            &|name| {
+                if POINT_VARIABLES.contains(&name) || STEP_VARIABLES.contains(&name) {
+                    Some(name.to_string())
+                } else {
+                    locals.contains(name).then(|| local_name(name))
+                }
+            },
+            &mut value,
+        )
+        .map_err(|error| with_line(error, Some(line_number)))?;
+
+        if POINT_VARIABLES.contains(&target) {
+            writeln!(statements, "    {target} = {value};").unwrap_or_default();
+        } else if locals.insert(target.to_string()) {
+            writeln!(statements, "    var {} = {value};", local_name(target)).unwrap_or_default();
+        } else {
+            writeln!(statements, "    {} = {value};", local_name(target)).unwrap_or_default();
+        }
+    }
+
+    Ok(statements)
+}
+
+/// Transpiles the iteration formula and distance estimate into the WGSL functions called by
+/// `custom_formula.wgsl`
+///
+/// ## Errors
+/// - `FormulaError` describing the first line which could not be transpiled
+pub(crate) fn transpile(iteration: &str, distance: &str) -> Result<String, FormulaError> {
+    let statements = iteration_statements(iteration)?;
+
+    let distance = Expression::parse(distance)
+        .map_err(|error| FormulaError::Expression { line: None, error })?;
+    let mut estimate = String::new();
+    write_wgsl(
+        &distance,
+        &|name| {
+            (POINT_VARIABLES.contains(&name) || DISTANCE_VARIABLES.contains(&name))
+                .then(|| name.to_string())
+        },
+        &mut estimate,
+    )?;
+
+    Ok(format!(
+        "fn custom_formula_step(point: CustomPoint, c: vec3<f32>, iteration: u32) -> CustomPoint {{
+    var x = point.position.x;
+    var y = point.position.y;
+    var z = point.position.z;
+    var dr = point.derivative;
+    let cx = c.x;
+    let cy = c.y;
+    let cz = c.z;
+    let i = f32(iteration);
+{statements}
+    return CustomPoint(vec3(x, y, z), dr);
+}}
+
+fn custom_formula_distance(point: CustomPoint, c: vec3<f32>) -> f32 {{
+    let x = point.position.x;
+    let y = point.position.y;
+    let z = point.position.z;
+    let dr = point.derivative;
+    let cx = c.x;
+    let cy = c.y;
+    let cz = c.z;
+    return {estimate};
+}}
+"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transpile() {
+        let wgsl = transpile(DEFAULT_ITERATION, DEFAULT_DISTANCE).unwrap();
+        assert!(wgsl.contains("    x = ((2.0 * clamp(x, (-1.0), 1.0)) - x);"));
+        assert!(wgsl.contains(
+            "    var user_k = (2.0 / clamp(pow(length(vec3(x, y, z)), 2.0), 0.25, 1.0));"
+        ));
+        assert!(wgsl.contains("    dr = ((dr * abs(user_k)) + 1.0);"));
+        assert!(wgsl.contains("    return (length(vec3(x, y, z)) / abs(dr));"));
+
+        let wgsl = transpile("a = x % 2\na = ln(a)", "x").unwrap();
+        assert!(wgsl.contains("    var user_a = (x - 2.0 * floor(x / 2.0));"));
+        assert!(wgsl.contains("    user_a = log(user_a);"));
+
+        assert!(matches!(
+            transpile("# comment\n\nx = y +", DEFAULT_DISTANCE),
+            Err(FormulaError::Expression {
+                line: Some(3),
+                error: ExpressionError::UnexpectedEnd
+            })
+        ));
+        assert!(matches!(
+            transpile("x = k\nk = 1", DEFAULT_DISTANCE),
+            Err(FormulaError::UnknownVariable { line: Some(1), name }) if name == "k"
+        ));
+        assert!(matches!(
+            transpile("cx = 1", DEFAULT_DISTANCE),
+            Err(FormulaError::ReadOnly { line: 1, .. })
+        ));
+        assert!(matches!(
+            transpile("x + 1", DEFAULT_DISTANCE),
+            Err(FormulaError::Assignment(1))
+        ));
+        // Iteration variables only exist within a step
+        assert!(matches!(
+            transpile(DEFAULT_ITERATION, "i"),
+            Err(FormulaError::UnknownVariable { line: None, .. })
+        ));
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{
+    data::{
+        GuiData,
+        preset::reset_shape_modifiers,
+        scene::{FractalGroup, HybridFormula, PrimitiveShape},
+    },
+    util::math::{Vector3, Vector4},
+};
+
+// Curated fractal shipped with the application, only the settings of the fractal itself are
+// replaced, so the palette, lighting and camera are kept
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct LibraryPreset {
+    pub(crate) name: &'static str,
+    pub(crate) description: &'static str,
+    configure: fn(&mut GuiData),
+}
+
+impl LibraryPreset {
+    #[must_use]
+    pub(crate) fn apply(&self, settings: &GuiData) -> GuiData {
+        let mut gui_data = *settings;
+        reset_shape_modifiers(&mut gui_data);
+        (self.configure)(&mut gui_data);
+
+        gui_data
+    }
+}
+
+fn quaternion_julia(gui_data: &mut GuiData, constant: Vector4<f32>) {
+    gui_data.fractal_group = FractalGroup::JuliaSet;
+    gui_data.power = 2.;
+    gui_data.constant = constant;
+    gui_data.is_translucent = false;
+}
+
+// Mandelbox alone is the hybrid which only ever folds
+fn mandelbox(gui_data: &mut GuiData, scale: f32) {
+    gui_data.fractal_group = FractalGroup::Hybrid;
+    gui_data.hybrid_formula_a = HybridFormula::MandelboxFold;
+    gui_data.hybrid_formula_b = HybridFormula::MandelboxFold;
+    gui_data.hybrid_pattern = 0;
+    gui_data.hybrid_pattern_length = 1;
+    gui_data.hybrid_iterations = 15;
+    gui_data.hybrid_box_scale = scale;
+}
+
+fn menger_sponge(gui_data: &mut GuiData, iterations: u32, hole_size: f32) {
+    gui_data.fractal_group = FractalGroup::KaleidoscopicIFS;
+    gui_data.primitive_shape = PrimitiveShape::MengerSponge;
+    gui_data.menger_iterations = iterations;
+    gui_data.menger_hole_size = hole_size;
+}
+
+fn sierpinski_tetrahedron(gui_data: &mut GuiData, scale: f32, rotation: Vector3<f32>) {
+    gui_data.fractal_group = FractalGroup::KaleidoscopicIFS;
+    gui_data.primitive_shape = PrimitiveShape::SierpinskiTetrahedron;
+    gui_data.sierpinski_scale = scale;
+    gui_data.sierpinski_offset = Vector3(1., 1., 1.);
+    gui_data.sierpinski_rotation = rotation;
+}
+
+pub(crate) const LIBRARY_PRESETS: [LibraryPreset; 10] = [
+    LibraryPreset {
+        name: "Julia: Dendrite",
+        description: "Branching quaternion Julia set of c = -1 + 0.2i",
+        configure: |gui_data| quaternion_julia(gui_data, Vector4(-1., 0.2, 0., 0.)),
+    },
+    LibraryPreset {
+        name: "Julia: Coral",
+        description: "Quaternion Julia set with bulbous growths on every side",
+        configure: |gui_data| quaternion_julia(gui_data, Vector4(-0.291, -0.399, 0.339, 0.437)),
+    },
+    LibraryPreset {
+        name: "Julia: Twisted shell",
+        description: "Quaternion Julia set whose lobes wind around its axis",
+        configure: |gui_data| quaternion_julia(gui_data, Vector4(-0.2, 0.4, -0.4, -0.4)),
+    },
+    LibraryPreset {
+        name: "Julia: Seahorse tail",
+        description: "Quaternion Julia set of a constant near the seahorse valley",
+        configure: |gui_data| quaternion_julia(gui_data, Vector4(-0.745, 0.113, 0.01, 0.01)),
+    },
+    LibraryPreset {
+        name: "Mandelbox: Classic",
+        description: "Mandelbox of scale 2, a box of cubes folded into each other",
+        configure: |gui_data| mandelbox(gui_data, 2.),
+    },
+    LibraryPreset {
+        name: "Mandelbox: Negative",
+        description: "Mandelbox of scale -1.5, with the organic surface negative scales give",
+        configure: |gui_data| mandelbox(gui_data, -1.5),
+    },
+    LibraryPreset {
+        name: "Mandelbox: Crystal",
+        description: "Mandelbox of scale -2.8, splitting into sharp crystal faces",
+        configure: |gui_data| mandelbox(gui_data, -2.8),
+    },
+    LibraryPreset {
+        name: "KIFS: Menger sponge",
+        description: "Menger sponge with the classic holes of a third",
+        configure: |gui_data| menger_sponge(gui_data, 5, 1. / 3.),
+    },
+    LibraryPreset {
+        name: "KIFS: Lattice sponge",
+        description: "Menger sponge with wide holes, leaving a thin lattice",
+        configure: |gui_data| menger_sponge(gui_data, 4, 0.45),
+    },
+    LibraryPreset {
+        name: "KIFS: Twisted tetrahedron",
+        description: "Sierpinski tetrahedron rotated between every fold",
+        configure: |gui_data| sierpinski_tetrahedron(gui_data, 2., Vector3(12., 0., 24.)),
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_library_presets() {
+        let look = GuiData {
+            fractal_color: [12, 34, 56],
+            ..GuiData::default()
+        };
+        let settings = GuiData {
+            twist_strength: 1.,
+            ..look
+        };
+
+        for (index, preset) in LIBRARY_PRESETS.iter().enumerate() {
+            assert!(
+                LIBRARY_PRESETS[..index]
+                    .iter()
+                    .all(|other| other.name != preset.name)
+            );
+
+            // Same fractal whatever was set before, with the look kept
+            let gui_data = preset.apply(&settings);
+            assert_eq!(
+                serde_json::to_value(gui_data).unwrap(),
+                serde_json::to_value(preset.apply(&look)).unwrap()
+            );
+            assert_eq!(gui_data.fractal_color, look.fractal_color);
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use std::collections::HashMap;
+
+use crate::util::math::Vector3;
+
+// Corners of a cell are numbered by their offsets along x, y and z in bits 0, 1 and 2, the edges
+// by the corners they join, four along every axis in turn with the lower corner first
+const CELL_EDGES: [(usize, usize); 12] = [
+    (0, 1),
+    (2, 3),
+    (4, 5),
+    (6, 7),
+    (0, 2),
+    (1, 3),
+    (4, 6),
+    (5, 7),
+    (0, 4),
+    (1, 5),
+    (2, 6),
+    (3, 7),
+];
+
+// Cube of distance samples centered on the origin, as set in the GUI
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct MeshSettings {
+    // Samples along every side of the cube, the cells between them are one fewer
+    pub(crate) resolution: u32,
+    // Half the side length of the cube
+    pub(crate) extent: f32,
+}
+
+impl Default for MeshSettings {
+    fn default() -> Self {
+        Self {
+            resolution: 128,
+            extent: 2.,
+        }
+    }
+}
+
+impl MeshSettings {
+    pub(crate) const MIN_RESOLUTION: u32 = 2;
+
+    #[must_use]
+    pub(crate) fn sample_count(self) -> usize {
+        (self.resolution as usize).pow(3)
+    }
+
+    // Distance between neighbouring samples
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn spacing(self) -> f32 {
+        2. * self.extent / (self.resolution.max(Self::MIN_RESOLUTION) - 1) as f32
+    }
+
+    // Note: has to match distance_grid_main in distance_grid.wgsl
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn position(self, x: u32, y: u32, z: u32) -> Vector3<f32> {
+        Vector3(x as f32, y as f32, z as f32) * self.spacing()
+            - Vector3(self.extent, self.extent, self.extent)
+    }
+
+    // Samples are stored row by row along x, then slice by slice along z
+    #[must_use]
+    pub(crate) fn index(self, x: u32, y: u32, z: u32) -> usize {
+        let resolution = self.resolution as usize;
+        (z as usize * resolution + y as usize) * resolution + x as usize
+    }
+}
+
+/// Triangles with the vertices they share, wound counterclockwise when seen from outside
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct TriangleMesh {
+    pub(crate) vertices: Vec<Vector3<f32>>,
+    pub(crate) triangles: Vec<[u32; 3]>,
+}
+
+// Distance estimates of the scene sampled on the grid of the settings, negative inside
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct DistanceGrid {
+    pub(crate) settings: MeshSettings,
+    pub(crate) distances: Vec<f32>,
+}
+
+// Index of the cell edge joining the two corners
+#[must_use]
+fn cell_edge(corner: usize, other_corner: usize) -> usize {
+    CELL_EDGES
+        .iter()
+        .position(|edge| *edge == (corner, other_corner) || *edge == (other_corner, corner))
+        .unwrap_or_default()
+}
+
+// Edges crossed by the surface of a cell, in the order they are joined into loops
+//
+// Every face of the cell is walked counterclockwise when seen from outside the cell, joining the
+// edge where the walk enters the inside to the one where it leaves it again. Faces with all four
+// edges crossed are ambiguous and keep their inside corners apart, which only depends on the face
+// itself, so neighbouring cells always agree and the surface has no holes
+#[must_use]
+fn cell_loops(inside_corners: u8) -> Vec<Vec<usize>> {
+    let is_inside = |corner: usize| inside_corners >> corner & 1 == 1;
+    let mut next_edges = [None; 12];
+
+    for axis in 0..3 {
+        let (u, w) = ((axis + 1) % 3, (axis + 2) % 3);
+        for side in 0..2 {
+            // Counterclockwise around the axis, which points outside of the cell on the upper side
+            let mut corners =
+                [(0, 0), (1, 0), (1, 1), (0, 1)].map(|(du, dw)| side << axis | du << u | dw << w);
+            if side == 0 {
+                corners.reverse();
+            }
+
+            let face_edge = |index: usize| cell_edge(corners[index % 4], corners[(index + 1) % 4]);
+            for entry in 0..4 {
+                if is_inside(corners[entry]) || !is_inside(corners[(entry + 1) % 4]) {
+                    continue;
+                }
+                // Closest edge after the entry where the walk leaves the inside again
+                if let Some(exit) = (entry + 1..entry + 4).find(|index| {
+                    is_inside(corners[index % 4]) && !is_inside(corners[(index + 1) % 4])
+                }) {
+                    next_edges[face_edge(entry)] = Some(face_edge(exit));
+                }
+            }
+        }
+    }
+
+    let mut loops = Vec::new();
+    let mut is_visited = [false; 12];
+    for start in 0..12 {
+        if is_visited[start] || next_edges[start].is_none() {
+            continue;
+        }
+
+        let mut edges = Vec::new();
+        let mut edge = start;
+        while !is_visited[edge] {
+            is_visited[edge] = true;
+            edges.push(edge);
+            let Some(next_edge) = next_edges[edge] else {
+                break;
+            };
+            edge = next_edge;
+        }
+        loops.push(edges);
+    }
+
+    loops
+}
+
+impl DistanceGrid {
+    /// Extracts the surface where the distance crosses zero as a closed mesh, shared edges of
+    /// neighbouring cells share their vertex
+    #[must_use]
+    pub(crate) fn marching_cubes(&self) -> TriangleMesh {
+        let settings = self.settings;
+        let cell_cases: Vec<Vec<Vec<usize>>> = (0..=u8::MAX).map(cell_loops).collect();
+        let mut mesh = TriangleMesh::default();
+        // Vertices by the sample at the lower end of their edge and the axis along it
+        let mut edge_vertices: HashMap<(usize, usize), u32> = HashMap::new();
+
+        let cells = settings.resolution.saturating_sub(1);
+        for z in 0..cells {
+            for y in 0..cells {
+                for x in 0..cells {
+                    let corner_sample = |corner: usize| {
+                        let offset = |bit: usize| u32::from(corner >> bit & 1 == 1);
+                        (x + offset(0), y + offset(1), z + offset(2))
+                    };
+                    let distance = |corner: usize| {
+                        let (x, y, z) = corner_sample(corner);
+                        self.distances[settings.index(x, y, z)]
+                    };
+
+                    // Samples which are not a number count as outside
+                    let inside_corners = (0..8)
+                        .filter(|corner| distance(*corner) < 0.)
+                        .fold(0, |inside_corners, corner| inside_corners | 1 << corner);
+                    let case = &cell_cases[inside_corners];
+                    if case.is_empty() {
+                        continue;
+                    }
+
+                    let mut edge_vertex = |edge: usize| {
+                        let (corner, other_corner) = CELL_EDGES[edge];
+                        let (sample_x, sample_y, sample_z) = corner_sample(corner);
+                        let key = (settings.index(sample_x, sample_y, sample_z), edge / 4);
+                        *edge_vertices.entry(key).or_insert_with(|| {
+                            let (distance, other_distance) =
+                                (distance(corner), distance(other_corner));
+                            let fraction = distance / (distance - other_distance);
+                            let fraction = if fraction.is_finite() {
+                                fraction.clamp(0., 1.)
+                            } else {
+                                0.5
+                            };
+                            let (other_x, other_y, other_z) = corner_sample(other_corner);
+                            let position = settings.position(sample_x, sample_y, sample_z);
+                            let other_position = settings.position(other_x, other_y, other_z);
+
+                            #[allow(clippy::cast_possible_truncation)]
+                            let vertex = mesh.vertices.len() as u32;
+                            mesh.vertices
+                                .push(position + (other_position - position) * fraction);
+                            vertex
+                        })
+                    };
+
+                    for edges in case {
+                        let vertices: Vec<u32> =
+                            edges.iter().map(|edge| edge_vertex(*edge)).collect();
+                        // Fans keep the winding of the loop
+                        for index in 1..vertices.len().saturating_sub(1) {
+                            mesh.triangles.push([
+                                vertices[0],
+                                vertices[index],
+                                vertices[index + 1],
+                            ]);
+                        }
+                    }
+                }
+            }
+        }
+
+        mesh
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marching_cubes() {
+        let settings = MeshSettings {
+            resolution: 24,
+            extent: 1.,
+        };
+        let radius = 0.7;
+        let mut distances = vec![0.; settings.sample_count()];
+        for z in 0..settings.resolution {
+            for y in 0..settings.resolution {
+                for x in 0..settings.resolution {
+                    distances[settings.index(x, y, z)] =
+                        settings.position(x, y, z).length() - radius;
+                }
+            }
+        }
+        let mesh = DistanceGrid {
+            settings,
+            distances,
+        }
+        .marching_cubes();
+        assert!(!mesh.triangles.is_empty());
+
+        // Vertices lie on the sphere, up to the error of interpolating its distance linearly
+        for vertex in &mesh.vertices {
+            assert!((vertex.length() - radius).abs() < 0.1 * settings.spacing());
+        }
+
+        // Closed surface, every edge is shared by exactly two triangles running along it in
+        // opposite directions
+        let mut edges = HashMap::new();
+        for triangle in &mesh.triangles {
+            for index in 0..3 {
+                *edges
+                    .entry((triangle[index], triangle[(index + 1) % 3]))
+                    .or_insert(0) += 1;
+            }
+        }
+        for ((start, end), count) in &edges {
+            assert_eq!(*count, 1);
+            assert_eq!(edges.get(&(*end, *start)), Some(&1));
+        }
+
+        // Normals by the winding point away from the center
+        for triangle in &mesh.triangles {
+            let [first, second, third] = triangle.map(|vertex| mesh.vertices[vertex as usize]);
+            let (u, v) = (second - first, third - first);
+            let normal = Vector3(
+                u.1 * v.2 - u.2 * v.1,
+                u.2 * v.0 - u.0 * v.2,
+                u.0 * v.1 - u.1 * v.0,
+            );
+            assert!(normal * (first + second + third) > 0.);
+        }
+
+        // Grids without a surface give an empty mesh
+        let empty = DistanceGrid {
+            settings,
+            distances: vec![1.; settings.sample_count()],
+        };
+        assert_eq!(empty.marching_cubes(), TriangleMesh::default());
+    }
+}
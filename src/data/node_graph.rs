@@ -0,0 +1,333 @@
+use std::fmt::{self, Write as _};
+
+use crate::data::options::WarpAxis;
+use crate::error::NodeGraphError;
+use crate::util::math::{Radians, Vector3};
+
+// Shape measured by the output node of the graph
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) enum NodeShape {
+    #[default]
+    Tetrahedron,
+    Sphere,
+    Cube,
+}
+
+impl NodeShape {
+    pub(crate) const ALL: [Self; 3] = [Self::Tetrahedron, Self::Sphere, Self::Cube];
+
+    // Distance to the shape of the given size around the origin, before undoing the scaling
+    #[must_use]
+    fn distance(self, size: f32) -> String {
+        match self {
+            Self::Tetrahedron => format!(
+                "(max(max(-x - y - z, x + y - z), max(-x + y + z, x - y + z)) - ({size})) / sqrt(3)"
+            ),
+            Self::Sphere => format!("r - ({size})"),
+            Self::Cube => format!("max(max(abs(x), abs(y)), abs(z)) - ({size})"),
+        }
+    }
+}
+
+impl fmt::Display for NodeShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tetrahedron => write!(f, "Tetrahedron"),
+            Self::Sphere => write!(f, "Sphere"),
+            Self::Cube => write!(f, "Cube"),
+        }
+    }
+}
+
+/// Operation of a node on the point flowing through it, applied once per iteration
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum NodeKind {
+    // Start of every iteration
+    Position,
+    AbsFold,
+    // Mirrors the points behind the plane through the origin to its front
+    PlaneFold { normal: Vector3<f32> },
+    Rotate { axis: WarpAxis, degrees: f32 },
+    // Scales the space around the center, the usual last step of a kaleidoscopic IFS
+    Scale { factor: f32, center: Vector3<f32> },
+    Translate { offset: Vector3<f32> },
+    // End of the graph, measured once all iterations are done
+    Primitive { shape: NodeShape, size: f32 },
+}
+
+impl NodeKind {
+    // Kinds with their default parameters, as offered for new nodes
+    pub(crate) const ALL: [Self; 7] = [
+        Self::Position,
+        Self::AbsFold,
+        Self::PlaneFold {
+            normal: Vector3(1., 1., 0.),
+        },
+        Self::Rotate {
+            axis: WarpAxis::Z,
+            degrees: 0.,
+        },
+        Self::Scale {
+            factor: 2.,
+            center: Vector3(1., 1., 1.),
+        },
+        Self::Translate {
+            offset: Vector3(0., 0., 0.),
+        },
+        Self::Primitive {
+            shape: NodeShape::Tetrahedron,
+            size: 1.,
+        },
+    ];
+
+    // Formula lines of the node, its own variables are suffixed by the node index to stay apart
+    fn write_formula(self, index: usize, formula: &mut String) {
+        match self {
+            Self::Position | Self::Primitive { .. } => {}
+            Self::AbsFold => formula.push_str("x = abs(x)\ny = abs(y)\nz = abs(z)\n"),
+            // Without a normal there is no plane to fold along
+            Self::PlaneFold { normal } if normal.length() == 0. => {}
+            Self::PlaneFold { normal } => {
+                let Vector3(nx, ny, nz) = normal.normalize();
+                writeln!(
+                    formula,
+                    "fold{index} = 2 * min(({nx}) * x + ({ny}) * y + ({nz}) * z, 0)\n\
+                    x = x - fold{index} * ({nx})\n\
+                    y = y - fold{index} * ({ny})\n\
+                    z = z - fold{index} * ({nz})"
+                )
+                .unwrap_or_default();
+            }
+            Self::Rotate { axis, degrees } => {
+                let angle = Radians::from_degrees(degrees);
+                let (cos, sin) = (angle.cos(), angle.sin());
+                let (u, v) = match axis {
+                    WarpAxis::X => ("y", "z"),
+                    WarpAxis::Y => ("z", "x"),
+                    WarpAxis::Z => ("x", "y"),
+                };
+                writeln!(
+                    formula,
+                    "rotated{index} = ({cos}) * {u} - ({sin}) * {v}\n\
+                    {v} = ({sin}) * {u} + ({cos}) * {v}\n\
+                    {u} = rotated{index}"
+                )
+                .unwrap_or_default();
+            }
+            Self::Scale { factor, center } => {
+                let Vector3(cx, cy, cz) = center;
+                writeln!(
+                    formula,
+                    "x = ({factor}) * x - ({cx}) * (({factor}) - 1)\n\
+                    y = ({factor}) * y - ({cy}) * (({factor}) - 1)\n\
+                    z = ({factor}) * z - ({cz}) * (({factor}) - 1)\n\
+                    dr = dr * abs({factor})"
+                )
+                .unwrap_or_default();
+            }
+            Self::Translate { offset } => {
+                let Vector3(ox, oy, oz) = offset;
+                writeln!(formula, "x = x + ({ox})\ny = y + ({oy})\nz = z + ({oz})")
+                    .unwrap_or_default();
+            }
+        }
+    }
+}
+
+impl fmt::Display for NodeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Position => write!(f, "Position"),
+            Self::AbsFold => write!(f, "Absolute fold"),
+            Self::PlaneFold { .. } => write!(f, "Plane fold"),
+            Self::Rotate { .. } => write!(f, "Rotate"),
+            Self::Scale { .. } => write!(f, "Scale"),
+            Self::Translate { .. } => write!(f, "Translate"),
+            Self::Primitive { .. } => write!(f, "Primitive"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct FractalNode {
+    pub(crate) kind: NodeKind,
+    // Index of the node whose output flows into this one
+    pub(crate) input: Option<usize>,
+}
+
+/// Nodes wired from a position node through folds and transforms into a primitive, compiled into
+/// the formula of the custom formula group
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct NodeGraph {
+    pub(crate) nodes: Vec<FractalNode>,
+}
+
+impl Default for NodeGraph {
+    // Sierpinski tetrahedron, folded along the three planes between the axes and scaled by 2
+    fn default() -> Self {
+        let kinds = [
+            NodeKind::Position,
+            NodeKind::PlaneFold {
+                normal: Vector3(1., 1., 0.),
+            },
+            NodeKind::PlaneFold {
+                normal: Vector3(1., 0., 1.),
+            },
+            NodeKind::PlaneFold {
+                normal: Vector3(0., 1., 1.),
+            },
+            NodeKind::Scale {
+                factor: 2.,
+                center: Vector3(1., 1., 1.),
+            },
+            NodeKind::Primitive {
+                shape: NodeShape::Tetrahedron,
+                size: 1.,
+            },
+        ];
+
+        Self {
+            nodes: kinds
+                .into_iter()
+                .enumerate()
+                .map(|(index, kind)| FractalNode {
+                    kind,
+                    input: index.checked_sub(1),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl NodeGraph {
+    // Removes the node, disconnecting the nodes it was wired into and shifting later indices
+    pub(crate) fn remove(&mut self, index: usize) {
+        if index >= self.nodes.len() {
+            return;
+        }
+
+        self.nodes.remove(index);
+        for node in &mut self.nodes {
+            node.input = match node.input {
+                Some(input) if input == index => None,
+                Some(input) if input > index => Some(input - 1),
+                input => input,
+            };
+        }
+    }
+
+    // Nodes from the position to the primitive, following the inputs back from the primitive
+    fn chain(&self) -> Result<Vec<usize>, NodeGraphError> {
+        let mut outputs = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| matches!(node.kind, NodeKind::Primitive { .. }))
+            .map(|(index, _)| index);
+        let output = outputs.next().ok_or(NodeGraphError::MissingOutput)?;
+        if outputs.next().is_some() {
+            return Err(NodeGraphError::MultipleOutputs);
+        }
+
+        let mut chain = vec![output];
+        let mut index = output;
+        while self.nodes[index].kind != NodeKind::Position {
+            index = self.nodes[index]
+                .input
+                .filter(|input| *input < self.nodes.len())
+                .ok_or(NodeGraphError::Unconnected(index))?;
+            // Every node can appear once, any longer chain went around a cycle
+            if chain.len() >= self.nodes.len() {
+                return Err(NodeGraphError::Cycle);
+            }
+            chain.push(index);
+        }
+        chain.reverse();
+
+        Ok(chain)
+    }
+
+    /// Compiles the graph into the iteration formula and distance estimate of the formula window
+    ///
+    /// ## Errors
+    /// - `NodeGraphError` when the primitive is not wired back to a position node
+    pub(crate) fn compile(&self) -> Result<(String, String), NodeGraphError> {
+        let chain = self.chain()?;
+
+        let mut iteration = String::new();
+        for index in &chain {
+            let node = self.nodes[*index];
+            writeln!(iteration, "# {} node {index}", node.kind).unwrap_or_default();
+            node.kind.write_formula(*index, &mut iteration);
+        }
+
+        let distance = chain
+            .last()
+            .and_then(|index| match self.nodes[*index].kind {
+                NodeKind::Primitive { shape, size } => {
+                    Some(format!("({}) / abs(dr)", shape.distance(size)))
+                }
+                _ => None,
+            })
+            .ok_or(NodeGraphError::MissingOutput)?;
+
+        Ok((iteration, distance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::formula::transpile;
+
+    #[test]
+    fn test_node_graph() {
+        let mut graph = NodeGraph::default();
+        let (iteration, distance) = graph.compile().unwrap();
+        assert!(iteration.contains("fold1 = 2 * min("));
+        assert!(iteration.contains("dr = dr * abs(2)"));
+        transpile(&iteration, &distance).unwrap();
+
+        // Every kind of node compiles into a valid formula between a position and a primitive
+        for kind in &NodeKind::ALL[1..NodeKind::ALL.len() - 1] {
+            let single = NodeGraph {
+                nodes: vec![
+                    FractalNode {
+                        kind: NodeKind::Position,
+                        input: None,
+                    },
+                    FractalNode {
+                        kind: *kind,
+                        input: Some(0),
+                    },
+                    FractalNode {
+                        kind: NodeKind::Primitive {
+                            shape: NodeShape::Sphere,
+                            size: 0.5,
+                        },
+                        input: Some(1),
+                    },
+                ],
+            };
+            let (iteration, distance) = single.compile().unwrap();
+            transpile(&iteration, &distance).unwrap();
+        }
+
+        // Removing a node in the middle disconnects the chain
+        graph.remove(2);
+        assert!(matches!(
+            graph.compile(),
+            Err(NodeGraphError::Unconnected(2))
+        ));
+
+        graph.nodes[1].input = Some(2);
+        graph.nodes[2].input = Some(1);
+        assert!(matches!(graph.compile(), Err(NodeGraphError::Cycle)));
+
+        graph.remove(4);
+        assert!(matches!(
+            graph.compile(),
+            Err(NodeGraphError::MissingOutput)
+        ));
+    }
+}
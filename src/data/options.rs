@@ -0,0 +1,628 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use strum_macros::{EnumIter, FromRepr};
+
+// Note: declared as COLORING_MODE_* in the shader constants
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum ColoringMode {
+    #[default]
+    Solid = 0,
+    Heatmap = 1,
+    Normal = 2,
+}
+
+impl ColoringMode {
+    #[must_use]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        ColoringMode::from_repr(id)
+    }
+}
+
+impl fmt::Display for ColoringMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColoringMode::Solid => write!(f, "Solid"),
+            ColoringMode::Heatmap => write!(f, "Heatmap"),
+            ColoringMode::Normal => write!(f, "Normal"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum RegionOfInterest {
+    #[default]
+    Off = 0,
+    Cursor = 1,
+    Drawn = 2,
+}
+
+impl RegionOfInterest {
+    #[must_use]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        RegionOfInterest::from_repr(id)
+    }
+}
+
+impl fmt::Display for RegionOfInterest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegionOfInterest::Off => write!(f, "Off"),
+            RegionOfInterest::Cursor => write!(f, "Around cursor"),
+            RegionOfInterest::Drawn => write!(f, "Drawn region"),
+        }
+    }
+}
+
+// Note: declared as DEBUG_VIEW_* in the shader constants
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum DebugView {
+    #[default]
+    Off = 0,
+    DistanceField = 1,
+    StepCount = 2,
+    Overstep = 3,
+    Bailout = 4,
+    FinalDistance = 5,
+    RayLength = 6,
+    BoundingTests = 7,
+}
+
+impl DebugView {
+    #[must_use]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        DebugView::from_repr(id)
+    }
+}
+
+impl fmt::Display for DebugView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DebugView::Off => write!(f, "Off"),
+            DebugView::DistanceField => write!(f, "Distance field"),
+            DebugView::StepCount => write!(f, "Step count"),
+            DebugView::Overstep => write!(f, "Overstep corrections"),
+            DebugView::Bailout => write!(f, "Bailout reason"),
+            DebugView::FinalDistance => write!(f, "Final distance"),
+            DebugView::RayLength => write!(f, "Ray length"),
+            DebugView::BoundingTests => write!(f, "Bounding tests"),
+        }
+    }
+}
+
+// Parameter varied along one axis of the parameter space preview
+// Note: declared as PARAMETER_AXIS_* in the shader constants
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum ParameterAxis {
+    #[default]
+    ConstantReal = 0,
+    ConstantI = 1,
+    ConstantJ = 2,
+    ConstantK = 3,
+    Power = 4,
+}
+
+impl ParameterAxis {
+    #[must_use]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        ParameterAxis::from_repr(id)
+    }
+}
+
+impl fmt::Display for ParameterAxis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterAxis::ConstantReal => write!(f, "Constant (real)"),
+            ParameterAxis::ConstantI => write!(f, "Constant (i)"),
+            ParameterAxis::ConstantJ => write!(f, "Constant (j)"),
+            ParameterAxis::ConstantK => write!(f, "Constant (k)"),
+            ParameterAxis::Power => write!(f, "Power"),
+        }
+    }
+}
+
+// How rays are turned into colors, chosen separately for every fractal group
+// Note: declared as RENDER_MODE_* in the shader constants
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum RenderMode {
+    #[default]
+    Surface = 0,
+    Volumetric = 1,
+}
+
+impl RenderMode {
+    #[must_use]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        RenderMode::from_repr(id)
+    }
+}
+
+impl fmt::Display for RenderMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderMode::Surface => write!(f, "Surface"),
+            RenderMode::Volumetric => write!(f, "Volumetric density"),
+        }
+    }
+}
+
+// Auxiliary layer rendered alongside the color, see AovState
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum AovLayer {
+    #[default]
+    Depth = 0,
+    Normal = 1,
+    Iterations = 2,
+}
+
+impl AovLayer {
+    #[must_use]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        AovLayer::from_repr(id)
+    }
+}
+
+impl fmt::Display for AovLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AovLayer::Depth => write!(f, "Linear depth"),
+            AovLayer::Normal => write!(f, "World normal"),
+            AovLayer::Iterations => write!(f, "Iteration count"),
+        }
+    }
+}
+
+// Note: declared as FLOOR_PATTERN_* in the shader constants
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum FloorPattern {
+    #[default]
+    Plain = 0,
+    Checkerboard = 1,
+}
+
+impl FloorPattern {
+    #[must_use]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        FloorPattern::from_repr(id)
+    }
+}
+
+impl fmt::Display for FloorPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FloorPattern::Plain => write!(f, "Plain"),
+            FloorPattern::Checkerboard => write!(f, "Checkerboard"),
+        }
+    }
+}
+
+// Expensive shading terms an object of the scene receives, so a heavy fractal can keep them while
+// helper geometry stays cheap
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObjectShading {
+    pub is_soft_shadows: bool,
+    pub is_ambient_occlusion: bool,
+}
+
+impl ObjectShading {
+    pub(crate) const FULL: Self = Self {
+        is_soft_shadows: true,
+        is_ambient_occlusion: true,
+    };
+
+    // Note: declared as SHADING_* in the shader constants
+    #[must_use]
+    pub(crate) fn bits(self) -> u32 {
+        u32::from(self.is_soft_shadows) | (u32::from(self.is_ambient_occlusion) << 1)
+    }
+}
+
+// Planes the scene is mirrored about, the diagonal ones pass through two axes at 45 degrees
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct MirrorPlanes {
+    pub is_x: bool,
+    pub is_y: bool,
+    pub is_z: bool,
+    pub is_xy: bool,
+    pub is_yz: bool,
+    pub is_xz: bool,
+}
+
+impl MirrorPlanes {
+    // Planes in the order of their bits, named by the axes they are perpendicular to or pass through
+    pub(crate) const NAMES: [&str; 6] = ["X", "Y", "Z", "XY", "YZ", "XZ"];
+
+    #[must_use]
+    pub(crate) fn from_flags(flags: [bool; 6]) -> Self {
+        Self {
+            is_x: flags[0],
+            is_y: flags[1],
+            is_z: flags[2],
+            is_xy: flags[3],
+            is_yz: flags[4],
+            is_xz: flags[5],
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn flags(self) -> [bool; 6] {
+        [
+            self.is_x, self.is_y, self.is_z, self.is_xy, self.is_yz, self.is_xz,
+        ]
+    }
+
+    // Note: declared as MIRROR_* in the shader constants
+    #[must_use]
+    pub(crate) fn bits(self) -> u32 {
+        self.flags()
+            .into_iter()
+            .enumerate()
+            .map(|(bit, is_enabled)| u32::from(is_enabled) << bit)
+            .sum()
+    }
+}
+
+// Note: declared as PROJECTION_* in the shader constants
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum Projection {
+    #[default]
+    Perspective = 0,
+    // Full sphere around the camera, longitude along the width and latitude along the height
+    Equirectangular = 1,
+}
+
+impl Projection {
+    #[must_use]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        Projection::from_repr(id)
+    }
+}
+
+impl fmt::Display for Projection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Projection::Perspective => write!(f, "Perspective"),
+            Projection::Equirectangular => write!(f, "Equirectangular (360°)"),
+        }
+    }
+}
+
+// Orbit cameras circle the pivot and look at it, free-fly cameras move and turn on their own
+#[derive(Clone, Copy, Debug, Default, PartialEq, EnumIter, Serialize, Deserialize)]
+pub enum CameraMode {
+    #[default]
+    Orbit,
+    FreeFly,
+}
+
+impl fmt::Display for CameraMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CameraMode::Orbit => write!(f, "Orbit"),
+            CameraMode::FreeFly => write!(f, "Free-fly"),
+        }
+    }
+}
+
+// Note: declared as STEREO_MODE_* in the shader constants
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum StereoMode {
+    #[default]
+    Off = 0,
+    // Left eye in the red channel and right eye in green and blue, for red/cyan glasses
+    Anaglyph = 1,
+    // Left eye in the left half of the screen, right eye in the right half
+    SideBySide = 2,
+}
+
+impl StereoMode {
+    #[must_use]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        StereoMode::from_repr(id)
+    }
+}
+
+impl fmt::Display for StereoMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StereoMode::Off => write!(f, "Off"),
+            StereoMode::Anaglyph => write!(f, "Anaglyph"),
+            StereoMode::SideBySide => write!(f, "Side by side"),
+        }
+    }
+}
+
+// Note: declared as TRANSFER_FUNCTION_* in the shader constants
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum TransferFunction {
+    // Piecewise sRGB curve, matching what sRGB surfaces and image viewers expect
+    #[default]
+    Srgb = 0,
+    // Plain power curve with an adjustable gamma
+    Gamma = 1,
+    // Linear values written out unchanged, only useful for debugging the tone of the image
+    Linear = 2,
+}
+
+impl TransferFunction {
+    #[must_use]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        TransferFunction::from_repr(id)
+    }
+}
+
+impl fmt::Display for TransferFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransferFunction::Srgb => write!(f, "sRGB"),
+            TransferFunction::Gamma => write!(f, "Gamma"),
+            TransferFunction::Linear => write!(f, "Linear"),
+        }
+    }
+}
+
+// Note: declared as UPSCALE_FILTER_* in the shader constants
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum UpscaleFilter {
+    #[default]
+    Nearest = 0,
+    Bilinear = 1,
+    // Catmull-Rom spline over the 4x4 surrounding texels
+    Bicubic = 2,
+    // Nearest texel sharpened against its neighbours, limited to their range like FSR's RCAS
+    Sharpen = 3,
+}
+
+impl UpscaleFilter {
+    #[must_use]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        UpscaleFilter::from_repr(id)
+    }
+}
+
+impl fmt::Display for UpscaleFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpscaleFilter::Nearest => write!(f, "Nearest"),
+            UpscaleFilter::Bilinear => write!(f, "Bilinear"),
+            UpscaleFilter::Bicubic => write!(f, "Bicubic"),
+            UpscaleFilter::Sharpen => write!(f, "Sharpen"),
+        }
+    }
+}
+
+// Boolean operation combining the fractal with the CSG shape
+// Note: declared as CSG_* in the shader constants
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum CsgOperation {
+    #[default]
+    Off = 0,
+    Union = 1,
+    Intersection = 2,
+    // Shape carved out of the fractal
+    Subtraction = 3,
+}
+
+impl CsgOperation {
+    #[must_use]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        CsgOperation::from_repr(id)
+    }
+}
+
+impl fmt::Display for CsgOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsgOperation::Off => write!(f, "Off"),
+            CsgOperation::Union => write!(f, "Union"),
+            CsgOperation::Intersection => write!(f, "Intersection"),
+            CsgOperation::Subtraction => write!(f, "Subtraction"),
+        }
+    }
+}
+
+// Note: declared as CSG_SHAPE_* in the shader constants
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum CsgShape {
+    #[default]
+    Sphere = 0,
+    Box = 1,
+    Cylinder = 2,
+    Torus = 3,
+}
+
+impl CsgShape {
+    #[must_use]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        CsgShape::from_repr(id)
+    }
+}
+
+impl fmt::Display for CsgShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsgShape::Sphere => write!(f, "Sphere"),
+            CsgShape::Box => write!(f, "Box"),
+            CsgShape::Cylinder => write!(f, "Cylinder"),
+            CsgShape::Torus => write!(f, "Torus"),
+        }
+    }
+}
+
+// Axis the twist domain warp rotates about
+// Note: declared as WARP_AXIS_* in the shader constants
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum WarpAxis {
+    X = 0,
+    Y = 1,
+    #[default]
+    Z = 2,
+}
+
+impl WarpAxis {
+    #[must_use]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        WarpAxis::from_repr(id)
+    }
+}
+
+impl fmt::Display for WarpAxis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WarpAxis::X => write!(f, "X"),
+            WarpAxis::Y => write!(f, "Y"),
+            WarpAxis::Z => write!(f, "Z"),
+        }
+    }
+}
+
+// How much of the distance estimate the march steps at once, estimates which are not true distance
+// bounds overshoot thin features at full length and leave holes
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum DistanceClamping {
+    // Full estimate
+    Off = 0,
+    // Fudge factor of the fractal group, see FractalGroup::distance_fudge
+    #[default]
+    FractalDefault = 1,
+    Custom = 2,
+}
+
+impl DistanceClamping {
+    #[must_use]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        DistanceClamping::from_repr(id)
+    }
+}
+
+impl fmt::Display for DistanceClamping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DistanceClamping::Off => write!(f, "Off"),
+            DistanceClamping::FractalDefault => write!(f, "Fractal default"),
+            DistanceClamping::Custom => write!(f, "Custom"),
+        }
+    }
+}
+
+// Side of an axis the cut away octant lies on, octants open along an unrestricted axis become
+// quadrants
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum CutawaySide {
+    #[default]
+    Any = 0,
+    Positive = 1,
+    Negative = 2,
+}
+
+impl CutawaySide {
+    #[must_use]
+    pub(crate) fn sign(self) -> f32 {
+        match self {
+            CutawaySide::Any => 0.,
+            CutawaySide::Positive => 1.,
+            CutawaySide::Negative => -1.,
+        }
+    }
+}
+
+impl fmt::Display for CutawaySide {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CutawaySide::Any => write!(f, "Any"),
+            CutawaySide::Positive => write!(f, "Positive"),
+            CutawaySide::Negative => write!(f, "Negative"),
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use std::f32::consts::TAU;
+
+use crate::{
+    data::{
+        CameraData, GuiData, Light,
+        options::{ColoringMode, DebugView},
+        scene::{ConstantAnimation, FractalGroup, JuliaFormula, PrimitiveShape},
+    },
+    util::math::{Radians, Vector2, Vector4},
+};
+
+// Presets are shared as kifs://seed/<seed>
+pub(crate) const SEED_LINK_PREFIX: &str = "kifs://seed/";
+// Fixed rather than every group and shape, so shared links keep their preset when more are added
+const SEEDED_GROUPS: [FractalGroup; 3] = [
+    FractalGroup::KaleidoscopicIFS,
+    FractalGroup::JuliaSet,
+    FractalGroup::GeneralizedJuliaSet,
+];
+const SEEDED_SHAPES: [PrimitiveShape; 7] = [
+    PrimitiveShape::Sphere,
+    PrimitiveShape::Cylinder,
+    PrimitiveShape::Box,
+    PrimitiveShape::Torus,
+    PrimitiveShape::SierpinskiTetrahedron,
+    PrimitiveShape::Bunny,
+    PrimitiveShape::MengerSponge,
+];
+
+// Seed of a shared link, or the text itself when it is a plain seed
+#[must_use]
+pub(crate) fn parse_seed(text: &str) -> &str {
+    let text = text.trim();
+    text.strip_prefix(SEED_LINK_PREFIX).unwrap_or(text)
+}
+
+#[must_use]
+pub(crate) fn seed_link(seed: &str) -> String {
+    format!("{SEED_LINK_PREFIX}{}", parse_seed(seed))
+}
+
+// Hand written generator instead of a library one, so a seed gives the same preset on every
+// platform and in every later version of the application
+#[derive(Clone, Copy, Debug)]
+struct SeedRng(u64);
+
+impl SeedRng {
+    // FNV-1a hash of the seed
+    #[must_use]
+    fn new(seed: &str) -> Self {
+        let state = seed.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+        Self(state)
+    }
+
+    // SplitMix64 step
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut value = self.0;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        value ^ (value >> 31)
+    }
+
+    // Uniform on [0, 1), using the 24 bits an f32 can represent exactly
+    #[allow(clippy::cast_precision_loss)]
+    fn unit(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1 << 24) as f32
+    }
+
+    fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.unit()
+    }
+
+    fn chance(&mut self, probability: f32) -> bool {
+        self.unit() < probability
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn pick<T: Copy>(&mut self, values: &[T]) -> T {
+        values[(self.next_u64() % values.len() as u64) as usize]
+    }
+}
+
+// Hue, saturation and value on [0, 1]
+#[must_use]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn hsv_to_srgb(hue: f32, saturation: f32, value: f32) -> [u8; 3] {
+    let channel = |offset: f32| {
+        let k = (offset + hue.rem_euclid(1.) * 6.) % 6.;
+        let amount = (k.min(4. - k)).clamp(0., 1.);
+        ((value - value * saturation * amount) * 255.).round() as u8
+    };
+
+    [channel(5.), channel(3.), channel(1.)]
+}
+
+// Settings which change the shape of every fractal, reset so a preset looks the same whatever was
+// set before it
+pub(crate) fn reset_shape_modifiers(gui_data: &mut GuiData) {
+    let default_data = GuiData::default();
+    gui_data.julia_formula = JuliaFormula::Standard;
+    gui_data.julia_abs_folds = [false; 4];
+    gui_data.bailout_radius = default_data.bailout_radius;
+    gui_data.constant_animation = ConstantAnimation::Off;
+    gui_data.hyperplane_offset = default_data.hyperplane_offset;
+    gui_data.hyperplane_rotation = default_data.hyperplane_rotation;
+    gui_data.csg_operation = default_data.csg_operation;
+    gui_data.twist_strength = default_data.twist_strength;
+    gui_data.bend_strength = default_data.bend_strength;
+    gui_data.mirror_planes = default_data.mirror_planes;
+    gui_data.scene_objects = default_data.scene_objects;
+}
+
+fn generate_fractal(rng: &mut SeedRng, gui_data: &mut GuiData) {
+    gui_data.fractal_group = rng.pick(&SEEDED_GROUPS);
+    reset_shape_modifiers(gui_data);
+    let default_data = GuiData::default();
+
+    if gui_data.fractal_group == FractalGroup::KaleidoscopicIFS {
+        gui_data.primitive_shape = rng.pick(&SEEDED_SHAPES);
+        gui_data.sphere_radius = default_data.sphere_radius;
+        gui_data.cylinder_radius = default_data.cylinder_radius;
+        gui_data.cylinder_half_height = default_data.cylinder_half_height;
+        gui_data.box_half_extents = default_data.box_half_extents;
+        gui_data.torus_outer_radius = default_data.torus_outer_radius;
+        gui_data.torus_inner_radius = default_data.torus_inner_radius;
+        gui_data.menger_iterations = rng.pick(&[3, 4, 5, 6]);
+        gui_data.menger_hole_size = rng.range(0.2, 0.45);
+        return;
+    }
+
+    // Constants of roughly this size give connected sets with detailed surfaces
+    let direction = Vector4(
+        rng.range(-1., 1.),
+        rng.range(-1., 1.),
+        rng.range(-1., 1.),
+        rng.range(-1., 1.),
+    );
+    let length =
+        (direction.0.powi(2) + direction.1.powi(2) + direction.2.powi(2) + direction.3.powi(2))
+            .sqrt()
+            .max(f32::EPSILON);
+    let size = rng.range(0.4, 0.9) / length;
+    gui_data.constant = Vector4(
+        direction.0 * size,
+        direction.1 * size,
+        direction.2 * size,
+        direction.3 * size,
+    );
+    gui_data.power = if gui_data.fractal_group == FractalGroup::GeneralizedJuliaSet {
+        rng.pick(&[2., 3., 4., 5., 8.])
+    } else {
+        2.
+    };
+    gui_data.is_translucent = rng.chance(0.2);
+}
+
+fn generate_palette(rng: &mut SeedRng, gui_data: &mut GuiData) {
+    let hue = rng.unit();
+    // Complementary or analogous background, kept dark so the fractal stands out
+    let background_hue = hue + rng.pick(&[0.5, 0.08, -0.08]);
+
+    gui_data.fractal_color = hsv_to_srgb(hue, rng.range(0.3, 0.8), rng.range(0.65, 0.95));
+    gui_data.background_color =
+        hsv_to_srgb(background_hue, rng.range(0.2, 0.7), rng.range(0.02, 0.25));
+    gui_data.absorption_color = hsv_to_srgb(hue + 0.5, 0.5, 1.);
+    gui_data.coloring_mode = if rng.chance(0.7) {
+        ColoringMode::Solid
+    } else {
+        ColoringMode::Heatmap
+    };
+    gui_data.is_smooth_heatmap = true;
+    gui_data.heatmap_scale = rng.range(0.5, 2.);
+    gui_data.heatmap_offset = rng.unit();
+    gui_data.debug_view = DebugView::default();
+}
+
+fn generate_lighting(rng: &mut SeedRng, gui_data: &mut GuiData) {
+    let mut lights = Light::default_rig();
+    let key_azimuth = rng.range(-180., 180.);
+    // Warm key light against a cool fill or the other way around
+    let is_warm_key = rng.chance(0.5);
+    let (warm, cool) = ([255, 225, 190], [190, 210, 255]);
+
+    lights[0].azimuth = key_azimuth;
+    lights[0].elevation = rng.range(20., 70.);
+    lights[0].color = if is_warm_key { warm } else { cool };
+    lights[0].intensity = rng.range(0.7, 1.);
+
+    lights[1].is_enabled = rng.chance(0.6);
+    lights[1].azimuth = key_azimuth + rng.range(90., 150.);
+    lights[1].elevation = rng.range(0., 30.);
+    lights[1].color = if is_warm_key { cool } else { warm };
+    lights[1].intensity = rng.range(0.2, 0.4);
+
+    lights[2].is_enabled = rng.chance(0.4);
+    lights[2].azimuth = key_azimuth + 180.;
+    lights[2].intensity = rng.range(0.3, 0.7);
+
+    gui_data.lights = lights;
+    gui_data.ambient_light = rng.range(0.05, 0.2);
+}
+
+fn generate_camera(rng: &mut SeedRng) -> CameraData {
+    let camera_data = CameraData::default();
+
+    CameraData {
+        origin_distance: rng.range(3., 5.5),
+        angles: Vector2(
+            Radians::from_radians(rng.range(0., TAU)),
+            Radians::from_radians(rng.range(-0.6, 1.)),
+        ),
+        ..camera_data
+    }
+}
+
+// Everything which decides the look of the image, generated from a seed
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SeededPreset {
+    pub(crate) gui_data: GuiData,
+    pub(crate) camera_data: CameraData,
+}
+
+impl SeededPreset {
+    // Settings unrelated to the look, such as the projection or the performance ones, are taken
+    // over from the given settings, all others only depend on the seed
+    #[must_use]
+    pub(crate) fn generate(seed: &str, settings: &GuiData) -> Self {
+        let mut rng = SeedRng::new(parse_seed(seed));
+        let mut gui_data = *settings;
+
+        generate_fractal(&mut rng, &mut gui_data);
+        generate_palette(&mut rng, &mut gui_data);
+        generate_lighting(&mut rng, &mut gui_data);
+        let camera_data = generate_camera(&mut rng);
+
+        Self {
+            gui_data,
+            camera_data,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seeded_preset() {
+        assert_eq!(parse_seed(" kifs://seed/coral-dusk "), "coral-dusk");
+        assert_eq!(seed_link("coral-dusk"), "kifs://seed/coral-dusk");
+        assert_eq!(hsv_to_srgb(0., 1., 1.), [255, 0, 0]);
+        assert_eq!(hsv_to_srgb(1. / 3., 1., 1.), [0, 255, 0]);
+
+        let preset = |seed| {
+            let preset = SeededPreset::generate(seed, &GuiData::default());
+            serde_json::to_string(&(preset.gui_data, preset.camera_data)).unwrap()
+        };
+        // Links and plain seeds give the same preset, different seeds different ones
+        assert_eq!(preset("coral-dusk"), preset("kifs://seed/coral-dusk"));
+        assert_ne!(preset("coral-dusk"), preset("coral-dawn"));
+    }
+}
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+use crate::{
+    data::{
+        CameraData, GuiData,
+        assets::{Asset, AssetManager},
+    },
+    error::ProjectError,
+};
+
+// Written into the working directory unless another path is given
+pub(crate) const DEFAULT_PROJECT_PATH: &str = "scene.kifs";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct ProjectFile {
+    gui_data: GuiData,
+    camera_data: CameraData,
+    assets: Vec<Asset>,
+}
+
+// Scene saved as a .kifs file together with the assets it uses
+#[derive(Clone, Debug)]
+pub(crate) struct Project {
+    pub(crate) gui_data: GuiData,
+    pub(crate) camera_data: CameraData,
+    pub(crate) assets: AssetManager,
+}
+
+#[must_use]
+fn project_directory(path: &Path) -> &Path {
+    path.parent().unwrap_or_else(|| Path::new(""))
+}
+
+/// File assets are made relative to the directory the project is saved into
+///
+/// ## Errors
+/// - `ProjectError::Json(serde_json::Error)` when serializing the project failed
+/// - `ProjectError::Io(io::Error)` when writing the file failed
+pub(crate) fn save_project(
+    path: &Path,
+    gui_data: &GuiData,
+    camera_data: &CameraData,
+    assets: &mut AssetManager,
+) -> Result<(), ProjectError> {
+    assets.rebase(project_directory(path));
+    let project_file = ProjectFile {
+        gui_data: *gui_data,
+        camera_data: *camera_data,
+        assets: assets.assets().to_vec(),
+    };
+    fs::write(path, serde_json::to_string_pretty(&project_file)?)?;
+
+    Ok(())
+}
+
+/// ## Errors
+/// - `ProjectError::Io(io::Error)` when the file could not be read
+/// - `ProjectError::Json(serde_json::Error)` when the file is not a valid project
+pub(crate) fn load_project(path: &Path) -> Result<Project, ProjectError> {
+    let project_file: ProjectFile = serde_json::from_str(&fs::read_to_string(path)?)?;
+
+    Ok(Project {
+        gui_data: project_file.gui_data,
+        camera_data: project_file.camera_data,
+        assets: AssetManager::new(project_directory(path).to_path_buf(), project_file.assets),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_round_trip() {
+        let directory = std::env::temp_dir().join("kifs_raymarching_test_project");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("sky.hdr"), b"sky").unwrap();
+
+        let mut assets = AssetManager::default();
+        assets.add_file(&directory.join("sky.hdr"));
+        let gui_data = GuiData {
+            max_iterations: 77,
+            ..GuiData::default()
+        };
+        let path = directory.join("scene.kifs");
+        save_project(&path, &gui_data, &CameraData::default(), &mut assets).unwrap();
+
+        // Moving the project together with its assets keeps them found
+        let moved_directory = std::env::temp_dir().join("kifs_raymarching_test_project_moved");
+        let _ = fs::remove_dir_all(&moved_directory);
+        fs::rename(&directory, &moved_directory).unwrap();
+        let project = load_project(&moved_directory.join("scene.kifs")).unwrap();
+        assert_eq!(project.gui_data.max_iterations, 77);
+        assert_eq!(project.assets.load("sky.hdr").unwrap(), b"sky");
+
+        fs::remove_dir_all(&moved_directory).unwrap();
+    }
+}
@@ -0,0 +1,132 @@
+use serde_json::Value;
+use std::{fmt::Write as _, fs, path::PathBuf};
+
+use crate::{
+    data::{CameraData, GuiData, scene::FractalGroup},
+    error::ReportError,
+    util::math::Vector3,
+};
+
+// Written into the working directory
+pub(crate) const SETTINGS_REPORT_PATH: &str = "settings_report.md";
+
+#[must_use]
+fn hex_color([red, green, blue]: [u8; 3]) -> String {
+    format!("#{red:02x}{green:02x}{blue:02x}")
+}
+
+// Nested settings get one row per value, named by their path such as lights[0].azimuth, short
+// lists of plain values such as colors and vectors stay on a single row
+fn flatten_value(name: &str, value: &Value, rows: &mut Vec<(String, String)>) {
+    let child_name = |key: &str| {
+        if name.is_empty() {
+            key.to_string()
+        } else {
+            format!("{name}.{key}")
+        }
+    };
+
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                flatten_value(&child_name(key), value, rows);
+            }
+        }
+        Value::Array(values) if values.iter().any(Value::is_object) => {
+            for (index, value) in values.iter().enumerate() {
+                flatten_value(&format!("{name}[{index}]"), value, rows);
+            }
+        }
+        Value::String(text) => rows.push((name.to_string(), text.clone())),
+        _ => rows.push((name.to_string(), value.to_string())),
+    }
+}
+
+/// Human readable summary of the scene for papers and forum posts, the full parameter table comes
+/// from the serialization seeded presets are compared by, so new settings show up without changes
+///
+/// ## Errors
+/// - `serde_json::Error` when the settings could not be serialized
+pub(crate) fn settings_report(
+    gui_data: &GuiData,
+    camera_data: &CameraData,
+) -> Result<String, serde_json::Error> {
+    let mut rows = Vec::new();
+    flatten_value("", &serde_json::to_value(gui_data)?, &mut rows);
+    flatten_value("camera", &serde_json::to_value(camera_data)?, &mut rows);
+
+    let formula = match gui_data.fractal_group {
+        FractalGroup::KaleidoscopicIFS => format!(" of a {}", gui_data.primitive_shape),
+        FractalGroup::JuliaSet | FractalGroup::GeneralizedJuliaSet => {
+            format!(", {} formula", gui_data.julia_formula)
+        }
+        _ => String::new(),
+    };
+    let Vector3(x, y, z) = camera_data.position();
+
+    let mut report = String::new();
+    // Writing into a string can not fail
+    let _ = writeln!(report, "# Fractal settings\n\n## Summary\n");
+    let _ = writeln!(report, "- Fractal: {}{formula}", gui_data.fractal_group);
+    let _ = writeln!(
+        report,
+        "- Iterations: {}, epsilon {}, max distance {}",
+        gui_data.max_iterations, gui_data.epsilon, gui_data.max_distance
+    );
+    let _ = writeln!(
+        report,
+        "- Colors: {} fractal on a {} background, {} coloring",
+        hex_color(gui_data.fractal_color),
+        hex_color(gui_data.background_color),
+        gui_data.coloring_mode
+    );
+    let _ = writeln!(
+        report,
+        "- Camera: at ({x:.3}, {y:.3}, {z:.3}), {:.3} from its target, azimuth {:.1}°, elevation {:.1}°",
+        camera_data.origin_distance,
+        camera_data.angles.0.degrees(),
+        camera_data.angles.1.degrees()
+    );
+
+    let _ = writeln!(
+        report,
+        "\n## Parameters\n\n| Parameter | Value |\n| --- | --- |"
+    );
+    for (name, value) in rows {
+        let _ = writeln!(report, "| {name} | {value} |");
+    }
+
+    Ok(report)
+}
+
+/// Writes the report into the working directory, returning the file written
+///
+/// ## Errors
+/// - `ReportError::Json(serde_json::Error)` when the settings could not be serialized
+/// - `ReportError::Io(io::Error)` when the file could not be written
+pub(crate) fn export_settings_report(
+    gui_data: &GuiData,
+    camera_data: &CameraData,
+) -> Result<PathBuf, ReportError> {
+    let path = PathBuf::from(SETTINGS_REPORT_PATH);
+    fs::write(&path, settings_report(gui_data, camera_data)?)?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_report() {
+        let report = settings_report(&GuiData::default(), &CameraData::default()).unwrap();
+
+        assert!(report.starts_with("# Fractal settings"));
+        assert!(report.contains("| max_iterations | 256 |"));
+        // Nested lists are split into rows, plain ones kept together
+        assert!(report.contains("| lights[0].azimuth |"));
+        assert!(report.contains("| fractal_color | ["));
+        assert!(report.contains("| camera.origin_distance | 5.0 |"));
+    }
+}
@@ -1,13 +1,24 @@
+use serde::{Deserialize, Serialize};
 use std::fmt;
-use strum_macros::{EnumIter, FromRepr};
+use strum_macros::{EnumCount, EnumIter, FromRepr};
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter)]
+use crate::util::math::Vector3;
+
+// Note: declared as FRACTAL_GROUP_* in the shader constants
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, EnumCount, Serialize, Deserialize,
+)]
 #[repr(u32)]
-pub(crate) enum FractalGroup {
+pub enum FractalGroup {
     #[default]
     KaleidoscopicIFS = 0,
     JuliaSet = 1,
     GeneralizedJuliaSet = 2,
+    KleinianLimitSet = 3,
+    Hybrid = 4,
+    AmazingSurf = 5,
+    CustomFormula = 6,
+    ShaderFile = 7,
 }
 
 impl FractalGroup {
@@ -20,6 +31,330 @@ impl FractalGroup {
     pub(crate) fn from_id(id: u32) -> Option<Self> {
         FractalGroup::from_repr(id)
     }
+
+    // Groups iterating a quaternion constant, which have an interior and a parameter space
+    #[must_use]
+    pub(crate) fn is_julia(self) -> bool {
+        matches!(
+            self,
+            FractalGroup::JuliaSet | FractalGroup::GeneralizedJuliaSet
+        )
+    }
+
+    // Share of the distance estimate marched at once unless set otherwise, the folds and powers of
+    // the later groups only give estimates which overshoot thin features at full length
+    #[must_use]
+    pub(crate) fn distance_fudge(self) -> f32 {
+        match self {
+            // Shader files are expected to return a proper bound
+            FractalGroup::KaleidoscopicIFS | FractalGroup::JuliaSet | FractalGroup::ShaderFile => {
+                1.
+            }
+            FractalGroup::GeneralizedJuliaSet => 0.8,
+            FractalGroup::KleinianLimitSet => 0.7,
+            FractalGroup::Hybrid | FractalGroup::AmazingSurf | FractalGroup::CustomFormula => 0.6,
+        }
+    }
+
+    // Volume around the origin the fractal never leaves, only known for the groups whose estimate
+    // is expensive and whose extent does not grow with the iterations
+    #[must_use]
+    pub(crate) fn bounding_volume(
+        self,
+        primitive_shape: PrimitiveShape,
+        sierpinski_offset: Vector3<f32>,
+    ) -> BoundingVolume {
+        match (self, primitive_shape) {
+            // Every fold keeps the norm and every scaling moves points beyond both the offset
+            // vertex and the final sphere further out
+            (FractalGroup::KaleidoscopicIFS, PrimitiveShape::SierpinskiTetrahedron) => {
+                BoundingVolume::sphere(sierpinski_offset.length().max(2.))
+            }
+            (FractalGroup::KaleidoscopicIFS, PrimitiveShape::Bunny) => BoundingVolume::sphere(1.),
+            // Scales of at least 2 keep every copy within the unit cube
+            (FractalGroup::KaleidoscopicIFS, PrimitiveShape::MengerSponge) => BoundingVolume {
+                shape: BoundingShape::Box,
+                extents: Vector3(1., 1., 1.),
+            },
+            (FractalGroup::JuliaSet | FractalGroup::GeneralizedJuliaSet, _) => {
+                BoundingVolume::sphere(2.)
+            }
+            _ => BoundingVolume::default(),
+        }
+    }
+}
+
+// Note: declared as BOUNDING_SHAPE_* in the shader constants
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter)]
+#[repr(u32)]
+pub(crate) enum BoundingShape {
+    #[default]
+    Unbounded = 0,
+    Sphere = 1,
+    Box = 2,
+}
+
+impl BoundingShape {
+    #[must_use]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+}
+
+/// Analytic volume enclosing the fractal, which rays are fast-forwarded to and which stands in for
+/// the distance estimate while the position is still outside of it
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct BoundingVolume {
+    pub(crate) shape: BoundingShape,
+    // Radius of the sphere in every coordinate, or the half extents of the box
+    pub(crate) extents: Vector3<f32>,
+}
+
+impl BoundingVolume {
+    #[must_use]
+    fn sphere(radius: f32) -> Self {
+        Self {
+            shape: BoundingShape::Sphere,
+            extents: Vector3(radius, radius, radius),
+        }
+    }
+}
+
+// Controls of the settings menu which belong to a fractal group, linked from its documentation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum FractalControl {
+    PresetShape,
+    ShapeSize,
+    MengerIterations,
+    MengerHoleSize,
+    SierpinskiScale,
+    SierpinskiOffset,
+    SierpinskiRotation,
+    KleinianBoxSize,
+    KleinianInversionRadius,
+    KleinianIterations,
+    HybridFormulas,
+    HybridPattern,
+    HybridIterations,
+    HybridBoxScale,
+    SurfFold,
+    SurfScale,
+    SurfMinRadius,
+    SurfIterations,
+    CustomIterations,
+    Formula,
+    AbsFolds,
+    Estimator,
+    Bailout,
+    Power,
+    PowerSweep,
+    Constant,
+    ConstantAnimation,
+    PhoenixStrength,
+    CubicConstant,
+    SliceHyperplane,
+    Translucency,
+    Interior,
+    ParameterPreview,
+}
+
+impl fmt::Display for FractalControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FractalControl::PresetShape => write!(f, "Preset shapes"),
+            FractalControl::ShapeSize => write!(f, "Shape size"),
+            FractalControl::MengerIterations => write!(f, "Sponge iterations"),
+            FractalControl::MengerHoleSize => write!(f, "Hole size"),
+            FractalControl::SierpinskiScale => write!(f, "Tetrahedron scale"),
+            FractalControl::SierpinskiOffset => write!(f, "Vertex offset"),
+            FractalControl::SierpinskiRotation => write!(f, "Fold rotation"),
+            FractalControl::KleinianBoxSize => write!(f, "Box size"),
+            FractalControl::KleinianInversionRadius => write!(f, "Inversion radius"),
+            FractalControl::KleinianIterations => write!(f, "Group iterations"),
+            FractalControl::HybridFormulas => write!(f, "Hybrid formulas"),
+            FractalControl::HybridPattern => write!(f, "Interleave pattern"),
+            FractalControl::HybridIterations => write!(f, "Hybrid iterations"),
+            FractalControl::HybridBoxScale => write!(f, "Box fold scale"),
+            FractalControl::SurfFold => write!(f, "Fold limits"),
+            FractalControl::SurfScale => write!(f, "Surf scale"),
+            FractalControl::SurfMinRadius => write!(f, "Minimum radius"),
+            FractalControl::SurfIterations => write!(f, "Surf iterations"),
+            FractalControl::CustomIterations => write!(f, "Formula iterations"),
+            FractalControl::Formula => write!(f, "Iteration formula"),
+            FractalControl::AbsFolds => write!(f, "Absolute folds"),
+            FractalControl::Bailout => write!(f, "Bailout radius"),
+            FractalControl::Estimator => write!(f, "Distance estimator"),
+            FractalControl::Power => write!(f, "Power variable"),
+            FractalControl::PowerSweep => write!(f, "Power sweep"),
+            FractalControl::Constant => write!(f, "Constant variable"),
+            FractalControl::ConstantAnimation => write!(f, "Constant animation"),
+            FractalControl::PhoenixStrength => write!(f, "Phoenix strength"),
+            FractalControl::CubicConstant => write!(f, "Cubic constant"),
+            FractalControl::SliceHyperplane => write!(f, "Slice hyperplane"),
+            FractalControl::Translucency => write!(f, "Translucent interior"),
+            FractalControl::Interior => write!(f, "Interior view"),
+            FractalControl::ParameterPreview => write!(f, "Parameter preview"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FractalDocumentation {
+    pub(crate) summary: &'static str,
+    // Iteration formula, {shape}, {sphere_radius}, {cylinder_size}, {box_extents}, {torus_radii},
+    // {abs_folds}, {julia_formula}, {power}, {constant},
+    // {phoenix_strength}, {cubic_constant}, {iterations}, {scale}, {sierpinski_scale}, {offset}, {rotation}, {box_size},
+    // {inversion_radius}, {kleinian_iterations}, {formula_a}, {formula_b}, {hybrid_pattern},
+    // {hybrid_iterations}, {box_scale}, {surf_fold}, {surf_scale}, {surf_min_radius},
+    // {surf_iterations} and {custom_iterations} stand in for the current parameter values
+    pub(crate) formula: &'static str,
+    pub(crate) controls: &'static [FractalControl],
+}
+
+impl FractalGroup {
+    #[must_use]
+    #[allow(clippy::too_many_lines)]
+    pub(crate) fn documentation(self) -> FractalDocumentation {
+        match self {
+            FractalGroup::KaleidoscopicIFS => FractalDocumentation {
+                summary: "Kaleidoscopic iterated function systems fold space onto itself and scale it \
+                    repeatedly, so a single shape is copied along every mirror. \
+                    The sphere, cylinder, box and torus can be resized freely. \
+                    Of the preset shapes the Sierpinski tetrahedron and the Menger sponge are folded, \
+                    the sponge with an adjustable number of folds and size of its holes, \
+                    the tetrahedron with an adjustable scale, vertex and rotation morphing it into related shapes.",
+                formula: "{shape}",
+                controls: &[
+                    FractalControl::PresetShape,
+                    FractalControl::ShapeSize,
+                    FractalControl::MengerIterations,
+                    FractalControl::MengerHoleSize,
+                    FractalControl::SierpinskiScale,
+                    FractalControl::SierpinskiOffset,
+                    FractalControl::SierpinskiRotation,
+                ],
+            },
+            FractalGroup::JuliaSet => FractalDocumentation {
+                summary: "3D Julia sets are rendered by finding all quaternions that do not converge to infinity \
+                    under continuous iteration of a function. \
+                    These are then displayed by rendering a 3D slice of the 4D set, \
+                    a hyperplane which can be moved along and rotated towards the fourth axis. \
+                    Instead of squaring, the quaternion can also be iterated by a cubic or a phoenix formula, \
+                    and the constant can be animated along a small path to watch the set morph. \
+                    Folding single components to their absolute value before every step gives burning ship style variants. \
+                    The bailout radius decides when a quaternion counts as escaped, small radii round off the finest detail. \
+                    Distances come from the derivative tracked through the iterations, \
+                    or for comparison from the gradient of the escape potential sampled around the position. \
+                    With the camera inside the set, its boundary can be shown from within in a color of its own.",
+                formula: "{abs_folds}{julia_formula}",
+                controls: &[
+                    FractalControl::Formula,
+                    FractalControl::AbsFolds,
+                    FractalControl::Bailout,
+                    FractalControl::Estimator,
+                    FractalControl::Constant,
+                    FractalControl::ConstantAnimation,
+                    FractalControl::PhoenixStrength,
+                    FractalControl::CubicConstant,
+                    FractalControl::SliceHyperplane,
+                    FractalControl::Translucency,
+                    FractalControl::Interior,
+                    FractalControl::ParameterPreview,
+                ],
+            },
+            FractalGroup::GeneralizedJuliaSet => FractalDocumentation {
+                summary: "Generalized Julia sets raise the quaternion to an arbitrary real power instead of squaring it, \
+                    which gives the set a rotational symmetry of that order. \
+                    The power can be swept back and forth through a range to watch the symmetry change. \
+                    They are displayed the same way as the regular Julia sets and offer the same formulas and folds.",
+                formula: "{abs_folds}{julia_formula}",
+                controls: &[
+                    FractalControl::Formula,
+                    FractalControl::AbsFolds,
+                    FractalControl::Bailout,
+                    FractalControl::Estimator,
+                    FractalControl::Power,
+                    FractalControl::PowerSweep,
+                    FractalControl::Constant,
+                    FractalControl::ConstantAnimation,
+                    FractalControl::PhoenixStrength,
+                    FractalControl::CubicConstant,
+                    FractalControl::SliceHyperplane,
+                    FractalControl::Translucency,
+                    FractalControl::Interior,
+                    FractalControl::ParameterPreview,
+                ],
+            },
+            FractalGroup::KleinianLimitSet => FractalDocumentation {
+                summary: "Pseudo-Kleinian limit sets are what remains of space under a group of reflections \
+                    in the faces of a box and inversions in a sphere, giving walls of nested circles and tubes \
+                    rather than the blobs of Julia sets. \
+                    The distance is measured to the cylinders the limit set clusters around.",
+                formula: "p ← 2 clamp(p, −{box_size}, {box_size}) − p, k = max({inversion_radius}² / |p|², 1), \
+                    p ← kp repeated {kleinian_iterations} times, d = max(|p.xy| − 0.928, |p.xy| |p.z| / |p|) / ∏k",
+                controls: &[
+                    FractalControl::KleinianBoxSize,
+                    FractalControl::KleinianInversionRadius,
+                    FractalControl::KleinianIterations,
+                ],
+            },
+            FractalGroup::Hybrid => FractalDocumentation {
+                summary: "Hybrid fractals alternate two formulas from one iteration to the next, \
+                    such as a Mandelbox fold followed by a Julia step, which is how most published 3D fractals are made. \
+                    The interleave pattern decides which of the two formulas each iteration uses and repeats \
+                    until the iterations run out. \
+                    The distance is estimated from the running derivative of all steps together.",
+                formula: "p ← A or B in the order {hybrid_pattern} repeated for {hybrid_iterations} iterations, \
+                    A: {formula_a}, B: {formula_b}, d = |p| / |dp|",
+                controls: &[
+                    FractalControl::HybridFormulas,
+                    FractalControl::HybridPattern,
+                    FractalControl::HybridIterations,
+                    FractalControl::HybridBoxScale,
+                    FractalControl::Power,
+                    FractalControl::PowerSweep,
+                    FractalControl::Constant,
+                    FractalControl::ConstantAnimation,
+                ],
+            },
+            FractalGroup::AmazingSurf => FractalDocumentation {
+                summary: "The Amazing Surf by Kali is a Mandelbox which only folds along the x and y axes, \
+                    so instead of a box the iterations grow into layered sheets, walls and arches \
+                    reminiscent of architecture. \
+                    The fold limits set the size of the folded box, the scale and minimum radius \
+                    those of the sphere fold following it. \
+                    The distance is estimated from the running derivative like for the hybrids.",
+                formula: "p.xy ← |p.xy + {surf_fold}| − |p.xy − {surf_fold}| − p.xy, \
+                    p ← {surf_scale} p / clamp(|p|², {surf_min_radius}², 1) + c \
+                    repeated {surf_iterations} times, d = |p| / |dp|",
+                controls: &[
+                    FractalControl::SurfFold,
+                    FractalControl::SurfScale,
+                    FractalControl::SurfMinRadius,
+                    FractalControl::SurfIterations,
+                ],
+            },
+            FractalGroup::CustomFormula => FractalDocumentation {
+                summary: "Custom formulas are typed into the formula window, one assignment per line, \
+                    and compiled into a new shader without restarting. \
+                    Each iteration updates the point x, y, z and the running derivative dr from the position cx, cy, cz, \
+                    the radius r and the iteration index i, and the distance is estimated from the final point. \
+                    Formulas which do not grow the derivative with the point give poor estimates and are marched slowly.",
+                formula: "(x, y, z, dr) ← formula(x, y, z, dr, c) repeated {custom_iterations} times, \
+                    d = estimate(x, y, z, dr)",
+                controls: &[FractalControl::CustomIterations],
+            },
+            FractalGroup::ShaderFile => FractalDocumentation {
+                summary: "Shader files are WGSL files defining fn user_sdf(p: vec3<f32>) -> f32, \
+                    loaded from the shader file window and marched like the built in groups. \
+                    They can use everything the other shaders can, such as the options uniform and the quaternion functions, \
+                    and are compiled into a new pipeline, with errors shown in the window instead of the scene. \
+                    The distance should never overestimate, or the march skips over parts of the surface.",
+                formula: "d = user_sdf(p)",
+                controls: &[],
+            },
+        }
+    }
 }
 
 impl fmt::Display for FractalGroup {
@@ -27,14 +362,197 @@ impl fmt::Display for FractalGroup {
         match self {
             FractalGroup::KaleidoscopicIFS => write!(f, "Kaleidoscopic IFS"),
             FractalGroup::JuliaSet => write!(f, "Julia Set"),
+            FractalGroup::KleinianLimitSet => write!(f, "Kleinian Limit Set"),
             FractalGroup::GeneralizedJuliaSet => write!(f, "Generalized Julia Set"),
+            FractalGroup::Hybrid => write!(f, "Hybrid"),
+            FractalGroup::AmazingSurf => write!(f, "Amazing Surf"),
+            FractalGroup::CustomFormula => write!(f, "Custom Formula"),
+            FractalGroup::ShaderFile => write!(f, "Shader File"),
         }
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter)]
+// Formula applied by a single iteration of the hybrid group, c is the position being iterated
+// Note: declared as HYBRID_FORMULA_* in the shader constants
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum HybridFormula {
+    #[default]
+    MandelboxFold = 0,
+    JuliaStep = 1,
+    Mandelbulb = 2,
+}
+
+impl HybridFormula {
+    #[must_use]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        HybridFormula::from_repr(id)
+    }
+
+    #[must_use]
+    pub(crate) fn formula(self) -> &'static str {
+        match self {
+            HybridFormula::MandelboxFold => "p ← {box_scale} sphere_fold(box_fold(p)) + c",
+            HybridFormula::JuliaStep => "p ← ((p, 0)² + {constant}).xyz",
+            HybridFormula::Mandelbulb => "p ← p^{power} + c in spherical coordinates",
+        }
+    }
+}
+
+impl fmt::Display for HybridFormula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HybridFormula::MandelboxFold => write!(f, "Mandelbox fold"),
+            HybridFormula::JuliaStep => write!(f, "Julia step"),
+            HybridFormula::Mandelbulb => write!(f, "Mandelbulb"),
+        }
+    }
+}
+
+// Function iterated by the Julia groups, the standard one is the group's own power of the quaternion
+// Note: declared as JULIA_FORMULA_* in the shader constants
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
 #[repr(u32)]
-pub(crate) enum PrimitiveShape {
+pub enum JuliaFormula {
+    #[default]
+    Standard = 0,
+    Cube = 1,
+    Phoenix = 2,
+    Cubic = 3,
+}
+
+impl JuliaFormula {
+    #[must_use]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        JuliaFormula::from_repr(id)
+    }
+
+    #[must_use]
+    pub(crate) fn formula(self, fractal_group: FractalGroup) -> &'static str {
+        match (self, fractal_group) {
+            (JuliaFormula::Standard, FractalGroup::GeneralizedJuliaSet) => {
+                "q ← q^{power} + {constant}"
+            }
+            (JuliaFormula::Standard, _) => "q ← q² + {constant}",
+            (JuliaFormula::Cube, _) => "q ← q³ + {constant}",
+            (JuliaFormula::Phoenix, _) => "q ← q² + {constant} + {phoenix_strength} q₋₁",
+            (JuliaFormula::Cubic, _) => "q ← q³ + {cubic_constant} q + {constant}",
+        }
+    }
+}
+
+// How the Julia groups turn the escaping quaternion into a distance
+// Note: declared as JULIA_ESTIMATOR_* in the shader constants
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum JuliaEstimator {
+    // Running derivative tracked alongside the quaternion
+    #[default]
+    Derivative = 0,
+    // Gradient of the escape potential sampled around the position, 6 extra iterations per estimate
+    Potential = 1,
+}
+
+impl JuliaEstimator {
+    #[must_use]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        JuliaEstimator::from_repr(id)
+    }
+}
+
+impl fmt::Display for JuliaEstimator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JuliaEstimator::Derivative => write!(f, "Running derivative"),
+            JuliaEstimator::Potential => write!(f, "Sampled potential gradient"),
+        }
+    }
+}
+
+impl fmt::Display for JuliaFormula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JuliaFormula::Standard => write!(f, "Standard"),
+            JuliaFormula::Cube => write!(f, "Cube"),
+            JuliaFormula::Phoenix => write!(f, "Phoenix"),
+            JuliaFormula::Cubic => write!(f, "Cubic with two constants"),
+        }
+    }
+}
+
+// Path the Julia constant follows around its configured value as the animation time passes
+// Note: declared as CONSTANT_ANIMATION_* in the shader constants
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum ConstantAnimation {
+    #[default]
+    Off = 0,
+    Circle = 1,
+    Lissajous = 2,
+}
+
+impl ConstantAnimation {
+    #[must_use]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        ConstantAnimation::from_repr(id)
+    }
+}
+
+impl fmt::Display for ConstantAnimation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstantAnimation::Off => write!(f, "Off"),
+            ConstantAnimation::Circle => write!(f, "Circle"),
+            ConstantAnimation::Lissajous => write!(f, "Lissajous curve"),
+        }
+    }
+}
+
+// Way the power is swept through its range while the sweep plays
+#[derive(Clone, Copy, Debug, Default, PartialEq, EnumIter, Serialize, Deserialize)]
+pub enum PowerSweep {
+    #[default]
+    Off,
+    // Jumps back to the start of the range after every period
+    Loop,
+    // Sweeps up and back down again within every period
+    PingPong,
+}
+
+impl fmt::Display for PowerSweep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PowerSweep::Off => write!(f, "Off"),
+            PowerSweep::Loop => write!(f, "Loop"),
+            PowerSweep::PingPong => write!(f, "Ping-pong"),
+        }
+    }
+}
+
+// Note: declared as PRIMITIVE_SHAPE_* in the shader constants
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum PrimitiveShape {
     #[default]
     Sphere = 0,
     Cylinder = 1,
@@ -42,6 +560,7 @@ pub(crate) enum PrimitiveShape {
     Torus = 3,
     SierpinskiTetrahedron = 4,
     Bunny = 5,
+    MengerSponge = 6,
 }
 
 impl PrimitiveShape {
@@ -56,6 +575,34 @@ impl PrimitiveShape {
     }
 }
 
+impl PrimitiveShape {
+    // Distance estimate of the shape as evaluated in kifs.wgsl
+    #[must_use]
+    pub(crate) fn formula(self) -> &'static str {
+        match self {
+            PrimitiveShape::Sphere => "d = |p| − {sphere_radius}",
+            PrimitiveShape::Cylinder => {
+                "q = (|p.xy|, |p.z|) − {cylinder_size}, d = |max(q, 0)| + min(max(q.x, q.y), 0)"
+            }
+            PrimitiveShape::Box => {
+                "q = |p| − {box_extents}, d = |max(q, 0)| + min(max(q.x, q.y, q.z), 0)"
+            }
+            PrimitiveShape::Torus => "(R, r) = {torus_radii}, d = |(|p.xy| − R, p.z)| − r",
+            PrimitiveShape::SierpinskiTetrahedron => {
+                "p ← R{rotation} fold(p), p ← sp − (s − 1){offset} repeated 10 times with s = {sierpinski_scale}, \
+                    d = (|p| − 2) / s¹⁰"
+            }
+            PrimitiveShape::Bunny => {
+                "d = neural network of sine layers fitted to the Stanford bunny"
+            }
+            PrimitiveShape::MengerSponge => {
+                "p ← sort(|p|), p ← sp − (s − 1)(1, 1, 0), fold p.z, repeated {iterations} times \
+                    with s = {scale}, d = box(p) / s^{iterations}"
+            }
+        }
+    }
+}
+
 impl fmt::Display for PrimitiveShape {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -65,6 +612,47 @@ impl fmt::Display for PrimitiveShape {
             PrimitiveShape::Torus => write!(f, "Torus"),
             PrimitiveShape::SierpinskiTetrahedron => write!(f, "Sierpinski Tetrahedron"),
             PrimitiveShape::Bunny => write!(f, "Bunny"),
+            PrimitiveShape::MengerSponge => write!(f, "Menger Sponge"),
+        }
+    }
+}
+
+// Entity of the scene, the fractal itself, a primitive of unit size or a group of other objects
+// Note: declared as OBJECT_* in the shader constants
+#[derive(Clone, Copy, Debug, Default, PartialEq, FromRepr, EnumIter, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum SceneObjectKind {
+    #[default]
+    Fractal = 0,
+    Sphere = 1,
+    Box = 2,
+    Cylinder = 3,
+    Torus = 4,
+    // Has no shape of its own, only moves the objects which have it as their parent
+    Group = 5,
+}
+
+impl SceneObjectKind {
+    #[must_use]
+    pub(crate) fn id(self) -> u32 {
+        self as u32
+    }
+
+    #[must_use]
+    pub(crate) fn from_id(id: u32) -> Option<Self> {
+        SceneObjectKind::from_repr(id)
+    }
+}
+
+impl fmt::Display for SceneObjectKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneObjectKind::Fractal => write!(f, "Fractal"),
+            SceneObjectKind::Sphere => write!(f, "Sphere"),
+            SceneObjectKind::Box => write!(f, "Box"),
+            SceneObjectKind::Cylinder => write!(f, "Cylinder"),
+            SceneObjectKind::Torus => write!(f, "Torus"),
+            SceneObjectKind::Group => write!(f, "Group"),
         }
     }
 }
@@ -0,0 +1,136 @@
+use crate::{
+    data::{MAX_SCENE_OBJECTS, SceneObject},
+    util::math::{MathContext, Matrix3x3, Vector3},
+};
+
+// Placement of an object, points are scaled and rotated about its origin before being translated
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Transform {
+    pub(crate) translation: Vector3<f32>,
+    pub(crate) rotation: Matrix3x3<f32>,
+    pub(crate) scale: f32,
+}
+
+impl Transform {
+    pub(crate) const IDENTITY: Self = Self {
+        translation: Vector3(0., 0., 0.),
+        rotation: Matrix3x3::IDENTITY,
+        scale: 1.,
+    };
+
+    // Compared approximately, as rotations composed along a group are rarely exact
+    #[must_use]
+    pub(crate) fn is_identity(&self) -> bool {
+        self.translation == Self::IDENTITY.translation
+            && self.rotation == Self::IDENTITY.rotation
+            && MathContext::DEFAULT.approx_eq(self.scale, 1.)
+    }
+
+    // Transform of the object relative to its parent
+    #[must_use]
+    pub(crate) fn local(object: &SceneObject) -> Self {
+        Self {
+            translation: object.translation,
+            rotation: object.rotation_matrix(),
+            scale: object.scale,
+        }
+    }
+
+    // Transform of a child given relative to this one, as seen from the frame this one is given in
+    #[must_use]
+    pub(crate) fn compose(self, child: Self) -> Self {
+        Self {
+            translation: self.apply(child.translation),
+            rotation: self.rotation * child.rotation,
+            scale: self.scale * child.scale,
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn apply(self, point: Vector3<f32>) -> Vector3<f32> {
+        self.translation + self.rotation * (point * self.scale)
+    }
+}
+
+// Indices of the object and its ancestors from the object upwards, a parent which would close a
+// cycle or does not exist ends the chain as if the object before it had no parent
+fn ancestry(objects: &[SceneObject; MAX_SCENE_OBJECTS], index: usize) -> Vec<usize> {
+    let mut chain = vec![index];
+
+    while let Some(parent) = objects[chain[chain.len() - 1]].parent {
+        if parent >= MAX_SCENE_OBJECTS || chain.contains(&parent) {
+            break;
+        }
+        chain.push(parent);
+    }
+
+    chain
+}
+
+// Transforms of every object in the scene, composed from the root of its group downwards, so
+// moving a group moves all of its members
+#[must_use]
+pub(crate) fn world_transforms(
+    objects: &[SceneObject; MAX_SCENE_OBJECTS],
+) -> [Transform; MAX_SCENE_OBJECTS] {
+    std::array::from_fn(|index| {
+        ancestry(objects, index)
+            .into_iter()
+            .rev()
+            .fold(Transform::IDENTITY, |transform, ancestor| {
+                transform.compose(Transform::local(&objects[ancestor]))
+            })
+    })
+}
+
+// Disabling a group hides all of its members
+#[must_use]
+pub(crate) fn is_visible(objects: &[SceneObject; MAX_SCENE_OBJECTS], index: usize) -> bool {
+    ancestry(objects, index)
+        .into_iter()
+        .all(|ancestor| objects[ancestor].is_enabled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{GuiData, Material, scene::SceneObjectKind};
+
+    #[test]
+    fn test_world_transforms() {
+        let mut objects = GuiData::default().scene_objects;
+        objects[1] = SceneObject {
+            is_enabled: true,
+            kind: SceneObjectKind::Group,
+            translation: Vector3(1., 0., 0.),
+            rotation: Vector3(0., 0., 90.),
+            scale: 2.,
+            parent: None,
+            material: Material::default(),
+        };
+        objects[2] = SceneObject {
+            is_enabled: true,
+            translation: Vector3(1., 0., 0.),
+            parent: Some(1),
+            ..objects[2]
+        };
+
+        // Child is moved, turned and scaled along with its group
+        let transforms = world_transforms(&objects);
+        assert_eq!(transforms[2].translation, Vector3(1., 2., 0.));
+        assert_eq!(
+            transforms[2].apply(Vector3(1., 0., 0.)),
+            Vector3(1., 4., 0.)
+        );
+        assert!((transforms[2].scale - 2.).abs() < f32::EPSILON);
+        assert!(is_visible(&objects, 2));
+
+        objects[1].is_enabled = false;
+        assert!(!is_visible(&objects, 2));
+
+        // Cycles are cut where they close instead of looping forever
+        objects[1].parent = Some(2);
+        assert_eq!(ancestry(&objects, 2), [2, 1]);
+        assert_eq!(ancestry(&objects, 1), [1, 2]);
+    }
+}
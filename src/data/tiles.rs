@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+
+use crate::data::{CameraData, GuiData, TileView};
+use crate::util::math::Vector2;
+
+// Side length of a tile in pixels, tiles along the right and bottom edge may be smaller
+pub(crate) const TILE_SIZE: u32 = 256;
+
+// Resolution and quality of a tiled export, as set in the GUI
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct TiledExportSettings {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) samples: u32,
+}
+
+impl Default for TiledExportSettings {
+    fn default() -> Self {
+        Self {
+            width: 3840,
+            height: 2160,
+            samples: 4,
+        }
+    }
+}
+
+// Part of the image in pixels
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct TileRect {
+    pub(crate) x: u32,
+    pub(crate) y: u32,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+}
+
+// Everything a machine needs to render tiles of the same image, the scene is fixed when the
+// export starts so changes made in the meantime do not end up in some of the tiles
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub(crate) struct ExportJob {
+    pub(crate) gui_data: GuiData,
+    pub(crate) camera_data: CameraData,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) samples: u32,
+}
+
+impl ExportJob {
+    #[must_use]
+    pub(crate) fn new(
+        gui_data: &GuiData,
+        camera_data: CameraData,
+        settings: TiledExportSettings,
+    ) -> Self {
+        Self {
+            gui_data: *gui_data,
+            camera_data,
+            width: settings.width.max(1),
+            height: settings.height.max(1),
+            samples: settings.samples.max(1),
+        }
+    }
+
+    #[must_use]
+    fn columns(&self) -> u32 {
+        self.width.div_ceil(TILE_SIZE)
+    }
+
+    // Tiles are numbered row by row from the top left corner
+    #[must_use]
+    pub(crate) fn tile_count(&self) -> u32 {
+        self.columns() * self.height.div_ceil(TILE_SIZE)
+    }
+
+    #[must_use]
+    pub(crate) fn tile(&self, index: u32) -> TileRect {
+        let x = index % self.columns() * TILE_SIZE;
+        let y = index / self.columns() * TILE_SIZE;
+
+        TileRect {
+            x,
+            y,
+            width: TILE_SIZE.min(self.width - x),
+            height: TILE_SIZE.min(self.height - y),
+        }
+    }
+
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn tile_view(&self, index: u32) -> TileView {
+        let tile = self.tile(index);
+
+        TileView {
+            offset: Vector2(tile.x as f32, tile.y as f32),
+            image_size: Vector2(self.width as f32, self.height as f32),
+        }
+    }
+}
+
+// Splits the tiles into contiguous ranges of nearly equal length, one for every part
+#[must_use]
+pub(crate) fn split_tiles(tiles: &[u32], parts: usize) -> Vec<Vec<u32>> {
+    let parts = parts.max(1);
+    let length = tiles.len() / parts;
+    let remainder = tiles.len() % parts;
+
+    let mut ranges = Vec::with_capacity(parts);
+    let mut start = 0;
+    for part in 0..parts {
+        // First ranges take one of the remaining tiles each
+        let end = start + length + usize::from(part < remainder);
+        ranges.push(tiles[start..end].to_vec());
+        start = end;
+    }
+
+    ranges
+}
+
+// Image assembled from tiles finished in any order, as RGB values row by row
+#[derive(Clone, Debug)]
+pub(crate) struct TiledImage {
+    job: ExportJob,
+    values: Vec<f32>,
+    is_finished: Vec<bool>,
+}
+
+impl TiledImage {
+    #[must_use]
+    pub(crate) fn new(job: &ExportJob) -> Self {
+        Self {
+            job: *job,
+            values: vec![0.; job.width as usize * job.height as usize * 3],
+            is_finished: vec![false; job.tile_count() as usize],
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn job(&self) -> &ExportJob {
+        &self.job
+    }
+
+    #[must_use]
+    pub(crate) fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    // Returns the number of finished and all tiles
+    #[must_use]
+    pub(crate) fn progress(&self) -> (usize, usize) {
+        let finished = self
+            .is_finished
+            .iter()
+            .filter(|is_finished| **is_finished)
+            .count();
+        (finished, self.is_finished.len())
+    }
+
+    #[must_use]
+    pub(crate) fn is_complete(&self) -> bool {
+        self.is_finished.iter().all(|is_finished| *is_finished)
+    }
+
+    // Copies the RGB values of a tile into place, returning false when they do not fit the tile
+    pub(crate) fn insert_tile(&mut self, index: u32, values: &[f32]) -> bool {
+        if index >= self.job.tile_count() {
+            return false;
+        }
+        let tile = self.job.tile(index);
+        let row_length = tile.width as usize * 3;
+        if values.len() != row_length * tile.height as usize {
+            return false;
+        }
+
+        for (row, tile_row) in (tile.y..).zip(values.chunks(row_length)) {
+            let start = (row as usize * self.job.width as usize + tile.x as usize) * 3;
+            self.values[start..start + row_length].copy_from_slice(tile_row);
+        }
+        self.is_finished[index as usize] = true;
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiled_image() {
+        let job = ExportJob::new(
+            &GuiData::default(),
+            CameraData::default(),
+            TiledExportSettings {
+                width: TILE_SIZE + 2,
+                height: 3,
+                samples: 1,
+            },
+        );
+        assert_eq!(job.tile_count(), 2);
+        assert_eq!(
+            job.tile(1),
+            TileRect {
+                x: TILE_SIZE,
+                y: 0,
+                width: 2,
+                height: 3,
+            }
+        );
+
+        let ranges = split_tiles(&[0, 1, 2, 3, 4], 2);
+        assert_eq!(ranges, [vec![0, 1, 2], vec![3, 4]]);
+
+        let mut image = TiledImage::new(&job);
+        assert!(!image.insert_tile(1, &[1.; 3]));
+        assert!(image.insert_tile(1, &[1.; 2 * 3 * 3]));
+        assert_eq!(image.progress(), (1, 2));
+        // Last row ends with the two pixels of the second tile
+        let row_length = job.width as usize * 3;
+        assert_eq!(
+            image.values()[3 * row_length - 7..],
+            [0., 1., 1., 1., 1., 1., 1.]
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::Path};
+
+use crate::error::WorkspaceError;
+
+// File the workspaces are kept in, relative to the working directory
+pub(crate) const WORKSPACES_PATH: &str = "workspaces.json";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct PanelLayout {
+    pub(crate) is_open: bool,
+    // Top left corner in points, None leaves the panel wherever it currently is
+    pub(crate) position: Option<(f32, f32)>,
+}
+
+// Named arrangement of the GUI panels, switchable from the workspace menu
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Workspace {
+    pub(crate) name: String,
+    // Settings menu is never closed, only collapsed
+    pub(crate) settings_menu: PanelLayout,
+    pub(crate) parameter_preview: PanelLayout,
+    // Ray inspector only opens once a ray is recorded, closing it discards the recorded ray
+    pub(crate) ray_inspector: PanelLayout,
+}
+
+impl Workspace {
+    #[must_use]
+    pub(crate) fn builtin() -> Vec<Self> {
+        vec![
+            Self {
+                name: "Exploring".to_string(),
+                settings_menu: PanelLayout {
+                    is_open: true,
+                    position: Some((8., 8.)),
+                },
+                parameter_preview: PanelLayout {
+                    is_open: true,
+                    position: Some((440., 8.)),
+                },
+                ray_inspector: PanelLayout::default(),
+            },
+            Self {
+                name: "Exporting".to_string(),
+                settings_menu: PanelLayout {
+                    is_open: false,
+                    position: Some((8., 8.)),
+                },
+                parameter_preview: PanelLayout::default(),
+                ray_inspector: PanelLayout::default(),
+            },
+            Self {
+                name: "Debugging".to_string(),
+                settings_menu: PanelLayout {
+                    is_open: true,
+                    position: Some((8., 8.)),
+                },
+                parameter_preview: PanelLayout::default(),
+                ray_inspector: PanelLayout {
+                    is_open: true,
+                    position: Some((440., 8.)),
+                },
+            },
+        ]
+    }
+}
+
+/// ## Errors
+/// - `WorkspaceError::Io(io::Error)` when the file exists but could not be read
+/// - `WorkspaceError::Json(serde_json::Error)` when the file is not a valid list of workspaces
+pub(crate) fn load_workspaces(path: &Path) -> Result<Vec<Workspace>, WorkspaceError> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(serde_json::from_str(&contents)?),
+        // Nothing was saved yet
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(Workspace::builtin()),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// ## Errors
+/// - `WorkspaceError::Json(serde_json::Error)` when serializing the workspaces failed
+/// - `WorkspaceError::Io(io::Error)` when writing the file failed
+pub(crate) fn save_workspaces(path: &Path, workspaces: &[Workspace]) -> Result<(), WorkspaceError> {
+    fs::write(path, serde_json::to_string_pretty(workspaces)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workspaces_round_trip() {
+        let path = std::env::temp_dir().join("kifs_raymarching_test_workspaces.json");
+        let workspaces = Workspace::builtin();
+
+        save_workspaces(&path, &workspaces).unwrap();
+        assert_eq!(load_workspaces(&path).unwrap(), workspaces);
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(load_workspaces(&path).unwrap(), workspaces);
+    }
+}
@@ -1,5 +1,5 @@
 use egui_wgpu::wgpu;
-use std::{error::Error, fmt};
+use std::{error::Error, fmt, io, sync::mpsc};
 use winit::error::{EventLoopError, OsError};
 
 macro_rules! impl_error {
@@ -52,30 +52,17 @@ impl fmt::Display for SurfaceMissizedError {
 
 impl_error!(SurfaceMissizedError);
 
-#[derive(Clone, Copy, Debug)]
-pub struct GUIUnconfiguredError;
-
-impl fmt::Display for GUIUnconfiguredError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "GUI was not configured before rendering")
-    }
-}
-
-impl_error!(GUIUnconfiguredError);
-
 #[derive(Clone, Debug)]
 pub enum RenderError {
     Surface(wgpu::SurfaceError),
     SurfaceMissized(SurfaceMissizedError),
-    GUIUnconfigured(GUIUnconfiguredError),
 }
 
-impl_enum_error_display!(RenderError{ ::Surface ::SurfaceMissized ::GUIUnconfigured });
+impl_enum_error_display!(RenderError{ ::Surface ::SurfaceMissized });
 impl_error!(RenderError);
 
 impl_enum_from!(error: wgpu::SurfaceError -> RenderError::Surface(error));
 impl_enum_from!(error: SurfaceMissizedError -> RenderError::SurfaceMissized(error));
-impl_enum_from!(error: GUIUnconfiguredError -> RenderError::GUIUnconfigured(error));
 
 #[derive(Clone, Debug)]
 pub enum RenderStateError {
@@ -91,6 +78,319 @@ impl_enum_from!(error: wgpu::CreateSurfaceError -> RenderStateError::CreateSurfa
 impl_enum_from!(error: wgpu::RequestAdapterError -> RenderStateError::RequestAdapter(error));
 impl_enum_from!(error: wgpu::RequestDeviceError -> RenderStateError::RequestDevice(error));
 
+#[derive(Debug)]
+pub enum WorkspaceError {
+    Io(io::Error),
+    Json(serde_json::Error),
+}
+
+impl_enum_error_display!(WorkspaceError{ ::Io ::Json });
+impl_error!(WorkspaceError);
+
+impl_enum_from!(error: io::Error -> WorkspaceError::Io(error));
+impl_enum_from!(error: serde_json::Error -> WorkspaceError::Json(error));
+
+#[derive(Debug)]
+pub enum CameraPathError {
+    Io(io::Error),
+    Json(serde_json::Error),
+}
+
+impl_enum_error_display!(CameraPathError{ ::Io ::Json });
+impl_error!(CameraPathError);
+
+impl_enum_from!(error: io::Error -> CameraPathError::Io(error));
+impl_enum_from!(error: serde_json::Error -> CameraPathError::Json(error));
+
+#[derive(Debug)]
+pub enum ReportError {
+    Io(io::Error),
+    Json(serde_json::Error),
+}
+
+impl_enum_error_display!(ReportError{ ::Io ::Json });
+impl_error!(ReportError);
+
+impl_enum_from!(error: io::Error -> ReportError::Io(error));
+impl_enum_from!(error: serde_json::Error -> ReportError::Json(error));
+
+#[derive(Debug)]
+pub enum ExpressionError {
+    UnexpectedCharacter(char),
+    UnexpectedToken(String),
+    UnexpectedEnd,
+    UnknownFunction(String),
+    ArgumentCount {
+        function: String,
+        expected: usize,
+        found: usize,
+    },
+    UnknownParameter(String),
+    DuplicateTarget(String),
+    // Parameters along the cycle, starting and ending with the same one
+    Cycle(Vec<String>),
+    NotFinite(String),
+    // Value the setting's type cannot hold
+    OutOfRange(String),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedCharacter(character) => write!(f, "Unexpected character '{character}'"),
+            Self::UnexpectedToken(token) => write!(f, "Unexpected '{token}'"),
+            Self::UnexpectedEnd => write!(f, "Expression ended unexpectedly"),
+            Self::UnknownFunction(function) => write!(f, "Unknown function {function}"),
+            Self::ArgumentCount {
+                function,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{function} takes {expected} arguments, but was given {found}"
+            ),
+            Self::UnknownParameter(name) => write!(f, "Unknown numeric parameter {name}"),
+            Self::DuplicateTarget(name) => write!(f, "{name} is already bound"),
+            Self::Cycle(names) => {
+                write!(f, "Bindings depend on each other: {}", names.join(" -> "))
+            }
+            Self::NotFinite(name) => write!(f, "Value of {name} is not a finite number"),
+            Self::OutOfRange(name) => write!(f, "Value of {name} is out of its range"),
+            Self::Json(error) => error.fmt(f),
+        }
+    }
+}
+
+impl_error!(ExpressionError);
+
+impl_enum_from!(error: serde_json::Error -> ExpressionError::Json(error));
+
+#[derive(Debug)]
+pub enum AssetError {
+    Io(io::Error),
+    Missing(String),
+    TooLarge { name: String, size: u64 },
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => error.fmt(f),
+            Self::Missing(name) => write!(f, "Project has no asset named {name}"),
+            Self::TooLarge { name, size } => {
+                write!(f, "Asset {name} of {size} bytes is too large to embed")
+            }
+        }
+    }
+}
+
+impl_error!(AssetError);
+
+impl_enum_from!(error: io::Error -> AssetError::Io(error));
+
+// Validation message of a shader compiled from user input
+#[derive(Clone, Debug)]
+pub struct ShaderCompileError(pub String);
+
+impl fmt::Display for ShaderCompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Shader failed to compile: {}", self.0)
+    }
+}
+
+impl_error!(ShaderCompileError);
+
+// Lines of the iteration formula are counted from 1, the distance estimate is a single expression
+// without a line
+#[derive(Debug)]
+pub enum FormulaError {
+    Expression {
+        line: Option<usize>,
+        error: ExpressionError,
+    },
+    Assignment(usize),
+    ReadOnly {
+        line: usize,
+        name: String,
+    },
+    UnknownVariable {
+        line: Option<usize>,
+        name: String,
+    },
+    Shader(ShaderCompileError),
+}
+
+impl fmt::Display for FormulaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let location = |line: &Option<usize>| {
+            line.map_or("Distance estimate".to_string(), |line| {
+                format!("Line {line}")
+            })
+        };
+        match self {
+            Self::Expression { line, error } => write!(f, "{}: {error}", location(line)),
+            Self::Assignment(line) => write!(f, "Line {line}: Expected name = expression"),
+            Self::ReadOnly { line, name } => write!(f, "Line {line}: {name} can not be assigned"),
+            Self::UnknownVariable { line, name } => {
+                write!(f, "{}: Unknown variable {name}", location(line))
+            }
+            Self::Shader(error) => error.fmt(f),
+        }
+    }
+}
+
+impl_error!(FormulaError);
+
+impl_enum_from!(error: ShaderCompileError -> FormulaError::Shader(error));
+
+#[derive(Debug)]
+pub enum ShaderFileError {
+    Io(io::Error),
+    Asset(AssetError),
+    Shader(ShaderCompileError),
+}
+
+impl_enum_error_display!(ShaderFileError{ ::Io ::Asset ::Shader });
+impl_error!(ShaderFileError);
+
+impl_enum_from!(error: io::Error -> ShaderFileError::Io(error));
+impl_enum_from!(error: AssetError -> ShaderFileError::Asset(error));
+impl_enum_from!(error: ShaderCompileError -> ShaderFileError::Shader(error));
+
+#[derive(Clone, Copy, Debug)]
+pub enum NodeGraphError {
+    MissingOutput,
+    MultipleOutputs,
+    // Node without an input, which is not a position node
+    Unconnected(usize),
+    Cycle,
+}
+
+impl fmt::Display for NodeGraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingOutput => write!(f, "Graph has no primitive node"),
+            Self::MultipleOutputs => write!(f, "Graph has more than one primitive node"),
+            Self::Unconnected(index) => write!(f, "Node {index} has no input"),
+            Self::Cycle => write!(f, "Nodes are wired in a cycle"),
+        }
+    }
+}
+
+impl_error!(NodeGraphError);
+
+#[derive(Debug)]
+pub enum ProjectError {
+    Io(io::Error),
+    Json(serde_json::Error),
+}
+
+impl_enum_error_display!(ProjectError{ ::Io ::Json });
+impl_error!(ProjectError);
+
+impl_enum_from!(error: io::Error -> ProjectError::Io(error));
+impl_enum_from!(error: serde_json::Error -> ProjectError::Json(error));
+
+#[derive(Debug)]
+pub enum AovExportError {
+    Poll(wgpu::PollError),
+    BufferAsync(wgpu::BufferAsyncError),
+    Io(io::Error),
+}
+
+impl_enum_error_display!(AovExportError{ ::Poll ::BufferAsync ::Io });
+impl_error!(AovExportError);
+
+impl_enum_from!(error: wgpu::PollError -> AovExportError::Poll(error));
+impl_enum_from!(error: wgpu::BufferAsyncError -> AovExportError::BufferAsync(error));
+impl_enum_from!(error: io::Error -> AovExportError::Io(error));
+
+#[derive(Debug)]
+pub enum CaptureError {
+    Poll(wgpu::PollError),
+    BufferAsync(wgpu::BufferAsyncError),
+    // Application exited before the frame was captured
+    Closed(mpsc::RecvError),
+}
+
+impl_enum_error_display!(CaptureError{ ::Poll ::BufferAsync ::Closed });
+impl_error!(CaptureError);
+
+impl_enum_from!(error: wgpu::PollError -> CaptureError::Poll(error));
+impl_enum_from!(error: wgpu::BufferAsyncError -> CaptureError::BufferAsync(error));
+impl_enum_from!(error: mpsc::RecvError -> CaptureError::Closed(error));
+
+#[derive(Debug)]
+pub enum TiledExportError {
+    Poll(wgpu::PollError),
+    BufferAsync(wgpu::BufferAsyncError),
+    Io(io::Error),
+}
+
+impl_enum_error_display!(TiledExportError{ ::Poll ::BufferAsync ::Io });
+impl_error!(TiledExportError);
+
+impl_enum_from!(error: wgpu::PollError -> TiledExportError::Poll(error));
+impl_enum_from!(error: wgpu::BufferAsyncError -> TiledExportError::BufferAsync(error));
+impl_enum_from!(error: io::Error -> TiledExportError::Io(error));
+
+#[derive(Debug)]
+pub enum MeshError {
+    Poll(wgpu::PollError),
+    BufferAsync(wgpu::BufferAsyncError),
+}
+
+impl_enum_error_display!(MeshError{ ::Poll ::BufferAsync });
+impl_error!(MeshError);
+
+impl_enum_from!(error: wgpu::PollError -> MeshError::Poll(error));
+impl_enum_from!(error: wgpu::BufferAsyncError -> MeshError::BufferAsync(error));
+
+#[derive(Debug)]
+pub enum VoxelExportError {
+    Poll(wgpu::PollError),
+    BufferAsync(wgpu::BufferAsyncError),
+    Io(io::Error),
+}
+
+impl_enum_error_display!(VoxelExportError{ ::Poll ::BufferAsync ::Io });
+impl_error!(VoxelExportError);
+
+impl_enum_from!(error: wgpu::PollError -> VoxelExportError::Poll(error));
+impl_enum_from!(error: wgpu::BufferAsyncError -> VoxelExportError::BufferAsync(error));
+impl_enum_from!(error: io::Error -> VoxelExportError::Io(error));
+
+#[derive(Debug)]
+pub enum NetworkError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    // Peer announced more values than any tile holds
+    TooManyValues(usize),
+    // Peer sent no newline within the longest message allowed
+    MessageTooLong(usize),
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => error.fmt(f),
+            Self::Json(error) => error.fmt(f),
+            Self::TooManyValues(value_count) => {
+                write!(f, "Peer sent a tile of {value_count} values")
+            }
+            Self::MessageTooLong(length) => {
+                write!(f, "Peer sent a message longer than {length} bytes")
+            }
+        }
+    }
+}
+
+impl_error!(NetworkError);
+
+impl_enum_from!(error: io::Error -> NetworkError::Io(error));
+impl_enum_from!(error: serde_json::Error -> NetworkError::Json(error));
+
 #[derive(Debug)]
 pub enum ApplicationError {
     EventLoop(EventLoopError),
@@ -112,4 +412,3 @@ impl_enum_from!(error: wgpu::RequestDeviceError -> ApplicationError::RenderState
 impl_enum_from!(error: RenderError -> ApplicationError::Render(error));
 impl_enum_from!(error: wgpu::SurfaceError -> ApplicationError::Render(error.into()));
 impl_enum_from!(error: SurfaceMissizedError -> ApplicationError::Render(error.into()));
-impl_enum_from!(error: GUIUnconfiguredError -> ApplicationError::Render(error.into()));
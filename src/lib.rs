@@ -1,7 +1,9 @@
 #![allow(dead_code)]
 
 pub mod application;
+pub mod crash;
 pub(crate) mod data;
 pub mod error;
+pub mod network;
 pub mod render;
 pub(crate) mod util;
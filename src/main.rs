@@ -1,14 +1,79 @@
 use egui_wgpu::wgpu;
 use kifs_raymarching::application::Application;
-use kifs_raymarching::render::RenderStateOptions;
+use kifs_raymarching::crash;
+use kifs_raymarching::network::NetworkRole;
+use kifs_raymarching::render::{RenderStateOptions, SurfaceProfile, run_self_test};
 
 fn main() {
-    env_logger::init();
+    crash::install();
+
+    let is_safe_mode = std::env::args()
+        .skip(1)
+        .any(|argument| argument == "--safe-mode");
+    if is_safe_mode {
+        log::info!("Starting in safe mode");
+    }
+
+    // Given as --surface-profile=<name>
+    let surface_profile = std::env::args()
+        .skip(1)
+        .find_map(|argument| {
+            argument
+                .strip_prefix("--surface-profile=")
+                .map(str::to_owned)
+        })
+        .map_or(SurfaceProfile::default(), |name| {
+            SurfaceProfile::from_name(&name).unwrap_or_else(|| {
+                log::warn!("Unknown surface profile {name}, using the default one");
+                SurfaceProfile::default()
+            })
+        });
+
+    // Given as --coordinator=<address> or --worker=<address>
+    let network_role = std::env::args().skip(1).find_map(|argument| {
+        argument
+            .strip_prefix("--coordinator=")
+            .map(|address| NetworkRole::Coordinator(address.to_owned()))
+            .or_else(|| {
+                argument
+                    .strip_prefix("--worker=")
+                    .map(|address| NetworkRole::Worker(address.to_owned()))
+            })
+    });
+
+    // Given as --preset=<seed> or --preset=kifs://seed/<seed>
+    let preset_seed = std::env::args()
+        .skip(1)
+        .find_map(|argument| argument.strip_prefix("--preset=").map(str::to_owned));
 
     let state_options = RenderStateOptions {
         power_preference: wgpu::PowerPreference::HighPerformance,
+        is_safe_mode,
+        surface_profile,
+        network_role,
+        preset_seed,
         ..RenderStateOptions::default()
     };
+
+    // Prints a capability report instead of opening the window, failing when any check did
+    let is_diagnose = std::env::args()
+        .skip(1)
+        .any(|argument| argument == "--diagnose");
+    if is_diagnose {
+        match run_self_test(&state_options) {
+            Ok(report) => {
+                println!("{report}");
+                if !report.is_passed() {
+                    std::process::exit(1);
+                }
+            }
+            Err(error) => {
+                eprintln!("Self-test could not start: {error}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
     let mut app = Application::new(state_options);
 
     match app.run() {
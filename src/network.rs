@@ -0,0 +1,492 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{Shutdown, TcpListener, TcpStream},
+    sync::{Arc, Mutex, MutexGuard, PoisonError, Weak, mpsc},
+    thread,
+};
+
+use crate::data::tiles::{ExportJob, TILE_SIZE, split_tiles};
+use crate::error::NetworkError;
+
+/// Part a running application plays in splitting tiled exports between machines
+#[derive(Clone, Debug, PartialEq)]
+pub enum NetworkRole {
+    /// Listens on the address for workers and hands each of them a range of the tiles of every export
+    Coordinator(String),
+    /// Connects to the coordinator at the address and renders the tiles it is sent
+    Worker(String),
+}
+
+// Messages are a line of JSON, tiles are followed by their values as little endian floats
+#[derive(Debug, Serialize, Deserialize)]
+enum Message {
+    // Sent by the coordinator, the tiles are rendered in the given order
+    Job {
+        export_id: u32,
+        job: Box<ExportJob>,
+        tiles: Vec<u32>,
+    },
+    // Sent by a worker for every finished tile
+    Tile {
+        export_id: u32,
+        index: u32,
+        value_count: usize,
+    },
+}
+
+fn write_message(
+    stream: &mut impl Write,
+    message: &Message,
+    values: &[f32],
+) -> Result<(), NetworkError> {
+    let mut bytes = serde_json::to_vec(message)?;
+    bytes.push(b'\n');
+    for value in values {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    stream.write_all(&bytes)?;
+
+    Ok(())
+}
+
+// Jobs of the largest exports list 16384 tiles, which stays well below this
+const MAX_MESSAGE_LEN: usize = 1 << 20;
+
+// Returns None once the other side closed the connection
+fn read_message(reader: &mut impl BufRead) -> Result<Option<Message>, NetworkError> {
+    let mut line = String::new();
+    // Lines are cut off at the limit, so a peer never sending a newline cannot exhaust the memory
+    let length = reader.take(MAX_MESSAGE_LEN as u64).read_line(&mut line)?;
+    if length == 0 {
+        return Ok(None);
+    }
+    if length == MAX_MESSAGE_LEN && !line.ends_with('\n') {
+        return Err(NetworkError::MessageTooLong(MAX_MESSAGE_LEN));
+    }
+
+    Ok(Some(serde_json::from_str(&line)?))
+}
+
+// Tiles are never larger than TILE_SIZE in either direction and hold 3 values per pixel
+const MAX_TILE_VALUES: usize = TILE_SIZE as usize * TILE_SIZE as usize * 3;
+
+// Counts are taken from the peer, so they are checked before allocating anything
+fn read_values(reader: &mut impl Read, value_count: usize) -> Result<Vec<f32>, NetworkError> {
+    if value_count > MAX_TILE_VALUES {
+        return Err(NetworkError::TooManyValues(value_count));
+    }
+
+    let mut bytes = vec![0; value_count * size_of::<f32>()];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(bytes
+        .chunks_exact(size_of::<f32>())
+        .map(|value| f32::from_le_bytes([value[0], value[1], value[2], value[3]]))
+        .collect())
+}
+
+// Panicking while holding one of the locks cannot leave the state half written, as every
+// critical section either pushes or takes values as a whole
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[derive(Debug)]
+pub(crate) enum CoordinatorEvent {
+    Tile {
+        export_id: u32,
+        index: u32,
+        values: Vec<f32>,
+    },
+    // Tiles of a disconnected worker it did not send back, which have to be rendered elsewhere
+    WorkerLost {
+        export_id: u32,
+        tiles: Vec<u32>,
+    },
+}
+
+#[derive(Debug, Default)]
+struct PendingTiles {
+    export_id: u32,
+    tiles: Vec<u32>,
+    // Checked under the same lock the tiles are handed out with, so none get lost on disconnect
+    is_connected: bool,
+}
+
+#[derive(Debug)]
+struct WorkerLink {
+    stream: TcpStream,
+    pending_tiles: Arc<Mutex<PendingTiles>>,
+}
+
+impl WorkerLink {
+    // Forwards the finished tiles of the worker until it disconnects
+    fn receive_tiles(
+        stream: &TcpStream,
+        pending_tiles: &Mutex<PendingTiles>,
+        event_sender: &mpsc::Sender<CoordinatorEvent>,
+    ) -> Result<(), NetworkError> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        while let Some(message) = read_message(&mut reader)? {
+            let Message::Tile {
+                export_id,
+                index,
+                value_count,
+            } = message
+            else {
+                log::warn!("Worker sent a message only the coordinator sends");
+                continue;
+            };
+
+            // Rest of the stream can not be told apart from the next message once a tile is refused
+            let values = read_values(&mut reader, value_count).inspect_err(|_| {
+                stream.shutdown(Shutdown::Both).ok();
+            })?;
+            lock(pending_tiles).tiles.retain(|tile| *tile != index);
+            // Render loop only drops the receiver together with the coordinator
+            event_sender
+                .send(CoordinatorEvent::Tile {
+                    export_id,
+                    index,
+                    values,
+                })
+                .ok();
+        }
+
+        Ok(())
+    }
+
+    #[must_use]
+    fn spawn(stream: TcpStream, event_sender: mpsc::Sender<CoordinatorEvent>) -> Self {
+        let pending_tiles = Arc::new(Mutex::new(PendingTiles {
+            is_connected: true,
+            ..PendingTiles::default()
+        }));
+
+        let reader_stream = stream.try_clone();
+        let reader_pending_tiles = pending_tiles.clone();
+        thread::spawn(move || {
+            let result = reader_stream
+                .map_err(NetworkError::from)
+                .and_then(|stream| {
+                    Self::receive_tiles(&stream, &reader_pending_tiles, &event_sender)
+                });
+            match result {
+                Ok(()) => log::info!("Worker disconnected"),
+                Err(error) => log::warn!("Lost the connection to a worker: {error}"),
+            }
+
+            let mut pending_tiles = lock(&reader_pending_tiles);
+            pending_tiles.is_connected = false;
+            let tiles = std::mem::take(&mut pending_tiles.tiles);
+            if !tiles.is_empty() {
+                event_sender
+                    .send(CoordinatorEvent::WorkerLost {
+                        export_id: pending_tiles.export_id,
+                        tiles,
+                    })
+                    .ok();
+            }
+        });
+
+        Self {
+            stream,
+            pending_tiles,
+        }
+    }
+
+    // Returns false when the worker is no longer connected, in which case it did not take the tiles
+    fn send_job(&mut self, export_id: u32, job: &ExportJob, tiles: Vec<u32>) -> bool {
+        let mut pending_tiles = lock(&self.pending_tiles);
+        if !pending_tiles.is_connected {
+            return false;
+        }
+
+        let message = Message::Job {
+            export_id,
+            job: Box::new(*job),
+            tiles: tiles.clone(),
+        };
+        if let Err(error) = write_message(&mut self.stream, &message, &[]) {
+            log::warn!("Failed to send a job to a worker: {error}");
+            return false;
+        }
+        *pending_tiles = PendingTiles {
+            export_id,
+            tiles,
+            is_connected: true,
+        };
+
+        true
+    }
+}
+
+// Shutting down the stream also ends the thread reading from it
+impl Drop for WorkerLink {
+    fn drop(&mut self) {
+        self.stream.shutdown(Shutdown::Both).ok();
+    }
+}
+
+// Accepts workers in the background, which stay connected for every following export
+#[derive(Debug)]
+pub(crate) struct Coordinator {
+    workers: Arc<Mutex<Vec<WorkerLink>>>,
+    event_receiver: mpsc::Receiver<CoordinatorEvent>,
+}
+
+impl Coordinator {
+    /// ## Errors
+    /// - `NetworkError::Io(io::Error)` when the address could not be listened on
+    pub(crate) fn listen(address: &str) -> Result<Self, NetworkError> {
+        let listener = TcpListener::bind(address)?;
+        let workers = Arc::new(Mutex::new(Vec::new()));
+        let (event_sender, event_receiver) = mpsc::channel();
+
+        // Workers are only kept while the coordinator is, dropping it disconnects all of them
+        let accepted_workers = Arc::downgrade(&workers);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let Some(workers) = Weak::upgrade(&accepted_workers) else {
+                            break;
+                        };
+                        if let Ok(address) = stream.peer_addr() {
+                            log::info!("Worker connected from {address}");
+                        }
+                        let link = WorkerLink::spawn(stream, event_sender.clone());
+                        lock(&workers).push(link);
+                    }
+                    Err(error) => log::warn!("Failed to accept a worker: {error}"),
+                }
+            }
+        });
+
+        Ok(Self {
+            workers,
+            event_receiver,
+        })
+    }
+
+    #[must_use]
+    pub(crate) fn worker_count(&self) -> usize {
+        let mut workers = lock(&self.workers);
+        workers.retain(|worker| lock(&worker.pending_tiles).is_connected);
+        workers.len()
+    }
+
+    // Splits the tiles evenly between the coordinator and every worker, returning the ones the
+    // coordinator has to render itself, including those of workers which could not take their share
+    #[must_use]
+    pub(crate) fn distribute(&self, export_id: u32, job: &ExportJob, tiles: &[u32]) -> Vec<u32> {
+        let mut workers = lock(&self.workers);
+        let mut ranges = split_tiles(tiles, workers.len() + 1).into_iter();
+        let mut local_tiles = ranges.next().unwrap_or_default();
+
+        for (worker, range) in workers.iter_mut().zip(ranges) {
+            if range.is_empty() {
+                continue;
+            }
+            if !worker.send_job(export_id, job, range.clone()) {
+                local_tiles.extend(range);
+            }
+        }
+
+        local_tiles
+    }
+
+    pub(crate) fn take_events(&self) -> impl Iterator<Item = CoordinatorEvent> + '_ {
+        self.event_receiver.try_iter()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct WorkerJob {
+    pub(crate) export_id: u32,
+    pub(crate) job: ExportJob,
+    // Tiles still to be rendered, in order
+    pub(crate) tiles: Vec<u32>,
+}
+
+// Connection to a coordinator, whose jobs are received in the background
+#[derive(Debug)]
+pub(crate) struct Worker {
+    stream: TcpStream,
+    job_receiver: mpsc::Receiver<WorkerJob>,
+}
+
+impl Worker {
+    /// ## Errors
+    /// - `NetworkError::Io(io::Error)` when the coordinator could not be connected to
+    pub(crate) fn connect(address: &str) -> Result<Self, NetworkError> {
+        let stream = TcpStream::connect(address)?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let (job_sender, job_receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            loop {
+                match read_message(&mut reader) {
+                    Ok(Some(Message::Job {
+                        export_id,
+                        job,
+                        tiles,
+                    })) => {
+                        // Indices are taken from the coordinator, so those past the end are dropped
+                        let tile_count = job.tile_count();
+                        let (tiles, dropped_tiles): (Vec<_>, Vec<_>) =
+                            tiles.into_iter().partition(|index| *index < tile_count);
+                        if !dropped_tiles.is_empty() {
+                            log::warn!(
+                                "Coordinator sent {} tiles past the end of an export with {tile_count} tiles",
+                                dropped_tiles.len()
+                            );
+                        }
+                        log::info!("Received {} tiles to render", tiles.len());
+                        // Render loop only drops the receiver together with the worker
+                        job_sender
+                            .send(WorkerJob {
+                                export_id,
+                                job: *job,
+                                tiles,
+                            })
+                            .ok();
+                    }
+                    Ok(Some(_)) => log::warn!("Coordinator sent a message only workers send"),
+                    Ok(None) => {
+                        log::info!("Coordinator closed the connection");
+                        break;
+                    }
+                    Err(error) => {
+                        log::warn!("Lost the connection to the coordinator: {error}");
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            stream,
+            job_receiver,
+        })
+    }
+
+    pub(crate) fn take_jobs(&self) -> impl Iterator<Item = WorkerJob> + '_ {
+        self.job_receiver.try_iter()
+    }
+
+    /// ## Errors
+    /// - `NetworkError::Io(io::Error)` when the tile could not be sent
+    /// - `NetworkError::Json(serde_json::Error)` when the message could not be serialized
+    pub(crate) fn send_tile(
+        &mut self,
+        export_id: u32,
+        index: u32,
+        values: &[f32],
+    ) -> Result<(), NetworkError> {
+        write_message(
+            &mut self.stream,
+            &Message::Tile {
+                export_id,
+                index,
+                value_count: values.len(),
+            },
+            values,
+        )
+    }
+}
+
+// Shutting down the stream also ends the thread receiving the jobs
+impl Drop for Worker {
+    fn drop(&mut self) {
+        self.stream.shutdown(Shutdown::Both).ok();
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) enum NetworkNode {
+    #[default]
+    Standalone,
+    Coordinator(Coordinator),
+    Worker(Worker),
+}
+
+impl NetworkNode {
+    // Machines which cannot take their role still render their own exports
+    #[must_use]
+    pub(crate) fn new(role: Option<&NetworkRole>) -> Self {
+        match role {
+            None => NetworkNode::Standalone,
+            Some(NetworkRole::Coordinator(address)) => match Coordinator::listen(address) {
+                Ok(coordinator) => {
+                    log::info!("Listening for workers on {address}");
+                    NetworkNode::Coordinator(coordinator)
+                }
+                Err(error) => {
+                    log::warn!("Failed to listen for workers on {address}: {error}");
+                    NetworkNode::Standalone
+                }
+            },
+            Some(NetworkRole::Worker(address)) => match Worker::connect(address) {
+                Ok(worker) => {
+                    log::info!("Connected to the coordinator at {address}");
+                    NetworkNode::Worker(worker)
+                }
+                Err(error) => {
+                    log::warn!("Failed to connect to the coordinator at {address}: {error}");
+                    NetworkNode::Standalone
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_round_trip() {
+        let mut bytes = Vec::new();
+        write_message(
+            &mut bytes,
+            &Message::Tile {
+                export_id: 1,
+                index: 2,
+                value_count: 3,
+            },
+            &[0.5, 1., 2.],
+        )
+        .unwrap();
+
+        let mut reader = bytes.as_slice();
+        let Some(Message::Tile {
+            export_id,
+            index,
+            value_count,
+        }) = read_message(&mut reader).unwrap()
+        else {
+            panic!("Expected a tile");
+        };
+        assert_eq!((export_id, index, value_count), (1, 2, 3));
+        assert_eq!(
+            read_values(&mut reader, value_count).unwrap(),
+            [0.5, 1., 2.]
+        );
+        assert!(read_message(&mut reader).unwrap().is_none());
+
+        // Counts past the largest tile are refused before reading their values
+        assert!(matches!(
+            read_values(&mut reader, usize::MAX),
+            Err(NetworkError::TooManyValues(usize::MAX))
+        ));
+
+        // Lines without an end are refused once they reach the limit
+        let bytes = vec![b' '; MAX_MESSAGE_LEN + 1];
+        assert!(matches!(
+            read_message(&mut bytes.as_slice()),
+            Err(NetworkError::MessageTooLong(MAX_MESSAGE_LEN))
+        ));
+    }
+}
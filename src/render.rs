@@ -1,30 +1,310 @@
 use egui_wgpu::{ScreenDescriptor, wgpu};
 use limited_queue::LimitedQueue;
 use std::{
+    collections::HashSet,
+    mem,
     sync::Arc,
     time::{Duration, Instant},
 };
-use wgpu::wgt::SamplerDescriptor;
 use winit::{
     dpi::{PhysicalPosition, PhysicalSize},
-    event::{DeviceEvent, ElementState, MouseButton, MouseScrollDelta, WindowEvent},
+    event::{DeviceEvent, ElementState, KeyEvent, MouseButton, MouseScrollDelta, WindowEvent},
+    keyboard::{KeyCode, ModifiersState, PhysicalKey},
     window::Window,
 };
 
+use crate::application::handle::AppHandle;
+use crate::crash::{self, CrashState};
+use crate::data::{
+    AxesData, CameraData, MAX_LIGHTS, MAX_SCENE_OBJECTS, PostData,
+    diagnostics::detect_artifacts,
+    options::{
+        CameraMode, ColoringMode, CsgOperation, CsgShape, DebugView, FloorPattern, MirrorPlanes,
+        ObjectShading, ParameterAxis, Projection, RenderMode, StereoMode, TransferFunction,
+        UpscaleFilter, WarpAxis,
+    },
+    scene::{
+        BoundingShape, ConstantAnimation, FractalGroup, HybridFormula, JuliaEstimator,
+        JuliaFormula, PrimitiveShape, SceneObjectKind,
+    },
+    tiles::ExportJob,
+};
 use crate::error::{RenderError, RenderStateError, SurfaceMissizedError};
-use crate::util::math::Radians;
+use crate::network::{NetworkNode, NetworkRole};
+use crate::util::math::{Radians, Vector2, Vector3};
+use crate::util::shader::{WGSLConstants, WGSLShaderSource};
+
+macro_rules! shader_source {
+    ($path:expr $(,)?) => {
+        WGSLShaderSource(
+            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/shaders/", $path)).into(),
+        )
+    };
+}
 
+pub(crate) mod aov;
+pub(crate) mod axes;
+pub(crate) mod bake;
+pub(crate) mod capture;
+pub(crate) mod collision;
+pub(crate) mod distance_grid;
+pub(crate) mod distance_slice;
+pub(crate) mod exposure;
+pub(crate) mod governor;
 pub(crate) mod graphics;
 pub(crate) mod gui;
+pub(crate) mod inspector;
+pub(crate) mod legend;
+pub(crate) mod post;
+pub(crate) mod preview;
+pub(crate) mod self_test;
+pub(crate) mod slicer;
+pub(crate) mod snapshot;
+pub(crate) mod statistics;
+pub(crate) mod surface;
+pub(crate) mod taa;
+pub(crate) mod tiled;
 
+// Ids of the options and sizes of the buffers as the shaders see them, placed in front of every
+// other source of the shaders which use them
+#[must_use]
+pub(crate) fn shader_constants() -> WGSLShaderSource<'static> {
+    let constants = WGSLConstants::default()
+        .integer("MAX_LIGHTS", MAX_LIGHTS)
+        .integer("MAX_SCENE_OBJECTS", MAX_SCENE_OBJECTS)
+        .integer("MAX_RAY_PATH_STEPS", inspector::MAX_RAY_PATH_STEPS)
+        .integer("PREVIEW_WORKGROUP_SIZE", preview::PREVIEW_WORKGROUP_SIZE)
+        .integer(
+            "DISTANCE_SLICE_WORKGROUP_SIZE",
+            distance_slice::DISTANCE_SLICE_WORKGROUP_SIZE,
+        )
+        .integer(
+            "DISTANCE_GRID_WORKGROUP_SIZE",
+            distance_grid::DISTANCE_GRID_WORKGROUP_SIZE,
+        )
+        .enumeration("FRACTAL_GROUP", FractalGroup::id)
+        .enumeration("BOUNDING_SHAPE", BoundingShape::id)
+        .enumeration("PRIMITIVE_SHAPE", PrimitiveShape::id)
+        .enumeration("HYBRID_FORMULA", HybridFormula::id)
+        .enumeration("JULIA_FORMULA", JuliaFormula::id)
+        .enumeration("JULIA_ESTIMATOR", JuliaEstimator::id)
+        .enumeration("CONSTANT_ANIMATION", ConstantAnimation::id)
+        .enumeration("OBJECT", SceneObjectKind::id)
+        .enumeration("COLORING_MODE", ColoringMode::id)
+        .enumeration("DEBUG_VIEW", DebugView::id)
+        .enumeration("RENDER_MODE", RenderMode::id)
+        .enumeration("FLOOR_PATTERN", FloorPattern::id)
+        .enumeration("PROJECTION", Projection::id)
+        .enumeration("STEREO_MODE", StereoMode::id)
+        .enumeration("PARAMETER_AXIS", ParameterAxis::id)
+        .enumeration("CSG", CsgOperation::id)
+        .enumeration("CSG_SHAPE", CsgShape::id)
+        .enumeration("WARP_AXIS", WarpAxis::id)
+        .enumeration("UPSCALE_FILTER", UpscaleFilter::id)
+        .enumeration("TRANSFER_FUNCTION", TransferFunction::id)
+        .unsigned(
+            "SHADING_SOFT_SHADOWS",
+            ObjectShading {
+                is_soft_shadows: true,
+                ..Default::default()
+            }
+            .bits(),
+        )
+        .unsigned(
+            "SHADING_AMBIENT_OCCLUSION",
+            ObjectShading {
+                is_ambient_occlusion: true,
+                ..Default::default()
+            }
+            .bits(),
+        );
+
+    // Each plane on its own gives the bit the shaders test for it
+    MirrorPlanes::NAMES
+        .iter()
+        .enumerate()
+        .fold(constants, |constants, (i, name)| {
+            let mut flags = [false; MirrorPlanes::NAMES.len()];
+            flags[i] = true;
+            constants.unsigned(
+                &format!("MIRROR_{name}"),
+                MirrorPlanes::from_flags(flags).bits(),
+            )
+        })
+        .into()
+}
+
+use aov::AovState;
+use axes::AxesState;
+use bake::BakeState;
+use collision::{CollisionState, limit_camera_step};
+use distance_grid::{export_voxels, sample_distance_grid};
+use distance_slice::DistanceSliceState;
+use exposure::ExposureState;
+use governor::FrameGovernor;
 use graphics::GraphicState;
-use gui::GuiState;
+pub use gui::{GuiFont, GuiStyle};
+use gui::{GuiFrame, GuiState};
+use inspector::RayInspectorState;
+use post::PostState;
+use preview::PreviewState;
+pub use self_test::{SelfTestReport, run_self_test};
+use slicer::FrameSlicer;
+use snapshot::create_snapshot_texture;
+use statistics::StatisticsState;
+pub use surface::SurfaceProfile;
+use surface::SurfaceSettings;
+use taa::TaaState;
+use tiled::TiledExportState;
+
+// Fractals are rendered into a linear HDR texture, which the post pass then writes onto the surface
+const RENDER_TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
 
 #[derive(Clone, Debug, Default)]
 pub struct RenderStateOptions {
     pub power_preference: wgpu::PowerPreference,
     pub required_features: wgpu::Features,
     pub required_limits: wgpu::Limits,
+    // Recovery startup which prefers the fallback adapter and ignores everything saved by earlier sessions
+    pub is_safe_mode: bool,
+    pub surface_profile: SurfaceProfile,
+    // Machines splitting tiled exports between them, rendering alone when None
+    pub network_role: Option<NetworkRole>,
+    // Seed or kifs://seed/ link of a preset to start with
+    pub preset_seed: Option<String>,
+    // Fonts and style overrides of embedding applications, ignored in safe mode
+    pub gui_style: GuiStyle,
+}
+
+// Mouse input received since the last frame, so the camera is moved and its buffer written once per
+// rendered frame however many events arrive in between
+#[derive(Clone, Copy, Debug, Default)]
+struct CameraInput {
+    // Summed mouse motion, right and down positive
+    motion: Option<(f64, f64)>,
+    // Summed mouse motion while panning, in the same directions
+    pan_motion: Option<(f64, f64)>,
+    // Summed lines scrolled, positive away from the user
+    zoom_lines: Option<f32>,
+}
+
+impl CameraInput {
+    fn add_motion(&mut self, (dx, dy): (f64, f64)) {
+        let (x, y) = self.motion.unwrap_or_default();
+        self.motion = Some((x + dx, y + dy));
+    }
+
+    fn add_pan(&mut self, (dx, dy): (f64, f64)) {
+        let (x, y) = self.pan_motion.unwrap_or_default();
+        self.pan_motion = Some((x + dx, y + dy));
+    }
+
+    fn add_zoom(&mut self, lines: f32) {
+        self.zoom_lines = Some(self.zoom_lines.unwrap_or_default() + lines);
+    }
+
+    // Splits off the share of the input to apply now, keeping the rest for the next frames until it
+    // is too small to notice
+    #[must_use]
+    fn take_share(&mut self, share: f64) -> Self {
+        let split_motion = |motion: &mut Option<(f64, f64)>| {
+            let (dx, dy) = (*motion)?;
+            let (rest_x, rest_y) = (dx * (1. - share), dy * (1. - share));
+            *motion = (rest_x.hypot(rest_y) >= MIN_CAMERA_INPUT).then_some((rest_x, rest_y));
+            Some((dx - rest_x, dy - rest_y))
+        };
+        let motion = split_motion(&mut self.motion);
+        let pan_motion = split_motion(&mut self.pan_motion);
+
+        #[allow(clippy::cast_possible_truncation)]
+        let zoom_lines = self.zoom_lines.map(|lines| {
+            let rest = lines * (1. - share) as f32;
+            self.zoom_lines = (f64::from(rest.abs()) >= MIN_CAMERA_INPUT).then_some(rest);
+            lines - rest
+        });
+
+        Self {
+            motion,
+            pan_motion,
+            zoom_lines,
+        }
+    }
+}
+
+// Input left to apply below which smoothing stops, in pixels of motion or lines scrolled
+const MIN_CAMERA_INPUT: f64 = 1e-3;
+
+// Speed of the free-fly camera in units per second, changed by a factor per line scrolled within
+// limits far enough apart for deep zooms
+const DEFAULT_FLY_SPEED: f32 = 1.;
+const FLY_SPEED_STEP: f32 = 1.2;
+const MIN_FLY_SPEED: f32 = 1e-6;
+const MAX_FLY_SPEED: f32 = 100.;
+// Multiplier of the speed while shift is held
+const FLY_SPRINT_FACTOR: f32 = 4.;
+// Longest frame the camera moves for, so a stalled frame does not fling it away
+const MAX_CAMERA_FRAME_TIME: Duration = Duration::from_millis(100);
+
+// Keys held down to move the free-fly camera, which unlike the mouse input stay held across frames
+#[derive(Clone, Debug)]
+struct FlyControls {
+    held_keys: HashSet<KeyCode>,
+    speed: f32,
+    last_frame: Option<Instant>,
+}
+
+impl FlyControls {
+    #[must_use]
+    fn new() -> Self {
+        Self {
+            held_keys: HashSet::new(),
+            speed: DEFAULT_FLY_SPEED,
+            last_frame: None,
+        }
+    }
+
+    fn key_event(&mut self, key: KeyCode, is_pressed: bool) {
+        if is_pressed {
+            self.held_keys.insert(key);
+        } else {
+            self.held_keys.remove(&key);
+        }
+    }
+
+    fn release_keys(&mut self) {
+        self.held_keys.clear();
+    }
+
+    fn scale_speed(&mut self, lines: f32) {
+        self.speed = (self.speed * FLY_SPEED_STEP.powf(lines)).clamp(MIN_FLY_SPEED, MAX_FLY_SPEED);
+    }
+
+    // Offset along the right, up and forward directions of the camera since the previous frame
+    // WASD moves within the view, E and Q up and down, shift sprints
+    #[must_use]
+    fn offset(&mut self, now: Instant) -> Option<Vector3<f32>> {
+        let last_frame = self.last_frame.replace(now)?;
+        let is_held = |key: KeyCode| self.held_keys.contains(&key);
+        let axis = |positive: KeyCode, negative: KeyCode| {
+            f32::from(is_held(positive)) - f32::from(is_held(negative))
+        };
+        let direction = Vector3(
+            axis(KeyCode::KeyD, KeyCode::KeyA),
+            axis(KeyCode::KeyE, KeyCode::KeyQ),
+            axis(KeyCode::KeyW, KeyCode::KeyS),
+        );
+        if direction.length() == 0. {
+            return None;
+        }
+
+        let seconds = (now - last_frame).min(MAX_CAMERA_FRAME_TIME).as_secs_f32();
+        let speed = if is_held(KeyCode::ShiftLeft) || is_held(KeyCode::ShiftRight) {
+            self.speed * FLY_SPRINT_FACTOR
+        } else {
+            self.speed
+        };
+        Some(direction.normalize() * (speed * seconds))
+    }
 }
 
 pub(crate) struct RenderState {
@@ -33,21 +313,64 @@ pub(crate) struct RenderState {
     device: wgpu::Device,
     queue: wgpu::Queue,
     config: wgpu::SurfaceConfiguration,
+    surface_settings: SurfaceSettings,
+    render_texture: wgpu::Texture,
+    render_texture_view: wgpu::TextureView,
     graphic_state: GraphicState,
+    aov_state: AovState,
+    exposure_state: ExposureState,
+    post_state: PostState,
+    taa_state: TaaState,
+    axes_state: AxesState,
+    preview_state: PreviewState,
+    distance_slice_state: DistanceSliceState,
+    collision_state: CollisionState,
+    ray_inspector_state: RayInspectorState,
+    statistics_state: StatisticsState,
+    tiled_export_state: TiledExportState,
+    bake_state: BakeState,
     gui_state: GuiState,
+    // Parameters shared with other threads
+    app_handle: AppHandle,
+    // Cursor position when the left mouse button was pressed, to tell clicks apart from drags
+    click_position: Option<Vector2<f32>>,
+    camera_input: CameraInput,
+    last_camera_frame: Option<Instant>,
+    // Whether the middle mouse button is held, shift held while rotating pans as well
+    is_panning: bool,
+    modifiers: ModifiersState,
+    fly_controls: FlyControls,
     frametimes: LimitedQueue<Duration>,
+    frame_governor: FrameGovernor,
+    frame_slicer: FrameSlicer,
 }
 
 impl RenderState {
     async fn create_adapter(
         instance: &wgpu::Instance,
-        surface: &wgpu::Surface<'static>,
+        compatible_surface: Option<&wgpu::Surface<'static>>,
         options: &RenderStateOptions,
     ) -> Result<wgpu::Adapter, wgpu::RequestAdapterError> {
+        if options.is_safe_mode {
+            let fallback_adapter = instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: options.power_preference,
+                    compatible_surface,
+                    force_fallback_adapter: true,
+                })
+                .await;
+
+            // Not every platform has a software adapter, in which case the usual one has to do
+            match fallback_adapter {
+                Ok(adapter) => return Ok(adapter),
+                Err(error) => log::warn!("No fallback adapter available in safe mode: {error}"),
+            }
+        }
+
         instance
             .request_adapter(&wgpu::RequestAdapterOptions {
                 power_preference: options.power_preference,
-                compatible_surface: Some(surface),
+                compatible_surface,
                 force_fallback_adapter: false,
             })
             .await
@@ -68,47 +391,6 @@ impl RenderState {
             .await
     }
 
-    #[must_use]
-    fn surface_format(surface_capabilities: &wgpu::SurfaceCapabilities) -> wgpu::TextureFormat {
-        // Find first preferred SRGB format, otherwise use the general preferred one
-        surface_capabilities
-            .formats
-            .iter()
-            .find(|f| f.is_srgb())
-            .copied()
-            .unwrap_or(surface_capabilities.formats[0])
-    }
-
-    #[must_use]
-    fn alpha_mode(surface_capabilities: &wgpu::SurfaceCapabilities) -> wgpu::CompositeAlphaMode {
-        if surface_capabilities
-            .alpha_modes
-            .contains(&wgpu::CompositeAlphaMode::Auto)
-        {
-            wgpu::CompositeAlphaMode::Auto
-        } else {
-            surface_capabilities.alpha_modes[0]
-        }
-    }
-
-    #[must_use]
-    fn create_surface_config(
-        format: wgpu::TextureFormat,
-        alpha_mode: wgpu::CompositeAlphaMode,
-        size: PhysicalSize<u32>,
-    ) -> wgpu::SurfaceConfiguration {
-        wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format,
-            width: size.width,
-            height: size.height,
-            present_mode: wgpu::PresentMode::AutoVsync, // This is supported on all platforms if adapter is compatible with the surface
-            alpha_mode,
-            view_formats: vec![], // View formats of the surface format are always allowed, even when specifying an empty vector
-            desired_maximum_frame_latency: 2,
-        }
-    }
-
     #[must_use]
     fn create_render_texture(
         device: &wgpu::Device,
@@ -126,36 +408,24 @@ impl RenderState {
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format,
-            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::STORAGE_BINDING
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC,
             view_formats: &[],
         })
     }
 
-    #[must_use]
-    fn create_render_sampler(device: &wgpu::Device) -> wgpu::Sampler {
-        device.create_sampler(&SamplerDescriptor {
-            label: Some("render_sampler"),
-            address_mode_u: wgpu::AddressMode::Repeat,
-            address_mode_v: wgpu::AddressMode::Repeat,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Nearest,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            lod_min_clamp: 0.,
-            lod_max_clamp: 32.,
-            compare: None,
-            anisotropy_clamp: 1,
-            border_color: None,
-        })
-    }
-
     /// ## Errors
     /// - `RenderStateError::CreateSurface(CreateSurfaceError)` when surface creation failed
     /// - `RenderStateError::RequestAdapter(RequestAdapterError)` when adapter request failed
     /// - `RenderStateError::RequestDevice(RequestDeviceError)` when device request failed
+    #[allow(clippy::too_many_lines)]
     pub(crate) async fn new(
         window: Arc<Window>,
         options: &RenderStateOptions,
+        app_handle: AppHandle,
     ) -> Result<Self, RenderStateError> {
         let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
             backends: wgpu::Backends::all(),
@@ -165,20 +435,80 @@ impl RenderState {
         let size = window.inner_size();
         let surface = instance.create_surface(window.clone())?;
 
-        let adapter = Self::create_adapter(&instance, &surface, options).await?;
+        let adapter = Self::create_adapter(&instance, Some(&surface), options).await?;
         let (device, queue) = Self::create_device_and_queue(&adapter, options).await?;
 
         let surface_capabilities = surface.get_capabilities(&adapter);
 
-        // Surface is guaranteed compatible with adapter on adapter initialisation, meaning surface_format and alpha_mode are well defined
-        let surface_format = Self::surface_format(&surface_capabilities);
-        let alpha_mode = Self::alpha_mode(&surface_capabilities);
-        let config = Self::create_surface_config(surface_format, alpha_mode, size);
+        let surface_settings = SurfaceSettings::new(
+            options.surface_profile,
+            &surface_capabilities,
+            adapter.get_downlevel_capabilities().flags,
+        );
+
+        let render_texture = Self::create_render_texture(&device, RENDER_TEXTURE_FORMAT, size);
+        let render_texture_view =
+            render_texture.create_view(&wgpu::TextureViewDescriptor::default());
 
-        let graphic_state = GraphicState::new(&window, &device, &config);
-        let gui_state = GuiState::new(&window, &device, surface_format);
+        let ray_inspector_state = RayInspectorState::new(&device);
+        let statistics_state = StatisticsState::new(&device);
+        let graphic_state = GraphicState::new(
+            size,
+            &device,
+            RENDER_TEXTURE_FORMAT,
+            ray_inspector_state.ray_path_buffer(),
+            statistics_state.statistics_buffer(),
+        );
+        let aov_state = AovState::new(&device, size);
+        let exposure_state = ExposureState::new(&device, &render_texture_view);
+        let post_state = PostState::new(
+            &device,
+            surface_settings.view_format,
+            &render_texture_view,
+            exposure_state.exposure_buffer(),
+            &aov_state,
+            surface_settings.is_transparent,
+        );
+        let taa_state = TaaState::new(
+            &device,
+            RENDER_TEXTURE_FORMAT,
+            size,
+            &render_texture_view,
+            &graphic_state,
+            &aov_state,
+        );
+        let axes_state = AxesState::new(
+            &device,
+            surface_settings.view_format,
+            &graphic_state,
+            &aov_state,
+        );
+        let preview_state = PreviewState::new(&device, &graphic_state);
+        let distance_slice_state = DistanceSliceState::new(&device, &graphic_state);
+        let collision_state = CollisionState::new(&device, &graphic_state);
+        let tiled_export_state = TiledExportState::new(
+            &device,
+            RENDER_TEXTURE_FORMAT,
+            NetworkNode::new(options.network_role.as_ref()),
+        );
+        let mut gui_state = GuiState::new(
+            &window,
+            &device,
+            surface_settings.view_format,
+            preview_state.preview_texture_view(),
+            distance_slice_state.distance_slice_texture_view(),
+            aov_state.display_texture_view(),
+            options.is_safe_mode,
+        );
+        if !options.is_safe_mode {
+            gui_state.apply_style(&options.gui_style);
+        }
+        if let Some(seed) = &options.preset_seed {
+            gui_state.apply_seeded_preset(seed);
+        }
 
         // Configure the surface for the first time
+        let config = surface_settings.surface_config(size, gui_state.gui_data().frame_latency());
         surface.configure(&device, &config);
 
         Ok(Self {
@@ -187,9 +517,33 @@ impl RenderState {
             device,
             queue,
             config,
+            surface_settings,
+            render_texture,
+            render_texture_view,
             graphic_state,
+            aov_state,
+            exposure_state,
+            post_state,
+            taa_state,
+            axes_state,
+            preview_state,
+            distance_slice_state,
+            collision_state,
+            ray_inspector_state,
+            statistics_state,
+            tiled_export_state,
+            bake_state: BakeState::new(),
             gui_state,
+            app_handle,
+            click_position: None,
+            camera_input: CameraInput::default(),
+            last_camera_frame: None,
+            is_panning: false,
+            modifiers: ModifiersState::default(),
+            fly_controls: FlyControls::new(),
             frametimes: LimitedQueue::with_capacity(5),
+            frame_governor: FrameGovernor::new(),
+            frame_slicer: FrameSlicer::new(),
         })
     }
 
@@ -213,6 +567,29 @@ impl RenderState {
             self.config.height = new_size.height;
             self.surface.configure(&self.device, &self.config);
 
+            self.render_texture =
+                Self::create_render_texture(&self.device, RENDER_TEXTURE_FORMAT, new_size);
+            self.render_texture_view = self
+                .render_texture
+                .create_view(&wgpu::TextureViewDescriptor::default());
+            self.aov_state.update_size(&self.device, new_size);
+            self.exposure_state
+                .update_render_texture(&self.device, &self.render_texture_view);
+            self.post_state.update_render_texture(
+                &self.device,
+                &self.render_texture_view,
+                &self.aov_state,
+            );
+            self.taa_state.update_render_texture(
+                &self.device,
+                RENDER_TEXTURE_FORMAT,
+                new_size,
+                &self.render_texture_view,
+                &self.aov_state,
+            );
+            self.axes_state
+                .update_depth_texture(&self.device, &self.aov_state);
+
             self.graphic_state
                 .update_screen_data(&self.queue, new_size.into());
             self.window.request_redraw();
@@ -222,6 +599,7 @@ impl RenderState {
     pub(crate) fn window_event(&mut self, event: &WindowEvent) {
         // Check if event was for the GUI
         let response = self.gui_state.window_event(&self.window, event);
+        self.keyboard_event(event);
         if self.gui_state.wants_pointer_input() || response.consumed {
             self.window.request_redraw();
             return;
@@ -233,11 +611,35 @@ impl RenderState {
                 button: MouseButton::Left,
                 ..
             } => match state {
-                ElementState::Pressed => self.graphic_state.enable_camera_rotation(),
-                ElementState::Released => self.graphic_state.disable_camera_rotation(),
+                ElementState::Pressed => {
+                    self.click_position = Some(self.graphic_state.cursor_position());
+                    self.graphic_state.enable_camera_rotation();
+                }
+                ElementState::Released => {
+                    self.graphic_state.disable_camera_rotation();
+                    self.click();
+                }
             },
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Middle,
+                ..
+            } => self.is_panning = state.is_pressed(),
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Right,
+                ..
+            } => match state {
+                ElementState::Pressed => self.graphic_state.start_roi_drawing(),
+                ElementState::Released => self.graphic_state.finish_roi_drawing(),
+            },
+            WindowEvent::CursorMoved { position, .. } => {
+                #[allow(clippy::cast_possible_truncation)]
+                self.graphic_state
+                    .update_cursor_position(Vector2(position.x as f32, position.y as f32));
+            }
             WindowEvent::MouseWheel { delta, .. } => {
-                let distance = match delta {
+                let lines = match delta {
                     MouseScrollDelta::LineDelta(_, dy) => *dy,
                     #[allow(clippy::cast_possible_truncation)]
                     MouseScrollDelta::PixelDelta(PhysicalPosition { y: dy, .. }) => {
@@ -245,30 +647,480 @@ impl RenderState {
                     }
                 };
 
-                self.graphic_state.zoom_camera(&self.queue, distance);
+                self.camera_input.add_zoom(lines);
                 self.window.request_redraw();
             }
             _ => {}
         }
     }
 
+    // Releases always count, so no key stays held when the GUI takes the keyboard in between
+    fn keyboard_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(key),
+                        state,
+                        ..
+                    },
+                ..
+            } if !state.is_pressed() || !self.gui_state.wants_keyboard_input() => {
+                self.fly_controls.key_event(*key, state.is_pressed());
+            }
+            WindowEvent::ModifiersChanged(modifiers) => self.modifiers = modifiers.state(),
+            WindowEvent::Focused(false) => self.fly_controls.release_keys(),
+            _ => {}
+        }
+    }
+
+    // Clicking a pixel in a debug view records its ray for the ray inspector
+    fn click(&mut self) {
+        let position = self.graphic_state.cursor_position();
+        if self.click_position.take() != Some(position)
+            || self.gui_state.gui_data().debug_view == DebugView::Off
+        {
+            return;
+        }
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        self.ray_inspector_state
+            .inspect_pixel(Vector2(position.0 as u32, position.1 as u32));
+    }
+
     pub(crate) fn device_event(&mut self, event: &DeviceEvent) {
         // Right is positive x and down is positive y
         if let DeviceEvent::MouseMotion { delta: (dx, dy) } = event {
             self.gui_state.mouse_motion((*dx, *dy));
-            if self.graphic_state.is_camera_rotatable() {
-                self.graphic_state.rotate_camera(
-                    &self.queue,
-                    #[allow(clippy::cast_possible_truncation)]
-                    Radians::from_degrees(-(*dx / 10.) as f32),
-                    #[allow(clippy::cast_possible_truncation)]
-                    Radians::from_degrees((*dy / 10.) as f32),
-                );
+            let is_rotatable = self.graphic_state.is_camera_rotatable();
+            if self.is_panning || (is_rotatable && self.modifiers.shift_key()) {
+                self.camera_input.add_pan((*dx, *dy));
+                self.window.request_redraw();
+            } else if is_rotatable {
+                self.camera_input.add_motion((*dx, *dy));
                 self.window.request_redraw();
             }
         }
     }
 
+    // Fractals are first rendered into the intermediate render texture, which is skipped once all
+    // progressive samples have been accumulated
+    fn raymarch(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        self.graphic_state.prepare_pipeline(&self.device);
+        let Some(load) = self
+            .graphic_state
+            .next_sample(&self.queue, self.gui_state.gui_data().into())
+        else {
+            return;
+        };
+        self.statistics_state.clear(&self.queue);
+
+        let render_size = self.graphic_state.render_size();
+        let bands = self.frame_slicer.bands(render_size.height);
+        for (i, band) in bands.iter().enumerate() {
+            // Every further band is a submission of its own, only the first one clears the targets
+            let band_load = if i == 0 {
+                load
+            } else {
+                let band_encoder = mem::replace(
+                    encoder,
+                    self.device
+                        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                            label: Some("raymarch_band_encoder"),
+                        }),
+                );
+                self.queue.submit(std::iter::once(band_encoder.finish()));
+                wgpu::LoadOp::Load
+            };
+
+            let [depth_attachment, normal_attachment, iterations_attachment] =
+                self.aov_state.color_attachments(band_load);
+            let mut raymarch_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("raymarch_pass"),
+                color_attachments: &[
+                    Some(wgpu::RenderPassColorAttachment {
+                        view: &self.render_texture_view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: band_load,
+                            store: wgpu::StoreOp::Store,
+                        },
+                    }),
+                    depth_attachment,
+                    normal_attachment,
+                    iterations_attachment,
+                ],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+
+            #[allow(clippy::cast_precision_loss)]
+            raymarch_pass.set_viewport(
+                0.,
+                0.,
+                render_size.width as f32,
+                render_size.height as f32,
+                0.,
+                1.,
+            );
+            raymarch_pass.set_scissor_rect(0, band.y, render_size.width, band.height);
+            self.graphic_state.render(&mut raymarch_pass);
+        }
+
+        self.statistics_state.copy_statistics(encoder);
+
+        if matches!(load, wgpu::LoadOp::Clear(_)) {
+            self.taa_state.resolve(
+                &self.queue,
+                encoder,
+                &self.render_texture,
+                self.graphic_state.camera_data(),
+                &self.gui_state.gui_data().into(),
+            );
+        }
+    }
+
+    // Renders the post processed frame once more into a texture kept by the snapshot
+    fn capture_requested_snapshot(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if !self.gui_state.take_snapshot_request() {
+            return;
+        }
+
+        let size = self.size();
+        let snapshot_texture =
+            create_snapshot_texture(&self.device, self.surface_settings.view_format, size);
+        let snapshot_view = snapshot_texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut snapshot_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("snapshot_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &snapshot_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+        self.post_state.render(&mut snapshot_pass);
+        drop(snapshot_pass);
+
+        self.gui_state.add_snapshot(
+            &self.device,
+            &snapshot_view,
+            self.graphic_state.camera_data(),
+            size,
+        );
+    }
+
+    // Moves the camera by the mouse input since the last frame, or by its share when smoothed, and by
+    // the keys held for flying
+    fn apply_camera_input(&mut self) {
+        let now = Instant::now();
+        let elapsed = self
+            .last_camera_frame
+            .replace(now)
+            .map_or(MAX_CAMERA_FRAME_TIME, |last_frame| {
+                (now - last_frame).min(MAX_CAMERA_FRAME_TIME)
+            });
+        let camera_input = self
+            .camera_input
+            .take_share(self.gui_state.gui_data().camera_input_share(elapsed));
+
+        let previous_camera = self.graphic_state.camera_data();
+        let safe_step = if self.gui_state.gui_data().is_camera_collision {
+            self.collision_state.safe_step(previous_camera.position())
+        } else {
+            None
+        };
+        let mut camera_data = previous_camera;
+        let mut is_moved = false;
+        if let Some((dx, dy)) = camera_input.motion {
+            #[allow(clippy::cast_possible_truncation)]
+            let delta_angles = Vector2(
+                Radians::from_degrees(-(dx / 10.) as f32),
+                Radians::from_degrees((dy / 10.) as f32),
+            );
+            camera_data = camera_data.rotated(delta_angles);
+            is_moved = true;
+        }
+
+        match camera_data.mode {
+            CameraMode::Orbit => {
+                // Target follows the cursor at its distance, where the view is twice the view scale
+                // of distance high
+                if let Some((dx, dy)) = camera_input.pan_motion {
+                    #[allow(clippy::cast_precision_loss)]
+                    let scale = 2. * camera_data.view_scale() * camera_data.origin_distance
+                        / self.window.inner_size().height.max(1) as f32;
+                    #[allow(clippy::cast_possible_truncation)]
+                    let offset = Vector2(-dx as f32, dy as f32) * scale;
+                    camera_data = camera_data.pan(offset);
+                    is_moved = true;
+                }
+                // Zooming in slows down towards the surface just like towards the minimum distance,
+                // while zooming out is left as it is so the camera can always back off
+                if let Some(lines) = camera_input.zoom_lines {
+                    let zoom_speed = self.gui_state.gui_data().zoom_speed;
+                    let min_distance = safe_step.filter(|_| lines > 0.).map_or(
+                        camera_data.min_distance,
+                        |safe_step| {
+                            camera_data
+                                .min_distance
+                                .max(camera_data.origin_distance - safe_step.distance)
+                        },
+                    );
+                    camera_data = CameraData {
+                        min_distance,
+                        ..camera_data
+                    }
+                    .zoomed(lines, zoom_speed);
+                    camera_data.min_distance = previous_camera.min_distance;
+                    is_moved = true;
+                }
+            }
+            // Scrolling changes the speed instead, as the free-fly camera has nothing to zoom towards,
+            // and the keys already move it sideways so it is not panned
+            CameraMode::FreeFly => {
+                if let Some(lines) = camera_input.zoom_lines {
+                    self.fly_controls.scale_speed(lines);
+                }
+                if let Some(offset) = self.fly_controls.offset(now) {
+                    camera_data = camera_data.fly(offset);
+                    is_moved = true;
+                }
+            }
+        }
+
+        if let Some(safe_step) = safe_step {
+            camera_data = limit_camera_step(previous_camera, camera_data, safe_step);
+        }
+        if is_moved {
+            self.graphic_state.update_camera(&self.queue, camera_data);
+        }
+    }
+
+    // Passes the settings from the GUI on to every render state
+    fn update_render_data(&mut self) {
+        self.apply_camera_input();
+        self.gui_state.advance_animation();
+        self.gui_state.apply_bindings();
+        self.compile_requested_sources();
+        if let Some(camera_data) = self.gui_state.take_restored_camera() {
+            self.graphic_state.update_camera(&self.queue, camera_data);
+        }
+        if let Some(request) = self.gui_state.take_keyframe_request() {
+            self.gui_state
+                .record_keyframe(request, self.graphic_state.camera_data());
+            self.bake_state.clear();
+        }
+        if self.gui_state.take_orbit_request() {
+            self.gui_state.play_orbit(self.graphic_state.camera_data());
+            self.bake_state.clear();
+        }
+        if let Some(camera_data) = self.gui_state.playback_camera() {
+            self.graphic_state.update_camera(&self.queue, camera_data);
+        }
+        // Baked frames replace the adapting iteration count and exposure during playback
+        let baked_frame = self
+            .gui_state
+            .playback_time()
+            .and_then(|time| self.bake_state.baked_frame(time));
+        let drag_preview_scale = self.gui_state.drag_preview_scale();
+        let mut options_data = self.frame_governor.govern(
+            Instant::now(),
+            self.gui_state.gui_data().frame_budget(),
+            &self.gui_state.gui_data().into(),
+            self.graphic_state.camera_data(),
+            drag_preview_scale.is_some(),
+        );
+        if let Some(baked_frame) = baked_frame {
+            options_data.max_iterations = baked_frame.max_iterations;
+        }
+        self.graphic_state
+            .set_render_scale(drag_preview_scale.unwrap_or(1.));
+        self.graphic_state
+            .update_options(&self.queue, &options_data);
+        self.graphic_state
+            .update_lighting(&self.queue, self.gui_state.gui_data().into());
+        self.graphic_state
+            .update_scene(&self.queue, &self.gui_state.gui_data().into());
+        self.exposure_state
+            .update_exposure_data(&self.queue, self.gui_state.gui_data().into());
+        let render_scale = self.graphic_state.render_scale();
+        let mut post_data = PostData {
+            render_scale,
+            ..self.gui_state.gui_data().into()
+        };
+        if let Some(baked_frame) = baked_frame
+            && post_data.is_auto_exposure
+        {
+            post_data.is_auto_exposure = false;
+            post_data.exposure += baked_frame.exposure.log2();
+        }
+        self.post_state.update_post_data(&self.queue, post_data);
+        self.aov_state
+            .update_aov_data(&self.queue, self.gui_state.gui_data().into());
+        self.axes_state.update_axes_data(
+            &self.queue,
+            AxesData {
+                render_scale,
+                ..self.gui_state.gui_data().into()
+            },
+        );
+        self.taa_state
+            .update_taa_data(self.gui_state.gui_data().into());
+        self.preview_state.update_preview_data(
+            &self.queue,
+            self.gui_state.gui_data().into(),
+            &self.graphic_state.options_data(),
+            self.graphic_state.camera_data(),
+        );
+        self.distance_slice_state
+            .update_distance_slice_data(&self.queue, self.gui_state.gui_data().into());
+        self.collision_state
+            .update_is_enabled(self.gui_state.gui_data().is_camera_collision);
+        self.update_frame_latency();
+    }
+
+    // Takes effect from the next acquired surface texture, as the surface has to be reconfigured
+    // Every request is read back on its own, as the errors of a readback can not be cloned
+    fn answer_capture_requests(&self) {
+        for sender in self.app_handle.take_capture_requests() {
+            let capture = capture::capture_frame(
+                &self.device,
+                &self.queue,
+                &self.render_texture,
+                self.aov_state.layer_textures(),
+                self.graphic_state.render_size(),
+            );
+            // Requester may have stopped waiting
+            sender.send(capture).ok();
+        }
+    }
+
+    fn update_frame_latency(&mut self) {
+        let frame_latency = self
+            .surface_settings
+            .frame_latency(self.gui_state.gui_data().frame_latency());
+        if frame_latency != self.config.desired_maximum_frame_latency {
+            self.config.desired_maximum_frame_latency = frame_latency;
+            self.surface.configure(&self.device, &self.config);
+        }
+    }
+
+    // Hot-swaps the pipelines of the groups compiled from user input, keeping the last valid ones
+    // on errors
+    fn compile_requested_sources(&mut self) {
+        if let Some(source) = self.gui_state.take_formula_request() {
+            let result = self.graphic_state.compile_user_source(
+                &self.device,
+                FractalGroup::CustomFormula,
+                source,
+            );
+            if let Err(error) = &result {
+                log::warn!("Failed to compile the custom formula: {error}");
+            }
+            self.gui_state
+                .set_formula_result(result.map_err(Into::into));
+        }
+        if let Some(source) = self.gui_state.take_shader_file_request() {
+            let result = self.graphic_state.compile_user_source(
+                &self.device,
+                FractalGroup::ShaderFile,
+                source,
+            );
+            if let Err(error) = &result {
+                log::warn!("Failed to compile the shader file: {error}");
+            }
+            self.gui_state
+                .set_shader_file_result(result.map_err(Into::into));
+        }
+    }
+
+    // Samples the scene on the grid and extracts its surface, blocking until the mesh is done
+    fn extract_requested_mesh(&mut self) {
+        let Some(settings) = self.gui_state.take_mesh_request() else {
+            return;
+        };
+
+        let result = sample_distance_grid(&self.device, &self.queue, &self.graphic_state, settings)
+            .map(|distance_grid| distance_grid.marching_cubes());
+        match &result {
+            Ok(mesh) => log::info!(
+                "Extracted a mesh of {} triangles at a resolution of {}",
+                mesh.triangles.len(),
+                settings.resolution
+            ),
+            Err(error) => log::warn!("Failed to extract the mesh: {error}"),
+        }
+        self.gui_state.set_mesh_result(result);
+    }
+
+    // Samples the scene on the grid and writes the voxels inside it, blocking like the mesh
+    fn export_requested_voxels(&mut self) {
+        let Some(settings) = self.gui_state.take_voxel_export_request() else {
+            return;
+        };
+
+        let result = export_voxels(&self.device, &self.queue, &self.graphic_state, settings);
+        if let Err(error) = &result {
+            log::warn!("Failed to export the voxels: {error}");
+        }
+        self.gui_state.set_voxel_export_result(result);
+    }
+
+    // Renders the next tile of a running export, after presenting so the frame is not held back
+    fn advance_tiled_export(&mut self) {
+        if let Some(settings) = self.gui_state.take_tiled_export_request() {
+            self.tiled_export_state.start_export(&ExportJob::new(
+                &self.gui_state.gui_data(),
+                self.graphic_state.camera_data(),
+                settings,
+            ));
+        }
+        self.tiled_export_state
+            .advance(&self.device, &self.queue, &mut self.graphic_state);
+        self.gui_state
+            .set_tiled_export_status(self.tiled_export_state.status());
+    }
+
+    // Renders the next frame of a running bake, after presenting like the tiled export
+    fn advance_bake(&mut self) {
+        if let Some(camera_path) = self.gui_state.take_bake_request() {
+            self.bake_state
+                .start_bake(&self.gui_state.gui_data(), camera_path, self.size());
+        }
+        self.bake_state.advance(
+            &self.device,
+            &self.queue,
+            &mut self.graphic_state,
+            &self.tiled_export_state,
+        );
+        self.gui_state.set_bake_status(self.bake_state.status());
+    }
+
+    // Panoramas cover 360 by 180 degrees, so they only keep square pixels at a 2:1 aspect ratio
+    // Note: needs to happen after presenting, as the resize recreates the render textures
+    fn lock_aspect_ratio(&mut self) {
+        if self.gui_state.gui_data().projection != Projection::Equirectangular {
+            return;
+        }
+
+        let size = self.size();
+        let locked_size = PhysicalSize::new(size.width, size.width / 2);
+        if size != locked_size
+            && let Some(new_size) = self.window.request_inner_size(locked_size)
+        {
+            // Size was applied immediately, in which case a resize event is not guaranteed
+            self.resize(new_size);
+        }
+    }
+
     // Due to weird ass bullshit this check is needed sometime i guess idk wtf
     pub(crate) fn check_surface_missized(
         &self,
@@ -288,14 +1140,59 @@ impl RenderState {
 
     /// ## Errors
     /// - `RenderError::Surface(SurfaceError)` when getting current surface texture failed
-    /// - `RenderError::GUINotConfigured(GUINotConfiguredError)` when tried to render unconfigured GUI
+    /// - `RenderError::SurfaceMissized(SurfaceMissizedError)` when the surface does not match the window size
+    fn acquire_surface_texture(&self) -> Result<wgpu::SurfaceTexture, RenderError> {
+        let surface_texture = self.surface.get_current_texture()?;
+        self.check_surface_missized(&surface_texture)?;
+
+        Ok(surface_texture)
+    }
+
+    // Applies the pending settings and updates the GUI, whose uploads are recorded into the encoder
+    fn prepare_frame(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        screen_descriptor: &ScreenDescriptor,
+    ) -> GuiFrame {
+        self.gui_state.update_warnings(detect_artifacts(
+            &self.gui_state.gui_data().into(),
+            self.graphic_state.camera_data(),
+            self.graphic_state.screen_data(),
+            self.statistics_state.statistics(),
+        ));
+        self.gui_state
+            .update_camera_data(self.graphic_state.camera_data());
+        let (pending_gui_data, pending_camera_data) = self.app_handle.take_pending();
+        if let Some(gui_data) = pending_gui_data {
+            self.gui_state.set_gui_data(&gui_data);
+        }
+        if let Some(camera_data) = pending_camera_data {
+            self.graphic_state.update_camera(&self.queue, camera_data);
+        }
+        let gui_frame = self.gui_state.update_gui(
+            &self.window,
+            &self.device,
+            &self.queue,
+            encoder,
+            screen_descriptor,
+        );
+        self.update_render_data();
+        self.app_handle
+            .publish(&self.gui_state.gui_data(), self.graphic_state.camera_data());
+        crash::record_state(&CrashState {
+            gui_data: self.gui_state.gui_data(),
+            camera_data: self.graphic_state.camera_data(),
+        });
+
+        gui_frame
+    }
+
+    /// ## Errors
+    /// - `RenderError::Surface(SurfaceError)` when getting current surface texture failed
+    /// - `RenderError::SurfaceMissized(SurfaceMissizedError)` when the surface does not match the window size
     pub(crate) fn render(&mut self) -> Result<(), RenderError> {
         let start_time = Instant::now();
-
-        let surface_texture = self.surface.get_current_texture()?;
-        let view = surface_texture
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+        self.frame_slicer.begin_frame(start_time);
 
         let screen_descriptor = ScreenDescriptor {
             size_in_pixels: [self.size().width, self.size().height],
@@ -310,17 +1207,53 @@ impl RenderState {
             });
 
         // Prepare everything for render
-        self.gui_state.update_gui(
-            &self.window,
-            &self.device,
-            &self.queue,
+        let gui_frame = self.prepare_frame(&mut encoder, &screen_descriptor);
+
+        // Recording a ray needs a raymarch pass, even when all samples have been accumulated
+        if self.ray_inspector_state.prepare(&self.queue) {
+            self.graphic_state.reset_samples();
+        }
+
+        // Drawing and rendering calls happen here
+        self.raymarch(&mut encoder);
+        self.ray_inspector_state.copy_ray_path(&mut encoder);
+        self.aov_state.render_display(&mut encoder);
+        self.preview_state
+            .compute(&mut encoder, &self.graphic_state);
+        self.distance_slice_state
+            .compute(&self.device, &mut encoder, &self.graphic_state);
+        self.collision_state
+            .compute(&self.device, &mut encoder, &self.graphic_state);
+
+        // Histogram of the accumulated image drives the auto exposure used by the post pass
+        self.exposure_state.compute(
             &mut encoder,
-            &screen_descriptor,
+            (self.render_texture.width(), self.render_texture.height()),
         );
-        self.graphic_state
-            .update_options(&self.queue, self.gui_state.gui_data().into());
 
-        // Drawing and rendering calls happen here
+        // Offscreen passes are submitted before waiting for a surface texture, so the GPU starts on
+        // them while earlier frames are still queued for presentation
+        self.queue.submit(std::iter::once(encoder.finish()));
+        let surface_texture = match self.acquire_surface_texture() {
+            Ok(surface_texture) => surface_texture,
+            Err(error) => {
+                // GUI uploads were submitted with the offscreen passes, so its textures can be freed
+                self.gui_state.finish_frame(gui_frame);
+                return Err(error);
+            }
+        };
+        let view = surface_texture
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor {
+                format: Some(self.surface_settings.view_format),
+                ..Default::default()
+            });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("present_encoder"),
+            });
         {
             // GUI rendering requires 'static lifetime render pass, so we forget the lifetime
             let mut render_pass = encoder
@@ -330,7 +1263,7 @@ impl RenderState {
                         view: &view,
                         resolve_target: None,
                         ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                            load: wgpu::LoadOp::Clear(self.surface_settings.clear_color()),
                             store: wgpu::StoreOp::Discard,
                         },
                     })],
@@ -340,21 +1273,41 @@ impl RenderState {
                 })
                 .forget_lifetime();
 
-            self.check_surface_missized(&surface_texture)?;
-
-            self.graphic_state.render(&mut render_pass);
+            self.post_state.render(&mut render_pass);
+            self.axes_state.render(&mut render_pass);
             // Execute GUI rendering last so it stays on top of our graphics and because it consumes the render_pass
             self.gui_state
-                .render(&mut render_pass, &screen_descriptor)?;
+                .render(&mut render_pass, &gui_frame, &screen_descriptor);
 
             drop(render_pass);
         }
 
+        self.capture_requested_snapshot(&mut encoder);
+
         // Submit the queue to the GPU and present the changed surface
         self.queue.submit(std::iter::once(encoder.finish()));
+        self.gui_state.finish_frame(gui_frame);
         self.window.pre_present_notify();
         surface_texture.present();
 
+        if self.gui_state.take_aov_export_request()
+            && let Err(error) = self.aov_state.export(&self.device, &self.queue)
+        {
+            log::warn!("Failed to export the output layers: {error}");
+        }
+        self.answer_capture_requests();
+        self.extract_requested_mesh();
+        self.export_requested_voxels();
+        self.statistics_state.read_statistics(&self.device);
+        self.collision_state.read_camera_distance(&self.device);
+        self.ray_inspector_state.read_ray_path(&self.device);
+        if let Some(ray_path) = self.ray_inspector_state.take_ray_path() {
+            self.gui_state.show_ray_path(ray_path);
+        }
+        self.advance_tiled_export();
+        self.advance_bake();
+        self.lock_aspect_ratio();
+
         self.frametimes
             .push(Instant::now().duration_since(start_time));
         // println!(
@@ -0,0 +1,383 @@
+use egui_wgpu::wgpu;
+use std::{fs, path::PathBuf};
+use strum::IntoEnumIterator as _;
+use winit::dpi::PhysicalSize;
+
+use crate::data::options::AovLayer;
+use crate::data::{AovData, GuiData};
+use crate::error::AovExportError;
+use crate::render::capture::read_texture;
+use crate::util::buffer::{
+    ResourceGroup, ResourceGroupDescriptor, ResourceGroupInit as _, ResourceGroupLayoutEntry,
+};
+use crate::util::shader::{
+    WGSLShaderModuleDescriptor, WGSLShaderModuleInit as _, WGSLShaderSource,
+};
+use crate::util::uniform::{UniformBuffer, UniformBufferDescriptor, UniformBufferInit as _};
+
+// Formats of the auxiliary color targets of the fractal pipelines, in the order of the layer ids
+// Note: together with the render texture they fill the default limit of 32 bytes per sample
+pub(crate) const AOV_FORMATS: [wgpu::TextureFormat; 3] = [
+    wgpu::TextureFormat::R32Float,
+    wgpu::TextureFormat::Rgba32Float,
+    wgpu::TextureFormat::R32Float,
+];
+// Has to match AOV_DISPLAY_SIZE in aov.wgsl
+const AOV_DISPLAY_TEXTURE_SIZE: u32 = 512;
+const AOV_DISPLAY_TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+// Portable float map, with rows stored from the bottom up
+#[must_use]
+pub(crate) fn encode_pfm(width: u32, height: u32, channels: usize, values: &[f32]) -> Vec<u8> {
+    let identifier = if channels == 1 { "Pf" } else { "PF" };
+    // Negative scale marks the data as little endian
+    let mut bytes = format!("{identifier}\n{width} {height}\n-1.0\n").into_bytes();
+
+    let row_length = width as usize * channels;
+    for row in values.chunks(row_length).rev() {
+        for value in row {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    bytes
+}
+
+#[must_use]
+fn export_path(layer: AovLayer) -> PathBuf {
+    let name = match layer {
+        AovLayer::Depth => "depth",
+        AovLayer::Normal => "normal",
+        AovLayer::Iterations => "iterations",
+    };
+
+    PathBuf::from(format!("aov_{name}.pfm"))
+}
+
+// Auxiliary output layers, written by the raymarch pass next to the color
+// Unlike the color they are not accumulated, as averaging depths or normals across an edge gives
+// values belonging to neither surface, so every sample simply overwrites them
+#[derive(Clone, Debug)]
+pub(crate) struct AovState {
+    aov_data: AovData,
+    aov_uniform_buffer: UniformBuffer,
+    layer_textures: [wgpu::Texture; 3],
+    layer_texture_views: [wgpu::TextureView; 3],
+    display_texture_view: wgpu::TextureView,
+    resource_group: ResourceGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl AovState {
+    #[must_use]
+    fn create_layer_textures(device: &wgpu::Device, size: PhysicalSize<u32>) -> [wgpu::Texture; 3] {
+        AOV_FORMATS.map(|format| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("aov_texture"),
+                size: wgpu::Extent3d {
+                    width: size.width,
+                    height: size.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            })
+        })
+    }
+
+    #[must_use]
+    fn create_display_texture_view(device: &wgpu::Device) -> wgpu::TextureView {
+        let display_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("aov_display_texture"),
+            size: wgpu::Extent3d {
+                width: AOV_DISPLAY_TEXTURE_SIZE,
+                height: AOV_DISPLAY_TEXTURE_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: AOV_DISPLAY_TEXTURE_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        display_texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    #[must_use]
+    fn resources<'a>(
+        layer_texture_views: &'a [wgpu::TextureView; 3],
+        aov_uniform_buffer: &'a UniformBuffer,
+    ) -> [wgpu::BindingResource<'a>; 4] {
+        let [depth_view, normal_view, iterations_view] = layer_texture_views;
+
+        [
+            wgpu::BindingResource::TextureView(depth_view),
+            wgpu::BindingResource::TextureView(normal_view),
+            wgpu::BindingResource::TextureView(iterations_view),
+            aov_uniform_buffer.as_entire_binding(),
+        ]
+    }
+
+    #[must_use]
+    fn create_aov_group(
+        device: &wgpu::Device,
+        resources: &[wgpu::BindingResource],
+    ) -> ResourceGroup {
+        // 32 bit float textures are not filterable, which is fine as they are only loaded from
+        let layer_entry = ResourceGroupLayoutEntry {
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+
+        device.create_resource_group(&ResourceGroupDescriptor {
+            label: Some("aov"),
+            resources,
+            entries: &[
+                layer_entry,
+                layer_entry,
+                layer_entry,
+                ResourceGroupLayoutEntry {
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    #[must_use]
+    fn create_display_pipeline(
+        device: &wgpu::Device,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+    ) -> wgpu::RenderPipeline {
+        let aov_shader = device.create_wgsl_shader_module(WGSLShaderModuleDescriptor {
+            label: Some("aov_shader"),
+            main: shader_source!("aov.wgsl"),
+            dependencies: &[shader_source!("dependencies/vertex.wgsl")],
+        });
+
+        let aov_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("aov_pipeline_layout"),
+            bind_group_layouts,
+            push_constant_ranges: &[],
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("aov_render_pipeline"),
+            layout: Some(&aov_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &aov_shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &aov_shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: AOV_DISPLAY_TEXTURE_FORMAT,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    #[must_use]
+    pub(crate) fn new(device: &wgpu::Device, size: PhysicalSize<u32>) -> Self {
+        let aov_data = GuiData::default().into();
+        let aov_uniform_buffer = device.create_uniform_buffer(&UniformBufferDescriptor {
+            label: Some("aov_uniform_buffer"),
+            data_descriptor: aov_data,
+        });
+
+        let layer_textures = Self::create_layer_textures(device, size);
+        let layer_texture_views = layer_textures
+            .each_ref()
+            .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()));
+        let display_texture_view = Self::create_display_texture_view(device);
+
+        let resource_group = Self::create_aov_group(
+            device,
+            &Self::resources(&layer_texture_views, &aov_uniform_buffer),
+        );
+        let pipeline = Self::create_display_pipeline(device, &[resource_group.bind_group_layout()]);
+
+        Self {
+            aov_data,
+            aov_uniform_buffer,
+            layer_textures,
+            layer_texture_views,
+            display_texture_view,
+            resource_group,
+            pipeline,
+        }
+    }
+
+    // Selected layer mapped into displayable colors, shown by the GUI
+    #[must_use]
+    pub(crate) fn display_texture_view(&self) -> &wgpu::TextureView {
+        &self.display_texture_view
+    }
+
+    // In the order of the layer ids
+    #[must_use]
+    pub(crate) fn layer_textures(&self) -> &[wgpu::Texture; 3] {
+        &self.layer_textures
+    }
+
+    #[must_use]
+    pub(crate) fn layer_texture_view(&self, layer: AovLayer) -> &wgpu::TextureView {
+        &self.layer_texture_views[layer.id() as usize]
+    }
+
+    // Color attachments following the render texture in the raymarch pass
+    #[must_use]
+    pub(crate) fn color_attachments(
+        &self,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) -> [Option<wgpu::RenderPassColorAttachment<'_>>; 3] {
+        self.layer_texture_views.each_ref().map(|view| {
+            Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })
+        })
+    }
+
+    // Needs to be called whenever the render texture is recreated, e.g. on resize
+    pub(crate) fn update_size(&mut self, device: &wgpu::Device, size: PhysicalSize<u32>) {
+        self.layer_textures = Self::create_layer_textures(device, size);
+        self.layer_texture_views = self
+            .layer_textures
+            .each_ref()
+            .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()));
+
+        device.rebind_resource_group(
+            &mut self.resource_group,
+            Some("aov"),
+            &Self::resources(&self.layer_texture_views, &self.aov_uniform_buffer),
+        );
+    }
+
+    pub(crate) fn update_aov_data(&mut self, queue: &wgpu::Queue, new_aov_data: AovData) {
+        self.aov_data = new_aov_data;
+        self.aov_uniform_buffer.update_buffer(queue, self.aov_data);
+    }
+
+    // Needs to be called after the raymarch pass, skipped while the layers are not shown
+    pub(crate) fn render_display(&self, encoder: &mut wgpu::CommandEncoder) {
+        if !self.aov_data.is_enabled {
+            return;
+        }
+
+        let mut display_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("aov_display_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.display_texture_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+        display_pass.set_pipeline(&self.pipeline);
+        display_pass.set_bind_group(0, self.resource_group.bind_group(), &[]);
+
+        display_pass.draw(0..3, 0..2);
+    }
+
+    /// Writes every layer into a portable float map in the working directory, blocking until done
+    ///
+    /// ## Errors
+    /// - `AovExportError::Poll(PollError)` when waiting for the copy failed
+    /// - `AovExportError::BufferAsync(BufferAsyncError)` when the copy could not be mapped
+    /// - `AovExportError::Io(io::Error)` when writing a file failed
+    pub(crate) fn export(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> Result<(), AovExportError> {
+        for layer in AovLayer::iter() {
+            let texture = &self.layer_textures[layer.id() as usize];
+            let size = PhysicalSize::new(texture.width(), texture.height());
+            let bytes = read_texture::<AovExportError>(device, queue, texture, size)?;
+            let values = bytemuck::pod_collect_to_vec::<u8, f32>(&bytes);
+
+            // Normals are stored with an unused fourth channel, which the float map has no room for
+            let components = texture.format().components() as usize;
+            let channels = components.min(3);
+            let values = values
+                .chunks(components)
+                .flat_map(|pixel| &pixel[..channels])
+                .copied()
+                .collect::<Vec<_>>();
+
+            fs::write(
+                export_path(layer),
+                encode_pfm(texture.width(), texture.height(), channels, &values),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_pfm() {
+        let bytes = encode_pfm(2, 2, 1, &[1., 2., 3., 4.]);
+        let header = b"Pf\n2 2\n-1.0\n";
+
+        assert_eq!(&bytes[..header.len()], header);
+        let values = bytemuck::pod_collect_to_vec::<u8, f32>(&bytes[header.len()..]);
+        // Bottom row comes first
+        assert_eq!(values, [3., 4., 1., 2.]);
+    }
+}
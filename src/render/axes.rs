@@ -0,0 +1,207 @@
+use egui_wgpu::wgpu;
+
+use crate::data::options::AovLayer;
+use crate::data::{AxesData, GuiData};
+use crate::render::aov::AovState;
+use crate::render::graphics::GraphicState;
+use crate::util::buffer::{
+    ResourceGroup, ResourceGroupDescriptor, ResourceGroupInit as _, ResourceGroupLayoutEntry,
+};
+use crate::util::shader::{
+    WGSLShaderModuleDescriptor, WGSLShaderModuleInit as _, WGSLShaderSource,
+};
+use crate::util::uniform::{UniformBuffer, UniformBufferDescriptor, UniformBufferInit as _};
+
+// Has to match MAX_TICKS_PER_SIDE in axes.wgsl
+const MAX_TICKS_PER_SIDE: u32 = 32;
+// Three axis lines followed by the ticks on both halves of every axis, two vertices per line
+const AXES_VERTEX_COUNT: u32 = 2 * (3 + 3 * 2 * MAX_TICKS_PER_SIDE);
+
+// Coordinate axes rasterized as lines on top of the post processed image, so they stay one pixel
+// wide at any zoom, and hidden behind the fractal using the depth layer
+#[derive(Clone, Debug)]
+pub(crate) struct AxesState {
+    axes_data: AxesData,
+    axes_uniform_buffer: UniformBuffer,
+    screen_uniform_buffer: UniformBuffer,
+    camera_uniform_buffer: UniformBuffer,
+    resource_group: ResourceGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl AxesState {
+    #[must_use]
+    fn resources<'a>(
+        screen_uniform_buffer: &'a UniformBuffer,
+        camera_uniform_buffer: &'a UniformBuffer,
+        axes_uniform_buffer: &'a UniformBuffer,
+        depth_texture_view: &'a wgpu::TextureView,
+    ) -> [wgpu::BindingResource<'a>; 4] {
+        [
+            screen_uniform_buffer.as_entire_binding(),
+            camera_uniform_buffer.as_entire_binding(),
+            axes_uniform_buffer.as_entire_binding(),
+            wgpu::BindingResource::TextureView(depth_texture_view),
+        ]
+    }
+
+    #[must_use]
+    fn create_axes_group(
+        device: &wgpu::Device,
+        resources: &[wgpu::BindingResource],
+    ) -> ResourceGroup {
+        let uniform_entry = ResourceGroupLayoutEntry {
+            visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+
+        device.create_resource_group(&ResourceGroupDescriptor {
+            label: Some("axes"),
+            resources,
+            entries: &[
+                uniform_entry,
+                uniform_entry,
+                uniform_entry,
+                ResourceGroupLayoutEntry {
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    #[must_use]
+    fn create_axes_pipeline(
+        device: &wgpu::Device,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        let axes_shader = device.create_wgsl_shader_module(WGSLShaderModuleDescriptor {
+            label: Some("axes_shader"),
+            main: shader_source!("axes.wgsl"),
+            dependencies: &[],
+        });
+
+        let axes_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("axes_pipeline_layout"),
+            bind_group_layouts,
+            push_constant_ranges: &[],
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("axes_render_pipeline"),
+            layout: Some(&axes_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &axes_shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &axes_shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    #[must_use]
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        graphic_state: &GraphicState,
+        aov_state: &AovState,
+    ) -> Self {
+        let axes_data = GuiData::default().into();
+        let axes_uniform_buffer = device.create_uniform_buffer(&UniformBufferDescriptor {
+            label: Some("axes_uniform_buffer"),
+            data_descriptor: axes_data,
+        });
+        let screen_uniform_buffer = graphic_state.screen_uniform_buffer().clone();
+        let camera_uniform_buffer = graphic_state.camera_uniform_buffer().clone();
+
+        let resource_group = Self::create_axes_group(
+            device,
+            &Self::resources(
+                &screen_uniform_buffer,
+                &camera_uniform_buffer,
+                &axes_uniform_buffer,
+                aov_state.layer_texture_view(AovLayer::Depth),
+            ),
+        );
+        let pipeline =
+            Self::create_axes_pipeline(device, &[resource_group.bind_group_layout()], format);
+
+        Self {
+            axes_data,
+            axes_uniform_buffer,
+            screen_uniform_buffer,
+            camera_uniform_buffer,
+            resource_group,
+            pipeline,
+        }
+    }
+
+    // Needs to be called whenever the output layers are recreated, e.g. on resize
+    pub(crate) fn update_depth_texture(&mut self, device: &wgpu::Device, aov_state: &AovState) {
+        device.rebind_resource_group(
+            &mut self.resource_group,
+            Some("axes"),
+            &Self::resources(
+                &self.screen_uniform_buffer,
+                &self.camera_uniform_buffer,
+                &self.axes_uniform_buffer,
+                aov_state.layer_texture_view(AovLayer::Depth),
+            ),
+        );
+    }
+
+    pub(crate) fn update_axes_data(&mut self, queue: &wgpu::Queue, new_axes_data: AxesData) {
+        self.axes_data = new_axes_data;
+        self.axes_uniform_buffer
+            .update_buffer(queue, self.axes_data);
+    }
+
+    pub(crate) fn render(&self, render_pass: &mut wgpu::RenderPass) {
+        if !self.axes_data.is_enabled {
+            return;
+        }
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, self.resource_group.bind_group(), &[]);
+
+        render_pass.draw(0..AXES_VERTEX_COUNT, 0..1);
+    }
+}
@@ -0,0 +1,155 @@
+use egui_wgpu::wgpu;
+use std::{collections::VecDeque, time::Instant};
+use winit::dpi::PhysicalSize;
+
+use crate::data::{
+    GuiData,
+    bake::{BakedFrame, BakedTimeline, FrameMeasurement, frame_times, settled_exposure},
+    camera_path::CameraPath,
+    tiles::{ExportJob, TILE_SIZE, TiledExportSettings},
+};
+use crate::render::{graphics::GraphicState, tiled::TiledExportState};
+
+// Shown by the GUI next to the camera path playback
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct BakeStatus {
+    // Baked and all frames of the running bake
+    pub(crate) progress: Option<(usize, usize)>,
+    pub(crate) baked_frames: Option<usize>,
+}
+
+// Camera path being baked, with the scene fixed when the bake started
+#[derive(Clone, Debug)]
+struct Bake {
+    gui_data: GuiData,
+    camera_path: CameraPath,
+    settings: TiledExportSettings,
+    // Frames left to render, one per frame of the window
+    times: VecDeque<f32>,
+    frame_count: usize,
+    measurements: Vec<FrameMeasurement>,
+}
+
+// Renders every frame of the camera path offscreen before playback, measuring how long each takes
+// and what the auto exposure settles on, so the played animation uses the baked values instead
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BakeState {
+    bake: Option<Bake>,
+    timeline: Option<BakedTimeline>,
+}
+
+impl BakeState {
+    #[must_use]
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub(crate) fn status(&self) -> BakeStatus {
+        BakeStatus {
+            progress: self
+                .bake
+                .as_ref()
+                .map(|bake| (bake.measurements.len(), bake.frame_count)),
+            baked_frames: self
+                .timeline
+                .as_ref()
+                .map(|timeline| timeline.frames().len()),
+        }
+    }
+
+    // Baked values of the frame playing at the time, if the camera path was baked
+    #[must_use]
+    pub(crate) fn baked_frame(&self, time: f32) -> Option<BakedFrame> {
+        self.timeline.as_ref()?.frame_at(time)
+    }
+
+    // Needs to be called whenever the camera path changes, as the baked frames no longer match it
+    pub(crate) fn clear(&mut self) {
+        self.bake = None;
+        self.timeline = None;
+    }
+
+    // Frames are rendered at a tile's size with the aspect ratio of the screen, which is enough to
+    // compare their render times and brightness
+    pub(crate) fn start_bake(
+        &mut self,
+        gui_data: &GuiData,
+        camera_path: CameraPath,
+        size: PhysicalSize<u32>,
+    ) {
+        let (width, height) = (size.width.max(1), size.height.max(1));
+        let settings = if width >= height {
+            TiledExportSettings {
+                width: TILE_SIZE,
+                height: (TILE_SIZE * height / width).max(1),
+                samples: 1,
+            }
+        } else {
+            TiledExportSettings {
+                width: (TILE_SIZE * width / height).max(1),
+                height: TILE_SIZE,
+                samples: 1,
+            }
+        };
+        let times = VecDeque::from(frame_times(camera_path.duration(), camera_path.frame_rate));
+
+        self.timeline = None;
+        self.bake = Some(Bake {
+            gui_data: *gui_data,
+            camera_path,
+            settings,
+            frame_count: times.len(),
+            times,
+            measurements: Vec::new(),
+        });
+    }
+
+    // Renders a single frame of the camera path per call, so the window stays responsive
+    pub(crate) fn advance(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        graphic_state: &mut GraphicState,
+        tiled_export_state: &TiledExportState,
+    ) {
+        let Some(bake) = &mut self.bake else {
+            return;
+        };
+
+        if let Some(time) = bake.times.pop_front()
+            && let Some(camera_data) = bake.camera_path.camera_at(time)
+        {
+            let job = ExportJob::new(&bake.gui_data, camera_data, bake.settings);
+            let start = Instant::now();
+            // Reading the tile back waits for the GPU, so the elapsed time covers the whole frame
+            match tiled_export_state.render_job_tile(device, queue, graphic_state, &job, 0) {
+                Ok(values) => bake.measurements.push(FrameMeasurement {
+                    time,
+                    exposure: settled_exposure(&values, bake.gui_data.into()),
+                    render_time: start.elapsed(),
+                }),
+                Err(error) => {
+                    log::warn!(
+                        "Failed to bake the frame at {time:.2} s, cancelling the bake: {error}"
+                    );
+                    self.bake = None;
+                    return;
+                }
+            }
+        }
+
+        let Some(bake) = self.bake.take_if(|bake| bake.times.is_empty()) else {
+            return;
+        };
+        log::info!(
+            "Baked {} frames of the camera path",
+            bake.measurements.len()
+        );
+        self.timeline = Some(BakedTimeline::new(
+            &bake.measurements,
+            bake.gui_data.max_iterations,
+            bake.camera_path.frame_rate,
+        ));
+    }
+}
@@ -0,0 +1,219 @@
+use egui_wgpu::wgpu;
+use std::sync::mpsc;
+use winit::dpi::PhysicalSize;
+
+use crate::error::CaptureError;
+use crate::util::math::f16_to_f32;
+
+/// Image of 32 bit floats, stored row by row from the top with the channels of every pixel next to
+/// each other
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FloatImage {
+    pub width: u32,
+    pub height: u32,
+    pub channels: u32,
+    pub values: Vec<f32>,
+}
+
+impl FloatImage {
+    /// Height, width and channels, the row-major shape of the values as taken by e.g.
+    /// `ndarray::Array3::from_shape_vec`
+    #[must_use]
+    pub fn shape(&self) -> [usize; 3] {
+        [
+            self.height as usize,
+            self.width as usize,
+            self.channels as usize,
+        ]
+    }
+
+    /// Channels of the pixel, None outside of the image
+    #[must_use]
+    pub fn pixel(&self, x: u32, y: u32) -> Option<&[f32]> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let channels = self.channels as usize;
+        let start = (y as usize * self.width as usize + x as usize) * channels;
+        self.values.get(start..start + channels)
+    }
+}
+
+/// Frame as rendered before post processing, for numerical analysis of the fractal
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrameCapture {
+    /// Linear RGB averaged over the accumulated samples, before exposure and tone mapping
+    pub color: FloatImage,
+    /// Samples accumulated per pixel, fewer outside of the region of interest
+    pub samples: FloatImage,
+    /// Distance from the camera along its view direction, the max distance where nothing was hit
+    pub depth: FloatImage,
+    /// World space normal of the hit surface, zero where nothing was hit
+    pub normal: FloatImage,
+    /// Number of march steps taken by the ray
+    pub iterations: FloatImage,
+}
+
+/// Copies the top left corner of the texture into a mappable buffer, blocking until its rows are
+/// read back without padding
+///
+/// ## Errors
+/// - `PollError` when waiting for the copy failed
+/// - `BufferAsyncError` when the copy could not be mapped
+pub(crate) fn read_texture<E: From<wgpu::PollError> + From<wgpu::BufferAsyncError>>(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    size: PhysicalSize<u32>,
+) -> Result<Vec<u8>, E> {
+    // Only float formats are read back, which all have a single block per pixel
+    let pixel_size = texture.format().block_copy_size(None).unwrap_or(4);
+    let row_size = size.width * pixel_size;
+    let padded_row_size = row_size.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+    let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("readback_buffer"),
+        size: wgpu::BufferAddress::from(padded_row_size * size.height),
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("readback_encoder"),
+    });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &readback_buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row_size),
+                rows_per_image: None,
+            },
+        },
+        wgpu::Extent3d {
+            width: size.width,
+            height: size.height,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = readback_buffer.slice(..);
+    let (sender, receiver) = mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        // Receiving end is only dropped after the result was received
+        sender.send(result).ok();
+    });
+    device.poll(wgpu::PollType::Wait)?;
+    receiver.recv().unwrap_or(Err(wgpu::BufferAsyncError))?;
+
+    let bytes = slice
+        .get_mapped_range()
+        .chunks(padded_row_size as usize)
+        .flat_map(|row| row[..row_size as usize].iter().copied())
+        .collect();
+    readback_buffer.unmap();
+
+    Ok(bytes)
+}
+
+// Samples are summed up in the render texture, with the alpha channel counting them
+#[must_use]
+fn average_samples(size: PhysicalSize<u32>, bits: &[u16]) -> (FloatImage, FloatImage) {
+    let mut color = Vec::with_capacity(bits.len() / 4 * 3);
+    let mut samples = Vec::with_capacity(bits.len() / 4);
+    for pixel in bits.chunks(4) {
+        let sample_count = f16_to_f32(pixel[3]);
+        let weight = if sample_count > 0. {
+            sample_count.recip()
+        } else {
+            0.
+        };
+        color.extend(pixel[..3].iter().map(|bits| f16_to_f32(*bits) * weight));
+        samples.push(sample_count);
+    }
+
+    (
+        FloatImage {
+            width: size.width,
+            height: size.height,
+            channels: 3,
+            values: color,
+        },
+        FloatImage {
+            width: size.width,
+            height: size.height,
+            channels: 1,
+            values: samples,
+        },
+    )
+}
+
+// Layers with an unused fourth channel, like the normals, are cut down to three
+#[must_use]
+fn layer_image(size: PhysicalSize<u32>, format: wgpu::TextureFormat, bytes: &[u8]) -> FloatImage {
+    let components = u32::from(format.components());
+    let channels = components.min(3);
+    let values = bytemuck::pod_collect_to_vec::<u8, f32>(bytes)
+        .chunks(components as usize)
+        .flat_map(|pixel| pixel[..channels as usize].iter().copied())
+        .collect();
+
+    FloatImage {
+        width: size.width,
+        height: size.height,
+        channels,
+        values,
+    }
+}
+
+/// Reads back the part of the render texture and output layers covered by the last raymarch pass
+///
+/// ## Errors
+/// - `CaptureError::Poll(PollError)` when waiting for a copy failed
+/// - `CaptureError::BufferAsync(BufferAsyncError)` when a copy could not be mapped
+pub(crate) fn capture_frame(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    render_texture: &wgpu::Texture,
+    layer_textures: &[wgpu::Texture; 3],
+    size: PhysicalSize<u32>,
+) -> Result<FrameCapture, CaptureError> {
+    let color_bytes = read_texture::<CaptureError>(device, queue, render_texture, size)?;
+    let (color, samples) =
+        average_samples(size, &bytemuck::pod_collect_to_vec::<u8, u16>(&color_bytes));
+
+    let [depth, normal, iterations] = layer_textures;
+    let read_layer = |texture: &wgpu::Texture| {
+        read_texture::<CaptureError>(device, queue, texture, size)
+            .map(|bytes| layer_image(size, texture.format(), &bytes))
+    };
+
+    Ok(FrameCapture {
+        color,
+        samples,
+        depth: read_layer(depth)?,
+        normal: read_layer(normal)?,
+        iterations: read_layer(iterations)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_average_samples() {
+        let size = PhysicalSize::new(2, 1);
+        // Four samples of a bright pixel and an untouched pixel, in half precision
+        let bits = [0x4400, 0x4000, 0x3c00, 0x4400, 0, 0, 0, 0];
+        let (color, samples) = average_samples(size, &bits);
+
+        assert_eq!(color.shape(), [1, 2, 3]);
+        assert_eq!(color.pixel(0, 0), Some(&[1., 0.5, 0.25][..]));
+        assert_eq!(color.pixel(1, 0), Some(&[0., 0., 0.][..]));
+        assert_eq!(color.pixel(2, 0), None);
+        assert_eq!(samples.values, [4., 0.]);
+    }
+}
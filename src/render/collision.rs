@@ -0,0 +1,347 @@
+use egui_wgpu::wgpu;
+use std::sync::mpsc;
+
+use crate::data::CameraData;
+use crate::data::camera_path::interpolate_cameras;
+use crate::data::scene::FractalGroup;
+use crate::render::graphics::GraphicState;
+use crate::util::buffer::{
+    ResourceGroup, ResourceGroupDescriptor, ResourceGroupInit as _, ResourceGroupLayoutEntry,
+};
+use crate::util::math::Vector3;
+use crate::util::shader::WGSLShaderSource;
+use crate::util::storage::{StorageBuffer, StorageBufferDescriptor, StorageBufferInit as _};
+
+// Direction away from the surface followed by the distance estimate, see camera_distance.wgsl
+const CAMERA_DISTANCE_BUFFER_SIZE: usize = size_of::<[f32; 4]>();
+// Share of the distance estimate the camera may cover, as estimates of fractals are only roughly
+// a bound of the distance to the surface
+const COLLISION_MARGIN: f32 = 0.8;
+// Steps along a curved path, like orbiting, are shortened this many times before the camera stays
+const STEP_ATTEMPTS: u32 = 4;
+// Shortened steps aim a little short, as a curved path covers less of the way than its share of
+// the whole step would suggest
+const STEP_SHORTENING: f32 = 0.9;
+
+// Room the camera has before it reaches the surface, only steps towards the surface use it up
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct SafeStep {
+    pub(crate) distance: f32,
+    // Unit gradient of the distance estimate, None where it has no direction and every step
+    // counts as one towards the surface
+    pub(crate) away: Option<Vector3<f32>>,
+}
+
+impl SafeStep {
+    // Part of the step which approaches the surface
+    #[must_use]
+    fn approach(&self, step: Vector3<f32>) -> f32 {
+        self.away
+            .map_or_else(|| step.length(), |away| (-(step * away)).max(0.))
+    }
+}
+
+// Camera on the way from the previous to the proposed one whose position approached the surface
+// at most the safe distance, or the previous camera when no such one was found
+#[must_use]
+pub(crate) fn limit_camera_step(
+    previous: CameraData,
+    proposed: CameraData,
+    safe_step: SafeStep,
+) -> CameraData {
+    let mut fraction = 1.;
+    for _ in 0..STEP_ATTEMPTS {
+        let camera_data = interpolate_cameras(previous, proposed, fraction);
+        let approach = safe_step.approach(camera_data.position() - previous.position());
+        if approach <= safe_step.distance {
+            return camera_data;
+        }
+        fraction *= STEP_SHORTENING * safe_step.distance / approach;
+    }
+
+    previous
+}
+
+// Pipeline of the fractal group and user source it was compiled for
+#[derive(Debug)]
+struct CollisionPipeline {
+    fractal_group: FractalGroup,
+    user_source: String,
+    pipeline: wgpu::ComputePipeline,
+}
+
+// Distance estimate of the scene at the camera, read back without stalling the frame
+#[derive(Debug)]
+pub(crate) struct CollisionState {
+    camera_distance_buffer: StorageBuffer,
+    readback_buffer: wgpu::Buffer,
+    resource_group: ResourceGroup,
+    pipeline_layout: wgpu::PipelineLayout,
+    is_enabled: bool,
+    // Only compiled once collision is enabled, and again whenever the fractal group changes
+    pipeline: Option<CollisionPipeline>,
+    // Camera position of the estimate being copied or read back
+    pending_position: Option<Vector3<f32>>,
+    is_copy_pending: bool,
+    readback_receiver: Option<mpsc::Receiver<Result<(), wgpu::BufferAsyncError>>>,
+    // Most recent estimate which finished reading back, with the position it was taken at
+    measurement: Option<(Vector3<f32>, SafeStep)>,
+}
+
+impl CollisionState {
+    #[must_use]
+    pub(crate) fn new(device: &wgpu::Device, graphic_state: &GraphicState) -> Self {
+        let camera_distance_buffer = device.create_storage_buffer(&StorageBufferDescriptor {
+            label: Some("camera_distance_storage_buffer"),
+            contents: bytemuck::bytes_of(&[0_f32; 4]),
+            extra_usage: wgpu::BufferUsages::COPY_SRC,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("camera_distance_readback_buffer"),
+            size: CAMERA_DISTANCE_BUFFER_SIZE as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let resource_group = device.create_resource_group(&ResourceGroupDescriptor {
+            label: Some("camera_distance"),
+            resources: &[camera_distance_buffer.as_entire_binding()],
+            entries: &[ResourceGroupLayoutEntry::storage_buffer(
+                wgpu::ShaderStages::COMPUTE,
+                false,
+            )],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("camera_distance_pipeline_layout"),
+            bind_group_layouts: &[
+                graphic_state.uniform_group().bind_group_layout(),
+                graphic_state.storage_group().bind_group_layout(),
+                resource_group.bind_group_layout(),
+            ],
+            push_constant_ranges: &[],
+        });
+
+        Self {
+            camera_distance_buffer,
+            readback_buffer,
+            resource_group,
+            pipeline_layout,
+            is_enabled: false,
+            pipeline: None,
+            pending_position: None,
+            is_copy_pending: false,
+            readback_receiver: None,
+            measurement: None,
+        }
+    }
+
+    pub(crate) fn update_is_enabled(&mut self, is_enabled: bool) {
+        // Estimates of a scene changed in between would no longer hold once enabled again
+        if !is_enabled {
+            self.measurement = None;
+        }
+        self.is_enabled = is_enabled;
+    }
+
+    // Distance the camera may still move from the position without entering the surface, None
+    // while nothing was measured yet or the camera already is inside, so it can always get out
+    #[must_use]
+    pub(crate) fn safe_step(&self, position: Vector3<f32>) -> Option<SafeStep> {
+        let (measured_position, measured_step) = self.measurement?;
+        (measured_step.distance > 0.).then(|| SafeStep {
+            distance: (measured_step.distance - (position - measured_position).length()).max(0.)
+                * COLLISION_MARGIN,
+            ..measured_step
+        })
+    }
+
+    // Shares the shader of the fractal pipeline, so the camera stops at the very surface it sees
+    fn prepare_pipeline(&mut self, device: &wgpu::Device, graphic_state: &GraphicState) {
+        let fractal_group = graphic_state.options_data().fractal_group;
+        let user_source = graphic_state.user_source(fractal_group);
+        if self.pipeline.as_ref().is_some_and(|pipeline| {
+            pipeline.fractal_group == fractal_group && pipeline.user_source == user_source
+        }) {
+            return;
+        }
+
+        log::info!("Compiling the {fractal_group} camera distance pipeline");
+        let shader = GraphicState::create_fractal_shader(
+            device,
+            fractal_group,
+            user_source,
+            &[shader_source!("dependencies/camera_distance.wgsl")],
+        );
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("camera_distance_pipeline"),
+            layout: Some(&self.pipeline_layout),
+            module: &shader,
+            entry_point: Some("camera_distance_main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        self.pipeline = Some(CollisionPipeline {
+            fractal_group,
+            user_source: user_source.to_string(),
+            pipeline,
+        });
+    }
+
+    // Needs to be called after the camera moved for the frame, skipped while the previous estimate
+    // is still being read
+    pub(crate) fn compute(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        graphic_state: &GraphicState,
+    ) {
+        if !self.is_enabled || self.readback_receiver.is_some() || self.is_copy_pending {
+            return;
+        }
+
+        self.prepare_pipeline(device, graphic_state);
+        let Some(pipeline) = &self.pipeline else {
+            return;
+        };
+
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("camera_distance_pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&pipeline.pipeline);
+            compute_pass.set_bind_group(0, graphic_state.uniform_group().bind_group(), &[]);
+            compute_pass.set_bind_group(1, graphic_state.storage_group().bind_group(), &[]);
+            compute_pass.set_bind_group(2, self.resource_group.bind_group(), &[]);
+            compute_pass.dispatch_workgroups(1, 1, 1);
+        }
+
+        encoder.copy_buffer_to_buffer(
+            &self.camera_distance_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            CAMERA_DISTANCE_BUFFER_SIZE as wgpu::BufferAddress,
+        );
+        self.pending_position = Some(graphic_state.camera_data().position());
+        self.is_copy_pending = true;
+    }
+
+    // Needs to be called after submitting the frame, picks up the estimate once it is mapped
+    pub(crate) fn read_camera_distance(&mut self, device: &wgpu::Device) {
+        if self.is_copy_pending {
+            let (sender, receiver) = mpsc::channel();
+            self.readback_buffer
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    // Receiving end is only dropped after the result was received
+                    sender.send(result).ok();
+                });
+            self.readback_receiver = Some(receiver);
+            self.is_copy_pending = false;
+        }
+
+        let Some(receiver) = &self.readback_receiver else {
+            return;
+        };
+        if let Err(error) = device.poll(wgpu::PollType::Poll) {
+            log::warn!("Failed to poll the camera distance readback: {error}");
+        }
+
+        match receiver.try_recv() {
+            Ok(Ok(())) => {
+                let [x, y, z, distance]: [f32; 4] = bytemuck::pod_read_unaligned(
+                    &self.readback_buffer.slice(..).get_mapped_range(),
+                );
+                self.readback_buffer.unmap();
+                // Estimates which are not a number can not bound any step, while a gradient
+                // which is not one only leaves the direction of the surface unknown
+                let safe_step = SafeStep {
+                    distance,
+                    away: [x, y, z]
+                        .iter()
+                        .all(|component| component.is_finite())
+                        .then_some(Vector3(x, y, z)),
+                };
+                self.measurement = self
+                    .pending_position
+                    .take()
+                    .filter(|_| distance.is_finite())
+                    .map(|position| (position, safe_step));
+            }
+            Ok(Err(error)) => log::warn!("Failed to read back the camera distance: {error}"),
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => {}
+        }
+        self.readback_receiver = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::math::{Radians, Vector2};
+
+    #[test]
+    fn test_limit_camera_step() {
+        let previous = CameraData::default();
+        let safe_step = |distance| SafeStep {
+            distance,
+            away: None,
+        };
+
+        // Short steps are taken as they are
+        let proposed = previous.pan(Vector2(0.1, 0.));
+        let limited = limit_camera_step(previous, proposed, safe_step(1.));
+        assert_eq!(limited.position(), proposed.position());
+
+        // Straight steps stop at the limit
+        let proposed = previous.pan(Vector2(2., 0.));
+        let limited = limit_camera_step(previous, proposed, safe_step(0.5));
+        let step = (limited.position() - previous.position()).length();
+        assert!(step <= 0.5 && step > 0.4);
+
+        // Orbiting around the target stays within the limit along its curve
+        let proposed = previous.rotated(Vector2(
+            Radians::from_degrees(90.),
+            Radians::from_degrees(0.),
+        ));
+        let limited = limit_camera_step(previous, proposed, safe_step(1.));
+        let step = (limited.position() - previous.position()).length();
+        assert!(step <= 1. && step > 0.5);
+        assert!((limited.origin_distance - previous.origin_distance).abs() < 1e-5);
+
+        // Without any room the camera stays where it was
+        let limited = limit_camera_step(previous, proposed, safe_step(0.));
+        assert_eq!(limited.position(), previous.position());
+        assert_eq!(limited.camera_matrix(), previous.camera_matrix());
+    }
+
+    #[test]
+    fn test_limit_camera_step_direction() {
+        let previous = CameraData::default();
+        let (back_direction, right_direction, up_direction) = previous.camera_matrix().columns();
+        let proposed = previous.pan(Vector2(2., 0.));
+
+        // Steps away from the surface or along it are not limited
+        for away in [right_direction, up_direction] {
+            let safe_step = SafeStep {
+                distance: 0.,
+                away: Some(away),
+            };
+            let limited = limit_camera_step(previous, proposed, safe_step);
+            assert_eq!(limited.position(), proposed.position());
+        }
+
+        // Only the part of a step towards the surface uses up the room
+        let away = (back_direction - right_direction).normalize();
+        let safe_step = SafeStep {
+            distance: 0.5,
+            away: Some(away),
+        };
+        let limited = limit_camera_step(previous, proposed, safe_step);
+        let approach = -((limited.position() - previous.position()) * away);
+        assert!(approach <= 0.5 && approach > 0.4);
+    }
+}
@@ -0,0 +1,275 @@
+use egui_wgpu::wgpu;
+use std::{fs, sync::mpsc};
+
+use crate::data::DistanceGridData;
+use crate::data::mesh::{DistanceGrid, MeshSettings};
+use crate::error::VoxelExportError;
+use crate::render::graphics::GraphicState;
+use crate::util::buffer::{
+    ResourceGroup, ResourceGroupDescriptor, ResourceGroupInit as _, ResourceGroupLayoutEntry,
+};
+use crate::util::shader::WGSLShaderSource;
+use crate::util::uniform::{UniformBufferDescriptor, UniformBufferInit as _};
+
+// Side length of the workgroups of the grid shader, declared in the shader constants
+pub(crate) const DISTANCE_GRID_WORKGROUP_SIZE: u32 = 4;
+// Layers along z sampled by one submission, so fine grids of expensive fractals do not run long
+// enough for the driver to treat the GPU as hung
+const LAYERS_PER_SUBMISSION: u32 = 16;
+const VOXEL_EXPORT_PATH: &str = "voxels.vox";
+// Voxel models store their coordinates in single bytes
+pub(crate) const MAX_VOX_SIZE: u32 = 256;
+// White in the default palette every voxel editor falls back to when a file has none
+const VOX_COLOR_INDEX: u8 = 1;
+
+// Identifier, size of the content and size of the children, followed by the content
+fn push_vox_chunk(bytes: &mut Vec<u8>, id: [u8; 4], content: &[u8], children_size: usize) {
+    bytes.extend_from_slice(&id);
+    #[allow(clippy::cast_possible_truncation)]
+    for size in [content.len(), children_size] {
+        bytes.extend_from_slice(&(size as u32).to_le_bytes());
+    }
+    bytes.extend_from_slice(content);
+}
+
+// MagicaVoxel model of the samples inside the scene, up to the first 256 samples along every side
+// Both the grid and the model have z pointing up, so the axes carry over as they are
+#[must_use]
+pub(crate) fn encode_vox(distance_grid: &DistanceGrid) -> Vec<u8> {
+    let settings = distance_grid.settings;
+    let size = settings.resolution.min(MAX_VOX_SIZE);
+
+    let mut voxels = Vec::new();
+    for z in 0..size {
+        for y in 0..size {
+            for x in 0..size {
+                if distance_grid.distances[settings.index(x, y, z)] < 0. {
+                    #[allow(clippy::cast_possible_truncation)]
+                    voxels.extend_from_slice(&[x as u8, y as u8, z as u8, VOX_COLOR_INDEX]);
+                }
+            }
+        }
+    }
+
+    let mut size_content = Vec::new();
+    for _ in 0..3 {
+        size_content.extend_from_slice(&size.to_le_bytes());
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    let mut voxel_content = ((voxels.len() / 4) as u32).to_le_bytes().to_vec();
+    voxel_content.extend_from_slice(&voxels);
+
+    let mut children = Vec::new();
+    push_vox_chunk(&mut children, *b"SIZE", &size_content, 0);
+    push_vox_chunk(&mut children, *b"XYZI", &voxel_content, 0);
+
+    let mut bytes = b"VOX ".to_vec();
+    bytes.extend_from_slice(&150_u32.to_le_bytes());
+    push_vox_chunk(&mut bytes, *b"MAIN", &[], children.len());
+    bytes.extend_from_slice(&children);
+
+    bytes
+}
+
+// Shares the shader of the fractal pipeline, like the distance slice, so the samples match the
+// rendered surface
+#[must_use]
+fn create_distance_grid_pipeline(
+    device: &wgpu::Device,
+    graphic_state: &GraphicState,
+    resource_group: &ResourceGroup,
+) -> wgpu::ComputePipeline {
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("distance_grid_pipeline_layout"),
+        bind_group_layouts: &[
+            graphic_state.uniform_group().bind_group_layout(),
+            graphic_state.storage_group().bind_group_layout(),
+            resource_group.bind_group_layout(),
+        ],
+        push_constant_ranges: &[],
+    });
+
+    let fractal_group = graphic_state.options_data().fractal_group;
+    let shader = GraphicState::create_fractal_shader(
+        device,
+        fractal_group,
+        graphic_state.user_source(fractal_group),
+        &[shader_source!("dependencies/distance_grid.wgsl")],
+    );
+    device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: Some("distance_grid_pipeline"),
+        layout: Some(&pipeline_layout),
+        module: &shader,
+        entry_point: Some("distance_grid_main"),
+        compilation_options: wgpu::PipelineCompilationOptions::default(),
+        cache: None,
+    })
+}
+
+/// Samples the distance estimate of the scene objects on the grid, blocking until all samples are
+/// read back
+///
+/// ## Errors
+/// - `PollError` when waiting for the samples failed
+/// - `BufferAsyncError` when the samples could not be mapped
+pub(crate) fn sample_distance_grid<E: From<wgpu::PollError> + From<wgpu::BufferAsyncError>>(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    graphic_state: &GraphicState,
+    settings: MeshSettings,
+) -> Result<DistanceGrid, E> {
+    let settings = MeshSettings {
+        resolution: settings.resolution.max(MeshSettings::MIN_RESOLUTION),
+        ..settings
+    };
+    let buffer_size = (settings.sample_count() * size_of::<f32>()) as wgpu::BufferAddress;
+
+    let mut distance_grid_uniform_buffer = device.create_uniform_buffer(&UniformBufferDescriptor {
+        label: Some("distance_grid_uniform_buffer"),
+        data_descriptor: DistanceGridData {
+            settings,
+            first_layer: 0,
+        },
+    });
+    let samples_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("distance_grid_samples_buffer"),
+        size: buffer_size,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+    // Mappable copy of the samples buffer, which the GPU cannot write to directly
+    let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("distance_grid_readback_buffer"),
+        size: buffer_size,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let resource_group = device.create_resource_group(&ResourceGroupDescriptor {
+        label: Some("distance_grid"),
+        resources: &[
+            distance_grid_uniform_buffer.as_entire_binding(),
+            samples_buffer.as_entire_binding(),
+        ],
+        entries: &[
+            ResourceGroupLayoutEntry {
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            ResourceGroupLayoutEntry::storage_buffer(wgpu::ShaderStages::COMPUTE, false),
+        ],
+    });
+    let pipeline = create_distance_grid_pipeline(device, graphic_state, &resource_group);
+
+    let workgroups = settings.resolution.div_ceil(DISTANCE_GRID_WORKGROUP_SIZE);
+    for first_layer in (0..settings.resolution).step_by(LAYERS_PER_SUBMISSION as usize) {
+        distance_grid_uniform_buffer.update_buffer(
+            queue,
+            DistanceGridData {
+                settings,
+                first_layer,
+            },
+        );
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("distance_grid_encoder"),
+        });
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("distance_grid_pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&pipeline);
+            compute_pass.set_bind_group(0, graphic_state.uniform_group().bind_group(), &[]);
+            compute_pass.set_bind_group(1, graphic_state.storage_group().bind_group(), &[]);
+            compute_pass.set_bind_group(2, resource_group.bind_group(), &[]);
+            compute_pass.dispatch_workgroups(
+                workgroups,
+                workgroups,
+                LAYERS_PER_SUBMISSION.div_ceil(DISTANCE_GRID_WORKGROUP_SIZE),
+            );
+        }
+        if first_layer + LAYERS_PER_SUBMISSION >= settings.resolution {
+            encoder.copy_buffer_to_buffer(&samples_buffer, 0, &readback_buffer, 0, buffer_size);
+        }
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    let slice = readback_buffer.slice(..);
+    let (sender, receiver) = mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        // Receiving end is only dropped after the result was received
+        sender.send(result).ok();
+    });
+    device.poll(wgpu::PollType::Wait)?;
+    receiver.recv().unwrap_or(Err(wgpu::BufferAsyncError))?;
+
+    let distances = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+    readback_buffer.unmap();
+
+    Ok(DistanceGrid {
+        settings,
+        distances,
+    })
+}
+
+/// Samples the scene on the grid and writes the samples inside it as voxels into the working
+/// directory, returns the number of voxels written
+///
+/// ## Errors
+/// - `VoxelExportError` when the samples could not be read back or the file not written
+pub(crate) fn export_voxels(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    graphic_state: &GraphicState,
+    settings: MeshSettings,
+) -> Result<usize, VoxelExportError> {
+    let settings = MeshSettings {
+        resolution: settings.resolution.min(MAX_VOX_SIZE),
+        ..settings
+    };
+    let distance_grid =
+        sample_distance_grid::<VoxelExportError>(device, queue, graphic_state, settings)?;
+    let bytes = encode_vox(&distance_grid);
+    fs::write(VOXEL_EXPORT_PATH, &bytes)?;
+
+    Ok(distance_grid
+        .distances
+        .iter()
+        .filter(|distance| **distance < 0.)
+        .count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_vox() {
+        let settings = MeshSettings {
+            resolution: 2,
+            extent: 1.,
+        };
+        let distance_grid = DistanceGrid {
+            settings,
+            distances: vec![-1., 1., 1., 1., 1., 1., 1., -1.],
+        };
+        let bytes = encode_vox(&distance_grid);
+
+        assert_eq!(&bytes[..4], b"VOX ");
+        assert_eq!(&bytes[8..12], b"MAIN");
+        // Main chunk has no content of its own, only the size and voxel chunks as children
+        assert_eq!(bytes[12..16], 0_u32.to_le_bytes());
+        assert_eq!(bytes[16..20], 48_u32.to_le_bytes());
+        assert_eq!(&bytes[20..24], b"SIZE");
+        assert_eq!(bytes[32..36], 2_u32.to_le_bytes());
+        assert_eq!(&bytes[44..48], b"XYZI");
+        assert_eq!(bytes[56..60], 2_u32.to_le_bytes());
+        assert_eq!(bytes[60..], [0, 0, 0, 1, 1, 1, 1, 1]);
+    }
+}
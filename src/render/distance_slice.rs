@@ -0,0 +1,206 @@
+use egui_wgpu::wgpu;
+
+use crate::data::scene::FractalGroup;
+use crate::data::{DistanceSliceData, GuiData};
+use crate::render::graphics::GraphicState;
+use crate::util::buffer::{
+    ResourceGroup, ResourceGroupDescriptor, ResourceGroupInit as _, ResourceGroupLayoutEntry,
+};
+use crate::util::shader::WGSLShaderSource;
+use crate::util::uniform::{UniformBuffer, UniformBufferDescriptor, UniformBufferInit as _};
+
+// Side length of the slice texture in pixels
+const DISTANCE_SLICE_TEXTURE_SIZE: u32 = 256;
+const DISTANCE_SLICE_TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+// Side length of the workgroups of the slice shader, declared in the shader constants
+pub(crate) const DISTANCE_SLICE_WORKGROUP_SIZE: u32 = 8;
+
+// Pipeline of the fractal group and user source it was compiled for
+#[derive(Clone, Debug)]
+struct SlicePipeline {
+    fractal_group: FractalGroup,
+    user_source: String,
+    pipeline: wgpu::ComputePipeline,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct DistanceSliceState {
+    distance_slice_data: DistanceSliceData,
+    distance_slice_uniform_buffer: UniformBuffer,
+    distance_slice_texture_view: wgpu::TextureView,
+    resource_group: ResourceGroup,
+    pipeline_layout: wgpu::PipelineLayout,
+    // Only compiled once the window is opened, and again whenever the fractal group changes
+    pipeline: Option<SlicePipeline>,
+}
+
+impl DistanceSliceState {
+    #[must_use]
+    fn create_distance_slice_texture_view(device: &wgpu::Device) -> wgpu::TextureView {
+        let distance_slice_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("distance_slice_texture"),
+            size: wgpu::Extent3d {
+                width: DISTANCE_SLICE_TEXTURE_SIZE,
+                height: DISTANCE_SLICE_TEXTURE_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: DISTANCE_SLICE_TEXTURE_FORMAT,
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        distance_slice_texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    #[must_use]
+    fn create_distance_slice_group(
+        device: &wgpu::Device,
+        resources: &[wgpu::BindingResource],
+    ) -> ResourceGroup {
+        device.create_resource_group(&ResourceGroupDescriptor {
+            label: Some("distance_slice"),
+            resources,
+            entries: &[
+                ResourceGroupLayoutEntry {
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                ResourceGroupLayoutEntry {
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: DISTANCE_SLICE_TEXTURE_FORMAT,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    #[must_use]
+    pub(crate) fn new(device: &wgpu::Device, graphic_state: &GraphicState) -> Self {
+        let distance_slice_data = GuiData::default().into();
+        let distance_slice_uniform_buffer =
+            device.create_uniform_buffer(&UniformBufferDescriptor {
+                label: Some("distance_slice_uniform_buffer"),
+                data_descriptor: distance_slice_data,
+            });
+        let distance_slice_texture_view = Self::create_distance_slice_texture_view(device);
+
+        let resource_group = Self::create_distance_slice_group(
+            device,
+            &[
+                distance_slice_uniform_buffer.as_entire_binding(),
+                wgpu::BindingResource::TextureView(&distance_slice_texture_view),
+            ],
+        );
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("distance_slice_pipeline_layout"),
+            bind_group_layouts: &[
+                graphic_state.uniform_group().bind_group_layout(),
+                graphic_state.storage_group().bind_group_layout(),
+                resource_group.bind_group_layout(),
+            ],
+            push_constant_ranges: &[],
+        });
+
+        Self {
+            distance_slice_data,
+            distance_slice_uniform_buffer,
+            distance_slice_texture_view,
+            resource_group,
+            pipeline_layout,
+            pipeline: None,
+        }
+    }
+
+    // Texture the slice is computed into, shown by the GUI
+    #[must_use]
+    pub(crate) fn distance_slice_texture_view(&self) -> &wgpu::TextureView {
+        &self.distance_slice_texture_view
+    }
+
+    pub(crate) fn update_distance_slice_data(
+        &mut self,
+        queue: &wgpu::Queue,
+        new_distance_slice_data: DistanceSliceData,
+    ) {
+        self.distance_slice_data = new_distance_slice_data;
+        self.distance_slice_uniform_buffer
+            .update_buffer(queue, self.distance_slice_data);
+    }
+
+    // Shares the shader of the fractal pipeline, so the slice shows the very same distance estimate
+    fn prepare_pipeline(&mut self, device: &wgpu::Device, graphic_state: &GraphicState) {
+        let fractal_group = graphic_state.options_data().fractal_group;
+        let user_source = graphic_state.user_source(fractal_group);
+        if self.pipeline.as_ref().is_some_and(|pipeline| {
+            pipeline.fractal_group == fractal_group && pipeline.user_source == user_source
+        }) {
+            return;
+        }
+
+        log::info!("Compiling the {fractal_group} distance slice pipeline");
+        let shader = GraphicState::create_fractal_shader(
+            device,
+            fractal_group,
+            user_source,
+            &[shader_source!("dependencies/distance_slice.wgsl")],
+        );
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("distance_slice_pipeline"),
+            layout: Some(&self.pipeline_layout),
+            module: &shader,
+            entry_point: Some("distance_slice_main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        self.pipeline = Some(SlicePipeline {
+            fractal_group,
+            user_source: user_source.to_string(),
+            pipeline,
+        });
+    }
+
+    // A single distance estimate per texel is cheap enough to redo every frame, which keeps the
+    // slice in step with the scene objects and the animated constant
+    pub(crate) fn compute(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        graphic_state: &GraphicState,
+    ) {
+        if !self.distance_slice_data.is_enabled {
+            return;
+        }
+
+        self.prepare_pipeline(device, graphic_state);
+        let Some(pipeline) = &self.pipeline else {
+            return;
+        };
+
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("distance_slice_pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&pipeline.pipeline);
+        compute_pass.set_bind_group(0, graphic_state.uniform_group().bind_group(), &[]);
+        compute_pass.set_bind_group(1, graphic_state.storage_group().bind_group(), &[]);
+        compute_pass.set_bind_group(2, self.resource_group.bind_group(), &[]);
+        compute_pass.dispatch_workgroups(
+            DISTANCE_SLICE_TEXTURE_SIZE.div_ceil(DISTANCE_SLICE_WORKGROUP_SIZE),
+            DISTANCE_SLICE_TEXTURE_SIZE.div_ceil(DISTANCE_SLICE_WORKGROUP_SIZE),
+            1,
+        );
+    }
+}
@@ -0,0 +1,221 @@
+use egui_wgpu::wgpu;
+use std::time::Instant;
+
+use crate::data::{ExposureData, GuiData};
+use crate::util::buffer::{
+    ResourceGroup, ResourceGroupDescriptor, ResourceGroupInit as _, ResourceGroupLayoutEntry,
+};
+use crate::util::shader::{
+    WGSLShaderModuleDescriptor, WGSLShaderModuleInit as _, WGSLShaderSource,
+};
+use crate::util::storage::{StorageBuffer, StorageBufferDescriptor, StorageBufferInit as _};
+use crate::util::uniform::{UniformBuffer, UniformBufferDescriptor, UniformBufferInit as _};
+
+const HISTOGRAM_BINS: usize = 256;
+const HISTOGRAM_WORKGROUP_SIZE: u32 = 16;
+
+#[derive(Clone, Debug)]
+pub(crate) struct ExposureState {
+    exposure_data: ExposureData,
+    exposure_uniform_buffer: UniformBuffer,
+    histogram_buffer: StorageBuffer,
+    exposure_buffer: StorageBuffer,
+    resource_group: ResourceGroup,
+    histogram_pipeline: wgpu::ComputePipeline,
+    average_pipeline: wgpu::ComputePipeline,
+    last_update: Instant,
+}
+
+impl ExposureState {
+    #[must_use]
+    fn create_exposure_group(
+        device: &wgpu::Device,
+        resources: &[wgpu::BindingResource],
+    ) -> ResourceGroup {
+        let storage_entry = ResourceGroupLayoutEntry {
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: false },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+
+        device.create_resource_group(&ResourceGroupDescriptor {
+            label: Some("exposure"),
+            resources,
+            entries: &[
+                ResourceGroupLayoutEntry {
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                storage_entry,
+                storage_entry,
+                ResourceGroupLayoutEntry {
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    #[must_use]
+    fn create_compute_pipelines(
+        device: &wgpu::Device,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+    ) -> (wgpu::ComputePipeline, wgpu::ComputePipeline) {
+        let exposure_shader = device.create_wgsl_shader_module(WGSLShaderModuleDescriptor {
+            label: Some("exposure_shader"),
+            main: shader_source!("exposure.wgsl"),
+            dependencies: &[],
+        });
+
+        let exposure_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("exposure_pipeline_layout"),
+                bind_group_layouts,
+                push_constant_ranges: &[],
+            });
+
+        let histogram_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("histogram_compute_pipeline"),
+            layout: Some(&exposure_pipeline_layout),
+            module: &exposure_shader,
+            entry_point: Some("histogram_main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+        let average_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("average_compute_pipeline"),
+            layout: Some(&exposure_pipeline_layout),
+            module: &exposure_shader,
+            entry_point: Some("average_main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        (histogram_pipeline, average_pipeline)
+    }
+
+    #[must_use]
+    pub(crate) fn new(device: &wgpu::Device, render_texture_view: &wgpu::TextureView) -> Self {
+        let exposure_data = GuiData::default().into();
+        let exposure_uniform_buffer = device.create_uniform_buffer(&UniformBufferDescriptor {
+            label: Some("exposure_uniform_buffer"),
+            data_descriptor: exposure_data,
+        });
+
+        let histogram_buffer = device.create_storage_buffer(&StorageBufferDescriptor {
+            label: Some("histogram_storage_buffer"),
+            contents: bytemuck::cast_slice(&[0u32; HISTOGRAM_BINS]),
+            extra_usage: wgpu::BufferUsages::empty(),
+        });
+        // Exposure starts out neutral and adapts from there
+        let exposure_buffer = device.create_storage_buffer(&StorageBufferDescriptor {
+            label: Some("exposure_storage_buffer"),
+            contents: bytemuck::cast_slice(&[1f32]),
+            extra_usage: wgpu::BufferUsages::empty(),
+        });
+
+        let resource_group = Self::create_exposure_group(
+            device,
+            &[
+                wgpu::BindingResource::TextureView(render_texture_view),
+                histogram_buffer.as_entire_binding(),
+                exposure_buffer.as_entire_binding(),
+                exposure_uniform_buffer.as_entire_binding(),
+            ],
+        );
+        let (histogram_pipeline, average_pipeline) =
+            Self::create_compute_pipelines(device, &[resource_group.bind_group_layout()]);
+
+        Self {
+            exposure_data,
+            exposure_uniform_buffer,
+            histogram_buffer,
+            exposure_buffer,
+            resource_group,
+            histogram_pipeline,
+            average_pipeline,
+            last_update: Instant::now(),
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn exposure_data(&self) -> ExposureData {
+        self.exposure_data
+    }
+
+    // Storage buffer holding the adapted exposure multiplier, read by the post pass
+    #[must_use]
+    pub(crate) fn exposure_buffer(&self) -> &StorageBuffer {
+        &self.exposure_buffer
+    }
+
+    // Needs to be called whenever the render texture is recreated, e.g. on resize
+    pub(crate) fn update_render_texture(
+        &mut self,
+        device: &wgpu::Device,
+        render_texture_view: &wgpu::TextureView,
+    ) {
+        device.rebind_resource_group(
+            &mut self.resource_group,
+            Some("exposure"),
+            &[
+                wgpu::BindingResource::TextureView(render_texture_view),
+                self.histogram_buffer.as_entire_binding(),
+                self.exposure_buffer.as_entire_binding(),
+                self.exposure_uniform_buffer.as_entire_binding(),
+            ],
+        );
+    }
+
+    pub(crate) fn update_exposure_data(
+        &mut self,
+        queue: &wgpu::Queue,
+        new_exposure_data: ExposureData,
+    ) {
+        let now = Instant::now();
+
+        self.exposure_data = ExposureData {
+            delta_time: now.duration_since(self.last_update).as_secs_f32(),
+            ..new_exposure_data
+        };
+        self.last_update = now;
+        self.exposure_uniform_buffer
+            .update_buffer(queue, self.exposure_data);
+    }
+
+    pub(crate) fn compute(&self, encoder: &mut wgpu::CommandEncoder, size: (u32, u32)) {
+        if !self.exposure_data.is_auto_exposure {
+            return;
+        }
+
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("exposure_pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_bind_group(0, self.resource_group.bind_group(), &[]);
+
+        compute_pass.set_pipeline(&self.histogram_pipeline);
+        compute_pass.dispatch_workgroups(
+            size.0.div_ceil(HISTOGRAM_WORKGROUP_SIZE),
+            size.1.div_ceil(HISTOGRAM_WORKGROUP_SIZE),
+            1,
+        );
+
+        compute_pass.set_pipeline(&self.average_pipeline);
+        compute_pass.dispatch_workgroups(1, 1, 1);
+    }
+}
@@ -0,0 +1,162 @@
+use std::time::{Duration, Instant};
+
+use crate::data::{CameraData, OptionsData};
+use crate::util::uniform::BufferDataDescriptor as _;
+
+// Interaction is over once neither the settings nor the camera changed for this long
+const IDLE_DELAY: Duration = Duration::from_millis(250);
+// Iteration count is never lowered below this fraction of the configured one
+const MIN_ITERATION_SCALE: f32 = 0.1;
+const DECREASE_FACTOR: f32 = 0.85;
+const INCREASE_FACTOR: f32 = 1.05;
+// Frame times this close to the target are left alone, otherwise vsync makes the scale oscillate
+const TOLERANCE: f32 = 0.1;
+// Iteration count is lowered at least this far while a GUI value is being dragged
+const DRAG_ITERATION_SCALE: f32 = 0.25;
+
+// Lowers the iteration count while the view is being interacted with, so frames stay within the
+// target frame time, and restores the full iteration count as soon as the view is left alone
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FrameGovernor {
+    iteration_scale: f32,
+    last_frame: Option<Instant>,
+    last_change: Option<Instant>,
+    last_state: Option<(OptionsData, CameraData)>,
+}
+
+impl FrameGovernor {
+    #[must_use]
+    pub(crate) fn new() -> Self {
+        Self {
+            iteration_scale: 1.,
+            ..Default::default()
+        }
+    }
+
+    #[must_use]
+    fn is_changed(&self, options_data: &OptionsData, camera_data: CameraData) -> bool {
+        self.last_state.is_none_or(|(last_options, last_camera)| {
+            bytemuck::bytes_of(&last_options.into_buffer_data())
+                != bytemuck::bytes_of(&options_data.into_buffer_data())
+                || bytemuck::bytes_of(&last_camera.into_buffer_data())
+                    != bytemuck::bytes_of(&camera_data.into_buffer_data())
+        })
+    }
+
+    // Needs to be called once per frame with the settings as configured by the user, dragging
+    // lowers the iteration count regardless of the budget until the value is released
+    #[must_use]
+    pub(crate) fn govern(
+        &mut self,
+        now: Instant,
+        target_frame_time: Option<Duration>,
+        options_data: &OptionsData,
+        camera_data: CameraData,
+        is_dragging: bool,
+    ) -> OptionsData {
+        // Time between frames includes the GPU work, unlike timing the render call itself
+        let frame_time = self.last_frame.map(|last_frame| now - last_frame);
+        self.last_frame = Some(now);
+        if self.is_changed(options_data, camera_data) {
+            self.last_change = Some(now);
+        }
+        self.last_state = Some((*options_data, camera_data));
+
+        let is_interacting = self
+            .last_change
+            .is_some_and(|last_change| now - last_change < IDLE_DELAY);
+        match (target_frame_time, frame_time) {
+            (Some(target_frame_time), Some(frame_time)) if is_interacting => {
+                let ratio = frame_time.as_secs_f32() / target_frame_time.as_secs_f32();
+                if ratio > 1. + TOLERANCE {
+                    self.iteration_scale *= DECREASE_FACTOR;
+                } else if ratio < 1. - TOLERANCE {
+                    self.iteration_scale *= INCREASE_FACTOR;
+                }
+                self.iteration_scale = self.iteration_scale.clamp(MIN_ITERATION_SCALE, 1.);
+            }
+            _ => self.iteration_scale = 1.,
+        }
+
+        let iteration_scale = if is_dragging {
+            self.iteration_scale.min(DRAG_ITERATION_SCALE)
+        } else {
+            self.iteration_scale
+        };
+        #[allow(
+            clippy::cast_precision_loss,
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss
+        )]
+        let max_iterations = (options_data.max_iterations as f32 * iteration_scale).round() as u32;
+        OptionsData {
+            max_iterations: max_iterations.max(1),
+            ..*options_data
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_governor_budget() {
+        let mut governor = FrameGovernor::new();
+        let options_data = OptionsData {
+            max_iterations: 100,
+            ..Default::default()
+        };
+        let target = Some(Duration::from_millis(16));
+        let start = Instant::now();
+
+        // Camera moving every frame, with frames taking twice the budget
+        let mut governed = options_data;
+        for (frame, origin_distance) in (0..4).zip([0., 1., 2., 3.]) {
+            let camera_data = CameraData {
+                origin_distance,
+                ..Default::default()
+            };
+            governed = governor.govern(
+                start + frame * Duration::from_millis(32),
+                target,
+                &options_data,
+                camera_data,
+                false,
+            );
+        }
+        assert!(governed.max_iterations < 100);
+
+        // Nothing changed since the last frame for longer than the idle delay
+        let camera_data = CameraData {
+            origin_distance: 3.,
+            ..Default::default()
+        };
+        let relaxed = governor.govern(
+            start + Duration::from_secs(1),
+            target,
+            &options_data,
+            camera_data,
+            false,
+        );
+        assert_eq!(relaxed.max_iterations, 100);
+
+        // Dragging lowers the iterations without a budget, releasing restores them
+        let dragged = governor.govern(
+            start + Duration::from_secs(2),
+            None,
+            &options_data,
+            camera_data,
+            true,
+        );
+        assert_eq!(dragged.max_iterations, 25);
+        let released = governor.govern(
+            start + Duration::from_secs(2),
+            None,
+            &options_data,
+            camera_data,
+            false,
+        );
+        assert_eq!(released.max_iterations, 100);
+    }
+}
@@ -1,25 +1,39 @@
 use egui_wgpu::wgpu;
-use winit::window::Window;
+use winit::dpi::PhysicalSize;
 
+use crate::data::formula;
+use crate::data::options::RegionOfInterest;
 use crate::data::scene::FractalGroup;
-use crate::data::{CameraData, GuiData, OptionsData, ScreenData};
+use crate::data::{
+    CameraData, GuiData, LightingData, OptionsData, ProgressiveData, RegionShape, SampleData,
+    SceneData, ScreenData, TileView,
+};
+use crate::error::ShaderCompileError;
+use crate::render::aov::AOV_FORMATS;
+use crate::render::shader_constants;
 use crate::util::buffer::{
-    FixedEntryResourceGroupDescriptor, ResourceGroup, ResourceGroupInit as _,
-    ResourceGroupLayoutEntry,
+    FixedEntryResourceGroupDescriptor, ResourceGroup, ResourceGroupDescriptor,
+    ResourceGroupInit as _, ResourceGroupLayoutEntry,
 };
-use crate::util::math::{PI, Radians, Vector2};
+use crate::util::lru::LruCache;
+use crate::util::math::{Vector2, halton};
 use crate::util::shader::{
-    WGSLShaderModuleDescriptor, WGSLShaderModuleInit as _, WGSLShaderSource,
+    WGSLShaderModule, WGSLShaderModuleDescriptor, WGSLShaderModuleInit as _, WGSLShaderSource,
+};
+use crate::util::storage::{StorageBuffer, StorageBufferDescriptor, StorageBufferInit as _};
+use crate::util::uniform::{
+    BufferDataDescriptor as _, UniformBuffer, UniformBufferDescriptor, UniformBufferInit as _,
 };
-use crate::util::uniform::{UniformBuffer, UniformBufferDescriptor, UniformBufferInit as _};
 
-macro_rules! shader_source {
-    ($path:expr $(,)?) => {
-        WGSLShaderSource(
-            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/shaders/", $path)).into(),
-        )
-    };
-}
+// Length of the jitter sequence of the TAA, after which the same sub-pixel offsets repeat
+const TAA_JITTER_PERIOD: u32 = 16;
+// Shown until a shader file is loaded, also a template for writing one
+pub(crate) const DEFAULT_SHADER_FILE: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/shaders/user_sdf.wgsl"
+));
+// Number of fractal pipelines kept compiled, the least recently shown ones are dropped beyond it
+const PIPELINE_CACHE_CAPACITY: usize = 3;
 
 #[derive(Clone, Debug)]
 pub(crate) struct GraphicState {
@@ -30,10 +44,29 @@ pub(crate) struct GraphicState {
     camera_rotatable: bool,
     options_data: OptionsData,
     options_uniform_buffer: UniformBuffer,
+    lighting_data: LightingData,
+    lighting_buffer: StorageBuffer,
+    scene_data: SceneData,
+    scene_buffer: StorageBuffer,
+    sample_data: SampleData,
+    sample_uniform_buffer: UniformBuffer,
+    // Number of samples taken at the start of the accumulation, picks the jitter of the TAA sequence
+    taa_frame: u32,
+    // Fraction of the screen resolution rendered into the top left corner of the render texture
+    render_scale: f32,
+    cursor_position: Vector2<f32>,
+    // Corner where the user started drawing the region and the finished region
+    roi_drawing_start: Option<Vector2<f32>>,
+    drawn_roi: Option<RegionShape>,
     uniform_group: ResourceGroup,
-    kifs_pipeline: wgpu::RenderPipeline,
-    julia_pipeline: wgpu::RenderPipeline,
-    generalized_julia_pipeline: wgpu::RenderPipeline,
+    storage_group: ResourceGroup,
+    format: wgpu::TextureFormat,
+    // Pipelines of the recently shown fractal groups by their id, compiled when first shown
+    pipeline_cache: LruCache<u32, wgpu::RenderPipeline>,
+    // Sources of the groups compiled from user input, the transpiled functions of the custom
+    // formula and the loaded shader file, kept to compile their pipelines again after eviction
+    custom_formula: String,
+    shader_file: String,
 }
 
 impl GraphicState {
@@ -46,7 +79,8 @@ impl GraphicState {
             label: Some("uniform_buffer_group"),
             resources,
             entry: ResourceGroupLayoutEntry {
-                visibility: wgpu::ShaderStages::FRAGMENT,
+                // Compute shaders march the same scene for the parameter space preview
+                visibility: wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE,
                 ty: wgpu::BindingType::Buffer {
                     ty: wgpu::BufferBindingType::Uniform,
                     has_dynamic_offset: false,
@@ -57,11 +91,32 @@ impl GraphicState {
         })
     }
 
+    // Ray path and march statistics are written by the shaders, the lighting and scene objects only read
+    #[must_use]
+    fn create_storage_group(
+        device: &wgpu::Device,
+        resources: &[wgpu::BindingResource],
+    ) -> ResourceGroup {
+        // Compute shaders march the same scene for the parameter space preview
+        let visibility = wgpu::ShaderStages::FRAGMENT | wgpu::ShaderStages::COMPUTE;
+
+        device.create_resource_group(&ResourceGroupDescriptor {
+            label: Some("storage"),
+            resources,
+            entries: &[
+                ResourceGroupLayoutEntry::storage_buffer(visibility, false),
+                ResourceGroupLayoutEntry::storage_buffer(visibility, false),
+                ResourceGroupLayoutEntry::storage_buffer(visibility, true),
+                ResourceGroupLayoutEntry::storage_buffer(visibility, true),
+            ],
+        })
+    }
+
     #[must_use]
     fn create_render_pipeline(
         device: &wgpu::Device,
         bind_group_layouts: &[&wgpu::BindGroupLayout],
-        config: &wgpu::SurfaceConfiguration,
+        format: wgpu::TextureFormat,
         shader: &wgpu::ShaderModule,
         label: wgpu::Label,
     ) -> wgpu::RenderPipeline {
@@ -84,11 +139,29 @@ impl GraphicState {
             fragment: Some(wgpu::FragmentState {
                 module: shader,
                 entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
+                targets: &[
+                    Some(wgpu::ColorTargetState {
+                        format,
+                        // Samples are summed up, alpha counting the number of samples taken per pixel
+                        blend: Some(wgpu::BlendState {
+                            color: wgpu::BlendComponent {
+                                src_factor: wgpu::BlendFactor::One,
+                                dst_factor: wgpu::BlendFactor::One,
+                                operation: wgpu::BlendOperation::Add,
+                            },
+                            alpha: wgpu::BlendComponent {
+                                src_factor: wgpu::BlendFactor::One,
+                                dst_factor: wgpu::BlendFactor::One,
+                                operation: wgpu::BlendOperation::Add,
+                            },
+                        }),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    }),
+                    // Auxiliary layers are overwritten by every sample, see AovState
+                    Some(AOV_FORMATS[0].into()),
+                    Some(AOV_FORMATS[1].into()),
+                    Some(AOV_FORMATS[2].into()),
+                ],
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
             }),
             primitive: wgpu::PrimitiveState {
@@ -114,78 +187,97 @@ impl GraphicState {
         })
     }
 
+    // Prefix of the shader and pipeline labels, and the shader defining the group's distance estimate
     #[must_use]
-    fn create_pipelines(
+    fn fractal_shader_main(
+        fractal_group: FractalGroup,
+    ) -> (&'static str, WGSLShaderSource<'static>) {
+        match fractal_group {
+            FractalGroup::KaleidoscopicIFS => ("kifs", shader_source!("kifs.wgsl")),
+            FractalGroup::JuliaSet => ("julia", shader_source!("julia.wgsl")),
+            FractalGroup::GeneralizedJuliaSet => {
+                ("generalized_julia", shader_source!("gen_julia.wgsl"))
+            }
+            FractalGroup::KleinianLimitSet => ("kleinian", shader_source!("kleinian.wgsl")),
+            FractalGroup::Hybrid => ("hybrid", shader_source!("hybrid.wgsl")),
+            FractalGroup::AmazingSurf => ("amazing_surf", shader_source!("amazing_surf.wgsl")),
+            FractalGroup::CustomFormula => {
+                ("custom_formula", shader_source!("custom_formula.wgsl"))
+            }
+            FractalGroup::ShaderFile => ("shader_file", shader_source!("shader_file.wgsl")),
+        }
+    }
+
+    // Shader of the fractal group, pipelines other than the render pipeline add the entry points
+    // they need as extra dependencies
+    #[must_use]
+    pub(crate) fn create_fractal_shader(
         device: &wgpu::Device,
-        bind_group_layouts: &[&wgpu::BindGroupLayout],
-        config: &wgpu::SurfaceConfiguration,
-    ) -> (
-        wgpu::RenderPipeline,
-        wgpu::RenderPipeline,
-        wgpu::RenderPipeline,
-    ) {
-        let kifs_shader = device.create_wgsl_shader_module(WGSLShaderModuleDescriptor {
-            label: Some("kifs_shader"),
-            main: shader_source!("kifs.wgsl"),
-            dependencies: &[
-                shader_source!("dependencies/bindings.wgsl"),
-                shader_source!("dependencies/entry.wgsl"),
-                shader_source!("dependencies/quaternions.wgsl"),
-            ],
-        });
-        let kifs_pipeline = Self::create_render_pipeline(
-            device,
-            bind_group_layouts,
-            config,
-            &kifs_shader,
-            Some("kifs_render_pipeline"),
-        );
+        fractal_group: FractalGroup,
+        user_source: &str,
+        extra_dependencies: &[WGSLShaderSource<'static>],
+    ) -> WGSLShaderModule {
+        let (name, main) = Self::fractal_shader_main(fractal_group);
+
+        let mut dependencies = vec![
+            shader_constants(),
+            shader_source!("dependencies/bindings.wgsl"),
+            shader_source!("dependencies/bounds.wgsl"),
+            shader_source!("dependencies/csg.wgsl"),
+            shader_source!("dependencies/entry.wgsl"),
+            shader_source!("dependencies/quaternions.wgsl"),
+            shader_source!("dependencies/random.wgsl"),
+            shader_source!("dependencies/transforms.wgsl"),
+            shader_source!("dependencies/vertex.wgsl"),
+        ];
+        // Formulas shared by the Julia groups and their parameter preview
+        if fractal_group.is_julia() {
+            dependencies.push(shader_source!("dependencies/julia_formulas.wgsl"));
+        }
+        if matches!(
+            fractal_group,
+            FractalGroup::CustomFormula | FractalGroup::ShaderFile
+        ) {
+            dependencies.push(WGSLShaderSource(user_source.to_string().into()));
+        }
+        dependencies.extend_from_slice(extra_dependencies);
 
-        let julia_shader = device.create_wgsl_shader_module(WGSLShaderModuleDescriptor {
-            label: Some("julia_shader"),
-            main: shader_source!("julia.wgsl"),
-            dependencies: &[
-                shader_source!("dependencies/bindings.wgsl"),
-                shader_source!("dependencies/entry.wgsl"),
-                shader_source!("dependencies/quaternions.wgsl"),
-            ],
-        });
-        let julia_pipeline = Self::create_render_pipeline(
-            device,
-            bind_group_layouts,
-            config,
-            &julia_shader,
-            Some("julia_render_pipeline"),
-        );
+        device.create_wgsl_shader_module(WGSLShaderModuleDescriptor {
+            label: Some(&format!("{name}_shader")),
+            main,
+            dependencies: &dependencies,
+        })
+    }
 
-        let generalized_julia_shader =
-            device.create_wgsl_shader_module(WGSLShaderModuleDescriptor {
-                label: Some("generalized_julia_shader"),
-                main: shader_source!("gen_julia.wgsl"),
-                dependencies: &[
-                    shader_source!("dependencies/bindings.wgsl"),
-                    shader_source!("dependencies/entry.wgsl"),
-                    shader_source!("dependencies/quaternions.wgsl"),
-                ],
-            });
-        let generalized_julia_pipeline = Self::create_render_pipeline(
+    #[must_use]
+    fn create_fractal_pipeline(
+        device: &wgpu::Device,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        format: wgpu::TextureFormat,
+        fractal_group: FractalGroup,
+        user_source: &str,
+    ) -> wgpu::RenderPipeline {
+        let (name, _) = Self::fractal_shader_main(fractal_group);
+        let shader = Self::create_fractal_shader(device, fractal_group, user_source, &[]);
+
+        Self::create_render_pipeline(
             device,
             bind_group_layouts,
-            config,
-            &generalized_julia_shader,
-            Some("generalized_julia_render_pipeline"),
-        );
-
-        (kifs_pipeline, julia_pipeline, generalized_julia_pipeline)
+            format,
+            &shader,
+            Some(&format!("{name}_render_pipeline")),
+        )
     }
 
     #[must_use]
     pub(crate) fn new(
-        window: &Window,
+        size: PhysicalSize<u32>,
         device: &wgpu::Device,
-        config: &wgpu::SurfaceConfiguration,
+        format: wgpu::TextureFormat,
+        ray_path_buffer: &StorageBuffer,
+        statistics_buffer: &StorageBuffer,
     ) -> Self {
-        let screen_data = window.inner_size().into();
+        let screen_data = size.into();
         let screen_uniform_buffer = device.create_uniform_buffer(&UniformBufferDescriptor {
             label: Some("size_uniform_buffer"),
             data_descriptor: screen_data,
@@ -204,17 +296,45 @@ impl GraphicState {
             data_descriptor: options_data,
         });
 
+        let sample_data = SampleData::default();
+        let sample_uniform_buffer = device.create_uniform_buffer(&UniformBufferDescriptor {
+            label: Some("sample_uniform_buffer"),
+            data_descriptor: sample_data,
+        });
+
         let uniform_group = Self::create_uniform_group(
             device,
             &[
                 screen_uniform_buffer.as_entire_binding(),
                 camera_uniform_buffer.as_entire_binding(),
                 options_uniform_buffer.as_entire_binding(),
+                sample_uniform_buffer.as_entire_binding(),
             ],
         );
 
-        let (kifs_pipeline, julia_pipeline, generalized_julia_pipeline) =
-            Self::create_pipelines(device, &[uniform_group.bind_group_layout()], config);
+        let lighting_data: LightingData = GuiData::default().into();
+        let lighting_buffer = device.create_storage_buffer(&StorageBufferDescriptor {
+            label: Some("lighting_storage_buffer"),
+            contents: bytemuck::bytes_of(&lighting_data.into_buffer_data()),
+            extra_usage: wgpu::BufferUsages::empty(),
+        });
+
+        let scene_data: SceneData = GuiData::default().into();
+        let scene_buffer = device.create_storage_buffer(&StorageBufferDescriptor {
+            label: Some("scene_storage_buffer"),
+            contents: bytemuck::bytes_of(&scene_data.into_buffer_data()),
+            extra_usage: wgpu::BufferUsages::empty(),
+        });
+
+        let storage_group = Self::create_storage_group(
+            device,
+            &[
+                ray_path_buffer.as_entire_binding(),
+                statistics_buffer.as_entire_binding(),
+                lighting_buffer.as_entire_binding(),
+                scene_buffer.as_entire_binding(),
+            ],
+        );
 
         Self {
             screen_data,
@@ -224,10 +344,28 @@ impl GraphicState {
             camera_rotatable,
             options_data,
             options_uniform_buffer,
+            lighting_data,
+            lighting_buffer,
+            scene_data,
+            scene_buffer,
+            sample_data,
+            sample_uniform_buffer,
+            taa_frame: 0,
+            render_scale: 1.,
+            cursor_position: Vector2(0., 0.),
+            roi_drawing_start: None,
+            drawn_roi: None,
             uniform_group,
-            kifs_pipeline,
-            julia_pipeline,
-            generalized_julia_pipeline,
+            storage_group,
+            format,
+            pipeline_cache: LruCache::new(PIPELINE_CACHE_CAPACITY),
+            // Default formula is covered by the tests of the transpiler
+            custom_formula: formula::transpile(
+                formula::DEFAULT_ITERATION,
+                formula::DEFAULT_DISTANCE,
+            )
+            .unwrap_or_default(),
+            shader_file: DEFAULT_SHADER_FILE.to_string(),
         }
     }
 
@@ -254,72 +392,339 @@ impl GraphicState {
         self.camera_rotatable = false;
     }
 
+    // Shared with the overlays drawn on top of the fractal, so they are seen from the same camera
+    #[must_use]
+    pub(crate) fn screen_uniform_buffer(&self) -> &UniformBuffer {
+        &self.screen_uniform_buffer
+    }
+
+    #[must_use]
+    pub(crate) fn camera_uniform_buffer(&self) -> &UniformBuffer {
+        &self.camera_uniform_buffer
+    }
+
     #[must_use]
     pub(crate) fn options_data(&self) -> OptionsData {
         self.options_data
     }
 
+    // Uniforms and storage buffers every fractal shader is bound to, as groups 0 and 1
+    #[must_use]
+    pub(crate) fn lighting_data(&self) -> LightingData {
+        self.lighting_data
+    }
+
+    #[must_use]
+    pub(crate) fn scene_data(&self) -> SceneData {
+        self.scene_data
+    }
+
+    #[must_use]
+    pub(crate) fn uniform_group(&self) -> &ResourceGroup {
+        &self.uniform_group
+    }
+
+    #[must_use]
+    pub(crate) fn storage_group(&self) -> &ResourceGroup {
+        &self.storage_group
+    }
+
+    #[must_use]
+    pub(crate) fn sample_data(&self) -> SampleData {
+        self.sample_data
+    }
+
+    #[must_use]
+    pub(crate) fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    // Part of the render texture covered by the raymarch pass at the current render scale
+    #[must_use]
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    pub(crate) fn render_size(&self) -> PhysicalSize<u32> {
+        let scale = |length: u32| {
+            ((length as f32 * self.render_scale).ceil() as u32).clamp(1, length.max(1))
+        };
+        PhysicalSize::new(
+            scale(self.screen_data.width),
+            scale(self.screen_data.height),
+        )
+    }
+
+    // Lowers the resolution for a while, e.g. to preview a dragged value, without recreating textures
+    pub(crate) fn set_render_scale(&mut self, render_scale: f32) {
+        if (render_scale - self.render_scale).abs() > f32::EPSILON {
+            self.reset_samples();
+            self.render_scale = render_scale;
+        }
+    }
+
+    // Discards all accumulated samples, needs to be called whenever the rendered image changes
+    pub(crate) fn reset_samples(&mut self) {
+        self.sample_data.index = 0;
+    }
+
     pub(crate) fn update_screen_data(&mut self, queue: &wgpu::Queue, new_screen_data: ScreenData) {
+        self.reset_samples();
         self.screen_data = new_screen_data;
         self.screen_uniform_buffer
             .update_buffer(queue, self.screen_data);
     }
 
-    pub(crate) fn zoom_camera(&mut self, queue: &wgpu::Queue, distance: f32) {
-        let current_distance = self.camera_data.origin_distance;
-        let min_distance = self.camera_data.min_distance;
-
-        self.camera_data = CameraData {
-            origin_distance: f32::max(min_distance, current_distance - distance),
-            ..self.camera_data
-        };
+    pub(crate) fn update_camera(&mut self, queue: &wgpu::Queue, new_camera_data: CameraData) {
+        self.reset_samples();
+        self.camera_data = new_camera_data;
         self.camera_uniform_buffer
             .update_buffer(queue, self.camera_data);
     }
 
-    pub(crate) fn rotate_camera(
+    pub(crate) fn update_options(&mut self, queue: &wgpu::Queue, new_options_data: &OptionsData) {
+        if bytemuck::bytes_of(&self.options_data.into_buffer_data())
+            != bytemuck::bytes_of(&new_options_data.into_buffer_data())
+        {
+            self.reset_samples();
+        }
+
+        self.options_data = *new_options_data;
+        self.options_uniform_buffer
+            .update_buffer(queue, self.options_data);
+    }
+
+    pub(crate) fn update_lighting(&mut self, queue: &wgpu::Queue, new_lighting_data: LightingData) {
+        if bytemuck::bytes_of(&self.lighting_data.into_buffer_data())
+            != bytemuck::bytes_of(&new_lighting_data.into_buffer_data())
+        {
+            self.reset_samples();
+        }
+
+        self.lighting_data = new_lighting_data;
+        self.lighting_buffer
+            .update_buffer(queue, 0, &[self.lighting_data.into_buffer_data()]);
+    }
+
+    pub(crate) fn update_scene(&mut self, queue: &wgpu::Queue, new_scene_data: &SceneData) {
+        if bytemuck::bytes_of(&self.scene_data.into_buffer_data())
+            != bytemuck::bytes_of(&new_scene_data.into_buffer_data())
+        {
+            self.reset_samples();
+        }
+
+        self.scene_data = *new_scene_data;
+        self.scene_buffer
+            .update_buffer(queue, 0, &[self.scene_data.into_buffer_data()]);
+    }
+
+    #[must_use]
+    pub(crate) fn cursor_position(&self) -> Vector2<f32> {
+        self.cursor_position
+    }
+
+    pub(crate) fn update_cursor_position(&mut self, position: Vector2<f32>) {
+        self.cursor_position = position;
+
+        if let Some(start) = self.roi_drawing_start {
+            self.drawn_roi = Some(RegionShape::Rectangle {
+                min: Vector2(start.0.min(position.0), start.1.min(position.1)),
+                max: Vector2(start.0.max(position.0), start.1.max(position.1)),
+            });
+        }
+    }
+
+    pub(crate) fn start_roi_drawing(&mut self) {
+        self.roi_drawing_start = Some(self.cursor_position);
+        self.drawn_roi = None;
+    }
+
+    pub(crate) fn finish_roi_drawing(&mut self) {
+        self.roi_drawing_start = None;
+    }
+
+    #[must_use]
+    fn region_of_interest(&self, progressive_data: ProgressiveData) -> Option<RegionShape> {
+        match progressive_data.region_of_interest {
+            RegionOfInterest::Off => None,
+            RegionOfInterest::Cursor => Some(RegionShape::Circle {
+                center: self.cursor_position,
+                radius: progressive_data.roi_radius,
+            }),
+            RegionOfInterest::Drawn => self.drawn_roi,
+        }
+    }
+
+    // Prepares the next sample and returns how the render pass should load the accumulated samples,
+    // or None if no more samples need to be taken
+    pub(crate) fn next_sample(
         &mut self,
         queue: &wgpu::Queue,
-        delta_phi: Radians,
-        delta_theta: Radians,
-    ) {
-        if !self.camera_rotatable {
-            return;
+        progressive_data: ProgressiveData,
+    ) -> Option<wgpu::LoadOp<wgpu::Color>> {
+        if !progressive_data.is_progressive {
+            self.reset_samples();
         }
 
-        let angles = self.camera_data.angles;
-        let Vector2(new_phi, mut new_theta) = angles + Vector2(delta_phi, delta_theta);
+        let index = self.sample_data.index;
+        let region = self.region_of_interest(progressive_data);
+        let (inside_rate, outside_rate) = progressive_data.sample_rates(index, region.is_some())?;
+
+        self.sample_data = SampleData {
+            index,
+            // First sample goes through pixel centers, the following ones are spread over the pixel
+            jitter: match (index, progressive_data.is_taa) {
+                (0, false) => Vector2(0., 0.),
+                (0, true) => {
+                    self.taa_frame = self.taa_frame.wrapping_add(1);
+                    let taa_index = self.taa_frame % TAA_JITTER_PERIOD + 1;
+                    Vector2(halton(taa_index, 2) - 0.5, halton(taa_index, 3) - 0.5)
+                }
+                _ => Vector2(halton(index, 2) - 0.5, halton(index, 3) - 0.5),
+            },
+            region,
+            inside_rate,
+            outside_rate,
+            tile: self.scaled_view(),
+        };
+        self.sample_uniform_buffer
+            .update_buffer(queue, self.sample_data);
+        self.sample_data.index += 1;
+
+        Some(if index == 0 {
+            wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT)
+        } else {
+            wgpu::LoadOp::Load
+        })
+    }
 
-        // Limit theta on [-PI/2, PI/2]
-        new_theta = new_theta.clamp(-PI / 2., PI / 2.);
+    // Whole screen as a smaller image, or None at full resolution
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    fn scaled_view(&self) -> Option<TileView> {
+        (self.render_scale < 1.).then_some(TileView {
+            offset: Vector2(0., 0.),
+            image_size: Vector2(
+                self.screen_data.width as f32 * self.render_scale,
+                self.screen_data.height as f32 * self.render_scale,
+            ),
+        })
+    }
 
-        self.camera_data = CameraData {
-            angles: Vector2(new_phi.standardize(), new_theta),
-            ..self.camera_data
+    // Prepares a sample of a tile, the following frame starts accumulating from scratch again
+    pub(crate) fn prepare_tile_sample(&mut self, queue: &wgpu::Queue, tile: TileView, index: u32) {
+        self.sample_data = SampleData {
+            index,
+            jitter: if index == 0 {
+                Vector2(0., 0.)
+            } else {
+                Vector2(halton(index, 2) - 0.5, halton(index, 3) - 0.5)
+            },
+            tile: Some(tile),
+            ..SampleData::default()
         };
-        self.camera_uniform_buffer
-            .update_buffer(queue, self.camera_data);
+        self.sample_uniform_buffer
+            .update_buffer(queue, self.sample_data);
+        self.reset_samples();
     }
 
-    pub(crate) fn update_options(&mut self, queue: &wgpu::Queue, new_options_data: OptionsData) {
-        self.options_data = new_options_data;
-        self.options_uniform_buffer
-            .update_buffer(queue, self.options_data);
+    // Compiles the pipeline of the current fractal group unless it is cached, has to be called
+    // before rendering whenever the options might have changed the group
+    pub(crate) fn prepare_pipeline(&mut self, device: &wgpu::Device) {
+        let fractal_group = self.options_data.fractal_group;
+        let bind_group_layouts = [
+            self.uniform_group.bind_group_layout(),
+            self.storage_group.bind_group_layout(),
+        ];
+        let user_source = match fractal_group {
+            FractalGroup::CustomFormula => self.custom_formula.as_str(),
+            FractalGroup::ShaderFile => self.shader_file.as_str(),
+            _ => "",
+        };
+        let evicted = self
+            .pipeline_cache
+            .touch_or_insert_with(fractal_group.id(), || {
+                log::info!("Compiling the {fractal_group} pipeline");
+                Self::create_fractal_pipeline(
+                    device,
+                    &bind_group_layouts,
+                    self.format,
+                    fractal_group,
+                    user_source,
+                )
+            });
+
+        if let Some(group) = evicted.and_then(FractalGroup::from_id) {
+            log::debug!(
+                "Evicted the {group} pipeline, keeping {} compiled pipelines",
+                self.pipeline_cache.len()
+            );
+        }
     }
 
-    pub(crate) fn render(&self, render_pass: &mut wgpu::RenderPass) {
-        match self.options_data.fractal_group {
-            FractalGroup::KaleidoscopicIFS => {
-                render_pass.set_pipeline(&self.kifs_pipeline);
-            }
-            FractalGroup::JuliaSet => {
-                render_pass.set_pipeline(&self.julia_pipeline);
-            }
-            FractalGroup::GeneralizedJuliaSet => {
-                render_pass.set_pipeline(&self.generalized_julia_pipeline);
-            }
+    // Source last compiled successfully for the custom formula or shader file group, empty for the
+    // groups which have none
+    #[must_use]
+    pub(crate) fn user_source(&self, fractal_group: FractalGroup) -> &str {
+        match fractal_group {
+            FractalGroup::CustomFormula => self.custom_formula.as_str(),
+            FractalGroup::ShaderFile => self.shader_file.as_str(),
+            _ => "",
+        }
+    }
+
+    /// Compiles user source of the custom formula or shader file group, replacing the pipeline of
+    /// the group only when it is valid
+    ///
+    /// ## Errors
+    /// - `ShaderCompileError` with the validation message when the shader failed to compile
+    pub(crate) fn compile_user_source(
+        &mut self,
+        device: &wgpu::Device,
+        fractal_group: FractalGroup,
+        user_source: String,
+    ) -> Result<(), ShaderCompileError> {
+        let bind_group_layouts = [
+            self.uniform_group.bind_group_layout(),
+            self.storage_group.bind_group_layout(),
+        ];
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let pipeline = Self::create_fractal_pipeline(
+            device,
+            &bind_group_layouts,
+            self.format,
+            fractal_group,
+            &user_source,
+        );
+        if let Some(error) = pollster::block_on(device.pop_error_scope()) {
+            return Err(ShaderCompileError(error.to_string()));
         }
+
+        let id = fractal_group.id();
+        self.pipeline_cache.remove(id);
+        self.pipeline_cache.touch_or_insert_with(id, || pipeline);
+        match fractal_group {
+            FractalGroup::CustomFormula => self.custom_formula = user_source,
+            FractalGroup::ShaderFile => self.shader_file = user_source,
+            _ => {}
+        }
+        self.reset_samples();
+
+        Ok(())
+    }
+
+    pub(crate) fn render(&self, render_pass: &mut wgpu::RenderPass) {
+        // Nothing is drawn until the pipeline was prepared
+        let Some(pipeline) = self
+            .pipeline_cache
+            .peek(self.options_data.fractal_group.id())
+        else {
+            return;
+        };
+        render_pass.set_pipeline(pipeline);
         render_pass.set_bind_group(0, self.uniform_group.bind_group(), &[]);
+        render_pass.set_bind_group(1, self.storage_group.bind_group(), &[]);
 
         render_pass.draw(0..3, 0..2);
     }
@@ -1,50 +1,425 @@
 use egui::{
-    ClippedPrimitive, Context, DragValue, Label, RichText, TexturesDelta, Ui, Vec2, ViewportId,
+    Align, Align2, ClippedPrimitive, Color32, Context, CornerRadius, DragValue, FontData, FontId,
+    Frame, Id, Image, Label, LayerId, Margin, Order, Pos2, Rect, Response, RichText, Sense, Shape,
+    Slider, Stroke, StrokeKind, TextEdit, TextureId, TexturesDelta, Ui, Vec2, ViewportId,
     Window as EguiWindow,
+    collapsing_header::CollapsingState,
+    epaint::{
+        ClippedShape,
+        text::{FontInsert, FontPriority, InsertFontFamily},
+    },
 };
 use egui_wgpu::{Renderer, ScreenDescriptor, wgpu};
 use egui_winit::{EventResponse, State as EguiState};
+use std::{
+    env,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 use strum::IntoEnumIterator as _;
 use winit::{event::WindowEvent, window::Window};
 
 use crate::{
+    crash::{CRASH_REPORT_PATH, take_unseen_crash},
     data::{
-        GuiData,
-        scene::{FractalGroup, PrimitiveShape},
+        CameraData, GuiData, Light, MAX_HYBRID_PATTERN_LENGTH, MAX_SAMPLES, MAX_SCENE_OBJECTS,
+        MAX_VERTICAL_FOV, MIN_VERTICAL_FOV, Marker, PreviewData, RayPath, RayStep,
+        assets::{AssetManager, AssetSource, MAX_EMBEDDED_ASSET_SIZE},
+        bindings::{ParameterBinding, apply_bindings, numeric_parameters},
+        camera_path::{CameraPath, CameraPathFormat, DEFAULT_CAMERA_PATH_FILE, KeyframeEasing},
+        diagnostics::ArtifactWarning,
+        formula::{DEFAULT_DISTANCE, DEFAULT_ITERATION, transpile},
+        library::LIBRARY_PRESETS,
+        mesh::{MeshSettings, TriangleMesh},
+        node_graph::{FractalNode, NodeGraph, NodeKind, NodeShape},
+        options::{
+            AovLayer, CameraMode, ColoringMode, CsgOperation, CsgShape, CutawaySide, DebugView,
+            DistanceClamping, FloorPattern, ObjectShading, ParameterAxis, Projection,
+            RegionOfInterest, RenderMode, StereoMode, TransferFunction, UpscaleFilter, WarpAxis,
+        },
+        preset::{SeededPreset, parse_seed, seed_link},
+        project::{DEFAULT_PROJECT_PATH, load_project, save_project},
+        report::export_settings_report,
+        scene::{
+            ConstantAnimation, FractalControl, FractalGroup, HybridFormula, JuliaEstimator,
+            JuliaFormula, PowerSweep, PrimitiveShape, SceneObjectKind,
+        },
+        tiles::TiledExportSettings,
+        workspace::{PanelLayout, WORKSPACES_PATH, Workspace, load_workspaces, save_workspaces},
     },
-    error::GUIUnconfiguredError,
+    error::{
+        ExpressionError, FormulaError, MeshError, NodeGraphError, ShaderFileError, VoxelExportError,
+    },
+    render::{
+        bake::BakeStatus,
+        legend::{Legend, legend_overlay},
+        snapshot::{MAX_SNAPSHOTS, Snapshot},
+        tiled::TiledExportStatus,
+    },
+    util::math::{MathContext, Vector2, Vector3},
 };
+use winit::dpi::PhysicalSize;
 
-fn general_section(ui: &mut Ui, gui_data: &mut GuiData) {
-    ui.heading(RichText::new("General settings").strong());
+fn frame_budget(ui: &mut Ui, gui_data: &mut GuiData) {
+    ui.label("Frame budget:").on_hover_text(
+        "Lower the iteration count while moving the camera or changing settings to keep the frame time",
+    );
+    ui.checkbox(&mut gui_data.is_frame_budget, "").on_hover_text(
+        "Lower the iteration count while moving the camera or changing settings to keep the frame time",
+    );
     ui.end_row();
 
-    ui.label("Max iterations:")
-        .on_hover_text("Maximum number of steps to take when raymarching");
-    ui.add(DragValue::new(&mut gui_data.max_iterations).range(1..=1000))
-        .on_hover_text("Maximum number of steps to take when raymarching");
+    if gui_data.is_frame_budget {
+        ui.label("Target frame time:")
+            .on_hover_text("Frame time to hold during interaction");
+        ui.add(
+            DragValue::new(&mut gui_data.target_frame_time)
+                .range(4.0..=100.0)
+                .suffix(" ms"),
+        )
+        .on_hover_text("Frame time to hold during interaction");
+        ui.end_row();
+    }
+}
+
+fn frame_latency(ui: &mut Ui, gui_data: &mut GuiData) {
+    ui.label("Low latency:").on_hover_text(
+        "Only let a single frame wait for the GPU, so the view follows the input while tuning",
+    );
+    ui.checkbox(&mut gui_data.is_low_latency, "").on_hover_text(
+        "Only let a single frame wait for the GPU, so the view follows the input while tuning",
+    );
+    ui.end_row();
+
+    if !gui_data.is_low_latency {
+        ui.label("Frame latency:").on_hover_text(
+            "Frames prepared ahead of the GPU, more give smoother frame rates but lag behind the input",
+        );
+        ui.add(Slider::new(&mut gui_data.max_frame_latency, 1..=3))
+            .on_hover_text(
+                "Frames prepared ahead of the GPU, more give smoother frame rates but lag behind the input",
+            );
+        ui.end_row();
+    }
+}
+
+fn camera_smoothing(ui: &mut Ui, gui_data: &mut GuiData) {
+    ui.label("Camera smoothing:").on_hover_text(
+        "Ease the camera towards where the mouse moved it over a few frames, for smoother captured videos",
+    );
+    ui.checkbox(&mut gui_data.is_camera_smoothing, "").on_hover_text(
+        "Ease the camera towards where the mouse moved it over a few frames, for smoother captured videos",
+    );
+    ui.end_row();
+
+    if gui_data.is_camera_smoothing {
+        ui.label("Smoothing time:").on_hover_text(
+            "Seconds the camera takes to cover most of the way to where it was moved",
+        );
+        ui.add(Slider::new(&mut gui_data.camera_smoothing, 0.01..=1.0).suffix(" s"))
+            .on_hover_text(
+                "Seconds the camera takes to cover most of the way to where it was moved",
+            );
+        ui.end_row();
+    }
+}
+
+fn camera_collision(ui: &mut Ui, gui_data: &mut GuiData) {
+    ui.label("Camera collision:").on_hover_text(
+        "Stop the camera before it enters the surface, measured by the distance estimate at its position",
+    );
+    ui.checkbox(&mut gui_data.is_camera_collision, "").on_hover_text(
+        "Stop the camera before it enters the surface, measured by the distance estimate at its position",
+    );
+    ui.end_row();
+}
+
+fn drag_preview(ui: &mut Ui, gui_data: &mut GuiData) {
+    ui.label("Drag preview:").on_hover_text(
+        "Lower the iteration count and resolution while dragging a value, full quality returns on release",
+    );
+    ui.checkbox(&mut gui_data.is_drag_preview, "").on_hover_text(
+        "Lower the iteration count and resolution while dragging a value, full quality returns on release",
+    );
+    ui.end_row();
+
+    if gui_data.is_drag_preview {
+        ui.label("Preview resolution:")
+            .on_hover_text("Fraction of the resolution rendered while dragging");
+        ui.add(Slider::new(&mut gui_data.drag_preview_scale, 0.25..=1.0))
+            .on_hover_text("Fraction of the resolution rendered while dragging");
+        ui.end_row();
+    }
+}
+
+// Drag steps per point relative to the zoom scale of the camera
+const CAMERA_DISTANCE_DRAG_STEP: f64 = 0.01;
+const EPSILON_DRAG_STEP: f64 = 1e-6;
+
+// Speed and shown decimal places of a drag value following the zoom depth, so a value dragged at a
+// deep zoom moves in steps as fine as the detail on screen and far away in steps as coarse
+fn zoom_drag_value(value: &mut f32, zoom_scale: f32, relative_step: f64) -> DragValue<'_> {
+    let step = f64::from(zoom_scale) * relative_step;
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let decimals = (-step.log10()).ceil().clamp(0., 15.) as usize;
+
+    DragValue::new(value)
+        .speed(step)
+        .min_decimals(decimals)
+        .max_decimals(decimals + 2)
+}
+
+fn camera_mode(ui: &mut Ui, camera_data: &mut CameraData) {
+    ui.label("Camera mode:").on_hover_text(
+        "Orbit around the pivot, or fly freely with WASD, E and Q, sprinting with shift and changing the speed by scrolling",
+    );
+    let mut mode = camera_data.mode;
+    egui::ComboBox::from_id_salt("camera_mode")
+        .selected_text(format!("{mode}"))
+        .show_ui(ui, |ui| {
+            for option in CameraMode::iter() {
+                ui.selectable_value(&mut mode, option, format!("{option}"));
+            }
+        })
+        .response
+        .on_hover_text(
+            "Orbit around the pivot, or fly freely with WASD, E and Q, sprinting with shift and changing the speed by scrolling",
+        );
+    *camera_data = camera_data.with_mode(mode);
+    ui.end_row();
+
+    ui.label("Field of view:")
+        .on_hover_text("Angle the height of the view spans, narrower views zoom in without moving");
+    ui.add(
+        Slider::new(
+            &mut camera_data.vertical_fov,
+            MIN_VERTICAL_FOV..=MAX_VERTICAL_FOV,
+        )
+        .suffix("°"),
+    )
+    .on_hover_text("Angle the height of the view spans, narrower views zoom in without moving");
     ui.end_row();
+}
 
+// Rows whose steps follow the zoom depth of the camera
+fn march_precision(ui: &mut Ui, gui_data: &mut GuiData, camera_data: &mut CameraData) {
     ui.label("Max distance:")
         .on_hover_text("Maximum distance before we stop rendering");
     ui.add(DragValue::new(&mut gui_data.max_distance).range(10.0..=10000.0))
         .on_hover_text("Maximum distance before we stop rendering");
     ui.end_row();
 
+    let zoom_scale = camera_data.zoom_scale();
     ui.label("Epsilon:")
         .on_hover_text("Accuracy of calculation");
     ui.add(
-        DragValue::new(&mut gui_data.epsilon)
-            .speed(0.000_001)
+        zoom_drag_value(&mut gui_data.epsilon, zoom_scale, EPSILON_DRAG_STEP)
             .range(0.000_001..=1.0),
     )
     .on_hover_text("Accuracy of calculations");
     ui.end_row();
 
-    ui.label("Heatmap rendering:")
-        .on_hover_text("Display color via heatmap - brighter spots have higher iteration count");
-    ui.checkbox(&mut gui_data.is_heatmap, "")
-        .on_hover_text("Display color via heatmap - brighter spots have higher iteration count");
+    // Only the orbit camera keeps a distance to the pivot
+    if camera_data.mode == CameraMode::FreeFly {
+        return;
+    }
+
+    ui.label("Camera distance:")
+        .on_hover_text("Distance of the camera from the point it orbits");
+    let min_distance = camera_data.min_distance;
+    ui.add(
+        zoom_drag_value(
+            &mut camera_data.origin_distance,
+            zoom_scale,
+            CAMERA_DISTANCE_DRAG_STEP,
+        )
+        .range(min_distance..=gui_data.max_distance.max(min_distance)),
+    )
+    .on_hover_text("Distance of the camera from the point it orbits");
+    ui.end_row();
+
+    ui.label("Zoom speed:").on_hover_text(
+        "Multiplier of the scrolling, every line covers the same share of the distance left to the minimum distance",
+    );
+    ui.add(Slider::new(&mut gui_data.zoom_speed, 0.1..=5.0).logarithmic(true))
+        .on_hover_text(
+            "Multiplier of the scrolling, every line covers the same share of the distance left to the minimum distance",
+        );
+    ui.end_row();
+
+    ui.label("Pan target:").on_hover_text(
+        "Point the camera orbits and looks at, moved by dragging with the middle mouse button or with shift held",
+    );
+    ui.horizontal(|ui| {
+        vector3_drag_values(
+            ui,
+            &mut camera_data.pan_target,
+            f64::from(zoom_scale) * CAMERA_DISTANCE_DRAG_STEP,
+            -gui_data.max_distance..=gui_data.max_distance,
+            "Point the camera orbits and looks at, moved by dragging with the middle mouse button or with shift held",
+        );
+        if ui
+            .button("Reset")
+            .on_hover_text("Look at the pivot again")
+            .clicked()
+        {
+            camera_data.pan_target = CameraData::PIVOT;
+        }
+    });
+    ui.end_row();
+}
+
+fn distance_clamping(ui: &mut Ui, gui_data: &mut GuiData) {
+    ui.label("Distance clamping:").on_hover_text(
+        "Share of the distance estimate marched at once, lower values close holes left by estimates which overshoot",
+    );
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_salt("distance_clamping")
+            .selected_text(format!("{}", gui_data.distance_clamping))
+            .show_ui(ui, |ui| {
+                for clamping in DistanceClamping::iter() {
+                    ui.selectable_value(
+                        &mut gui_data.distance_clamping,
+                        clamping,
+                        format!("{clamping}"),
+                    );
+                }
+            });
+        match gui_data.distance_clamping {
+            DistanceClamping::Off => {}
+            DistanceClamping::FractalDefault => {
+                ui.label(format!("{:.2}", gui_data.fractal_group.distance_fudge()));
+            }
+            DistanceClamping::Custom => {
+                ui.add(Slider::new(&mut gui_data.distance_fudge, 0.05..=1.0));
+            }
+        }
+    })
+    .response
+    .on_hover_text(
+        "Share of the distance estimate marched at once, lower values close holes left by estimates which overshoot",
+    );
+    ui.end_row();
+}
+
+fn step_clamp(ui: &mut Ui, gui_data: &mut GuiData) {
+    ui.label("Step clamp:").on_hover_text(
+        "Cut down long steps, which keeps optimistic estimates from jumping through thin details",
+    );
+    ui.checkbox(&mut gui_data.is_step_clamped, "").on_hover_text(
+        "Cut down long steps, which keeps optimistic estimates from jumping through thin details",
+    );
+    ui.end_row();
+
+    if gui_data.is_step_clamped {
+        ui.label("Max step:")
+            .on_hover_text("Longest single step of the march");
+        ui.add(
+            DragValue::new(&mut gui_data.max_step)
+                .speed(0.001)
+                .range(0.001..=10.0),
+        )
+        .on_hover_text("Longest single step of the march");
+        ui.end_row();
+    }
+}
+
+fn general_section(ui: &mut Ui, gui_data: &mut GuiData, camera_data: &mut CameraData) {
+    ui.heading(RichText::new("General settings").strong());
+    ui.end_row();
+
+    ui.label("Max iterations:")
+        .on_hover_text("Maximum number of steps to take when raymarching");
+    ui.add(DragValue::new(&mut gui_data.max_iterations).range(1..=1000))
+        .on_hover_text("Maximum number of steps to take when raymarching");
+    ui.end_row();
+
+    frame_budget(ui, gui_data);
+    frame_latency(ui, gui_data);
+    drag_preview(ui, gui_data);
+
+    camera_mode(ui, camera_data);
+    camera_smoothing(ui, gui_data);
+    camera_collision(ui, gui_data);
+    march_precision(ui, gui_data, camera_data);
+
+    ui.label("Relaxation:").on_hover_text(
+        "Step length multiplier, larger values march faster but can overshoot thin details",
+    );
+    ui.add(Slider::new(&mut gui_data.relaxation, 1.0..=2.0))
+        .on_hover_text(
+            "Step length multiplier, larger values march faster but can overshoot thin details",
+        );
+    ui.end_row();
+
+    distance_clamping(ui, gui_data);
+
+    step_clamp(ui, gui_data);
+
+    ui.label("Coloring:").on_hover_text(
+        "Heatmap - brighter spots have higher iteration count, Normal - surface direction as RGB",
+    );
+    egui::ComboBox::from_label("Coloring")
+        .selected_text(format!("{}", gui_data.coloring_mode))
+        .show_ui(ui, |ui| {
+            for mode in ColoringMode::iter() {
+                ui.selectable_value(&mut gui_data.coloring_mode, mode, format!("{mode}"));
+            }
+        });
+    ui.end_row();
+
+    if gui_data.coloring_mode == ColoringMode::Heatmap {
+        ui.label("Smooth heatmap:")
+            .on_hover_text("Interpolate between iteration counts to avoid color banding");
+        ui.checkbox(&mut gui_data.is_smooth_heatmap, "")
+            .on_hover_text("Interpolate between iteration counts to avoid color banding");
+        ui.end_row();
+
+        ui.label("Heatmap ramp:")
+            .on_hover_text("Scale and offset of the color ramp over the iteration count");
+        ui.horizontal(|ui| {
+            ui.add(
+                DragValue::new(&mut gui_data.heatmap_scale)
+                    .speed(0.01)
+                    .range(0.0..=100.0)
+                    .prefix("scale "),
+            );
+            ui.add(
+                DragValue::new(&mut gui_data.heatmap_offset)
+                    .speed(0.01)
+                    .range(-1.0..=1.0)
+                    .prefix("offset "),
+            );
+        })
+        .response
+        .on_hover_text("Scale and offset of the color ramp over the iteration count");
+        ui.end_row();
+    }
+
+    ui.label("Analytic normals:").on_hover_text(
+        "Use exact surface gradients where known instead of 6 extra distance evaluations",
+    );
+    ui.checkbox(&mut gui_data.is_analytic_normal, "")
+        .on_hover_text(
+            "Use exact surface gradients where known instead of 6 extra distance evaluations",
+        );
+    ui.end_row();
+
+    ui.label("Projection:").on_hover_text(
+        "Perspective camera or a full 360° panorama, which locks the window to a 2:1 aspect ratio",
+    );
+    egui::ComboBox::from_label("Projection")
+        .selected_text(format!("{}", gui_data.projection))
+        .show_ui(ui, |ui| {
+            for projection in Projection::iter() {
+                ui.selectable_value(
+                    &mut gui_data.projection,
+                    projection,
+                    format!("{projection}"),
+                );
+            }
+        });
     ui.end_row();
 
     ui.label("Fractal color:");
@@ -56,147 +431,3826 @@ fn general_section(ui: &mut Ui, gui_data: &mut GuiData) {
     ui.end_row();
 }
 
-fn julia_description(ui: &mut Ui, gui_data: &mut GuiData) {
-    ui.label("Description:");
-    ui.add(Label::new(
-        "3D Julia sets are rendered by finding all quaternions that do not converge to infinity under continuous iteration of a function.\
-        These are then displayed by rendering only three of the coordinate axes."
-    ).wrap());
+fn scene_objects_section(ui: &mut Ui, gui_data: &mut GuiData) {
+    ui.heading(RichText::new("Scene objects").strong());
     ui.end_row();
 
-    ui.label("Quaternion function:")
-        .on_hover_text("Function used to construct the Julia set");
-    ui.label(format!(
-        "f(q) = q^{} + ({}, {}, {}, {})",
-        match gui_data.fractal_group {
-            FractalGroup::GeneralizedJuliaSet => format!("{}", gui_data.power),
-            _ => "2".to_string(),
-        },
-        gui_data.constant.0,
-        gui_data.constant.1,
-        gui_data.constant.2,
-        gui_data.constant.3,
-    ))
-    .on_hover_text("Function used to construct the Julia set");
+    for index in 0..MAX_SCENE_OBJECTS {
+        let object = &mut gui_data.scene_objects[index];
+        ui.label(format!("Object {}:", index + 1)).on_hover_text(
+            "Fractal or unit sized primitive, every enabled object is part of the scene",
+        );
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut object.is_enabled, "");
+            egui::ComboBox::from_id_salt(("scene_object_kind", index))
+                .selected_text(format!("{}", object.kind))
+                .show_ui(ui, |ui| {
+                    for kind in SceneObjectKind::iter() {
+                        ui.selectable_value(&mut object.kind, kind, format!("{kind}"));
+                    }
+                });
+        })
+        .response
+        .on_hover_text(
+            "Fractal or unit sized primitive, every enabled object is part of the scene",
+        );
+        ui.end_row();
+
+        if !object.is_enabled {
+            continue;
+        }
+
+        ui.label("    Parent:").on_hover_text(
+            "Object the transform is relative to, moving it moves this object along",
+        );
+        egui::ComboBox::from_id_salt(("scene_object_parent", index))
+            .selected_text(object.parent.map_or("None".to_string(), |parent| {
+                format!("Object {}", parent + 1)
+            }))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut object.parent, None, "None");
+                for parent in (0..MAX_SCENE_OBJECTS).filter(|parent| *parent != index) {
+                    ui.selectable_value(
+                        &mut object.parent,
+                        Some(parent),
+                        format!("Object {}", parent + 1),
+                    );
+                }
+            })
+            .response
+            .on_hover_text(
+                "Object the transform is relative to, moving it moves this object along",
+            );
+        ui.end_row();
+
+        ui.label("    Position:")
+            .on_hover_text("Translation of the object from the origin of its parent");
+        ui.horizontal(|ui| {
+            for i in 0..3 {
+                ui.add(DragValue::new(&mut object.translation[i]).speed(0.01));
+            }
+        })
+        .response
+        .on_hover_text("Translation of the object from the origin of its parent");
+        ui.end_row();
+
+        ui.label("    Rotation:")
+            .on_hover_text("Rotation about the x, y and z axes, applied in that order");
+        ui.horizontal(|ui| {
+            for i in 0..3 {
+                ui.add(
+                    DragValue::new(&mut object.rotation[i])
+                        .speed(1.)
+                        .range(-180.0..=180.0)
+                        .suffix("°"),
+                );
+            }
+        })
+        .response
+        .on_hover_text("Rotation about the x, y and z axes, applied in that order");
+        ui.end_row();
+
+        ui.label("    Scale:")
+            .on_hover_text("Uniform scale of the object");
+        ui.add(Slider::new(&mut object.scale, 0.05..=5.0).logarithmic(true))
+            .on_hover_text("Uniform scale of the object");
+        ui.end_row();
+    }
+}
+
+fn csg_section(ui: &mut Ui, gui_data: &mut GuiData) {
+    ui.heading(RichText::new("CSG shape").strong());
+    ui.end_row();
+
+    ui.label("Operation:")
+        .on_hover_text("How a primitive shape is combined with the fractal");
+    egui::ComboBox::from_label("Operation")
+        .selected_text(format!("{}", gui_data.csg_operation))
+        .show_ui(ui, |ui| {
+            for operation in CsgOperation::iter() {
+                ui.selectable_value(
+                    &mut gui_data.csg_operation,
+                    operation,
+                    format!("{operation}"),
+                );
+            }
+        })
+        .response
+        .on_hover_text("How a primitive shape is combined with the fractal");
+    ui.end_row();
+
+    if gui_data.csg_operation == CsgOperation::Off {
+        return;
+    }
+
+    ui.label("Shape:")
+        .on_hover_text("Primitive shape combined with the fractal");
+    egui::ComboBox::from_label("Shape")
+        .selected_text(format!("{}", gui_data.csg_shape))
+        .show_ui(ui, |ui| {
+            for shape in CsgShape::iter() {
+                ui.selectable_value(&mut gui_data.csg_shape, shape, format!("{shape}"));
+            }
+        });
+    ui.end_row();
+
+    ui.label("Center:")
+        .on_hover_text("Position of the center of the shape");
+    ui.horizontal(|ui| {
+        for i in 0..3 {
+            ui.add(DragValue::new(&mut gui_data.csg_center[i]).speed(0.01));
+        }
+    })
+    .response
+    .on_hover_text("Position of the center of the shape");
+    ui.end_row();
+
+    ui.label("Size:")
+        .on_hover_text("Radius of the sphere, cylinder and torus, or half the side of the box");
+    ui.add(Slider::new(&mut gui_data.csg_size, 0.05..=3.0).logarithmic(true))
+        .on_hover_text("Radius of the sphere, cylinder and torus, or half the side of the box");
+    ui.end_row();
+
+    ui.label("Smoothness:")
+        .on_hover_text("Distance over which the shape and the fractal melt into each other, zero keeps a sharp edge");
+    ui.add(Slider::new(&mut gui_data.csg_smoothness, 0.0..=1.0))
+        .on_hover_text("Distance over which the shape and the fractal melt into each other, zero keeps a sharp edge");
     ui.end_row();
 }
 
-fn julia_power(ui: &mut Ui, gui_data: &mut GuiData) {
-    ui.label("Power variable:")
-        .on_hover_text("Power variable in quaternion function");
-    ui.add(
-        DragValue::new(&mut gui_data.power)
-            .speed(0.01)
-            .range(1.0..=10.0),
-    )
-    .on_hover_text("Power variable in quaternion function");
+fn warp_section(ui: &mut Ui, gui_data: &mut GuiData) {
+    ui.heading(RichText::new("Symmetry and warps").strong());
+    ui.end_row();
+
+    ui.label("Twist:")
+        .on_hover_text("Rotation about the axis in radians per unit along it, zero turns it off");
+    ui.horizontal(|ui| {
+        ui.add(Slider::new(&mut gui_data.twist_strength, -3.0..=3.0));
+        egui::ComboBox::from_id_salt("twist_axis")
+            .selected_text(format!("{}", gui_data.twist_axis))
+            .width(40.)
+            .show_ui(ui, |ui| {
+                for axis in WarpAxis::iter() {
+                    ui.selectable_value(&mut gui_data.twist_axis, axis, format!("{axis}"));
+                }
+            });
+    })
+    .response
+    .on_hover_text("Rotation about the axis in radians per unit along it, zero turns it off");
+    ui.end_row();
+
+    ui.label("Bend:")
+        .on_hover_text("Curves the x axis upwards in radians per unit along it, zero turns it off");
+    ui.add(Slider::new(&mut gui_data.bend_strength, -1.0..=1.0))
+        .on_hover_text("Curves the x axis upwards in radians per unit along it, zero turns it off");
+    ui.end_row();
+
+    ui.label("Mirror planes:").on_hover_text(
+        "Reflect the fractal about the planes, the axis ones keep its positive side",
+    );
+    let planes = &mut gui_data.mirror_planes;
+    ui.vertical(|ui| {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut planes.is_x, "X");
+            ui.checkbox(&mut planes.is_y, "Y");
+            ui.checkbox(&mut planes.is_z, "Z");
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut planes.is_xy, "X=Y");
+            ui.checkbox(&mut planes.is_yz, "Y=Z");
+            ui.checkbox(&mut planes.is_xz, "X=Z");
+        });
+    })
+    .response
+    .on_hover_text("Reflect the fractal about the planes, the axis ones keep its positive side");
     ui.end_row();
 }
 
-fn julia_constant(ui: &mut Ui, gui_data: &mut GuiData) {
-    ui.label("Constant variable:")
-        .on_hover_text("Constant variable in quaternion function");
+fn clipping_section(ui: &mut Ui, gui_data: &mut GuiData) {
+    ui.heading(RichText::new("Clipping plane").strong());
+    ui.end_row();
+
+    ui.label("Clipping:")
+        .on_hover_text("Cut the fractal open along a plane to see its interior");
+    ui.checkbox(&mut gui_data.is_clipping, "")
+        .on_hover_text("Cut the fractal open along a plane to see its interior");
+    ui.end_row();
+
+    if !gui_data.is_clipping {
+        return;
+    }
+
+    ui.label("Plane normal:")
+        .on_hover_text("Direction the plane faces, everything on this side of it is cut away");
+    ui.vertical(|ui| {
+        for (i, axis) in ["x", "y", "z"].into_iter().enumerate() {
+            ui.add(Slider::new(&mut gui_data.clip_normal[i], -1.0..=1.0).text(axis));
+        }
+    })
+    .response
+    .on_hover_text("Direction the plane faces, everything on this side of it is cut away");
+    ui.end_row();
+
+    ui.label("Plane offset:")
+        .on_hover_text("Distance of the plane from the origin along its normal");
+    ui.add(Slider::new(&mut gui_data.clip_offset, -2.0..=2.0))
+        .on_hover_text("Distance of the plane from the origin along its normal");
+    ui.end_row();
+
+    ui.label("Interior color:")
+        .on_hover_text("Color of the surface left where the fractal was cut");
+    ui.color_edit_button_srgb(&mut gui_data.clip_color)
+        .on_hover_text("Color of the surface left where the fractal was cut");
+    ui.end_row();
+}
+
+fn cutaway_section(ui: &mut Ui, gui_data: &mut GuiData) {
+    ui.heading(RichText::new("Cutaway").strong());
+    ui.end_row();
+
+    ui.label("Removed octant:").on_hover_text(
+        "Side of every axis the removed part lies on, any leaves it open along the axis, \
+        so two sides remove a quadrant and three an octant",
+    );
     ui.horizontal(|ui| {
-        ui.style_mut().spacing.item_spacing = Vec2::new(3., 3.);
-        for i in 0..4 {
-            ui.add(
-                DragValue::new(&mut gui_data.constant[i])
-                    .speed(0.01)
-                    .range(-1.0..=1.0),
-            )
-            .on_hover_text("Constant variable in quaternion function");
+        for (i, axis) in ["x", "y", "z"].into_iter().enumerate() {
+            egui::ComboBox::from_label(axis)
+                .selected_text(format!("{}", gui_data.cutaway[i]))
+                .width(80.)
+                .show_ui(ui, |ui| {
+                    for side in CutawaySide::iter() {
+                        ui.selectable_value(&mut gui_data.cutaway[i], side, format!("{side}"));
+                    }
+                });
         }
+    })
+    .response
+    .on_hover_text(
+        "Side of every axis the removed part lies on, any leaves it open along the axis, \
+        so two sides remove a quadrant and three an octant",
+    );
+    ui.end_row();
+
+    // Caps share the color of the clipping plane, which is only shown along with the plane
+    if gui_data
+        .cutaway
+        .iter()
+        .any(|side| *side != CutawaySide::Any)
+        && !gui_data.is_clipping
+    {
+        ui.label("Interior color:")
+            .on_hover_text("Color of the surface left where the fractal was cut");
+        ui.color_edit_button_srgb(&mut gui_data.clip_color)
+            .on_hover_text("Color of the surface left where the fractal was cut");
+        ui.end_row();
+    }
+}
+
+// Shading terms of a single object, which cost additional distance evaluations per pixel
+fn object_shading(ui: &mut Ui, label: &str, shading: &mut ObjectShading) {
+    ui.label(label)
+        .on_hover_text("Expensive shading terms the object receives, cheaper when turned off");
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut shading.is_soft_shadows, "Soft shadows")
+            .on_hover_text("Shadows cast by the fractal, marched towards every light");
+        ui.checkbox(&mut shading.is_ambient_occlusion, "Ambient occlusion")
+            .on_hover_text("Darkening where the fractal is close to the surface");
     });
     ui.end_row();
 }
 
-fn fractal_group_section(ui: &mut Ui, gui_data: &mut GuiData) {
-    ui.heading(RichText::new("Fractal settings").strong());
+fn floor_section(ui: &mut Ui, gui_data: &mut GuiData) {
+    ui.heading(RichText::new("Floor").strong());
     ui.end_row();
 
-    ui.label("Fractal group:")
-        .on_hover_text("Group of fractals to display");
-    egui::ComboBox::from_label("Group")
-        .selected_text(format!("{}", gui_data.fractal_group))
+    ui.label("Floor:")
+        .on_hover_text("Infinite ground plane which catches the shadows of the fractal");
+    ui.checkbox(&mut gui_data.is_floor, "")
+        .on_hover_text("Infinite ground plane which catches the shadows of the fractal");
+    ui.end_row();
+
+    if !gui_data.is_floor {
+        return;
+    }
+
+    ui.label("Pattern:")
+        .on_hover_text("Surface pattern of the floor");
+    egui::ComboBox::from_label("Pattern")
+        .selected_text(format!("{}", gui_data.floor_pattern))
         .show_ui(ui, |ui| {
-            for group in FractalGroup::iter() {
-                ui.selectable_value(&mut gui_data.fractal_group, group, format!("{group}"));
+            for pattern in FloorPattern::iter() {
+                ui.selectable_value(&mut gui_data.floor_pattern, pattern, format!("{pattern}"));
             }
         });
     ui.end_row();
 
-    match gui_data.fractal_group {
-        FractalGroup::KaleidoscopicIFS => {
-            ui.label("Description:");
+    ui.label("Height:")
+        .on_hover_text("Height of the floor along the vertical axis");
+    ui.add(Slider::new(&mut gui_data.floor_height, -5.0..=0.0))
+        .on_hover_text("Height of the floor along the vertical axis");
+    ui.end_row();
+
+    if gui_data.floor_pattern == FloorPattern::Checkerboard {
+        ui.label("Checker scale:")
+            .on_hover_text("Side length of a single checkerboard square");
+        ui.add(Slider::new(&mut gui_data.floor_checker_scale, 0.05..=5.0).logarithmic(true))
+            .on_hover_text("Side length of a single checkerboard square");
+        ui.end_row();
+    }
+
+    ui.label("Floor color:")
+        .on_hover_text("Color of the floor where it is fully lit");
+    ui.color_edit_button_srgb(&mut gui_data.floor_color)
+        .on_hover_text("Color of the floor where it is fully lit");
+    ui.end_row();
+
+    ui.label("Cut at floor:").on_hover_text(
+        "Intersect the scene with the space above the floor, so it rests on the floor instead of passing through",
+    );
+    ui.checkbox(&mut gui_data.is_floor_cut, "").on_hover_text(
+        "Intersect the scene with the space above the floor, so it rests on the floor instead of passing through",
+    );
+    ui.end_row();
+
+    object_shading(ui, "Floor shading:", &mut gui_data.floor_shading);
+}
+
+fn lighting_section(ui: &mut Ui, gui_data: &mut GuiData) {
+    ui.heading(RichText::new("Lighting").strong());
+    ui.end_row();
+
+    ui.label("Ambient light:")
+        .on_hover_text("Light reaching every surface regardless of its orientation");
+    ui.add(Slider::new(&mut gui_data.ambient_light, 0.0..=1.0))
+        .on_hover_text("Light reaching every surface regardless of its orientation");
+    ui.end_row();
+
+    object_shading(ui, "Fractal shading:", &mut gui_data.fractal_shading);
+
+    for (name, light) in Light::NAMES.iter().zip(&mut gui_data.lights) {
+        ui.label(format!("{name} light:")).on_hover_text(
+            "Directional light shading the fractal and casting shadows on the floor",
+        );
+        ui.checkbox(&mut light.is_enabled, "").on_hover_text(
+            "Directional light shading the fractal and casting shadows on the floor",
+        );
+        ui.end_row();
+
+        if !light.is_enabled {
+            continue;
+        }
+
+        ui.label("    Direction:").on_hover_text(
+            "Angle around the vertical axis and angle above the horizon of the light",
+        );
+        ui.horizontal(|ui| {
             ui.add(
-                Label::new(
-                    RichText::new("Currently can only display preset shapes. No fractals :-(.")
-                        .italics(),
-                )
-                .wrap(),
+                DragValue::new(&mut light.azimuth)
+                    .speed(1.)
+                    .range(-180.0..=180.0)
+                    .suffix("°"),
             );
-            ui.end_row();
+            ui.add(
+                DragValue::new(&mut light.elevation)
+                    .speed(1.)
+                    .range(-90.0..=90.0)
+                    .suffix("°"),
+            );
+        })
+        .response
+        .on_hover_text("Angle around the vertical axis and angle above the horizon of the light");
+        ui.end_row();
 
-            ui.label("Preset shapes:")
-                .on_hover_text("Choose one of the preset shapes to display");
-            egui::ComboBox::from_label("Shape")
-                .selected_text(format!("{}", gui_data.primitive_shape))
-                .show_ui(ui, |ui| {
-                    for shape in PrimitiveShape::iter() {
-                        ui.selectable_value(
-                            &mut gui_data.primitive_shape,
-                            shape,
-                            format!("{shape}"),
-                        );
-                    }
-                });
-            ui.end_row();
-        }
-        FractalGroup::JuliaSet => {
-            julia_description(ui, gui_data);
-            julia_constant(ui, gui_data);
-        }
-        FractalGroup::GeneralizedJuliaSet => {
-            julia_description(ui, gui_data);
-            julia_power(ui, gui_data);
-            julia_constant(ui, gui_data);
-        }
+        ui.label("    Color:")
+            .on_hover_text("Color and intensity of the light");
+        ui.horizontal(|ui| {
+            ui.color_edit_button_srgb(&mut light.color);
+            ui.add(
+                DragValue::new(&mut light.intensity)
+                    .speed(0.01)
+                    .range(0.0..=5.0),
+            );
+        })
+        .response
+        .on_hover_text("Color and intensity of the light");
+        ui.end_row();
     }
-    ui.end_row();
 }
 
-fn update_ui(ui: &mut Ui, gui_data: &mut GuiData) {
-    egui::Grid::new("main_grid")
-        .num_columns(2)
-        .spacing([40.0, 4.0])
-        .striped(true)
-        .show(ui, |ui| {
-            ui.label("GUI Theme");
-            let mut theme_preference = ui.ctx().options(|opt| opt.theme_preference);
-            theme_preference.radio_buttons(ui);
-            ui.ctx().set_theme(theme_preference);
+fn stereo_section(ui: &mut Ui, gui_data: &mut GuiData) {
+    ui.heading(RichText::new("Stereo").strong());
+    ui.end_row();
+
+    ui.label("Stereo mode:")
+        .on_hover_text("Render the scene once for each eye and combine the two images");
+    egui::ComboBox::from_label("Stereo")
+        .selected_text(format!("{}", gui_data.stereo_mode))
+        .show_ui(ui, |ui| {
+            for mode in StereoMode::iter() {
+                ui.selectable_value(&mut gui_data.stereo_mode, mode, format!("{mode}"));
+            }
+        });
+    ui.end_row();
+
+    if gui_data.stereo_mode == StereoMode::Off {
+        return;
+    }
+
+    ui.label("Eye separation:")
+        .on_hover_text("Distance between the eyes, larger values make the depth effect stronger");
+    ui.add(Slider::new(&mut gui_data.eye_separation, 0.0..=0.5))
+        .on_hover_text("Distance between the eyes, larger values make the depth effect stronger");
+    ui.end_row();
+}
+
+fn debug_section(ui: &mut Ui, gui_data: &mut GuiData) {
+    ui.heading(RichText::new("Debugging").strong());
+    ui.end_row();
+
+    ui.label("Debug view:")
+        .on_hover_text("Replace the rendered surface with a debugging visualization");
+    egui::ComboBox::from_label("View")
+        .selected_text(format!("{}", gui_data.debug_view))
+        .show_ui(ui, |ui| {
+            for view in DebugView::iter() {
+                ui.selectable_value(&mut gui_data.debug_view, view, format!("{view}"));
+            }
+        });
+    ui.end_row();
+
+    if gui_data.debug_view == DebugView::DistanceField {
+        ui.label("Slice offset:").on_hover_text(
+            "Distance of the slicing plane from the origin, the plane always faces the camera",
+        );
+        ui.add(DragValue::new(&mut gui_data.slice_offset).speed(0.01))
+            .on_hover_text(
+                "Distance of the slicing plane from the origin, the plane always faces the camera",
+            );
+        ui.end_row();
+    }
+
+    ui.label("Color legend:")
+        .on_hover_text("Show the color scale of the heatmap and false color debug views");
+    ui.checkbox(&mut gui_data.is_legend, "")
+        .on_hover_text("Show the color scale of the heatmap and false color debug views");
+    ui.end_row();
+
+    ui.label("Output layers:")
+        .on_hover_text("Show the linear depth, world normal and iteration count layers");
+    ui.checkbox(&mut gui_data.is_aov_preview, "")
+        .on_hover_text("Show the linear depth, world normal and iteration count layers");
+    ui.end_row();
+
+    ui.label("Distance slice:")
+        .on_hover_text("Show the distance field on a plane across one of the axes");
+    ui.checkbox(&mut gui_data.is_distance_slice, "")
+        .on_hover_text("Show the distance field on a plane across one of the axes");
+    ui.end_row();
+
+    ui.label("Coordinate axes:")
+        .on_hover_text("Draw the X (red), Y (green) and Z (blue) axes over the fractal");
+    ui.checkbox(&mut gui_data.is_axes, "")
+        .on_hover_text("Draw the X (red), Y (green) and Z (blue) axes over the fractal");
+    ui.end_row();
+
+    if gui_data.is_axes {
+        ui.label("Axes length:")
+            .on_hover_text("How far the axes reach from the origin in each direction");
+        ui.add(Slider::new(&mut gui_data.axes_length, 0.5..=10.0))
+            .on_hover_text("How far the axes reach from the origin in each direction");
+        ui.end_row();
+
+        ui.label("Axes ticks:")
+            .on_hover_text("Mark distances along the axes with short ticks");
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut gui_data.is_axes_ticks, "");
+            ui.add_enabled(
+                gui_data.is_axes_ticks,
+                DragValue::new(&mut gui_data.axes_tick_spacing)
+                    .speed(0.01)
+                    .range(0.1..=5.0)
+                    .prefix("every "),
+            );
+        })
+        .response
+        .on_hover_text("Mark distances along the axes with short ticks");
+        ui.end_row();
+    }
+
+    ui.label("Pivot marker:")
+        .on_hover_text("Mark the center of the view and the point the camera rotates around");
+    ui.checkbox(&mut gui_data.is_pivot_marker, "")
+        .on_hover_text("Mark the center of the view and the point the camera rotates around");
+    ui.end_row();
+
+    ui.label("Half precision:").on_hover_text(
+        "Round the ray state and fractal iterations to 16 bit floats, as on mobile GPUs",
+    );
+    ui.checkbox(&mut gui_data.is_half_precision, "")
+        .on_hover_text(
+            "Round the ray state and fractal iterations to 16 bit floats, as on mobile GPUs",
+        );
+    ui.end_row();
+}
+
+fn progressive_section(ui: &mut Ui, gui_data: &mut GuiData) {
+    ui.heading(RichText::new("Progressive rendering").strong());
+    ui.end_row();
+
+    ui.label("Progressive rendering:").on_hover_text(
+        "Accumulate jittered samples while the view stays still for a smoother image",
+    );
+    ui.checkbox(&mut gui_data.is_progressive, "").on_hover_text(
+        "Accumulate jittered samples while the view stays still for a smoother image",
+    );
+    ui.end_row();
+
+    ui.label("Temporal anti-aliasing:").on_hover_text(
+        "Blend every restarted frame with the previous ones, which smooths shimmering while the camera moves",
+    );
+    ui.checkbox(&mut gui_data.is_taa, "").on_hover_text(
+        "Blend every restarted frame with the previous ones, which smooths shimmering while the camera moves",
+    );
+    ui.end_row();
+
+    if gui_data.is_taa {
+        ui.label("TAA blend:").on_hover_text(
+            "Weight of the new frame, lower values are smoother but leave more ghosting",
+        );
+        ui.add(Slider::new(&mut gui_data.taa_blend, 0.02..=0.5))
+            .on_hover_text(
+                "Weight of the new frame, lower values are smoother but leave more ghosting",
+            );
+        ui.end_row();
+    }
+
+    if !gui_data.is_progressive {
+        return;
+    }
+
+    ui.label("Max samples:")
+        .on_hover_text("Number of samples to accumulate per pixel");
+    ui.add(DragValue::new(&mut gui_data.max_samples).range(1..=MAX_SAMPLES))
+        .on_hover_text("Number of samples to accumulate per pixel");
+    ui.end_row();
+
+    ui.label("Region of interest:")
+        .on_hover_text("Region of the screen which receives samples first");
+    egui::ComboBox::from_label("Region")
+        .selected_text(format!("{}", gui_data.region_of_interest))
+        .show_ui(ui, |ui| {
+            for region in RegionOfInterest::iter() {
+                ui.selectable_value(
+                    &mut gui_data.region_of_interest,
+                    region,
+                    format!("{region}"),
+                );
+            }
+        });
+    ui.end_row();
+
+    match gui_data.region_of_interest {
+        RegionOfInterest::Off => return,
+        RegionOfInterest::Cursor => {
+            ui.label("Region radius:")
+                .on_hover_text("Radius of the region around the cursor in pixels");
+            ui.add(DragValue::new(&mut gui_data.roi_radius).range(10.0..=1000.0))
+                .on_hover_text("Radius of the region around the cursor in pixels");
+            ui.end_row();
+        }
+        RegionOfInterest::Drawn => {
+            ui.label("Drawing:");
+            ui.label(
+                RichText::new("Drag with the right mouse button to draw the region").italics(),
+            );
+            ui.end_row();
+        }
+    }
+
+    ui.label("Outside sample rate:")
+        .on_hover_text("Fraction of samples taken outside the region until the region converges");
+    ui.add(Slider::new(&mut gui_data.roi_outside_rate, 0.0..=1.0))
+        .on_hover_text("Fraction of samples taken outside the region until the region converges");
+    ui.end_row();
+}
+
+fn transfer_function(ui: &mut Ui, gui_data: &mut GuiData) {
+    ui.label("Transfer function:").on_hover_text(
+        "Encoding of the linear image into display colors, independent of the surface format",
+    );
+    egui::ComboBox::from_label("Transfer function")
+        .selected_text(format!("{}", gui_data.transfer_function))
+        .show_ui(ui, |ui| {
+            for transfer_function in TransferFunction::iter() {
+                ui.selectable_value(
+                    &mut gui_data.transfer_function,
+                    transfer_function,
+                    format!("{transfer_function}"),
+                );
+            }
+        });
+    ui.end_row();
+
+    if gui_data.transfer_function == TransferFunction::Gamma {
+        ui.label("Gamma:")
+            .on_hover_text("Display gamma the image is encoded for");
+        ui.add(Slider::new(&mut gui_data.gamma, 1.0..=3.0))
+            .on_hover_text("Display gamma the image is encoded for");
+        ui.end_row();
+    }
+}
+
+fn upscale_filter(ui: &mut Ui, gui_data: &mut GuiData) {
+    ui.label("Upscale filter:")
+        .on_hover_text("Filter the rendered image is read with before the post effects");
+    egui::ComboBox::from_label("Upscale filter")
+        .selected_text(format!("{}", gui_data.upscale_filter))
+        .show_ui(ui, |ui| {
+            for upscale_filter in UpscaleFilter::iter() {
+                ui.selectable_value(
+                    &mut gui_data.upscale_filter,
+                    upscale_filter,
+                    format!("{upscale_filter}"),
+                );
+            }
+        });
+    ui.end_row();
+
+    if gui_data.upscale_filter == UpscaleFilter::Sharpen {
+        ui.label("Sharpness:").on_hover_text(
+            "Strength of the sharpening, limited to the range of neighbouring pixels",
+        );
+        ui.add(Slider::new(&mut gui_data.sharpness, 0.0..=2.0))
+            .on_hover_text(
+                "Strength of the sharpening, limited to the range of neighbouring pixels",
+            );
+        ui.end_row();
+    }
+}
+
+fn post_effects_section(ui: &mut Ui, gui_data: &mut GuiData) {
+    ui.heading(RichText::new("Post effects").strong());
+    ui.end_row();
+
+    ui.label("Exposure:")
+        .on_hover_text("Brightness adjustment in stops, applied on top of auto exposure");
+    ui.add(
+        DragValue::new(&mut gui_data.exposure)
+            .speed(0.05)
+            .range(-10.0..=10.0)
+            .suffix(" EV"),
+    )
+    .on_hover_text("Brightness adjustment in stops, applied on top of auto exposure");
+    ui.end_row();
+
+    ui.label("Auto exposure:")
+        .on_hover_text("Adapt exposure over time to the average brightness of the image");
+    ui.checkbox(&mut gui_data.is_auto_exposure, "")
+        .on_hover_text("Adapt exposure over time to the average brightness of the image");
+    ui.end_row();
+
+    if gui_data.is_auto_exposure {
+        ui.label("Exposure range:")
+            .on_hover_text("Minimum and maximum exposure auto exposure can adapt to");
+        ui.horizontal(|ui| {
+            ui.add(
+                DragValue::new(&mut gui_data.min_exposure)
+                    .speed(0.05)
+                    .range(-10.0..=gui_data.max_exposure)
+                    .suffix(" EV"),
+            );
+            ui.add(
+                DragValue::new(&mut gui_data.max_exposure)
+                    .speed(0.05)
+                    .range(gui_data.min_exposure..=10.0)
+                    .suffix(" EV"),
+            );
+        })
+        .response
+        .on_hover_text("Minimum and maximum exposure auto exposure can adapt to");
+        ui.end_row();
+
+        ui.label("Adaptation speed:")
+            .on_hover_text("How quickly exposure follows changes in brightness");
+        ui.add(Slider::new(&mut gui_data.adaptation_speed, 0.1..=10.0))
+            .on_hover_text("How quickly exposure follows changes in brightness");
+        ui.end_row();
+    }
+
+    ui.label("Chromatic aberration:")
+        .on_hover_text("Color fringing that grows towards the edges of the screen");
+    ui.add(Slider::new(&mut gui_data.chromatic_aberration, 0.0..=1.0))
+        .on_hover_text("Color fringing that grows towards the edges of the screen");
+    ui.end_row();
+
+    ui.label("Film grain:")
+        .on_hover_text("Animated noise imitating the grain of photographic film");
+    ui.add(Slider::new(&mut gui_data.film_grain, 0.0..=1.0))
+        .on_hover_text("Animated noise imitating the grain of photographic film");
+    ui.end_row();
+
+    ui.label("Outline:")
+        .on_hover_text("Draw lines along the silhouette and creases of the fractal");
+    ui.checkbox(&mut gui_data.is_outline, "")
+        .on_hover_text("Draw lines along the silhouette and creases of the fractal");
+    ui.end_row();
+
+    if gui_data.is_outline {
+        ui.label("Outline thickness:")
+            .on_hover_text("Width of the outline in pixels");
+        ui.add(Slider::new(&mut gui_data.outline_thickness, 1.0..=5.0).suffix(" px"))
+            .on_hover_text("Width of the outline in pixels");
+        ui.end_row();
+
+        ui.label("Outline color:")
+            .on_hover_text("Color of the outline, unaffected by exposure");
+        ui.color_edit_button_srgb(&mut gui_data.outline_color)
+            .on_hover_text("Color of the outline, unaffected by exposure");
+        ui.end_row();
+    }
+}
+
+// Time in seconds a control stays highlighted after its link in the documentation was clicked
+const CONTROL_HIGHLIGHT_DURATION: f64 = 1.5;
+
+#[must_use]
+fn highlighted_control_id() -> Id {
+    Id::new("highlighted_control")
+}
+
+// Label of a control linked from the fractal documentation, which is highlighted and scrolled to
+// when the link was clicked
+fn control_label(ui: &mut Ui, control: FractalControl, text: &str) -> Response {
+    let time = ui.input(|input| input.time);
+    let highlight = ui
+        .ctx()
+        .data(|data| data.get_temp::<(FractalControl, f64)>(highlighted_control_id()))
+        .filter(|(highlighted_control, highlight_time)| {
+            *highlighted_control == control && time - highlight_time < CONTROL_HIGHLIGHT_DURATION
+        });
+
+    let Some((_, highlight_time)) = highlight else {
+        return ui.label(text);
+    };
+
+    let response = ui.label(
+        RichText::new(text)
+            .strong()
+            .color(ui.visuals().selection.stroke.color),
+    );
+    // Links are laid out above the controls, so the click happened earlier in this frame
+    #[allow(clippy::float_cmp)]
+    if highlight_time == time {
+        response.scroll_to_me(Some(Align::Center));
+        ui.ctx()
+            .request_repaint_after(Duration::from_secs_f64(CONTROL_HIGHLIGHT_DURATION));
+    }
+
+    response
+}
+
+#[must_use]
+// Fold applied before every step, empty when no component is folded
+fn julia_abs_folds_formula(gui_data: &GuiData) -> String {
+    if !gui_data.julia_abs_folds.contains(&true) {
+        return String::new();
+    }
+
+    let components = gui_data
+        .julia_abs_folds
+        .iter()
+        .zip(["q₀", "q₁", "q₂", "q₃"])
+        .map(|(&is_folded, component)| {
+            if is_folded {
+                format!("|{component}|")
+            } else {
+                component.to_owned()
+            }
+        })
+        .collect::<Vec<_>>();
+    format!("q ← ({}), ", components.join(", "))
+}
+
+#[allow(clippy::too_many_lines)]
+fn fractal_formula(template: &str, gui_data: &GuiData) -> String {
+    let constant = gui_data.constant;
+    let offset = gui_data.sierpinski_offset;
+    let rotation = gui_data.sierpinski_rotation;
+    let box_size = gui_data.kleinian_box_size;
+    let cubic_constant = gui_data.cubic_constant;
+
+    template
+        .replace("{shape}", gui_data.primitive_shape.formula())
+        .replace("{sphere_radius}", &format!("{:.2}", gui_data.sphere_radius))
+        .replace(
+            "{cylinder_size}",
+            &format!(
+                "({:.2}, {:.2})",
+                gui_data.cylinder_radius, gui_data.cylinder_half_height
+            ),
+        )
+        .replace(
+            "{box_extents}",
+            &format!(
+                "({:.2}, {:.2}, {:.2})",
+                gui_data.box_half_extents.0,
+                gui_data.box_half_extents.1,
+                gui_data.box_half_extents.2
+            ),
+        )
+        .replace(
+            "{torus_radii}",
+            &format!(
+                "({:.2}, {:.2})",
+                gui_data.torus_outer_radius, gui_data.torus_inner_radius
+            ),
+        )
+        .replace("{abs_folds}", &julia_abs_folds_formula(gui_data))
+        .replace(
+            "{julia_formula}",
+            gui_data.julia_formula.formula(gui_data.fractal_group),
+        )
+        .replace("{power}", &format!("{}", gui_data.power))
+        .replace("{iterations}", &format!("{}", gui_data.menger_iterations))
+        .replace("{scale}", &format!("{:.2}", gui_data.menger_scale()))
+        .replace(
+            "{sierpinski_scale}",
+            &format!("{:.2}", gui_data.sierpinski_scale),
+        )
+        .replace(
+            "{offset}",
+            &format!("({:.2}, {:.2}, {:.2})", offset.0, offset.1, offset.2),
+        )
+        .replace(
+            "{rotation}",
+            &format!(
+                "({:.0}°, {:.0}°, {:.0}°)",
+                rotation.0, rotation.1, rotation.2
+            ),
+        )
+        .replace(
+            "{box_size}",
+            &format!("({:.2}, {:.2}, {:.2})", box_size.0, box_size.1, box_size.2),
+        )
+        .replace(
+            "{inversion_radius}",
+            &format!("{:.2}", gui_data.kleinian_inversion_radius),
+        )
+        .replace(
+            "{kleinian_iterations}",
+            &format!("{}", gui_data.kleinian_iterations),
+        )
+        .replace("{formula_a}", gui_data.hybrid_formula_a.formula())
+        .replace("{formula_b}", gui_data.hybrid_formula_b.formula())
+        .replace("{hybrid_pattern}", &gui_data.hybrid_pattern_text())
+        .replace(
+            "{hybrid_iterations}",
+            &format!("{}", gui_data.hybrid_iterations),
+        )
+        .replace("{box_scale}", &format!("{:.2}", gui_data.hybrid_box_scale))
+        .replace(
+            "{surf_fold}",
+            &format!("({:.2}, {:.2})", gui_data.surf_fold.0, gui_data.surf_fold.1),
+        )
+        .replace("{surf_scale}", &format!("{:.2}", gui_data.surf_scale))
+        .replace(
+            "{surf_min_radius}",
+            &format!("{:.2}", gui_data.surf_min_radius),
+        )
+        .replace(
+            "{surf_iterations}",
+            &format!("{}", gui_data.surf_iterations),
+        )
+        .replace(
+            "{custom_iterations}",
+            &format!("{}", gui_data.custom_iterations),
+        )
+        .replace(
+            "{phoenix_strength}",
+            &format!("{:.2}", gui_data.phoenix_strength),
+        )
+        .replace(
+            "{cubic_constant}",
+            &format!(
+                "({}, {}, {}, {})",
+                cubic_constant.0, cubic_constant.1, cubic_constant.2, cubic_constant.3
+            ),
+        )
+        .replace(
+            "{constant}",
+            &format!(
+                "({}, {}, {}, {})",
+                constant.0, constant.1, constant.2, constant.3
+            ),
+        )
+}
+
+fn fractal_documentation(ui: &mut Ui, gui_data: &GuiData) {
+    let documentation = gui_data.fractal_group.documentation();
+
+    ui.label("Documentation:");
+    ui.collapsing(format!("About the {}", gui_data.fractal_group), |ui| {
+        ui.set_max_width(320.);
+        ui.add(Label::new(documentation.summary).wrap());
+        ui.add_space(4.);
+
+        ui.label(RichText::new(fractal_formula(documentation.formula, gui_data)).monospace())
+            .on_hover_text("Iteration formula with the current parameter values");
+        ui.add_space(4.);
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Controls:");
+            for control in documentation.controls {
+                if ui.link(format!("{control}")).clicked() {
+                    let time = ui.input(|input| input.time);
+                    ui.ctx().data_mut(|data| {
+                        data.insert_temp(highlighted_control_id(), (*control, time));
+                    });
+                }
+            }
+        });
+    });
+    ui.end_row();
+}
+
+fn kifs_preset_shape(ui: &mut Ui, gui_data: &mut GuiData) {
+    control_label(ui, FractalControl::PresetShape, "Preset shapes:")
+        .on_hover_text("Choose one of the preset shapes to display");
+    egui::ComboBox::from_label("Shape")
+        .selected_text(format!("{}", gui_data.primitive_shape))
+        .show_ui(ui, |ui| {
+            for shape in PrimitiveShape::iter() {
+                ui.selectable_value(&mut gui_data.primitive_shape, shape, format!("{shape}"));
+            }
+        });
+    ui.end_row();
+}
+
+// Size of the simple preset shapes, the folded ones have their own parameters
+fn kifs_shape_size(ui: &mut Ui, gui_data: &mut GuiData) {
+    match gui_data.primitive_shape {
+        PrimitiveShape::Sphere => {
+            control_label(ui, FractalControl::ShapeSize, "Radius:")
+                .on_hover_text("Radius of the sphere");
+            ui.add(
+                DragValue::new(&mut gui_data.sphere_radius)
+                    .speed(0.01)
+                    .range(0.05..=3.0),
+            )
+            .on_hover_text("Radius of the sphere");
+            ui.end_row();
+        }
+        PrimitiveShape::Cylinder => {
+            control_label(ui, FractalControl::ShapeSize, "Radius:")
+                .on_hover_text("Radius of the cylinder");
+            ui.add(
+                DragValue::new(&mut gui_data.cylinder_radius)
+                    .speed(0.01)
+                    .range(0.05..=3.0),
+            )
+            .on_hover_text("Radius of the cylinder");
+            ui.end_row();
+
+            ui.label("Half height:")
+                .on_hover_text("Distance of the caps of the cylinder from its center");
+            ui.add(
+                DragValue::new(&mut gui_data.cylinder_half_height)
+                    .speed(0.01)
+                    .range(0.05..=3.0),
+            )
+            .on_hover_text("Distance of the caps of the cylinder from its center");
+            ui.end_row();
+        }
+        PrimitiveShape::Box => {
+            control_label(ui, FractalControl::ShapeSize, "Half extents:")
+                .on_hover_text("Distances of the faces of the box from its center");
+            vector3_drag_values(
+                ui,
+                &mut gui_data.box_half_extents,
+                0.01,
+                0.05..=3.0,
+                "Distances of the faces of the box from its center",
+            );
+            ui.end_row();
+        }
+        PrimitiveShape::Torus => {
+            control_label(ui, FractalControl::ShapeSize, "Ring radius:")
+                .on_hover_text("Distance of the middle of the tube from the center of the torus");
+            ui.add(
+                DragValue::new(&mut gui_data.torus_outer_radius)
+                    .speed(0.01)
+                    .range(0.05..=3.0),
+            )
+            .on_hover_text("Distance of the middle of the tube from the center of the torus");
+            ui.end_row();
+
+            ui.label("Tube radius:")
+                .on_hover_text("Radius of the tube wrapped around the ring");
+            ui.add(
+                DragValue::new(&mut gui_data.torus_inner_radius)
+                    .speed(0.01)
+                    .range(0.01..=3.0),
+            )
+            .on_hover_text("Radius of the tube wrapped around the ring");
             ui.end_row();
+        }
+        PrimitiveShape::SierpinskiTetrahedron
+        | PrimitiveShape::Bunny
+        | PrimitiveShape::MengerSponge => {}
+    }
+}
+
+fn kifs_menger_sponge(ui: &mut Ui, gui_data: &mut GuiData) {
+    control_label(ui, FractalControl::MengerIterations, "Sponge iterations:")
+        .on_hover_text("Number of times space is folded, each adding a smaller level of holes");
+    ui.add(Slider::new(&mut gui_data.menger_iterations, 1..=10))
+        .on_hover_text("Number of times space is folded, each adding a smaller level of holes");
+    ui.end_row();
+
+    control_label(ui, FractalControl::MengerHoleSize, "Hole size:")
+        .on_hover_text("Width of the holes as a fraction of the cube they are cut out of");
+    ui.add(Slider::new(&mut gui_data.menger_hole_size, 0.05..=0.6))
+        .on_hover_text("Width of the holes as a fraction of the cube they are cut out of");
+    ui.end_row();
+}
+
+// Components shown side by side, as the vector controls of the settings menu
+fn vector3_drag_values(
+    ui: &mut Ui,
+    vector: &mut Vector3<f32>,
+    speed: f64,
+    range: RangeInclusive<f32>,
+    hover_text: &str,
+) {
+    ui.horizontal(|ui| {
+        ui.style_mut().spacing.item_spacing = Vec2::new(3., 3.);
+        for i in 0..3 {
+            ui.add(
+                DragValue::new(&mut vector[i])
+                    .speed(speed)
+                    .range(range.clone()),
+            )
+            .on_hover_text(hover_text);
+        }
+    });
+}
+
+fn kifs_sierpinski_tetrahedron(ui: &mut Ui, gui_data: &mut GuiData) {
+    control_label(ui, FractalControl::SierpinskiScale, "Tetrahedron scale:")
+        .on_hover_text("Factor the folded copies are scaled by, 2 gives the classic tetrahedron");
+    ui.add(Slider::new(&mut gui_data.sierpinski_scale, 1.2..=3.))
+        .on_hover_text("Factor the folded copies are scaled by, 2 gives the classic tetrahedron");
+    ui.end_row();
+
+    control_label(ui, FractalControl::SierpinskiOffset, "Vertex offset:")
+        .on_hover_text("Vertex the folded copies are scaled towards");
+    vector3_drag_values(
+        ui,
+        &mut gui_data.sierpinski_offset,
+        0.01,
+        -2.0..=2.0,
+        "Vertex the folded copies are scaled towards",
+    );
+    ui.end_row();
+
+    control_label(ui, FractalControl::SierpinskiRotation, "Fold rotation:")
+        .on_hover_text("Rotation around the x, y and z axes applied after every fold");
+    vector3_drag_values(
+        ui,
+        &mut gui_data.sierpinski_rotation,
+        0.5,
+        -180.0..=180.0,
+        "Rotation around the x, y and z axes applied after every fold",
+    );
+    ui.end_row();
+}
+
+fn kleinian_parameters(ui: &mut Ui, gui_data: &mut GuiData) {
+    control_label(ui, FractalControl::KleinianBoxSize, "Box size:")
+        .on_hover_text("Half extents of the box space is reflected in");
+    vector3_drag_values(
+        ui,
+        &mut gui_data.kleinian_box_size,
+        0.002,
+        0.1..=2.0,
+        "Half extents of the box space is reflected in",
+    );
+    ui.end_row();
+
+    control_label(
+        ui,
+        FractalControl::KleinianInversionRadius,
+        "Inversion radius:",
+    )
+    .on_hover_text("Radius of the sphere points are inverted in");
+    ui.add(
+        DragValue::new(&mut gui_data.kleinian_inversion_radius)
+            .speed(0.002)
+            .range(0.1..=2.0),
+    )
+    .on_hover_text("Radius of the sphere points are inverted in");
+    ui.end_row();
+
+    control_label(ui, FractalControl::KleinianIterations, "Group iterations:").on_hover_text(
+        "Number of reflections and inversions, each adding a smaller level of detail",
+    );
+    ui.add(Slider::new(&mut gui_data.kleinian_iterations, 1..=30))
+        .on_hover_text(
+            "Number of reflections and inversions, each adding a smaller level of detail",
+        );
+    ui.end_row();
+}
+
+fn amazing_surf_parameters(ui: &mut Ui, gui_data: &mut GuiData) {
+    control_label(ui, FractalControl::SurfFold, "Fold limits:")
+        .on_hover_text("Limits of the box fold along the x and y axes, z is never folded");
+    ui.horizontal(|ui| {
+        ui.style_mut().spacing.item_spacing = Vec2::new(3., 3.);
+        for i in 0..2 {
+            ui.add(
+                DragValue::new(&mut gui_data.surf_fold[i])
+                    .speed(0.01)
+                    .range(0.0..=3.0),
+            )
+            .on_hover_text("Limits of the box fold along the x and y axes, z is never folded");
+        }
+    });
+    ui.end_row();
+
+    control_label(ui, FractalControl::SurfScale, "Surf scale:")
+        .on_hover_text("Factor every iteration scales the folded space by");
+    ui.add(
+        DragValue::new(&mut gui_data.surf_scale)
+            .speed(0.01)
+            .range(-3.0..=3.0),
+    )
+    .on_hover_text("Factor every iteration scales the folded space by");
+    ui.end_row();
+
+    control_label(ui, FractalControl::SurfMinRadius, "Minimum radius:")
+        .on_hover_text("Radius below which the sphere fold stops inverting points");
+    ui.add(
+        DragValue::new(&mut gui_data.surf_min_radius)
+            .speed(0.005)
+            .range(0.05..=1.0),
+    )
+    .on_hover_text("Radius below which the sphere fold stops inverting points");
+    ui.end_row();
+
+    control_label(ui, FractalControl::SurfIterations, "Surf iterations:")
+        .on_hover_text("Number of folds, each adding a smaller level of detail");
+    ui.add(Slider::new(&mut gui_data.surf_iterations, 1..=30))
+        .on_hover_text("Number of folds, each adding a smaller level of detail");
+    ui.end_row();
+}
+
+fn custom_formula_parameters(ui: &mut Ui, gui_data: &mut GuiData) {
+    control_label(ui, FractalControl::CustomIterations, "Formula iterations:").on_hover_text(
+        "Number of times the formula of the formula window is applied before estimating the distance",
+    );
+    ui.add(Slider::new(&mut gui_data.custom_iterations, 1..=50)).on_hover_text(
+        "Number of times the formula of the formula window is applied before estimating the distance",
+    );
+    ui.end_row();
+}
+
+fn hybrid_parameters(ui: &mut Ui, gui_data: &mut GuiData) {
+    control_label(ui, FractalControl::HybridFormulas, "Hybrid formulas:")
+        .on_hover_text("Formulas A and B the iterations alternate between");
+    ui.horizontal(|ui| {
+        for (label, formula) in [
+            ("Formula A", &mut gui_data.hybrid_formula_a),
+            ("Formula B", &mut gui_data.hybrid_formula_b),
+        ] {
+            egui::ComboBox::from_label(label)
+                .selected_text(format!("{formula}"))
+                .show_ui(ui, |ui| {
+                    for option in HybridFormula::iter() {
+                        ui.selectable_value(formula, option, format!("{option}"));
+                    }
+                });
+        }
+    });
+    ui.end_row();
+
+    control_label(ui, FractalControl::HybridPattern, "Interleave pattern:")
+        .on_hover_text("Formula of every iteration, repeated until the iterations run out");
+    ui.horizontal(|ui| {
+        ui.add(
+            DragValue::new(&mut gui_data.hybrid_pattern_length)
+                .range(1..=MAX_HYBRID_PATTERN_LENGTH)
+                .suffix(" steps"),
+        )
+        .on_hover_text("Number of iterations before the pattern repeats");
+
+        ui.style_mut().spacing.item_spacing = Vec2::new(3., 3.);
+        for step in 0..gui_data.hybrid_pattern_length {
+            let is_formula_b = gui_data.hybrid_pattern & (1 << step) != 0;
+            if ui
+                .selectable_label(is_formula_b, if is_formula_b { "B" } else { "A" })
+                .on_hover_text(format!(
+                    "Iteration {} of the pattern uses the {}, click to switch",
+                    step + 1,
+                    gui_data.hybrid_formula(step)
+                ))
+                .clicked()
+            {
+                gui_data.hybrid_pattern ^= 1 << step;
+            }
+        }
+    });
+    ui.end_row();
+
+    control_label(ui, FractalControl::HybridIterations, "Hybrid iterations:")
+        .on_hover_text("Number of iterations, each applying one of the two formulas");
+    ui.add(Slider::new(&mut gui_data.hybrid_iterations, 1..=40))
+        .on_hover_text("Number of iterations, each applying one of the two formulas");
+    ui.end_row();
+
+    control_label(ui, FractalControl::HybridBoxScale, "Box fold scale:")
+        .on_hover_text("Scale of the Mandelbox fold, negative scales give more intricate boxes");
+    ui.add(
+        DragValue::new(&mut gui_data.hybrid_box_scale)
+            .speed(0.01)
+            .range(-3.0..=3.0),
+    )
+    .on_hover_text("Scale of the Mandelbox fold, negative scales give more intricate boxes");
+    ui.end_row();
+}
+
+fn julia_formula(ui: &mut Ui, gui_data: &mut GuiData) {
+    control_label(ui, FractalControl::Formula, "Iteration formula:")
+        .on_hover_text("Function the quaternion is iterated with");
+    egui::ComboBox::from_label("Formula")
+        .selected_text(format!("{}", gui_data.julia_formula))
+        .show_ui(ui, |ui| {
+            for formula in JuliaFormula::iter() {
+                ui.selectable_value(&mut gui_data.julia_formula, formula, format!("{formula}"));
+            }
+        });
+    ui.end_row();
+}
+
+fn julia_abs_folds(ui: &mut Ui, gui_data: &mut GuiData) {
+    control_label(ui, FractalControl::AbsFolds, "Absolute folds:").on_hover_text(
+        "Components replaced by their absolute value before every step, burning ship style",
+    );
+    ui.horizontal(|ui| {
+        for (is_folded, component) in gui_data
+            .julia_abs_folds
+            .iter_mut()
+            .zip(["Real", "i", "j", "k"])
+        {
+            ui.checkbox(is_folded, component).on_hover_text(
+                "Components replaced by their absolute value before every step, burning ship style",
+            );
+        }
+    });
+    ui.end_row();
+}
+
+fn julia_bailout(ui: &mut Ui, gui_data: &mut GuiData) {
+    control_label(ui, FractalControl::Bailout, "Bailout radius:")
+        .on_hover_text("Norm past which the iterated quaternion counts as escaped");
+    ui.add(
+        DragValue::new(&mut gui_data.bailout_radius)
+            .speed(0.1)
+            .range(2.0..=1000.0),
+    )
+    .on_hover_text("Norm past which the iterated quaternion counts as escaped");
+    ui.end_row();
+}
+
+fn julia_estimator(ui: &mut Ui, gui_data: &mut GuiData) {
+    control_label(ui, FractalControl::Estimator, "Distance estimator:")
+        .on_hover_text("How the escaping quaternion is turned into the distance to the set");
+    egui::ComboBox::from_label("Estimator")
+        .selected_text(format!("{}", gui_data.julia_estimator))
+        .show_ui(ui, |ui| {
+            for estimator in JuliaEstimator::iter() {
+                ui.selectable_value(
+                    &mut gui_data.julia_estimator,
+                    estimator,
+                    format!("{estimator}"),
+                );
+            }
+        });
+    ui.end_row();
+}
+
+// Parameters only some of the formulas have, shown for the selected one
+fn julia_formula_parameters(ui: &mut Ui, gui_data: &mut GuiData) {
+    match gui_data.julia_formula {
+        JuliaFormula::Standard if gui_data.fractal_group == FractalGroup::GeneralizedJuliaSet => {
+            julia_power(ui, gui_data);
+        }
+        JuliaFormula::Phoenix => {
+            control_label(ui, FractalControl::PhoenixStrength, "Phoenix strength:")
+                .on_hover_text("Multiplier of the quaternion from two iterations back");
+            ui.add(
+                DragValue::new(&mut gui_data.phoenix_strength)
+                    .speed(0.01)
+                    .range(-1.0..=1.0),
+            )
+            .on_hover_text("Multiplier of the quaternion from two iterations back");
+            ui.end_row();
+        }
+        JuliaFormula::Cubic => {
+            control_label(ui, FractalControl::CubicConstant, "Cubic constant:")
+                .on_hover_text("Second constant, multiplying the quaternion");
+            ui.horizontal(|ui| {
+                ui.style_mut().spacing.item_spacing = Vec2::new(3., 3.);
+                for i in 0..4 {
+                    ui.add(
+                        DragValue::new(&mut gui_data.cubic_constant[i])
+                            .speed(0.01)
+                            .range(-1.0..=1.0),
+                    )
+                    .on_hover_text("Second constant, multiplying the quaternion");
+                }
+            });
+            ui.end_row();
+        }
+        JuliaFormula::Standard | JuliaFormula::Cube => {}
+    }
+}
+
+fn julia_power(ui: &mut Ui, gui_data: &mut GuiData) {
+    control_label(ui, FractalControl::Power, "Power variable:")
+        .on_hover_text("Power variable in quaternion function");
+    ui.add(
+        DragValue::new(&mut gui_data.power)
+            .speed(0.01)
+            .range(1.0..=10.0),
+    )
+    .on_hover_text("Power variable in quaternion function");
+    ui.end_row();
+
+    julia_power_sweep(ui, gui_data);
+}
+
+fn julia_power_sweep(ui: &mut Ui, gui_data: &mut GuiData) {
+    control_label(ui, FractalControl::PowerSweep, "Power sweep:").on_hover_text(
+        "Sweep the power through a range while the sweep plays, in place of the power variable",
+    );
+    egui::ComboBox::from_label("Sweep")
+        .selected_text(format!("{}", gui_data.power_sweep))
+        .show_ui(ui, |ui| {
+            for sweep in PowerSweep::iter() {
+                ui.selectable_value(&mut gui_data.power_sweep, sweep, format!("{sweep}"));
+            }
+        });
+    ui.end_row();
+
+    if gui_data.power_sweep == PowerSweep::Off {
+        return;
+    }
+
+    ui.label("Sweep range:")
+        .on_hover_text("Powers the sweep starts and ends at");
+    ui.horizontal(|ui| {
+        ui.add(
+            DragValue::new(&mut gui_data.power_sweep_min)
+                .speed(0.01)
+                .range(1.0..=10.0),
+        )
+        .on_hover_text("Power the sweep starts at");
+        ui.label("to");
+        ui.add(
+            DragValue::new(&mut gui_data.power_sweep_max)
+                .speed(0.01)
+                .range(1.0..=10.0),
+        )
+        .on_hover_text("Power the sweep ends at");
+    });
+    ui.end_row();
+
+    ui.label("Sweep period:")
+        .on_hover_text("Seconds the power takes to sweep through the range");
+    ui.add(
+        DragValue::new(&mut gui_data.power_sweep_period)
+            .speed(0.1)
+            .range(0.1..=120.0)
+            .suffix(" s"),
+    )
+    .on_hover_text("Seconds the power takes to sweep through the range");
+    ui.end_row();
+
+    ui.label("Sweep time:")
+        .on_hover_text("Play or pause the sweep, or drag the time to a point in the range");
+    ui.horizontal(|ui| {
+        let label = if gui_data.is_power_sweep_playing {
+            "Pause"
+        } else {
+            "Play"
+        };
+        if ui
+            .button(label)
+            .on_hover_text("Play or pause the sweep")
+            .clicked()
+        {
+            gui_data.is_power_sweep_playing = !gui_data.is_power_sweep_playing;
+        }
+        let period = gui_data.power_sweep_period();
+        ui.add(Slider::new(&mut gui_data.power_sweep_time, 0.0..=period).suffix(" s"))
+            .on_hover_text(format!("Current power {:.3}", gui_data.swept_power()));
+    });
+    ui.end_row();
+}
+
+fn julia_translucency(ui: &mut Ui, gui_data: &mut GuiData) {
+    control_label(ui, FractalControl::Translucency, "Translucent interior:")
+        .on_hover_text("Let light pass through the set, absorbed more the thicker it is");
+    ui.checkbox(&mut gui_data.is_translucent, "")
+        .on_hover_text("Let light pass through the set, absorbed more the thicker it is");
+    ui.end_row();
+
+    if !gui_data.is_translucent {
+        return;
+    }
+
+    ui.label("Absorption density:")
+        .on_hover_text("How much light is absorbed per unit of thickness");
+    ui.add(
+        DragValue::new(&mut gui_data.absorption_density)
+            .speed(0.05)
+            .range(0.0..=100.0),
+    )
+    .on_hover_text("How much light is absorbed per unit of thickness");
+    ui.end_row();
+
+    ui.label("Absorption color:")
+        .on_hover_text("Color of the light which passes through the set");
+    ui.color_edit_button_srgb(&mut gui_data.absorption_color)
+        .on_hover_text("Color of the light which passes through the set");
+    ui.end_row();
+}
+
+fn julia_interior(ui: &mut Ui, gui_data: &mut GuiData) {
+    control_label(ui, FractalControl::Interior, "Interior view:")
+        .on_hover_text("Show the boundary of the set from within while the camera is inside of it");
+    ui.checkbox(&mut gui_data.is_interior_visible, "")
+        .on_hover_text("Show the boundary of the set from within while the camera is inside of it");
+    ui.end_row();
+
+    if !gui_data.is_interior_visible {
+        return;
+    }
+
+    ui.label("Interior color:")
+        .on_hover_text("Color of the boundary seen from inside the set");
+    ui.color_edit_button_srgb(&mut gui_data.interior_color)
+        .on_hover_text("Color of the boundary seen from inside the set");
+    ui.end_row();
+}
+
+fn julia_hyperplane(ui: &mut Ui, gui_data: &mut GuiData) {
+    control_label(ui, FractalControl::SliceHyperplane, "Slice offset:")
+        .on_hover_text("Fourth coordinate of the rendered 3D slice of the set");
+    ui.add(
+        DragValue::new(&mut gui_data.hyperplane_offset)
+            .speed(0.005)
+            .range(-2.0..=2.0),
+    )
+    .on_hover_text("Fourth coordinate of the rendered 3D slice of the set");
+    ui.end_row();
+
+    ui.label("Slice rotation:").on_hover_text(
+        "Degrees the x, y and z axes of the slice are turned towards the fourth axis",
+    );
+    vector3_drag_values(
+        ui,
+        &mut gui_data.hyperplane_rotation,
+        0.5,
+        -180.0..=180.0,
+        "Degrees the x, y and z axes of the slice are turned towards the fourth axis",
+    );
+    ui.end_row();
+}
+
+fn julia_constant(ui: &mut Ui, gui_data: &mut GuiData) {
+    control_label(ui, FractalControl::Constant, "Constant variable:")
+        .on_hover_text("Constant variable in quaternion function");
+    ui.horizontal(|ui| {
+        ui.style_mut().spacing.item_spacing = Vec2::new(3., 3.);
+        for i in 0..4 {
+            ui.add(
+                DragValue::new(&mut gui_data.constant[i])
+                    .speed(0.01)
+                    .range(-1.0..=1.0),
+            )
+            .on_hover_text("Constant variable in quaternion function");
+        }
+    });
+    ui.end_row();
+}
+
+fn julia_constant_animation(ui: &mut Ui, gui_data: &mut GuiData) {
+    control_label(ui, FractalControl::ConstantAnimation, "Constant animation:")
+        .on_hover_text("Path the constant moves along around its value while the animation plays");
+    egui::ComboBox::from_label("Animation")
+        .selected_text(format!("{}", gui_data.constant_animation))
+        .show_ui(ui, |ui| {
+            for animation in ConstantAnimation::iter() {
+                ui.selectable_value(
+                    &mut gui_data.constant_animation,
+                    animation,
+                    format!("{animation}"),
+                );
+            }
+        });
+    ui.end_row();
+
+    if gui_data.constant_animation == ConstantAnimation::Off {
+        return;
+    }
+
+    ui.label("Animation speed:")
+        .on_hover_text("How fast the constant moves along the path");
+    ui.add(
+        DragValue::new(&mut gui_data.animation_speed)
+            .speed(0.01)
+            .range(0.01..=10.0)
+            .suffix(" rad/s"),
+    )
+    .on_hover_text("How fast the constant moves along the path");
+    ui.end_row();
+
+    ui.label("Animation radius:")
+        .on_hover_text("How far the constant moves away from its value");
+    ui.add(
+        DragValue::new(&mut gui_data.animation_radius)
+            .speed(0.001)
+            .range(0.0..=0.5),
+    )
+    .on_hover_text("How far the constant moves away from its value");
+    ui.end_row();
+
+    ui.label("Animation time:")
+        .on_hover_text("Play or pause the animation, or drag the time to a point on the path");
+    ui.horizontal(|ui| {
+        let label = if gui_data.is_animation_playing {
+            "Pause"
+        } else {
+            "Play"
+        };
+        if ui
+            .button(label)
+            .on_hover_text("Play or pause the animation")
+            .clicked()
+        {
+            gui_data.is_animation_playing = !gui_data.is_animation_playing;
+        }
+        let period = gui_data.animation_period();
+        ui.add(Slider::new(&mut gui_data.animation_time, 0.0..=period).suffix(" s"))
+            .on_hover_text("Point on the path the constant is at");
+    });
+    ui.end_row();
+}
+
+fn preview_axis_combo(
+    ui: &mut Ui,
+    label: &str,
+    axis: &mut ParameterAxis,
+    fractal_group: FractalGroup,
+) {
+    egui::ComboBox::from_label(label)
+        .selected_text(format!("{axis}"))
+        .show_ui(ui, |ui| {
+            for option in ParameterAxis::iter() {
+                // Power is fixed to 2 for the regular Julia set
+                if option == ParameterAxis::Power
+                    && fractal_group != FractalGroup::GeneralizedJuliaSet
+                {
+                    continue;
+                }
+                ui.selectable_value(axis, option, format!("{option}"));
+            }
+        });
+}
+
+fn julia_parameter_preview(ui: &mut Ui, gui_data: &mut GuiData) {
+    control_label(ui, FractalControl::ParameterPreview, "Parameter preview:")
+        .on_hover_text("Show a grid of previews with the parameters varied along two axes");
+    ui.checkbox(&mut gui_data.is_parameter_preview, "")
+        .on_hover_text("Show a grid of previews with the parameters varied along two axes");
+    ui.end_row();
+
+    if !gui_data.is_parameter_preview {
+        return;
+    }
+
+    ui.label("Preview axes:")
+        .on_hover_text("Parameters varied horizontally and vertically across the grid");
+    ui.vertical(|ui| {
+        preview_axis_combo(
+            ui,
+            "Horizontal",
+            &mut gui_data.preview_horizontal_axis,
+            gui_data.fractal_group,
+        );
+        preview_axis_combo(
+            ui,
+            "Vertical",
+            &mut gui_data.preview_vertical_axis,
+            gui_data.fractal_group,
+        );
+    })
+    .response
+    .on_hover_text("Parameters varied horizontally and vertically across the grid");
+    ui.end_row();
+
+    ui.label("Preview range:")
+        .on_hover_text("How far the parameters are varied, centered on their current values");
+    ui.add(
+        DragValue::new(&mut gui_data.preview_range)
+            .speed(0.01)
+            .range(0.01..=2.0),
+    )
+    .on_hover_text("How far the parameters are varied, centered on their current values");
+    ui.end_row();
+
+    ui.label("Preview grid size:")
+        .on_hover_text("Number of previews along each side of the grid");
+    ui.add(Slider::new(&mut gui_data.preview_grid_size, 2..=12))
+        .on_hover_text("Number of previews along each side of the grid");
+    ui.end_row();
+}
+
+fn render_mode(ui: &mut Ui, gui_data: &mut GuiData) {
+    let render_mode = &mut gui_data.render_modes[gui_data.fractal_group.id() as usize];
+
+    ui.label("Render mode:")
+        .on_hover_text("How the current fractal group is rendered, remembered for every group");
+    egui::ComboBox::from_label("Mode")
+        .selected_text(format!("{render_mode}"))
+        .show_ui(ui, |ui| {
+            for mode in RenderMode::iter() {
+                ui.selectable_value(render_mode, mode, format!("{mode}"));
+            }
+        })
+        .response
+        .on_hover_text("How the current fractal group is rendered, remembered for every group");
+    ui.end_row();
+
+    if *render_mode != RenderMode::Volumetric {
+        return;
+    }
+
+    ui.label("Volume density:")
+        .on_hover_text("How strongly the fractal emits its color and absorbs light behind it");
+    ui.add(
+        DragValue::new(&mut gui_data.volume_density)
+            .speed(0.1)
+            .range(0.0..=1000.0),
+    )
+    .on_hover_text("How strongly the fractal emits its color and absorbs light behind it");
+    ui.end_row();
+
+    ui.label("Volume step:")
+        .on_hover_text("Step length through the volume, smaller is more accurate but slower");
+    ui.add(
+        DragValue::new(&mut gui_data.volume_step)
+            .speed(0.001)
+            .range(0.001..=0.1),
+    )
+    .on_hover_text("Step length through the volume, smaller is more accurate but slower");
+    ui.end_row();
+}
+
+fn fractal_group_section(ui: &mut Ui, gui_data: &mut GuiData) {
+    ui.heading(RichText::new("Fractal settings").strong());
+    ui.end_row();
+
+    ui.label("Presets:")
+        .on_hover_text("Replace the fractal by one of the built-in presets, keeping the look");
+    egui::ComboBox::from_id_salt("library_preset")
+        .selected_text("Choose...")
+        .show_ui(ui, |ui| {
+            for preset in &LIBRARY_PRESETS {
+                if ui
+                    .selectable_label(false, preset.name)
+                    .on_hover_text(preset.description)
+                    .clicked()
+                {
+                    *gui_data = preset.apply(gui_data);
+                }
+            }
+        })
+        .response
+        .on_hover_text("Replace the fractal by one of the built-in presets, keeping the look");
+    ui.end_row();
+
+    ui.label("Fractal group:")
+        .on_hover_text("Group of fractals to display");
+    egui::ComboBox::from_label("Group")
+        .selected_text(format!("{}", gui_data.fractal_group))
+        .show_ui(ui, |ui| {
+            for group in FractalGroup::iter() {
+                ui.selectable_value(&mut gui_data.fractal_group, group, format!("{group}"));
+            }
+        });
+    ui.end_row();
+
+    render_mode(ui, gui_data);
+    fractal_documentation(ui, gui_data);
+
+    match gui_data.fractal_group {
+        FractalGroup::KaleidoscopicIFS => {
+            kifs_preset_shape(ui, gui_data);
+            match gui_data.primitive_shape {
+                PrimitiveShape::MengerSponge => kifs_menger_sponge(ui, gui_data),
+                PrimitiveShape::SierpinskiTetrahedron => kifs_sierpinski_tetrahedron(ui, gui_data),
+                _ => kifs_shape_size(ui, gui_data),
+            }
+        }
+        FractalGroup::JuliaSet | FractalGroup::GeneralizedJuliaSet => {
+            julia_formula(ui, gui_data);
+            julia_abs_folds(ui, gui_data);
+            julia_bailout(ui, gui_data);
+            julia_estimator(ui, gui_data);
+            julia_formula_parameters(ui, gui_data);
+            julia_constant(ui, gui_data);
+            julia_constant_animation(ui, gui_data);
+            julia_hyperplane(ui, gui_data);
+            julia_translucency(ui, gui_data);
+            julia_interior(ui, gui_data);
+            julia_parameter_preview(ui, gui_data);
+        }
+        FractalGroup::KleinianLimitSet => kleinian_parameters(ui, gui_data),
+        FractalGroup::Hybrid => {
+            hybrid_parameters(ui, gui_data);
+            julia_power(ui, gui_data);
+            julia_constant(ui, gui_data);
+            julia_constant_animation(ui, gui_data);
+        }
+        FractalGroup::AmazingSurf => amazing_surf_parameters(ui, gui_data),
+        FractalGroup::CustomFormula => custom_formula_parameters(ui, gui_data),
+        // Shader files bring their own parameters
+        FractalGroup::ShaderFile => {}
+    }
+    ui.end_row();
+}
+
+fn update_ui(ui: &mut Ui, gui_data: &mut GuiData, camera_data: &mut CameraData) {
+    egui::Grid::new("main_grid")
+        .num_columns(2)
+        .spacing([40.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("GUI Theme");
+            let mut theme_preference = ui.ctx().options(|opt| opt.theme_preference);
+            theme_preference.radio_buttons(ui);
+            ui.ctx().set_theme(theme_preference);
+            ui.end_row();
+
+            ui.end_row();
+
+            general_section(ui, gui_data, camera_data);
+            ui.end_row();
+
+            fractal_group_section(ui, gui_data);
+            ui.end_row();
+
+            scene_objects_section(ui, gui_data);
+            ui.end_row();
+
+            csg_section(ui, gui_data);
+            ui.end_row();
+
+            warp_section(ui, gui_data);
+            ui.end_row();
+
+            clipping_section(ui, gui_data);
+            ui.end_row();
+
+            cutaway_section(ui, gui_data);
+            ui.end_row();
+
+            floor_section(ui, gui_data);
+            ui.end_row();
+
+            lighting_section(ui, gui_data);
+            ui.end_row();
+
+            stereo_section(ui, gui_data);
+            ui.end_row();
+
+            progressive_section(ui, gui_data);
+            ui.end_row();
+
+            post_effects_section(ui, gui_data);
+            ui.end_row();
+
+            debug_section(ui, gui_data);
+            ui.end_row();
+        });
+
+    ui.add_space(16.);
+    ui.separator();
+    ui.label(RichText::new("Tip: Hover over some items for an explanation").italics());
+}
+
+// Plots the distance estimate against the step index, on a logarithmic scale since it spans many orders of magnitude
+fn distance_chart(ui: &mut Ui, steps: &[RayStep]) {
+    let (response, painter) = ui.allocate_painter(Vec2::new(360., 180.), Sense::hover());
+    let rect = response.rect;
+    let text_color = ui.visuals().text_color();
+
+    painter.rect_stroke(
+        rect,
+        0.,
+        ui.visuals().widgets.noninteractive.bg_stroke,
+        StrokeKind::Inside,
+    );
+
+    let log_distances: Vec<f32> = steps
+        .iter()
+        .map(|step| step.distance.abs().max(f32::MIN_POSITIVE).log10())
+        .collect();
+    if log_distances.is_empty() {
+        return;
+    }
+    let min = log_distances.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = log_distances
+        .iter()
+        .copied()
+        .fold(f32::NEG_INFINITY, f32::max);
+
+    #[allow(clippy::cast_precision_loss)]
+    let last_index = (log_distances.len() - 1).max(1) as f32;
+    // Flat lines would otherwise divide by zero
+    let range = (max - min).max(f32::EPSILON);
+    #[allow(clippy::cast_precision_loss)]
+    let points: Vec<Pos2> = log_distances
+        .iter()
+        .enumerate()
+        .map(|(index, log_distance)| {
+            Pos2::new(
+                rect.left() + rect.width() * index as f32 / last_index,
+                rect.bottom() - rect.height() * (log_distance - min) / range,
+            )
+        })
+        .collect();
+    painter.add(Shape::line(
+        points,
+        Stroke::new(1.5, ui.visuals().selection.bg_fill),
+    ));
+
+    let font = FontId::monospace(10.);
+    painter.text(
+        rect.left_top(),
+        Align2::LEFT_TOP,
+        format!("{:.1e}", 10_f32.powf(max)),
+        font.clone(),
+        text_color,
+    );
+    painter.text(
+        rect.left_bottom(),
+        Align2::LEFT_BOTTOM,
+        format!("{:.1e}", 10_f32.powf(min)),
+        font.clone(),
+        text_color,
+    );
+    painter.text(
+        rect.right_bottom(),
+        Align2::RIGHT_BOTTOM,
+        format!("step {}", steps.len()),
+        font,
+        text_color,
+    );
+
+    if let Some(hover_position) = response.hover_pos() {
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            clippy::cast_precision_loss
+        )]
+        let index = (((hover_position.x - rect.left()) / rect.width() * last_index).round()
+            as usize)
+            .min(steps.len() - 1);
+        response.on_hover_text(format!(
+            "Step {index}: distance {:.3e}",
+            steps[index].distance
+        ));
+    }
+}
+
+const SETTINGS_MENU_TITLE: &str = "Settings Menu";
+const PARAMETER_PREVIEW_TITLE: &str = "Parameter Space";
+const RAY_INSPECTOR_TITLE: &str = "Ray Inspector";
+const OUTPUT_LAYERS_TITLE: &str = "Output Layers";
+const DISTANCE_SLICE_TITLE: &str = "Distance Slice";
+const CAMERA_PATH_TITLE: &str = "Camera Path";
+const TILED_EXPORT_TITLE: &str = "Tiled Export";
+const MESH_TITLE: &str = "Mesh";
+const SEEDED_PRESET_TITLE: &str = "Seeded Preset";
+const EXPRESSIONS_TITLE: &str = "Expressions";
+const OBJECT_INSPECTOR_TITLE: &str = "Object Inspector";
+const PROJECT_TITLE: &str = "Project";
+const FORMULA_TITLE: &str = "Formula";
+const NODE_GRAPH_TITLE: &str = "Node Graph";
+const SHADER_FILE_TITLE: &str = "Shader File";
+const MARKERS_TITLE: &str = "Markers";
+const CRASH_NOTICE_TITLE: &str = "Crash Report";
+
+// Moves a window to the given position for this frame only, after which it can be dragged freely again
+fn place_window(window: EguiWindow<'_>, position: Option<Pos2>) -> EguiWindow<'_> {
+    match position {
+        Some(position) => window.current_pos(position),
+        None => window,
+    }
+}
+
+// Returns the camera when its mode or distance was edited, as the render state owns the camera
+fn settings_menu(
+    context: &Context,
+    position: Option<Pos2>,
+    gui_data: &mut GuiData,
+    camera_data: CameraData,
+    bound_parameters: &[String],
+) -> Option<CameraData> {
+    let mut edited_camera = camera_data;
+    place_window(EguiWindow::new(SETTINGS_MENU_TITLE), position)
+        .resizable(false)
+        .default_open(false)
+        .show(context, |ui| {
+            // Edits of bound settings are overwritten on the next frame
+            if !bound_parameters.is_empty() {
+                ui.label(
+                    RichText::new(format!("ƒ Bound: {}", bound_parameters.join(", ")))
+                        .color(ui.visuals().warn_fg_color),
+                )
+                .on_hover_text(format!(
+                    "Settings driven by expressions every frame, edit them in the {EXPRESSIONS_TITLE} window"
+                ));
+            }
+            update_ui(ui, gui_data, &mut edited_camera);
+        });
+
+    // Compared at the zoom scale, as an edit at a deep zoom is far below the default tolerance
+    let context = MathContext::with_unit_scale(camera_data.zoom_scale());
+    (edited_camera.mode != camera_data.mode
+        || !context.approx_eq(edited_camera.origin_distance, camera_data.origin_distance)
+        || edited_camera.pan_target != camera_data.pan_target
+        || !context.approx_eq(edited_camera.vertical_fov, camera_data.vertical_fov))
+    .then_some(edited_camera)
+}
+
+// Shown while a ray is recorded, closing the window discards the ray
+fn ray_inspector_window(
+    context: &Context,
+    recorded_ray_path: &mut Option<RayPath>,
+    position: Option<Pos2>,
+) {
+    let Some(ray_path) = recorded_ray_path else {
+        return;
+    };
+
+    let mut is_open = true;
+    place_window(EguiWindow::new(RAY_INSPECTOR_TITLE), position)
+        .open(&mut is_open)
+        .resizable(false)
+        .show(context, |ui| {
+            ui.label(format!(
+                "Pixel: ({}, {})",
+                ray_path.pixel.0, ray_path.pixel.1
+            ));
+            ui.label(format!("Steps: {}", ray_path.steps.len()));
+            if let Some(last_step) = ray_path.steps.last() {
+                ui.label(format!(
+                    "Final position: ({:.4}, {:.4}, {:.4})",
+                    last_step.position.0, last_step.position.1, last_step.position.2
+                ));
+                ui.label(format!("Final distance: {:.3e}", last_step.distance));
+            }
+
+            ui.add_space(8.);
+            ui.label(RichText::new("Distance estimate per step").strong());
+            distance_chart(ui, &ray_path.steps);
+        });
+
+    if !is_open {
+        *recorded_ray_path = None;
+    }
+}
+
+// Side length of the displayed preview grid in points
+const PREVIEW_DISPLAY_SIZE: f32 = 384.;
+
+// Clicking a tile moves the parameters to the ones it was rendered with
+fn parameter_preview_window(
+    context: &Context,
+    texture: TextureId,
+    gui_data: &mut GuiData,
+    position: Option<Pos2>,
+) {
+    let preview_data = PreviewData::from(*gui_data);
+    let mut is_open = gui_data.is_parameter_preview;
+
+    place_window(EguiWindow::new(PARAMETER_PREVIEW_TITLE), position)
+        .open(&mut is_open)
+        .resizable(false)
+        .show(context, |ui| {
+            ui.label(format!(
+                "Horizontal: {}, vertical: {}",
+                preview_data.horizontal_axis, preview_data.vertical_axis
+            ));
+
+            let response = ui.add(
+                Image::new((texture, Vec2::splat(PREVIEW_DISPLAY_SIZE))).sense(Sense::click()),
+            );
+            let Some(hover_position) = response.hover_pos() else {
+                return;
+            };
+
+            let relative_position = (hover_position - response.rect.min) / response.rect.size();
+            #[allow(
+                clippy::cast_possible_truncation,
+                clippy::cast_sign_loss,
+                clippy::cast_precision_loss
+            )]
+            let tile = Vector2(
+                ((relative_position.x * preview_data.grid_size as f32) as u32)
+                    .min(preview_data.grid_size - 1),
+                ((relative_position.y * preview_data.grid_size as f32) as u32)
+                    .min(preview_data.grid_size - 1),
+            );
+            let offsets = preview_data.tile_offsets(tile);
+
+            let mut tile_data = *gui_data;
+            tile_data.offset_parameter(preview_data.horizontal_axis, offsets.0);
+            tile_data.offset_parameter(preview_data.vertical_axis, offsets.1);
+
+            if response.clicked() {
+                *gui_data = tile_data;
+            }
+            response.on_hover_text(format!(
+                "{}: {:.3}\n{}: {:.3}\nClick to use these parameters",
+                preview_data.horizontal_axis,
+                tile_data.parameter(preview_data.horizontal_axis),
+                preview_data.vertical_axis,
+                tile_data.parameter(preview_data.vertical_axis),
+            ));
+        });
+
+    gui_data.is_parameter_preview = is_open;
+}
+
+// Width of the shown layer in points, the height follows the aspect ratio of the window
+const AOV_DISPLAY_WIDTH: f32 = 384.;
+
+// Returns whether the layers should be exported
+fn output_layers_window(
+    context: &Context,
+    texture: TextureId,
+    aspect_ratio: f32,
+    gui_data: &mut GuiData,
+) -> bool {
+    let mut is_open = gui_data.is_aov_preview;
+    let mut is_export_requested = false;
+
+    EguiWindow::new(OUTPUT_LAYERS_TITLE)
+        .open(&mut is_open)
+        .resizable(false)
+        .show(context, |ui| {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_label("Layer")
+                    .selected_text(format!("{}", gui_data.aov_layer))
+                    .show_ui(ui, |ui| {
+                        for layer in AovLayer::iter() {
+                            ui.selectable_value(&mut gui_data.aov_layer, layer, format!("{layer}"));
+                        }
+                    });
+
+                is_export_requested = ui
+                    .button("Export")
+                    .on_hover_text(
+                        "Write every layer as a portable float map (aov_*.pfm) into the working directory",
+                    )
+                    .clicked();
+            });
+
+            ui.add(Image::new((
+                texture,
+                Vec2::new(AOV_DISPLAY_WIDTH, AOV_DISPLAY_WIDTH / aspect_ratio),
+            )));
+        });
+
+    gui_data.is_aov_preview = is_open;
+    is_export_requested
+}
+
+// Side length of the shown slice in points
+const DISTANCE_SLICE_DISPLAY_SIZE: f32 = 320.;
+
+fn distance_slice_window(context: &Context, texture: TextureId, gui_data: &mut GuiData) {
+    let mut is_open = gui_data.is_distance_slice;
+
+    EguiWindow::new(DISTANCE_SLICE_TITLE)
+        .open(&mut is_open)
+        .resizable(false)
+        .show(context, |ui| {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_label("Normal")
+                    .selected_text(format!("{}", gui_data.distance_slice_axis))
+                    .show_ui(ui, |ui| {
+                        for axis in WarpAxis::iter() {
+                            ui.selectable_value(
+                                &mut gui_data.distance_slice_axis,
+                                axis,
+                                format!("{axis}"),
+                            );
+                        }
+                    })
+                    .response
+                    .on_hover_text("Axis the plane lies across");
+                ui.add(
+                    DragValue::new(&mut gui_data.distance_slice_offset)
+                        .speed(0.01)
+                        .range(-10.0..=10.0)
+                        .prefix("offset "),
+                )
+                .on_hover_text("Position of the plane along its normal");
+                ui.add(
+                    DragValue::new(&mut gui_data.distance_slice_extent)
+                        .speed(0.01)
+                        .range(0.01..=10.0)
+                        .prefix("extent "),
+                )
+                .on_hover_text("Half the side length of the shown square");
+            });
+
+            ui.add(Image::new((
+                texture,
+                Vec2::splat(DISTANCE_SLICE_DISPLAY_SIZE),
+            )))
+            .on_hover_text(
+                "Warm outside and cold inside the surface, which is drawn in white, with contours every 0.05 units",
+            );
+
+            // Note: has to match distance_slice_position in distance_slice.wgsl
+            let (horizontal, vertical) = match gui_data.distance_slice_axis {
+                WarpAxis::X => ("Y", "Z"),
+                WarpAxis::Y => ("X", "Z"),
+                WarpAxis::Z => ("X", "Y"),
+            };
+            let extent = gui_data.distance_slice_extent;
+            ui.label(format!(
+                "{horizontal} to the right and {vertical} upwards, both from {:.2} to {extent:.2}",
+                -extent
+            ));
+        });
+
+    gui_data.is_distance_slice = is_open;
+}
+
+fn warnings_window(context: &Context, warnings: &[ArtifactWarning]) {
+    EguiWindow::new("Warnings")
+        .anchor(Align2::LEFT_BOTTOM, [8., -8.])
+        .resizable(false)
+        .default_open(true)
+        .show(context, |ui| {
+            ui.set_max_width(320.);
+            for warning in warnings {
+                ui.add(
+                    Label::new(
+                        RichText::new(format!("{warning}")).color(ui.visuals().warn_fg_color),
+                    )
+                    .wrap(),
+                );
+            }
+        });
+}
+
+// Size of the view center crosshair and the pivot ring in points, drawn faintly so they do not
+// distract from the image
+const CROSSHAIR_SIZE: f32 = 8.;
+const PIVOT_MARKER_RADIUS: f32 = 6.;
+const PIVOT_MARKER_ALPHA: u8 = 96;
+
+// Crosshair in the center of the view and a ring around the point the camera orbits, which only
+// coincide while the camera looks straight at it
+fn pivot_overlay(context: &Context, camera_data: CameraData) {
+    let screen_rect = context.screen_rect();
+    let painter = context.layer_painter(LayerId::new(Order::Background, Id::new("pivot_marker")));
+    let stroke = Stroke::new(1., Color32::from_white_alpha(PIVOT_MARKER_ALPHA));
+
+    let center = screen_rect.center();
+    for direction in [Vec2::X, Vec2::Y] {
+        painter.line_segment(
+            [
+                center - CROSSHAIR_SIZE * direction,
+                center + CROSSHAIR_SIZE * direction,
+            ],
+            stroke,
+        );
+    }
+
+    if let Some(Vector2(x, y)) =
+        camera_data.project(camera_data.pan_target, screen_rect.aspect_ratio())
+    {
+        let pivot = center + 0.5 * Vec2::new(x * screen_rect.width(), -y * screen_rect.height());
+        painter.circle_stroke(pivot, PIVOT_MARKER_RADIUS, stroke);
+    }
+}
+
+const MARKER_SIZE: f32 = 5.;
+
+// Diamond with the name of the marker next to it, for every marker in front of the camera
+fn markers_overlay(context: &Context, markers: &[Marker], camera_data: CameraData) {
+    let screen_rect = context.screen_rect();
+    let painter = context.layer_painter(LayerId::new(Order::Background, Id::new("markers")));
+    let color = Color32::from_rgb(255, 200, 60);
+
+    for marker in markers {
+        let Some(Vector2(x, y)) = camera_data.project(marker.position, screen_rect.aspect_ratio())
+        else {
+            continue;
+        };
+        let center = screen_rect.center()
+            + 0.5 * Vec2::new(x * screen_rect.width(), -y * screen_rect.height());
+        let corners =
+            [Vec2::X, Vec2::Y, -Vec2::X, -Vec2::Y].map(|corner| center + MARKER_SIZE * corner);
+        painter.add(egui::Shape::convex_polygon(
+            corners.to_vec(),
+            color.gamma_multiply(0.5),
+            Stroke::new(1., color),
+        ));
+        painter.text(
+            center + Vec2::new(2. * MARKER_SIZE, 0.),
+            egui::Align2::LEFT_CENTER,
+            &marker.name,
+            egui::FontId::proportional(12.),
+            color,
+        );
+    }
+}
+
+// Read-only panels describing the current image
+fn info_overlays(
+    context: &Context,
+    gui_data: &GuiData,
+    warnings: &[ArtifactWarning],
+    camera_data: CameraData,
+    markers: &[Marker],
+) {
+    // Panoramas and stereo pairs do not map the view to the screen with a single perspective
+    if gui_data.projection == Projection::Perspective && gui_data.stereo_mode == StereoMode::Off {
+        if gui_data.is_pivot_marker {
+            pivot_overlay(context, camera_data);
+        }
+        markers_overlay(context, markers, camera_data);
+    }
+
+    if gui_data.is_legend
+        && let Some(legend) = Legend::new(gui_data)
+    {
+        legend_overlay(context, &legend);
+    }
+
+    if !warnings.is_empty() {
+        warnings_window(context, warnings);
+    }
+}
+
+// Keyframe recorded from the current camera once the frame is rendered
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum KeyframeRequest {
+    Add,
+    Replace(usize),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum CameraPathAction {
+    AddKeyframe,
+    ReplaceKeyframe(usize),
+    RemoveKeyframe(usize),
+    // Moves the camera to the path at the time in seconds
+    Seek(f32),
+    Save,
+    Load,
+    // Pick the path file in a dialog before saving or loading it
+    SaveAs,
+    Open,
+    Clear,
+    Orbit,
+    Play,
+    Stop,
+    Bake,
+    Export(CameraPathFormat),
+}
+
+// Rows of the camera path window which move the camera along the path
+fn camera_path_playback(
+    ui: &mut Ui,
+    camera_path: &CameraPath,
+    is_playing: bool,
+    bake_status: BakeStatus,
+) -> Option<CameraPathAction> {
+    let mut action = None;
+
+    ui.label("Orbit:").on_hover_text(
+        "Replace the keyframes with a full turn around the point the camera looks at and play it",
+    );
+    if ui
+        .button("Orbit focus")
+        .on_hover_text(
+            "Replace the keyframes with a full turn around the point the camera looks at and play it",
+        )
+        .clicked()
+    {
+        action = Some(CameraPathAction::Orbit);
+    }
+    ui.end_row();
+
+    ui.label("Playback:")
+        .on_hover_text("Move the camera along the keyframes");
+    if is_playing {
+        if ui
+            .button("Stop")
+            .on_hover_text("Move the camera along the keyframes")
+            .clicked()
+        {
+            action = Some(CameraPathAction::Stop);
+        }
+    } else if ui
+        .add_enabled(camera_path.keyframes().len() > 1, egui::Button::new("Play"))
+        .on_hover_text("Move the camera along the keyframes")
+        .clicked()
+    {
+        action = Some(CameraPathAction::Play);
+    }
+    ui.end_row();
+
+    ui.label("Bake:").on_hover_text(
+        "Render every frame offscreen first, so playback uses even iteration counts and a settled exposure",
+    );
+    ui.horizontal(|ui| {
+        if let Some((baked, total)) = bake_status.progress {
+            ui.label(format!("{baked} / {total} frames"));
+        } else {
+            if ui
+                .add_enabled(camera_path.keyframes().len() > 1, egui::Button::new("Bake"))
+                .on_hover_text(
+                    "Render every frame offscreen first, so playback uses even iteration counts and a settled exposure",
+                )
+                .clicked()
+            {
+                action = Some(CameraPathAction::Bake);
+            }
+            if let Some(baked_frames) = bake_status.baked_frames {
+                ui.label(format!("{baked_frames} frames baked"));
+            }
+        }
+    });
+    ui.end_row();
+
+    action
+}
+
+// Rows of the camera path window listing the keyframes in time, with a slider scrubbing through them
+fn camera_path_timeline(
+    ui: &mut Ui,
+    camera_path: &mut CameraPath,
+    timeline_time: &mut f32,
+    is_playing: bool,
+) -> Option<CameraPathAction> {
+    let mut action = None;
+
+    ui.label("Timeline:")
+        .on_hover_text("Move the camera to the path at the time");
+    let duration = camera_path.duration();
+    *timeline_time = timeline_time.min(duration);
+    if ui
+        .add_enabled(
+            !is_playing && camera_path.keyframes().len() > 1,
+            Slider::new(timeline_time, 0.0..=duration).suffix(" s"),
+        )
+        .on_hover_text("Move the camera to the path at the time")
+        .changed()
+    {
+        action = Some(CameraPathAction::Seek(*timeline_time));
+    }
+    ui.end_row();
+
+    ui.label("Easing:")
+        .on_hover_text("Pace of the camera between two keyframes");
+    egui::ComboBox::from_id_salt("keyframe_easing")
+        .selected_text(format!("{}", camera_path.easing))
+        .show_ui(ui, |ui| {
+            for easing in KeyframeEasing::iter() {
+                ui.selectable_value(&mut camera_path.easing, easing, format!("{easing}"));
+            }
+        })
+        .response
+        .on_hover_text("Pace of the camera between two keyframes");
+    ui.end_row();
+
+    for index in 0..camera_path.keyframes().len() {
+        ui.label(format!("Keyframe {}:", index + 1));
+        ui.horizontal(|ui| {
+            let mut time = camera_path.keyframes()[index].time;
+            if ui
+                .add(
+                    DragValue::new(&mut time)
+                        .range(0.0..=f32::MAX)
+                        .speed(0.05)
+                        .suffix(" s"),
+                )
+                .on_hover_text("Time of the keyframe, it can not pass its neighbours")
+                .changed()
+            {
+                camera_path.set_keyframe_time(index, time);
+            }
+            if ui
+                .button("View")
+                .on_hover_text("Move the camera to the keyframe")
+                .clicked()
+            {
+                action = Some(CameraPathAction::Seek(time));
+            }
+            if ui
+                .button("Replace")
+                .on_hover_text("Record the current camera in place of the keyframe")
+                .clicked()
+            {
+                action = Some(CameraPathAction::ReplaceKeyframe(index));
+            }
+            if ui
+                .button("Remove")
+                .on_hover_text("Remove the keyframe")
+                .clicked()
+            {
+                action = Some(CameraPathAction::RemoveKeyframe(index));
+            }
+        });
+        ui.end_row();
+    }
+
+    action
+}
+
+fn camera_path_window(
+    context: &Context,
+    camera_path: &mut CameraPath,
+    camera_path_file: &mut String,
+    timeline_time: &mut f32,
+    is_playing: bool,
+    bake_status: BakeStatus,
+) -> Option<CameraPathAction> {
+    let mut action = None;
+
+    EguiWindow::new(CAMERA_PATH_TITLE)
+        .resizable(false)
+        .default_open(false)
+        .show(context, |ui| {
+            egui::Grid::new("camera_path_grid").show(ui, |ui| {
+                ui.label("Keyframes:")
+                    .on_hover_text("Camera positions recorded so far");
+                ui.horizontal(|ui| {
+                    ui.label(format!("{}", camera_path.keyframes().len()));
+                    if ui
+                        .button("Add")
+                        .on_hover_text("Record the current camera as the next keyframe")
+                        .clicked()
+                    {
+                        action = Some(CameraPathAction::AddKeyframe);
+                    }
+                    if ui
+                        .add_enabled(!camera_path.keyframes().is_empty(), egui::Button::new("Clear"))
+                        .on_hover_text("Remove every keyframe")
+                        .clicked()
+                    {
+                        action = Some(CameraPathAction::Clear);
+                    }
+                });
+                ui.end_row();
+
+                if let Some(playback_action) = camera_path_playback(ui, camera_path, is_playing, bake_status) {
+                    action = Some(playback_action);
+                }
+                if let Some(timeline_action) =
+                    camera_path_timeline(ui, camera_path, timeline_time, is_playing)
+                {
+                    action = Some(timeline_action);
+                }
+
+                ui.label("Keyframe interval:")
+                    .on_hover_text("Time between a keyframe and the one recorded after it");
+                ui.add(
+                    DragValue::new(&mut camera_path.keyframe_interval)
+                        .range(0.1..=60.0)
+                        .speed(0.1)
+                        .suffix(" s"),
+                )
+                .on_hover_text("Time between a keyframe and the one recorded after it");
+                ui.end_row();
+
+                ui.label("Frame rate:")
+                    .on_hover_text("Frame rate the keyframe times are converted to frames with");
+                ui.add(
+                    DragValue::new(&mut camera_path.frame_rate)
+                        .range(1..=240)
+                        .suffix(" fps"),
+                )
+                .on_hover_text("Frame rate the keyframe times are converted to frames with");
+                ui.end_row();
+
+                ui.label("Path file:").on_hover_text(
+                    "Keyframes and playback settings, which load back into any scene",
+                );
+                ui.horizontal(|ui| {
+                    ui.add(TextEdit::singleline(camera_path_file).desired_width(160.));
+                    if ui
+                        .add_enabled(!camera_path.keyframes().is_empty(), egui::Button::new("Save"))
+                        .clicked()
+                    {
+                        action = Some(CameraPathAction::Save);
+                    }
+                    if ui.button("Load").clicked() {
+                        action = Some(CameraPathAction::Load);
+                    }
+                    if ui
+                        .add_enabled(
+                            !camera_path.keyframes().is_empty(),
+                            egui::Button::new("Save as"),
+                        )
+                        .on_hover_text("Pick the file in a dialog and save the path to it")
+                        .clicked()
+                    {
+                        action = Some(CameraPathAction::SaveAs);
+                    }
+                    if ui
+                        .button("Open")
+                        .on_hover_text("Pick the file in a dialog and load the path from it")
+                        .clicked()
+                    {
+                        action = Some(CameraPathAction::Open);
+                    }
+                })
+                .response
+                .on_hover_text(
+                    "Keyframes and playback settings, which load back into any scene",
+                );
+                ui.end_row();
+
+                ui.label("Export:")
+                    .on_hover_text("Write the path into the working directory");
+                ui.add_enabled_ui(!camera_path.keyframes().is_empty(), |ui| {
+                    ui.horizontal(|ui| {
+                        for (format, name, description) in [
+                            (
+                                CameraPathFormat::AlembicJson,
+                                "JSON",
+                                "Camera to world matrix of every keyframe (camera_path.json)",
+                            ),
+                            (
+                                CameraPathFormat::BlenderScript,
+                                "Blender",
+                                "Script creating the animated camera, run it from Blender's text editor (camera_path.py)",
+                            ),
+                        ] {
+                            if ui.button(name).on_hover_text(description).clicked() {
+                                action = Some(CameraPathAction::Export(format));
+                            }
+                        }
+                    });
+                });
+                ui.end_row();
+            });
+        });
+
+    action
+}
+
+// Returns whether an export with the settings was requested
+fn tiled_export_window(
+    context: &Context,
+    settings: &mut TiledExportSettings,
+    status: TiledExportStatus,
+) -> bool {
+    let mut is_requested = false;
+
+    EguiWindow::new(TILED_EXPORT_TITLE)
+        .resizable(false)
+        .default_open(false)
+        .show(context, |ui| {
+            // Workers only render the tiles the coordinator sends them
+            ui.add_enabled_ui(!status.is_worker && status.progress.is_none(), |ui| {
+                egui::Grid::new("tiled_export_grid").show(ui, |ui| {
+                    ui.label("Resolution:")
+                        .on_hover_text("Size of the exported image in pixels");
+                    ui.horizontal(|ui| {
+                        ui.add(DragValue::new(&mut settings.width).range(1..=32768))
+                            .on_hover_text("Size of the exported image in pixels");
+                        ui.label("x");
+                        ui.add(DragValue::new(&mut settings.height).range(1..=32768))
+                            .on_hover_text("Size of the exported image in pixels");
+                    });
+                    ui.end_row();
+
+                    ui.label("Samples:")
+                        .on_hover_text("Samples accumulated for every pixel of the exported image");
+                    ui.add(DragValue::new(&mut settings.samples).range(1..=1024))
+                        .on_hover_text("Samples accumulated for every pixel of the exported image");
+                    ui.end_row();
+
+                    ui.label("Export:").on_hover_text(
+                        "Render the image tile by tile and write it into the working directory (tiled_export.pfm)",
+                    );
+                    is_requested = ui
+                        .button("Start")
+                        .on_hover_text(
+                            "Render the image tile by tile and write it into the working directory (tiled_export.pfm)",
+                        )
+                        .clicked();
+                    ui.end_row();
+                });
+            });
+
+            ui.separator();
+            if let Some(worker_count) = status.worker_count {
+                ui.label(format!("Coordinating {worker_count} workers"));
+            } else if status.is_worker {
+                ui.label(format!(
+                    "Connected to a coordinator, {} tiles queued",
+                    status.queued_tiles
+                ));
+            }
+            if let Some((finished, all)) = status.progress {
+                ui.label(format!("Rendered {finished} of {all} tiles"));
+            }
+        });
+
+    is_requested
+}
+
+#[derive(Clone, Copy, Debug)]
+enum MeshAction {
+    Extract,
+    ExportVoxels,
+}
+
+// Finest grid the mesh can be extracted from, its samples take 64 MiB
+const MAX_MESH_RESOLUTION: u32 = 256;
+
+fn mesh_window(
+    context: &Context,
+    settings: &mut MeshSettings,
+    result: Option<&Result<TriangleMesh, MeshError>>,
+    voxel_export_result: Option<&Result<usize, VoxelExportError>>,
+) -> Option<MeshAction> {
+    let mut action = None;
+
+    EguiWindow::new(MESH_TITLE)
+        .resizable(false)
+        .default_open(false)
+        .show(context, |ui| {
+            egui::Grid::new("mesh_grid").show(ui, |ui| {
+                ui.label("Resolution:")
+                    .on_hover_text("Samples of the distance estimate along every side of the grid");
+                ui.add(
+                    DragValue::new(&mut settings.resolution)
+                        .range(MeshSettings::MIN_RESOLUTION..=MAX_MESH_RESOLUTION),
+                )
+                .on_hover_text("Samples of the distance estimate along every side of the grid");
+                ui.end_row();
+
+                ui.label("Extent:")
+                    .on_hover_text("Half the side length of the grid, which is centered on the origin");
+                ui.add(
+                    DragValue::new(&mut settings.extent)
+                        .speed(0.01)
+                        .range(0.01..=16.),
+                )
+                .on_hover_text("Half the side length of the grid, which is centered on the origin");
+                ui.end_row();
+
+                ui.label("Surface:").on_hover_text(
+                    "Sample the scene objects on the grid and extract their surface with marching cubes",
+                );
+                if ui
+                    .button("Extract")
+                    .on_hover_text(
+                        "Sample the scene objects on the grid and extract their surface with marching cubes",
+                    )
+                    .clicked()
+                {
+                    action = Some(MeshAction::Extract);
+                }
+                ui.end_row();
+
+                ui.label("Voxels:").on_hover_text(
+                    "Write the samples inside the scene objects into the working directory (voxels.vox), at most 256 along every side",
+                );
+                if ui
+                    .button("Export")
+                    .on_hover_text(
+                        "Write the samples inside the scene objects into the working directory (voxels.vox), at most 256 along every side",
+                    )
+                    .clicked()
+                {
+                    action = Some(MeshAction::ExportVoxels);
+                }
+                ui.end_row();
+            });
+
+            match result {
+                Some(Ok(mesh)) => {
+                    ui.label(format!(
+                        "{} triangles, {} vertices",
+                        mesh.triangles.len(),
+                        mesh.vertices.len()
+                    ));
+                }
+                Some(Err(error)) => {
+                    ui.label(RichText::new(error.to_string()).color(ui.visuals().error_fg_color));
+                }
+                None => {}
+            }
+            match voxel_export_result {
+                Some(Ok(voxel_count)) => {
+                    ui.label(format!("Exported {voxel_count} voxels"));
+                }
+                Some(Err(error)) => {
+                    ui.label(RichText::new(error.to_string()).color(ui.visuals().error_fg_color));
+                }
+                None => {}
+            }
+        });
+
+    action
+}
+
+#[derive(Clone, Copy, Debug)]
+enum PresetAction {
+    Generate,
+    Randomize,
+    CopyLink,
+    ExportReport,
+}
+
+fn seeded_preset_window(context: &Context, seed: &mut String) -> Option<PresetAction> {
+    let mut action = None;
+
+    EguiWindow::new(SEEDED_PRESET_TITLE)
+        .resizable(false)
+        .default_open(false)
+        .show(context, |ui| {
+            egui::Grid::new("seeded_preset_grid").show(ui, |ui| {
+                ui.label("Seed:").on_hover_text(
+                    "Any text or a shared kifs://seed/ link, the same seed always gives the same preset",
+                );
+                ui.add(TextEdit::singleline(seed).desired_width(160.))
+                    .on_hover_text(
+                        "Any text or a shared kifs://seed/ link, the same seed always gives the same preset",
+                    );
+                ui.end_row();
+
+                let is_seeded = !parse_seed(seed).is_empty();
+                ui.label("Preset:")
+                    .on_hover_text("Replace the fractal, palette, lighting and camera");
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(is_seeded, egui::Button::new("Generate"))
+                        .on_hover_text("Replace the fractal, palette, lighting and camera")
+                        .clicked()
+                    {
+                        action = Some(PresetAction::Generate);
+                    }
+                    if ui
+                        .button("Random")
+                        .on_hover_text("Generate a preset from a new random seed")
+                        .clicked()
+                    {
+                        action = Some(PresetAction::Randomize);
+                    }
+                    if ui
+                        .add_enabled(is_seeded, egui::Button::new("Copy link"))
+                        .on_hover_text("Copy a link others can generate the same preset from")
+                        .clicked()
+                    {
+                        action = Some(PresetAction::CopyLink);
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Report:").on_hover_text(
+                    "Write a readable summary of every current setting for papers or forum posts (settings_report.md)",
+                );
+                if ui
+                    .button("Export settings report")
+                    .on_hover_text(
+                        "Write a readable summary of every current setting for papers or forum posts (settings_report.md)",
+                    )
+                    .clicked()
+                {
+                    action = Some(PresetAction::ExportReport);
+                }
+                ui.end_row();
+            });
+        });
+
+    action
+}
+
+// Material of a single scene object, the object is picked in the window itself
+fn object_inspector_window(
+    context: &Context,
+    gui_data: &mut GuiData,
+    inspected_object: &mut usize,
+) {
+    EguiWindow::new(OBJECT_INSPECTOR_TITLE)
+        .resizable(false)
+        .default_open(false)
+        .show(context, |ui| {
+            egui::Grid::new("object_inspector_grid").show(ui, |ui| {
+                let object_name =
+                    |index: usize| format!("Object {} ({})", index + 1, gui_data.scene_objects[index].kind);
+                ui.label("Object:").on_hover_text("Scene object whose material is edited");
+                egui::ComboBox::from_id_salt("inspected_object")
+                    .selected_text(object_name(*inspected_object))
+                    .show_ui(ui, |ui| {
+                        for index in 0..MAX_SCENE_OBJECTS {
+                            ui.selectable_value(inspected_object, index, object_name(index));
+                        }
+                    })
+                    .response
+                    .on_hover_text("Scene object whose material is edited");
+                ui.end_row();
+
+                let object = &mut gui_data.scene_objects[*inspected_object];
+                if object.kind == SceneObjectKind::Group {
+                    ui.label("Groups have no surface of their own");
+                    ui.end_row();
+                    return;
+                }
+                let material = &mut object.material;
+
+                ui.label("Albedo:")
+                    .on_hover_text("Tint of the surface, multiplied with the fractal color");
+                ui.color_edit_button_srgb(&mut material.albedo)
+                    .on_hover_text("Tint of the surface, multiplied with the fractal color");
+                ui.end_row();
+
+                ui.label("Specular:")
+                    .on_hover_text("Strength of the highlights the lights leave on the surface");
+                ui.add(Slider::new(&mut material.specular, 0.0..=1.0))
+                    .on_hover_text("Strength of the highlights the lights leave on the surface");
+                ui.end_row();
+
+                ui.label("Reflectivity:").on_hover_text(
+                    "Share of the color mirrored from the rest of the scene, mirrored surfaces are not reflected again",
+                );
+                ui.add(Slider::new(&mut material.reflectivity, 0.0..=1.0))
+                    .on_hover_text(
+                        "Share of the color mirrored from the rest of the scene, mirrored surfaces are not reflected again",
+                    );
+                ui.end_row();
+
+                ui.label("Emissive:")
+                    .on_hover_text("Light the surface gives off regardless of the lighting");
+                ui.horizontal(|ui| {
+                    ui.color_edit_button_srgb(&mut material.emissive);
+                    ui.add(
+                        Slider::new(&mut material.emission_strength, 0.0..=10.0)
+                            .logarithmic(true),
+                    );
+                })
+                .response
+                .on_hover_text("Light the surface gives off regardless of the lighting");
+                ui.end_row();
+            });
+        });
+}
+
+// Bound settings are listed with the value their expression gave on the last frame
+fn expressions_window(
+    context: &Context,
+    gui_data: &GuiData,
+    bindings: &mut Vec<ParameterBinding>,
+    results: &[Result<f64, ExpressionError>],
+) {
+    EguiWindow::new(EXPRESSIONS_TITLE)
+        .resizable(false)
+        .default_open(false)
+        .show(context, |ui| {
+            let parameters = numeric_parameters(gui_data).unwrap_or_default();
+            let mut removed = None;
+
+            egui::Grid::new("expressions_grid").show(ui, |ui| {
+                for (index, binding) in bindings.iter_mut().enumerate() {
+                    egui::ComboBox::from_id_salt(("binding_target", index))
+                        .selected_text(binding.target.as_str())
+                        .height(300.)
+                        .show_ui(ui, |ui| {
+                            for parameter in &parameters {
+                                ui.selectable_value(
+                                    &mut binding.target,
+                                    parameter.clone(),
+                                    parameter.as_str(),
+                                );
+                            }
+                        })
+                        .response
+                        .on_hover_text("Setting driven by the expression");
+                    ui.label("=");
+                    ui.add(
+                        TextEdit::singleline(&mut binding.source)
+                            .desired_width(180.)
+                            .hint_text("2 + sin(t) * 0.5"),
+                    )
+                    .on_hover_text(
+                        "Numbers, other settings by name, the time t in seconds and pi, combined with + - * / % ^ and sin, cos, tan, abs, sqrt, exp, ln, floor, ceil, fract, sign, min, max, pow, clamp and mix",
+                    );
+                    match results.get(index) {
+                        Some(Ok(value)) => {
+                            ui.label(format!("{value:.4}"));
+                        }
+                        Some(Err(error)) => {
+                            ui.label(RichText::new("Error").color(ui.visuals().error_fg_color))
+                                .on_hover_text(error.to_string());
+                        }
+                        None => {
+                            ui.label("");
+                        }
+                    }
+                    if ui.small_button("Remove").clicked() {
+                        removed = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+
+            if let Some(index) = removed {
+                bindings.remove(index);
+            }
+            if ui
+                .button("Add")
+                .on_hover_text("Drive another setting by an expression")
+                .clicked()
+            {
+                bindings.push(ParameterBinding {
+                    target: "power".to_string(),
+                    source: String::new(),
+                });
+            }
+        });
+}
+
+// Returns whether the formula was requested to be compiled
+fn formula_window(
+    context: &Context,
+    iteration: &mut String,
+    distance: &mut String,
+    result: Option<&Result<(), FormulaError>>,
+) -> bool {
+    let mut is_requested = false;
+
+    EguiWindow::new(FORMULA_TITLE)
+        .resizable(false)
+        .default_open(false)
+        .show(context, |ui| {
+            ui.label("Iteration:").on_hover_text(
+                "One name = expression per line, applied in order. \
+                Assign the point x, y, z and its derivative dr from the position cx, cy, cz, the radius r, \
+                the iteration i, pi and variables of earlier lines, combined with + - * / % ^ and \
+                sin, cos, tan, abs, sqrt, exp, ln, floor, ceil, fract, sign, min, max, pow, clamp and mix. \
+                Lines starting with # are comments",
+            );
+            ui.add(
+                TextEdit::multiline(iteration)
+                    .code_editor()
+                    .desired_rows(10)
+                    .desired_width(320.),
+            );
+
+            ui.label("Distance estimate:").on_hover_text(
+                "Expression of the final x, y, z, dr, r and the position cx, cy, cz",
+            );
+            ui.add(
+                TextEdit::singleline(distance)
+                    .code_editor()
+                    .desired_width(320.)
+                    .hint_text(DEFAULT_DISTANCE),
+            );
+
+            ui.horizontal(|ui| {
+                is_requested = ui
+                    .button("Compile")
+                    .on_hover_text("Compile the formula and show it as the custom formula group")
+                    .clicked();
+                if ui
+                    .button("Reset")
+                    .on_hover_text("Replace the formula by the default Mandelbox")
+                    .clicked()
+                {
+                    *iteration = DEFAULT_ITERATION.to_string();
+                    *distance = DEFAULT_DISTANCE.to_string();
+                }
+            });
+
+            match result {
+                Some(Ok(())) => {
+                    ui.label("Compiled");
+                }
+                Some(Err(error)) => {
+                    ui.label(RichText::new(error.to_string()).color(ui.visuals().error_fg_color));
+                }
+                None => {}
+            }
+        });
+
+    is_requested
+}
+
+#[derive(Clone, Copy, Debug)]
+enum MarkerAction {
+    Drop,
+    Center(usize),
+    Remove(usize),
+}
+
+fn markers_window(
+    context: &Context,
+    markers: &[Marker],
+    name: &mut String,
+    is_ray_recorded: bool,
+) -> Option<MarkerAction> {
+    let mut action = None;
+
+    EguiWindow::new(MARKERS_TITLE)
+        .resizable(false)
+        .default_open(false)
+        .show(context, |ui| {
+            ui.horizontal(|ui| {
+                ui.add(
+                    TextEdit::singleline(name)
+                        .desired_width(140.)
+                        .hint_text(format!("Marker {}", markers.len() + 1)),
+                )
+                .on_hover_text("Name of the next marker");
+                if ui
+                    .add_enabled(is_ray_recorded, egui::Button::new("Drop"))
+                    .on_hover_text("Drop a marker where the ray of the ray inspector ended")
+                    .on_disabled_hover_text("Pick a surface point with the ray inspector first")
+                    .clicked()
+                {
+                    action = Some(MarkerAction::Drop);
+                }
+            });
+
+            egui::Grid::new("markers_grid").show(ui, |ui| {
+                for (index, marker) in markers.iter().enumerate() {
+                    let Vector3(x, y, z) = marker.position;
+                    if ui
+                        .link(&marker.name)
+                        .on_hover_text(format!(
+                            "({x:.4}, {y:.4}, {z:.4}), click to center the camera on it"
+                        ))
+                        .clicked()
+                    {
+                        action = Some(MarkerAction::Center(index));
+                    }
+                    if ui.small_button("Remove").clicked() {
+                        action = Some(MarkerAction::Remove(index));
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+
+    action
+}
+
+// Returns whether the file was requested to be loaded
+// Shown on the first start after a crash until it is dismissed, as the console is often hidden
+fn crash_notice_window(context: &Context, crash_notice: &mut Option<String>) {
+    let Some(panic) = crash_notice else {
+        return;
+    };
+    let mut is_open = true;
+    let mut is_dismissed = false;
+
+    EguiWindow::new(CRASH_NOTICE_TITLE)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, [0., 0.])
+        .open(&mut is_open)
+        .show(context, |ui| {
+            ui.label(format!(
+                "kifs-raymarching crashed the last time it ran. The settings you were working with and the recent log were saved to {CRASH_REPORT_PATH}, please attach it when reporting the bug."
+            ));
+            if !panic.is_empty() {
+                ui.label(RichText::new(panic.as_str()).color(ui.visuals().error_fg_color));
+            }
+            is_dismissed = ui.button("Dismiss").clicked();
+        });
+
+    if !is_open || is_dismissed {
+        *crash_notice = None;
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum ShaderFileAction {
+    Load,
+    // Pick the file in a dialog before loading it
+    Browse,
+}
+
+fn shader_file_window(
+    context: &Context,
+    path: &mut String,
+    result: Option<&Result<(), ShaderFileError>>,
+) -> Option<ShaderFileAction> {
+    let mut action = None;
+
+    EguiWindow::new(SHADER_FILE_TITLE)
+        .resizable(false)
+        .default_open(false)
+        .show(context, |ui| {
+            egui::Grid::new("shader_file_grid").show(ui, |ui| {
+                ui.label("Path:").on_hover_text(
+                    "Project asset or WGSL file defining fn user_sdf(p: vec3<f32>) -> f32, the distance to its surface, files are added to the project assets",
+                );
+                ui.add(
+                    TextEdit::singleline(path)
+                        .desired_width(240.)
+                        .hint_text("shapes/sdf.wgsl"),
+                )
+                .on_hover_text(
+                    "Project asset or WGSL file defining fn user_sdf(p: vec3<f32>) -> f32, the distance to its surface, files are added to the project assets",
+                );
+                ui.end_row();
+
+                ui.label("Load:")
+                    .on_hover_text("Read and compile the file again, after every edit of it");
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Load")
+                        .on_hover_text("Read and compile the file again, after every edit of it")
+                        .clicked()
+                    {
+                        action = Some(ShaderFileAction::Load);
+                    }
+                    if ui
+                        .button("Browse")
+                        .on_hover_text("Pick the file in a dialog and load it")
+                        .clicked()
+                    {
+                        action = Some(ShaderFileAction::Browse);
+                    }
+                });
+                ui.end_row();
+            });
+
+            match result {
+                Some(Ok(())) => {
+                    ui.label("Compiled");
+                }
+                Some(Err(error)) => {
+                    ui.label(RichText::new(error.to_string()).color(ui.visuals().error_fg_color));
+                }
+                None => {}
+            }
+        });
+
+    action
+}
+
+// Parameters of the node shown in its row of the node graph
+fn node_parameters(ui: &mut Ui, index: usize, kind: &mut NodeKind) {
+    match kind {
+        NodeKind::Position | NodeKind::AbsFold => {
+            ui.label("");
+        }
+        NodeKind::PlaneFold { normal } => vector3_drag_values(
+            ui,
+            normal,
+            0.01,
+            -1.0..=1.0,
+            "Normal of the mirror plane through the origin, points behind it are mirrored to its front",
+        ),
+        NodeKind::Rotate { axis, degrees } => {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt(("node_axis", index))
+                    .selected_text(format!("{axis}"))
+                    .width(40.)
+                    .show_ui(ui, |ui| {
+                        for option in WarpAxis::iter() {
+                            ui.selectable_value(axis, option, format!("{option}"));
+                        }
+                    });
+                ui.add(
+                    DragValue::new(degrees)
+                        .speed(0.5)
+                        .range(-180.0..=180.0)
+                        .suffix("°"),
+                )
+                .on_hover_text("Angle of the rotation around the axis");
+            });
+        }
+        NodeKind::Scale { factor, center } => {
+            ui.horizontal(|ui| {
+                ui.add(DragValue::new(factor).speed(0.01).range(-4.0..=4.0))
+                    .on_hover_text("Factor the space is scaled by, copies shrink by its inverse");
+                vector3_drag_values(
+                    ui,
+                    center,
+                    0.01,
+                    -2.0..=2.0,
+                    "Point the space is scaled around",
+                );
+            });
+        }
+        NodeKind::Translate { offset } => {
+            vector3_drag_values(ui, offset, 0.01, -2.0..=2.0, "Offset added to the point");
+        }
+        NodeKind::Primitive { shape, size } => {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt(("node_shape", index))
+                    .selected_text(format!("{shape}"))
+                    .show_ui(ui, |ui| {
+                        for option in NodeShape::ALL {
+                            ui.selectable_value(shape, option, format!("{option}"));
+                        }
+                    });
+                ui.add(DragValue::new(size).speed(0.01).range(0.01..=2.0))
+                    .on_hover_text("Size of the shape measured after the last iteration");
+            });
+        }
+    }
+}
+
+// Returns whether the graph was requested to be compiled
+fn node_graph_window(
+    context: &Context,
+    graph: &mut NodeGraph,
+    error: Option<NodeGraphError>,
+) -> bool {
+    let mut is_requested = false;
+
+    EguiWindow::new(NODE_GRAPH_TITLE)
+        .resizable(false)
+        .default_open(false)
+        .show(context, |ui| {
+            let labels: Vec<String> = graph
+                .nodes
+                .iter()
+                .enumerate()
+                .map(|(index, node)| format!("{index}: {}", node.kind))
+                .collect();
+            let mut removed = None;
+
+            egui::Grid::new("node_graph_grid").show(ui, |ui| {
+                for (index, node) in graph.nodes.iter_mut().enumerate() {
+                    ui.label(&labels[index]);
+
+                    if node.kind == NodeKind::Position {
+                        ui.label("");
+                    } else {
+                        egui::ComboBox::from_id_salt(("node_input", index))
+                            .selected_text(
+                                node.input
+                                    .and_then(|input| labels.get(input))
+                                    .map_or("Unconnected", String::as_str),
+                            )
+                            .show_ui(ui, |ui| {
+                                for (input, label) in labels.iter().enumerate() {
+                                    if input != index {
+                                        ui.selectable_value(
+                                            &mut node.input,
+                                            Some(input),
+                                            label.as_str(),
+                                        );
+                                    }
+                                }
+                            })
+                            .response
+                            .on_hover_text("Node whose output flows into this one");
+                    }
+
+                    node_parameters(ui, index, &mut node.kind);
+                    if ui.small_button("Remove").clicked() {
+                        removed = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+
+            if let Some(index) = removed {
+                graph.remove(index);
+            }
+
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt("node_add")
+                    .selected_text("Add node")
+                    .show_ui(ui, |ui| {
+                        for kind in NodeKind::ALL {
+                            if ui.selectable_label(false, format!("{kind}")).clicked() {
+                                // New nodes continue from the last one, the usual place to extend a chain
+                                let input = (kind != NodeKind::Position)
+                                    .then(|| graph.nodes.len().checked_sub(1))
+                                    .flatten();
+                                graph.nodes.push(FractalNode { kind, input });
+                            }
+                        }
+                    });
+                is_requested = ui
+                    .button("Compile")
+                    .on_hover_text(
+                        "Compile the nodes from the position to the primitive into the formula window and show it",
+                    )
+                    .clicked();
+                if ui
+                    .button("Reset")
+                    .on_hover_text("Replace the graph by the Sierpinski tetrahedron")
+                    .clicked()
+                {
+                    *graph = NodeGraph::default();
+                }
+            });
+
+            if let Some(error) = error {
+                ui.label(RichText::new(error.to_string()).color(ui.visuals().error_fg_color));
+            }
+        });
+
+    is_requested
+}
+
+#[derive(Clone, Copy, Debug)]
+enum ProjectAction {
+    Save,
+    Load,
+    // Pick the file in a dialog before saving, loading or adding it
+    SaveAs,
+    Open,
+    AddAsset,
+    BrowseAsset,
+    EmbedAsset(usize),
+    RemoveAsset(usize),
+}
+
+fn project_window(
+    context: &Context,
+    project_path: &mut String,
+    asset_path: &mut String,
+    assets: &AssetManager,
+) -> Option<ProjectAction> {
+    let mut action = None;
+
+    EguiWindow::new(PROJECT_TITLE)
+        .resizable(false)
+        .default_open(false)
+        .show(context, |ui| {
+            egui::Grid::new("project_grid").show(ui, |ui| {
+                ui.label("Project file:").on_hover_text(
+                    "Settings, camera and assets of the scene, relative asset paths are resolved against the directory of this file",
+                );
+                ui.horizontal(|ui| {
+                    ui.add(TextEdit::singleline(project_path).desired_width(160.));
+                    if ui.button("Save").clicked() {
+                        action = Some(ProjectAction::Save);
+                    }
+                    if ui.button("Load").clicked() {
+                        action = Some(ProjectAction::Load);
+                    }
+                    if ui
+                        .button("Save as")
+                        .on_hover_text("Pick the file in a dialog and save the project to it")
+                        .clicked()
+                    {
+                        action = Some(ProjectAction::SaveAs);
+                    }
+                    if ui
+                        .button("Open")
+                        .on_hover_text("Pick the file in a dialog and load the project from it")
+                        .clicked()
+                    {
+                        action = Some(ProjectAction::Open);
+                    }
+                })
+                .response
+                .on_hover_text(
+                    "Settings, camera and assets of the scene, relative asset paths are resolved against the directory of this file",
+                );
+                ui.end_row();
+
+                for (index, asset) in assets.assets().iter().enumerate() {
+                    ui.label(format!("{}:", asset.name));
+                    ui.horizontal(|ui| {
+                        match &asset.source {
+                            AssetSource::File(path) => {
+                                ui.label(path.display().to_string());
+                                if ui
+                                    .small_button("Embed")
+                                    .on_hover_text(format!(
+                                        "Copy the file into the project, for files up to {} KiB",
+                                        MAX_EMBEDDED_ASSET_SIZE / 1024
+                                    ))
+                                    .clicked()
+                                {
+                                    action = Some(ProjectAction::EmbedAsset(index));
+                                }
+                            }
+                            AssetSource::Embedded(data) => {
+                                ui.label(format!("Embedded, {} bytes", data.len()));
+                            }
+                        }
+                        if ui.small_button("Remove").clicked() {
+                            action = Some(ProjectAction::RemoveAsset(index));
+                        }
+                    });
+                    ui.end_row();
+                }
+
+                ui.label("Add asset:")
+                    .on_hover_text("Environment map, palette or shader file used by the scene");
+                ui.horizontal(|ui| {
+                    ui.add(TextEdit::singleline(asset_path).desired_width(160.));
+                    if ui
+                        .add_enabled(!asset_path.trim().is_empty(), egui::Button::new("Add"))
+                        .clicked()
+                    {
+                        action = Some(ProjectAction::AddAsset);
+                    }
+                    if ui
+                        .button("Browse")
+                        .on_hover_text("Pick the file in a dialog and add it")
+                        .clicked()
+                    {
+                        action = Some(ProjectAction::BrowseAsset);
+                    }
+                })
+                .response
+                .on_hover_text("Environment map, palette or shader file used by the scene");
+                ui.end_row();
+            });
+        });
+
+    action
+}
+
+#[derive(Clone, Copy, Debug)]
+enum SnapshotAction {
+    Capture,
+    Restore(usize),
+}
+
+// Height of the snapshots in the strip in points
+const SNAPSHOT_THUMBNAIL_HEIGHT: f32 = 54.;
+
+// Strip along the bottom of the screen, snapshots can be dragged from it onto the compare view
+fn snapshot_strip(
+    context: &Context,
+    snapshots: &[Snapshot],
+    is_comparing: &mut bool,
+) -> Option<SnapshotAction> {
+    let mut action = None;
+
+    egui::Area::new(Id::new("snapshot_strip"))
+        .anchor(Align2::CENTER_BOTTOM, [0., -8.])
+        .show(context, |ui| {
+            Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Capture")
+                        .on_hover_text("Capture the current settings, camera and image")
+                        .clicked()
+                    {
+                        action = Some(SnapshotAction::Capture);
+                    }
+                    ui.toggle_value(is_comparing, "Compare")
+                        .on_hover_text("Compare two snapshots side by side");
+
+                    for (index, snapshot) in snapshots.iter().enumerate() {
+                        let size = Vec2::new(
+                            SNAPSHOT_THUMBNAIL_HEIGHT * snapshot.aspect_ratio(),
+                            SNAPSHOT_THUMBNAIL_HEIGHT,
+                        );
+                        let response = ui
+                            .add(Image::new((snapshot.image, size)).sense(Sense::click_and_drag()))
+                            .on_hover_text(
+                                "Click to restore, drag onto the compare view to compare",
+                            );
+                        response.dnd_set_drag_payload(index);
+                        if response.clicked() {
+                            action = Some(SnapshotAction::Restore(index));
+                        }
+                    }
+                });
+            });
+        });
+
+    action
+}
+
+// Width of the compared images in points
+const COMPARE_DISPLAY_WIDTH: f32 = 480.;
+
+// Snapshot A is shown left and snapshot B right of the split
+fn compare_window(
+    context: &Context,
+    snapshots: &[Snapshot],
+    compared: &mut [Option<usize>; 2],
+    split: &mut f32,
+    is_open: &mut bool,
+) {
+    EguiWindow::new("Compare")
+        .open(is_open)
+        .resizable(false)
+        .show(context, |ui| {
+            ui.horizontal(|ui| {
+                for (slot, label) in compared.iter_mut().zip(["A", "B"]) {
+                    let (_, payload) =
+                        ui.dnd_drop_zone::<usize, _>(Frame::group(ui.style()), |ui| {
+                            match slot.map(|index| &snapshots[index]) {
+                                Some(snapshot) => {
+                                    let size = Vec2::new(
+                                        SNAPSHOT_THUMBNAIL_HEIGHT * snapshot.aspect_ratio(),
+                                        SNAPSHOT_THUMBNAIL_HEIGHT,
+                                    );
+                                    ui.add(Image::new((snapshot.image, size)));
+                                }
+                                None => {
+                                    ui.label(format!("Drop snapshot {label} here"));
+                                }
+                            }
+                        });
+                    if let Some(index) = payload {
+                        *slot = Some(*index);
+                    }
+                }
+            });
+
+            let [Some(a), Some(b)] = compared.map(|slot| slot.map(|index| &snapshots[index]))
+            else {
+                return;
+            };
+
+            ui.add(Slider::new(split, 0.0..=1.0).text("Split"))
+                .on_hover_text("Position of the border between snapshot A and B");
+
+            let (rect, _) = ui.allocate_exact_size(
+                Vec2::new(
+                    COMPARE_DISPLAY_WIDTH,
+                    COMPARE_DISPLAY_WIDTH / a.aspect_ratio(),
+                ),
+                Sense::hover(),
+            );
+            let split_x = rect.left() + *split * rect.width();
+            let painter = ui.painter();
+            painter.image(
+                a.image,
+                rect,
+                Rect::from_min_max(Pos2::ZERO, Pos2::new(1., 1.)),
+                Color32::WHITE,
+            );
+            painter.image(
+                b.image,
+                Rect::from_min_max(Pos2::new(split_x, rect.top()), rect.max),
+                Rect::from_min_max(Pos2::new(*split, 0.), Pos2::new(1., 1.)),
+                Color32::WHITE,
+            );
+            painter.vline(split_x, rect.y_range(), Stroke::new(1.5, Color32::WHITE));
+        });
+}
+
+#[derive(Clone, Copy, Debug)]
+enum WorkspaceAction {
+    Switch(usize),
+    Save,
+    Delete(usize),
+}
+
+// Menu in the top right corner for switching between, saving and deleting workspaces
+fn workspace_menu(
+    context: &Context,
+    workspaces: &[Workspace],
+    new_name: &mut String,
+) -> Option<WorkspaceAction> {
+    let mut action = None;
+
+    egui::Area::new(Id::new("workspace_menu"))
+        .anchor(Align2::RIGHT_TOP, [-8., 8.])
+        .show(context, |ui| {
+            Frame::popup(ui.style()).show(ui, |ui| {
+                ui.menu_button("Workspaces", |ui| {
+                    for (index, workspace) in workspaces.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if ui.button(&workspace.name).clicked() {
+                                action = Some(WorkspaceAction::Switch(index));
+                                ui.close();
+                            }
+                            if ui.small_button("Delete").clicked() {
+                                action = Some(WorkspaceAction::Delete(index));
+                            }
+                        });
+                    }
+
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.add(TextEdit::singleline(new_name).desired_width(120.));
+                        let is_named = !new_name.trim().is_empty();
+                        if ui
+                            .add_enabled(is_named, egui::Button::new("Save"))
+                            .on_hover_text(
+                                "Save the current layout, replacing a workspace with the same name",
+                            )
+                            .clicked()
+                        {
+                            action = Some(WorkspaceAction::Save);
+                        }
+                    });
+                });
+            });
+        });
+
+    action
+}
+
+fn window_position(context: &Context, title: &str) -> Option<(f32, f32)> {
+    context
+        .memory(|memory| memory.area_rect(Id::new(title)))
+        .map(|rect| (rect.min.x, rect.min.y))
+}
+
+fn settings_menu_collapsing_id() -> Id {
+    Id::new(SETTINGS_MENU_TITLE).with("collapsing")
+}
+
+fn panel_position(panel: PanelLayout) -> Option<Pos2> {
+    panel.position.map(|(x, y)| Pos2::new(x, y))
+}
+
+/// Font added to the GUI, e.g. to cover more scripts or to match the application it is embedded in
+#[derive(Clone, Debug)]
+pub struct GuiFont {
+    /// Name the font is registered under, registering a name twice keeps the first font
+    pub name: String,
+    /// Contents of a `.ttf` or `.otf` file
+    pub data: Vec<u8>,
+    pub family: egui::FontFamily,
+    /// Preferred fonts are used before the default ones, others only for glyphs the defaults miss
+    pub is_preferred: bool,
+}
+
+/// Overrides of the default GUI style, values left as `None` keep the egui defaults
+#[derive(Clone, Debug, Default)]
+pub struct GuiStyle {
+    pub fonts: Vec<GuiFont>,
+    /// Horizontal and vertical space between widgets in points
+    pub item_spacing: Option<(f32, f32)>,
+    /// Space between the border of a window and its contents in points
+    pub window_margin: Option<i8>,
+    /// Rounding of the corners of windows, menus and widgets in points
+    pub corner_radius: Option<u8>,
+}
 
-            ui.end_row();
+// Name and extensions of the files a dialog lists
+type FileFilter = (&'static str, &'static [&'static str]);
 
-            general_section(ui, gui_data);
-            ui.end_row();
+const PROJECT_FILTER: FileFilter = ("Project", &["kifs"]);
+const CAMERA_PATH_FILTER: FileFilter = ("Camera path", &["json"]);
+const SHADER_FILE_FILTER: FileFilter = ("WGSL shader", &["wgsl"]);
 
-            fractal_group_section(ui, gui_data);
-            ui.end_row();
-        });
+// Native dialog starting at the path typed in, relative paths are taken from the working directory
+#[must_use]
+fn file_dialog(path: &str, filter: Option<FileFilter>) -> rfd::FileDialog {
+    let mut dialog = rfd::FileDialog::new();
+    if let Some((name, extensions)) = filter {
+        dialog = dialog.add_filter(name, extensions);
+    }
 
-    ui.add_space(16.);
-    ui.separator();
-    ui.label(RichText::new("Tip: Hover over some items for an explanation").italics());
+    let working_directory = env::current_dir().unwrap_or_default();
+    let path = path.trim();
+    if path.is_empty() {
+        return dialog.set_directory(working_directory);
+    }
+    let path = working_directory.join(path);
+    if let Some(directory) = path.parent().filter(|directory| directory.is_dir()) {
+        dialog = dialog.set_directory(directory);
+    }
+    if let Some(file_name) = path.file_name() {
+        dialog = dialog.set_file_name(file_name.to_string_lossy());
+    }
+
+    dialog
+}
+
+// Picked paths inside the working directory are shown relative to it, like typed in paths
+#[must_use]
+fn dialog_path(path: &Path) -> String {
+    let working_directory = env::current_dir().unwrap_or_default();
+    path.strip_prefix(&working_directory)
+        .unwrap_or(path)
+        .display()
+        .to_string()
 }
 
+// Chosen in the windows while the GUI is borrowed by egui, handled after the frame's GUI update
+#[derive(Clone, Copy, Debug, Default)]
+struct GuiActions {
+    workspace: Option<WorkspaceAction>,
+    snapshot: Option<SnapshotAction>,
+    camera_path: Option<CameraPathAction>,
+    preset: Option<PresetAction>,
+    project: Option<ProjectAction>,
+    is_node_graph_compiled: bool,
+    shader_file: Option<ShaderFileAction>,
+    marker: Option<MarkerAction>,
+}
+
+#[allow(clippy::struct_excessive_bools)]
 pub(crate) struct GuiState {
     gui_data: GuiData,
+    // Detected misconfigurations, shown as long as they persist
+    warnings: Vec<ArtifactWarning>,
+    camera_data: CameraData,
+    // Ray recorded by the ray inspector, shown until its window is closed
+    ray_path: Option<RayPath>,
+    preview_texture: TextureId,
+    aov_texture: TextureId,
+    distance_slice_texture: TextureId,
+    is_aov_export_requested: bool,
+    workspaces: Vec<Workspace>,
+    // Name typed into the workspace menu for saving the current layout
+    workspace_name: String,
+    // Workspace switched to, whose panel positions are applied on the next frame
+    pending_workspace: Option<Workspace>,
+    snapshots: Vec<Snapshot>,
+    is_snapshot_requested: bool,
+    // Camera of a restored snapshot, which the render state has to take over
+    restored_camera: Option<CameraData>,
+    // Indices of the snapshots compared as A and B
+    compared_snapshots: [Option<usize>; 2],
+    compare_split: f32,
+    is_comparing: bool,
+    camera_path: CameraPath,
+    // File typed into the camera path window to save the keyframes into or load them from
+    camera_path_file: String,
+    keyframe_request: Option<KeyframeRequest>,
+    is_orbit_requested: bool,
+    // Time the camera path started playing at
+    playback_start: Option<Instant>,
+    // Time on the camera path the timeline slider is at, following the playback
+    timeline_time: f32,
+    // Time the Julia constant animation was last advanced at
+    animation_instant: Option<Instant>,
+    is_bake_requested: bool,
+    bake_status: BakeStatus,
+    // Seed typed into the seeded preset window
+    preset_seed: String,
+    // Path typed into the project window and the assets of the project
+    project_path: String,
+    asset_path: String,
+    assets: AssetManager,
+    // Index of the scene object shown in the object inspector
+    inspected_object: usize,
+    parameter_bindings: Vec<ParameterBinding>,
+    // Value or error of every binding on the last frame
+    binding_results: Vec<Result<f64, ExpressionError>>,
+    // Time expressions measure t from
+    bindings_start: Instant,
+    tiled_export_settings: TiledExportSettings,
+    tiled_export_status: TiledExportStatus,
+    is_tiled_export_requested: bool,
+    // Mesh extracted last, kept until the next extraction
+    mesh_settings: MeshSettings,
+    mesh_result: Option<Result<TriangleMesh, MeshError>>,
+    is_mesh_requested: bool,
+    // Number of voxels written by the last export
+    voxel_export_result: Option<Result<usize, VoxelExportError>>,
+    is_voxel_export_requested: bool,
+    // Source of the custom formula group and the outcome of compiling it last
+    formula_iteration: String,
+    formula_distance: String,
+    formula_result: Option<Result<(), FormulaError>>,
+    is_formula_requested: bool,
+    node_graph: NodeGraph,
+    node_graph_error: Option<NodeGraphError>,
+    // Path of the WGSL file defining user_sdf, its source until compiled and the outcome
+    shader_file_path: String,
+    shader_file_request: Option<String>,
+    shader_file_result: Option<Result<(), ShaderFileError>>,
+    markers: Vec<Marker>,
+    // Name typed in for the next dropped marker
+    marker_name: String,
+    // Panic message of the crash on the last run, until the notice is dismissed
+    crash_notice: Option<String>,
     egui_state: EguiState,
     renderer: Renderer,
-    tris: Option<Vec<ClippedPrimitive>>,
-    delta: Option<TexturesDelta>,
+}
+
+// Output of a single GUI update, uploaded before the frame is recorded and released after its
+// submission, so the GUI can never be drawn without being updated first
+#[must_use]
+pub(crate) struct GuiFrame {
+    tris: Vec<ClippedPrimitive>,
+    textures_delta: TexturesDelta,
 }
 
 impl GuiState {
@@ -205,8 +4259,16 @@ impl GuiState {
         window: &Window,
         device: &wgpu::Device,
         output_color_format: wgpu::TextureFormat,
+        preview_texture_view: &wgpu::TextureView,
+        distance_slice_texture_view: &wgpu::TextureView,
+        aov_texture_view: &wgpu::TextureView,
+        is_safe_mode: bool,
     ) -> Self {
-        let gui_data = GuiData::default();
+        let gui_data = if is_safe_mode {
+            GuiData::safe_mode()
+        } else {
+            GuiData::default()
+        };
         let egui_state = EguiState::new(
             Context::default(),
             ViewportId::ROOT,
@@ -217,22 +4279,694 @@ impl GuiState {
             Some(1024),
         );
 
-        let renderer = Renderer::new(device, output_color_format, None, 1, true);
+        let mut renderer = Renderer::new(device, output_color_format, None, 1, true);
+        let preview_texture = renderer.register_native_texture(
+            device,
+            preview_texture_view,
+            wgpu::FilterMode::Linear,
+        );
+        let aov_texture =
+            renderer.register_native_texture(device, aov_texture_view, wgpu::FilterMode::Linear);
+        let distance_slice_texture = renderer.register_native_texture(
+            device,
+            distance_slice_texture_view,
+            wgpu::FilterMode::Linear,
+        );
+
+        // Saved workspaces could be what keeps the normal startup from working
+        let workspaces = if is_safe_mode {
+            Workspace::builtin()
+        } else {
+            load_workspaces(Path::new(WORKSPACES_PATH)).unwrap_or_else(|error| {
+                log::warn!("Failed to load the saved workspaces: {error}");
+                Workspace::builtin()
+            })
+        };
+        // Plain egui look, so a broken theme cannot hide the settings needed to recover
+        if is_safe_mode {
+            egui_state.egui_ctx().set_visuals(egui::Visuals::dark());
+        }
 
         Self {
             gui_data,
+            warnings: Vec::new(),
+            camera_data: CameraData::default(),
+            ray_path: None,
+            preview_texture,
+            aov_texture,
+            distance_slice_texture,
+            is_aov_export_requested: false,
+            workspaces,
+            workspace_name: String::new(),
+            pending_workspace: None,
+            snapshots: Vec::new(),
+            is_snapshot_requested: false,
+            restored_camera: None,
+            compared_snapshots: [None; 2],
+            compare_split: 0.5,
+            is_comparing: false,
+            camera_path: CameraPath::default(),
+            camera_path_file: DEFAULT_CAMERA_PATH_FILE.to_string(),
+            keyframe_request: None,
+            is_orbit_requested: false,
+            playback_start: None,
+            timeline_time: 0.,
+            animation_instant: None,
+            is_bake_requested: false,
+            bake_status: BakeStatus::default(),
+            preset_seed: String::new(),
+            project_path: DEFAULT_PROJECT_PATH.to_string(),
+            asset_path: String::new(),
+            assets: AssetManager::default(),
+            inspected_object: 0,
+            parameter_bindings: Vec::new(),
+            binding_results: Vec::new(),
+            bindings_start: Instant::now(),
+            tiled_export_settings: TiledExportSettings::default(),
+            tiled_export_status: TiledExportStatus::default(),
+            is_tiled_export_requested: false,
+            mesh_settings: MeshSettings::default(),
+            mesh_result: None,
+            is_mesh_requested: false,
+            voxel_export_result: None,
+            is_voxel_export_requested: false,
+            formula_iteration: DEFAULT_ITERATION.to_string(),
+            formula_distance: DEFAULT_DISTANCE.to_string(),
+            formula_result: None,
+            is_formula_requested: false,
+            node_graph: NodeGraph::default(),
+            node_graph_error: None,
+            shader_file_path: String::new(),
+            shader_file_request: None,
+            shader_file_result: None,
+            markers: Vec::new(),
+            marker_name: String::new(),
+            crash_notice: take_unseen_crash(),
             egui_state,
             renderer,
-            tris: None,
-            delta: None,
         }
     }
 
+    // Takes effect from the next frame on
+    pub(crate) fn register_font(&self, font: &GuiFont) {
+        let priority = if font.is_preferred {
+            FontPriority::Highest
+        } else {
+            FontPriority::Lowest
+        };
+
+        self.egui_state.egui_ctx().add_font(FontInsert::new(
+            &font.name,
+            FontData::from_owned(font.data.clone()),
+            vec![InsertFontFamily {
+                family: font.family.clone(),
+                priority,
+            }],
+        ));
+    }
+
+    // Applied to both the dark and light theme, on top of the current style
+    pub(crate) fn apply_style(&self, gui_style: &GuiStyle) {
+        for font in &gui_style.fonts {
+            self.register_font(font);
+        }
+
+        self.egui_state.egui_ctx().all_styles_mut(|style| {
+            if let Some((x, y)) = gui_style.item_spacing {
+                style.spacing.item_spacing = Vec2::new(x, y);
+            }
+            if let Some(margin) = gui_style.window_margin {
+                style.spacing.window_margin = Margin::same(margin);
+            }
+            if let Some(radius) = gui_style.corner_radius {
+                let corner_radius = CornerRadius::same(radius);
+                let widgets = &mut style.visuals.widgets;
+                style.visuals.window_corner_radius = corner_radius;
+                style.visuals.menu_corner_radius = corner_radius;
+                for widget in [
+                    &mut widgets.noninteractive,
+                    &mut widgets.inactive,
+                    &mut widgets.hovered,
+                    &mut widgets.active,
+                    &mut widgets.open,
+                ] {
+                    widget.corner_radius = corner_radius;
+                }
+            }
+        });
+    }
+
     #[must_use]
     pub(crate) fn gui_data(&self) -> GuiData {
         self.gui_data
     }
 
+    // Replaces the settings, e.g. with ones written through the application handle
+    pub(crate) fn set_gui_data(&mut self, gui_data: &GuiData) {
+        self.gui_data = *gui_data;
+    }
+
+    pub(crate) fn update_warnings(&mut self, warnings: Vec<ArtifactWarning>) {
+        self.warnings = warnings;
+    }
+
+    // Camera the overlays are drawn for and the settings report describes
+    pub(crate) fn update_camera_data(&mut self, camera_data: CameraData) {
+        self.camera_data = camera_data;
+    }
+
+    pub(crate) fn show_ray_path(&mut self, ray_path: RayPath) {
+        self.ray_path = Some(ray_path);
+    }
+
+    // Returns whether the output layers should be exported after the frame is submitted
+    #[must_use]
+    pub(crate) fn take_aov_export_request(&mut self) -> bool {
+        std::mem::take(&mut self.is_aov_export_requested)
+    }
+
+    // Returns whether a snapshot should be captured from the frame being rendered
+    #[must_use]
+    pub(crate) fn take_snapshot_request(&mut self) -> bool {
+        std::mem::take(&mut self.is_snapshot_requested)
+    }
+
+    // Returns where the current camera should be recorded as a keyframe
+    #[must_use]
+    pub(crate) fn take_keyframe_request(&mut self) -> Option<KeyframeRequest> {
+        self.keyframe_request.take()
+    }
+
+    pub(crate) fn record_keyframe(&mut self, request: KeyframeRequest, camera_data: CameraData) {
+        match request {
+            KeyframeRequest::Add => self.camera_path.add_keyframe(camera_data),
+            KeyframeRequest::Replace(index) => {
+                self.camera_path.replace_keyframe(index, camera_data);
+            }
+        }
+    }
+
+    // Returns whether the camera path should be replaced by an orbit around the current focus
+    #[must_use]
+    pub(crate) fn take_orbit_request(&mut self) -> bool {
+        std::mem::take(&mut self.is_orbit_requested)
+    }
+
+    pub(crate) fn play_orbit(&mut self, camera_data: CameraData) {
+        self.camera_path.set_orbit(camera_data);
+        self.playback_start = Some(Instant::now());
+    }
+
+    // Camera of the playing camera path, playback stops after its last keyframe
+    #[must_use]
+    pub(crate) fn playback_camera(&mut self) -> Option<CameraData> {
+        let time = self.playback_time()?;
+        let camera_data = self.camera_path.camera_at(time);
+        if camera_data.is_none() {
+            self.playback_start = None;
+        }
+
+        camera_data
+    }
+
+    // Moves the Julia constant animation and the power sweep on by the time since the previous frame
+    pub(crate) fn advance_animation(&mut self) {
+        let now = Instant::now();
+        if let Some(previous) = self.animation_instant.replace(now) {
+            self.gui_data
+                .advance_animation((now - previous).as_secs_f32());
+        }
+    }
+
+    // Overwrites the bound settings with the values of their expressions
+    pub(crate) fn apply_bindings(&mut self) {
+        if self.parameter_bindings.is_empty() {
+            self.binding_results.clear();
+            return;
+        }
+
+        let time = self.bindings_start.elapsed().as_secs_f64();
+        match apply_bindings(&mut self.gui_data, &self.parameter_bindings, time) {
+            Ok(results) => self.binding_results = results,
+            Err(error) => log::warn!("Failed to apply the expression bindings: {error}"),
+        }
+    }
+
+    // Settings whose expression was applied on the last frame
+    #[must_use]
+    fn bound_parameters(&self) -> Vec<String> {
+        self.parameter_bindings
+            .iter()
+            .zip(&self.binding_results)
+            .filter(|(_, result)| result.is_ok())
+            .map(|(binding, _)| binding.target.trim().to_string())
+            .collect()
+    }
+
+    // Seconds since the camera path started playing
+    #[must_use]
+    pub(crate) fn playback_time(&self) -> Option<f32> {
+        Some(self.playback_start?.elapsed().as_secs_f32())
+    }
+
+    // Returns the camera path to bake with the current scene
+    #[must_use]
+    pub(crate) fn take_bake_request(&mut self) -> Option<CameraPath> {
+        std::mem::take(&mut self.is_bake_requested).then(|| self.camera_path.clone())
+    }
+
+    pub(crate) fn set_bake_status(&mut self, status: BakeStatus) {
+        self.bake_status = status;
+    }
+
+    // Returns the settings of a tiled export to start with the current scene
+    #[must_use]
+    pub(crate) fn take_tiled_export_request(&mut self) -> Option<TiledExportSettings> {
+        std::mem::take(&mut self.is_tiled_export_requested).then_some(self.tiled_export_settings)
+    }
+
+    pub(crate) fn set_tiled_export_status(&mut self, status: TiledExportStatus) {
+        self.tiled_export_status = status;
+    }
+
+    // Returns the settings of a mesh to extract from the current scene
+    #[must_use]
+    pub(crate) fn take_mesh_request(&mut self) -> Option<MeshSettings> {
+        std::mem::take(&mut self.is_mesh_requested).then_some(self.mesh_settings)
+    }
+
+    pub(crate) fn set_mesh_result(&mut self, result: Result<TriangleMesh, MeshError>) {
+        self.mesh_result = Some(result);
+    }
+
+    // Returns the grid to sample the voxels of the current scene on
+    #[must_use]
+    pub(crate) fn take_voxel_export_request(&mut self) -> Option<MeshSettings> {
+        std::mem::take(&mut self.is_voxel_export_requested).then_some(self.mesh_settings)
+    }
+
+    pub(crate) fn set_voxel_export_result(&mut self, result: Result<usize, VoxelExportError>) {
+        self.voxel_export_result = Some(result);
+    }
+
+    // Returns the transpiled formula to compile, errors of the transpiler are shown right away
+    #[must_use]
+    pub(crate) fn take_formula_request(&mut self) -> Option<String> {
+        if !std::mem::take(&mut self.is_formula_requested) {
+            return None;
+        }
+
+        match transpile(&self.formula_iteration, &self.formula_distance) {
+            Ok(source) => Some(source),
+            Err(error) => {
+                self.formula_result = Some(Err(error));
+                None
+            }
+        }
+    }
+
+    // Compiled formulas are shown right away
+    pub(crate) fn set_formula_result(&mut self, result: Result<(), FormulaError>) {
+        if result.is_ok() {
+            self.gui_data.fractal_group = FractalGroup::CustomFormula;
+        }
+        self.formula_result = Some(result);
+    }
+
+    #[must_use]
+    pub(crate) fn take_shader_file_request(&mut self) -> Option<String> {
+        self.shader_file_request.take()
+    }
+
+    // Loaded files are shown right away, like compiled formulas
+    pub(crate) fn set_shader_file_result(&mut self, result: Result<(), ShaderFileError>) {
+        if result.is_ok() {
+            self.gui_data.fractal_group = FractalGroup::ShaderFile;
+        }
+        self.shader_file_result = Some(result);
+    }
+
+    #[must_use]
+    pub(crate) fn take_restored_camera(&mut self) -> Option<CameraData> {
+        self.restored_camera.take()
+    }
+
+    // Adds a snapshot of the current settings, with the image already rendered into the texture view
+    pub(crate) fn add_snapshot(
+        &mut self,
+        device: &wgpu::Device,
+        image_view: &wgpu::TextureView,
+        camera_data: CameraData,
+        size: PhysicalSize<u32>,
+    ) {
+        if self.snapshots.len() == MAX_SNAPSHOTS {
+            let oldest = self.snapshots.remove(0);
+            self.renderer.free_texture(&oldest.image);
+            self.compared_snapshots = self
+                .compared_snapshots
+                .map(|slot| slot.and_then(|index| index.checked_sub(1)));
+        }
+
+        let image =
+            self.renderer
+                .register_native_texture(device, image_view, wgpu::FilterMode::Linear);
+        self.snapshots.push(Snapshot {
+            gui_data: self.gui_data,
+            camera_data,
+            image,
+            size,
+        });
+    }
+
+    fn handle_snapshot_action(&mut self, action: SnapshotAction) {
+        match action {
+            SnapshotAction::Capture => self.is_snapshot_requested = true,
+            SnapshotAction::Restore(index) => {
+                let snapshot = self.snapshots[index];
+                self.gui_data = snapshot.gui_data;
+                self.restored_camera = Some(snapshot.camera_data);
+            }
+        }
+    }
+
+    // Replaces the look of the scene with the one generated from the seed
+    pub(crate) fn apply_seeded_preset(&mut self, seed: &str) {
+        let preset = SeededPreset::generate(seed, &self.gui_data);
+        self.gui_data = preset.gui_data;
+        self.restored_camera = Some(preset.camera_data);
+        parse_seed(seed).clone_into(&mut self.preset_seed);
+    }
+
+    fn handle_preset_action(&mut self, action: PresetAction) {
+        match action {
+            PresetAction::Generate => self.apply_seeded_preset(&self.preset_seed.clone()),
+            PresetAction::Randomize => {
+                // Only the seed has to be random, the preset itself is reproduced from it
+                let nanos = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .subsec_nanos();
+                self.apply_seeded_preset(&format!("{nanos:08x}"));
+            }
+            PresetAction::CopyLink => self
+                .egui_state
+                .egui_ctx()
+                .copy_text(seed_link(&self.preset_seed)),
+            PresetAction::ExportReport => {
+                match export_settings_report(&self.gui_data, &self.camera_data) {
+                    Ok(path) => log::info!("Exported the settings report to {}", path.display()),
+                    Err(error) => log::warn!("Failed to export the settings report: {error}"),
+                }
+            }
+        }
+    }
+
+    fn handle_project_action(&mut self, action: ProjectAction) {
+        let path = PathBuf::from(self.project_path.trim());
+        match action {
+            ProjectAction::Save => {
+                match save_project(&path, &self.gui_data, &self.camera_data, &mut self.assets) {
+                    Ok(()) => log::info!("Saved the project to {}", path.display()),
+                    Err(error) => log::warn!("Failed to save the project: {error}"),
+                }
+            }
+            ProjectAction::Load => match load_project(&path) {
+                Ok(project) => {
+                    self.gui_data = project.gui_data;
+                    self.restored_camera = Some(project.camera_data);
+                    self.assets = project.assets;
+                    log::info!("Loaded the project from {}", path.display());
+                }
+                Err(error) => log::warn!("Failed to load the project: {error}"),
+            },
+            ProjectAction::SaveAs => {
+                if let Some(path) =
+                    file_dialog(&self.project_path, Some(PROJECT_FILTER)).save_file()
+                {
+                    self.project_path = dialog_path(&path);
+                    self.handle_project_action(ProjectAction::Save);
+                }
+            }
+            ProjectAction::Open => {
+                if let Some(path) =
+                    file_dialog(&self.project_path, Some(PROJECT_FILTER)).pick_file()
+                {
+                    self.project_path = dialog_path(&path);
+                    self.handle_project_action(ProjectAction::Load);
+                }
+            }
+            ProjectAction::AddAsset => {
+                self.assets.add_file(Path::new(self.asset_path.trim()));
+                self.asset_path.clear();
+            }
+            ProjectAction::BrowseAsset => {
+                if let Some(path) = file_dialog(&self.asset_path, None).pick_file() {
+                    self.asset_path = dialog_path(&path);
+                    self.handle_project_action(ProjectAction::AddAsset);
+                }
+            }
+            ProjectAction::EmbedAsset(index) => {
+                if let Err(error) = self.assets.embed(index) {
+                    log::warn!("Failed to embed the asset: {error}");
+                }
+            }
+            ProjectAction::RemoveAsset(index) => self.assets.remove(index),
+        }
+    }
+
+    fn handle_camera_path_action(&mut self, action: CameraPathAction) {
+        match action {
+            CameraPathAction::AddKeyframe => self.keyframe_request = Some(KeyframeRequest::Add),
+            CameraPathAction::ReplaceKeyframe(index) => {
+                self.keyframe_request = Some(KeyframeRequest::Replace(index));
+            }
+            CameraPathAction::RemoveKeyframe(index) => self.camera_path.remove_keyframe(index),
+            CameraPathAction::Seek(time) => {
+                self.timeline_time = time;
+                self.restored_camera = self.camera_path.camera_at(time);
+            }
+            CameraPathAction::Save => {
+                let path = PathBuf::from(self.camera_path_file.trim());
+                match self.camera_path.save(&path) {
+                    Ok(()) => log::info!("Saved the camera path to {}", path.display()),
+                    Err(error) => log::warn!("Failed to save the camera path: {error}"),
+                }
+            }
+            CameraPathAction::Load => {
+                let path = PathBuf::from(self.camera_path_file.trim());
+                match CameraPath::load(&path) {
+                    Ok(camera_path) => {
+                        self.camera_path = camera_path;
+                        self.playback_start = None;
+                        log::info!("Loaded the camera path from {}", path.display());
+                    }
+                    Err(error) => log::warn!("Failed to load the camera path: {error}"),
+                }
+            }
+            CameraPathAction::SaveAs => {
+                if let Some(path) =
+                    file_dialog(&self.camera_path_file, Some(CAMERA_PATH_FILTER)).save_file()
+                {
+                    self.camera_path_file = dialog_path(&path);
+                    self.handle_camera_path_action(CameraPathAction::Save);
+                }
+            }
+            CameraPathAction::Open => {
+                if let Some(path) =
+                    file_dialog(&self.camera_path_file, Some(CAMERA_PATH_FILTER)).pick_file()
+                {
+                    self.camera_path_file = dialog_path(&path);
+                    self.handle_camera_path_action(CameraPathAction::Load);
+                }
+            }
+            CameraPathAction::Clear => {
+                self.camera_path.clear();
+                self.playback_start = None;
+            }
+            CameraPathAction::Orbit => self.is_orbit_requested = true,
+            CameraPathAction::Play => self.playback_start = Some(Instant::now()),
+            CameraPathAction::Stop => self.playback_start = None,
+            CameraPathAction::Bake => self.is_bake_requested = true,
+            CameraPathAction::Export(format) => match self.camera_path.export(format) {
+                Ok(path) => log::info!("Exported the camera path to {}", path.display()),
+                Err(error) => log::warn!("Failed to export the camera path: {error}"),
+            },
+        }
+    }
+
+    // Opens and closes the panels as in the workspace, their positions are applied on the next frame
+    fn switch_workspace(&mut self, workspace: Workspace) {
+        let context = self.egui_state.egui_ctx();
+        let mut settings_menu_state =
+            CollapsingState::load_with_default_open(context, settings_menu_collapsing_id(), false);
+        settings_menu_state.set_open(workspace.settings_menu.is_open);
+        settings_menu_state.store(context);
+
+        self.gui_data.is_parameter_preview = workspace.parameter_preview.is_open;
+        if !workspace.ray_inspector.is_open {
+            self.ray_path = None;
+        }
+
+        self.pending_workspace = Some(workspace);
+    }
+
+    // Saves the current layout under the typed name, replacing any workspace with the same name
+    fn save_workspace(&mut self) {
+        let context = self.egui_state.egui_ctx();
+        let workspace = Workspace {
+            name: self.workspace_name.trim().to_string(),
+            settings_menu: PanelLayout {
+                is_open: CollapsingState::load(context, settings_menu_collapsing_id())
+                    .is_some_and(|state| state.is_open()),
+                position: window_position(context, SETTINGS_MENU_TITLE),
+            },
+            parameter_preview: PanelLayout {
+                is_open: self.gui_data.is_parameter_preview,
+                position: window_position(context, PARAMETER_PREVIEW_TITLE),
+            },
+            ray_inspector: PanelLayout {
+                is_open: self.ray_path.is_some(),
+                position: window_position(context, RAY_INSPECTOR_TITLE),
+            },
+        };
+
+        match self
+            .workspaces
+            .iter_mut()
+            .find(|saved| saved.name == workspace.name)
+        {
+            Some(saved) => *saved = workspace,
+            None => self.workspaces.push(workspace),
+        }
+        self.workspace_name.clear();
+        self.persist_workspaces();
+    }
+
+    fn persist_workspaces(&self) {
+        if let Err(error) = save_workspaces(Path::new(WORKSPACES_PATH), &self.workspaces) {
+            log::warn!("Failed to save the workspaces: {error}");
+        }
+    }
+
+    fn handle_actions(&mut self, actions: GuiActions) {
+        if let Some(action) = actions.workspace {
+            self.handle_workspace_action(action);
+        }
+        if let Some(action) = actions.snapshot {
+            self.handle_snapshot_action(action);
+        }
+        if let Some(action) = actions.camera_path {
+            self.handle_camera_path_action(action);
+        }
+        if let Some(action) = actions.preset {
+            self.handle_preset_action(action);
+        }
+        if let Some(action) = actions.project {
+            self.handle_project_action(action);
+        }
+        if actions.is_node_graph_compiled {
+            self.compile_node_graph();
+        }
+        if let Some(action) = actions.shader_file {
+            self.handle_shader_file_action(action);
+        }
+        if let Some(action) = actions.marker {
+            self.handle_marker_action(action);
+        }
+    }
+
+    fn handle_shader_file_action(&mut self, action: ShaderFileAction) {
+        match action {
+            ShaderFileAction::Load => self.load_shader_file(),
+            ShaderFileAction::Browse => {
+                if let Some(path) =
+                    file_dialog(&self.shader_file_path, Some(SHADER_FILE_FILTER)).pick_file()
+                {
+                    self.shader_file_path = dialog_path(&path);
+                    self.load_shader_file();
+                }
+            }
+        }
+    }
+
+    fn handle_marker_action(&mut self, action: MarkerAction) {
+        match action {
+            MarkerAction::Drop => {
+                if let Some(step) = self
+                    .ray_path
+                    .as_ref()
+                    .and_then(|ray_path| ray_path.steps.last())
+                {
+                    let name = match self.marker_name.trim() {
+                        "" => format!("Marker {}", self.markers.len() + 1),
+                        name => name.to_string(),
+                    };
+                    self.markers.push(Marker {
+                        name,
+                        position: step.position,
+                    });
+                    self.marker_name.clear();
+                }
+            }
+            MarkerAction::Center(index) => {
+                self.restored_camera =
+                    Some(self.camera_data.centered_on(self.markers[index].position));
+            }
+            MarkerAction::Remove(index) => {
+                self.markers.remove(index);
+            }
+        }
+    }
+
+    // File is read right away, so missing files are reported before anything is compiled, and
+    // through the project assets, so relative paths resolve against the project directory
+    fn load_shader_file(&mut self) {
+        let source = self
+            .assets
+            .load_or_add(self.shader_file_path.trim())
+            .map_err(ShaderFileError::from)
+            .and_then(|bytes| {
+                String::from_utf8(bytes).map_err(|error| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidData, error).into()
+                })
+            });
+        match source {
+            Ok(source) => self.shader_file_request = Some(source),
+            Err(error) => self.shader_file_result = Some(Err(error)),
+        }
+    }
+
+    // Graph is compiled through the formula window, which then shows the formula it became
+    fn compile_node_graph(&mut self) {
+        match self.node_graph.compile() {
+            Ok((iteration, distance)) => {
+                self.formula_iteration = iteration;
+                self.formula_distance = distance;
+                self.is_formula_requested = true;
+                self.node_graph_error = None;
+            }
+            Err(error) => self.node_graph_error = Some(error),
+        }
+    }
+
+    fn handle_workspace_action(&mut self, action: WorkspaceAction) {
+        match action {
+            WorkspaceAction::Switch(index) => self.switch_workspace(self.workspaces[index].clone()),
+            WorkspaceAction::Save => self.save_workspace(),
+            WorkspaceAction::Delete(index) => {
+                self.workspaces.remove(index);
+                self.persist_workspaces();
+            }
+        }
+    }
+
+    // Render scale while a value in the GUI is being dragged, so parameter sweeps respond immediately
+    #[must_use]
+    pub(crate) fn drag_preview_scale(&self) -> Option<f32> {
+        (self.gui_data.is_drag_preview && self.egui_state.egui_ctx().dragged_id().is_some())
+            .then_some(self.gui_data.drag_preview_scale)
+    }
+
     #[must_use]
     pub(crate) fn wants_pointer_input(&self) -> bool {
         self.egui_state.egui_ctx().wants_pointer_input()
@@ -251,6 +4985,9 @@ impl GuiState {
         self.egui_state.on_mouse_motion(delta);
     }
 
+    // Texture and buffer uploads are recorded into the frame encoder, so they are part of the same
+    // submission as the passes drawing the returned frame
+    #[allow(clippy::too_many_lines)]
     pub(crate) fn update_gui(
         &mut self,
         window: &Window,
@@ -258,7 +4995,7 @@ impl GuiState {
         queue: &wgpu::Queue,
         encoder: &mut wgpu::CommandEncoder,
         screen_descriptor: &ScreenDescriptor,
-    ) {
+    ) -> GuiFrame {
         let pixels_per_point = screen_descriptor.pixels_per_point;
         self.egui_state
             .egui_ctx()
@@ -266,25 +5003,198 @@ impl GuiState {
 
         let raw_input = self.egui_state.take_egui_input(window);
 
+        // Positions of a workspace switched to are applied for a single frame
+        let pending_workspace = self.pending_workspace.take();
+        let pending_position = |panel: fn(&Workspace) -> PanelLayout| {
+            pending_workspace
+                .as_ref()
+                .and_then(|workspace| panel_position(panel(workspace)))
+        };
+        let settings_menu_position = pending_position(|workspace| workspace.settings_menu);
+        let parameter_preview_position = pending_position(|workspace| workspace.parameter_preview);
+        let ray_inspector_position = pending_position(|workspace| workspace.ray_inspector);
+        let mut actions = GuiActions::default();
+
+        let bound_parameters = self.bound_parameters();
+
+        if let Some(time) = self.playback_time() {
+            self.timeline_time = time;
+        }
         let full_output = self.egui_state.egui_ctx().run(raw_input, |_context| {
-            EguiWindow::new("Settings Menu")
-                .resizable(false)
-                .default_open(false)
-                .show(self.egui_state.egui_ctx(), |ui| {
-                    update_ui(ui, &mut self.gui_data);
-                });
+            if let Some(camera_data) = settings_menu(
+                self.egui_state.egui_ctx(),
+                settings_menu_position,
+                &mut self.gui_data,
+                self.camera_data,
+                &bound_parameters,
+            ) {
+                self.restored_camera = Some(camera_data);
+            }
+
+            actions.workspace = workspace_menu(
+                self.egui_state.egui_ctx(),
+                &self.workspaces,
+                &mut self.workspace_name,
+            );
+
+            actions.snapshot = snapshot_strip(
+                self.egui_state.egui_ctx(),
+                &self.snapshots,
+                &mut self.is_comparing,
+            );
+            if self.is_comparing {
+                compare_window(
+                    self.egui_state.egui_ctx(),
+                    &self.snapshots,
+                    &mut self.compared_snapshots,
+                    &mut self.compare_split,
+                    &mut self.is_comparing,
+                );
+            }
+
+            actions.camera_path = camera_path_window(
+                self.egui_state.egui_ctx(),
+                &mut self.camera_path,
+                &mut self.camera_path_file,
+                &mut self.timeline_time,
+                self.playback_start.is_some(),
+                self.bake_status,
+            );
+            actions.preset =
+                seeded_preset_window(self.egui_state.egui_ctx(), &mut self.preset_seed);
+            actions.project = project_window(
+                self.egui_state.egui_ctx(),
+                &mut self.project_path,
+                &mut self.asset_path,
+                &self.assets,
+            );
+            object_inspector_window(
+                self.egui_state.egui_ctx(),
+                &mut self.gui_data,
+                &mut self.inspected_object,
+            );
+            expressions_window(
+                self.egui_state.egui_ctx(),
+                &self.gui_data,
+                &mut self.parameter_bindings,
+                &self.binding_results,
+            );
+            self.is_tiled_export_requested |= tiled_export_window(
+                self.egui_state.egui_ctx(),
+                &mut self.tiled_export_settings,
+                self.tiled_export_status,
+            );
+            match mesh_window(
+                self.egui_state.egui_ctx(),
+                &mut self.mesh_settings,
+                self.mesh_result.as_ref(),
+                self.voxel_export_result.as_ref(),
+            ) {
+                Some(MeshAction::Extract) => self.is_mesh_requested = true,
+                Some(MeshAction::ExportVoxels) => self.is_voxel_export_requested = true,
+                None => {}
+            }
+            self.is_formula_requested |= formula_window(
+                self.egui_state.egui_ctx(),
+                &mut self.formula_iteration,
+                &mut self.formula_distance,
+                self.formula_result.as_ref(),
+            );
+            actions.is_node_graph_compiled = node_graph_window(
+                self.egui_state.egui_ctx(),
+                &mut self.node_graph,
+                self.node_graph_error,
+            );
+            actions.marker = markers_window(
+                self.egui_state.egui_ctx(),
+                &self.markers,
+                &mut self.marker_name,
+                self.ray_path
+                    .as_ref()
+                    .is_some_and(|ray_path| !ray_path.steps.is_empty()),
+            );
+            actions.shader_file = shader_file_window(
+                self.egui_state.egui_ctx(),
+                &mut self.shader_file_path,
+                self.shader_file_result.as_ref(),
+            );
+            crash_notice_window(self.egui_state.egui_ctx(), &mut self.crash_notice);
+
+            info_overlays(
+                self.egui_state.egui_ctx(),
+                &self.gui_data,
+                &self.warnings,
+                self.camera_data,
+                &self.markers,
+            );
+
+            if PreviewData::from(self.gui_data).is_enabled {
+                parameter_preview_window(
+                    self.egui_state.egui_ctx(),
+                    self.preview_texture,
+                    &mut self.gui_data,
+                    parameter_preview_position,
+                );
+            }
+
+            if self.gui_data.is_aov_preview {
+                #[allow(clippy::cast_precision_loss)]
+                let aspect_ratio = screen_descriptor.size_in_pixels[0] as f32
+                    / screen_descriptor.size_in_pixels[1].max(1) as f32;
+                self.is_aov_export_requested |= output_layers_window(
+                    self.egui_state.egui_ctx(),
+                    self.aov_texture,
+                    aspect_ratio,
+                    &mut self.gui_data,
+                );
+            }
+
+            if self.gui_data.is_distance_slice {
+                distance_slice_window(
+                    self.egui_state.egui_ctx(),
+                    self.distance_slice_texture,
+                    &mut self.gui_data,
+                );
+            }
+
+            ray_inspector_window(
+                self.egui_state.egui_ctx(),
+                &mut self.ray_path,
+                ray_inspector_position,
+            );
         });
 
+        self.handle_actions(actions);
+
         // let full_output = self.egui_state.egui_ctx().end_pass();
         self.egui_state
             .handle_platform_output(window, full_output.platform_output);
 
-        let tris = self.egui_state.egui_ctx().tessellate(
+        self.upload_frame(
+            device,
+            queue,
+            encoder,
             full_output.shapes,
-            self.egui_state.egui_ctx().pixels_per_point(),
-        );
+            full_output.textures_delta,
+            screen_descriptor,
+        )
+    }
+
+    fn upload_frame(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        shapes: Vec<ClippedShape>,
+        textures_delta: TexturesDelta,
+        screen_descriptor: &ScreenDescriptor,
+    ) -> GuiFrame {
+        let tris = self
+            .egui_state
+            .egui_ctx()
+            .tessellate(shapes, self.egui_state.egui_ctx().pixels_per_point());
 
-        for (id, image_delta) in &full_output.textures_delta.set {
+        for (id, image_delta) in &textures_delta.set {
             self.renderer
                 .update_texture(device, queue, *id, image_delta);
         }
@@ -292,27 +5202,27 @@ impl GuiState {
         self.renderer
             .update_buffers(device, queue, encoder, &tris, screen_descriptor);
 
-        self.tris = Some(tris);
-        self.delta = Some(full_output.textures_delta);
+        GuiFrame {
+            tris,
+            textures_delta,
+        }
     }
 
-    /// ## Errors
-    /// - `GUINotConfiguredError` when tried to render unconfigured GUI
     pub(crate) fn render(
-        &mut self,
+        &self,
         render_pass: &mut wgpu::RenderPass<'static>,
+        frame: &GuiFrame,
         screen_descriptor: &ScreenDescriptor,
-    ) -> Result<(), GUIUnconfiguredError> {
-        self.renderer.render(
-            render_pass,
-            self.tris.as_mut().ok_or(GUIUnconfiguredError)?,
-            screen_descriptor,
-        );
+    ) {
+        self.renderer
+            .render(render_pass, &frame.tris, screen_descriptor);
+    }
 
-        for id in &self.delta.as_mut().ok_or(GUIUnconfiguredError)?.free {
-            self.renderer.free_texture(id);
+    // Needs to be called after the submission drawing the frame, textures freed earlier could
+    // still be referenced by its commands
+    pub(crate) fn finish_frame(&mut self, frame: GuiFrame) {
+        for id in frame.textures_delta.free {
+            self.renderer.free_texture(&id);
         }
-
-        Ok(())
     }
 }
@@ -0,0 +1,129 @@
+use egui_wgpu::wgpu;
+use std::sync::mpsc;
+
+use crate::data::{RayPath, RayPathHeaderData, RayPathRequestData, RayStepData};
+use crate::util::math::Vector2;
+use crate::util::storage::{StorageBuffer, StorageBufferDescriptor, StorageBufferInit as _};
+use crate::util::uniform::BufferDataDescriptor as _;
+
+// Has to be at least the maximum number of iterations, declared in the shader constants
+pub(crate) const MAX_RAY_PATH_STEPS: usize = 1024;
+const RAY_PATH_BUFFER_SIZE: usize =
+    size_of::<RayPathHeaderData>() + MAX_RAY_PATH_STEPS * size_of::<RayStepData>();
+
+#[derive(Clone, Debug)]
+pub(crate) struct RayInspectorState {
+    ray_path_buffer: StorageBuffer,
+    // Mappable copy of the ray path buffer, which the GPU cannot write to directly
+    readback_buffer: wgpu::Buffer,
+    requested_pixel: Option<Vector2<u32>>,
+    is_recording: bool,
+    ray_path: Option<RayPath>,
+}
+
+impl RayInspectorState {
+    #[must_use]
+    pub(crate) fn new(device: &wgpu::Device) -> Self {
+        let ray_path_buffer = device.create_storage_buffer(&StorageBufferDescriptor {
+            label: Some("ray_path_storage_buffer"),
+            contents: &vec![0; RAY_PATH_BUFFER_SIZE],
+            extra_usage: wgpu::BufferUsages::COPY_SRC,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("ray_path_readback_buffer"),
+            size: RAY_PATH_BUFFER_SIZE as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            ray_path_buffer,
+            readback_buffer,
+            requested_pixel: None,
+            is_recording: false,
+            ray_path: None,
+        }
+    }
+
+    // Storage buffer the fractal shaders record the inspected ray into
+    #[must_use]
+    pub(crate) fn ray_path_buffer(&self) -> &StorageBuffer {
+        &self.ray_path_buffer
+    }
+
+    // Returns the most recently recorded ray path, if it was not taken yet
+    #[must_use]
+    pub(crate) fn take_ray_path(&mut self) -> Option<RayPath> {
+        self.ray_path.take()
+    }
+
+    // Records the ray through the given pixel during the next raymarch pass
+    pub(crate) fn inspect_pixel(&mut self, pixel: Vector2<u32>) {
+        self.requested_pixel = Some(pixel);
+    }
+
+    // Needs to be called before the raymarch pass, returns whether a ray is recorded this frame
+    pub(crate) fn prepare(&mut self, queue: &wgpu::Queue) -> bool {
+        let was_recording = self.is_recording;
+        self.is_recording = self.requested_pixel.is_some();
+
+        if self.is_recording || was_recording {
+            let request = RayPathRequestData {
+                pixel: self.requested_pixel.take().unwrap_or_default(),
+                is_recording: self.is_recording,
+            };
+            self.ray_path_buffer
+                .update_buffer(queue, 0, &[request.into_buffer_data()]);
+        }
+
+        self.is_recording
+    }
+
+    // Needs to be called after the raymarch pass
+    pub(crate) fn copy_ray_path(&self, encoder: &mut wgpu::CommandEncoder) {
+        if !self.is_recording {
+            return;
+        }
+
+        encoder.copy_buffer_to_buffer(
+            &self.ray_path_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            RAY_PATH_BUFFER_SIZE as wgpu::BufferAddress,
+        );
+    }
+
+    // Needs to be called after submitting the frame, blocks until the recorded ray is available
+    pub(crate) fn read_ray_path(&mut self, device: &wgpu::Device) {
+        if !self.is_recording {
+            return;
+        }
+
+        let slice = self.readback_buffer.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            // Receiving end is only dropped after the result was received
+            sender.send(result).ok();
+        });
+
+        if let Err(error) = device.poll(wgpu::PollType::Wait) {
+            log::warn!("Failed to wait for the ray path: {error}");
+            return;
+        }
+        match receiver.recv() {
+            Ok(Ok(())) => {}
+            Ok(Err(error)) => {
+                log::warn!("Failed to read back the ray path: {error}");
+                return;
+            }
+            Err(_) => {
+                log::warn!("Ray path buffer was never mapped");
+                return;
+            }
+        }
+
+        self.ray_path = Some(RayPath::from_bytes(&slice.get_mapped_range()));
+        self.readback_buffer.unmap();
+    }
+}
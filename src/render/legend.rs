@@ -0,0 +1,240 @@
+use egui::{Align2, Color32, Context, FontId, Frame, Id, Pos2, Rect, Rgba, Sense, Stroke, Vec2};
+
+use crate::data::{
+    GuiData,
+    options::{ColoringMode, DebugView},
+};
+
+// Has to match MAX_DISPLAYED_CORRECTIONS in entry.wgsl
+const MAX_DISPLAYED_CORRECTIONS: f32 = 8.;
+// Has to match MAX_DISPLAYED_DISTANCE_OCTAVES in entry.wgsl
+const MAX_DISPLAYED_DISTANCE_OCTAVES: f32 = 16.;
+// Has to match BAILOUT_COLORS in entry.wgsl
+const BAILOUT_CATEGORIES: [(&str, [f32; 3]); 3] = [
+    ("Max iterations", [1., 0., 0.]),
+    ("Max distance", [0., 0., 1.]),
+    ("Hit", [0., 1., 0.]),
+];
+
+// Number of flat segments the color scale is drawn with
+const LEGEND_SEGMENTS: usize = 64;
+const LEGEND_TICKS: usize = 5;
+// Size of the color scale in points
+const LEGEND_SIZE: Vec2 = Vec2::new(240., 14.);
+const TICK_LENGTH: f32 = 4.;
+
+// Blue to red color ramp for values in [0, 1], same as false_color in entry.wgsl
+#[must_use]
+fn false_color(value: f32) -> Rgba {
+    let channel = |center: f32| (1.5 - (4. * value - center).abs()).clamp(0., 1.);
+    Rgba::from_rgb(channel(3.), channel(2.), channel(1.))
+}
+
+#[must_use]
+fn format_value(value: f32) -> String {
+    if value != 0. && !(1e-2..1e4).contains(&value.abs()) {
+        format!("{value:.1e}")
+    } else if value.fract() == 0. {
+        format!("{value}")
+    } else {
+        format!("{value:.2}")
+    }
+}
+
+// Color scale of the current coloring or debug view, from its minimum to its maximum value
+#[derive(Clone, Debug)]
+pub(crate) struct Legend {
+    pub(crate) title: String,
+    pub(crate) colors: Vec<Color32>,
+    // Positions along the scale in [0, 1] together with the values found there
+    pub(crate) ticks: Vec<(f32, String)>,
+}
+
+impl Legend {
+    #[must_use]
+    fn gradient(title: &str, color: impl Fn(f32) -> Rgba, value: impl Fn(f32) -> String) -> Self {
+        #[allow(clippy::cast_precision_loss)]
+        let position = |index: usize, count: usize| index as f32 / (count - 1) as f32;
+
+        Self {
+            title: title.to_string(),
+            colors: (0..LEGEND_SEGMENTS)
+                .map(|index| color(position(index, LEGEND_SEGMENTS)).into())
+                .collect(),
+            ticks: (0..LEGEND_TICKS)
+                .map(|index| {
+                    let position = position(index, LEGEND_TICKS);
+                    (position, value(position))
+                })
+                .collect(),
+        }
+    }
+
+    #[must_use]
+    fn categories(title: &str, categories: &[(&str, [f32; 3])]) -> Self {
+        #[allow(clippy::cast_precision_loss)]
+        let count = categories.len() as f32;
+
+        Self {
+            title: title.to_string(),
+            colors: categories
+                .iter()
+                .map(|(_, [r, g, b])| Rgba::from_rgb(*r, *g, *b).into())
+                .collect(),
+            ticks: (0u16..)
+                .zip(categories)
+                .map(|(index, (name, _))| ((f32::from(index) + 0.5) / count, (*name).to_string()))
+                .collect(),
+        }
+    }
+
+    // Debug views take precedence over the heatmap, as they are drawn on top of it
+    #[must_use]
+    pub(crate) fn new(gui_data: &GuiData) -> Option<Self> {
+        #[allow(clippy::cast_precision_loss)]
+        let max_iterations = gui_data.max_iterations as f32;
+
+        match gui_data.debug_view {
+            DebugView::StepCount => Some(Self::gradient("Step count", false_color, |position| {
+                format_value((position * max_iterations).round())
+            })),
+            DebugView::Overstep => Some(Self::gradient(
+                "Overstep corrections",
+                false_color,
+                |position| {
+                    let corrections = format_value(position * MAX_DISPLAYED_CORRECTIONS);
+                    if position < 1. {
+                        corrections
+                    } else {
+                        format!("{corrections}+")
+                    }
+                },
+            )),
+            DebugView::Bailout => Some(Self::categories("Bailout reason", &BAILOUT_CATEGORIES)),
+            DebugView::FinalDistance => {
+                Some(Self::gradient("Final distance", false_color, |position| {
+                    let octaves = position * MAX_DISPLAYED_DISTANCE_OCTAVES;
+                    format_value(gui_data.epsilon * octaves.exp2())
+                }))
+            }
+            DebugView::RayLength => Some(Self::gradient("Ray length", false_color, |position| {
+                format_value(position * gui_data.max_distance)
+            })),
+            DebugView::BoundingTests => {
+                Some(Self::gradient("Bounding tests", false_color, |position| {
+                    format!("{:.0}%", 100. * position)
+                }))
+            }
+            DebugView::Off if gui_data.coloring_mode == ColoringMode::Heatmap => {
+                let fractal_color = Rgba::from(Color32::from_rgb(
+                    gui_data.fractal_color[0],
+                    gui_data.fractal_color[1],
+                    gui_data.fractal_color[2],
+                ));
+                // Inverse of the ramp in entry.wgsl, which is clamped to [0, 1] before coloring
+                let steps = |position: f32| {
+                    let ratio = (position - gui_data.heatmap_offset) / gui_data.heatmap_scale;
+                    format_value((ratio * max_iterations).clamp(0., max_iterations).round())
+                };
+                Some(Self::gradient(
+                    "Step count",
+                    |position| fractal_color * position,
+                    steps,
+                ))
+            }
+            _ => None,
+        }
+    }
+}
+
+// Color scale in the bottom right corner, so images of debug views can be read on their own
+pub(crate) fn legend_overlay(context: &Context, legend: &Legend) {
+    egui::Area::new(Id::new("legend"))
+        .anchor(Align2::RIGHT_BOTTOM, [-8., -8.])
+        .interactable(false)
+        .show(context, |ui| {
+            Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(&legend.title);
+
+                let label_height = ui.text_style_height(&egui::TextStyle::Small);
+                let (response, painter) = ui.allocate_painter(
+                    LEGEND_SIZE + Vec2::new(0., TICK_LENGTH + label_height),
+                    Sense::hover(),
+                );
+                let scale_rect = Rect::from_min_size(response.rect.min, LEGEND_SIZE);
+
+                #[allow(clippy::cast_precision_loss)]
+                let segment_width = scale_rect.width() / legend.colors.len() as f32;
+                for (index, color) in (0u16..).zip(&legend.colors) {
+                    let left = scale_rect.left() + f32::from(index) * segment_width;
+                    painter.rect_filled(
+                        Rect::from_x_y_ranges(left..=left + segment_width, scale_rect.y_range()),
+                        0.,
+                        *color,
+                    );
+                }
+
+                let stroke = Stroke::new(1., ui.visuals().text_color());
+                for (position, label) in &legend.ticks {
+                    let x = scale_rect.left() + position * scale_rect.width();
+                    painter.line_segment(
+                        [
+                            Pos2::new(x, scale_rect.bottom()),
+                            Pos2::new(x, scale_rect.bottom() + TICK_LENGTH),
+                        ],
+                        stroke,
+                    );
+
+                    // Outermost labels are aligned to the ends, so they stay within the frame
+                    let anchor = if *position <= 0. {
+                        Align2::LEFT_TOP
+                    } else if *position >= 1. {
+                        Align2::RIGHT_TOP
+                    } else {
+                        Align2::CENTER_TOP
+                    };
+                    painter.text(
+                        Pos2::new(x, scale_rect.bottom() + TICK_LENGTH),
+                        anchor,
+                        label,
+                        FontId::proportional(label_height),
+                        ui.visuals().text_color(),
+                    );
+                }
+            });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heatmap_legend() {
+        let gui_data = GuiData {
+            coloring_mode: ColoringMode::Heatmap,
+            max_iterations: 200,
+            heatmap_scale: 2.,
+            heatmap_offset: 0.,
+            ..Default::default()
+        };
+        let legend = Legend::new(&gui_data).unwrap();
+
+        // Ramp saturates halfway through the iterations with a scale of 2
+        assert_eq!(legend.ticks.first().unwrap().1, "0");
+        assert_eq!(legend.ticks.last().unwrap().1, "100");
+
+        let gui_data = GuiData {
+            debug_view: DebugView::Bailout,
+            ..gui_data
+        };
+        assert_eq!(Legend::new(&gui_data).unwrap().colors.len(), 3);
+
+        let gui_data = GuiData {
+            coloring_mode: ColoringMode::default(),
+            debug_view: DebugView::Off,
+            ..gui_data
+        };
+        assert!(Legend::new(&gui_data).is_none());
+    }
+}
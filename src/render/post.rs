@@ -0,0 +1,270 @@
+use egui_wgpu::wgpu;
+
+use crate::data::options::AovLayer;
+use crate::data::{GuiData, PostData};
+use crate::render::aov::AovState;
+use crate::render::shader_constants;
+use crate::util::buffer::{
+    ResourceGroup, ResourceGroupDescriptor, ResourceGroupInit as _, ResourceGroupLayoutEntry,
+};
+use crate::util::shader::{
+    WGSLShaderModuleDescriptor, WGSLShaderModuleInit as _, WGSLShaderSource,
+};
+use crate::util::storage::StorageBuffer;
+use crate::util::uniform::{UniformBuffer, UniformBufferDescriptor, UniformBufferInit as _};
+
+#[derive(Clone, Debug)]
+pub(crate) struct PostState {
+    post_data: PostData,
+    // Whether the background is left transparent for the compositor
+    is_transparent: bool,
+    // Whether the output format applies the sRGB encoding, which the resolve has to account for
+    is_srgb_target: bool,
+    post_uniform_buffer: UniformBuffer,
+    exposure_buffer: StorageBuffer,
+    render_sampler: wgpu::Sampler,
+    resource_group: ResourceGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl PostState {
+    #[must_use]
+    fn resources<'a>(
+        render_texture_view: &'a wgpu::TextureView,
+        depth_texture_view: &'a wgpu::TextureView,
+        normal_texture_view: &'a wgpu::TextureView,
+        post_uniform_buffer: &'a UniformBuffer,
+        exposure_buffer: &'a StorageBuffer,
+        render_sampler: &'a wgpu::Sampler,
+    ) -> [wgpu::BindingResource<'a>; 6] {
+        [
+            wgpu::BindingResource::TextureView(render_texture_view),
+            post_uniform_buffer.as_entire_binding(),
+            exposure_buffer.as_entire_binding(),
+            wgpu::BindingResource::TextureView(depth_texture_view),
+            wgpu::BindingResource::TextureView(normal_texture_view),
+            wgpu::BindingResource::Sampler(render_sampler),
+        ]
+    }
+
+    // Filtering itself is chosen in the shader, nearest and bicubic reads load the texels directly
+    #[must_use]
+    fn create_render_sampler(device: &wgpu::Device) -> wgpu::Sampler {
+        device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("render_sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        })
+    }
+
+    #[must_use]
+    fn create_post_group(
+        device: &wgpu::Device,
+        resources: &[wgpu::BindingResource],
+    ) -> ResourceGroup {
+        // Output layers are 32 bit float textures, which are not filterable
+        let layer_entry = ResourceGroupLayoutEntry {
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+
+        device.create_resource_group(&ResourceGroupDescriptor {
+            label: Some("post"),
+            resources,
+            entries: &[
+                ResourceGroupLayoutEntry {
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                ResourceGroupLayoutEntry {
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                ResourceGroupLayoutEntry {
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                layer_entry,
+                layer_entry,
+                ResourceGroupLayoutEntry {
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    #[must_use]
+    fn create_post_pipeline(
+        device: &wgpu::Device,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        let post_shader = device.create_wgsl_shader_module(WGSLShaderModuleDescriptor {
+            label: Some("post_shader"),
+            main: shader_source!("post.wgsl"),
+            dependencies: &[
+                shader_constants(),
+                shader_source!("dependencies/random.wgsl"),
+                shader_source!("dependencies/vertex.wgsl"),
+            ],
+        });
+
+        let post_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("post_pipeline_layout"),
+            bind_group_layouts,
+            push_constant_ranges: &[],
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("post_render_pipeline"),
+            layout: Some(&post_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &post_shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &post_shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    #[must_use]
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        render_texture_view: &wgpu::TextureView,
+        exposure_buffer: &StorageBuffer,
+        aov_state: &AovState,
+        is_transparent: bool,
+    ) -> Self {
+        let post_data = GuiData::default().into();
+        let post_uniform_buffer = device.create_uniform_buffer(&UniformBufferDescriptor {
+            label: Some("post_uniform_buffer"),
+            data_descriptor: post_data,
+        });
+
+        let render_sampler = Self::create_render_sampler(device);
+
+        let resource_group = Self::create_post_group(
+            device,
+            &Self::resources(
+                render_texture_view,
+                aov_state.layer_texture_view(AovLayer::Depth),
+                aov_state.layer_texture_view(AovLayer::Normal),
+                &post_uniform_buffer,
+                exposure_buffer,
+                &render_sampler,
+            ),
+        );
+        let pipeline =
+            Self::create_post_pipeline(device, &[resource_group.bind_group_layout()], format);
+
+        Self {
+            post_data,
+            is_transparent,
+            is_srgb_target: format.is_srgb(),
+            post_uniform_buffer,
+            exposure_buffer: exposure_buffer.clone(),
+            render_sampler,
+            resource_group,
+            pipeline,
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn post_data(&self) -> PostData {
+        self.post_data
+    }
+
+    // Needs to be called whenever the render texture or output layers are recreated, e.g. on resize
+    pub(crate) fn update_render_texture(
+        &mut self,
+        device: &wgpu::Device,
+        render_texture_view: &wgpu::TextureView,
+        aov_state: &AovState,
+    ) {
+        device.rebind_resource_group(
+            &mut self.resource_group,
+            Some("post"),
+            &Self::resources(
+                render_texture_view,
+                aov_state.layer_texture_view(AovLayer::Depth),
+                aov_state.layer_texture_view(AovLayer::Normal),
+                &self.post_uniform_buffer,
+                &self.exposure_buffer,
+                &self.render_sampler,
+            ),
+        );
+    }
+
+    pub(crate) fn update_post_data(&mut self, queue: &wgpu::Queue, new_post_data: PostData) {
+        self.post_data = PostData {
+            frame: self.post_data.frame.wrapping_add(1),
+            is_transparent: self.is_transparent,
+            is_srgb_target: self.is_srgb_target,
+            ..new_post_data
+        };
+        self.post_uniform_buffer
+            .update_buffer(queue, self.post_data);
+    }
+
+    pub(crate) fn render(&self, render_pass: &mut wgpu::RenderPass) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, self.resource_group.bind_group(), &[]);
+
+        render_pass.draw(0..3, 0..2);
+    }
+}
@@ -0,0 +1,276 @@
+use egui_wgpu::wgpu;
+
+use crate::data::scene::FractalGroup;
+use crate::data::{CameraData, GuiData, OptionsData, PreviewData};
+use crate::render::graphics::GraphicState;
+use crate::render::shader_constants;
+use crate::util::buffer::{
+    ResourceGroup, ResourceGroupDescriptor, ResourceGroupInit as _, ResourceGroupLayoutEntry,
+};
+use crate::util::shader::{
+    WGSLShaderModuleDescriptor, WGSLShaderModuleInit as _, WGSLShaderSource,
+};
+use crate::util::uniform::{
+    BufferDataDescriptor as _, UniformBuffer, UniformBufferDescriptor, UniformBufferInit as _,
+};
+
+// Side length of the preview texture in pixels, split evenly between the tiles
+const PREVIEW_TEXTURE_SIZE: u32 = 512;
+const PREVIEW_TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+// Side length of the workgroups of the preview shader, declared in the shader constants
+pub(crate) const PREVIEW_WORKGROUP_SIZE: u32 = 8;
+
+#[derive(Clone, Debug)]
+pub(crate) struct PreviewState {
+    preview_data: PreviewData,
+    preview_uniform_buffer: UniformBuffer,
+    preview_texture_view: wgpu::TextureView,
+    resource_group: ResourceGroup,
+    julia_pipeline: wgpu::ComputePipeline,
+    generalized_julia_pipeline: wgpu::ComputePipeline,
+    // Scene the preview was last requested for, it is only rendered again once something changes
+    options_data: OptionsData,
+    camera_data: CameraData,
+    is_outdated: bool,
+}
+
+impl PreviewState {
+    #[must_use]
+    fn create_preview_texture_view(device: &wgpu::Device) -> wgpu::TextureView {
+        let preview_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("preview_texture"),
+            size: wgpu::Extent3d {
+                width: PREVIEW_TEXTURE_SIZE,
+                height: PREVIEW_TEXTURE_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: PREVIEW_TEXTURE_FORMAT,
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        preview_texture.create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    #[must_use]
+    fn create_preview_group(
+        device: &wgpu::Device,
+        resources: &[wgpu::BindingResource],
+    ) -> ResourceGroup {
+        device.create_resource_group(&ResourceGroupDescriptor {
+            label: Some("preview"),
+            resources,
+            entries: &[
+                ResourceGroupLayoutEntry {
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                ResourceGroupLayoutEntry {
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::StorageTexture {
+                        access: wgpu::StorageTextureAccess::WriteOnly,
+                        format: PREVIEW_TEXTURE_FORMAT,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    #[must_use]
+    fn create_compute_pipeline(
+        device: &wgpu::Device,
+        pipeline_layout: &wgpu::PipelineLayout,
+        shader: &wgpu::ShaderModule,
+        label: wgpu::Label,
+    ) -> wgpu::ComputePipeline {
+        device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label,
+            layout: Some(pipeline_layout),
+            module: shader,
+            entry_point: Some("preview_main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        })
+    }
+
+    #[must_use]
+    fn create_compute_pipelines(
+        device: &wgpu::Device,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+    ) -> (wgpu::ComputePipeline, wgpu::ComputePipeline) {
+        let preview_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("preview_pipeline_layout"),
+                bind_group_layouts,
+                push_constant_ranges: &[],
+            });
+
+        let julia_shader = device.create_wgsl_shader_module(WGSLShaderModuleDescriptor {
+            label: Some("julia_preview_shader"),
+            main: shader_source!("julia.wgsl"),
+            dependencies: &[
+                shader_constants(),
+                shader_source!("dependencies/bindings.wgsl"),
+                shader_source!("dependencies/bounds.wgsl"),
+                shader_source!("dependencies/csg.wgsl"),
+                shader_source!("dependencies/entry.wgsl"),
+                shader_source!("dependencies/julia_formulas.wgsl"),
+                shader_source!("dependencies/preview.wgsl"),
+                shader_source!("dependencies/quaternions.wgsl"),
+                shader_source!("dependencies/random.wgsl"),
+                shader_source!("dependencies/transforms.wgsl"),
+                shader_source!("dependencies/vertex.wgsl"),
+            ],
+        });
+        let julia_pipeline = Self::create_compute_pipeline(
+            device,
+            &preview_pipeline_layout,
+            &julia_shader,
+            Some("julia_preview_pipeline"),
+        );
+
+        let generalized_julia_shader =
+            device.create_wgsl_shader_module(WGSLShaderModuleDescriptor {
+                label: Some("generalized_julia_preview_shader"),
+                main: shader_source!("gen_julia.wgsl"),
+                dependencies: &[
+                    shader_constants(),
+                    shader_source!("dependencies/bindings.wgsl"),
+                    shader_source!("dependencies/bounds.wgsl"),
+                    shader_source!("dependencies/csg.wgsl"),
+                    shader_source!("dependencies/entry.wgsl"),
+                    shader_source!("dependencies/julia_formulas.wgsl"),
+                    shader_source!("dependencies/preview.wgsl"),
+                    shader_source!("dependencies/quaternions.wgsl"),
+                    shader_source!("dependencies/random.wgsl"),
+                    shader_source!("dependencies/transforms.wgsl"),
+                    shader_source!("dependencies/vertex.wgsl"),
+                ],
+            });
+        let generalized_julia_pipeline = Self::create_compute_pipeline(
+            device,
+            &preview_pipeline_layout,
+            &generalized_julia_shader,
+            Some("generalized_julia_preview_pipeline"),
+        );
+
+        (julia_pipeline, generalized_julia_pipeline)
+    }
+
+    #[must_use]
+    pub(crate) fn new(device: &wgpu::Device, graphic_state: &GraphicState) -> Self {
+        let preview_data = GuiData::default().into();
+        let preview_uniform_buffer = device.create_uniform_buffer(&UniformBufferDescriptor {
+            label: Some("preview_uniform_buffer"),
+            data_descriptor: preview_data,
+        });
+        let preview_texture_view = Self::create_preview_texture_view(device);
+
+        let resource_group = Self::create_preview_group(
+            device,
+            &[
+                preview_uniform_buffer.as_entire_binding(),
+                wgpu::BindingResource::TextureView(&preview_texture_view),
+            ],
+        );
+        let (julia_pipeline, generalized_julia_pipeline) = Self::create_compute_pipelines(
+            device,
+            &[
+                graphic_state.uniform_group().bind_group_layout(),
+                graphic_state.storage_group().bind_group_layout(),
+                resource_group.bind_group_layout(),
+            ],
+        );
+
+        Self {
+            preview_data,
+            preview_uniform_buffer,
+            preview_texture_view,
+            resource_group,
+            julia_pipeline,
+            generalized_julia_pipeline,
+            options_data: graphic_state.options_data(),
+            camera_data: graphic_state.camera_data(),
+            is_outdated: true,
+        }
+    }
+
+    // Texture the tiles are rendered into, shown by the GUI
+    #[must_use]
+    pub(crate) fn preview_texture_view(&self) -> &wgpu::TextureView {
+        &self.preview_texture_view
+    }
+
+    pub(crate) fn update_preview_data(
+        &mut self,
+        queue: &wgpu::Queue,
+        new_preview_data: PreviewData,
+        options_data: &OptionsData,
+        camera_data: CameraData,
+    ) {
+        let is_changed = bytemuck::bytes_of(&self.preview_data.into_buffer_data())
+            != bytemuck::bytes_of(&new_preview_data.into_buffer_data())
+            || bytemuck::bytes_of(&self.options_data.into_buffer_data())
+                != bytemuck::bytes_of(&options_data.into_buffer_data())
+            || bytemuck::bytes_of(&self.camera_data.into_buffer_data())
+                != bytemuck::bytes_of(&camera_data.into_buffer_data());
+        // Reopening the window shows the current scene
+        self.is_outdated |= is_changed || !self.preview_data.is_enabled;
+
+        self.preview_data = new_preview_data;
+        self.options_data = *options_data;
+        self.camera_data = camera_data;
+        self.preview_uniform_buffer
+            .update_buffer(queue, self.preview_data);
+    }
+
+    // Needs to be called after the raymarch pass copied its statistics, as marching the tiles adds to them
+    pub(crate) fn compute(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        graphic_state: &GraphicState,
+    ) {
+        if !self.preview_data.is_enabled || !self.is_outdated {
+            return;
+        }
+
+        let pipeline = match self.options_data.fractal_group {
+            FractalGroup::KaleidoscopicIFS
+            | FractalGroup::KleinianLimitSet
+            | FractalGroup::Hybrid
+            | FractalGroup::AmazingSurf
+            | FractalGroup::CustomFormula
+            | FractalGroup::ShaderFile => {
+                return;
+            }
+            FractalGroup::JuliaSet => &self.julia_pipeline,
+            FractalGroup::GeneralizedJuliaSet => &self.generalized_julia_pipeline,
+        };
+
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("preview_pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(pipeline);
+        compute_pass.set_bind_group(0, graphic_state.uniform_group().bind_group(), &[]);
+        compute_pass.set_bind_group(1, graphic_state.storage_group().bind_group(), &[]);
+        compute_pass.set_bind_group(2, self.resource_group.bind_group(), &[]);
+        compute_pass.dispatch_workgroups(
+            PREVIEW_TEXTURE_SIZE.div_ceil(PREVIEW_WORKGROUP_SIZE),
+            PREVIEW_TEXTURE_SIZE.div_ceil(PREVIEW_WORKGROUP_SIZE),
+            1,
+        );
+
+        self.is_outdated = false;
+    }
+}
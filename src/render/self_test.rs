@@ -0,0 +1,262 @@
+use egui_wgpu::wgpu;
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+use strum::IntoEnumIterator as _;
+use winit::dpi::PhysicalSize;
+
+use crate::data::{
+    CameraData, GuiData,
+    scene::FractalGroup,
+    tiles::{ExportJob, TILE_SIZE, TiledExportSettings},
+};
+use crate::error::RenderStateError;
+use crate::network::NetworkNode;
+use crate::render::{
+    RENDER_TEXTURE_FORMAT, RenderState, RenderStateOptions, aov::AovState, axes::AxesState,
+    exposure::ExposureState, graphics::GraphicState, inspector::RayInspectorState, post::PostState,
+    preview::PreviewState, statistics::StatisticsState, taa::TaaState, tiled::TiledExportState,
+};
+
+// There is no surface to ask for its format, so the pipelines drawing onto it use the most common one
+const SELF_TEST_VIEW_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8UnormSrgb;
+const SELF_TEST_SIZE: PhysicalSize<u32> = PhysicalSize::new(TILE_SIZE, TILE_SIZE);
+
+#[derive(Clone, Debug)]
+struct FrameReport {
+    fractal_group: FractalGroup,
+    render_time: Duration,
+    // Validation error, or the reason the rendered image can not be right
+    error: Option<String>,
+}
+
+// Capabilities of the machine and the outcome of every check, printed for bug reports
+#[derive(Clone, Debug)]
+pub struct SelfTestReport {
+    adapters: Vec<wgpu::AdapterInfo>,
+    adapter: wgpu::AdapterInfo,
+    limits: wgpu::Limits,
+    downlevel_capabilities: wgpu::DownlevelCapabilities,
+    pipeline_time: Duration,
+    pipeline_error: Option<String>,
+    frames: Vec<FrameReport>,
+}
+
+impl SelfTestReport {
+    #[must_use]
+    pub fn is_passed(&self) -> bool {
+        self.pipeline_error.is_none() && self.frames.iter().all(|frame| frame.error.is_none())
+    }
+}
+
+fn write_adapter(f: &mut fmt::Formatter<'_>, adapter: &wgpu::AdapterInfo) -> fmt::Result {
+    write!(
+        f,
+        "{} ({:?}, {:?}, driver {} {})",
+        adapter.name, adapter.backend, adapter.device_type, adapter.driver, adapter.driver_info
+    )
+}
+
+// Time taken by a check, or why it failed
+fn write_outcome(
+    f: &mut fmt::Formatter<'_>,
+    duration: Duration,
+    error: Option<&String>,
+) -> fmt::Result {
+    if let Some(error) = error {
+        writeln!(f, "FAILED, {error}")
+    } else {
+        writeln!(f, "{:.1} ms", duration.as_secs_f64() * 1000.)
+    }
+}
+
+impl fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Adapters:")?;
+        for adapter in &self.adapters {
+            write!(f, "  - ")?;
+            write_adapter(f, adapter)?;
+            writeln!(f)?;
+        }
+
+        write!(f, "Selected adapter: ")?;
+        write_adapter(f, &self.adapter)?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "  Max texture size: {}",
+            self.limits.max_texture_dimension_2d
+        )?;
+        writeln!(
+            f,
+            "  Max storage buffer size: {}",
+            self.limits.max_storage_buffer_binding_size
+        )?;
+        writeln!(
+            f,
+            "  WebGPU compliant: {}",
+            if self.downlevel_capabilities.is_webgpu_compliant() {
+                "yes"
+            } else {
+                "no"
+            }
+        )?;
+
+        write!(f, "Shaders and pipelines: ")?;
+        write_outcome(f, self.pipeline_time, self.pipeline_error.as_ref())?;
+
+        writeln!(f, "Frames ({TILE_SIZE}x{TILE_SIZE}):")?;
+        for frame in &self.frames {
+            write!(f, "  - {}: ", frame.fractal_group)?;
+            write_outcome(f, frame.render_time, frame.error.as_ref())?;
+        }
+
+        write!(
+            f,
+            "Result: {}",
+            if self.is_passed() { "passed" } else { "failed" }
+        )
+    }
+}
+
+// Renders the default scene of the fractal group into a single tile
+async fn render_frame(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    graphic_state: &mut GraphicState,
+    tiled_export_state: &TiledExportState,
+    fractal_group: FractalGroup,
+) -> FrameReport {
+    let gui_data = GuiData {
+        fractal_group,
+        ..GuiData::default()
+    };
+    let job = ExportJob::new(
+        &gui_data,
+        CameraData::default(),
+        TiledExportSettings {
+            width: TILE_SIZE,
+            height: TILE_SIZE,
+            samples: 1,
+        },
+    );
+    graphic_state.update_camera(queue, job.camera_data);
+    graphic_state.update_options(queue, &gui_data.into());
+    graphic_state.update_lighting(queue, gui_data.into());
+    graphic_state.update_scene(queue, &gui_data.into());
+
+    let start = Instant::now();
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let result = tiled_export_state.render_tile(device, queue, graphic_state, &job, 0);
+    let validation_error = device.pop_error_scope().await;
+    let render_time = start.elapsed();
+
+    let error = match (validation_error, result) {
+        (Some(error), _) => Some(error.to_string()),
+        (None, Err(error)) => Some(error.to_string()),
+        (None, Ok(values)) if values.iter().any(|value| !value.is_finite()) => {
+            Some("image contains values which are not finite".to_owned())
+        }
+        (None, Ok(_)) => None,
+    };
+
+    FrameReport {
+        fractal_group,
+        render_time,
+        error,
+    }
+}
+
+async fn self_test(options: &RenderStateOptions) -> Result<SelfTestReport, RenderStateError> {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::all(),
+        ..wgpu::InstanceDescriptor::default()
+    });
+    let adapters = instance
+        .enumerate_adapters(wgpu::Backends::all())
+        .iter()
+        .map(wgpu::Adapter::get_info)
+        .collect();
+
+    let adapter = RenderState::create_adapter(&instance, None, options).await?;
+    let (device, queue) = RenderState::create_device_and_queue(&adapter, options).await?;
+
+    // Same states as the window creates, so every shader and pipeline is compiled, those of the
+    // fractal groups are compiled on demand when their frame is rendered
+    let pipeline_start = Instant::now();
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let render_texture =
+        RenderState::create_render_texture(&device, RENDER_TEXTURE_FORMAT, SELF_TEST_SIZE);
+    let render_texture_view = render_texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let ray_inspector_state = RayInspectorState::new(&device);
+    let statistics_state = StatisticsState::new(&device);
+    let mut graphic_state = GraphicState::new(
+        SELF_TEST_SIZE,
+        &device,
+        RENDER_TEXTURE_FORMAT,
+        ray_inspector_state.ray_path_buffer(),
+        statistics_state.statistics_buffer(),
+    );
+    let aov_state = AovState::new(&device, SELF_TEST_SIZE);
+    let exposure_state = ExposureState::new(&device, &render_texture_view);
+    let _post_state = PostState::new(
+        &device,
+        SELF_TEST_VIEW_FORMAT,
+        &render_texture_view,
+        exposure_state.exposure_buffer(),
+        &aov_state,
+        false,
+    );
+    let _taa_state = TaaState::new(
+        &device,
+        RENDER_TEXTURE_FORMAT,
+        SELF_TEST_SIZE,
+        &render_texture_view,
+        &graphic_state,
+        &aov_state,
+    );
+    let _axes_state = AxesState::new(&device, SELF_TEST_VIEW_FORMAT, &graphic_state, &aov_state);
+    let _preview_state = PreviewState::new(&device, &graphic_state);
+    let tiled_export_state =
+        TiledExportState::new(&device, RENDER_TEXTURE_FORMAT, NetworkNode::new(None));
+    let pipeline_error = device
+        .pop_error_scope()
+        .await
+        .map(|error| error.to_string());
+    let pipeline_time = pipeline_start.elapsed();
+
+    let mut frames = Vec::new();
+    for fractal_group in FractalGroup::iter() {
+        frames.push(
+            render_frame(
+                &device,
+                &queue,
+                &mut graphic_state,
+                &tiled_export_state,
+                fractal_group,
+            )
+            .await,
+        );
+    }
+
+    Ok(SelfTestReport {
+        adapters,
+        adapter: adapter.get_info(),
+        limits: adapter.limits(),
+        downlevel_capabilities: adapter.get_downlevel_capabilities(),
+        pipeline_time,
+        pipeline_error,
+        frames,
+    })
+}
+
+/// Checks the GPU setup without opening a window, by compiling every shader and pipeline and
+/// rendering one frame of each fractal group offscreen
+///
+/// ## Errors
+/// - `RenderStateError::RequestAdapter(RequestAdapterError)` when adapter request failed
+/// - `RenderStateError::RequestDevice(RequestDeviceError)` when device request failed
+pub fn run_self_test(options: &RenderStateOptions) -> Result<SelfTestReport, RenderStateError> {
+    pollster::block_on(self_test(options))
+}
@@ -0,0 +1,103 @@
+use std::time::{Duration, Instant};
+
+// Longest time the raymarch of a single submission should take, well below the two seconds after
+// which drivers such as the Windows one reset a device that stopped responding
+const SUBMISSION_BUDGET: Duration = Duration::from_millis(200);
+// Every band costs a render pass and a submission of its own
+const MAX_BANDS: u32 = 64;
+const MIN_BAND_HEIGHT: u32 = 8;
+
+// Rows of the render target raymarched by one submission
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Band {
+    pub(crate) y: u32,
+    pub(crate) height: u32,
+}
+
+// Splits heavy frames into horizontal bands submitted one after another, so no submission runs long
+// enough for the driver to treat the GPU as hung, while light frames stay a single submission
+#[derive(Clone, Debug)]
+pub(crate) struct FrameSlicer {
+    band_count: u32,
+    last_frame: Option<Instant>,
+    // Frames which only present the accumulated image say nothing about the cost of the raymarch
+    is_last_frame_raymarched: bool,
+}
+
+impl FrameSlicer {
+    #[must_use]
+    pub(crate) fn new() -> Self {
+        Self {
+            band_count: 1,
+            last_frame: None,
+            is_last_frame_raymarched: false,
+        }
+    }
+
+    // Needs to be called once at the start of every frame, the time since the previous frame
+    // estimates how long its raymarch took, as presenting waits for the GPU to catch up
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub(crate) fn begin_frame(&mut self, now: Instant) {
+        if let Some(last_frame) = self.last_frame
+            && self.is_last_frame_raymarched
+        {
+            let frame_time = now - last_frame;
+            let band_count = ((frame_time.as_secs_f32() / SUBMISSION_BUDGET.as_secs_f32()).ceil()
+                as u32)
+                .clamp(1, MAX_BANDS);
+            if band_count != self.band_count {
+                log::debug!("Raymarching frames in {band_count} bands");
+            }
+            self.band_count = band_count;
+        }
+        self.last_frame = Some(now);
+        self.is_last_frame_raymarched = false;
+    }
+
+    // Bands covering the given height from the top, marks the current frame as raymarched
+    #[must_use]
+    pub(crate) fn bands(&mut self, height: u32) -> Vec<Band> {
+        self.is_last_frame_raymarched = true;
+        let band_count = self.band_count.min(height.div_ceil(MIN_BAND_HEIGHT)).max(1);
+        let band_height = height.div_ceil(band_count).max(1);
+
+        (0..height)
+            .step_by(band_height as usize)
+            .map(|y| Band {
+                y,
+                height: band_height.min(height - y),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_slicer() {
+        let mut slicer = FrameSlicer::new();
+        let start = Instant::now();
+
+        slicer.begin_frame(start);
+        assert_eq!(slicer.bands(100), [Band { y: 0, height: 100 }]);
+
+        // Raymarch took about two and a half budgets, so it is split into three bands
+        slicer.begin_frame(start + Duration::from_millis(500));
+        let bands = slicer.bands(100);
+        assert_eq!(bands.len(), 3);
+        assert_eq!(bands[2], Band { y: 68, height: 32 });
+
+        // Time spent waiting after the accumulation finished leaves the bands alone
+        slicer.begin_frame(start + Duration::from_secs(1));
+        slicer.begin_frame(start + Duration::from_secs(61));
+        assert_eq!(slicer.bands(100).len(), 3);
+
+        // Light frame goes back to a single submission, thin targets are never split too finely
+        slicer.begin_frame(start + Duration::from_millis(61_016));
+        assert_eq!(slicer.bands(100).len(), 1);
+        slicer.band_count = MAX_BANDS;
+        assert_eq!(slicer.bands(10).len(), 2);
+    }
+}
@@ -0,0 +1,49 @@
+use egui_wgpu::wgpu;
+use winit::dpi::PhysicalSize;
+
+use crate::data::{CameraData, GuiData};
+
+// Older snapshots are dropped once there are more, as every one keeps a full frame in memory
+pub(crate) const MAX_SNAPSHOTS: usize = 8;
+
+// State of the session at some point, which can be restored or compared against another snapshot
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Snapshot {
+    pub(crate) gui_data: GuiData,
+    pub(crate) camera_data: CameraData,
+    // Post processed frame at the time of capture, registered with the GUI renderer
+    pub(crate) image: egui::TextureId,
+    pub(crate) size: PhysicalSize<u32>,
+}
+
+impl Snapshot {
+    #[must_use]
+    pub(crate) fn aspect_ratio(&self) -> f32 {
+        #[allow(clippy::cast_precision_loss)]
+        let aspect_ratio = self.size.width as f32 / self.size.height.max(1) as f32;
+        aspect_ratio
+    }
+}
+
+// Texture the post pass renders the captured frame into, it needs to match the surface format of the post pipeline
+#[must_use]
+pub(crate) fn create_snapshot_texture(
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+    size: PhysicalSize<u32>,
+) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("snapshot_texture"),
+        size: wgpu::Extent3d {
+            width: size.width,
+            height: size.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    })
+}
@@ -0,0 +1,113 @@
+use egui_wgpu::wgpu;
+use std::sync::mpsc;
+
+use crate::data::MarchStatisticsData;
+use crate::data::diagnostics::MarchStatistics;
+use crate::util::storage::{StorageBuffer, StorageBufferDescriptor, StorageBufferInit as _};
+
+const STATISTICS_BUFFER_SIZE: usize = size_of::<MarchStatisticsData>();
+
+// Counts how the rays of each raymarch pass ended, read back without stalling the frame
+#[derive(Debug)]
+pub(crate) struct StatisticsState {
+    statistics_buffer: StorageBuffer,
+    readback_buffer: wgpu::Buffer,
+    is_copy_pending: bool,
+    readback_receiver: Option<mpsc::Receiver<Result<(), wgpu::BufferAsyncError>>>,
+    statistics: Option<MarchStatistics>,
+}
+
+impl StatisticsState {
+    #[must_use]
+    pub(crate) fn new(device: &wgpu::Device) -> Self {
+        let statistics_buffer = device.create_storage_buffer(&StorageBufferDescriptor {
+            label: Some("statistics_storage_buffer"),
+            contents: bytemuck::bytes_of(&MarchStatisticsData::default()),
+            extra_usage: wgpu::BufferUsages::COPY_SRC,
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("statistics_readback_buffer"),
+            size: STATISTICS_BUFFER_SIZE as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            statistics_buffer,
+            readback_buffer,
+            is_copy_pending: false,
+            readback_receiver: None,
+            statistics: None,
+        }
+    }
+
+    // Storage buffer the fractal shaders count bailout reasons into
+    #[must_use]
+    pub(crate) fn statistics_buffer(&self) -> &StorageBuffer {
+        &self.statistics_buffer
+    }
+
+    // Statistics of the most recent raymarch pass which finished reading back
+    #[must_use]
+    pub(crate) fn statistics(&self) -> Option<MarchStatistics> {
+        self.statistics
+    }
+
+    // Needs to be called before the raymarch pass
+    pub(crate) fn clear(&self, queue: &wgpu::Queue) {
+        self.statistics_buffer
+            .update_buffer(queue, 0, &[MarchStatisticsData::default()]);
+    }
+
+    // Needs to be called after the raymarch pass, skipped while the previous copy is still being read
+    pub(crate) fn copy_statistics(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if self.readback_receiver.is_some() {
+            return;
+        }
+
+        encoder.copy_buffer_to_buffer(
+            &self.statistics_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            STATISTICS_BUFFER_SIZE as wgpu::BufferAddress,
+        );
+        self.is_copy_pending = true;
+    }
+
+    // Needs to be called after submitting the frame, picks up statistics once they are mapped
+    pub(crate) fn read_statistics(&mut self, device: &wgpu::Device) {
+        if self.is_copy_pending {
+            let (sender, receiver) = mpsc::channel();
+            self.readback_buffer
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    // Receiving end is only dropped after the result was received
+                    sender.send(result).ok();
+                });
+            self.readback_receiver = Some(receiver);
+            self.is_copy_pending = false;
+        }
+
+        let Some(receiver) = &self.readback_receiver else {
+            return;
+        };
+        if let Err(error) = device.poll(wgpu::PollType::Poll) {
+            log::warn!("Failed to poll the statistics readback: {error}");
+        }
+
+        match receiver.try_recv() {
+            Ok(Ok(())) => {
+                let data: MarchStatisticsData = bytemuck::pod_read_unaligned(
+                    &self.readback_buffer.slice(..).get_mapped_range(),
+                );
+                self.readback_buffer.unmap();
+                self.statistics = Some(data.into());
+            }
+            Ok(Err(error)) => log::warn!("Failed to read back the statistics: {error}"),
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => {}
+        }
+        self.readback_receiver = None;
+    }
+}
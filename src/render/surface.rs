@@ -0,0 +1,269 @@
+use egui_wgpu::wgpu;
+use std::fmt;
+use strum::IntoEnumIterator as _;
+use strum_macros::EnumIter;
+use winit::dpi::PhysicalSize;
+
+/// Presentation setup of the window surface, chosen at startup
+#[derive(Clone, Copy, Debug, Default, PartialEq, EnumIter)]
+pub enum SurfaceProfile {
+    /// Opaque window synchronized with the display
+    #[default]
+    Desktop,
+    /// Background is left transparent for the compositor, so only the fractal is drawn over the desktop
+    Transparent,
+    /// Canvas surfaces only offer linear formats, so colors are encoded through an sRGB view instead
+    Web,
+    /// Opaque window with strict vsync and a short queue, giving screen recorders evenly paced frames
+    Recording,
+}
+
+impl SurfaceProfile {
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            SurfaceProfile::Desktop => "desktop",
+            SurfaceProfile::Transparent => "transparent",
+            SurfaceProfile::Web => "web",
+            SurfaceProfile::Recording => "recording",
+        }
+    }
+
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        SurfaceProfile::iter().find(|profile| profile.name() == name)
+    }
+
+    // Alpha modes in the order of preference, falling back to the first supported one otherwise
+    #[must_use]
+    fn alpha_modes(self) -> &'static [wgpu::CompositeAlphaMode] {
+        match self {
+            SurfaceProfile::Desktop => &[wgpu::CompositeAlphaMode::Auto],
+            SurfaceProfile::Transparent => &[
+                wgpu::CompositeAlphaMode::PreMultiplied,
+                wgpu::CompositeAlphaMode::PostMultiplied,
+                wgpu::CompositeAlphaMode::Inherit,
+            ],
+            SurfaceProfile::Web | SurfaceProfile::Recording => &[
+                wgpu::CompositeAlphaMode::Opaque,
+                wgpu::CompositeAlphaMode::Auto,
+            ],
+        }
+    }
+
+    // Present modes in the order of preference, the automatic modes are supported everywhere
+    #[must_use]
+    fn present_modes(self) -> &'static [wgpu::PresentMode] {
+        match self {
+            SurfaceProfile::Desktop | SurfaceProfile::Transparent => {
+                &[wgpu::PresentMode::AutoVsync]
+            }
+            SurfaceProfile::Web | SurfaceProfile::Recording => &[wgpu::PresentMode::Fifo],
+        }
+    }
+
+    // Frame latency the profile needs regardless of the settings, if any
+    #[must_use]
+    fn fixed_frame_latency(self) -> Option<u32> {
+        match self {
+            SurfaceProfile::Recording => Some(1),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SurfaceProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// Surface configuration of a profile, validated against what the surface supports
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SurfaceSettings {
+    pub(crate) format: wgpu::TextureFormat,
+    // Format everything is rendered in, the sRGB variant of the surface format where possible
+    pub(crate) view_format: wgpu::TextureFormat,
+    pub(crate) alpha_mode: wgpu::CompositeAlphaMode,
+    pub(crate) present_mode: wgpu::PresentMode,
+    pub(crate) fixed_frame_latency: Option<u32>,
+    pub(crate) is_transparent: bool,
+}
+
+impl SurfaceSettings {
+    #[must_use]
+    fn format(
+        profile: SurfaceProfile,
+        surface_capabilities: &wgpu::SurfaceCapabilities,
+        is_view_format_supported: bool,
+    ) -> (wgpu::TextureFormat, wgpu::TextureFormat) {
+        let srgb_format = surface_capabilities
+            .formats
+            .iter()
+            .find(|format| format.is_srgb())
+            .copied();
+        let linear_format = surface_capabilities
+            .formats
+            .iter()
+            .find(|format| !format.is_srgb() && format.add_srgb_suffix() != **format)
+            .copied();
+
+        let format = match (profile, srgb_format, linear_format) {
+            (SurfaceProfile::Web, _, Some(format)) if is_view_format_supported => format,
+            (_, Some(format), _) => format,
+            _ => surface_capabilities.formats[0],
+        };
+        let view_format = if is_view_format_supported {
+            format.add_srgb_suffix()
+        } else {
+            format
+        };
+
+        (format, view_format)
+    }
+
+    // Surface is guaranteed compatible with the adapter, so the capabilities list at least one of each
+    #[must_use]
+    pub(crate) fn new(
+        profile: SurfaceProfile,
+        surface_capabilities: &wgpu::SurfaceCapabilities,
+        downlevel_flags: wgpu::DownlevelFlags,
+    ) -> Self {
+        let is_view_format_supported =
+            downlevel_flags.contains(wgpu::DownlevelFlags::SURFACE_VIEW_FORMATS);
+        let (format, view_format) =
+            Self::format(profile, surface_capabilities, is_view_format_supported);
+
+        let preferred_alpha_mode = profile.alpha_modes().iter().copied().find(|alpha_mode| {
+            *alpha_mode == wgpu::CompositeAlphaMode::Auto
+                || surface_capabilities.alpha_modes.contains(alpha_mode)
+        });
+        let alpha_mode = preferred_alpha_mode.unwrap_or_else(|| {
+            log::warn!("Surface does not support the alpha modes of the {profile} profile");
+            surface_capabilities.alpha_modes[0]
+        });
+
+        let preferred_present_mode = profile
+            .present_modes()
+            .iter()
+            .copied()
+            .find(|present_mode| {
+                matches!(
+                    present_mode,
+                    wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync
+                ) || surface_capabilities.present_modes.contains(present_mode)
+            });
+        let present_mode = preferred_present_mode.unwrap_or_else(|| {
+            log::warn!("Surface does not support the present modes of the {profile} profile");
+            wgpu::PresentMode::AutoVsync
+        });
+
+        Self {
+            format,
+            view_format,
+            alpha_mode,
+            present_mode,
+            fixed_frame_latency: profile.fixed_frame_latency(),
+            is_transparent: profile == SurfaceProfile::Transparent
+                && matches!(
+                    alpha_mode,
+                    wgpu::CompositeAlphaMode::PreMultiplied
+                        | wgpu::CompositeAlphaMode::PostMultiplied
+                        | wgpu::CompositeAlphaMode::Inherit
+                ),
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn frame_latency(&self, requested_frame_latency: u32) -> u32 {
+        self.fixed_frame_latency.unwrap_or(requested_frame_latency)
+    }
+
+    #[must_use]
+    pub(crate) fn surface_config(
+        &self,
+        size: PhysicalSize<u32>,
+        frame_latency: u32,
+    ) -> wgpu::SurfaceConfiguration {
+        wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: self.format,
+            width: size.width,
+            height: size.height,
+            present_mode: self.present_mode,
+            alpha_mode: self.alpha_mode,
+            // View formats of the surface format are always allowed, other ones only with the downlevel flag
+            view_formats: if self.view_format == self.format {
+                vec![]
+            } else {
+                vec![self.view_format]
+            },
+            desired_maximum_frame_latency: self.frame_latency(frame_latency),
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn clear_color(&self) -> wgpu::Color {
+        if self.is_transparent {
+            wgpu::Color::TRANSPARENT
+        } else {
+            wgpu::Color::BLACK
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_fallback() {
+        let surface_capabilities = wgpu::SurfaceCapabilities {
+            formats: vec![
+                wgpu::TextureFormat::Bgra8Unorm,
+                wgpu::TextureFormat::Bgra8UnormSrgb,
+            ],
+            present_modes: vec![wgpu::PresentMode::Fifo],
+            alpha_modes: vec![wgpu::CompositeAlphaMode::Opaque],
+            usages: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        };
+
+        let desktop = SurfaceSettings::new(
+            SurfaceProfile::Desktop,
+            &surface_capabilities,
+            wgpu::DownlevelFlags::empty(),
+        );
+        assert_eq!(desktop.format, wgpu::TextureFormat::Bgra8UnormSrgb);
+        assert_eq!(desktop.view_format, wgpu::TextureFormat::Bgra8UnormSrgb);
+        assert_eq!(desktop.frame_latency(3), 3);
+
+        let web = SurfaceSettings::new(
+            SurfaceProfile::Web,
+            &surface_capabilities,
+            wgpu::DownlevelFlags::SURFACE_VIEW_FORMATS,
+        );
+        assert_eq!(web.format, wgpu::TextureFormat::Bgra8Unorm);
+        assert_eq!(web.view_format, wgpu::TextureFormat::Bgra8UnormSrgb);
+
+        // Compositor transparency is not available, so the profile falls back to an opaque surface
+        let transparent = SurfaceSettings::new(
+            SurfaceProfile::Transparent,
+            &surface_capabilities,
+            wgpu::DownlevelFlags::empty(),
+        );
+        assert_eq!(transparent.alpha_mode, wgpu::CompositeAlphaMode::Opaque);
+        assert!(!transparent.is_transparent);
+
+        assert_eq!(
+            SurfaceProfile::from_name("recording"),
+            Some(SurfaceProfile::Recording)
+        );
+        // Recordings need evenly paced frames, whatever the latency setting
+        let recording = SurfaceSettings::new(
+            SurfaceProfile::Recording,
+            &surface_capabilities,
+            wgpu::DownlevelFlags::empty(),
+        );
+        assert_eq!(recording.frame_latency(3), 1);
+    }
+}
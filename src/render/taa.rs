@@ -0,0 +1,384 @@
+use egui_wgpu::wgpu;
+use winit::dpi::PhysicalSize;
+
+use crate::data::options::AovLayer;
+use crate::data::{CameraData, GuiData, OptionsData, TaaData};
+use crate::render::aov::AovState;
+use crate::render::graphics::GraphicState;
+use crate::util::buffer::{
+    ResourceGroup, ResourceGroupDescriptor, ResourceGroupInit as _, ResourceGroupLayoutEntry,
+};
+use crate::util::shader::{
+    WGSLShaderModuleDescriptor, WGSLShaderModuleInit as _, WGSLShaderSource,
+};
+use crate::util::uniform::{
+    BufferDataDescriptor as _, UniformBuffer, UniformBufferDescriptor, UniformBufferInit as _,
+};
+
+// Temporal anti-aliasing of the frames which restart the accumulation, e.g. while the camera moves
+// The jittered sample of the frame is blended with the previous resolved frame, reprojected using
+// the depth layer and the camera it was rendered with, and the result replaces the sample in the
+// render texture, so the progressive accumulation continues on top of it
+#[derive(Clone, Debug)]
+pub(crate) struct TaaState {
+    taa_data: TaaData,
+    taa_uniform_buffer: UniformBuffer,
+    screen_uniform_buffer: UniformBuffer,
+    camera_uniform_buffer: UniformBuffer,
+    previous_camera_uniform_buffer: UniformBuffer,
+    // Camera and settings the history was rendered with, or None when there is no usable history
+    history_state: Option<(CameraData, OptionsData)>,
+    history_texture: wgpu::Texture,
+    history_texture_view: wgpu::TextureView,
+    resolved_texture: wgpu::Texture,
+    resolved_texture_view: wgpu::TextureView,
+    history_sampler: wgpu::Sampler,
+    resource_group: ResourceGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl TaaState {
+    #[must_use]
+    fn create_taa_texture(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        size: PhysicalSize<u32>,
+        label: &str,
+    ) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: size.width,
+                height: size.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        })
+    }
+
+    #[must_use]
+    fn create_history_sampler(device: &wgpu::Device) -> wgpu::Sampler {
+        device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("taa_history_sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        })
+    }
+
+    #[must_use]
+    #[allow(clippy::too_many_arguments)]
+    fn resources<'a>(
+        screen_uniform_buffer: &'a UniformBuffer,
+        camera_uniform_buffer: &'a UniformBuffer,
+        previous_camera_uniform_buffer: &'a UniformBuffer,
+        taa_uniform_buffer: &'a UniformBuffer,
+        render_texture_view: &'a wgpu::TextureView,
+        history_texture_view: &'a wgpu::TextureView,
+        depth_texture_view: &'a wgpu::TextureView,
+        history_sampler: &'a wgpu::Sampler,
+    ) -> [wgpu::BindingResource<'a>; 8] {
+        [
+            screen_uniform_buffer.as_entire_binding(),
+            camera_uniform_buffer.as_entire_binding(),
+            previous_camera_uniform_buffer.as_entire_binding(),
+            taa_uniform_buffer.as_entire_binding(),
+            wgpu::BindingResource::TextureView(render_texture_view),
+            wgpu::BindingResource::TextureView(history_texture_view),
+            wgpu::BindingResource::TextureView(depth_texture_view),
+            wgpu::BindingResource::Sampler(history_sampler),
+        ]
+    }
+
+    #[must_use]
+    fn create_taa_group(
+        device: &wgpu::Device,
+        resources: &[wgpu::BindingResource],
+    ) -> ResourceGroup {
+        let uniform_entry = ResourceGroupLayoutEntry {
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let texture_entry = |filterable| ResourceGroupLayoutEntry {
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+
+        device.create_resource_group(&ResourceGroupDescriptor {
+            label: Some("taa"),
+            resources,
+            entries: &[
+                uniform_entry,
+                uniform_entry,
+                uniform_entry,
+                uniform_entry,
+                texture_entry(true),
+                texture_entry(true),
+                // Output layers are 32 bit float textures, which are not filterable
+                texture_entry(false),
+                ResourceGroupLayoutEntry {
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    #[must_use]
+    fn create_taa_pipeline(
+        device: &wgpu::Device,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        let taa_shader = device.create_wgsl_shader_module(WGSLShaderModuleDescriptor {
+            label: Some("taa_shader"),
+            main: shader_source!("taa.wgsl"),
+            dependencies: &[shader_source!("dependencies/vertex.wgsl")],
+        });
+
+        let taa_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("taa_pipeline_layout"),
+            bind_group_layouts,
+            push_constant_ranges: &[],
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("taa_render_pipeline"),
+            layout: Some(&taa_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &taa_shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &taa_shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    // Format and size have to match the render texture, which the resolved frame is copied into
+    #[must_use]
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        size: PhysicalSize<u32>,
+        render_texture_view: &wgpu::TextureView,
+        graphic_state: &GraphicState,
+        aov_state: &AovState,
+    ) -> Self {
+        let taa_data = GuiData::default().into();
+        let taa_uniform_buffer = device.create_uniform_buffer(&UniformBufferDescriptor {
+            label: Some("taa_uniform_buffer"),
+            data_descriptor: taa_data,
+        });
+        let previous_camera_uniform_buffer =
+            device.create_uniform_buffer(&UniformBufferDescriptor {
+                label: Some("taa_previous_camera_uniform_buffer"),
+                data_descriptor: graphic_state.camera_data(),
+            });
+        let screen_uniform_buffer = graphic_state.screen_uniform_buffer().clone();
+        let camera_uniform_buffer = graphic_state.camera_uniform_buffer().clone();
+
+        let history_texture = Self::create_taa_texture(device, format, size, "taa_history_texture");
+        let history_texture_view =
+            history_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let resolved_texture =
+            Self::create_taa_texture(device, format, size, "taa_resolved_texture");
+        let resolved_texture_view =
+            resolved_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let history_sampler = Self::create_history_sampler(device);
+
+        let resource_group = Self::create_taa_group(
+            device,
+            &Self::resources(
+                &screen_uniform_buffer,
+                &camera_uniform_buffer,
+                &previous_camera_uniform_buffer,
+                &taa_uniform_buffer,
+                render_texture_view,
+                &history_texture_view,
+                aov_state.layer_texture_view(AovLayer::Depth),
+                &history_sampler,
+            ),
+        );
+        let pipeline =
+            Self::create_taa_pipeline(device, &[resource_group.bind_group_layout()], format);
+
+        Self {
+            taa_data,
+            taa_uniform_buffer,
+            screen_uniform_buffer,
+            camera_uniform_buffer,
+            previous_camera_uniform_buffer,
+            history_state: None,
+            history_texture,
+            history_texture_view,
+            resolved_texture,
+            resolved_texture_view,
+            history_sampler,
+            resource_group,
+            pipeline,
+        }
+    }
+
+    // Needs to be called whenever the render texture or output layers are recreated, e.g. on resize
+    pub(crate) fn update_render_texture(
+        &mut self,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        size: PhysicalSize<u32>,
+        render_texture_view: &wgpu::TextureView,
+        aov_state: &AovState,
+    ) {
+        self.history_state = None;
+        self.history_texture =
+            Self::create_taa_texture(device, format, size, "taa_history_texture");
+        self.history_texture_view = self
+            .history_texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        self.resolved_texture =
+            Self::create_taa_texture(device, format, size, "taa_resolved_texture");
+        self.resolved_texture_view = self
+            .resolved_texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        device.rebind_resource_group(
+            &mut self.resource_group,
+            Some("taa"),
+            &Self::resources(
+                &self.screen_uniform_buffer,
+                &self.camera_uniform_buffer,
+                &self.previous_camera_uniform_buffer,
+                &self.taa_uniform_buffer,
+                render_texture_view,
+                &self.history_texture_view,
+                aov_state.layer_texture_view(AovLayer::Depth),
+                &self.history_sampler,
+            ),
+        );
+    }
+
+    pub(crate) fn update_taa_data(&mut self, new_taa_data: TaaData) {
+        if !new_taa_data.is_enabled {
+            self.history_state = None;
+        }
+
+        self.taa_data = new_taa_data;
+    }
+
+    // Needs to be called right after the raymarch pass of a frame which restarted the accumulation
+    // Options have to be the ones set by the user, as changes of the frame governor keep the scene
+    pub(crate) fn resolve(
+        &mut self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        render_texture: &wgpu::Texture,
+        camera_data: CameraData,
+        options_data: &OptionsData,
+    ) {
+        if !self.taa_data.is_enabled {
+            return;
+        }
+
+        // History of different settings shows a different scene, which no reprojection can fix
+        let previous_camera_data = self
+            .history_state
+            .filter(|(_, history_options_data)| {
+                bytemuck::bytes_of(&history_options_data.into_buffer_data())
+                    == bytemuck::bytes_of(&options_data.into_buffer_data())
+            })
+            .map(|(history_camera_data, _)| history_camera_data);
+        if let Some(previous_camera_data) = previous_camera_data {
+            self.previous_camera_uniform_buffer
+                .update_buffer(queue, previous_camera_data);
+        }
+        self.taa_uniform_buffer.update_buffer(
+            queue,
+            TaaData {
+                is_history_valid: previous_camera_data.is_some(),
+                ..self.taa_data
+            },
+        );
+
+        let mut taa_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("taa_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.resolved_texture_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+        taa_pass.set_pipeline(&self.pipeline);
+        taa_pass.set_bind_group(0, self.resource_group.bind_group(), &[]);
+        taa_pass.draw(0..3, 0..2);
+        drop(taa_pass);
+
+        let size = self.resolved_texture.size();
+        encoder.copy_texture_to_texture(
+            self.resolved_texture.as_image_copy(),
+            self.history_texture.as_image_copy(),
+            size,
+        );
+        encoder.copy_texture_to_texture(
+            self.resolved_texture.as_image_copy(),
+            render_texture.as_image_copy(),
+            size,
+        );
+
+        self.history_state = Some((camera_data, *options_data));
+    }
+}
@@ -0,0 +1,413 @@
+use egui_wgpu::wgpu;
+use std::{collections::VecDeque, fs, sync::mpsc};
+
+use crate::data::tiles::{ExportJob, TILE_SIZE, TiledImage};
+use crate::error::TiledExportError;
+use crate::network::{CoordinatorEvent, NetworkNode, WorkerJob};
+use crate::render::aov::{AOV_FORMATS, encode_pfm};
+use crate::render::graphics::GraphicState;
+use crate::util::math::f16_to_f32;
+
+// Has to match the format of the render texture, which is a 16 bit float per channel
+const TILE_BYTES_PER_PIXEL: u32 = 8;
+// File finished exports are written to, relative to the working directory
+const TILED_EXPORT_PATH: &str = "tiled_export.pfm";
+
+// Export started on this machine, whose tiles are rendered here or by the workers
+#[derive(Clone, Debug)]
+struct TiledExport {
+    id: u32,
+    image: TiledImage,
+    // Tiles left for this machine, rendered one per frame
+    local_tiles: VecDeque<u32>,
+}
+
+// Shown by the GUI next to the export settings
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct TiledExportStatus {
+    // Connected workers, None unless this machine is a coordinator
+    pub(crate) worker_count: Option<usize>,
+    pub(crate) is_worker: bool,
+    // Finished and all tiles of the running export
+    pub(crate) progress: Option<(usize, usize)>,
+    // Tiles a worker was sent and has not rendered yet
+    pub(crate) queued_tiles: usize,
+}
+
+// Targets the tiles of an export are rendered into, so tiles keep their size whatever the size of
+// the window rendering them
+#[derive(Debug)]
+pub(crate) struct TiledExportState {
+    color_texture: wgpu::Texture,
+    color_texture_view: wgpu::TextureView,
+    layer_texture_views: [wgpu::TextureView; 3],
+    readback_buffer: wgpu::Buffer,
+    network_node: NetworkNode,
+    export: Option<TiledExport>,
+    next_export_id: u32,
+    worker_jobs: VecDeque<WorkerJob>,
+}
+
+impl TiledExportState {
+    #[must_use]
+    fn create_tile_texture(device: &wgpu::Device, format: wgpu::TextureFormat) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("tile_texture"),
+            size: wgpu::Extent3d {
+                width: TILE_SIZE,
+                height: TILE_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        })
+    }
+
+    #[must_use]
+    fn padded_row_size() -> u32 {
+        (TILE_SIZE * TILE_BYTES_PER_PIXEL).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+    }
+
+    #[must_use]
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        network_node: NetworkNode,
+    ) -> Self {
+        let color_texture = Self::create_tile_texture(device, format);
+        let color_texture_view = color_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let layer_texture_views = AOV_FORMATS.map(|format| {
+            Self::create_tile_texture(device, format)
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        });
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("tile_readback_buffer"),
+            size: wgpu::BufferAddress::from(Self::padded_row_size() * TILE_SIZE),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            color_texture,
+            color_texture_view,
+            layer_texture_views,
+            readback_buffer,
+            network_node,
+            export: None,
+            next_export_id: 0,
+            worker_jobs: VecDeque::new(),
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn status(&self) -> TiledExportStatus {
+        TiledExportStatus {
+            worker_count: match &self.network_node {
+                NetworkNode::Coordinator(coordinator) => Some(coordinator.worker_count()),
+                _ => None,
+            },
+            is_worker: matches!(self.network_node, NetworkNode::Worker(_)),
+            progress: self.export.as_ref().map(|export| export.image.progress()),
+            queued_tiles: self.worker_jobs.iter().map(|job| job.tiles.len()).sum(),
+        }
+    }
+
+    // Tiles are split between this machine and the connected workers, if it is a coordinator
+    pub(crate) fn start_export(&mut self, job: &ExportJob) {
+        if self.export.is_some() {
+            log::warn!("A tiled export is already running");
+            return;
+        }
+
+        self.next_export_id = self.next_export_id.wrapping_add(1);
+        let tiles = (0..job.tile_count()).collect::<Vec<_>>();
+        let local_tiles = match &self.network_node {
+            NetworkNode::Coordinator(coordinator) => {
+                coordinator.distribute(self.next_export_id, job, &tiles)
+            }
+            _ => tiles,
+        };
+
+        self.export = Some(TiledExport {
+            id: self.next_export_id,
+            image: TiledImage::new(job),
+            local_tiles: local_tiles.into(),
+        });
+    }
+
+    fn receive_from_network(&mut self) {
+        match &self.network_node {
+            NetworkNode::Standalone => {}
+            NetworkNode::Coordinator(coordinator) => {
+                for event in coordinator.take_events() {
+                    let Some(export) = &mut self.export else {
+                        continue;
+                    };
+
+                    match event {
+                        CoordinatorEvent::Tile {
+                            export_id,
+                            index,
+                            values,
+                        } if export_id == export.id => {
+                            // Indices are taken from the worker, so only those of the export are
+                            // rendered again
+                            let tile_count = export.image.job().tile_count();
+                            if index >= tile_count {
+                                log::warn!(
+                                    "Worker sent tile {index} of an export with {tile_count} tiles"
+                                );
+                            } else if !export.image.insert_tile(index, &values) {
+                                log::warn!("Worker sent tile {index} with the wrong size");
+                                export.local_tiles.push_back(index);
+                            }
+                        }
+                        CoordinatorEvent::WorkerLost { export_id, tiles }
+                            if export_id == export.id =>
+                        {
+                            export.local_tiles.extend(tiles);
+                        }
+                        // Left over from an earlier export
+                        _ => {}
+                    }
+                }
+            }
+            NetworkNode::Worker(worker) => self.worker_jobs.extend(worker.take_jobs()),
+        }
+    }
+
+    // Renders a tile of the job's scene, the current scene is restored before the next frame
+    pub(crate) fn render_job_tile(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        graphic_state: &mut GraphicState,
+        job: &ExportJob,
+        index: u32,
+    ) -> Result<Vec<f32>, TiledExportError> {
+        let camera_data = graphic_state.camera_data();
+        let options_data = graphic_state.options_data();
+        let lighting_data = graphic_state.lighting_data();
+        let scene_data = graphic_state.scene_data();
+
+        graphic_state.update_camera(queue, job.camera_data);
+        graphic_state.update_options(queue, &job.gui_data.into());
+        graphic_state.update_lighting(queue, job.gui_data.into());
+        graphic_state.update_scene(queue, &job.gui_data.into());
+        let values = self.render_tile(device, queue, graphic_state, job, index);
+
+        graphic_state.update_camera(queue, camera_data);
+        graphic_state.update_options(queue, &options_data);
+        graphic_state.update_lighting(queue, lighting_data);
+        graphic_state.update_scene(queue, &scene_data);
+
+        values
+    }
+
+    fn advance_export(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        graphic_state: &mut GraphicState,
+    ) {
+        let Some(export) = &self.export else {
+            return;
+        };
+
+        if let Some(&index) = export.local_tiles.front() {
+            let job = *export.image.job();
+            match self.render_job_tile(device, queue, graphic_state, &job, index) {
+                Ok(values) => {
+                    if let Some(export) = &mut self.export {
+                        export.local_tiles.pop_front();
+                        export.image.insert_tile(index, &values);
+                    }
+                }
+                Err(error) => {
+                    log::warn!("Failed to render tile {index}, cancelling the export: {error}");
+                    self.export = None;
+                    return;
+                }
+            }
+        }
+
+        let Some(export) = self.export.take_if(|export| export.image.is_complete()) else {
+            return;
+        };
+        let job = export.image.job();
+        let bytes = encode_pfm(job.width, job.height, 3, export.image.values());
+        match fs::write(TILED_EXPORT_PATH, bytes) {
+            Ok(()) => log::info!("Wrote the tiled export to {TILED_EXPORT_PATH}"),
+            Err(error) => log::warn!("Failed to write the tiled export: {error}"),
+        }
+    }
+
+    fn advance_worker_job(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        graphic_state: &mut GraphicState,
+    ) {
+        self.worker_jobs.retain(|job| !job.tiles.is_empty());
+        let Some(worker_job) = self.worker_jobs.front() else {
+            return;
+        };
+        let (export_id, job, index) = (worker_job.export_id, worker_job.job, worker_job.tiles[0]);
+
+        let result = self
+            .render_job_tile(device, queue, graphic_state, &job, index)
+            .map_err(|error| format!("{error}"))
+            .and_then(|values| match &mut self.network_node {
+                NetworkNode::Worker(worker) => worker
+                    .send_tile(export_id, index, &values)
+                    .map_err(|error| format!("{error}")),
+                _ => Ok(()),
+            });
+        if let Err(error) = result {
+            // Disconnecting hands the remaining tiles back to the coordinator
+            log::warn!("Failed to render tile {index} for the coordinator: {error}");
+            self.network_node = NetworkNode::Standalone;
+            self.worker_jobs.clear();
+            return;
+        }
+        self.worker_jobs[0].tiles.remove(0);
+    }
+
+    // Renders a single tile per frame, so the window stays responsive during long exports
+    pub(crate) fn advance(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        graphic_state: &mut GraphicState,
+    ) {
+        self.receive_from_network();
+
+        if self.export.is_some() {
+            self.advance_export(device, queue, graphic_state);
+        } else {
+            self.advance_worker_job(device, queue, graphic_state);
+        }
+    }
+
+    fn render_sample(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        graphic_state: &GraphicState,
+        load: wgpu::LoadOp<wgpu::Color>,
+        (width, height): (u32, u32),
+    ) {
+        let attachment = |view| {
+            Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })
+        };
+        let [depth_view, normal_view, iterations_view] = &self.layer_texture_views;
+
+        let mut tile_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("tile_pass"),
+            color_attachments: &[
+                attachment(&self.color_texture_view),
+                attachment(depth_view),
+                attachment(normal_view),
+                attachment(iterations_view),
+            ],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        });
+        // Tiles along the edges of the image are smaller, the rest of the targets is left alone
+        tile_pass.set_scissor_rect(0, 0, width, height);
+        graphic_state.render(&mut tile_pass);
+    }
+
+    /// Renders a tile with the scene the graphic state currently holds, blocking until its RGB
+    /// values are read back row by row
+    ///
+    /// ## Errors
+    /// - `TiledExportError::Poll(PollError)` when waiting for the copy failed
+    /// - `TiledExportError::BufferAsync(BufferAsyncError)` when the copy could not be mapped
+    pub(crate) fn render_tile(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        graphic_state: &mut GraphicState,
+        job: &ExportJob,
+        index: u32,
+    ) -> Result<Vec<f32>, TiledExportError> {
+        let tile = job.tile(index);
+        let padded_row_size = Self::padded_row_size();
+        graphic_state.prepare_pipeline(device);
+
+        // Every sample needs its own submission, as they differ in the uniforms only
+        for sample in 0..job.samples {
+            graphic_state.prepare_tile_sample(queue, job.tile_view(index), sample);
+
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("tile_encoder"),
+            });
+            let load = if sample == 0 {
+                wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT)
+            } else {
+                wgpu::LoadOp::Load
+            };
+            self.render_sample(&mut encoder, graphic_state, load, (tile.width, tile.height));
+
+            if sample + 1 == job.samples {
+                encoder.copy_texture_to_buffer(
+                    self.color_texture.as_image_copy(),
+                    wgpu::TexelCopyBufferInfo {
+                        buffer: &self.readback_buffer,
+                        layout: wgpu::TexelCopyBufferLayout {
+                            offset: 0,
+                            bytes_per_row: Some(padded_row_size),
+                            rows_per_image: None,
+                        },
+                    },
+                    wgpu::Extent3d {
+                        width: tile.width,
+                        height: tile.height,
+                        depth_or_array_layers: 1,
+                    },
+                );
+            }
+            queue.submit(std::iter::once(encoder.finish()));
+        }
+
+        let slice = self.readback_buffer.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            // Receiving end is only dropped after the result was received
+            sender.send(result).ok();
+        });
+        device.poll(wgpu::PollType::Wait)?;
+        receiver.recv().unwrap_or(Err(wgpu::BufferAsyncError))?;
+
+        // Every pixel is sampled each time, so the sum is divided by the number of samples
+        #[allow(clippy::cast_precision_loss)]
+        let samples = job.samples as f32;
+        let row_size = (tile.width * TILE_BYTES_PER_PIXEL) as usize;
+        let values = slice
+            .get_mapped_range()
+            .chunks(padded_row_size as usize)
+            .take(tile.height as usize)
+            .flat_map(|row| {
+                bytemuck::pod_collect_to_vec::<u8, u16>(&row[..row_size])
+                    .chunks(4)
+                    .flat_map(|pixel| pixel[..3].iter().map(|bits| f16_to_f32(*bits) / samples))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        self.readback_buffer.unmap();
+
+        Ok(values)
+    }
+}
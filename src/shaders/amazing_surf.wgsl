@@ -0,0 +1,43 @@
+// Amazing Surf by Kali, a Mandelbox folding only the x and y axes, with the fixed radius of the sphere fold at 1
+fn scene_SDF(position: vec3<f32>) -> f32 {
+    let min_sq_radius = options.surf_min_radius * options.surf_min_radius;
+    var pos = position;
+    var derivative = 1.;
+    for (var i = 0u; i < options.surf_iterations; i++) {
+        let fold = options.surf_fold;
+        pos = vec3(abs(pos.xy + fold) - abs(pos.xy - fold) - pos.xy, pos.z);
+
+        // Points outside the unit sphere are only scaled, those inside of it are inverted up to the minimum radius
+        let factor = options.surf_scale / clamp(dot(pos, pos), min_sq_radius, 1.);
+        pos = reduce_precision_vec3(factor * pos + position);
+        derivative = reduce_precision_f32(derivative * abs(factor) + 1.);
+
+        if dot(pos, pos) > options.max_distance {
+            break;
+        }
+    }
+
+    return length(pos) / abs(derivative);
+}
+
+// Escape time normalized to [0, 1], points which never escape are fully dense
+fn scene_density(position: vec3<f32>) -> f32 {
+    let min_sq_radius = options.surf_min_radius * options.surf_min_radius;
+    var pos = position;
+    for (var i = 0u; i < options.surf_iterations; i++) {
+        let fold = options.surf_fold;
+        pos = vec3(abs(pos.xy + fold) - abs(pos.xy - fold) - pos.xy, pos.z);
+        pos = options.surf_scale / clamp(dot(pos, pos), min_sq_radius, 1.) * pos + position;
+
+        if dot(pos, pos) > options.max_distance {
+            return f32(i) / f32(options.surf_iterations);
+        }
+    }
+
+    return 1.;
+}
+
+// No closed form gradient through the folds
+fn get_normal(position: vec3<f32>) -> vec3<f32> {
+    return finite_difference_normal(position);
+}
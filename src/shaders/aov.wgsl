@@ -0,0 +1,48 @@
+struct AovUniform {
+    layer: u32,
+    max_distance: f32,
+    max_iterations: u32,
+}
+
+@group(0)
+@binding(0)
+var depth_texture: texture_2d<f32>;
+
+@group(0)
+@binding(1)
+var normal_texture: texture_2d<f32>;
+
+@group(0)
+@binding(2)
+var iterations_texture: texture_2d<f32>;
+
+@group(0)
+@binding(3)
+var<uniform> aov: AovUniform;
+
+// Has to match AOV_DISPLAY_TEXTURE_SIZE in aov.rs
+const AOV_DISPLAY_SIZE = 512.;
+
+// Maps the selected layer into displayable colors, stretched over the whole display texture
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = textureDimensions(depth_texture);
+    let coords = min(vec2<u32>(in.position.xy / AOV_DISPLAY_SIZE * vec2<f32>(size)), size - 1u);
+
+    switch aov.layer {
+        // Logarithmic, so both close details and the far plane stay visible, with near being bright
+        case 0u: {
+            let depth = max(textureLoad(depth_texture, coords, 0).r, 0.);
+            return vec4(vec3(1. - log(1. + depth) / log(1. + aov.max_distance)), 1.);
+        }
+        // Background has no normal and stays black
+        case 1u: {
+            let normal = textureLoad(normal_texture, coords, 0).xyz;
+            return vec4(select(0.5 + 0.5 * normal, vec3(0.), all(normal == vec3(0.))), 1.);
+        }
+        default: {
+            let iterations = textureLoad(iterations_texture, coords, 0).r;
+            return vec4(vec3(iterations / f32(aov.max_iterations)), 1.);
+        }
+    }
+}
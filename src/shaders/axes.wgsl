@@ -0,0 +1,108 @@
+struct ScreenUniform {
+    width: f32,
+    height: f32,
+    aspect_ratio: f32,
+}
+
+@group(0)
+@binding(0)
+var<uniform> screen: ScreenUniform;
+
+struct CameraUniform {
+    origin: vec3<f32>,
+    // Half the height of the view at unit depth
+    view_scale: f32,
+    matrix: mat3x3<f32>,
+}
+
+@group(0)
+@binding(1)
+var<uniform> camera: CameraUniform;
+
+struct AxesUniform {
+    length: f32,
+    tick_spacing: f32,
+    is_ticks: u32,
+    // Fraction of the depth layer covered by the image, lowered while previewing a drag
+    render_scale: f32,
+}
+
+@group(0)
+@binding(2)
+var<uniform> axes: AxesUniform;
+
+// Linear depth of the raymarched surface, which hides the parts of the axes behind it
+@group(0)
+@binding(3)
+var depth_texture: texture_2d<f32>;
+
+// Has to match MAX_TICKS_PER_SIDE in axes.rs
+const MAX_TICKS_PER_SIDE = 32u;
+// Tick length as a fraction of the tick spacing
+const TICK_SIZE = 0.2;
+// Parts of the axes closer to the camera than this are clipped
+const NEAR_DISTANCE = 1e-3;
+const AXIS_COLORS = array(vec3(1., 0.1, 0.1), vec3(0.1, 1., 0.1), vec3(0.2, 0.4, 1.));
+
+struct AxesVertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec3<f32>,
+    // Distance along the viewing direction, matching the depth layer
+    @location(1) depth: f32,
+}
+
+fn axis_direction(axis: u32) -> vec3<f32> {
+    var direction = vec3(0.);
+    direction[axis] = 1.;
+    return direction;
+}
+
+// Inverse of the ray generation in entry.wgsl, where the screen height spans [-view_scale, view_scale] at unit depth
+fn project(position: vec3<f32>, color: vec3<f32>) -> AxesVertexOutput {
+    let relative_position = position - camera.origin;
+    let depth = dot(relative_position, -camera.matrix[0]);
+    let x = dot(relative_position, camera.matrix[1]) / (screen.aspect_ratio * camera.view_scale);
+    let y = dot(relative_position, camera.matrix[2]) / camera.view_scale;
+
+    return AxesVertexOutput(vec4(x, y, depth - NEAR_DISTANCE, depth), color, depth);
+}
+
+// Lies outside the clip volume, so lines ending here are not drawn
+fn hidden_vertex() -> AxesVertexOutput {
+    return AxesVertexOutput(vec4(0., 0., -1., 1.), vec3(0.), 0.);
+}
+
+// Every pair of vertices is a line, first the three axes and then the ticks of each axis
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> AxesVertexOutput {
+    let line = index / 2u;
+    let end = select(-1., 1., index % 2u == 1u);
+
+    if line < 3u {
+        return project(end * axes.length * axis_direction(line), AXIS_COLORS[line]);
+    }
+
+    let tick = line - 3u;
+    let axis = tick / (2u * MAX_TICKS_PER_SIDE);
+    let tick_index = tick % (2u * MAX_TICKS_PER_SIDE);
+    // Ticks alternate between the positive and the negative half of the axis
+    let offset = f32(tick_index / 2u + 1u) * select(-1., 1., tick_index % 2u == 0u) * axes.tick_spacing;
+    if !bool(axes.is_ticks) || abs(offset) > axes.length {
+        return hidden_vertex();
+    }
+
+    // Ticks of the horizontal axes point up, the ones of the vertical axis point along X
+    let tick_direction = select(vec3(0., 0., 1.), vec3(1., 0., 0.), axis == 2u);
+    let position = offset * axis_direction(axis) + 0.5 * end * TICK_SIZE * axes.tick_spacing * tick_direction;
+    return project(position, AXIS_COLORS[axis]);
+}
+
+@fragment
+fn fs_main(in: AxesVertexOutput) -> @location(0) vec4<f32> {
+    let surface_depth = textureLoad(depth_texture, vec2<i32>(axes.render_scale * in.position.xy), 0).r;
+    if in.depth > surface_depth {
+        discard;
+    }
+
+    return vec4(in.color, 1.);
+}
@@ -0,0 +1,40 @@
+// Position of an iteration together with the length of its running derivative
+struct CustomPoint {
+    position: vec3<f32>,
+    derivative: f32,
+}
+
+// Note: custom_formula_step and custom_formula_distance are transpiled from the formula typed into the GUI
+fn scene_SDF(position: vec3<f32>) -> f32 {
+    var point = CustomPoint(position, 1.);
+    for (var i = 0u; i < options.custom_iterations; i++) {
+        point = custom_formula_step(point, position, i);
+        point.position = reduce_precision_vec3(point.position);
+        point.derivative = reduce_precision_f32(point.derivative);
+
+        if dot(point.position, point.position) > options.max_distance {
+            break;
+        }
+    }
+
+    return custom_formula_distance(point, position);
+}
+
+// Escape time normalized to [0, 1], points which never escape are fully dense
+fn scene_density(position: vec3<f32>) -> f32 {
+    var point = CustomPoint(position, 1.);
+    for (var i = 0u; i < options.custom_iterations; i++) {
+        point = custom_formula_step(point, position, i);
+
+        if dot(point.position, point.position) > options.max_distance {
+            return f32(i) / f32(options.custom_iterations);
+        }
+    }
+
+    return 1.;
+}
+
+// Nothing is known about the formula, so the gradient is always sampled
+fn get_normal(position: vec3<f32>) -> vec3<f32> {
+    return finite_difference_normal(position);
+}
@@ -1,3 +1,6 @@
+// Option ids and buffer sizes such as MAX_LIGHTS are generated from the Rust definitions and
+// placed in front of this file, see shader_constants in render.rs
+
 struct ScreenUniform {
     width: f32,
     height: f32,
@@ -10,6 +13,8 @@ var<uniform> screen: ScreenUniform;
 
 struct CameraUniform {
     origin: vec3<f32>,
+    // Half the height of the view at unit depth
+    view_scale: f32,
     matrix: mat3x3<f32>,
 }
 
@@ -23,13 +28,186 @@ struct OptionsUniform {
     epsilon: f32,
     fractal_color: vec3<f32>,
     background_color: vec3<f32>,
-    is_heatmap: u32,
+    coloring_mode: u32,
     fractal_group_id: u32,
     primitive_id: u32,
     power: f32,
+    is_analytic_normal: u32,
     constant: Quaternion,
+    debug_view: u32,
+    slice_offset: f32,
+    relaxation: f32,
+    is_smooth_heatmap: u32,
+    heatmap_scale: f32,
+    heatmap_offset: f32,
+    is_translucent: u32,
+    absorption_density: f32,
+    absorption_color: vec3<f32>,
+    render_mode: u32,
+    volume_density: f32,
+    volume_step: f32,
+    clip_normal: vec3<f32>,
+    is_clipping: u32,
+    clip_color: vec3<f32>,
+    clip_offset: f32,
+    floor_color: vec3<f32>,
+    is_floor: u32,
+    floor_height: f32,
+    floor_checker_scale: f32,
+    floor_pattern: u32,
+    is_half_precision: u32,
+    stereo_mode: u32,
+    eye_separation: f32,
+    projection: u32,
+    menger_iterations: u32,
+    menger_scale: f32,
+    sierpinski_scale: f32,
+    sierpinski_offset: vec3<f32>,
+    sierpinski_rotation: mat3x3<f32>,
+    kleinian_box_size: vec3<f32>,
+    kleinian_inversion_radius: f32,
+    kleinian_iterations: u32,
+    julia_formula: u32,
+    phoenix_strength: f32,
+    cubic_constant: Quaternion,
+    hyperplane_rotation: mat4x4<f32>,
+    hyperplane_offset: f32,
+    // Seconds into the animation of the Julia constant
+    animation_time: f32,
+    constant_animation: u32,
+    animation_speed: f32,
+    animation_radius: f32,
+    hybrid_formula_a: u32,
+    hybrid_formula_b: u32,
+    hybrid_pattern: u32,
+    hybrid_pattern_length: u32,
+    hybrid_iterations: u32,
+    hybrid_box_scale: f32,
+    julia_abs_folds: u32,
+    fractal_shading: u32,
+    floor_shading: u32,
+    sphere_radius: f32,
+    cylinder_radius: f32,
+    box_half_extents: vec3<f32>,
+    cylinder_half_height: f32,
+    torus_outer_radius: f32,
+    torus_inner_radius: f32,
+    csg_operation: u32,
+    csg_shape: u32,
+    csg_center: vec3<f32>,
+    csg_size: f32,
+    csg_smoothness: f32,
+    twist_strength: f32,
+    twist_axis: u32,
+    bend_strength: f32,
+    mirror_planes: u32,
+    // Share of the distance estimate marched at once
+    distance_fudge: f32,
+    // Norm past which the Julia iteration counts as escaped
+    bailout_radius: f32,
+    is_interior_visible: u32,
+    julia_estimator: u32,
+    interior_color: vec3<f32>,
+    surf_scale: f32,
+    surf_fold: vec2<f32>,
+    surf_min_radius: f32,
+    surf_iterations: u32,
+    custom_iterations: u32,
+    cutaway_signs: vec3<f32>,
+    is_floor_cut: u32,
+    // Radius of the bounding sphere in every coordinate, or the half extents of the bounding box
+    bounding_extents: vec3<f32>,
+    bounding_shape: u32,
+    max_step: f32,
 }
 
 @group(0)
 @binding(2)
 var<uniform> options: OptionsUniform;
+
+struct SampleUniform {
+    jitter: vec2<f32>,
+    index: u32,
+    roi_mode: u32,
+    roi_center: vec2<f32>,
+    roi_extent: vec2<f32>,
+    inside_rate: f32,
+    outside_rate: f32,
+    // Offset of the rendered tile within the image of the given size, zero sized for the screen
+    view_offset: vec2<f32>,
+    view_size: vec2<f32>,
+}
+
+@group(0)
+@binding(3)
+var<uniform> sample: SampleUniform;
+
+struct RayStep {
+    position: vec3<f32>,
+    distance: f32,
+}
+
+// Single ray recorded for the ray inspector
+struct RayPath {
+    pixel: vec2<u32>,
+    is_recording: u32,
+    length: u32,
+    steps: array<RayStep, MAX_RAY_PATH_STEPS>,
+}
+
+@group(1)
+@binding(0)
+var<storage, read_write> ray_path: RayPath;
+
+// Number of rays by bailout reason, see BAILOUT_* in entry.wgsl
+@group(1)
+@binding(1)
+var<storage, read_write> march_statistics: array<atomic<u32>, 4>;
+
+struct Light {
+    // Unit vector pointing towards the light
+    direction: vec3<f32>,
+    intensity: f32,
+    color: vec3<f32>,
+    is_enabled: u32,
+}
+
+struct Lighting {
+    ambient: f32,
+    lights: array<Light, MAX_LIGHTS>,
+}
+
+@group(1)
+@binding(2)
+var<storage, read> lighting: Lighting;
+
+// Colors are linear
+struct Material {
+    // Multiplied with the fractal color
+    albedo: vec3<f32>,
+    specular: f32,
+    // Already scaled by the emission strength
+    emissive: vec3<f32>,
+    reflectivity: f32,
+}
+
+struct SceneObject {
+    // Takes directions of the scene into the frame of the object
+    inverse_rotation: mat3x3<f32>,
+    translation: vec3<f32>,
+    scale: f32,
+    kind: u32,
+    material: Material,
+}
+
+// Only the first count objects are enabled
+struct SceneObjects {
+    count: u32,
+    // Fractal alone at the origin, see SceneData::is_plain
+    is_plain: u32,
+    objects: array<SceneObject, MAX_SCENE_OBJECTS>,
+}
+
+@group(1)
+@binding(3)
+var<storage, read> scene_objects: SceneObjects;
@@ -0,0 +1,64 @@
+// Signed distance to the bounding volume of the fractal, which is never further than the fractal
+// itself, unbounded fractals are inside of it everywhere
+fn bounding_volume_SDF(position: vec3<f32>) -> f32 {
+    switch options.bounding_shape {
+        case BOUNDING_SHAPE_SPHERE: {
+            return length(position) - options.bounding_extents.x;
+        }
+        case BOUNDING_SHAPE_BOX: {
+            let extents = options.bounding_extents;
+            return box_SDF(Box(extents.x, extents.y, extents.z), position);
+        }
+        default: {
+            return -options.max_distance;
+        }
+    }
+}
+
+// Distance estimate of the fractal, answered by the bounding volume alone while the position is
+// still outside of it, which also keeps estimates meant for points near the set from breaking down
+fn bounded_scene_SDF(position: vec3<f32>) -> f32 {
+    let bound_distance = bounding_volume_SDF(position);
+    if bound_distance > options.epsilon {
+        bounding_tests++;
+        return bound_distance;
+    }
+
+    return scene_SDF(position);
+}
+
+// The volume only bounds the scene when the fractal is alone at the origin, neither warped nor
+// extended by a primitive
+fn is_scene_bounded() -> bool {
+    return options.bounding_shape != BOUNDING_SHAPE_UNBOUNDED && bool(scene_objects.is_plain) && !is_warped()
+        && (options.csg_operation == CSG_OFF || options.csg_operation == CSG_INTERSECTION);
+}
+
+// Travel distances at which the ray enters and leaves the bounding volume of the scene, the entry
+// lies past the exit when the ray misses it
+fn bounding_volume_span(ray: Ray) -> vec2<f32> {
+    if !is_scene_bounded() {
+        return vec2(0., options.max_distance);
+    }
+
+    if options.bounding_shape == BOUNDING_SHAPE_SPHERE {
+        let radius = options.bounding_extents.x;
+        let closest_travel_distance = -dot(ray.origin, ray.direction);
+        let sq_miss_distance = dot(ray.origin, ray.origin) - closest_travel_distance * closest_travel_distance;
+        let sq_half_chord = radius * radius - sq_miss_distance;
+        if sq_half_chord < 0. {
+            return vec2(1., 0.);
+        }
+
+        let half_chord = sqrt(sq_half_chord);
+        return vec2(closest_travel_distance - half_chord, closest_travel_distance + half_chord);
+    }
+
+    // Slabs of the box, directions parallel to one of them never cross it
+    let direction = select(ray.direction, vec3(1e-6), abs(ray.direction) < vec3(1e-6));
+    let near = (-options.bounding_extents - ray.origin) / direction;
+    let far = (options.bounding_extents - ray.origin) / direction;
+    let entry = min(near, far);
+    let exit = max(near, far);
+    return vec2(max(max(entry.x, entry.y), entry.z), min(min(exit.x, exit.y), exit.z));
+}
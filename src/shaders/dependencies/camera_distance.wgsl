@@ -0,0 +1,21 @@
+// Direction in which the estimate grows, pointing away from the nearest surface
+struct CameraDistance {
+    away: vec3<f32>,
+    distance: f32,
+}
+
+@group(2)
+@binding(0)
+var<storage, read_write> camera_distance: CameraDistance;
+
+// Distance estimate of the scene at the camera, read back to keep the camera out of the surface
+@compute
+@workgroup_size(1)
+fn camera_distance_main() {
+    fractal_constant = animated_constant();
+    fractal_power = options.power;
+    bounding_tests = 0;
+
+    camera_distance.away = finite_difference_normal(camera.origin);
+    camera_distance.distance = scene_objects_SDF(camera.origin);
+}
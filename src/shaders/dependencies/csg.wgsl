@@ -0,0 +1,238 @@
+// Primitive shapes shared by the preset shapes of the KIFS and the CSG shape every fractal can be combined with
+
+struct Sphere {
+    radius: f32,
+}
+
+fn sphere_SDF(sphere: Sphere, position: vec3<f32>) -> f32 {
+    return length(position) - sphere.radius;
+}
+
+fn sphere_normal(position: vec3<f32>) -> vec3<f32> {
+    return normalize(position);
+}
+
+struct Cylinder {
+    radius: f32,
+    height: f32,
+}
+
+fn cylinder_SDF(cylinder: Cylinder, position: vec3<f32>) -> f32 {
+    let d = abs(vec2(length(position.xy), position.z)) - vec2(cylinder.radius, cylinder.height);
+    return min(max(d.x, d.y), 0.) + length(max(d, vec2(0., 0.)));
+}
+
+fn cylinder_normal(cylinder: Cylinder, position: vec3<f32>) -> vec3<f32> {
+    let radial = normalize(vec3(position.xy, 0.));
+    let axial = vec3(0., 0., sign(position.z));
+    let d = abs(vec2(length(position.xy), position.z)) - vec2(cylinder.radius, cylinder.height);
+
+    if d.x > 0. || d.y > 0. {
+        let outside = max(d, vec2(0., 0.));
+        return normalize(outside.x * radial + outside.y * axial);
+    }
+
+    // Inside the cylinder the closest face determines the gradient
+    return select(axial, radial, d.x > d.y);
+}
+
+struct Box {
+    length: f32,
+    width: f32,
+    height: f32,
+}
+
+fn box_SDF(box: Box, position: vec3<f32>) -> f32 {
+    let q = abs(position) - vec3(box.length, box.width, box.height);
+    return length(max(q, vec3(0., 0., 0.))) + min(max(q.x, max(q.y, q.z)), 0.);
+}
+
+fn box_normal(box: Box, position: vec3<f32>) -> vec3<f32> {
+    let q = abs(position) - vec3(box.length, box.width, box.height);
+    let signs = sign(position);
+
+    if any(q > vec3(0., 0., 0.)) {
+        return signs * normalize(max(q, vec3(0., 0., 0.)));
+    }
+
+    // Inside the box the closest face determines the gradient
+    if q.x > q.y && q.x > q.z {
+        return vec3(signs.x, 0., 0.);
+    } else if q.y > q.z {
+        return vec3(0., signs.y, 0.);
+    }
+    return vec3(0., 0., signs.z);
+}
+
+struct Torus {
+    outer_radius: f32,
+    inner_radius: f32,
+}
+
+fn torus_SDF(torus: Torus, position: vec3<f32>) -> f32 {
+    let q = vec2(length(position.xy) - torus.outer_radius, position.z);
+    return length(q) - torus.inner_radius;
+}
+
+fn torus_normal(torus: Torus, position: vec3<f32>) -> vec3<f32> {
+    let radial = normalize(position.xy);
+    let q = vec2(length(position.xy) - torus.outer_radius, position.z);
+
+    return normalize(vec3(q.x * radial, q.y));
+}
+
+// Polynomial smooth minimum, blending the two distances where they are within k of each other,
+// which melts the shapes together instead of leaving a crease, a k of zero gives the plain minimum
+fn smooth_min(distance_a: f32, distance_b: f32, k: f32) -> f32 {
+    if k <= 0. {
+        return min(distance_a, distance_b);
+    }
+
+    let h = clamp(0.5 + 0.5 * (distance_b - distance_a) / k, 0., 1.);
+    return mix(distance_b, distance_a, h) - k * h * (1. - h);
+}
+
+fn csg_union(distance_a: f32, distance_b: f32, k: f32) -> f32 {
+    return smooth_min(distance_a, distance_b, k);
+}
+
+fn csg_intersection(distance_a: f32, distance_b: f32, k: f32) -> f32 {
+    return -smooth_min(-distance_a, -distance_b, k);
+}
+
+// Shape b carved out of shape a
+fn csg_subtraction(distance_a: f32, distance_b: f32, k: f32) -> f32 {
+    return -smooth_min(-distance_a, distance_b, k);
+}
+
+// Shape the fractal is combined with, scaled by the single size option around its center
+fn csg_shape_SDF(position: vec3<f32>) -> f32 {
+    let local_position = position - options.csg_center;
+    let size = options.csg_size;
+
+    switch options.csg_shape {
+        case CSG_SHAPE_BOX: {
+            return box_SDF(Box(size, size, size), local_position);
+        }
+        case CSG_SHAPE_CYLINDER: {
+            return cylinder_SDF(Cylinder(size, size), local_position);
+        }
+        case CSG_SHAPE_TORUS: {
+            return torus_SDF(Torus(size, 0.3 * size), local_position);
+        }
+        default: {
+            return sphere_SDF(Sphere(size), local_position);
+        }
+    }
+}
+
+// Warped fractal combined with the CSG shape, placed into the scene by its object
+fn csg_SDF(position: vec3<f32>) -> f32 {
+    let distance = warped_scene_SDF(position);
+    let k = options.csg_smoothness;
+
+    switch options.csg_operation {
+        case CSG_UNION: {
+            return csg_union(distance, csg_shape_SDF(position), k);
+        }
+        case CSG_INTERSECTION: {
+            return csg_intersection(distance, csg_shape_SDF(position), k);
+        }
+        case CSG_SUBTRACTION: {
+            return csg_subtraction(distance, csg_shape_SDF(position), k);
+        }
+        default: {
+            return distance;
+        }
+    }
+}
+
+// Shape has no density of its own, so in the volume it only masks the fractal
+fn csg_density(position: vec3<f32>) -> f32 {
+    let density = warped_scene_density(position);
+
+    switch options.csg_operation {
+        case CSG_INTERSECTION: {
+            return select(density, 0., csg_shape_SDF(position) > 0.);
+        }
+        case CSG_SUBTRACTION: {
+            return select(density, 0., csg_shape_SDF(position) < 0.);
+        }
+        default: {
+            return density;
+        }
+    }
+}
+
+// Distances outside every object, returned when the scene is empty
+const EMPTY_SCENE_DISTANCE = 1e10;
+
+fn object_position(object: SceneObject, position: vec3<f32>) -> vec3<f32> {
+    return object.inverse_rotation * (position - object.translation) / object.scale;
+}
+
+// Distance in the frame of the object, scaled back into the scene
+fn object_SDF(object: SceneObject, position: vec3<f32>) -> f32 {
+    let local_position = object_position(object, position);
+
+    var distance: f32;
+    switch object.kind {
+        case OBJECT_SPHERE: {
+            distance = sphere_SDF(Sphere(1.), local_position);
+        }
+        case OBJECT_BOX: {
+            distance = box_SDF(Box(1., 1., 1.), local_position);
+        }
+        case OBJECT_CYLINDER: {
+            distance = cylinder_SDF(Cylinder(1., 1.), local_position);
+        }
+        case OBJECT_TORUS: {
+            distance = torus_SDF(Torus(1., 0.3), local_position);
+        }
+        default: {
+            distance = csg_SDF(local_position);
+        }
+    }
+
+    return distance * object.scale;
+}
+
+// Union of every enabled object, which is what every other part of the renderer sees as the scene
+fn scene_objects_SDF(position: vec3<f32>) -> f32 {
+    var distance = EMPTY_SCENE_DISTANCE;
+    for (var i = 0u; i < scene_objects.count; i++) {
+        distance = min(distance, object_SDF(scene_objects.objects[i], position));
+    }
+
+    return distance;
+}
+
+// Object whose surface is nearest, which at a hit is the object that was hit
+fn scene_object_at(position: vec3<f32>) -> u32 {
+    var nearest = 0u;
+    var distance = EMPTY_SCENE_DISTANCE;
+    for (var i = 0u; i < scene_objects.count; i++) {
+        let object_distance = object_SDF(scene_objects.objects[i], position);
+        if object_distance < distance {
+            nearest = i;
+            distance = object_distance;
+        }
+    }
+
+    return nearest;
+}
+
+// Primitives are solid, so in the volume they hide whatever fractal is inside them
+fn scene_objects_density(position: vec3<f32>) -> f32 {
+    var density = 0.;
+    for (var i = 0u; i < scene_objects.count; i++) {
+        let object = scene_objects.objects[i];
+        if object.kind == OBJECT_FRACTAL {
+            density = max(density, csg_density(object_position(object, position)));
+        } else if object_SDF(object, position) < 0. {
+            density = 1.;
+        }
+    }
+
+    return density;
+}
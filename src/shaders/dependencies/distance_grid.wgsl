@@ -0,0 +1,34 @@
+struct DistanceGridUniform {
+    resolution: u32,
+    extent: f32,
+    first_layer: u32,
+}
+
+@group(2)
+@binding(0)
+var<uniform> distance_grid: DistanceGridUniform;
+
+@group(2)
+@binding(1)
+var<storage, read_write> distance_grid_samples: array<f32>;
+
+// Distance estimates on a cube of samples centered on the origin, a band of layers along z at a time
+// Note: has to match MeshSettings in mesh.rs
+@compute
+@workgroup_size(DISTANCE_GRID_WORKGROUP_SIZE, DISTANCE_GRID_WORKGROUP_SIZE, DISTANCE_GRID_WORKGROUP_SIZE)
+fn distance_grid_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let resolution = distance_grid.resolution;
+    let sample = id + vec3(0u, 0u, distance_grid.first_layer);
+    if any(sample >= vec3(resolution)) {
+        return;
+    }
+
+    fractal_constant = animated_constant();
+    fractal_power = options.power;
+    bounding_tests = 0;
+
+    let spacing = 2. * distance_grid.extent / f32(resolution - 1u);
+    let position = vec3<f32>(sample) * spacing - distance_grid.extent;
+    distance_grid_samples[(sample.z * resolution + sample.y) * resolution + sample.x] =
+        scene_objects_SDF(position);
+}
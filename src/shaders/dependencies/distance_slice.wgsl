@@ -0,0 +1,50 @@
+struct DistanceSliceUniform {
+    axis: u32,
+    offset: f32,
+    extent: f32,
+}
+
+@group(2)
+@binding(0)
+var<uniform> distance_slice: DistanceSliceUniform;
+
+@group(2)
+@binding(1)
+var distance_slice_texture: texture_storage_2d<rgba8unorm, write>;
+
+// Point of the plane across the slice axis, the coordinates follow the other two axes in order
+fn distance_slice_position(coordinates: vec2<f32>) -> vec3<f32> {
+    switch distance_slice.axis {
+        case WARP_AXIS_X: {
+            return vec3(distance_slice.offset, coordinates);
+        }
+        case WARP_AXIS_Y: {
+            return vec3(coordinates.x, distance_slice.offset, coordinates.y);
+        }
+        default: {
+            return vec3(coordinates, distance_slice.offset);
+        }
+    }
+}
+
+// Distance field of the scene on the slice plane, colored like the distance field debug view
+// Note: has to match distance_slice_window in gui.rs
+@compute
+@workgroup_size(DISTANCE_SLICE_WORKGROUP_SIZE, DISTANCE_SLICE_WORKGROUP_SIZE)
+fn distance_slice_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let texture_size = textureDimensions(distance_slice_texture);
+    if any(id.xy >= texture_size) {
+        return;
+    }
+
+    fractal_constant = animated_constant();
+    fractal_power = options.power;
+    bounding_tests = 0;
+
+    // Square centered on the slice axis, with the vertical coordinate growing upwards
+    let uv_position = 2. * (vec2<f32>(id.xy) + 0.5) / vec2<f32>(texture_size) - 1.;
+    let coordinates = distance_slice.extent * vec2(uv_position.x, -uv_position.y);
+    let distance = scene_objects_SDF(distance_slice_position(coordinates));
+
+    textureStore(distance_slice_texture, id.xy, vec4(distance_field_color(distance), 1.));
+}
@@ -3,57 +3,776 @@ struct Ray {
     direction: vec3<f32>,
 }
 
-fn raymarch(ray: Ray) -> vec4<f32> {
-    var output_color = vec4(options.background_color, 1.);
+// Whether the ray of the current pixel is recorded into ray_path
+var<private> is_inspected_pixel: bool;
 
-    var travel_distance = 0.;
-    var position = ray.origin;
-    var i: i32;
-    for (i = 0; i < options.max_iterations && travel_distance < options.max_distance; i++) {
-        let distance = scene_SDF(position);
+// Julia parameters used by scene_SDF, taken from the options unless varied by the parameter space preview
+var<private> fractal_constant: Quaternion;
+var<private> fractal_power: f32;
+
+// Auxiliary layers of the current pixel, filled in by raymarch and left at the background values otherwise
+struct Aov {
+    // Distance along the viewing direction, not along the ray
+    depth: f32,
+    normal: vec3<f32>,
+    iterations: f32,
+}
+
+var<private> aov: Aov;
+
+// Number of distance evaluations answered by the bounding volume of the fractal instead of the full estimate
+var<private> bounding_tests: i32;
+
+// Largest finite 16 bit float, rounding anything larger is undefined
+const MAX_HALF = 65504.;
+
+// Julia constant moved along a small path around the configured one, in its real and first imaginary part
+fn animated_constant() -> Quaternion {
+    let angle = options.animation_speed * options.animation_time;
+    var offset = vec2(0.);
+    switch options.constant_animation {
+        case CONSTANT_ANIMATION_CIRCLE: {
+            offset = vec2(cos(angle), sin(angle));
+        }
+        case CONSTANT_ANIMATION_LISSAJOUS: {
+            offset = vec2(sin(3. * angle), sin(2. * angle));
+        }
+        default: {}
+    }
+
+    return Quaternion(options.constant.inner + options.animation_radius * vec4(offset, 0., 0.));
+}
+
+// Rounds intermediate values to 16 bit float precision when simulating half precision raymarching
+fn reduce_precision(value: vec4<f32>) -> vec4<f32> {
+    if !bool(options.is_half_precision) {
+        return value;
+    }
+
+    let clamped = clamp(value, vec4(-MAX_HALF), vec4(MAX_HALF));
+    return vec4(
+        unpack2x16float(pack2x16float(clamped.xy)),
+        unpack2x16float(pack2x16float(clamped.zw)),
+    );
+}
+
+fn reduce_precision_f32(value: f32) -> f32 {
+    return reduce_precision(vec4(value)).x;
+}
+
+fn reduce_precision_vec3(value: vec3<f32>) -> vec3<f32> {
+    return reduce_precision(vec4(value, 0.)).xyz;
+}
+
+// Signed distance to the clipping plane, positive on the side which is cut away
+fn clip_plane_SDF(position: vec3<f32>) -> f32 {
+    return dot(position, options.clip_normal) - options.clip_offset;
+}
+
+fn is_cutaway() -> bool {
+    return any(options.cutaway_signs != vec3(0.));
+}
+
+// Signed distance into the cut away octant, positive inside of it, axes without a sign leave it open along them
+fn cutaway_SDF(position: vec3<f32>) -> f32 {
+    var distance = options.max_distance;
+    for (var i = 0; i < 3; i++) {
+        if options.cutaway_signs[i] != 0. {
+            distance = min(distance, options.cutaway_signs[i] * position[i]);
+        }
+    }
+
+    return distance;
+}
+
+// Wall of the octant closest to the position, facing into the octant
+fn cutaway_normal(position: vec3<f32>) -> vec3<f32> {
+    var normal = vec3(0.);
+    var distance = options.max_distance;
+    for (var i = 0; i < 3; i++) {
+        let side = options.cutaway_signs[i];
+        if side != 0. && side * position[i] < distance {
+            distance = side * position[i];
+            normal = vec3(0.);
+            normal[i] = side;
+        }
+    }
+
+    return normal;
+}
+
+fn is_clipped(position: vec3<f32>) -> bool {
+    return (bool(options.is_clipping) && clip_plane_SDF(position) > 0.) || (is_cutaway() && cutaway_SDF(position) > 0.)
+        || (bool(options.is_floor_cut) && position.z < options.floor_height);
+}
+
+// Scene with the part in front of the clipping plane and the cutaway octant removed, which is what rays are marched against
+fn clipped_SDF(position: vec3<f32>) -> f32 {
+    var distance = scene_objects_SDF(position);
+    if bool(options.is_clipping) {
+        distance = max(distance, clip_plane_SDF(position));
+    }
+    if is_cutaway() {
+        distance = max(distance, cutaway_SDF(position));
+    }
+    // Cut lies just below the floor, so the floor is hit before the flat underside of the scene
+    if bool(options.is_floor_cut) {
+        let above_floor_SDF = options.floor_height - 2. * options.epsilon - position.z;
+        distance = csg_intersection(distance, above_floor_SDF, 0.);
+    }
+
+    return distance;
+}
+
+// Generic normal from central differences, costs 6 extra distance evaluations
+fn finite_difference_normal(position: vec3<f32>) -> vec3<f32> {
+    let h_x = vec3(options.epsilon, 0., 0.);
+    let h_y = vec3(0., options.epsilon, 0.);
+    let h_z = vec3(0., 0., options.epsilon);
+
+    let sdf_dx = scene_objects_SDF(position + h_x) - scene_objects_SDF(position - h_x);
+    let sdf_dy = scene_objects_SDF(position + h_y) - scene_objects_SDF(position - h_y);
+    let sdf_dz = scene_objects_SDF(position + h_z) - scene_objects_SDF(position - h_z);
+
+    return normalize(vec3(sdf_dx, sdf_dy, sdf_dz));
+}
+
+// Every fractal shader provides get_normal, using an analytic gradient where one is known, which
+// only describes the unwarped fractal alone at the origin and not the surface of other objects
+fn surface_normal(position: vec3<f32>) -> vec3<f32> {
+    if bool(options.is_analytic_normal) && bool(scene_objects.is_plain) && options.csg_operation == CSG_OFF && !is_warped() {
+        return get_normal(position);
+    }
+
+    return finite_difference_normal(position);
+}
+
+// Exponent of the Blinn-Phong highlights, higher ones give smaller and sharper highlights
+const SPECULAR_EXPONENT = 32.;
+
+// Light reaching a surface, the diffuse part is tinted by its color while highlights keep the color of the lights
+struct SurfaceLight {
+    diffuse: vec3<f32>,
+    specular: vec3<f32>,
+}
+
+// Lambertian lighting by every enabled light of the rig, on top of the ambient light, and Blinn-Phong
+// highlights towards the viewer, with the shadows and occlusion the object opted into
+fn surface_light(position: vec3<f32>, normal: vec3<f32>, view_direction: vec3<f32>, shading: u32) -> SurfaceLight {
+    // Shadow rays start slightly above the surface, so they do not hit it right away
+    let shadow_origin = position + 2. * options.epsilon * normal;
+    var light = SurfaceLight(vec3(lighting.ambient), vec3(0.));
+    for (var i = 0; i < MAX_LIGHTS; i++) {
+        let rig_light = lighting.lights[i];
+        let lambert = max(dot(normal, rig_light.direction), 0.);
+        if bool(rig_light.is_enabled) && lambert > 0. {
+            var shadow = 1.;
+            if (shading & SHADING_SOFT_SHADOWS) != 0u {
+                shadow = soft_shadow(shadow_origin, rig_light.direction);
+            }
+            let radiance = rig_light.intensity * rig_light.color * shadow;
+            let half_direction = normalize(rig_light.direction + view_direction);
+            light.diffuse += radiance * lambert;
+            light.specular += radiance * pow(max(dot(normal, half_direction), 0.), SPECULAR_EXPONENT);
+        }
+    }
+
+    if (shading & SHADING_AMBIENT_OCCLUSION) != 0u {
+        light.diffuse *= ambient_occlusion(position, normal);
+    }
+    return light;
+}
+
+// Surface lies on the clipping plane wherever the plane is further away than the fractal, which caps the cut
+fn is_clipping_cap(position: vec3<f32>) -> bool {
+    return bool(options.is_clipping) && clip_plane_SDF(position) > scene_objects_SDF(position)
+        && !(is_cutaway() && cutaway_SDF(position) > clip_plane_SDF(position));
+}
+
+// Walls of the cutaway octant cap it like the clipping plane, where they are further away than both
+fn is_cutaway_cap(position: vec3<f32>) -> bool {
+    let distance = cutaway_SDF(position);
+    return is_cutaway() && distance > scene_objects_SDF(position)
+        && !(bool(options.is_clipping) && clip_plane_SDF(position) >= distance);
+}
+
+// Cap of a cut is plain, whichever object it cuts through
+fn surface_material(position: vec3<f32>) -> Material {
+    if is_clipping_cap(position) || is_cutaway_cap(position) {
+        return Material(vec3(1.), 0., vec3(0.), 0.);
+    }
+
+    return scene_objects.objects[scene_object_at(position)].material;
+}
+
+// View direction points from the surface towards the viewer
+fn surface_color(position: vec3<f32>, view_direction: vec3<f32>) -> vec3<f32> {
+    var normal: vec3<f32>;
+    var color: vec3<f32>;
+    if is_clipping_cap(position) {
+        normal = options.clip_normal;
+        color = options.clip_color;
+    } else if is_cutaway_cap(position) {
+        normal = cutaway_normal(position);
+        color = options.clip_color;
+    } else {
+        normal = surface_normal(position);
+        color = options.fractal_color;
+    }
+    aov.normal = normal;
+
+    switch options.coloring_mode {
+        // Normal mapped from [-1, 1] to RGB, unlit so the gradient is shown as is
+        case COLORING_MODE_NORMAL: {
+            return 0.5 + 0.5 * normal;
+        }
+        default: {
+            let material = surface_material(position);
+            let light = surface_light(position, normal, view_direction, options.fractal_shading);
+            return light.diffuse * color * material.albedo + light.specular * material.specular + material.emissive;
+        }
+    }
+}
+
+const BAILOUT_MAX_ITERATIONS = 0u;
+const BAILOUT_MAX_DISTANCE = 1u;
+const BAILOUT_HIT = 2u;
+
+struct MarchResult {
+    position: vec3<f32>,
+    steps: i32,
+    // Number of over-relaxed steps which overshot and had to be taken back
+    corrections: i32,
+    bailout: u32,
+    // Continuous step count, interpolated from how far the final step went past the bailout threshold
+    smooth_steps: f32,
+    // Last distance estimate and how far the ray got, both shown by the debug views
+    distance: f32,
+    travel_distance: f32,
+    // Steps which only needed the bounding volume of the fractal
+    bounding_tests: i32,
+}
+
+// Over-relaxed sphere tracing, falling back to plain sphere tracing whenever a step overshoots
+fn march(ray: Ray) -> MarchResult {
+    var result = MarchResult(ray.origin, 0, 0, BAILOUT_MAX_ITERATIONS, f32(options.max_iterations), 0., 0., 0);
+    let initial_bounding_tests = bounding_tests;
+
+    // Rays start where they enter the bounding volume, those passing it by go straight to the max distance
+    let bounds = bounding_volume_span(ray);
+    var relaxation = options.relaxation;
+    var travel_distance = max(bounds.x, 0.);
+    if bounds.y < travel_distance {
+        travel_distance = options.max_distance;
+    }
+    var previous_distance = 0.;
+    var step_length = 0.;
+    for (; result.steps < options.max_iterations; result.steps++) {
+        if travel_distance >= options.max_distance {
+            result.bailout = BAILOUT_MAX_DISTANCE;
+            let overshoot = (travel_distance - options.max_distance) / max(step_length, options.epsilon);
+            result.smooth_steps = f32(result.steps) - clamp(overshoot, 0., 1.);
+            break;
+        }
+
+        result.position = reduce_precision_vec3(ray.origin + travel_distance * ray.direction);
+        let distance = reduce_precision_f32(clipped_SDF(result.position));
+        result.distance = distance;
+
+        if is_inspected_pixel {
+            ray_path.steps[result.steps] = RayStep(result.position, distance);
+            ray_path.length = u32(result.steps) + 1u;
+        }
+
+        // Unbounding spheres of the last two positions do not overlap, so a surface could have been skipped
+        let is_overstep = relaxation > 1. && distance + previous_distance < step_length;
+        if is_overstep {
+            travel_distance -= step_length;
+            step_length = 0.;
+            relaxation = 1.;
+            result.corrections++;
+            continue;
+        }
 
         if distance < options.epsilon {
-            let normal = get_normal(position);
-            let diffuse = 0.1 + 0.9 * clamp(dot(normal, vec3(1., 1., 1.)), 0., 1.);
+            result.bailout = BAILOUT_HIT;
+            // Distances shrink roughly geometrically near the surface, so the ratio of the last two
+            // tells what fraction of a step it took to get below epsilon
+            let clamped_distance = max(distance, 1e-3 * options.epsilon);
+            let ratio = clamped_distance / previous_distance;
+            let fraction = log(clamped_distance / options.epsilon) / log(ratio);
+            result.smooth_steps = f32(result.steps) - select(0., clamp(fraction, 0., 1.), ratio > 0. && ratio < 1.);
+            break;
+        }
+
+        step_length = min(relaxation * options.distance_fudge * distance, options.max_step);
+        previous_distance = distance;
+        travel_distance = reduce_precision_f32(travel_distance + step_length);
+    }
+
+    result.travel_distance = min(travel_distance, options.max_distance);
+    result.bounding_tests = bounding_tests - initial_bounding_tests;
+    atomicAdd(&march_statistics[result.bailout], 1u);
+    return result;
+}
+
+// Step length inside the set, where distance estimates are not reliable
+const ABSORPTION_STEP = 0.01;
+
+// Continues the ray through the set and returns the fraction of each color channel passing through
+// by Beer-Lambert's law, outside of the set we keep sphere tracing to skip empty space
+fn transmittance(ray: Ray, entry_position: vec3<f32>) -> vec3<f32> {
+    var travel_distance = distance(ray.origin, entry_position) + ABSORPTION_STEP;
+    var thickness = 0.;
+    for (var i = 0; i < options.max_iterations && travel_distance < options.max_distance; i++) {
+        let sdf = clipped_SDF(ray.origin + travel_distance * ray.direction);
+
+        if sdf < options.epsilon {
+            thickness += ABSORPTION_STEP;
+            travel_distance += ABSORPTION_STEP;
+        } else {
+            travel_distance += max(sdf, ABSORPTION_STEP);
+        }
+    }
+
+    let absorption = options.absorption_density * (1. - options.absorption_color);
+    return exp(-absorption * thickness);
+}
+
+// Step length inside the set, where the distance estimates only hold outside of it
+const INTERIOR_STEP = 0.01;
+// Halvings of the last step, refining where the ray leaves the set
+const INTERIOR_REFINEMENTS = 8;
+
+// Orbits which never escape have the full density
+fn is_inside_set(position: vec3<f32>) -> bool {
+    return scene_density(position) >= 1.;
+}
+
+// Distance along a ray starting inside the set to where it leaves it, negative if it never does
+fn interior_distance(ray: Ray) -> f32 {
+    var travel_distance = 0.;
+    for (var i = 0; i < options.max_iterations && travel_distance < options.max_distance; i++) {
+        travel_distance += INTERIOR_STEP;
+        if is_inside_set(ray.origin + travel_distance * ray.direction) {
+            continue;
+        }
+
+        var inside_distance = travel_distance - INTERIOR_STEP;
+        var outside_distance = travel_distance;
+        for (var j = 0; j < INTERIOR_REFINEMENTS; j++) {
+            let middle_distance = 0.5 * (inside_distance + outside_distance);
+            if is_inside_set(ray.origin + middle_distance * ray.direction) {
+                inside_distance = middle_distance;
+            } else {
+                outside_distance = middle_distance;
+            }
+        }
+        return inside_distance;
+    }
+
+    return -1.;
+}
+
+// Boundary of the set seen from within, lit from the camera as the lights of the rig do not reach inside
+fn interior_raymarch(ray: Ray) -> vec4<f32> {
+    let travel_distance = interior_distance(ray);
+    if travel_distance < 0. {
+        return vec4(options.interior_color * lighting.ambient, 1.);
+    }
+
+    let position = ray.origin + travel_distance * ray.direction;
+    // Normal of the set points outwards, away from the viewer inside of it
+    let normal = -surface_normal(position);
+    aov.depth = dot(position - ray.origin, -camera.matrix[0]);
+    aov.normal = normal;
+
+    let light = lighting.ambient + max(dot(normal, -ray.direction), 0.);
+    return vec4(light * options.interior_color, 1.);
+}
 
-            output_color = vec4(diffuse * options.fractal_color, 1.);
+// Density is only sampled this close to the surface, further away the ray sphere traces through empty space
+const VOLUME_SHELL = 0.05;
+// Rays stop once almost nothing behind them could be seen anyway
+const MIN_TRANSMITTANCE = 0.01;
+
+// Emission-absorption volume rendering of the density every fractal shader provides with scene_density
+fn volume_march(ray: Ray) -> vec4<f32> {
+    var color = vec3(0.);
+    var transmittance = 1.;
+    var travel_distance = 0.;
+    for (var i = 0; i < options.max_iterations && travel_distance < options.max_distance; i++) {
+        let position = ray.origin + travel_distance * ray.direction;
+
+        let distance = clipped_SDF(position);
+        if distance > VOLUME_SHELL {
+            travel_distance += distance - 0.5 * VOLUME_SHELL;
+            continue;
+        }
+
+        // Denser parts emit more of the fractal color and hide more of what lies behind them
+        let density = select(scene_objects_density(position), 0., is_clipped(position));
+        let opacity = 1. - exp(-options.volume_density * density * options.volume_step);
+        color += transmittance * opacity * options.fractal_color;
+        transmittance *= 1. - opacity;
+        if transmittance < MIN_TRANSMITTANCE {
             break;
         }
 
+        travel_distance += options.volume_step;
+    }
+
+    return vec4(color + transmittance * options.background_color, 1.);
+}
+
+// Higher values give harder shadows
+const SHADOW_SOFTNESS = 16.;
+const SHADOW_STEPS = 64;
+// Floor fades into the background, hiding the aliasing of the checkerboard towards the horizon
+const FLOOR_FADE_DISTANCE = 50.;
+
+// Distance along the ray to the floor, negative when the ray never reaches it
+fn floor_distance(ray: Ray) -> f32 {
+    if !bool(options.is_floor) || ray.direction.z >= 0. {
+        return -1.;
+    }
+
+    return (options.floor_height - ray.origin.z) / ray.direction.z;
+}
+
+// Soft shadow estimated from the closest approach of a ray marched towards the light
+fn soft_shadow(position: vec3<f32>, light_direction: vec3<f32>) -> f32 {
+    var shadow = 1.;
+    var travel_distance = 10. * options.epsilon;
+    for (var i = 0; i < SHADOW_STEPS && travel_distance < options.max_distance; i++) {
+        let distance = clipped_SDF(position + travel_distance * light_direction);
+        if distance < options.epsilon {
+            return 0.;
+        }
+
+        shadow = min(shadow, SHADOW_SOFTNESS * distance / travel_distance);
         travel_distance += distance;
-        position = ray.origin + travel_distance * ray.direction;
     }
 
-    let heatmap_color = vec4(f32(i) / f32(options.max_iterations) * options.fractal_color, 1.);
-    return select(output_color, heatmap_color, bool(options.is_heatmap));
+    return clamp(shadow, 0., 1.);
 }
 
-struct VertexOutput {
-    @builtin(position) position: vec4<f32>,
+// Ambient occlusion from how much closer the fractal is than points sampled along the normal
+fn ambient_occlusion(position: vec3<f32>, normal: vec3<f32>) -> f32 {
+    var occlusion = 0.;
+    var weight = 1.;
+    for (var i = 0; i < 5; i++) {
+        let height = 0.01 + 0.03 * f32(i);
+        occlusion += weight * max(height - clipped_SDF(position + height * normal), 0.);
+        weight *= 0.95;
+    }
+
+    return clamp(1. - 3. * occlusion, 0., 1.);
 }
 
-@vertex
-fn vs_main(
-    @builtin(vertex_index) in_vertex_index: u32,
-    @builtin(instance_index) in_instance_index: u32,
-) -> VertexOutput {
-    var out: VertexOutput;
+// Floor only receives light, so it has no influence on how the fractal itself is shaded
+fn floor_color(ray: Ray, travel_distance: f32) -> vec3<f32> {
+    let position = ray.origin + travel_distance * ray.direction;
 
-    let x = f32((in_vertex_index & 1u) ^ in_instance_index);
-    let y = f32((in_vertex_index >> 1u) ^ in_instance_index);
+    var color = options.floor_color;
+    if options.floor_pattern == FLOOR_PATTERN_CHECKERBOARD {
+        let square = floor(position.xy / options.floor_checker_scale);
+        color *= select(1., 0.5, (i32(square.x) + i32(square.y)) % 2 != 0);
+    }
 
-    out.position = vec4<f32>(2. * vec2(x, y) - 1., 0., 1.);
-    return out;
+    // Lights below the floor do not reach it
+    var light = vec3(lighting.ambient);
+    for (var i = 0; i < MAX_LIGHTS; i++) {
+        let rig_light = lighting.lights[i];
+        if bool(rig_light.is_enabled) && rig_light.direction.z > 0. {
+            var shadow = 1.;
+            if (options.floor_shading & SHADING_SOFT_SHADOWS) != 0u {
+                shadow = soft_shadow(position, rig_light.direction);
+            }
+            light += rig_light.intensity * rig_light.color * shadow;
+        }
+    }
+
+    if (options.floor_shading & SHADING_AMBIENT_OCCLUSION) != 0u {
+        light *= ambient_occlusion(position, vec3(0., 0., 1.));
+    }
+    color *= light;
+    return mix(color, options.background_color, clamp(travel_distance / FLOOR_FADE_DISTANCE, 0., 1.));
 }
 
-@fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let uv_position = 2. * in.position.xy / screen.height - vec2(screen.aspect_ratio, 1.);
+// Color seen in a mirror surface, shaded without further reflections
+fn reflected_color(ray: Ray, position: vec3<f32>) -> vec3<f32> {
+    let normal = surface_normal(position);
+    let direction = reflect(ray.direction, normal);
+    let reflected_ray = Ray(position + 2. * options.epsilon * normal, direction);
+
+    // Only the primary ray is recorded and written into the output layers
+    let primary_aov = aov;
+    let is_primary_inspected = is_inspected_pixel;
+    is_inspected_pixel = false;
+
+    let result = march(reflected_ray);
+    let floor_travel_distance = floor_distance(reflected_ray);
+    var color = options.background_color;
+    if floor_travel_distance > 0. && floor_travel_distance < options.max_distance
+        && (result.bailout != BAILOUT_HIT || floor_travel_distance < distance(reflected_ray.origin, result.position)) {
+        color = floor_color(reflected_ray, floor_travel_distance);
+    } else if result.bailout == BAILOUT_HIT {
+        color = surface_color(result.position, -direction);
+    }
+
+    aov = primary_aov;
+    is_inspected_pixel = is_primary_inspected;
+    return color;
+}
+
+// Blue to red color ramp for values in [0, 1]
+fn false_color(value: f32) -> vec3<f32> {
+    return clamp(1.5 - abs(4. * value - vec3(3., 2., 1.)), vec3(0.), vec3(1.));
+}
+
+fn raymarch(ray: Ray) -> vec4<f32> {
+    // Distance estimates are meaningless inside the set, where the march would stop right away
+    if bool(options.is_interior_visible) && is_inside_set(ray.origin) {
+        return interior_raymarch(ray);
+    }
+
+    let result = march(ray);
+    aov.iterations = f32(result.steps);
+
+    // Floor is only seen where it lies in front of the fractal
+    let floor_travel_distance = floor_distance(ray);
+    let is_floor_hit = floor_travel_distance > 0. && floor_travel_distance < options.max_distance
+        && (result.bailout != BAILOUT_HIT || floor_travel_distance < distance(ray.origin, result.position));
+
+    var output_color = vec4(options.background_color, 1.);
+    if is_floor_hit {
+        // First matrix column points from the origin towards the camera
+        aov.depth = floor_travel_distance * dot(ray.direction, -camera.matrix[0]);
+        aov.normal = vec3(0., 0., 1.);
+        output_color = vec4(floor_color(ray, floor_travel_distance), 1.);
+    } else if result.bailout == BAILOUT_HIT {
+        aov.depth = dot(result.position - ray.origin, -camera.matrix[0]);
+        output_color = vec4(surface_color(result.position, -ray.direction), 1.);
+
+        let reflectivity = surface_material(result.position).reflectivity;
+        if reflectivity > 0. && options.coloring_mode != COLORING_MODE_NORMAL {
+            output_color = vec4(mix(output_color.rgb, reflected_color(ray, result.position), reflectivity), 1.);
+        }
+
+        if bool(options.is_translucent) {
+            let transmitted = transmittance(ray, result.position);
+            output_color = vec4(mix(output_color.rgb, options.background_color, transmitted), 1.);
+        }
+    }
+
+    let step_ratio = f32(result.steps) / f32(options.max_iterations);
+
+    // Heatmap colors the background as well, so it is handled outside of the hit branch
+    if options.coloring_mode == COLORING_MODE_HEATMAP {
+        let steps = select(f32(result.steps), result.smooth_steps, bool(options.is_smooth_heatmap));
+        let ramp = options.heatmap_offset + options.heatmap_scale * steps / f32(options.max_iterations);
+        output_color = vec4(clamp(ramp, 0., 1.) * options.fractal_color, 1.);
+    }
+
+    // False color overlays keep some of the shading, so the shape stays recognizable
+    var overlay_color: vec3<f32>;
+    switch options.debug_view {
+        case DEBUG_VIEW_STEP_COUNT: {
+            overlay_color = false_color(step_ratio);
+        }
+        case DEBUG_VIEW_OVERSTEP: {
+            overlay_color = false_color(clamp(f32(result.corrections) / MAX_DISPLAYED_CORRECTIONS, 0., 1.));
+        }
+        case DEBUG_VIEW_BAILOUT: {
+            overlay_color = BAILOUT_COLORS[result.bailout];
+        }
+        // Hits end below epsilon, so anything warm stopped short of the surface
+        case DEBUG_VIEW_FINAL_DISTANCE: {
+            let octaves = log2(max(abs(result.distance) / options.epsilon, 1.));
+            overlay_color = false_color(clamp(octaves / MAX_DISPLAYED_DISTANCE_OCTAVES, 0., 1.));
+        }
+        case DEBUG_VIEW_RAY_LENGTH: {
+            overlay_color = false_color(result.travel_distance / options.max_distance);
+        }
+        // Share of the steps which never had to evaluate the fractal itself
+        case DEBUG_VIEW_BOUNDING_TESTS: {
+            overlay_color = false_color(f32(result.bounding_tests) / f32(max(result.steps, 1)));
+        }
+        default: {
+            return output_color;
+        }
+    }
+
+    let shading = dot(output_color.rgb, vec3(0.2126, 0.7152, 0.0722));
+    return vec4(mix(vec3(shading), overlay_color, DEBUG_OVERLAY_OPACITY), 1.);
+}
+
+const DEBUG_OVERLAY_OPACITY = 0.75;
+const MAX_DISPLAYED_CORRECTIONS = 8.;
+// Final distances are shown on a logarithmic scale up to this many doublings of epsilon
+const MAX_DISPLAYED_DISTANCE_OCTAVES = 16.;
+// Red for max iterations, blue for max distance and green for hits
+const BAILOUT_COLORS = array(vec3(1., 0., 0.), vec3(0., 0., 1.), vec3(0., 1., 0.));
+
+// Spacing of the distance field contour lines in world units
+const CONTOUR_SPACING = 0.05;
+const CONTOUR_WIDTH = 0.005;
+const TAU = 6.28318530718;
+
+// Warm outside and cold inside the surface, fading out away from it, with the surface itself drawn in white
+fn distance_field_color(distance: f32) -> vec3<f32> {
+    var color = select(vec3(0.65, 0.85, 1.), vec3(0.9, 0.6, 0.3), distance > 0.);
+    color *= 1. - exp(-6. * abs(distance));
+    color *= 0.8 + 0.2 * cos(TAU * distance / CONTOUR_SPACING);
+    return mix(color, vec3(1.), 1. - smoothstep(0., CONTOUR_WIDTH, abs(distance)));
+}
+
+// False color distance field on the plane facing the camera
+fn distance_field_slice(ray: Ray) -> vec4<f32> {
+    // First matrix column points from the origin towards the camera
+    let plane_normal = camera.matrix[0];
+    let travel_distance = (options.slice_offset - dot(ray.origin, plane_normal)) / dot(ray.direction, plane_normal);
+
+    if travel_distance <= 0. || travel_distance > options.max_distance {
+        return vec4(options.background_color, 1.);
+    }
+
+    let distance = scene_objects_SDF(ray.origin + travel_distance * ray.direction);
+    return vec4(distance_field_color(distance), 1.);
+}
+
+// Color seen along the ray in the render mode of the current fractal group
+fn trace(ray: Ray) -> vec4<f32> {
+    switch options.render_mode {
+        case RENDER_MODE_VOLUMETRIC: {
+            return volume_march(ray);
+        }
+        default: {
+            return raymarch(ray);
+        }
+    }
+}
+
+fn in_region_of_interest(position: vec2<f32>) -> bool {
+    let offset = abs(position - sample.roi_center);
+
+    switch sample.roi_mode {
+        // Circle with radius roi_extent.x
+        case 1u: {
+            return length(offset) <= sample.roi_extent.x;
+        }
+        // Rectangle with half extents roi_extent
+        case 2u: {
+            return all(offset <= sample.roi_extent);
+        }
+        default: {
+            return false;
+        }
+    }
+}
+
+fn is_sampled(position: vec2<f32>) -> bool {
+    // Inspected ray has to be recorded no matter the sampling rate
+    if is_inspected_pixel {
+        return true;
+    }
+
+    let rate = select(sample.outside_rate, sample.inside_rate, in_region_of_interest(position));
+    return random_float(pixel_hash(vec2<u32>(position), sample.index)) < rate;
+}
+
+// Direction on the sphere around the camera, with the view direction in the center of the panorama
+fn equirectangular_direction(pixel_position: vec2<f32>, view_size: vec2<f32>) -> vec3<f32> {
+    let longitude = TAU * (pixel_position.x / view_size.x - 0.5);
+    // Top of the screen looks straight up
+    let latitude = 0.5 * TAU * (0.5 - pixel_position.y / view_size.y);
+
+    let horizontal = cos(longitude) * -camera.matrix[0] + sin(longitude) * camera.matrix[1];
+    return cos(latitude) * horizontal + sin(latitude) * camera.matrix[2];
+}
+
+// Ray through the given pixel of a view, eye is -1 for the left eye, 1 for the right eye and 0 without stereo
+fn camera_ray(pixel_position: vec2<f32>, view_size: vec2<f32>, eye: f32) -> Ray {
+    let eye_origin = camera.origin + 0.5 * eye * options.eye_separation * camera.matrix[1];
+    if options.projection == PROJECTION_EQUIRECTANGULAR {
+        return Ray(eye_origin, equirectangular_direction(pixel_position, view_size));
+    }
+
+    let uv_position = camera.view_scale * (2. * pixel_position / view_size.y - vec2(view_size.x / view_size.y, 1.));
 
     // Matrix columns are the transformed vector basis, we use those to find pixel positions in space
     // Note: uv_position.y is flipped (-1 at top of screen and 1 at bottom)
-    let ray_direction = normalize(uv_position.x * camera.matrix[1] - uv_position.y * camera.matrix[2] - camera.matrix[0]);
-    let ray = Ray(camera.origin, ray_direction);
+    let view_direction = uv_position.x * camera.matrix[1] - uv_position.y * camera.matrix[2] - camera.matrix[0];
+
+    // Eyes are shifted sideways and aimed at the same point on the plane through the orbited origin,
+    // which keeps objects at that distance on the screen plane instead of converging at infinity
+    let convergence_point = camera.origin + length(camera.origin) * view_direction;
+
+    return Ray(eye_origin, normalize(convergence_point - eye_origin));
+}
+
+fn shade(ray: Ray) -> vec4<f32> {
+    switch options.debug_view {
+        case DEBUG_VIEW_DISTANCE_FIELD: {
+            // Ray is still marched when inspected, so its path can be compared with the slice
+            if is_inspected_pixel {
+                march(ray);
+            }
+
+            return distance_field_slice(ray);
+        }
+        // Remaining debug views are overlays computed by raymarch
+        default: {
+            return trace(ray);
+        }
+    }
+}
+
+// Has to match the color targets of the fractal pipelines
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    @location(1) depth: f32,
+    @location(2) normal: vec4<f32>,
+    @location(3) iterations: f32,
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> FragmentOutput {
+    fractal_constant = animated_constant();
+    fractal_power = options.power;
+    bounding_tests = 0;
+    aov = Aov(options.max_distance, vec3(0.), 0.);
+    is_inspected_pixel = bool(ray_path.is_recording) && all(vec2<u32>(in.position.xy) == ray_path.pixel);
+
+    // Skipped pixels keep their accumulated samples, which is how the region of interest converges first
+    if !is_sampled(in.position.xy) {
+        discard;
+    }
+
+    // Tiles of a larger image are rendered into the top left corner of the targets
+    let pixel_position = in.position.xy + sample.jitter + sample.view_offset;
+    let screen_size = select(vec2(screen.width, screen.height), sample.view_size, sample.view_size.y > 0.);
+
+    var color: vec4<f32>;
+    switch options.stereo_mode {
+        case STEREO_MODE_ANAGLYPH: {
+            let left_color = shade(camera_ray(pixel_position, screen_size, -1.));
+            let right_color = shade(camera_ray(pixel_position, screen_size, 1.));
+            color = vec4(left_color.r, right_color.gb, left_color.a);
+        }
+        case STEREO_MODE_SIDE_BY_SIDE: {
+            // Each half of the screen is a complete view, squeezed horizontally
+            let half_width = 0.5 * screen_size.x;
+            let is_right_eye = pixel_position.x >= half_width;
+            let eye_position = vec2(pixel_position.x - select(0., half_width, is_right_eye), pixel_position.y);
+            let eye_size = vec2(half_width, screen_size.y);
+            color = shade(camera_ray(eye_position, eye_size, select(-1., 1., is_right_eye)));
+        }
+        default: {
+            color = shade(camera_ray(pixel_position, screen_size, 0.));
+        }
+    }
 
-    return raymarch(ray);
+    return FragmentOutput(color, aov.depth, vec4(aov.normal, 0.), aov.iterations);
 }
@@ -0,0 +1,117 @@
+// Point of quaternion space shown at the position, on the rotated and offset slice hyperplane
+fn julia_seed(position: vec3<f32>) -> Quaternion {
+    return Quaternion(options.hyperplane_rotation * vec4(position, options.hyperplane_offset));
+}
+
+// Burning ship style variant, the components with their bit set in the flags are replaced by their absolute value
+fn julia_abs_fold(q: Quaternion) -> Quaternion {
+    let is_folded = ((vec4(options.julia_abs_folds) >> vec4(0u, 1u, 2u, 3u)) & vec4(1u)) == vec4(1u);
+    return Quaternion(select(q.inner, abs(q.inner), is_folded));
+}
+
+// Next quaternion of the selected formula, the previous one is only used by the phoenix formula
+// Standard formula raises the quaternion to the power of the group
+fn julia_formula_step(unfolded_q: Quaternion, previous_q: Quaternion, power: f32) -> Quaternion {
+    // Folding keeps the norm, so the derivative bounds below hold for the variants as well
+    let q = julia_abs_fold(unfolded_q);
+    switch options.julia_formula {
+        case JULIA_FORMULA_CUBE: {
+            return quat_add(quat_mul(quat_sq(q), q), fractal_constant);
+        }
+        case JULIA_FORMULA_PHOENIX: {
+            return quat_add(quat_add(quat_sq(q), fractal_constant), quat_scalar_mul(options.phoenix_strength, previous_q));
+        }
+        case JULIA_FORMULA_CUBIC: {
+            return quat_add(quat_add(quat_mul(quat_sq(q), q), quat_mul(options.cubic_constant, q)), fractal_constant);
+        }
+        default: {
+            if power == 2. {
+                return quat_add(quat_sq(q), fractal_constant);
+            }
+            return quat_add(quat_pow(q, power), fractal_constant);
+        }
+    }
+}
+
+// Squared norm of the running derivative after a step, bounded by the norms of the derivative terms
+fn julia_formula_derivative(q_sq_norm: f32, dq_sq_norm: f32, previous_dq_sq_norm: f32, power: f32) -> f32 {
+    switch options.julia_formula {
+        case JULIA_FORMULA_CUBE: {
+            return 9. * q_sq_norm * q_sq_norm * dq_sq_norm;
+        }
+        case JULIA_FORMULA_PHOENIX: {
+            let dq_norm = 2. * sqrt(q_sq_norm * dq_sq_norm) + abs(options.phoenix_strength) * sqrt(previous_dq_sq_norm);
+            return dq_norm * dq_norm;
+        }
+        case JULIA_FORMULA_CUBIC: {
+            let factor = 3. * q_sq_norm + quat_norm2(options.cubic_constant);
+            return factor * factor * dq_sq_norm;
+        }
+        default: {
+            if power == 2. {
+                return 4. * q_sq_norm * dq_sq_norm;
+            }
+            return dq_sq_norm * power * power * pow(q_sq_norm, power - 1.);
+        }
+    }
+}
+
+// Squared escape radius, compared against squared norms to save a square root every iteration
+fn julia_bailout_sq() -> f32 {
+    return options.bailout_radius * options.bailout_radius;
+}
+
+// Escape potential log|q| / degree^n of the position, zero for the points which never escape
+fn julia_potential(position: vec3<f32>, power: f32) -> f32 {
+    let degree = julia_formula_degree(power);
+    var q = julia_seed(position);
+    var previous_q = Quaternion(vec4(0.));
+    var scale = 1.;
+    for (var i = 0; i < JULIA_ITERATIONS; i++) {
+        let next_q = julia_formula_step(q, previous_q, power);
+        previous_q = q;
+        q = Quaternion(reduce_precision(quat_as_vec(next_q)));
+        scale *= degree;
+
+        let q_sq_norm = quat_sq_norm2(q);
+        if q_sq_norm > julia_bailout_sq() {
+            return 0.5 * log(q_sq_norm) / scale;
+        }
+    }
+
+    return 0.;
+}
+
+// Distance estimate G / 2|∇G| from the escape potential alone, its gradient sampled by central
+// differences instead of tracked through the iterations, which makes it independent of the
+// derivative bounds of the formulas at the cost of 6 more iterations of the quaternion
+fn julia_potential_SDF(position: vec3<f32>, power: f32) -> f32 {
+    let potential = julia_potential(position, power);
+    if potential <= 0. {
+        return 0.;
+    }
+
+    let h = vec2(options.epsilon, 0.);
+    let gradient = vec3(
+        julia_potential(position + h.xyy, power) - julia_potential(position - h.xyy, power),
+        julia_potential(position + h.yxy, power) - julia_potential(position - h.yxy, power),
+        julia_potential(position + h.yyx, power) - julia_potential(position - h.yyx, power),
+    ) / (2. * options.epsilon);
+
+    return 0.5 * potential / max(length(gradient), 1e-10);
+}
+
+// Factor the logarithm of the norm grows by every iteration, once the quaternion has escaped
+fn julia_formula_degree(power: f32) -> f32 {
+    switch options.julia_formula {
+        case JULIA_FORMULA_CUBE, JULIA_FORMULA_CUBIC: {
+            return 3.;
+        }
+        case JULIA_FORMULA_PHOENIX: {
+            return 2.;
+        }
+        default: {
+            return power;
+        }
+    }
+}
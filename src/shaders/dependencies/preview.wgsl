@@ -0,0 +1,52 @@
+struct PreviewUniform {
+    grid_size: u32,
+    horizontal_axis: u32,
+    vertical_axis: u32,
+    range: f32,
+}
+
+@group(2)
+@binding(0)
+var<uniform> preview: PreviewUniform;
+
+@group(2)
+@binding(1)
+var preview_texture: texture_storage_2d<rgba8unorm, write>;
+
+// Axes 0 to 3 are the quaternion components of the constant and 4 is the power, both clamped like in the GUI
+fn offset_parameter(axis: u32, offset: f32) {
+    if axis == PARAMETER_AXIS_POWER {
+        fractal_power = clamp(fractal_power + offset, 1., 10.);
+    } else {
+        fractal_constant.inner[axis] = clamp(fractal_constant.inner[axis] + offset, -1., 1.);
+    }
+}
+
+// Renders a grid of tiles, each with the parameters offset along the two chosen axes
+// Note: has to match PreviewData::tile_offsets
+@compute
+@workgroup_size(PREVIEW_WORKGROUP_SIZE, PREVIEW_WORKGROUP_SIZE)
+fn preview_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let texture_size = textureDimensions(preview_texture);
+    let tile_size = texture_size.x / preview.grid_size;
+    let tile = id.xy / tile_size;
+    if any(id.xy >= texture_size) || any(tile >= vec2(preview.grid_size)) {
+        return;
+    }
+
+    // Tiles are centered on the current parameters, with the vertical axis growing upwards
+    let grid_position = (vec2<f32>(tile) + 0.5) / f32(preview.grid_size) - 0.5;
+    fractal_constant = animated_constant();
+    fractal_power = options.power;
+    bounding_tests = 0;
+    offset_parameter(preview.horizontal_axis, preview.range * grid_position.x);
+    offset_parameter(preview.vertical_axis, -preview.range * grid_position.y);
+
+    // Every tile is a square view through the main camera
+    let pixel_position = vec2<f32>(id.xy % tile_size) + 0.5;
+    let uv_position = camera.view_scale * (2. * pixel_position / f32(tile_size) - 1.);
+    let ray_direction = normalize(uv_position.x * camera.matrix[1] - uv_position.y * camera.matrix[2] - camera.matrix[0]);
+
+    let color = trace(Ray(camera.origin, ray_direction));
+    textureStore(preview_texture, id.xy, vec4(clamp(color.rgb, vec3(0.), vec3(1.)), 1.));
+}
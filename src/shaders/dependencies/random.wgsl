@@ -0,0 +1,15 @@
+// Source: https://www.reedbeta.com/blog/hash-functions-for-gpu-rendering/
+fn pcg_hash(input: u32) -> u32 {
+    let state = input * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+fn pixel_hash(pixel: vec2<u32>, seed: u32) -> u32 {
+    return pcg_hash(pixel.x ^ pcg_hash(pixel.y ^ pcg_hash(seed)));
+}
+
+// Uniformly distributed on [0, 1), using only the upper 24 bits so the conversion to f32 is exact
+fn random_float(hash: u32) -> f32 {
+    return f32(hash >> 8u) / 16777216.;
+}
@@ -0,0 +1,116 @@
+// Point moved by the domain warps, together with how much the warps stretch space around it
+struct WarpedPoint {
+    position: vec3<f32>,
+    stretch: f32,
+}
+
+fn is_warped() -> bool {
+    return options.mirror_planes != 0u || options.twist_strength != 0. || options.bend_strength != 0.;
+}
+
+// Folds the point onto one side of every enabled plane, so the fractal on that side is reflected
+// onto the other, the axis planes keep the positive side and the diagonal ones the side where the
+// first named coordinate is larger
+fn mirror(point: WarpedPoint) -> WarpedPoint {
+    var p = point.position;
+    let planes = options.mirror_planes;
+
+    if (planes & MIRROR_X) != 0u {
+        p.x = abs(p.x);
+    }
+    if (planes & MIRROR_Y) != 0u {
+        p.y = abs(p.y);
+    }
+    if (planes & MIRROR_Z) != 0u {
+        p.z = abs(p.z);
+    }
+    if (planes & MIRROR_XY) != 0u && p.x < p.y {
+        p = p.yxz;
+    }
+    if (planes & MIRROR_YZ) != 0u && p.y < p.z {
+        p = p.xzy;
+    }
+    if (planes & MIRROR_XZ) != 0u && p.x < p.z {
+        p = p.zyx;
+    }
+
+    // Reflections preserve distances
+    return WarpedPoint(p, point.stretch);
+}
+
+// Coordinates with the twist axis as the last one, so the twist only has to handle the z axis
+fn to_twist_frame(position: vec3<f32>) -> vec3<f32> {
+    switch options.twist_axis {
+        case WARP_AXIS_X: {
+            return position.yzx;
+        }
+        case WARP_AXIS_Y: {
+            return position.zxy;
+        }
+        default: {
+            return position;
+        }
+    }
+}
+
+fn from_twist_frame(position: vec3<f32>) -> vec3<f32> {
+    switch options.twist_axis {
+        case WARP_AXIS_X: {
+            return position.zxy;
+        }
+        case WARP_AXIS_Y: {
+            return position.yzx;
+        }
+        default: {
+            return position;
+        }
+    }
+}
+
+// Rotates the point about the twist axis by an angle growing with the distance along it
+fn twist(point: WarpedPoint) -> WarpedPoint {
+    let q = to_twist_frame(point.position);
+    let angle = options.twist_strength * q.z;
+    let c = cos(angle);
+    let s = sin(angle);
+    let twisted = vec3(c * q.x - s * q.y, s * q.x + c * q.y, q.z);
+
+    // Points further from the axis are sheared more
+    let stretch = sqrt(1. + pow(options.twist_strength * length(q.xy), 2.));
+    return WarpedPoint(from_twist_frame(twisted), point.stretch * stretch);
+}
+
+// Bends the x axis upwards, rotating the point in the xz plane by an angle growing along x
+fn bend(point: WarpedPoint) -> WarpedPoint {
+    let p = point.position;
+    let angle = options.bend_strength * p.x;
+    let c = cos(angle);
+    let s = sin(angle);
+    let bent = vec3(c * p.x - s * p.z, p.y, s * p.x + c * p.z);
+
+    let stretch = sqrt(1. + pow(options.bend_strength * length(p.xz), 2.));
+    return WarpedPoint(bent, point.stretch * stretch);
+}
+
+fn warp(position: vec3<f32>) -> WarpedPoint {
+    return bend(twist(mirror(WarpedPoint(position, 1.))));
+}
+
+// Distance estimate of the fractal at the warped point, shortened by the stretch of the warps, as
+// distances measured in warped space overestimate the real ones by up to that factor
+fn warped_scene_SDF(position: vec3<f32>) -> f32 {
+    if !is_warped() {
+        return bounded_scene_SDF(position);
+    }
+
+    let point = warp(position);
+    return bounded_scene_SDF(point.position) / point.stretch;
+}
+
+fn warped_scene_density(position: vec3<f32>) -> f32 {
+    if !is_warped() {
+        return scene_density(position);
+    }
+
+    return scene_density(warp(position).position);
+}
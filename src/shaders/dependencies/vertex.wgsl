@@ -0,0 +1,17 @@
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+}
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) in_vertex_index: u32,
+    @builtin(instance_index) in_instance_index: u32,
+) -> VertexOutput {
+    var out: VertexOutput;
+
+    let x = f32((in_vertex_index & 1u) ^ in_instance_index);
+    let y = f32((in_vertex_index >> 1u) ^ in_instance_index);
+
+    out.position = vec4<f32>(2. * vec2(x, y) - 1., 0., 1.);
+    return out;
+}
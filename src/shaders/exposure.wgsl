@@ -0,0 +1,101 @@
+struct ExposureUniform {
+    min_exposure: f32,
+    max_exposure: f32,
+    adaptation_speed: f32,
+    delta_time: f32,
+}
+
+@group(0)
+@binding(0)
+var render_texture: texture_2d<f32>;
+
+@group(0)
+@binding(1)
+var<storage, read_write> histogram: array<atomic<u32>, 256>;
+
+// Adapted exposure multiplier, persists between frames
+@group(0)
+@binding(2)
+var<storage, read_write> exposure: f32;
+
+@group(0)
+@binding(3)
+var<uniform> settings: ExposureUniform;
+
+const HISTOGRAM_BINS = 256u;
+const MIN_LOG_LUMINANCE = -10.;
+const LOG_LUMINANCE_RANGE = 20.;
+// Middle grey, which the average luminance gets mapped to
+const KEY_VALUE = 0.18;
+
+var<workgroup> local_histogram: array<atomic<u32>, 256>;
+var<workgroup> weighted_counts: array<f32, 256>;
+
+fn luminance_bin(color: vec3<f32>) -> u32 {
+    let luminance = dot(color, vec3(0.2126, 0.7152, 0.0722));
+
+    // Bin 0 is reserved for black pixels, so the empty background does not skew the average
+    if luminance < exp2(MIN_LOG_LUMINANCE) {
+        return 0u;
+    }
+
+    let normalized = clamp((log2(luminance) - MIN_LOG_LUMINANCE) / LOG_LUMINANCE_RANGE, 0., 1.);
+    return u32(normalized * f32(HISTOGRAM_BINS - 2u)) + 1u;
+}
+
+@compute
+@workgroup_size(16, 16)
+fn histogram_main(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(local_invocation_index) local_index: u32,
+) {
+    atomicStore(&local_histogram[local_index], 0u);
+    workgroupBarrier();
+
+    if all(global_id.xy < textureDimensions(render_texture)) {
+        // Samples are accumulated additively, with the sample count stored in the alpha channel
+        let accumulated = textureLoad(render_texture, global_id.xy, 0);
+        atomicAdd(&local_histogram[luminance_bin(accumulated.rgb / max(accumulated.a, 1.))], 1u);
+    }
+    workgroupBarrier();
+
+    atomicAdd(&histogram[local_index], atomicLoad(&local_histogram[local_index]));
+}
+
+@compute
+@workgroup_size(256)
+fn average_main(@builtin(local_invocation_index) local_index: u32) {
+    let count = atomicLoad(&histogram[local_index]);
+    weighted_counts[local_index] = f32(count) * f32(local_index);
+    // Histogram is cleared for the next frame
+    atomicStore(&histogram[local_index], 0u);
+    workgroupBarrier();
+
+    for (var stride = HISTOGRAM_BINS / 2u; stride > 0u; stride >>= 1u) {
+        if local_index < stride {
+            weighted_counts[local_index] += weighted_counts[local_index + stride];
+        }
+        workgroupBarrier();
+    }
+
+    if local_index == 0u {
+        let size = textureDimensions(render_texture);
+        // Thread 0 loaded the count of black pixels
+        let lit_pixels = f32(size.x * size.y - count);
+        if lit_pixels < 1. {
+            return;
+        }
+
+        let average_bin = weighted_counts[0] / lit_pixels - 1.;
+        let average_luminance = exp2(average_bin / f32(HISTOGRAM_BINS - 2u) * LOG_LUMINANCE_RANGE + MIN_LOG_LUMINANCE);
+        let target_exposure = clamp(
+            KEY_VALUE / average_luminance,
+            exp2(settings.min_exposure),
+            exp2(settings.max_exposure),
+        );
+
+        // Exponential smoothing, so adaptation speed does not depend on the frame rate
+        let blend = 1. - exp(-settings.delta_time * settings.adaptation_speed);
+        exposure += (target_exposure - exposure) * blend;
+    }
+}
@@ -1,23 +1,26 @@
-const w = 0.1;
 const JULIA_ITERATIONS = 100;
 const JULIA_NORMAL_ITERATIONS = 10;
 
 fn scene_SDF(position: vec3<f32>) -> f32 {
-    // Approximation breaks when too far away, so we patch it non-continuously
-    let norm = length(position);
-    if norm > 2. + options.epsilon {
-        return norm - 2.;
+    if options.julia_estimator == JULIA_ESTIMATOR_POTENTIAL {
+        return julia_potential_SDF(position, fractal_power);
     }
 
-    var q = Quaternion(vec4(position, w));
+    var q = julia_seed(position);
+    var previous_q = Quaternion(vec4(0.));
     var q_sq_norm = quat_sq_norm2(q);
     var dq_sq_norm = 1.;
+    var previous_dq_sq_norm = 0.;
     for(var i = 0; i < JULIA_ITERATIONS; i++) {
-        dq_sq_norm *= options.power * options.power * pow(q_sq_norm, options.power - 1.);
-        q = quat_add(quat_pow(q, options.power), options.constant);
+        let next_dq_sq_norm = julia_formula_derivative(q_sq_norm, dq_sq_norm, previous_dq_sq_norm, fractal_power);
+        previous_dq_sq_norm = dq_sq_norm;
+        dq_sq_norm = reduce_precision_f32(next_dq_sq_norm);
+        let next_q = julia_formula_step(q, previous_q, fractal_power);
+        previous_q = q;
+        q = Quaternion(reduce_precision(quat_as_vec(next_q)));
 
         q_sq_norm = quat_sq_norm2(q);
-        if(q_sq_norm > options.max_distance) {
+        if(q_sq_norm > julia_bailout_sq()) {
             break;
         }
     }
@@ -26,25 +29,55 @@ fn scene_SDF(position: vec3<f32>) -> f32 {
     return 0.25 * log(q_sq_norm) * sqrt(q_sq_norm / dq_sq_norm);
 }
 
+// Smooth escape time normalized to [0, 1], which approaches 1 towards the set
+fn scene_density(position: vec3<f32>) -> f32 {
+    if length(position) > 2. {
+        return 0.;
+    }
+
+    var q = julia_seed(position);
+    var previous_q = Quaternion(vec4(0.));
+    for (var i = 0; i < JULIA_ITERATIONS; i++) {
+        let next_q = julia_formula_step(q, previous_q, fractal_power);
+        previous_q = q;
+        q = next_q;
+
+        let q_sq_norm = quat_sq_norm2(q);
+        if q_sq_norm > julia_bailout_sq() {
+            // Every iteration multiplies the logarithm of the norm by the degree of the formula
+            let smooth_iterations = f32(i) + 1. - log(log(q_sq_norm) / log(julia_bailout_sq())) / log(julia_formula_degree(fractal_power));
+            return clamp(smooth_iterations / f32(JULIA_ITERATIONS), 0., 1.);
+        }
+    }
 
+    return 1.;
+}
+
+// Gradient of the escape potential, approximated with a few iterations of neighbouring points
 fn get_normal(position: vec3<f32>) -> vec3<f32> {
+    // Other formulas need the previous quaternion of every neighbouring point, the distance is cheaper
+    // Folded variants are not followed by the plain power below either
+    if options.julia_formula != JULIA_FORMULA_STANDARD || options.julia_abs_folds != 0u {
+        return finite_difference_normal(position);
+    }
+
     let h_x = vec3(options.epsilon, 0., 0.);
     let h_y = vec3(0., options.epsilon, 0.);
     let h_z = vec3(0., 0., options.epsilon);
 
-    var q_x_pos = Quaternion(vec4(position + h_x, w));
-    var q_x_neg = Quaternion(vec4(position - h_x, w));
-    var q_y_pos = Quaternion(vec4(position + h_y, w));
-    var q_y_neg = Quaternion(vec4(position - h_y, w));
-    var q_z_pos = Quaternion(vec4(position + h_z, w));
-    var q_z_neg = Quaternion(vec4(position - h_z, w));
+    var q_x_pos = julia_seed(position + h_x);
+    var q_x_neg = julia_seed(position - h_x);
+    var q_y_pos = julia_seed(position + h_y);
+    var q_y_neg = julia_seed(position - h_y);
+    var q_z_pos = julia_seed(position + h_z);
+    var q_z_neg = julia_seed(position - h_z);
     for(var i = 0; i < JULIA_NORMAL_ITERATIONS; i++) {
-        q_x_pos = quat_add(quat_pow(q_x_pos, options.power), options.constant);
-        q_x_neg = quat_add(quat_pow(q_x_neg, options.power), options.constant);
-        q_y_pos = quat_add(quat_pow(q_y_pos, options.power), options.constant);
-        q_y_neg = quat_add(quat_pow(q_y_neg, options.power), options.constant);
-        q_z_pos = quat_add(quat_pow(q_z_pos, options.power), options.constant);
-        q_z_neg = quat_add(quat_pow(q_z_neg, options.power), options.constant);
+        q_x_pos = quat_add(quat_pow(q_x_pos, fractal_power), fractal_constant);
+        q_x_neg = quat_add(quat_pow(q_x_neg, fractal_power), fractal_constant);
+        q_y_pos = quat_add(quat_pow(q_y_pos, fractal_power), fractal_constant);
+        q_y_neg = quat_add(quat_pow(q_y_neg, fractal_power), fractal_constant);
+        q_z_pos = quat_add(quat_pow(q_z_pos, fractal_power), fractal_constant);
+        q_z_neg = quat_add(quat_pow(q_z_neg, fractal_power), fractal_constant);
     }
 
     return normalize(vec3(
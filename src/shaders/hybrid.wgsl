@@ -0,0 +1,103 @@
+// Radii of the Mandelbox sphere fold, squared
+const MANDELBOX_MIN_SQ_RADIUS = 0.25;
+const MANDELBOX_FIXED_SQ_RADIUS = 1.;
+
+// Position of an iteration together with the length of its running derivative
+struct HybridPoint {
+    position: vec3<f32>,
+    derivative: f32,
+}
+
+fn mandelbox_fold_step(point: HybridPoint, c: vec3<f32>) -> HybridPoint {
+    var position = 2. * clamp(point.position, vec3(-1.), vec3(1.)) - point.position;
+    var derivative = point.derivative;
+
+    // Points close to the origin are scaled up, those inside the fixed sphere inverted
+    let sq_radius = dot(position, position);
+    if sq_radius < MANDELBOX_MIN_SQ_RADIUS {
+        let factor = MANDELBOX_FIXED_SQ_RADIUS / MANDELBOX_MIN_SQ_RADIUS;
+        position *= factor;
+        derivative *= factor;
+    } else if sq_radius < MANDELBOX_FIXED_SQ_RADIUS {
+        let factor = MANDELBOX_FIXED_SQ_RADIUS / sq_radius;
+        position *= factor;
+        derivative *= factor;
+    }
+
+    let scale = options.hybrid_box_scale;
+    return HybridPoint(scale * position + c, derivative * abs(scale) + 1.);
+}
+
+// Squaring of the point as a quaternion without its last component, which drops back out after adding the constant
+fn julia_step(point: HybridPoint) -> HybridPoint {
+    let q = quat_add(quat_sq(Quaternion(vec4(point.position, 0.))), fractal_constant);
+    return HybridPoint(quat_as_vec(q).xyz, 2. * length(point.position) * point.derivative);
+}
+
+// Power of the point in spherical coordinates, the triplex power of the Mandelbulb
+fn mandelbulb_step(point: HybridPoint, c: vec3<f32>) -> HybridPoint {
+    let radius = max(length(point.position), 1e-6);
+    let theta = fractal_power * acos(clamp(point.position.z / radius, -1., 1.));
+    let phi = fractal_power * atan2(point.position.y, point.position.x);
+    let scaled_radius = pow(radius, fractal_power);
+
+    return HybridPoint(
+        scaled_radius * vec3(sin(theta) * cos(phi), sin(theta) * sin(phi), cos(theta)) + c,
+        fractal_power * pow(radius, fractal_power - 1.) * point.derivative + 1.,
+    );
+}
+
+// Formula of the iteration, the set bits of the repeating pattern select the second formula
+fn hybrid_formula(iteration: u32) -> u32 {
+    let step = iteration % max(options.hybrid_pattern_length, 1u);
+    return select(options.hybrid_formula_a, options.hybrid_formula_b, ((options.hybrid_pattern >> step) & 1u) == 1u);
+}
+
+fn hybrid_step(point: HybridPoint, c: vec3<f32>, iteration: u32) -> HybridPoint {
+    switch hybrid_formula(iteration) {
+        case HYBRID_FORMULA_JULIA_STEP: {
+            return julia_step(point);
+        }
+        case HYBRID_FORMULA_MANDELBULB: {
+            return mandelbulb_step(point, c);
+        }
+        default: {
+            return mandelbox_fold_step(point, c);
+        }
+    }
+}
+
+// Linear distance estimate of the combined iteration, which holds for folds and powers alike
+fn scene_SDF(position: vec3<f32>) -> f32 {
+    var point = HybridPoint(position, 1.);
+    for (var i = 0u; i < options.hybrid_iterations; i++) {
+        point = hybrid_step(point, position, i);
+        point.position = reduce_precision_vec3(point.position);
+        point.derivative = reduce_precision_f32(point.derivative);
+
+        if dot(point.position, point.position) > options.max_distance {
+            break;
+        }
+    }
+
+    return length(point.position) / abs(point.derivative);
+}
+
+// Escape time normalized to [0, 1], points which never escape are fully dense
+fn scene_density(position: vec3<f32>) -> f32 {
+    var point = HybridPoint(position, 1.);
+    for (var i = 0u; i < options.hybrid_iterations; i++) {
+        point = hybrid_step(point, position, i);
+
+        if dot(point.position, point.position) > options.max_distance {
+            return f32(i) / f32(options.hybrid_iterations);
+        }
+    }
+
+    return 1.;
+}
+
+// Formulas differ from one iteration to the next, so there is no single closed form gradient
+fn get_normal(position: vec3<f32>) -> vec3<f32> {
+    return finite_difference_normal(position);
+}
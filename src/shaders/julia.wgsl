@@ -1,23 +1,26 @@
-const w = 0.1;
 const JULIA_ITERATIONS = 100;
 const JULIA_NORMAL_ITERATIONS = 10;
 
 fn scene_SDF(position: vec3<f32>) -> f32 {
-    // Approximation breaks when too far away, so we patch it non-continuously
-    let norm = length(position);
-    if norm > 2. + options.epsilon {
-        return norm - 2.;
+    if options.julia_estimator == JULIA_ESTIMATOR_POTENTIAL {
+        return julia_potential_SDF(position, 2.);
     }
 
-    var q = Quaternion(vec4(position, w));
+    var q = julia_seed(position);
+    var previous_q = Quaternion(vec4(0.));
     var q_sq_norm = quat_sq_norm2(q);
     var dq_sq_norm = 1.;
+    var previous_dq_sq_norm = 0.;
 	for(var i = 0; i < JULIA_ITERATIONS; i++) {
-		dq_sq_norm *= 4. * q_sq_norm;
-        q = quat_add(quat_sq(q), options.constant);
+        let next_dq_sq_norm = julia_formula_derivative(q_sq_norm, dq_sq_norm, previous_dq_sq_norm, 2.);
+        previous_dq_sq_norm = dq_sq_norm;
+		dq_sq_norm = reduce_precision_f32(next_dq_sq_norm);
+        let next_q = julia_formula_step(q, previous_q, 2.);
+        previous_q = q;
+        q = Quaternion(reduce_precision(quat_as_vec(next_q)));
 
         q_sq_norm = quat_sq_norm2(q);
-        if(q_sq_norm > options.max_distance) {
+        if(q_sq_norm > julia_bailout_sq()) {
             break;
         }
 	}
@@ -26,9 +29,39 @@ fn scene_SDF(position: vec3<f32>) -> f32 {
 	return 0.25 * log(q_sq_norm) * sqrt(q_sq_norm / dq_sq_norm);;
 }
 
+// Smooth escape time normalized to [0, 1], which approaches 1 towards the set
+fn scene_density(position: vec3<f32>) -> f32 {
+    if length(position) > 2. {
+        return 0.;
+    }
+
+    var q = julia_seed(position);
+    var previous_q = Quaternion(vec4(0.));
+    for (var i = 0; i < JULIA_ITERATIONS; i++) {
+        let next_q = julia_formula_step(q, previous_q, 2.);
+        previous_q = q;
+        q = next_q;
+
+        let q_sq_norm = quat_sq_norm2(q);
+        if q_sq_norm > julia_bailout_sq() {
+            // Every iteration multiplies the logarithm of the norm by the degree, so its ratio to the bailout tells the fractional iteration
+            let smooth_iterations = f32(i) + 1. - log(log(q_sq_norm) / log(julia_bailout_sq())) / log(julia_formula_degree(2.));
+            return clamp(smooth_iterations / f32(JULIA_ITERATIONS), 0., 1.);
+        }
+    }
+
+    return 1.;
+}
+
+// Analytic gradient of the escape potential using the running Jacobian of the iteration
 fn get_normal(position: vec3<f32>) -> vec3<f32> {
-    var q_vec = vec4(position, w);
-	var q = Quaternion(q_vec);
+    // Jacobian below is the one of squaring without folds
+    if options.julia_formula != JULIA_FORMULA_STANDARD || options.julia_abs_folds != 0u {
+        return finite_difference_normal(position);
+    }
+
+	var q = julia_seed(position);
+    var q_vec = quat_as_vec(q);
     // Jacobian matrix
     var J = mat4x4(
         1., 0., 0., 0.,
@@ -44,13 +77,14 @@ fn get_normal(position: vec3<f32>) -> vec3<f32> {
             q_vec.w,       0.,       0.,  q_vec.x,
         ) * J;
 
-        q = quat_add(quat_sq(q), options.constant); 
+        q = quat_add(quat_sq(q), fractal_constant); 
         q_vec = quat_as_vec(q);
         
-        if(quat_sq_norm2(q) > options.max_distance) {
+        if(quat_sq_norm2(q) > julia_bailout_sq()) {
 			break;
 		}
     }
     
-    return normalize((J * q_vec).xyz);
+    // Gradient in quaternion space is brought back onto the slice
+    return normalize((transpose(options.hyperplane_rotation) * (J * q_vec)).xyz);
 }
@@ -13,45 +13,6 @@ fn plane_mirror(plane: Plane, position: vec3<f32>) -> vec3<f32> {
     return position - 2. * plane_dist * normalize(plane.normal);
 }
 
-struct Sphere {
-    radius: f32,
-}
-
-fn sphere_SDF(sphere: Sphere, position: vec3<f32>) -> f32 {
-    return length(position) - sphere.radius;
-}
-
-struct Cylinder {
-    radius: f32,
-    height: f32,
-}
-
-fn cylinder_SDF(cylinder: Cylinder, position: vec3<f32>) -> f32 {
-    let d = abs(vec2(length(position.xy), position.z)) - vec2(cylinder.radius, cylinder.height);
-    return min(max(d.x, d.y), 0.) + length(max(d, vec2(0., 0.)));
-}
-
-struct Box {
-    length: f32,
-    width: f32,
-    height: f32,
-}
-
-fn box_SDF(box: Box, position: vec3<f32>) -> f32 {
-    let q = abs(position) - vec3(box.length, box.width, box.height);
-    return length(max(q, vec3(0., 0., 0.))) + min(max(q.x, max(q.y, q.z)), 0.);
-}
-
-struct Torus {
-    outer_radius: f32,
-    inner_radius: f32,
-}
-
-fn torus_SDF(torus: Torus, position: vec3<f32>) -> f32 {
-    let q = vec2(length(position.xy) - torus.outer_radius, position.z);
-    return length(q) - torus.inner_radius;
-}
-
 // TODO: OPTIMIZE!!!!
 fn tetrahedral_fold(position: vec3<f32>) -> vec3<f32> {
     var pos = position;
@@ -65,27 +26,63 @@ fn tetrahedral_fold(position: vec3<f32>) -> vec3<f32> {
     return pos;
 }
 
+// Scales the folded copies towards the offset vertex, rotating them first to morph the shape
 fn sierpinski_tetrahedron_SDF(position: vec3<f32>) -> f32 {
+    let offset = (options.sierpinski_scale - 1.) * options.sierpinski_offset;
     var scale = 1.;
     var pos = position;
     var r = length(pos);
     for(var i = 0; i < 10 && r < options.max_distance; i++) {
-        pos = tetrahedral_fold(pos);
+        pos = options.sierpinski_rotation * tetrahedral_fold(pos);
 
-        scale *= 2.;
-        pos = 2. * pos - vec3(1.);
+        scale *= options.sierpinski_scale;
+        pos = reduce_precision_vec3(options.sierpinski_scale * pos - offset);
         r = length(pos);
     }
 
     return (r - 2) / scale;
 }
 
-// Source: https://gist.github.com/munrocket/f247155fc22ecb8edf974d905c677de1
-fn bunny_SDF(position: vec3f) -> f32 {
-    if (dot(position, position) > 1.) {
-        return length(position) - 0.8;
+// Sorts the coordinates in decreasing order, which mirrors along the three diagonal planes
+fn sort_fold(position: vec3<f32>) -> vec3<f32> {
+    var pos = position;
+    if pos.x < pos.y {
+        pos = pos.yxz;
+    }
+    if pos.x < pos.z {
+        pos = pos.zyx;
+    }
+    if pos.y < pos.z {
+        pos = pos.xzy;
+    }
+
+    return pos;
+}
+
+// Folds the 20 cubes a scale 3 sponge keeps onto the one in the corner, scales with different
+// values move the cubes apart and widen the holes
+fn menger_sponge_SDF(position: vec3<f32>) -> f32 {
+    let offset = options.menger_scale - 1.;
+    var scale = 1.;
+    var pos = position;
+    for (var i = 0u; i < options.menger_iterations; i++) {
+        pos = sort_fold(abs(pos));
+
+        pos = options.menger_scale * pos - vec3(offset, offset, 0.);
+        // Cubes in the middle of the edges are folded onto the corner along z
+        if pos.z > 0.5 * offset {
+            pos.z -= offset;
+        }
+
+        scale *= options.menger_scale;
+        pos = reduce_precision_vec3(pos);
     }
 
+    return box_SDF(Box(1., 1., 1.), pos) / scale;
+}
+
+// Source: https://gist.github.com/munrocket/f247155fc22ecb8edf974d905c677de1
+fn bunny_SDF(position: vec3f) -> f32 {
     let q = vec4f(position.xzy * vec3(-1., 1., -1.), 1.);
     let f00 = sin(mat4x4f(-3.02, 1.95, -3.42, -0.6, 3.08, 0.85, -2.25, -0.24, -0.29, 1.16, -3.74, 2.89, -0.71, 4.5, -3.24, -3.5) * q);
     let f01 = sin(mat4x4f(-0.4, -3.61, 3.23, -0.14, -0.36, 3.64, -3.91, 2.66, 2.9, -0.54, -2.75, 2.71, 7.02, -5.41, -1.12, -7.41) * q);
@@ -136,32 +133,60 @@ fn bunny_SDF(position: vec3f) -> f32 {
         dot(f22, vec4f(-0.01, 0.06, -0.02, 0.07)) + dot(f23, vec4f(-0.05, 0.07, 0.03, 0.04)) - 0.16;
 }
 
+// Preset shapes sized by the options
+fn preset_sphere() -> Sphere {
+    return Sphere(options.sphere_radius);
+}
+
+fn preset_cylinder() -> Cylinder {
+    return Cylinder(options.cylinder_radius, options.cylinder_half_height);
+}
+
+fn preset_box() -> Box {
+    let extents = options.box_half_extents;
+    return Box(extents.x, extents.y, extents.z);
+}
+
+fn preset_torus() -> Torus {
+    return Torus(options.torus_outer_radius, options.torus_inner_radius);
+}
+
 fn scene_SDF(position: vec3<f32>) -> f32 {
-    if options.primitive_id == 0 {
-        return sphere_SDF(Sphere(1.), position);
-    } else if options.primitive_id == 1 {
-        return cylinder_SDF(Cylinder(1., 2.), position);
-    } else if options.primitive_id == 2 {
-        return box_SDF(Box(1., 1., 1.), position);
-    } else if options.primitive_id == 3 {
-        return torus_SDF(Torus(1., 0.3), position);
-    } else if options.primitive_id == 4 {
+    if options.primitive_id == PRIMITIVE_SHAPE_SPHERE {
+        return sphere_SDF(preset_sphere(), position);
+    } else if options.primitive_id == PRIMITIVE_SHAPE_CYLINDER {
+        return cylinder_SDF(preset_cylinder(), position);
+    } else if options.primitive_id == PRIMITIVE_SHAPE_BOX {
+        return box_SDF(preset_box(), position);
+    } else if options.primitive_id == PRIMITIVE_SHAPE_TORUS {
+        return torus_SDF(preset_torus(), position);
+    } else if options.primitive_id == PRIMITIVE_SHAPE_SIERPINSKI_TETRAHEDRON {
         return sierpinski_tetrahedron_SDF(position);
-    } else if options.primitive_id == 5 {
+    } else if options.primitive_id == PRIMITIVE_SHAPE_BUNNY {
         return bunny_SDF(position);
+    } else if options.primitive_id == PRIMITIVE_SHAPE_MENGER_SPONGE {
+        return menger_sponge_SDF(position);
     }
 
     return 1.;
 }
 
-fn get_normal(position: vec3<f32>) -> vec3<f32> {
-    let h_x = vec3(options.epsilon, 0., 0.);
-    let h_y = vec3(0., options.epsilon, 0.);
-    let h_z = vec3(0., 0., options.epsilon);
+// Preset shapes have no escape time, so they are filled with uniform density
+fn scene_density(position: vec3<f32>) -> f32 {
+    return select(0., 1., scene_SDF(position) < 0.);
+}
 
-    let sdf_dx = scene_SDF(position + h_x) - scene_SDF(position - h_x);
-    let sdf_dy = scene_SDF(position + h_y) - scene_SDF(position - h_y);
-    let sdf_dz = scene_SDF(position + h_z) - scene_SDF(position - h_z);
+fn get_normal(position: vec3<f32>) -> vec3<f32> {
+    if options.primitive_id == PRIMITIVE_SHAPE_SPHERE {
+        return sphere_normal(position);
+    } else if options.primitive_id == PRIMITIVE_SHAPE_CYLINDER {
+        return cylinder_normal(preset_cylinder(), position);
+    } else if options.primitive_id == PRIMITIVE_SHAPE_BOX {
+        return box_normal(preset_box(), position);
+    } else if options.primitive_id == PRIMITIVE_SHAPE_TORUS {
+        return torus_normal(preset_torus(), position);
+    }
 
-    return normalize(vec3(sdf_dx, sdf_dy, sdf_dz));
+    // No closed form gradient for the remaining shapes
+    return finite_difference_normal(position);
 }
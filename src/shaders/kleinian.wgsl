@@ -0,0 +1,31 @@
+// Radius of the cylinders the limit set clusters around, for the default box size
+const KLEINIAN_CORE_RADIUS = 0.92784;
+
+// Pseudo-Kleinian distance estimate by Knighty, reflecting in the box faces and inverting in the sphere
+fn scene_SDF(position: vec3<f32>) -> f32 {
+    let box_size = options.kleinian_box_size;
+    let inversion_sq_radius = options.kleinian_inversion_radius * options.kleinian_inversion_radius;
+    var scale = 1.;
+    var pos = position;
+    for (var i = 0u; i < options.kleinian_iterations; i++) {
+        pos = 2. * clamp(pos, -box_size, box_size) - pos;
+
+        // Points inside the sphere are inverted, those outside are left as they are
+        let k = max(inversion_sq_radius / dot(pos, pos), 1.);
+        pos = reduce_precision_vec3(k * pos);
+        scale *= k;
+    }
+
+    let r_xy = length(pos.xy);
+    return max(r_xy - KLEINIAN_CORE_RADIUS, abs(r_xy * pos.z) / length(pos)) / scale;
+}
+
+// Limit sets have no escape time, so they are filled with uniform density
+fn scene_density(position: vec3<f32>) -> f32 {
+    return select(0., 1., scene_SDF(position) < 0.);
+}
+
+// No closed form gradient through the folds and inversions
+fn get_normal(position: vec3<f32>) -> vec3<f32> {
+    return finite_difference_normal(position);
+}
@@ -0,0 +1,238 @@
+struct PostUniform {
+    chromatic_aberration: f32,
+    film_grain: f32,
+    frame: u32,
+    exposure: f32,
+    is_auto_exposure: u32,
+    is_outline: u32,
+    outline_thickness: f32,
+    is_transparent: u32,
+    outline_color: vec3<f32>,
+    transfer_function: u32,
+    gamma: f32,
+    is_srgb_target: u32,
+    upscale_filter: u32,
+    sharpness: f32,
+    // Fraction of the render texture covered by the image, lowered while previewing a drag
+    render_scale: f32,
+}
+
+@group(0)
+@binding(0)
+var render_texture: texture_2d<f32>;
+
+@group(0)
+@binding(1)
+var<uniform> post: PostUniform;
+
+// Exposure multiplier adapted by the exposure compute pass
+@group(0)
+@binding(2)
+var<storage, read> auto_exposure: f32;
+
+@group(0)
+@binding(3)
+var depth_texture: texture_2d<f32>;
+
+@group(0)
+@binding(4)
+var normal_texture: texture_2d<f32>;
+
+@group(0)
+@binding(5)
+var render_sampler: sampler;
+
+// Maximum channel shift as a fraction of the distance from the screen center
+const MAX_ABERRATION = 0.02;
+// Maximum amplitude of the grain noise in linear color space
+const MAX_GRAIN = 0.2;
+// Relative depth difference between neighbouring pixels treated as a silhouette edge
+const OUTLINE_DEPTH_THRESHOLD = 0.05;
+// Angle cosine between neighbouring normals below which a crease is outlined
+const OUTLINE_NORMAL_THRESHOLD = 0.8;
+
+// Samples are accumulated additively, with the sample count stored in the alpha channel
+fn resolve_samples(accumulated: vec4<f32>) -> vec3<f32> {
+    return accumulated.rgb / max(accumulated.a, 1.);
+}
+
+fn load_texel(coords: vec2<i32>) -> vec3<f32> {
+    let size = vec2<i32>(textureDimensions(render_texture));
+    return resolve_samples(textureLoad(render_texture, clamp(coords, vec2(0), size - 1), 0));
+}
+
+fn sample_bilinear(position: vec2<f32>) -> vec3<f32> {
+    let uv = position / vec2<f32>(textureDimensions(render_texture));
+    return resolve_samples(textureSampleLevel(render_texture, render_sampler, uv, 0.));
+}
+
+fn catmull_rom_weights(t: f32) -> vec4<f32> {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    return 0.5 * vec4(
+        -t3 + 2. * t2 - t,
+        3. * t3 - 5. * t2 + 2.,
+        -3. * t3 + 4. * t2 + t,
+        t3 - t2,
+    );
+}
+
+fn sample_bicubic(position: vec2<f32>) -> vec3<f32> {
+    // Texel centers lie at half integer positions
+    let texel_position = position - 0.5;
+    let base = vec2<i32>(floor(texel_position));
+    let fraction = texel_position - floor(texel_position);
+    let weights_x = catmull_rom_weights(fraction.x);
+    let weights_y = catmull_rom_weights(fraction.y);
+
+    var color = vec3(0.);
+    for (var y = 0; y < 4; y++) {
+        for (var x = 0; x < 4; x++) {
+            color += weights_x[x] * weights_y[y] * load_texel(base + vec2(x - 1, y - 1));
+        }
+    }
+
+    // Negative lobes of the spline overshoot around sharp edges
+    return max(color, vec3(0.));
+}
+
+fn sample_sharpened(position: vec2<f32>) -> vec3<f32> {
+    let coords = vec2<i32>(floor(position));
+    let center = load_texel(coords);
+    let north = load_texel(coords + vec2(0, -1));
+    let south = load_texel(coords + vec2(0, 1));
+    let west = load_texel(coords + vec2(-1, 0));
+    let east = load_texel(coords + vec2(1, 0));
+
+    let blurred = 0.25 * (north + south + west + east);
+    let sharpened = center + post.sharpness * (center - blurred);
+    // Limiting the result to the range of the neighbourhood keeps edges from ringing
+    let minimum = min(center, min(min(north, south), min(west, east)));
+    let maximum = max(center, max(max(north, south), max(west, east)));
+
+    return clamp(sharpened, minimum, maximum);
+}
+
+fn sample_render_texture(position: vec2<f32>) -> vec3<f32> {
+    switch post.upscale_filter {
+        case UPSCALE_FILTER_BILINEAR: {
+            return sample_bilinear(position);
+        }
+        case UPSCALE_FILTER_BICUBIC: {
+            return sample_bicubic(position);
+        }
+        case UPSCALE_FILTER_SHARPEN: {
+            return sample_sharpened(position);
+        }
+        default: {
+            return load_texel(vec2<i32>(floor(position)));
+        }
+    }
+}
+
+fn chromatic_aberration(position: vec2<f32>) -> vec3<f32> {
+    let center = 0.5 * post.render_scale * vec2<f32>(textureDimensions(render_texture));
+    // Red is pushed outwards and blue inwards, both growing towards the screen edges
+    let offset = post.chromatic_aberration * MAX_ABERRATION * (position - center);
+
+    return vec3(
+        sample_render_texture(position + offset).r,
+        sample_render_texture(position).g,
+        sample_render_texture(position - offset).b,
+    );
+}
+
+fn film_grain(position: vec2<f32>, color: vec3<f32>) -> vec3<f32> {
+    let noise = random_float(pixel_hash(vec2<u32>(position), post.frame)) - 0.5;
+
+    return max(color + post.film_grain * MAX_GRAIN * noise, vec3(0.));
+}
+
+fn load_layer(layer: texture_2d<f32>, position: vec2<f32>) -> vec4<f32> {
+    let size = vec2<i32>(textureDimensions(layer));
+    let coords = clamp(vec2<i32>(floor(position)), vec2(0), size - 1);
+
+    return textureLoad(layer, coords, 0);
+}
+
+fn is_edge(position: vec2<f32>, offset: vec2<f32>) -> bool {
+    let depth = load_layer(depth_texture, position).r;
+    let neighbour_depth = load_layer(depth_texture, position + offset).r;
+    let depth_difference = abs(depth - neighbour_depth) / max(min(depth, neighbour_depth), 1e-6);
+
+    // Background has a zero normal, but the depth jump already catches the silhouette there
+    let normal = load_layer(normal_texture, position).xyz;
+    let neighbour_normal = load_layer(normal_texture, position + offset).xyz;
+    let is_crease = dot(normal, neighbour_normal) < OUTLINE_NORMAL_THRESHOLD
+        && dot(normal, normal) > 0.
+        && dot(neighbour_normal, neighbour_normal) > 0.;
+
+    return depth_difference > OUTLINE_DEPTH_THRESHOLD || is_crease;
+}
+
+// Compares the pixel against its neighbours in all four directions, so the line is centered on the edge
+fn outline(position: vec2<f32>, color: vec3<f32>) -> vec3<f32> {
+    let offset = 0.5 * post.outline_thickness;
+    let is_outlined = is_edge(position, vec2(offset, 0.))
+        || is_edge(position, vec2(-offset, 0.))
+        || is_edge(position, vec2(0., offset))
+        || is_edge(position, vec2(0., -offset));
+
+    return select(color, post.outline_color, is_outlined);
+}
+
+fn linear_to_srgb(color: vec3<f32>) -> vec3<f32> {
+    let low = 12.92 * color;
+    let high = 1.055 * pow(color, vec3(1. / 2.4)) - 0.055;
+    return select(high, low, color <= vec3(0.0031308));
+}
+
+fn srgb_to_linear(color: vec3<f32>) -> vec3<f32> {
+    let low = color / 12.92;
+    let high = pow((color + 0.055) / 1.055, vec3(2.4));
+    return select(high, low, color <= vec3(0.04045));
+}
+
+// Encodes the linear image into display values, undoing the encoding an sRGB target applies on its
+// own afterwards, so the written values are the same on every surface format
+fn resolve(color: vec3<f32>) -> vec3<f32> {
+    let clamped = clamp(color, vec3(0.), vec3(1.));
+    var encoded: vec3<f32>;
+    switch post.transfer_function {
+        case TRANSFER_FUNCTION_GAMMA: {
+            encoded = pow(clamped, vec3(1. / post.gamma));
+        }
+        case TRANSFER_FUNCTION_LINEAR: {
+            encoded = clamped;
+        }
+        default: {
+            encoded = linear_to_srgb(clamped);
+        }
+    }
+
+    return select(encoded, srgb_to_linear(encoded), bool(post.is_srgb_target));
+}
+
+fn exposure() -> f32 {
+    let manual_exposure = exp2(post.exposure);
+    return select(manual_exposure, manual_exposure * auto_exposure, bool(post.is_auto_exposure));
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let position = post.render_scale * in.position.xy;
+    var color = exposure() * chromatic_aberration(position);
+    if bool(post.is_outline) {
+        color = outline(position, color);
+    }
+    color = resolve(film_grain(in.position.xy, color));
+
+    // Background has no surface normal, premultiplied alpha needs its color cleared as well
+    if bool(post.is_transparent) {
+        let normal = load_layer(normal_texture, position).xyz;
+        let alpha = select(0., 1., dot(normal, normal) > 0.);
+        return vec4(alpha * color, alpha);
+    }
+
+    return vec4(color, 1.);
+}
@@ -0,0 +1,14 @@
+// Note: user_sdf is defined by the loaded shader file
+fn scene_SDF(position: vec3<f32>) -> f32 {
+    return user_sdf(position);
+}
+
+// Nothing is iterated, so the shape is either solid or empty
+fn scene_density(position: vec3<f32>) -> f32 {
+    return select(0., 1., user_sdf(position) <= 0.);
+}
+
+// Shape of the file is unknown, so the gradient is always sampled
+fn get_normal(position: vec3<f32>) -> vec3<f32> {
+    return finite_difference_normal(position);
+}
@@ -0,0 +1,114 @@
+struct ScreenUniform {
+    width: f32,
+    height: f32,
+    aspect_ratio: f32,
+}
+
+@group(0)
+@binding(0)
+var<uniform> screen: ScreenUniform;
+
+struct CameraUniform {
+    origin: vec3<f32>,
+    // Half the height of the view at unit depth
+    view_scale: f32,
+    matrix: mat3x3<f32>,
+}
+
+@group(0)
+@binding(1)
+var<uniform> camera: CameraUniform;
+
+// Camera the history was rendered with
+@group(0)
+@binding(2)
+var<uniform> previous_camera: CameraUniform;
+
+struct TaaUniform {
+    blend: f32,
+    is_history_valid: u32,
+}
+
+@group(0)
+@binding(3)
+var<uniform> taa: TaaUniform;
+
+// Single jittered sample of the current frame, with the sample count in the alpha channel
+@group(0)
+@binding(4)
+var render_texture: texture_2d<f32>;
+
+@group(0)
+@binding(5)
+var history_texture: texture_2d<f32>;
+
+@group(0)
+@binding(6)
+var depth_texture: texture_2d<f32>;
+
+@group(0)
+@binding(7)
+var history_sampler: sampler;
+
+fn load_current(coords: vec2<i32>) -> vec3<f32> {
+    let size = vec2<i32>(textureDimensions(render_texture));
+    let accumulated = textureLoad(render_texture, clamp(coords, vec2(0), size - 1), 0);
+
+    return accumulated.rgb / max(accumulated.a, 1.);
+}
+
+// Inverse of the ray generation in entry.wgsl, where the screen height spans [-view_scale, view_scale] at unit depth
+fn world_position(pixel_position: vec2<f32>, depth: f32) -> vec3<f32> {
+    let uv_position = camera.view_scale * (2. * pixel_position / screen.height - vec2(screen.aspect_ratio, 1.));
+    let view_direction = uv_position.x * camera.matrix[1] - uv_position.y * camera.matrix[2] - camera.matrix[0];
+
+    // Depth is measured along the viewing direction, which the unnormalized direction has unit length in
+    return camera.origin + depth * view_direction;
+}
+
+// Pixel position of a point as seen by the previous camera, which lies behind it when z is not positive
+fn previous_pixel_position(position: vec3<f32>) -> vec3<f32> {
+    let relative_position = position - previous_camera.origin;
+    let depth = dot(relative_position, -previous_camera.matrix[0]);
+    let uv_position = vec2(
+        dot(relative_position, previous_camera.matrix[1]),
+        -dot(relative_position, previous_camera.matrix[2]),
+    ) / (depth * previous_camera.view_scale);
+
+    return vec3(0.5 * screen.height * (uv_position + vec2(screen.aspect_ratio, 1.)), depth);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let coords = vec2<i32>(floor(in.position.xy));
+    let current = load_current(coords);
+
+    if !bool(taa.is_history_valid) {
+        return vec4(current, 1.);
+    }
+
+    // Background has the maximum distance as its depth, which is far enough to barely move
+    let depth = textureLoad(depth_texture, coords, 0).r;
+    let previous_position = previous_pixel_position(world_position(in.position.xy, depth));
+    let screen_size = vec2(screen.width, screen.height);
+    if previous_position.z <= 0.
+        || any(previous_position.xy < vec2(0.))
+        || any(previous_position.xy >= screen_size) {
+        return vec4(current, 1.);
+    }
+
+    // History outside the colors around the pixel belongs to a surface which is no longer visible
+    var minimum = current;
+    var maximum = current;
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let neighbour = load_current(coords + vec2(x, y));
+            minimum = min(minimum, neighbour);
+            maximum = max(maximum, neighbour);
+        }
+    }
+    let history = textureSampleLevel(history_texture, history_sampler, previous_position.xy / screen_size, 0.).rgb;
+    let clamped_history = clamp(history, minimum, maximum);
+
+    return vec4(mix(clamped_history, current, taa.blend), 1.);
+}
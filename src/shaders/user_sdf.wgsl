@@ -0,0 +1,22 @@
+// Shader files define the distance to their surface as user_sdf, everything the built in fractals
+// use is available, such as options.max_distance or the quaternion functions
+//
+// Menger sponge of four iterations, the distance to a box with three crosses cut out at every scale
+fn box_distance(p: vec3<f32>, half_extents: vec3<f32>) -> f32 {
+    let q = abs(p) - half_extents;
+    return length(max(q, vec3(0.))) + min(max(q.x, max(q.y, q.z)), 0.);
+}
+
+fn user_sdf(p: vec3<f32>) -> f32 {
+    var distance = box_distance(p, vec3(1.));
+    var scale = 1.;
+    for (var i = 0; i < 4; i++) {
+        let a = (p * scale - 2. * floor(p * scale / 2.)) - 1.;
+        scale *= 3.;
+        let r = abs(1. - 3. * abs(a));
+        let cross = (min(max(r.x, r.y), min(max(r.y, r.z), max(r.z, r.x))) - 1.) / scale;
+        distance = max(distance, cross);
+    }
+
+    return distance;
+}
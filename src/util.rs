@@ -1,4 +1,6 @@
 pub(crate) mod buffer;
+pub(crate) mod lru;
 pub(crate) mod math;
 pub(crate) mod shader;
+pub(crate) mod storage;
 pub(crate) mod uniform;
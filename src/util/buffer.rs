@@ -8,6 +8,23 @@ pub(crate) struct ResourceGroupLayoutEntry {
     pub(crate) count: Option<NonZeroU32>,
 }
 
+impl ResourceGroupLayoutEntry {
+    // Read only storage buffers may be bound alongside ones the shaders write to, a group of
+    // mixed buffers is created from one such entry per resource
+    #[must_use]
+    pub(crate) fn storage_buffer(visibility: wgpu::ShaderStages, read_only: bool) -> Self {
+        Self {
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct ResourceGroupDescriptor<'a> {
     pub(crate) label: wgpu::Label<'a>,
@@ -138,6 +155,22 @@ pub(crate) trait ResourceGroupInit {
             bind_group,
         }
     }
+
+    // Only the bind group is recreated, so pipelines created with the existing layout stay valid
+    fn rebind_resource_group(
+        &self,
+        resource_group: &mut ResourceGroup,
+        label: wgpu::Label,
+        resources: &[wgpu::BindingResource],
+    ) {
+        resource_group.bind_group = self.create_resource_group_bind(&ResourceGroupBindDescriptor {
+            label: label
+                .map(|label| format!("{label}_buffer_group_bind"))
+                .as_deref(),
+            resources,
+            layout: &resource_group.bind_group_layout,
+        });
+    }
 }
 
 // Implement functionality for foreign type using trait
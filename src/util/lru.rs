@@ -0,0 +1,88 @@
+use std::collections::VecDeque;
+
+// Small cache keeping the most recently used values, evicting the least recently used one when
+// full, a linear scan is faster than hashing for the handful of entries it is meant for
+#[derive(Clone, Debug)]
+pub(crate) struct LruCache<K, V> {
+    capacity: usize,
+    // Ordered from the least to the most recently used
+    entries: VecDeque<(K, V)>,
+}
+
+impl<K: Copy + PartialEq, V> LruCache<K, V> {
+    #[must_use]
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: VecDeque::with_capacity(capacity.max(1)),
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    // Looks the value up without counting it as used
+    #[must_use]
+    pub(crate) fn peek(&self, key: K) -> Option<&V> {
+        self.entries
+            .iter()
+            .find(|(entry_key, _)| *entry_key == key)
+            .map(|(_, value)| value)
+    }
+
+    pub(crate) fn remove(&mut self, key: K) -> Option<V> {
+        let index = self
+            .entries
+            .iter()
+            .position(|(entry_key, _)| *entry_key == key)?;
+        self.entries.remove(index).map(|(_, value)| value)
+    }
+
+    // Marks the value of the key as the most recently used one, creating it when it is missing,
+    // and returns the key of the evicted value if the cache was full
+    pub(crate) fn touch_or_insert_with(&mut self, key: K, create: impl FnOnce() -> V) -> Option<K> {
+        if let Some(index) = self
+            .entries
+            .iter()
+            .position(|(entry_key, _)| *entry_key == key)
+        {
+            let entry = self.entries.remove(index)?;
+            self.entries.push_back(entry);
+            return None;
+        }
+
+        let evicted = (self.entries.len() >= self.capacity)
+            .then(|| self.entries.pop_front())
+            .flatten()
+            .map(|(evicted_key, _)| evicted_key);
+        self.entries.push_back((key, create()));
+        evicted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lru_cache() {
+        let mut cache = LruCache::new(2);
+        assert_eq!(cache.touch_or_insert_with(1, || "one"), None);
+        assert_eq!(cache.touch_or_insert_with(2, || "two"), None);
+        // Existing values are not created again
+        assert_eq!(cache.touch_or_insert_with(1, || "uno"), None);
+        assert_eq!(cache.peek(1), Some(&"one"));
+
+        // Two was used least recently, so it makes room for three
+        assert_eq!(cache.touch_or_insert_with(3, || "three"), Some(2));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.peek(2).is_none());
+        assert_eq!(cache.peek(3), Some(&"three"));
+
+        assert_eq!(cache.remove(1), Some("one"));
+        assert_eq!(cache.remove(1), None);
+        assert_eq!(cache.len(), 1);
+    }
+}
@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use std::ops::{Add, Div, Index, IndexMut, Mul, Neg, Sub};
 
 pub(crate) trait Num: num_traits::Num {}
@@ -36,9 +37,56 @@ impl_float!(f64);
 
 pub(crate) use std::f32::consts::PI;
 pub(crate) const TWO_PI: f32 = 2. * PI;
-// Accuracy of 0.0001 is good enough for our graphics
+// Accuracy of 0.0001 is good enough for our graphics at the default scale
 pub(crate) const EPSILON: f32 = 1.0e-4;
 
+// Tolerances of the approximate comparisons, == on vectors and angles uses the default context,
+// while deep zooms and large scenes compare with one matching their scale
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct MathContext {
+    // Relative tolerance, which is also the absolute one for angles
+    pub(crate) epsilon: f32,
+    // Length of a world unit in the units of the scene, lengths are compared relative to it
+    pub(crate) unit_scale: f32,
+}
+
+impl MathContext {
+    pub(crate) const DEFAULT: Self = Self {
+        epsilon: EPSILON,
+        unit_scale: 1.,
+    };
+
+    // Context for lengths of about the given size, such as the distances of a deeply zoomed camera
+    #[must_use]
+    pub(crate) fn with_unit_scale(unit_scale: f32) -> Self {
+        Self {
+            unit_scale,
+            ..Self::DEFAULT
+        }
+    }
+
+    // Largest difference of two lengths which are still considered equal
+    #[must_use]
+    pub(crate) fn length_tolerance(self) -> f32 {
+        self.epsilon * self.unit_scale
+    }
+
+    #[must_use]
+    pub(crate) fn approx_eq(self, a: f32, b: f32) -> bool {
+        (a - b).abs() < self.length_tolerance()
+    }
+}
+
+impl Default for MathContext {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+pub(crate) trait ApproxEq {
+    fn approx_eq(&self, other: &Self, context: MathContext) -> bool;
+}
+
 macro_rules! impl_vector_functionality {
     ($Vector:ident) => {
         impl<T> $Vector<T>
@@ -82,17 +130,26 @@ macro_rules! impl_vector_shrink {
 
 macro_rules! impl_vector_partial_eq {
     ($Vector:ident{.$field_head:tt $(.$field_tail:tt)*}) => {
-        impl<T> PartialEq for $Vector<T>
+        impl<T> ApproxEq for $Vector<T>
         where
             T: Float,
         {
-            fn eq(&self, other: &Self) -> bool {
-                let epsilon_t =
-                    T::from(EPSILON).expect("Can only compare values that can be cast to from f32");
+            fn approx_eq(&self, other: &Self, context: MathContext) -> bool {
+                let epsilon_t = T::from(context.length_tolerance())
+                    .expect("Can only compare values that can be cast to from f32");
 
                 (self.$field_head - other.$field_head).abs() < epsilon_t $(&& (self.$field_tail - other.$field_tail).abs() < epsilon_t)*
             }
         }
+
+        impl<T> PartialEq for $Vector<T>
+        where
+            T: Float,
+        {
+            fn eq(&self, other: &Self) -> bool {
+                self.approx_eq(other, MathContext::DEFAULT)
+            }
+        }
     };
 }
 
@@ -234,8 +291,8 @@ macro_rules! impl_vector_dot_product {
     };
 }
 
-#[derive(Clone, Copy, Debug, Default)]
-pub(crate) struct Vector2<T>(pub(crate) T, pub(crate) T);
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct Vector2<T>(pub T, pub T);
 
 impl_vector_functionality!(Vector2);
 impl_vector_extend!(Vector2{ .0 .1 } -> Vector3{ .0 .1 .2 });
@@ -247,8 +304,8 @@ impl_vector_negation_addition_subtraction!(<T> Vector2<T>{ .0 .1 });
 impl_vector_scalar_operations!(<T> Vector2<T>{ .0 .1 });
 impl_vector_dot_product!(<T> Vector2<T>{ .0 .1 });
 
-#[derive(Clone, Copy, Debug, Default)]
-pub(crate) struct Vector3<T>(pub(crate) T, pub(crate) T, pub(crate) T);
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct Vector3<T>(pub T, pub T, pub T);
 
 impl_vector_functionality!(Vector3);
 impl_vector_extend!(Vector3{ .0 .1 .2 } -> Vector4{ .0 .1 .2 .3 });
@@ -261,8 +318,8 @@ impl_vector_negation_addition_subtraction!(<T> Vector3<T>{ .0 .1 .2 });
 impl_vector_scalar_operations!(<T> Vector3<T>{ .0 .1 .2 });
 impl_vector_dot_product!(<T> Vector3<T>{ .0 .1 .2 });
 
-#[derive(Clone, Copy, Debug, Default)]
-pub(crate) struct Vector4<T>(pub(crate) T, pub(crate) T, pub(crate) T, pub(crate) T);
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct Vector4<T>(pub T, pub T, pub T, pub T);
 
 impl_vector_functionality!(Vector4);
 impl_vector_shrink!(Vector4{ .0 .1 .2 .3 } -> Vector3{ .0 .1 .2 });
@@ -311,12 +368,23 @@ impl<T> Matrix3x3<T> {
     }
 }
 
+impl<T> ApproxEq for Matrix3x3<T>
+where
+    T: Float,
+{
+    fn approx_eq(&self, other: &Self, context: MathContext) -> bool {
+        self.0.approx_eq(&other.0, context)
+            && self.1.approx_eq(&other.1, context)
+            && self.2.approx_eq(&other.2, context)
+    }
+}
+
 impl<T> PartialEq for Matrix3x3<T>
 where
     T: Float,
 {
     fn eq(&self, other: &Self) -> bool {
-        self.0 == other.0 && self.1 == other.1 && self.2 == other.2
+        self.approx_eq(other, MathContext::DEFAULT)
     }
 }
 
@@ -416,27 +484,27 @@ impl Matrix3x3<f32> {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default)]
-pub(crate) struct Radians(f32);
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct Radians(f32);
 
 impl Radians {
     #[must_use]
-    pub(crate) fn from_radians(radians: f32) -> Self {
+    pub fn from_radians(radians: f32) -> Self {
         Self(radians)
     }
 
     #[must_use]
-    pub(crate) fn from_degrees(degrees: f32) -> Self {
+    pub fn from_degrees(degrees: f32) -> Self {
         Self::from_radians((degrees / 180.0) * PI)
     }
 
     #[must_use]
-    pub(crate) fn radians(self) -> f32 {
+    pub fn radians(self) -> f32 {
         self.0
     }
 
     #[must_use]
-    pub(crate) fn degrees(self) -> f32 {
+    pub fn degrees(self) -> f32 {
         (self.radians() / PI) * 180.0
     }
 
@@ -468,16 +536,58 @@ impl Radians {
     }
 }
 
-impl PartialEq for Radians {
-    fn eq(&self, other: &Self) -> bool {
+// Angles have no unit, so only the epsilon of the context applies
+impl ApproxEq for Radians {
+    fn approx_eq(&self, other: &Self, context: MathContext) -> bool {
         let diff = (self.radians() - other.radians()).abs() % TWO_PI;
 
-        !(EPSILON..=TWO_PI - EPSILON).contains(&diff)
+        !(context.epsilon..=TWO_PI - context.epsilon).contains(&diff)
+    }
+}
+
+impl PartialEq for Radians {
+    fn eq(&self, other: &Self) -> bool {
+        self.approx_eq(other, MathContext::DEFAULT)
     }
 }
 
 impl_vector_negation_addition_subtraction!(Radians{ .0 });
 
+// Element of the Halton low-discrepancy sequence on [0, 1), base should be a prime number
+#[must_use]
+pub(crate) fn halton(mut index: u32, base: u32) -> f32 {
+    let mut fraction = 1.;
+    let mut result = 0.;
+
+    while index > 0 {
+        #[allow(clippy::cast_precision_loss)]
+        {
+            fraction /= base as f32;
+            result += fraction * (index % base) as f32;
+        }
+        index /= base;
+    }
+
+    result
+}
+
+// Converts the bits of an IEEE 754 half precision float, as stored in 16 bit float textures
+#[must_use]
+pub(crate) fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 == 0 { 1. } else { -1. };
+    let exponent = i32::from((bits >> 10) & 0x1f);
+    let mantissa = f32::from(bits & 0x3ff);
+
+    match exponent {
+        // Subnormal numbers have no implicit leading one
+        0 => sign * mantissa * (-24f32).exp2(),
+        0x1f if mantissa == 0. => sign * f32::INFINITY,
+        0x1f => f32::NAN,
+        #[allow(clippy::cast_precision_loss)]
+        _ => sign * (1. + mantissa / 1024.) * ((exponent - 15) as f32).exp2(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -786,6 +896,39 @@ mod tests {
         assert_eq!(-Radians::from_radians(TWO_PI), Radians::from_radians(0.));
     }
 
+    #[test]
+    fn test_math_context() {
+        let far = Vector3(1000., 0., 0.);
+        let near = Vector3(1e-6, 0., 0.);
+
+        // Default tolerance is too fine far away and too coarse up close
+        assert_ne!(far, Vector3(1000.05, 0., 0.));
+        assert_eq!(near, Vector3(5e-6, 0., 0.));
+
+        assert!(far.approx_eq(
+            &Vector3(1000.05, 0., 0.),
+            MathContext::with_unit_scale(1000.)
+        ));
+        assert!(!near.approx_eq(&Vector3(5e-6, 0., 0.), MathContext::with_unit_scale(1e-6)));
+        assert!(MathContext::with_unit_scale(10.).approx_eq(1., 1.0005));
+        // Angles ignore the unit scale
+        assert!(!Radians::from_radians(0.).approx_eq(
+            &Radians::from_radians(0.01),
+            MathContext::with_unit_scale(1000.)
+        ));
+    }
+
+    #[test]
+    fn test_halton_sequence() {
+        let base_2 = [0., 0.5, 0.25, 0.75, 0.125];
+        let base_3 = [0., 1. / 3., 2. / 3., 1. / 9., 4. / 9.];
+
+        for i in 0..5 {
+            assert!((halton(i, 2) - base_2[i as usize]).abs() < EPSILON);
+            assert!((halton(i, 3) - base_3[i as usize]).abs() < EPSILON);
+        }
+    }
+
     #[test]
     fn test_rotation_matrix_creation() {
         assert_eq!(
@@ -837,4 +980,15 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    // Every value checked is exactly representable in both formats
+    #[allow(clippy::float_cmp)]
+    fn test_f16_to_f32() {
+        assert_eq!(f16_to_f32(0x3c00), 1.);
+        assert_eq!(f16_to_f32(0xc000), -2.);
+        assert_eq!(f16_to_f32(0x3555), 0.333_251_95);
+        assert_eq!(f16_to_f32(0x0001), 5.960_464_5e-8);
+        assert_eq!(f16_to_f32(0x7c00), f32::INFINITY);
+    }
 }
@@ -1,9 +1,11 @@
 use egui_wgpu::wgpu;
 use std::{
     borrow::Cow,
+    fmt::{self, Write as _},
     iter::{self, Sum},
     ops::{Add, Deref},
 };
+use strum::IntoEnumIterator;
 
 #[derive(Clone, Debug, Default)]
 pub(crate) struct WGSLShaderSource<'a>(pub(crate) Cow<'a, str>);
@@ -41,6 +43,67 @@ impl Sum<Self> for WGSLShaderSource<'_> {
     }
 }
 
+// Variant name such as KaleidoscopicIFS as the KALEIDOSCOPIC_IFS part of a constant name
+#[must_use]
+fn screaming_snake_case(name: &str) -> String {
+    let characters = name.chars().collect::<Vec<_>>();
+    let mut result = String::with_capacity(2 * name.len());
+
+    for (i, &character) in characters.iter().enumerate() {
+        let is_word_start = i > 0
+            && character.is_uppercase()
+            && (characters[i - 1].is_lowercase()
+                || characters
+                    .get(i + 1)
+                    .is_some_and(|next| next.is_lowercase()));
+        if is_word_start {
+            result.push('_');
+        }
+        result.push(character.to_ascii_uppercase());
+    }
+
+    result
+}
+
+// Header of WGSL constants generated from their Rust definitions at module assembly, so the ids
+// and sizes the shaders use can never drift apart from the ones the Rust code writes
+#[derive(Clone, Debug, Default)]
+pub(crate) struct WGSLConstants(String);
+
+impl WGSLConstants {
+    // Abstract integer, so it can size arrays and be compared with signed and unsigned values alike
+    #[must_use]
+    pub(crate) fn integer(mut self, name: &str, value: impl fmt::Display) -> Self {
+        // Writing into a string can not fail
+        let _ = writeln!(self.0, "const {name} = {value};");
+        self
+    }
+
+    #[must_use]
+    pub(crate) fn unsigned(mut self, name: &str, value: u32) -> Self {
+        let _ = writeln!(self.0, "const {name} = {value}u;");
+        self
+    }
+
+    // Every variant as PREFIX_VARIANT, with the id the uniforms carry for it
+    #[must_use]
+    pub(crate) fn enumeration<T>(self, prefix: &str, id: impl Fn(T) -> u32) -> Self
+    where
+        T: IntoEnumIterator + fmt::Debug,
+    {
+        T::iter().fold(self, |constants, variant| {
+            let name = format!("{prefix}_{}", screaming_snake_case(&format!("{variant:?}")));
+            constants.unsigned(&name, id(variant))
+        })
+    }
+}
+
+impl From<WGSLConstants> for WGSLShaderSource<'_> {
+    fn from(constants: WGSLConstants) -> Self {
+        WGSLShaderSource(constants.0.into())
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub(crate) struct WGSLShaderModuleDescriptor<'a> {
     pub(crate) label: wgpu::Label<'a>,
@@ -88,3 +151,36 @@ impl WGSLShaderModuleInit for wgpu::Device {
         self.create_shader_module(descriptor)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, strum_macros::EnumIter)]
+    enum Shape {
+        Sphere,
+        SierpinskiTetrahedron,
+        KaleidoscopicIFS,
+    }
+
+    #[test]
+    fn test_wgsl_constants() {
+        assert_eq!(screaming_snake_case("X"), "X");
+        assert_eq!(screaming_snake_case("SideBySide"), "SIDE_BY_SIDE");
+        assert_eq!(
+            screaming_snake_case("KaleidoscopicIFS"),
+            "KALEIDOSCOPIC_IFS"
+        );
+
+        let constants = WGSLConstants::default()
+            .integer("MAX_LIGHTS", 4)
+            .enumeration("SHAPE", |shape: Shape| shape as u32);
+        assert_eq!(
+            WGSLShaderSource::from(constants).as_ref(),
+            "const MAX_LIGHTS = 4;\n\
+                const SHAPE_SPHERE = 0u;\n\
+                const SHAPE_SIERPINSKI_TETRAHEDRON = 1u;\n\
+                const SHAPE_KALEIDOSCOPIC_IFS = 2u;\n"
+        );
+    }
+}
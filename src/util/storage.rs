@@ -0,0 +1,56 @@
+use egui_wgpu::wgpu;
+use std::ops::Deref;
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct StorageBufferDescriptor<'a> {
+    pub(crate) label: wgpu::Label<'a>,
+    pub(crate) contents: &'a [u8],
+    // Usages needed on top of STORAGE and COPY_DST
+    pub(crate) extra_usage: wgpu::BufferUsages,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct StorageBuffer(wgpu::Buffer);
+
+impl StorageBuffer {
+    pub(crate) fn update_buffer<T>(
+        &self,
+        queue: &wgpu::Queue,
+        offset: wgpu::BufferAddress,
+        data: &[T],
+    ) where
+        T: bytemuck::Pod,
+    {
+        queue.write_buffer(&self.0, offset, bytemuck::cast_slice(data));
+    }
+}
+
+// Implicitly implement all methods of wgpu::Buffer on StorageBuffer
+impl Deref for StorageBuffer {
+    type Target = wgpu::Buffer;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+pub(crate) trait StorageBufferInit {
+    fn create_buffer_init(&self, descriptor: &wgpu::util::BufferInitDescriptor) -> wgpu::Buffer;
+
+    fn create_storage_buffer(&self, descriptor: &StorageBufferDescriptor) -> StorageBuffer {
+        StorageBuffer(self.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: descriptor.label,
+            contents: descriptor.contents,
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_DST
+                | descriptor.extra_usage,
+        }))
+    }
+}
+
+// Implement functionality for foreign type using trait
+impl StorageBufferInit for wgpu::Device {
+    fn create_buffer_init(&self, descriptor: &wgpu::util::BufferInitDescriptor) -> wgpu::Buffer {
+        wgpu::util::DeviceExt::create_buffer_init(self, descriptor)
+    }
+}